@@ -0,0 +1,284 @@
+//! HTTP API over the SolSage indexer's SQLite mirror.
+//!
+//! Lets agent backends discover entries, attributions and claims with plain
+//! GET requests instead of `getProgramAccounts` scans. Every list endpoint
+//! takes `limit` and `offset` and answers with one page:
+//!
+//! - `GET /entries?staker=&category=&active=`
+//! - `GET /entries/top?category=`: active entries, most attributed first
+//! - `GET /entries/<address>`
+//! - `GET /attributions?query_hash=&knowledge_entry=`
+//! - `GET /claims?staker=`
+
+use std::str::FromStr;
+
+use serde_json::{json, Value};
+use solana_program::pubkey::Pubkey;
+use solsage_indexer::{
+    AttributionFilter, AttributionRow, ClaimRow, EntryFilter, EntryOrder, EntryRow, IndexerResult, Page, Store,
+};
+use thiserror::Error;
+use tiny_http::{Header, Server};
+
+/// Rows per page when the request doesn't set `limit`
+pub const DEFAULT_PAGE_LIMIT: usize = 50;
+/// Most rows one page returns, whatever `limit` asks for
+pub const MAX_PAGE_LIMIT: usize = 200;
+
+#[derive(Error, Debug)]
+pub enum ApiError {
+    #[error("Failed to listen: {0}")]
+    Listen(Box<dyn std::error::Error + Send + Sync>),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+/// A JSON response and its HTTP status
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self { status, body: json!({ "error": message.into() }) }
+    }
+}
+
+/// Answer requests at `address` from `store` until the listener fails
+pub fn serve(store: &Store, address: &str) -> ApiResult<()> {
+    let server = Server::http(address).map_err(ApiError::Listen)?;
+    let content_type = Header::from_bytes("Content-Type", "application/json").expect("static header");
+    for request in server.incoming_requests() {
+        let response = handle(store, request.method().as_str(), request.url());
+        request.respond(
+            tiny_http::Response::from_string(response.body.to_string())
+                .with_status_code(response.status)
+                .with_header(content_type.clone()),
+        )?;
+    }
+    Ok(())
+}
+
+/// Route one request; `url` is its path and query string
+pub fn handle(store: &Store, method: &str, url: &str) -> Response {
+    if method != "GET" {
+        return Response::error(405, "only GET is supported");
+    }
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let query = match Query::parse(query) {
+        Ok(query) => query,
+        Err(message) => return Response::error(400, message),
+    };
+
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let result = match segments[..] {
+        ["entries"] => entries(store, &query),
+        ["entries", "top"] => top_entries(store, &query),
+        ["entries", address] => entry(store, address),
+        ["attributions"] => attributions(store, &query),
+        ["claims"] => claims(store, &query),
+        _ => return Response::error(404, "not found"),
+    };
+    match result {
+        Ok(Ok(response)) => response,
+        Ok(Err(message)) => Response::error(400, message),
+        Err(e) => Response::error(500, e.to_string()),
+    }
+}
+
+/// Return a handler's 400 when a parameter doesn't parse
+macro_rules! query_try {
+    ($parsed:expr) => {
+        match $parsed {
+            Ok(value) => value,
+            Err(message) => return Ok(Err(message)),
+        }
+    };
+}
+
+/// A handler's response, or the message of a 400 for its parameters
+type Handled = IndexerResult<Result<Response, String>>;
+
+fn entries(store: &Store, query: &Query) -> Handled {
+    let filter = EntryFilter {
+        staker: query_try!(query.pubkey("staker")),
+        category: query_try!(query.pubkey("category")),
+        active: query_try!(query.bool("active")),
+    };
+    let page = query_try!(query.page(&["staker", "category", "active"]));
+    let rows = store.knowledge_entries(&filter, EntryOrder::Address, page)?;
+    Ok(Ok(page_response(page, rows.iter().map(entry_json).collect())))
+}
+
+fn top_entries(store: &Store, query: &Query) -> Handled {
+    let filter = EntryFilter { category: query_try!(query.pubkey("category")), active: Some(true), staker: None };
+    let page = query_try!(query.page(&["category"]));
+    let rows = store.knowledge_entries(&filter, EntryOrder::MostAttributed, page)?;
+    Ok(Ok(page_response(page, rows.iter().map(entry_json).collect())))
+}
+
+fn entry(store: &Store, address: &str) -> Handled {
+    let Ok(address) = Pubkey::from_str(address) else {
+        return Ok(Err(format!("invalid address: {address}")));
+    };
+    Ok(Ok(match store.knowledge_entry(&address)? {
+        Some(row) => Response::ok(entry_json(&row)),
+        None => Response::error(404, format!("no entry at {address}")),
+    }))
+}
+
+fn attributions(store: &Store, query: &Query) -> Handled {
+    let filter = AttributionFilter {
+        knowledge_entry: query_try!(query.pubkey("knowledge_entry")),
+        query_hash: query_try!(query.hash("query_hash")),
+    };
+    let page = query_try!(query.page(&["knowledge_entry", "query_hash"]));
+    let rows = store.attributions(&filter, page)?;
+    Ok(Ok(page_response(page, rows.iter().map(attribution_json).collect())))
+}
+
+fn claims(store: &Store, query: &Query) -> Handled {
+    let Some(staker) = query_try!(query.pubkey("staker")) else {
+        return Ok(Err("staker is required".to_string()));
+    };
+    let page = query_try!(query.page(&["staker"]));
+    let rows = store.claims_by_staker(&staker, page)?;
+    Ok(Ok(page_response(page, rows.iter().map(claim_json).collect())))
+}
+
+// ============================================================================
+// PARAMETERS
+// ============================================================================
+
+/// A request's query string
+struct Query {
+    pairs: Vec<(String, String)>,
+}
+
+impl Query {
+    fn parse(query: &str) -> Result<Self, String> {
+        let pairs: Vec<(String, String)> = form_urlencoded::parse(query.as_bytes()).into_owned().collect();
+        for (index, (name, _)) in pairs.iter().enumerate() {
+            if pairs[..index].iter().any(|(earlier, _)| earlier == name) {
+                return Err(format!("{name} is given more than once"));
+            }
+        }
+        Ok(Self { pairs })
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.pairs.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    fn pubkey(&self, name: &str) -> Result<Option<Pubkey>, String> {
+        self.get(name)
+            .map(|value| Pubkey::from_str(value).map_err(|_| format!("{name} is not a valid address")))
+            .transpose()
+    }
+
+    fn bool(&self, name: &str) -> Result<Option<bool>, String> {
+        self.get(name)
+            .map(|value| bool::from_str(value).map_err(|_| format!("{name} must be true or false")))
+            .transpose()
+    }
+
+    /// A sha256 hash as 64 hex digits
+    fn hash(&self, name: &str) -> Result<Option<[u8; 32]>, String> {
+        self.get(name).map(|value| parse_hex(value).ok_or(format!("{name} must be 64 hex digits"))).transpose()
+    }
+
+    fn number(&self, name: &str, default: usize) -> Result<usize, String> {
+        self.get(name)
+            .map_or(Ok(default), |value| value.parse().map_err(|_| format!("{name} must be a non-negative integer")))
+    }
+
+    /// The requested page, once every parameter is known to be one the
+    /// endpoint takes: `filters` or `limit` and `offset`
+    fn page(&self, filters: &[&str]) -> Result<Page, String> {
+        if let Some((name, _)) = self
+            .pairs
+            .iter()
+            .find(|(name, _)| !filters.contains(&name.as_str()) && name != "limit" && name != "offset")
+        {
+            return Err(format!("unknown parameter: {name}"));
+        }
+        let limit = self.number("limit", DEFAULT_PAGE_LIMIT)?;
+        if limit == 0 {
+            return Err("limit must be at least 1".to_string());
+        }
+        Ok(Page { limit: limit.min(MAX_PAGE_LIMIT), offset: self.number("offset", 0)? })
+    }
+}
+
+fn parse_hex(value: &str) -> Option<[u8; 32]> {
+    if value.len() != 64 || !value.is_ascii() {
+        return None;
+    }
+    let mut hash = [0; 32];
+    for (byte, digits) in hash.iter_mut().zip(value.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()?;
+    }
+    Some(hash)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+// ============================================================================
+// RESPONSES
+// ============================================================================
+
+/// `items` with the page they fill; `next_offset` is null on the last page
+fn page_response(page: Page, items: Vec<Value>) -> Response {
+    let next_offset = (items.len() == page.limit).then(|| page.offset + items.len());
+    Response::ok(json!({ "items": items, "limit": page.limit, "offset": page.offset, "next_offset": next_offset }))
+}
+
+fn entry_json(row: &EntryRow) -> Value {
+    json!({
+        "address": row.address.to_string(),
+        "staker": row.staker.to_string(),
+        "category": row.category.to_string(),
+        "content_hash": to_hex(&row.content_hash),
+        "title": row.title,
+        "is_active": row.is_active,
+        "stake_amount": row.stake_amount,
+        "total_attributions": row.total_attributions,
+        "pending_rewards": row.pending_rewards,
+        "slot": row.slot,
+    })
+}
+
+fn attribution_json(row: &AttributionRow) -> Value {
+    json!({
+        "address": row.address.to_string(),
+        "knowledge_entry": row.knowledge_entry.to_string(),
+        "query_hash": to_hex(&row.query_hash),
+        "relevance_score": row.relevance_score,
+        "reward": row.reward,
+        "unlocks_at": row.unlocks_at,
+        "reward_claimed": row.reward_claimed,
+        "reversed": row.reversed,
+        "signature": row.signature,
+        "slot": row.slot,
+    })
+}
+
+fn claim_json(row: &ClaimRow) -> Value {
+    json!({
+        "signature": row.signature,
+        "staker": row.staker.to_string(),
+        "amount": row.amount,
+        "attributions": row.attributions,
+        "slot": row.slot,
+    })
+}
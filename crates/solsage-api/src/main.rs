@@ -0,0 +1,38 @@
+//! `solsage-api <database> [listen-address]`
+//!
+//! Serves the SQLite database a `solsage-indexer` keeps up to date, on
+//! 127.0.0.1:8080 unless told otherwise.
+
+use std::{env, process::ExitCode};
+
+use solsage_indexer::Store;
+
+const DEFAULT_LISTEN_ADDRESS: &str = "127.0.0.1:8080";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (database, address) = match &args[..] {
+        [database] => (database, DEFAULT_LISTEN_ADDRESS),
+        [database, address] => (database, address.as_str()),
+        _ => {
+            eprintln!("usage: solsage-api <database> [listen-address]");
+            return ExitCode::FAILURE;
+        }
+    };
+    let store = match Store::open(database) {
+        Ok(store) => store,
+        Err(e) => {
+            eprintln!("failed to open {database}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    println!("serving {database} on {address}");
+    match solsage_api::serve(&store, address) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use borsh::BorshSerialize;
+use serde_json::Value;
+use solana_program::pubkey::Pubkey;
+use solsage_api::{handle, MAX_PAGE_LIMIT};
+use solsage_client::{
+    solsage::{SolSageEvent, VestingBucket},
+    Attribution, KnowledgeEntry, LicenseType, LockupTier, ProgramAccount, Visibility, KNOWLEDGE_ENTRY_SCHEMA_VERSION,
+};
+use solsage_indexer::{Indexer, Store};
+
+fn entry(staker: Pubkey, category: Pubkey, total_attributions: u64, is_active: bool) -> Vec<u8> {
+    let entry = KnowledgeEntry {
+        is_initialized: true,
+        schema_version: KNOWLEDGE_ENTRY_SCHEMA_VERSION,
+        staker,
+        creator: staker,
+        entry_index: 0,
+        content_hash: [7; 32],
+        category,
+        is_active,
+        license: LicenseType::Mit,
+        title: "Async Rust".to_string(),
+        content_uri: None,
+        collection: None,
+        royalty_splits: Vec::new(),
+        created_at: 0,
+        updated_at: 0,
+        expires_at: 0,
+        last_attribution_at: 0,
+        version: 1,
+        total_attributions,
+        pending_rewards: 0,
+        vesting_schedule: [VestingBucket::default(); KnowledgeEntry::VESTING_BUCKETS],
+        stake_amount: 1_000,
+        bump: 0,
+        escrow_bump: 0,
+        tags: Vec::new(),
+        min_reward_per_use: 0,
+        lockup_tier: LockupTier::None,
+        locked_until: 0,
+        unstake_requested_at: 0,
+        receipt_asset_id: None,
+        quality_score: 0,
+        under_review: false,
+        reward_stream: Default::default(),
+        derived_from: Vec::new(),
+        citation_royalties: 0,
+        citation_rewards: 0,
+        document: None,
+        chunk_index: 0,
+        chunks: None,
+        embedding_fingerprint: None,
+        attestation: None,
+        verified: false,
+        identity_credential: None,
+        visibility: Visibility::Public,
+    };
+    let mut data = vec![0; KnowledgeEntry::LEN];
+    entry.pack_into(&mut data).unwrap();
+    data
+}
+
+fn attribution(knowledge_entry: Pubkey, query_hash: [u8; 32]) -> Vec<u8> {
+    let attribution = Attribution {
+        is_initialized: true,
+        schema_version: 1,
+        knowledge_entry,
+        query_hash,
+        relevance_score: 80,
+        reward: 500,
+        timestamp: 0,
+        unlocks_at: 100,
+        reward_claimed: false,
+        reversed: false,
+        epoch: None,
+        bump: 0,
+        agent_id: None,
+        model_id: None,
+        pending: false,
+        votes: Vec::new(),
+        streamed: false,
+        chunk_index: None,
+    };
+    let mut data = vec![0; Attribution::LEN];
+    attribution.pack_into(&mut data).unwrap();
+    data
+}
+
+/// Two stakers with two entries each under one category, the last the
+/// most attributed but inactive, three attributions over two queries and
+/// one claim
+struct Fixture {
+    indexer: Indexer,
+    stakers: [Pubkey; 2],
+    category: Pubkey,
+    entries: Vec<Pubkey>,
+}
+
+fn fixture() -> Fixture {
+    let program_id = Pubkey::new_unique();
+    let indexer = Indexer::new(program_id, Store::open_in_memory().unwrap());
+    let (stakers, category) = ([Pubkey::new_unique(), Pubkey::new_unique()], Pubkey::new_unique());
+    let mut entries = Vec::new();
+    for (index, total_attributions) in [1, 2, 3, 10].into_iter().enumerate() {
+        let address = Pubkey::new_unique();
+        let data = entry(stakers[index / 2], category, total_attributions, index != 3);
+        indexer.apply_account(&address, &data, 1).unwrap();
+        entries.push(address);
+    }
+    for (query_hash, knowledge_entry) in [([1; 32], entries[0]), ([1; 32], entries[1]), ([2; 32], entries[0])] {
+        indexer.apply_account(&Pubkey::new_unique(), &attribution(knowledge_entry, query_hash), 2).unwrap();
+    }
+    let claimed = SolSageEvent::RewardsClaimed { staker: stakers[0], amount: 500, attributions: 1 };
+    let logs = [
+        format!("Program {program_id} invoke [1]"),
+        format!("Program data: {}", BASE64.encode(claimed.try_to_vec().unwrap())),
+        format!("Program {program_id} success"),
+    ];
+    indexer.apply_logs("sig", 3, &logs, false).unwrap();
+    Fixture { indexer, stakers, category, entries }
+}
+
+impl Fixture {
+    /// Status and body of a GET to `url`
+    fn get(&self, url: &str) -> (u16, Value) {
+        let response = handle(self.indexer.store(), "GET", url);
+        (response.status, response.body)
+    }
+
+    fn addresses(&self, url: &str) -> Vec<String> {
+        let (status, body) = self.get(url);
+        assert_eq!(status, 200, "{body}");
+        body["items"].as_array().unwrap().iter().map(|item| item["address"].as_str().unwrap().to_string()).collect()
+    }
+}
+
+#[test]
+fn entries_filter_by_staker_category_and_activity() {
+    let fixture = fixture();
+    let [first, second] = fixture.stakers;
+    assert_eq!(fixture.addresses(&format!("/entries?staker={first}")).len(), 2);
+    assert_eq!(fixture.addresses(&format!("/entries?staker={second}&active=true")), [fixture.entries[2].to_string()]);
+    assert_eq!(fixture.addresses(&format!("/entries?category={}", fixture.category)).len(), 4);
+    assert!(fixture.addresses(&format!("/entries?category={}", Pubkey::new_unique())).is_empty());
+
+    let (status, body) = fixture.get(&format!("/entries/{}", fixture.entries[0]));
+    assert_eq!((status, body["total_attributions"].as_u64()), (200, Some(1)));
+    assert_eq!(body["content_hash"], "07".repeat(32));
+    assert_eq!(fixture.get(&format!("/entries/{}", Pubkey::new_unique())).0, 404);
+}
+
+#[test]
+fn top_entries_are_the_most_attributed_active_ones() {
+    let fixture = fixture();
+    let expected: Vec<_> = [2, 1, 0].iter().map(|&index| fixture.entries[index].to_string()).collect();
+    assert_eq!(fixture.addresses(&format!("/entries/top?category={}", fixture.category)), expected);
+    assert_eq!(fixture.addresses("/entries/top?limit=1"), expected[..1]);
+}
+
+#[test]
+fn pages_report_where_the_next_one_starts() {
+    let fixture = fixture();
+    let (_, first) = fixture.get("/entries?limit=3");
+    assert_eq!(first["items"].as_array().unwrap().len(), 3);
+    assert_eq!(first["next_offset"], 3);
+    let (_, last) = fixture.get("/entries?limit=3&offset=3");
+    assert_eq!(last["items"].as_array().unwrap().len(), 1);
+    assert!(last["next_offset"].is_null());
+
+    // Oversized pages are capped rather than refused
+    let (_, capped) = fixture.get("/entries?limit=100000");
+    assert_eq!(capped["limit"], MAX_PAGE_LIMIT);
+}
+
+#[test]
+fn attributions_filter_by_query_hash_and_entry() {
+    let fixture = fixture();
+    assert_eq!(fixture.addresses(&format!("/attributions?query_hash={}", "01".repeat(32))).len(), 2);
+    let url = format!("/attributions?query_hash={}&knowledge_entry={}", "01".repeat(32), fixture.entries[1]);
+    let (status, body) = fixture.get(&url);
+    assert_eq!(status, 200);
+    assert_eq!(body["items"][0]["knowledge_entry"], fixture.entries[1].to_string());
+    assert_eq!(body["items"].as_array().unwrap().len(), 1);
+
+    let (_, claims) = fixture.get(&format!("/claims?staker={}", fixture.stakers[0]));
+    assert_eq!(claims["items"][0]["amount"], 500);
+}
+
+#[test]
+fn bad_requests_are_refused_with_a_reason() {
+    let fixture = fixture();
+    for url in [
+        "/entries?staker=nope",
+        "/entries?active=maybe",
+        "/entries?limit=0",
+        "/entries?stakr=x",
+        "/entries?limit=1&limit=2",
+        "/attributions?query_hash=abcd",
+        "/claims",
+    ] {
+        let (status, body) = fixture.get(url);
+        assert_eq!(status, 400, "{url}");
+        assert!(body["error"].is_string());
+    }
+    assert_eq!(fixture.get("/stakers").0, 404);
+    assert_eq!(handle(fixture.indexer.store(), "POST", "/entries").status, 405);
+}
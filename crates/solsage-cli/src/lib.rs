@@ -0,0 +1,349 @@
+//! Command line operations against a SolSage deployment.
+//!
+//! Every subcommand signs with a keypair file and talks to a configurable
+//! cluster through `solsage-client`, so the program can be exercised without
+//! writing code. `main.rs` only parses the arguments and calls `run`.
+
+use std::{
+    fs,
+    path::PathBuf,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use clap::{Parser, Subcommand};
+use solana_program::{instruction::Instruction, native_token::lamports_to_sol, pubkey::Pubkey};
+use solana_sdk::{
+    native_token::sol_to_lamports,
+    signature::{read_keypair_file, Keypair},
+    signer::Signer,
+};
+use solsage_client::{
+    claim_rewards, find_access_pass_address, find_category_address, find_knowledge_entry_address, find_mint_address,
+    hashing, initialize, record_attribution, set_reward_rate, stake_knowledge, Attribution, AttributionClaim,
+    AttributionMetadata, AttributionTarget, ClientError, KnowledgeEntry, LicenseType, SolSageClient, StakedContent,
+    TransactionBuilder, Visibility,
+};
+use thiserror::Error;
+
+/// Attributions one `ClaimRewards` transaction settles; more are claimed
+/// over several transactions
+pub const CLAIM_CHUNK: usize = 8;
+
+const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = solana_program::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+#[derive(Error, Debug)]
+pub enum CliError {
+    #[error("Failed to read keypair {0}: {1}")]
+    Keypair(String, String),
+    #[error(transparent)]
+    Client(#[from] ClientError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+pub type CliResult<T> = Result<T, CliError>;
+
+#[derive(Parser, Debug)]
+#[command(name = "solsage-cli", version, about = "Operate a SolSage deployment from the command line")]
+pub struct Cli {
+    /// RPC URL, or one of the monikers mainnet-beta, devnet, testnet and
+    /// localhost
+    #[arg(long, short = 'u', global = true, env = "SOLSAGE_URL", default_value = "devnet")]
+    pub url: String,
+    /// Keypair file that signs and pays for every transaction
+    #[arg(long, short = 'k', global = true, env = "SOLSAGE_KEYPAIR", default_value = "~/.config/solana/id.json")]
+    pub keypair: String,
+    /// Address the SolSage program is deployed at
+    #[arg(long, env = "SOLSAGE_PROGRAM_ID")]
+    pub program_id: Pubkey,
+    /// Priority fee in micro-lamports per compute unit; estimated from
+    /// recent fees when unset
+    #[arg(long, global = true)]
+    pub priority_fee: Option<u64>,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum Command {
+    /// Create the protocol, with the keypair as its authority
+    Init,
+    /// Stake SOL on a new knowledge entry
+    Stake {
+        /// Name of the category the entry is filed under
+        #[arg(long)]
+        category: String,
+        #[arg(long)]
+        title: String,
+        /// Text file whose canonical hash is the entry's content hash
+        #[arg(long, required_unless_present = "content_hash", conflicts_with = "content_hash")]
+        content: Option<PathBuf>,
+        /// Content hash as 64 hex digits
+        #[arg(long, value_parser = parse_hash)]
+        content_hash: Option<[u8; 32]>,
+        /// mit, cc-by, proprietary, or the URL of custom terms
+        #[arg(long, default_value = "mit", value_parser = parse_license)]
+        license: LicenseType,
+        /// Stake in SOL
+        #[arg(long, value_parser = parse_sol)]
+        amount: u64,
+    },
+    /// Record an attribution to an entry, signed as an oracle
+    Attribute {
+        entry: Pubkey,
+        /// Hash of the query the entry answered, as 64 hex digits
+        #[arg(long, value_parser = parse_hash)]
+        query_hash: [u8; 32],
+        /// Relevance score from 0 to 100
+        #[arg(long)]
+        score: u8,
+    },
+    /// Claim an entry's vested rewards, as its staker or claim delegate
+    Claim {
+        entry: Pubkey,
+        /// SAGE token account the rewards go to; the staker's associated
+        /// token account when unset
+        #[arg(long)]
+        destination: Option<Pubkey>,
+    },
+    /// Print an entry's state
+    ShowEntry { entry: Pubkey },
+    /// List the entries a staker owns, by default the keypair's, or the
+    /// active entries in a category
+    ListEntries {
+        #[arg(long, conflicts_with = "category")]
+        staker: Option<Pubkey>,
+        /// Category name
+        #[arg(long)]
+        category: Option<String>,
+    },
+    /// Set the SAGE paid per attribution, as the protocol authority
+    SetRewardRate {
+        /// SAGE base units per attribution
+        reward_per_attribution: u64,
+    },
+}
+
+/// Run one parsed command, printing what it did
+pub fn run(cli: Cli) -> CliResult<()> {
+    let keypair_path = expand_home(&cli.keypair);
+    let signer = read_keypair_file(&keypair_path).map_err(|e| CliError::Keypair(cli.keypair.clone(), e.to_string()))?;
+    let client = SolSageClient::new(cluster_url(&cli.url), cli.program_id);
+    let program_id = &cli.program_id;
+    let payer = signer.pubkey();
+
+    match &cli.command {
+        Command::Init => send(&client, &cli, &signer, vec![initialize(program_id, &payer)]),
+        Command::Stake { category, title, content, content_hash, license, amount } => {
+            let content_hash = match (content, content_hash) {
+                (Some(path), _) => hashing::content_hash(&fs::read_to_string(path)?),
+                (None, Some(content_hash)) => *content_hash,
+                (None, None) => unreachable!("clap requires one of them"),
+            };
+            // Entries are numbered per staker; the index tracks the next one
+            let entry_index = match client.fetch_staker_index(&payer) {
+                Ok(staker_index) => staker_index.entry_count,
+                Err(ClientError::AccountNotFound(_)) => 0,
+                Err(e) => return Err(e.into()),
+            };
+            let content = StakedContent { content_hash, title: title.clone(), license: license.clone() };
+            let category = find_category_address(program_id, category).0;
+            let ix = stake_knowledge(program_id, &payer, entry_index, content, *amount, &category);
+            println!("entry {}", find_knowledge_entry_address(program_id, &payer, entry_index).0);
+            send(&client, &cli, &signer, vec![ix])
+        }
+        Command::Attribute { entry, query_hash, score } => {
+            let knowledge_entry = client.fetch_knowledge_entry(entry)?;
+            let target = AttributionTarget {
+                knowledge_entry: *entry,
+                staker: knowledge_entry.staker,
+                category: knowledge_entry.category,
+                collection: knowledge_entry.collection,
+                access_pass: (knowledge_entry.visibility != Visibility::Public)
+                    .then(|| find_access_pass_address(program_id, entry, &payer).0),
+                relevance_score: *score,
+            };
+            let ix = record_attribution(program_id, &payer, &target, *query_hash, None, None, AttributionMetadata::default());
+            send(&client, &cli, &signer, vec![ix])
+        }
+        Command::Claim { entry, destination } => {
+            let knowledge_entry = client.fetch_knowledge_entry(entry)?;
+            let mint = find_mint_address(program_id).0;
+            let destination = destination.unwrap_or_else(|| associated_token_address(&knowledge_entry.staker, &mint));
+            let co_author_destinations: Vec<_> = knowledge_entry
+                .royalty_splits
+                .iter()
+                .map(|split| associated_token_address(&split.co_author, &mint))
+                .collect();
+            let claims = claimable(&client.fetch_attributions_by_entry(entry)?, unix_timestamp());
+            println!("claiming {} attributions", claims.len());
+
+            // Entries with nothing left to settle still pay out their stream
+            let chunks: Vec<&[AttributionClaim]> = match claims.is_empty() {
+                true => vec![&[]],
+                false => claims.chunks(CLAIM_CHUNK).collect(),
+            };
+            for chunk in chunks {
+                let ix = claim_rewards(
+                    program_id,
+                    &payer,
+                    &knowledge_entry.staker,
+                    entry,
+                    &destination,
+                    &co_author_destinations,
+                    chunk,
+                );
+                send(&client, &cli, &signer, vec![ix])?;
+            }
+            Ok(())
+        }
+        Command::ShowEntry { entry } => {
+            print_entry(entry, &client.fetch_knowledge_entry(entry)?);
+            Ok(())
+        }
+        Command::ListEntries { staker, category } => {
+            let entries = match category {
+                Some(name) => {
+                    let category = find_category_address(program_id, name).0;
+                    client.fetch_active_knowledge_entries_by_category(&category)?
+                }
+                None => client.fetch_knowledge_entries_by_staker(&staker.unwrap_or(payer))?,
+            };
+            for (address, entry) in &entries {
+                println!(
+                    "{address}  {:>12} SOL  {:>6} attributions  {}",
+                    lamports_to_sol(entry.stake_amount),
+                    entry.total_attributions,
+                    entry.title
+                );
+            }
+            println!("{} entries", entries.len());
+            Ok(())
+        }
+        Command::SetRewardRate { reward_per_attribution } => {
+            send(&client, &cli, &signer, vec![set_reward_rate(program_id, &payer, *reward_per_attribution)])
+        }
+    }
+}
+
+fn send(client: &SolSageClient, cli: &Cli, signer: &Keypair, instructions: Vec<Instruction>) -> CliResult<()> {
+    let mut builder = TransactionBuilder::new(signer.pubkey()).instructions(instructions);
+    builder = match cli.priority_fee {
+        Some(priority_fee) => builder.priority_fee(priority_fee),
+        None => builder.fee_estimator(|accounts| client.fetch_priority_fee(accounts)),
+    };
+    let signature = client.send_transaction(&builder, &[signer])?;
+    println!("signature {signature}");
+    Ok(())
+}
+
+fn print_entry(address: &Pubkey, entry: &KnowledgeEntry) {
+    println!("entry              {address}");
+    println!("title              {}", entry.title);
+    println!("staker             {}", entry.staker);
+    println!("category           {}", entry.category);
+    println!("content hash       {}", to_hex(&entry.content_hash));
+    println!("license            {:?}", entry.license);
+    println!("active             {}", entry.is_active);
+    println!("version            {}", entry.version);
+    println!("stake              {} SOL", lamports_to_sol(entry.stake_amount));
+    println!("attributions       {}", entry.total_attributions);
+    println!("pending rewards    {}", entry.pending_rewards);
+    if let Some(collection) = entry.collection {
+        println!("collection         {collection}");
+    }
+    for split in &entry.royalty_splits {
+        println!("co-author          {} ({} bps)", split.co_author, split.bps);
+    }
+}
+
+// ============================================================================
+// HELPERS
+// ============================================================================
+
+/// The RPC URL a cluster moniker stands for; anything else is taken as a
+/// URL already
+pub fn cluster_url(url: &str) -> String {
+    match url {
+        "mainnet-beta" | "m" => "https://api.mainnet-beta.solana.com",
+        "devnet" | "d" => "https://api.devnet.solana.com",
+        "testnet" | "t" => "https://api.testnet.solana.com",
+        "localhost" | "l" => "http://localhost:8899",
+        url => url,
+    }
+    .to_string()
+}
+
+/// `path` with a leading `~` replaced by the home directory
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// `owner`'s associated token account for `mint`
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), spl_token::id().as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Attributions whose reward has vested by `now` and is still owed:
+/// not claimed, reversed, waiting on consensus or paid through the
+/// entry's reward stream
+pub fn claimable(attributions: &[(Pubkey, Attribution)], now: i64) -> Vec<AttributionClaim> {
+    attributions
+        .iter()
+        .filter(|(_, attribution)| {
+            !attribution.reward_claimed
+                && !attribution.reversed
+                && !attribution.pending
+                && !attribution.streamed
+                && attribution.unlocks_at <= now
+        })
+        .map(|(address, attribution)| AttributionClaim { attribution: *address, epoch: attribution.epoch })
+        .collect()
+}
+
+pub fn parse_hash(value: &str) -> Result<[u8; 32], String> {
+    let invalid = || format!("{value} is not 64 hex digits");
+    if value.len() != 64 || !value.is_ascii() {
+        return Err(invalid());
+    }
+    let mut hash = [0; 32];
+    for (byte, digits) in hash.iter_mut().zip(value.as_bytes().chunks(2)) {
+        let digits = std::str::from_utf8(digits).map_err(|_| invalid())?;
+        *byte = u8::from_str_radix(digits, 16).map_err(|_| invalid())?;
+    }
+    Ok(hash)
+}
+
+pub fn parse_license(value: &str) -> Result<LicenseType, String> {
+    match value {
+        "mit" => Ok(LicenseType::Mit),
+        "cc-by" => Ok(LicenseType::CcBy),
+        "proprietary" => Ok(LicenseType::Proprietary),
+        uri if uri.contains("://") => Ok(LicenseType::Custom { uri: uri.to_string() }),
+        _ => Err(format!("{value} is not mit, cc-by, proprietary or a terms URL")),
+    }
+}
+
+/// An amount of SOL, in lamports
+pub fn parse_sol(value: &str) -> Result<u64, String> {
+    match f64::from_str(value) {
+        Ok(sol) if sol.is_finite() && sol > 0.0 => Ok(sol_to_lamports(sol)),
+        _ => Err(format!("{value} is not a positive amount of SOL")),
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn unix_timestamp() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
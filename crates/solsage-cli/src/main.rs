@@ -0,0 +1,14 @@
+use std::process::ExitCode;
+
+use clap::Parser;
+use solsage_cli::Cli;
+
+fn main() -> ExitCode {
+    match solsage_cli::run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use clap::Parser;
+use solana_program::pubkey::Pubkey;
+use solsage_cli::{
+    associated_token_address, claimable, cluster_url, parse_hash, parse_license, parse_sol, Cli, Command,
+};
+use solsage_client::{Attribution, AttributionClaim, LicenseType};
+
+fn attribution(unlocks_at: i64, reward_claimed: bool, streamed: bool) -> Attribution {
+    Attribution {
+        is_initialized: true,
+        schema_version: 1,
+        knowledge_entry: Pubkey::new_unique(),
+        query_hash: [9; 32],
+        relevance_score: 80,
+        reward: 500,
+        timestamp: 0,
+        unlocks_at,
+        reward_claimed,
+        reversed: false,
+        epoch: Some(3),
+        bump: 0,
+        agent_id: None,
+        model_id: None,
+        pending: false,
+        votes: Vec::new(),
+        streamed,
+        chunk_index: None,
+    }
+}
+
+#[test]
+fn subcommands_parse_with_global_options_anywhere() {
+    let program_id = Pubkey::new_unique();
+    let cli = Cli::try_parse_from([
+        "solsage-cli",
+        "--program-id",
+        &program_id.to_string(),
+        "stake",
+        "--category",
+        "rust",
+        "--title",
+        "Async Rust",
+        "--content-hash",
+        &"ab".repeat(32),
+        "--amount",
+        "1.5",
+        "--url",
+        "localhost",
+    ])
+    .unwrap();
+    assert_eq!(cli.program_id, program_id);
+    assert_eq!(cluster_url(&cli.url), "http://localhost:8899");
+    assert_eq!(
+        cli.command,
+        Command::Stake {
+            category: "rust".to_string(),
+            title: "Async Rust".to_string(),
+            content: None,
+            content_hash: Some([0xab; 32]),
+            license: LicenseType::Mit,
+            amount: 1_500_000_000,
+        }
+    );
+
+    let cli = Cli::try_parse_from(["solsage-cli", "--program-id", &program_id.to_string(), "set-reward-rate", "42"]).unwrap();
+    assert_eq!(cli.command, Command::SetRewardRate { reward_per_attribution: 42 });
+}
+
+#[test]
+fn stakes_need_exactly_one_content_source() {
+    let program_id = Pubkey::new_unique().to_string();
+    let stake = ["solsage-cli", "--program-id", &program_id, "stake", "--category", "rust", "--title", "t", "--amount", "1"];
+    assert!(Cli::try_parse_from(stake).is_err());
+
+    let content_hash = "00".repeat(32);
+    let both = [&stake[..], &["--content", "notes.md", "--content-hash", &content_hash]].concat();
+    assert!(Cli::try_parse_from(both).is_err());
+}
+
+#[test]
+fn arguments_are_validated_before_anything_is_sent() {
+    assert_eq!(parse_hash(&"0f".repeat(32)), Ok([0x0f; 32]));
+    assert!(parse_hash("0f").is_err());
+    assert!(parse_hash(&"zz".repeat(32)).is_err());
+
+    assert_eq!(parse_license("cc-by"), Ok(LicenseType::CcBy));
+    assert_eq!(
+        parse_license("https://example.com/terms"),
+        Ok(LicenseType::Custom { uri: "https://example.com/terms".to_string() })
+    );
+    assert!(parse_license("gpl").is_err());
+
+    assert_eq!(parse_sol("0.25"), Ok(250_000_000));
+    assert!(parse_sol("0").is_err() && parse_sol("-1").is_err() && parse_sol("lots").is_err());
+
+    assert_eq!(cluster_url("https://rpc.example.com"), "https://rpc.example.com");
+}
+
+#[test]
+fn only_vested_unsettled_attributions_are_claimed() {
+    let attributions: Vec<_> = [
+        attribution(100, false, false),
+        attribution(101, false, false),
+        attribution(50, true, false),
+        attribution(50, false, true),
+    ]
+    .into_iter()
+    .map(|attribution| (Pubkey::new_unique(), attribution))
+    .collect();
+    assert_eq!(claimable(&attributions, 100), [AttributionClaim { attribution: attributions[0].0, epoch: Some(3) }]);
+    assert_eq!(claimable(&attributions, 101).len(), 2);
+}
+
+#[test]
+fn associated_token_accounts_differ_per_owner_and_mint() {
+    let (owner, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let address = associated_token_address(&owner, &mint);
+    assert_eq!(address, associated_token_address(&owner, &mint));
+    assert!(!address.is_on_curve());
+    assert_ne!(address, associated_token_address(&Pubkey::new_unique(), &mint));
+    assert_ne!(address, associated_token_address(&owner, &Pubkey::new_unique()));
+}
@@ -0,0 +1,917 @@
+//! Off-chain client for the SolSage native program.
+//!
+//! Fetches typed account state over JSON-RPC. PDA derivation and instruction
+//! builders come from `solsage-cpi` and are re-exported here, so integrators
+//! don't have to hand-roll Borsh payloads or seeds. `TransactionBuilder`
+//! wraps those instructions in transactions with a compute budget and
+//! priority fee attached.
+
+use std::{str::FromStr, thread, time::Duration};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use borsh::BorshSerialize;
+use serde_json::{json, Value};
+use solana_program::{
+    address_lookup_table::instruction as lookup_table, instruction::Instruction, pubkey::Pubkey, system_program,
+};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    message::Message,
+    nonce::state::{State as NonceState, Versions as NonceVersions},
+    signature::Signature,
+    signer::SignerError,
+    signers::Signers,
+    system_instruction,
+    transaction::Transaction,
+};
+use thiserror::Error;
+use ureq::Agent;
+
+pub use solsage_cpi::*;
+
+// ============================================================================
+// RPC
+// ============================================================================
+
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error(transparent)]
+    Http(Box<ureq::Error>),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("RPC error: {0}")]
+    Rpc(String),
+    #[error("Malformed RPC response")]
+    InvalidResponse,
+    #[error("Account {0} not found")]
+    AccountNotFound(Pubkey),
+    #[error("Account {0} is not owned by the SolSage program")]
+    IllegalOwner(Pubkey),
+    #[error("Account {0} does not hold valid SolSage state")]
+    InvalidAccountData(Pubkey),
+    #[error("Account {0} is not an initialized nonce account")]
+    InvalidNonceAccount(Pubkey),
+    #[error(transparent)]
+    Signer(#[from] SignerError),
+    #[error(transparent)]
+    Encoding(#[from] bincode::Error),
+    #[error("Transaction {0} failed: {1}")]
+    TransactionFailed(Signature, String),
+    #[error("Transaction {0} was not confirmed in time")]
+    Unconfirmed(Signature),
+    #[error("Blockhash expired on every one of {0} attempts")]
+    BlockhashExpired(usize),
+    #[error("Instruction is not a ClaimRewards signed by the staker alone")]
+    NotAClaim,
+}
+
+impl From<ureq::Error> for ClientError {
+    fn from(e: ureq::Error) -> Self {
+        ClientError::Http(Box::new(e))
+    }
+}
+
+pub type ClientResult<T> = Result<T, ClientError>;
+
+/// Typed access to SolSage accounts over a Solana JSON-RPC endpoint
+pub struct SolSageClient {
+    agent: Agent,
+    url: String,
+    program_id: Pubkey,
+}
+
+impl SolSageClient {
+    pub fn new(url: impl Into<String>, program_id: Pubkey) -> Self {
+        Self { agent: Agent::new(), url: url.into(), program_id }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn program_id(&self) -> &Pubkey {
+        &self.program_id
+    }
+
+    /// Fetch and decode a program-owned account
+    pub fn fetch<T: ProgramAccount>(&self, address: &Pubkey) -> ClientResult<T> {
+        let result = self.request(
+            "getAccountInfo",
+            json!([address.to_string(), { "encoding": "base64" }]),
+        )?;
+        match &result["value"] {
+            Value::Null => Err(ClientError::AccountNotFound(*address)),
+            account => self.decode(address, account),
+        }
+    }
+
+    pub fn fetch_protocol(&self) -> ClientResult<Protocol> {
+        self.fetch(&find_protocol_address(&self.program_id).0)
+    }
+
+    pub fn fetch_knowledge_entry(&self, address: &Pubkey) -> ClientResult<KnowledgeEntry> {
+        self.fetch(address)
+    }
+
+    pub fn fetch_staker_profile(&self, staker: &Pubkey) -> ClientResult<StakerProfile> {
+        self.fetch(&find_staker_profile_address(&self.program_id, staker).0)
+    }
+
+    pub fn fetch_staker_index(&self, staker: &Pubkey) -> ClientResult<StakerIndex> {
+        self.fetch(&find_staker_index_address(&self.program_id, staker).0)
+    }
+
+    /// Entries `staker` created, looked up through their staker index rather
+    /// than a program account scan. Closed entries are skipped.
+    pub fn fetch_staked_entries(&self, staker: &Pubkey) -> ClientResult<Vec<(Pubkey, KnowledgeEntry)>> {
+        let staker_index = self.fetch_staker_index(staker)?;
+        let mut entries = Vec::new();
+        for address in staked_entry_addresses(&self.program_id, staker, staker_index.entry_count) {
+            match self.fetch_knowledge_entry(&address) {
+                Ok(entry) => entries.push((address, entry)),
+                Err(ClientError::AccountNotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(entries)
+    }
+
+    pub fn fetch_report_queue(&self, knowledge_entry: &Pubkey) -> ClientResult<ReportQueue> {
+        self.fetch(&find_report_queue_address(&self.program_id, knowledge_entry).0)
+    }
+
+    pub fn fetch_bounty(&self, requester: &Pubkey, query_hash: &[u8; 32]) -> ClientResult<Bounty> {
+        self.fetch(&find_bounty_address(&self.program_id, requester, query_hash).0)
+    }
+
+    pub fn fetch_bounty_answer(&self, bounty: &Pubkey, knowledge_entry: &Pubkey) -> ClientResult<BountyAnswer> {
+        self.fetch(&find_bounty_answer_address(&self.program_id, bounty, knowledge_entry).0)
+    }
+
+    pub fn fetch_listing(&self, knowledge_entry: &Pubkey) -> ClientResult<Listing> {
+        self.fetch(&find_listing_address(&self.program_id, knowledge_entry).0)
+    }
+
+    /// The category named `name`, with its open entries, attributions and
+    /// rewards credited so far
+    pub fn fetch_category(&self, name: &str) -> ClientResult<Category> {
+        self.fetch(&find_category_address(&self.program_id, name).0)
+    }
+
+    pub fn fetch_collection(&self, owner: &Pubkey, name: &str) -> ClientResult<Collection> {
+        self.fetch(&find_collection_address(&self.program_id, owner, name).0)
+    }
+
+    pub fn fetch_tag_index(&self, tag: &str) -> ClientResult<TagIndex> {
+        self.fetch(&find_tag_index_address(&self.program_id, tag).0)
+    }
+
+    pub fn fetch_subscription(&self, consumer: &Pubkey) -> ClientResult<Subscription> {
+        self.fetch(&find_subscription_address(&self.program_id, consumer).0)
+    }
+
+    pub fn fetch_agent_stats(&self, agent_id: &[u8; 32]) -> ClientResult<AgentStats> {
+        self.fetch(&find_agent_stats_address(&self.program_id, agent_id).0)
+    }
+
+    pub fn fetch_oracle_state(&self, oracle: &Pubkey) -> ClientResult<OracleState> {
+        self.fetch(&find_oracle_state_address(&self.program_id, oracle).0)
+    }
+
+    pub fn fetch_payer_stats(&self, payer: &Pubkey) -> ClientResult<PayerStats> {
+        self.fetch(&find_payer_stats_address(&self.program_id, payer).0)
+    }
+
+    pub fn fetch_bridge_emitter(&self, emitter_chain: u16, emitter_address: &[u8; 32]) -> ClientResult<BridgeEmitter> {
+        self.fetch(&find_bridge_emitter_address(&self.program_id, emitter_chain, emitter_address).0)
+    }
+
+    pub fn fetch_snapshot(&self, day: u64) -> ClientResult<Snapshot> {
+        self.fetch(&find_snapshot_address(&self.program_id, day).0)
+    }
+
+    pub fn fetch_leaderboard(&self) -> ClientResult<Leaderboard> {
+        self.fetch(&find_leaderboard_address(&self.program_id).0)
+    }
+
+    pub fn fetch_reviewer_registry(&self) -> ClientResult<ReviewerRegistry> {
+        self.fetch(&find_reviewer_registry_address(&self.program_id).0)
+    }
+
+    pub fn fetch_proposal(&self, index: u64) -> ClientResult<Proposal> {
+        self.fetch(&find_proposal_address(&self.program_id, index).0)
+    }
+
+    pub fn fetch_vote_record(&self, proposal: &Pubkey, voter: &Pubkey) -> ClientResult<VoteRecord> {
+        self.fetch(&find_vote_record_address(&self.program_id, proposal, voter).0)
+    }
+
+    pub fn fetch_entry_vote(&self, knowledge_entry: &Pubkey, voter: &Pubkey) -> ClientResult<EntryVote> {
+        self.fetch(&find_entry_vote_address(&self.program_id, knowledge_entry, voter).0)
+    }
+
+    pub fn fetch_attribution(&self, address: &Pubkey) -> ClientResult<Attribution> {
+        self.fetch(address)
+    }
+
+    /// Entries currently owned by `staker`, including ones transferred in
+    pub fn fetch_knowledge_entries_by_staker(
+        &self,
+        staker: &Pubkey,
+    ) -> ClientResult<Vec<(Pubkey, KnowledgeEntry)>> {
+        self.fetch_program_accounts(&knowledge_entries_by_staker(staker))
+    }
+
+    /// Active entries filed under `category`
+    pub fn fetch_active_knowledge_entries_by_category(
+        &self,
+        category: &Pubkey,
+    ) -> ClientResult<Vec<(Pubkey, KnowledgeEntry)>> {
+        self.fetch_program_accounts(&active_knowledge_entries_by_category(category))
+    }
+
+    /// Entries offered under `license`
+    pub fn fetch_knowledge_entries_by_license(
+        &self,
+        license: &LicenseType,
+    ) -> ClientResult<Vec<(Pubkey, KnowledgeEntry)>> {
+        self.fetch_program_accounts(&knowledge_entries_by_license(license)?)
+    }
+
+    /// Every attribution recorded against `knowledge_entry`
+    pub fn fetch_attributions_by_entry(
+        &self,
+        knowledge_entry: &Pubkey,
+    ) -> ClientResult<Vec<(Pubkey, Attribution)>> {
+        // `knowledge_entry` follows the discriminator and schema version
+        self.fetch_program_accounts(&[
+            RpcFilter::DataSize(Attribution::LEN),
+            RpcFilter::memcmp(0, &[Attribution::DISCRIMINATOR]),
+            RpcFilter::memcmp(2, knowledge_entry.as_ref()),
+        ])
+    }
+
+    /// The slot the endpoint last processed, recent enough to derive a new
+    /// lookup table from
+    pub fn fetch_slot(&self) -> ClientResult<u64> {
+        self.request("getSlot", json!([{ "commitment": "finalized" }]))?
+            .as_u64()
+            .ok_or(ClientError::InvalidResponse)
+    }
+
+    /// The latest blockhash and the last block height it's valid at
+    pub fn fetch_latest_blockhash(&self) -> ClientResult<(Hash, u64)> {
+        let result = self.request("getLatestBlockhash", json!([{ "commitment": "confirmed" }]))?;
+        let blockhash = parse_hash(&result["value"]["blockhash"])?;
+        let last_valid_block_height =
+            result["value"]["lastValidBlockHeight"].as_u64().ok_or(ClientError::InvalidResponse)?;
+        Ok((blockhash, last_valid_block_height))
+    }
+
+    pub fn fetch_block_height(&self) -> ClientResult<u64> {
+        self.request("getBlockHeight", json!([{ "commitment": "confirmed" }]))?
+            .as_u64()
+            .ok_or(ClientError::InvalidResponse)
+    }
+
+    /// Median priority fee, in micro-lamports per compute unit, recent
+    /// transactions writing `writable_accounts` paid. Usable as a
+    /// `TransactionBuilder` fee estimator.
+    pub fn fetch_priority_fee(&self, writable_accounts: &[Pubkey]) -> ClientResult<u64> {
+        let accounts: Vec<_> = writable_accounts.iter().map(Pubkey::to_string).collect();
+        let result = self.request("getRecentPrioritizationFees", json!([accounts]))?;
+        let mut fees = result
+            .as_array()
+            .ok_or(ClientError::InvalidResponse)?
+            .iter()
+            .map(|fee| fee["prioritizationFee"].as_u64().ok_or(ClientError::InvalidResponse))
+            .collect::<ClientResult<Vec<_>>>()?;
+        fees.sort_unstable();
+        Ok(fees.get(fees.len() / 2).copied().unwrap_or(0))
+    }
+
+    /// The blockhash `nonce_account` currently stores, which a durable
+    /// nonce transaction is signed against
+    pub fn fetch_nonce(&self, nonce_account: &Pubkey) -> ClientResult<Hash> {
+        let result = self.request(
+            "getAccountInfo",
+            json!([nonce_account.to_string(), { "encoding": "base64" }]),
+        )?;
+        let account = match &result["value"] {
+            Value::Null => return Err(ClientError::AccountNotFound(*nonce_account)),
+            account => account,
+        };
+        if parse_pubkey(&account["owner"])? != system_program::id() {
+            return Err(ClientError::InvalidNonceAccount(*nonce_account));
+        }
+
+        let data = account["data"][0]
+            .as_str()
+            .and_then(|data| BASE64.decode(data).ok())
+            .ok_or(ClientError::InvalidResponse)?;
+        match bincode::deserialize::<NonceVersions>(&data).map(|versions| versions.state().clone()) {
+            Ok(NonceState::Initialized(nonce)) => Ok(nonce.blockhash()),
+            _ => Err(ClientError::InvalidNonceAccount(*nonce_account)),
+        }
+    }
+
+    /// Build, sign and send `builder`'s transaction, waiting for it to
+    /// confirm. A transaction whose blockhash expires first is rebuilt
+    /// against a fresh one, up to `SEND_ATTEMPTS` times; one spending a
+    /// durable nonce never expires, so it's sent once.
+    pub fn send_transaction<T: Signers + ?Sized>(
+        &self,
+        builder: &TransactionBuilder,
+        signers: &T,
+    ) -> ClientResult<Signature> {
+        if let Some(nonce) = builder.nonce() {
+            let mut transaction = builder.build(self.fetch_nonce(&nonce.account)?)?;
+            transaction.try_sign(signers, transaction.message.recent_blockhash)?;
+            return self.send_signed_transaction(&transaction);
+        }
+
+        for _ in 0..SEND_ATTEMPTS {
+            let (blockhash, last_valid_block_height) = self.fetch_latest_blockhash()?;
+            let mut transaction = builder.build(blockhash)?;
+            transaction.try_sign(signers, blockhash)?;
+            let signature = match self.submit(&transaction) {
+                Ok(signature) => signature,
+                Err(ClientError::Rpc(error)) if error.contains("Blockhash not found") => continue,
+                Err(e) => return Err(e),
+            };
+            if self.confirm(&signature, Some(last_valid_block_height))? {
+                return Ok(signature);
+            }
+        }
+        Err(ClientError::BlockhashExpired(SEND_ATTEMPTS))
+    }
+
+    /// Send a transaction signed elsewhere, such as a claim signed offline
+    /// against a durable nonce, and wait for it to confirm
+    pub fn send_signed_transaction(&self, transaction: &Transaction) -> ClientResult<Signature> {
+        let signature = self.submit(transaction)?;
+        self.confirm(&signature, None)?;
+        Ok(signature)
+    }
+
+    /// An unsigned `ClaimRewards` transaction for a cold wallet to sign
+    /// offline, priced at the going priority fee. See `nonce_claim_transaction`.
+    pub fn prepare_nonce_claim(
+        &self,
+        staker: &Pubkey,
+        claim: Instruction,
+        nonce_account: &Pubkey,
+    ) -> ClientResult<Transaction> {
+        let writable_accounts = TransactionBuilder::new(*staker).instruction(claim.clone()).writable_accounts();
+        let priority_fee = self.fetch_priority_fee(&writable_accounts)?;
+        nonce_claim_transaction(staker, claim, nonce_account, self.fetch_nonce(nonce_account)?, priority_fee)
+    }
+
+    /// Airdrop `lamports` to `recipient` and wait for it to land; only test
+    /// validators and devnet hand them out
+    pub fn request_airdrop(&self, recipient: &Pubkey, lamports: u64) -> ClientResult<Signature> {
+        let result = self.request("requestAirdrop", json!([recipient.to_string(), lamports]))?;
+        let signature = result
+            .as_str()
+            .and_then(|signature| Signature::from_str(signature).ok())
+            .ok_or(ClientError::InvalidResponse)?;
+        self.confirm(&signature, None)?;
+        Ok(signature)
+    }
+
+    fn submit(&self, transaction: &Transaction) -> ClientResult<Signature> {
+        let encoded = BASE64.encode(bincode::serialize(transaction)?);
+        let result = self.request(
+            "sendTransaction",
+            json!([encoded, { "encoding": "base64", "preflightCommitment": "confirmed" }]),
+        )?;
+        result
+            .as_str()
+            .and_then(|signature| Signature::from_str(signature).ok())
+            .ok_or(ClientError::InvalidResponse)
+    }
+
+    /// Poll until `signature` confirms, returning false once the chain is
+    /// past `last_valid_block_height` without it. Transactions with no
+    /// expiry are polled for `CONFIRMATION_POLLS` rounds at most.
+    fn confirm(&self, signature: &Signature, last_valid_block_height: Option<u64>) -> ClientResult<bool> {
+        for _ in 0..CONFIRMATION_POLLS {
+            let result = self.request("getSignatureStatuses", json!([[signature.to_string()]]))?;
+            let status = &result["value"][0];
+            if matches!(status["confirmationStatus"].as_str(), Some("confirmed" | "finalized")) {
+                return match &status["err"] {
+                    Value::Null => Ok(true),
+                    error => Err(ClientError::TransactionFailed(*signature, error.to_string())),
+                };
+            }
+            if let Some(last_valid_block_height) = last_valid_block_height {
+                if self.fetch_block_height()? > last_valid_block_height {
+                    return Ok(false);
+                }
+            }
+            thread::sleep(CONFIRMATION_POLL_INTERVAL);
+        }
+        Err(ClientError::Unconfirmed(*signature))
+    }
+
+    /// Lookup table addresses for `knowledge_entries`, fetched to find their
+    /// stakers, categories and collections. Addresses shared between entries
+    /// are listed once.
+    pub fn fetch_entry_lookup_addresses(&self, knowledge_entries: &[Pubkey]) -> ClientResult<Vec<Pubkey>> {
+        let mut addresses = Vec::new();
+        for address in knowledge_entries {
+            let entry = self.fetch_knowledge_entry(address)?;
+            for address in entry_lookup_addresses(&self.program_id, address, &entry) {
+                if !addresses.contains(&address) {
+                    addresses.push(address);
+                }
+            }
+        }
+        Ok(addresses)
+    }
+
+    /// Program accounts matching every one of `filters`
+    pub fn fetch_program_accounts<T: ProgramAccount>(
+        &self,
+        filters: &[RpcFilter],
+    ) -> ClientResult<Vec<(Pubkey, T)>> {
+        let filters: Vec<_> = filters.iter().map(RpcFilter::to_json).collect();
+        let result = self.request(
+            "getProgramAccounts",
+            json!([self.program_id.to_string(), { "encoding": "base64", "filters": filters }]),
+        )?;
+
+        result
+            .as_array()
+            .ok_or(ClientError::InvalidResponse)?
+            .iter()
+            .map(|keyed| {
+                let address = parse_pubkey(&keyed["pubkey"])?;
+                Ok((address, self.decode(&address, &keyed["account"])?))
+            })
+            .collect()
+    }
+
+    fn request(&self, method: &str, params: Value) -> ClientResult<Value> {
+        let mut response: Value = self
+            .agent
+            .post(&self.url)
+            .send_json(json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))?
+            .into_json()?;
+
+        if let Some(error) = response.get("error") {
+            return Err(ClientError::Rpc(error.to_string()));
+        }
+        response.get_mut("result").map(Value::take).ok_or(ClientError::InvalidResponse)
+    }
+
+    /// Decode a base64-encoded account, checking it belongs to the program.
+    /// Accounts are allocated at their maximum size, so trailing bytes are
+    /// ignored.
+    fn decode<T: ProgramAccount>(&self, address: &Pubkey, account: &Value) -> ClientResult<T> {
+        if parse_pubkey(&account["owner"])? != self.program_id {
+            return Err(ClientError::IllegalOwner(*address));
+        }
+
+        let data = account["data"][0]
+            .as_str()
+            .and_then(|data| BASE64.decode(data).ok())
+            .ok_or(ClientError::InvalidResponse)?;
+        T::unpack(&data).map_err(|_| ClientError::InvalidAccountData(*address))
+    }
+}
+
+// ============================================================================
+// FILTERS
+// ============================================================================
+
+/// A `getProgramAccounts` filter, as `RpcFilterType` encodes it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RpcFilter {
+    /// Accounts of exactly this many bytes
+    DataSize(usize),
+    /// Accounts holding `bytes` at byte `offset`
+    Memcmp { offset: usize, bytes: Vec<u8> },
+}
+
+impl RpcFilter {
+    pub fn memcmp(offset: usize, bytes: &[u8]) -> Self {
+        RpcFilter::Memcmp { offset, bytes: bytes.to_vec() }
+    }
+
+    pub fn to_json(&self) -> Value {
+        match self {
+            RpcFilter::DataSize(len) => json!({ "dataSize": len }),
+            RpcFilter::Memcmp { offset, bytes } => {
+                json!({ "memcmp": { "offset": offset, "bytes": BASE64.encode(bytes), "encoding": "base64" } })
+            }
+        }
+    }
+}
+
+/// Knowledge entries on the current layout. Entries still awaiting
+/// `MigrateAccount` keep their fields elsewhere or lack their tag, and are
+/// left out.
+pub fn knowledge_entries() -> Vec<RpcFilter> {
+    vec![
+        RpcFilter::DataSize(KnowledgeEntry::LEN),
+        RpcFilter::memcmp(0, &[KnowledgeEntry::DISCRIMINATOR]),
+        RpcFilter::memcmp(1, &[KNOWLEDGE_ENTRY_SCHEMA_VERSION]),
+    ]
+}
+
+/// Filters for the entries currently owned by `staker`
+pub fn knowledge_entries_by_staker(staker: &Pubkey) -> Vec<RpcFilter> {
+    let mut filters = knowledge_entries();
+    filters.push(RpcFilter::memcmp(KnowledgeEntry::STAKER_OFFSET, staker.as_ref()));
+    filters
+}
+
+/// Filters for the active entries filed under `category`
+pub fn active_knowledge_entries_by_category(category: &Pubkey) -> Vec<RpcFilter> {
+    let mut filters = knowledge_entries();
+    filters.push(RpcFilter::memcmp(KnowledgeEntry::CATEGORY_OFFSET, category.as_ref()));
+    filters.push(RpcFilter::memcmp(KnowledgeEntry::IS_ACTIVE_OFFSET, &[true as u8]));
+    filters
+}
+
+/// Filters for the entries offered under `license`
+pub fn knowledge_entries_by_license(license: &LicenseType) -> ClientResult<Vec<RpcFilter>> {
+    let mut filters = knowledge_entries();
+    filters.push(RpcFilter::memcmp(KnowledgeEntry::LICENSE_OFFSET, &license.try_to_vec()?));
+    Ok(filters)
+}
+
+// ============================================================================
+// QUERY HASHES
+// ============================================================================
+
+/// Prefix of every query hash preimage; a change to the scheme bumps its
+/// version
+pub const QUERY_HASH_DOMAIN: &[u8] = b"solsage-query-v1";
+/// Width of the time window a query hash commits to. The same query from
+/// the same consumer hashes the same within one window and differently in
+/// the next, so a repeat query later on can be attributed again.
+pub const QUERY_HASH_BUCKET_SECS: i64 = 3600;
+
+/// The `query_hash` an attribution for `query`, asked by `consumer` at unix
+/// time `timestamp`, should carry. With an empty salt this is
+///
+/// `sha256(QUERY_HASH_DOMAIN || u32le(len) || canonicalize(query) || consumer || i64le(bucket) || u32le(0))`
+///
+/// where `bucket` is `timestamp` divided by `QUERY_HASH_BUCKET_SECS`,
+/// rounding down, and `canonicalize` is `hashing::canonicalize`. Anyone who
+/// knows the three inputs can rederive the hash and so the attribution's
+/// address; two integrators can only collide by sharing a consumer.
+pub fn query_hash(query: &str, consumer: &Pubkey, timestamp: i64) -> [u8; 32] {
+    salted_query_hash(query, consumer, timestamp, &[])
+}
+
+/// `query_hash` with `salt`, length-prefixed, in place of the empty salt.
+/// A secret salt keeps the query text from being confirmed by hashing
+/// guesses; only holders of the salt can then rederive the hash.
+pub fn salted_query_hash(query: &str, consumer: &Pubkey, timestamp: i64, salt: &[u8]) -> [u8; 32] {
+    let query = hashing::canonicalize(query);
+    let bucket = timestamp.div_euclid(QUERY_HASH_BUCKET_SECS);
+    solana_program::hash::hashv(&[
+        QUERY_HASH_DOMAIN,
+        &(query.len() as u32).to_le_bytes(),
+        query.as_bytes(),
+        consumer.as_ref(),
+        &bucket.to_le_bytes(),
+        &(salt.len() as u32).to_le_bytes(),
+        salt,
+    ])
+    .to_bytes()
+}
+
+// ============================================================================
+// EMBEDDING FINGERPRINTS
+// ============================================================================
+
+/// Prefix of the seed every fingerprint hyperplane is derived from; a change
+/// to the scheme bumps its version
+pub const EMBEDDING_FINGERPRINT_DOMAIN: &[u8] = b"solsage-simhash-v1";
+/// Bits in a fingerprint, one per hyperplane
+pub const EMBEDDING_FINGERPRINT_BITS: usize = KnowledgeEntry::EMBEDDING_FINGERPRINT_LEN * 8;
+
+/// The fingerprint `UpdateEmbeddingFingerprint` should publish for a content
+/// embedding: a SimHash setting bit `i` when `embedding` lies on the
+/// positive side of hyperplane `i`. Component `j` of that hyperplane is +1
+/// where bit `j % 256` of
+///
+/// `sha256(EMBEDDING_FINGERPRINT_DOMAIN || u16le(i) || u32le(j / 256))`
+///
+/// is set and -1 where it isn't, bits counted from the low bit of the first
+/// byte. Embeddings pointing the same way share most bits, so the distance
+/// between two fingerprints out of `EMBEDDING_FINGERPRINT_BITS` estimates
+/// the angle between their embeddings as a fraction of 180 degrees.
+/// Fingerprints only compare when the same embedding model produced both.
+pub fn embedding_fingerprint(embedding: &[f32]) -> [u8; KnowledgeEntry::EMBEDDING_FINGERPRINT_LEN] {
+    let mut fingerprint = [0; KnowledgeEntry::EMBEDDING_FINGERPRINT_LEN];
+    for bit in 0..EMBEDDING_FINGERPRINT_BITS {
+        let mut projection = 0f64;
+        for (block, values) in (0u32..).zip(embedding.chunks(256)) {
+            let signs = solana_program::hash::hashv(&[
+                EMBEDDING_FINGERPRINT_DOMAIN,
+                &(bit as u16).to_le_bytes(),
+                &block.to_le_bytes(),
+            ])
+            .to_bytes();
+            for (index, value) in values.iter().enumerate() {
+                let value = f64::from(*value);
+                projection += if signs[index / 8] >> (index % 8) & 1 == 1 { value } else { -value };
+            }
+        }
+        if projection >= 0.0 {
+            fingerprint[bit / 8] |= 1 << (bit % 8);
+        }
+    }
+    fingerprint
+}
+
+/// Bits two fingerprints differ in; 0 for the same direction, around half of
+/// `EMBEDDING_FINGERPRINT_BITS` for unrelated content
+pub fn fingerprint_distance(
+    a: &[u8; KnowledgeEntry::EMBEDDING_FINGERPRINT_LEN],
+    b: &[u8; KnowledgeEntry::EMBEDDING_FINGERPRINT_LEN],
+) -> u32 {
+    a.iter().zip(b).map(|(a, b)| (a ^ b).count_ones()).sum()
+}
+
+/// Entries whose published fingerprint is within `max_distance` of
+/// `fingerprint`, nearest first, with their distance. Entries without a
+/// fingerprint are left out.
+pub fn similar_entries<'a>(
+    entries: &'a [(Pubkey, KnowledgeEntry)],
+    fingerprint: &[u8; KnowledgeEntry::EMBEDDING_FINGERPRINT_LEN],
+    max_distance: u32,
+) -> Vec<(&'a Pubkey, &'a KnowledgeEntry, u32)> {
+    let mut similar: Vec<_> = entries
+        .iter()
+        .filter_map(|(address, entry)| {
+            let distance = fingerprint_distance(entry.embedding_fingerprint.as_ref()?, fingerprint);
+            (distance <= max_distance).then_some((address, entry, distance))
+        })
+        .collect();
+    similar.sort_by_key(|(_, _, distance)| *distance);
+    similar
+}
+
+// ============================================================================
+// LOOKUP TABLES
+// ============================================================================
+
+/// Addresses one extend instruction adds, few enough that it still fits a
+/// legacy transaction alongside its payer and authority signatures
+pub const LOOKUP_TABLE_EXTEND_CHUNK: usize = 30;
+
+/// Accounts every batch attribution and claim names, whichever entries it
+/// touches
+pub fn protocol_lookup_addresses(program_id: &Pubkey) -> Vec<Pubkey> {
+    vec![
+        *program_id,
+        find_protocol_address(program_id).0,
+        find_treasury_address(program_id).0,
+        find_mint_address(program_id).0,
+        find_mint_authority_address(program_id).0,
+        find_reward_vault_address(program_id).0,
+        find_leaderboard_address(program_id).0,
+        system_program::id(),
+        spl_token::id(),
+    ]
+}
+
+/// Accounts a batch attribution or claim names for `knowledge_entry`: the
+/// entry, its staker's profile, its category and its collection, if any
+pub fn entry_lookup_addresses(program_id: &Pubkey, knowledge_entry: &Pubkey, entry: &KnowledgeEntry) -> Vec<Pubkey> {
+    let mut addresses = vec![
+        *knowledge_entry,
+        find_staker_profile_address(program_id, &entry.staker).0,
+        entry.category,
+    ];
+    addresses.extend(entry.collection);
+    addresses
+}
+
+/// Create a lookup table owned by `authority` and seed it with the
+/// protocol's addresses. Returns the instructions, which fit one
+/// transaction, and the table's address; `recent_slot` must be a slot the
+/// cluster still remembers, see `SolSageClient::fetch_slot`.
+pub fn create_protocol_lookup_table(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    recent_slot: u64,
+) -> (Vec<Instruction>, Pubkey) {
+    let (create, lookup_table) = lookup_table::create_lookup_table(*authority, *payer, recent_slot);
+    let mut instructions = vec![create];
+    instructions.extend(extend_protocol_lookup_table(
+        &lookup_table,
+        authority,
+        payer,
+        &protocol_lookup_addresses(program_id),
+    ));
+    (instructions, lookup_table)
+}
+
+/// Add `addresses` to `lookup_table`, `LOOKUP_TABLE_EXTEND_CHUNK` at a
+/// time. Each instruction needs its own transaction; `payer` funds the
+/// table's growing rent.
+pub fn extend_protocol_lookup_table(
+    lookup_table: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    addresses: &[Pubkey],
+) -> Vec<Instruction> {
+    addresses
+        .chunks(LOOKUP_TABLE_EXTEND_CHUNK)
+        .map(|chunk| lookup_table::extend_lookup_table(*lookup_table, *authority, Some(*payer), chunk.to_vec()))
+        .collect()
+}
+
+// ============================================================================
+// TRANSACTIONS
+// ============================================================================
+
+/// Times `SolSageClient::send_transaction` rebuilds a transaction whose
+/// blockhash expired before it landed
+pub const SEND_ATTEMPTS: usize = 3;
+/// Compute units budgeted for each instruction unless the builder sets a
+/// limit, as the runtime does for transactions without one
+pub const DEFAULT_COMPUTE_UNITS_PER_INSTRUCTION: u32 = 200_000;
+/// Most compute units a transaction can request
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+/// Status checks before a transaction is given up on, about 90 seconds,
+/// well past a blockhash's lifetime
+const CONFIRMATION_POLLS: usize = 180;
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Priority fee, in micro-lamports per compute unit, to pay for a
+/// transaction writing the given accounts
+pub type FeeEstimator<'a> = Box<dyn Fn(&[Pubkey]) -> ClientResult<u64> + 'a>;
+
+/// A nonce account a transaction spends in place of a recent blockhash,
+/// so it can be signed offline and sent whenever
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DurableNonce {
+    pub account: Pubkey,
+    pub authority: Pubkey,
+}
+
+/// Wraps instructions in a transaction with its compute budget and
+/// priority fee attached
+pub struct TransactionBuilder<'a> {
+    payer: Pubkey,
+    instructions: Vec<Instruction>,
+    compute_unit_limit: Option<u32>,
+    priority_fee: u64,
+    fee_estimator: Option<FeeEstimator<'a>>,
+    nonce: Option<DurableNonce>,
+}
+
+impl<'a> TransactionBuilder<'a> {
+    pub fn new(payer: Pubkey) -> Self {
+        Self {
+            payer,
+            instructions: Vec::new(),
+            compute_unit_limit: None,
+            priority_fee: 0,
+            fee_estimator: None,
+            nonce: None,
+        }
+    }
+
+    pub fn instruction(mut self, instruction: Instruction) -> Self {
+        self.instructions.push(instruction);
+        self
+    }
+
+    pub fn instructions(mut self, instructions: impl IntoIterator<Item = Instruction>) -> Self {
+        self.instructions.extend(instructions);
+        self
+    }
+
+    /// Clamped to `MAX_COMPUTE_UNIT_LIMIT`
+    pub fn compute_unit_limit(mut self, units: u32) -> Self {
+        self.compute_unit_limit = Some(units.min(MAX_COMPUTE_UNIT_LIMIT));
+        self
+    }
+
+    /// A fixed priority fee in micro-lamports per compute unit, paid when
+    /// there's no estimator or it suggests less
+    pub fn priority_fee(mut self, micro_lamports: u64) -> Self {
+        self.priority_fee = micro_lamports;
+        self
+    }
+
+    /// Called with the transaction's writable accounts each time it's
+    /// built, e.g. `|accounts| client.fetch_priority_fee(accounts)`
+    pub fn fee_estimator(mut self, estimator: impl Fn(&[Pubkey]) -> ClientResult<u64> + 'a) -> Self {
+        self.fee_estimator = Some(Box::new(estimator));
+        self
+    }
+
+    /// Spend `account`'s nonce instead of a recent blockhash; `authority`
+    /// must sign alongside the payer
+    pub fn durable_nonce(mut self, account: Pubkey, authority: Pubkey) -> Self {
+        self.nonce = Some(DurableNonce { account, authority });
+        self
+    }
+
+    pub fn payer(&self) -> &Pubkey {
+        &self.payer
+    }
+
+    pub fn nonce(&self) -> Option<&DurableNonce> {
+        self.nonce.as_ref()
+    }
+
+    /// Accounts the instructions write, the payer first
+    pub fn writable_accounts(&self) -> Vec<Pubkey> {
+        let mut accounts = vec![self.payer];
+        for meta in self.instructions.iter().flat_map(|ix| &ix.accounts) {
+            if meta.is_writable && !accounts.contains(&meta.pubkey) {
+                accounts.push(meta.pubkey);
+            }
+        }
+        accounts
+    }
+
+    /// Every instruction the transaction carries: the nonce advance first,
+    /// where the runtime looks for it, then the compute budget, then the
+    /// builder's own
+    pub fn build_instructions(&self) -> ClientResult<Vec<Instruction>> {
+        let mut instructions = Vec::with_capacity(self.instructions.len() + 3);
+        if let Some(nonce) = &self.nonce {
+            instructions.push(system_instruction::advance_nonce_account(&nonce.account, &nonce.authority));
+        }
+
+        let compute_unit_limit = self.compute_unit_limit.unwrap_or_else(|| {
+            (self.instructions.len() as u32)
+                .saturating_mul(DEFAULT_COMPUTE_UNITS_PER_INSTRUCTION)
+                .min(MAX_COMPUTE_UNIT_LIMIT)
+        });
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(compute_unit_limit));
+
+        let estimated = match &self.fee_estimator {
+            Some(estimator) => estimator(&self.writable_accounts())?,
+            None => 0,
+        };
+        let priority_fee = self.priority_fee.max(estimated);
+        if priority_fee > 0 {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(priority_fee));
+        }
+
+        instructions.extend(self.instructions.iter().cloned());
+        Ok(instructions)
+    }
+
+    /// The unsigned transaction against `blockhash`: a recent one, or the
+    /// durable nonce's stored value (`SolSageClient::fetch_nonce`)
+    pub fn build(&self, blockhash: Hash) -> ClientResult<Transaction> {
+        let message = Message::new_with_blockhash(&self.build_instructions()?, Some(&self.payer), &blockhash);
+        Ok(Transaction::new_unsigned(message))
+    }
+}
+
+/// An unsigned `ClaimRewards` transaction spending `nonce_account`'s
+/// stored `nonce` rather than a recent blockhash, so a cold wallet can sign
+/// it offline and it can be sent any time before the nonce next advances.
+/// `staker` claims, pays the fee and authorizes the nonce, so their
+/// signature is the only one it needs; send it signed with
+/// `SolSageClient::send_signed_transaction`.
+pub fn nonce_claim_transaction(
+    staker: &Pubkey,
+    claim: Instruction,
+    nonce_account: &Pubkey,
+    nonce: Hash,
+    priority_fee: u64,
+) -> ClientResult<Transaction> {
+    if claim.data != SolSageInstruction::ClaimRewards.try_to_vec()? {
+        return Err(ClientError::NotAClaim);
+    }
+    if claim.accounts.iter().any(|meta| meta.is_signer && meta.pubkey != *staker) {
+        return Err(ClientError::NotAClaim);
+    }
+    TransactionBuilder::new(*staker)
+        .instruction(claim)
+        .priority_fee(priority_fee)
+        .durable_nonce(*nonce_account, *staker)
+        .build(nonce)
+}
+
+fn parse_hash(value: &Value) -> ClientResult<Hash> {
+    value
+        .as_str()
+        .and_then(|hash| Hash::from_str(hash).ok())
+        .ok_or(ClientError::InvalidResponse)
+}
+
+fn parse_pubkey(value: &Value) -> ClientResult<Pubkey> {
+    value
+        .as_str()
+        .and_then(|key| Pubkey::from_str(key).ok())
+        .ok_or(ClientError::InvalidResponse)
+}
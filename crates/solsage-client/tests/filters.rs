@@ -0,0 +1,90 @@
+use solana_program::pubkey::Pubkey;
+use solsage_client::{
+    active_knowledge_entries_by_category, knowledge_entries_by_staker, solsage::VestingBucket, KnowledgeEntry,
+    LicenseType, LockupTier, ProgramAccount, RpcFilter, Visibility, KNOWLEDGE_ENTRY_SCHEMA_VERSION,
+};
+
+fn entry_data(category: Pubkey, is_active: bool) -> Vec<u8> {
+    let entry = KnowledgeEntry {
+        is_initialized: true,
+        schema_version: KNOWLEDGE_ENTRY_SCHEMA_VERSION,
+        staker: Pubkey::new_unique(),
+        creator: Pubkey::new_unique(),
+        entry_index: 0,
+        content_hash: [0; 32],
+        category,
+        is_active,
+        license: LicenseType::Custom { uri: "https://example.com/terms".to_string() },
+        title: "A title long enough to move anything stored after it".to_string(),
+        content_uri: Some("ar://content".to_string()),
+        collection: None,
+        royalty_splits: Vec::new(),
+        created_at: 0,
+        updated_at: 0,
+        expires_at: 0,
+        last_attribution_at: 0,
+        version: 1,
+        total_attributions: 0,
+        pending_rewards: 0,
+        vesting_schedule: [VestingBucket::default(); KnowledgeEntry::VESTING_BUCKETS],
+        stake_amount: 0,
+        bump: 0,
+        escrow_bump: 0,
+        tags: Vec::new(),
+        min_reward_per_use: 0,
+        lockup_tier: LockupTier::None,
+        locked_until: 0,
+        unstake_requested_at: 0,
+        receipt_asset_id: None,
+        quality_score: 0,
+        under_review: false,
+        reward_stream: Default::default(),
+        derived_from: Vec::new(),
+        citation_royalties: 0,
+        citation_rewards: 0,
+        document: None,
+        chunk_index: 0,
+        chunks: None,
+        embedding_fingerprint: None,
+        attestation: None,
+        verified: false,
+        identity_credential: None,
+        visibility: Visibility::Public,
+    };
+    let mut data = vec![0; KnowledgeEntry::LEN];
+    entry.pack_into(&mut data).unwrap();
+    data
+}
+
+fn matches(filters: &[RpcFilter], data: &[u8]) -> bool {
+    filters.iter().all(|filter| match filter {
+        RpcFilter::DataSize(len) => data.len() == *len,
+        RpcFilter::Memcmp { offset, bytes } => data.get(*offset..offset + bytes.len()) == Some(&bytes[..]),
+    })
+}
+
+#[test]
+fn category_filters_match_active_entries_whatever_their_strings() {
+    let category = Pubkey::new_unique();
+    let filters = active_knowledge_entries_by_category(&category);
+
+    assert!(matches(&filters, &entry_data(category, true)));
+    assert!(!matches(&filters, &entry_data(category, false)));
+    assert!(!matches(&filters, &entry_data(Pubkey::new_unique(), true)));
+
+    // Entries still on the old layout are left out
+    let mut unmigrated = entry_data(category, true);
+    unmigrated[1] = 1;
+    assert!(!matches(&filters, &unmigrated));
+}
+
+#[test]
+fn staker_filters_encode_as_base64_memcmps() {
+    let staker = Pubkey::new_unique();
+    let filters = knowledge_entries_by_staker(&staker);
+
+    let json = filters.last().unwrap().to_json();
+    assert_eq!(json["memcmp"]["offset"], KnowledgeEntry::STAKER_OFFSET);
+    assert_eq!(json["memcmp"]["encoding"], "base64");
+    assert_eq!(filters[0].to_json()["dataSize"], KnowledgeEntry::LEN);
+}
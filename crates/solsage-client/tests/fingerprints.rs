@@ -0,0 +1,103 @@
+use solana_program::pubkey::Pubkey;
+use solsage_client::{
+    embedding_fingerprint, fingerprint_distance, similar_entries, solsage::VestingBucket, KnowledgeEntry, LicenseType,
+    LockupTier, Visibility, EMBEDDING_FINGERPRINT_BITS, KNOWLEDGE_ENTRY_SCHEMA_VERSION,
+};
+
+fn entry(embedding_fingerprint: Option<[u8; KnowledgeEntry::EMBEDDING_FINGERPRINT_LEN]>) -> KnowledgeEntry {
+    KnowledgeEntry {
+        is_initialized: true,
+        schema_version: KNOWLEDGE_ENTRY_SCHEMA_VERSION,
+        staker: Pubkey::new_unique(),
+        creator: Pubkey::new_unique(),
+        entry_index: 0,
+        content_hash: [0; 32],
+        category: Pubkey::new_unique(),
+        is_active: true,
+        license: LicenseType::Mit,
+        title: "Async Rust".to_string(),
+        content_uri: None,
+        collection: None,
+        royalty_splits: Vec::new(),
+        created_at: 0,
+        updated_at: 0,
+        expires_at: 0,
+        last_attribution_at: 0,
+        version: 1,
+        total_attributions: 0,
+        pending_rewards: 0,
+        vesting_schedule: [VestingBucket::default(); KnowledgeEntry::VESTING_BUCKETS],
+        stake_amount: 0,
+        bump: 0,
+        escrow_bump: 0,
+        tags: Vec::new(),
+        min_reward_per_use: 0,
+        lockup_tier: LockupTier::None,
+        locked_until: 0,
+        unstake_requested_at: 0,
+        receipt_asset_id: None,
+        quality_score: 0,
+        under_review: false,
+        reward_stream: Default::default(),
+        derived_from: Vec::new(),
+        citation_royalties: 0,
+        citation_rewards: 0,
+        document: None,
+        chunk_index: 0,
+        chunks: None,
+        embedding_fingerprint,
+        attestation: None,
+        verified: false,
+        identity_credential: None,
+        visibility: Visibility::Public,
+    }
+}
+
+/// A 384-dimension embedding, varied enough to cross several hash blocks
+fn embedding(seed: f32) -> Vec<f32> {
+    (0..384).map(|index| ((index as f32 + 1.0) * seed).sin()).collect()
+}
+
+#[test]
+fn nearby_embeddings_share_most_fingerprint_bits() {
+    let base = embedding(0.7);
+    let nudged: Vec<f32> = base.iter().enumerate().map(|(index, value)| value + 0.05 * (index as f32).cos()).collect();
+    let unrelated = embedding(1.3);
+    let fingerprint = embedding_fingerprint(&base);
+
+    assert_eq!(embedding_fingerprint(&base), fingerprint);
+    // Scaling keeps the direction, so no bit changes
+    let scaled: Vec<f32> = base.iter().map(|value| value * 3.0).collect();
+    assert_eq!(embedding_fingerprint(&scaled), fingerprint);
+    let near = fingerprint_distance(&embedding_fingerprint(&nudged), &fingerprint);
+    let far = fingerprint_distance(&embedding_fingerprint(&unrelated), &fingerprint);
+    assert!(near < EMBEDDING_FINGERPRINT_BITS as u32 / 8, "near: {near}");
+    assert!(far > EMBEDDING_FINGERPRINT_BITS as u32 / 3, "far: {far}");
+}
+
+#[test]
+fn opposite_embeddings_differ_in_every_bit() {
+    let base = embedding(0.7);
+    let negated: Vec<f32> = base.iter().map(|value| -value).collect();
+    assert_eq!(
+        fingerprint_distance(&embedding_fingerprint(&base), &embedding_fingerprint(&negated)),
+        EMBEDDING_FINGERPRINT_BITS as u32
+    );
+}
+
+#[test]
+fn similar_entries_are_nearest_first_within_the_distance() {
+    let query = embedding_fingerprint(&embedding(0.7));
+    let mut one_off = query;
+    one_off[0] ^= 1;
+    let mut three_off = query;
+    three_off[5] ^= 0b111;
+    let entries: Vec<(Pubkey, KnowledgeEntry)> = [Some(three_off), None, Some(query), Some(one_off), Some([0; 64])]
+        .into_iter()
+        .map(|fingerprint| (Pubkey::new_unique(), entry(fingerprint)))
+        .collect();
+
+    let similar = similar_entries(&entries, &query, 3);
+    let found: Vec<_> = similar.iter().map(|(address, _, distance)| (**address, *distance)).collect();
+    assert_eq!(found, [(entries[2].0, 0), (entries[3].0, 1), (entries[0].0, 3)]);
+}
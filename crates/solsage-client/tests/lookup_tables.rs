@@ -0,0 +1,94 @@
+use solana_program::{address_lookup_table, pubkey::Pubkey};
+use solsage_client::{
+    create_protocol_lookup_table, entry_lookup_addresses, extend_protocol_lookup_table, find_protocol_address,
+    find_staker_profile_address, find_treasury_address, solsage::VestingBucket, KnowledgeEntry, LicenseType,
+    LockupTier, Visibility, KNOWLEDGE_ENTRY_SCHEMA_VERSION, LOOKUP_TABLE_EXTEND_CHUNK,
+};
+
+fn entry(collection: Option<Pubkey>) -> KnowledgeEntry {
+    KnowledgeEntry {
+        is_initialized: true,
+        schema_version: KNOWLEDGE_ENTRY_SCHEMA_VERSION,
+        staker: Pubkey::new_unique(),
+        creator: Pubkey::new_unique(),
+        entry_index: 0,
+        content_hash: [0; 32],
+        category: Pubkey::new_unique(),
+        is_active: true,
+        license: LicenseType::Mit,
+        title: "Async Rust".to_string(),
+        content_uri: None,
+        collection,
+        royalty_splits: Vec::new(),
+        created_at: 0,
+        updated_at: 0,
+        expires_at: 0,
+        last_attribution_at: 0,
+        version: 1,
+        total_attributions: 0,
+        pending_rewards: 0,
+        vesting_schedule: [VestingBucket::default(); KnowledgeEntry::VESTING_BUCKETS],
+        stake_amount: 0,
+        bump: 0,
+        escrow_bump: 0,
+        tags: Vec::new(),
+        min_reward_per_use: 0,
+        lockup_tier: LockupTier::None,
+        locked_until: 0,
+        unstake_requested_at: 0,
+        receipt_asset_id: None,
+        quality_score: 0,
+        under_review: false,
+        reward_stream: Default::default(),
+        derived_from: Vec::new(),
+        citation_royalties: 0,
+        citation_rewards: 0,
+        document: None,
+        chunk_index: 0,
+        chunks: None,
+        embedding_fingerprint: None,
+        attestation: None,
+        verified: false,
+        identity_credential: None,
+        visibility: Visibility::Public,
+    }
+}
+
+#[test]
+fn protocol_tables_are_created_and_seeded_in_one_transaction() {
+    let (program_id, authority, payer) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let (instructions, lookup_table) = create_protocol_lookup_table(&program_id, &authority, &payer, 42);
+    assert_eq!(lookup_table, address_lookup_table::instruction::derive_lookup_table_address(&authority, 42).0);
+    assert_eq!(instructions.len(), 2);
+    assert!(instructions.iter().all(|ix| ix.program_id == address_lookup_table::program::id()));
+    assert_eq!(instructions[1].accounts[0].pubkey, lookup_table);
+
+    // The extend instruction carries the protocol's own addresses
+    let data = &instructions[1].data;
+    for address in [find_protocol_address(&program_id).0, find_treasury_address(&program_id).0] {
+        assert!(data.windows(32).any(|window| window == address.as_ref()));
+    }
+}
+
+#[test]
+fn extensions_are_split_into_chunks_that_fit_a_transaction() {
+    let (lookup_table, authority, payer) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let addresses: Vec<_> = (0..LOOKUP_TABLE_EXTEND_CHUNK * 2 + 1).map(|_| Pubkey::new_unique()).collect();
+    let instructions = extend_protocol_lookup_table(&lookup_table, &authority, &payer, &addresses);
+    assert_eq!(instructions.len(), 3);
+    assert!(instructions.iter().all(|ix| ix.accounts[0].pubkey == lookup_table && ix.accounts[2].pubkey == payer));
+    assert!(extend_protocol_lookup_table(&lookup_table, &authority, &payer, &[]).is_empty());
+}
+
+#[test]
+fn entries_contribute_their_staker_profile_category_and_collection() {
+    let (program_id, knowledge_entry, collection) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let loose = entry(None);
+    assert_eq!(
+        entry_lookup_addresses(&program_id, &knowledge_entry, &loose),
+        [knowledge_entry, find_staker_profile_address(&program_id, &loose.staker).0, loose.category]
+    );
+
+    let collected = entry(Some(collection));
+    assert_eq!(entry_lookup_addresses(&program_id, &knowledge_entry, &collected).last(), Some(&collection));
+}
@@ -0,0 +1,42 @@
+use solana_program::{hash::hashv, pubkey::Pubkey};
+use solsage_client::{query_hash, salted_query_hash, QUERY_HASH_BUCKET_SECS, QUERY_HASH_DOMAIN};
+
+const NOON: i64 = 1_767_268_800;
+
+#[test]
+fn query_hashes_follow_the_documented_preimage() {
+    let consumer = Pubkey::new_unique();
+    let query = "how do I derive a PDA?";
+    let expected = hashv(&[
+        QUERY_HASH_DOMAIN,
+        &(query.len() as u32).to_le_bytes(),
+        query.as_bytes(),
+        consumer.as_ref(),
+        &(NOON / QUERY_HASH_BUCKET_SECS).to_le_bytes(),
+        &0u32.to_le_bytes(),
+    ])
+    .to_bytes();
+    assert_eq!(query_hash(query, &consumer, NOON), expected);
+}
+
+#[test]
+fn repeat_queries_share_a_hash_within_one_window() {
+    let consumer = Pubkey::new_unique();
+    let hash = query_hash("how do I derive a PDA?", &consumer, NOON);
+    assert_eq!(query_hash("  how do I   derive a PDA?\r\n", &consumer, NOON + QUERY_HASH_BUCKET_SECS - 1), hash);
+    assert_ne!(query_hash("how do I derive a PDA?", &consumer, NOON + QUERY_HASH_BUCKET_SECS), hash);
+    assert_ne!(query_hash("how do I derive a PDA?", &consumer, NOON - 1), hash);
+    // Before the epoch, buckets still round down
+    assert_ne!(query_hash("q", &consumer, -1), query_hash("q", &consumer, 0));
+}
+
+#[test]
+fn consumers_and_salts_separate_query_hashes() {
+    let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let hash = query_hash("how do I derive a PDA?", &alice, NOON);
+    assert_ne!(query_hash("how do I derive a PDA?", &bob, NOON), hash);
+    assert_eq!(salted_query_hash("how do I derive a PDA?", &alice, NOON, &[]), hash);
+    assert_ne!(salted_query_hash("how do I derive a PDA?", &alice, NOON, b"secret"), hash);
+    // Length prefixes keep text from sliding between fields
+    assert_ne!(salted_query_hash("ab", &alice, NOON, b"c"), salted_query_hash("a", &alice, NOON, b"bc"));
+}
@@ -0,0 +1,134 @@
+use std::cell::RefCell;
+
+use solana_program::{pubkey::Pubkey, system_program};
+use solana_sdk::{
+    compute_budget::{self, ComputeBudgetInstruction},
+    hash::Hash,
+    signature::Keypair,
+    signer::Signer,
+};
+use solsage_client::{
+    claim_all_rewards, claim_rewards, find_protocol_address, find_staker_profile_address, nonce_claim_transaction,
+    AttributionClaim, ClientError, TransactionBuilder, DEFAULT_COMPUTE_UNITS_PER_INSTRUCTION, MAX_COMPUTE_UNIT_LIMIT,
+};
+
+fn claim(program_id: &Pubkey, staker: &Pubkey) -> solana_program::instruction::Instruction {
+    claim_all_rewards(program_id, staker, staker, &Pubkey::new_unique(), &[])
+}
+
+#[test]
+fn compute_budget_instructions_come_before_the_builders_own() {
+    let (program_id, payer) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let instructions = TransactionBuilder::new(payer)
+        .instruction(claim(&program_id, &payer))
+        .instruction(claim(&program_id, &payer))
+        .priority_fee(5_000)
+        .build_instructions()
+        .unwrap();
+
+    assert_eq!(instructions.len(), 4);
+    assert!(instructions[..2].iter().all(|ix| ix.program_id == compute_budget::id()));
+    assert_eq!(
+        instructions[0].data,
+        ComputeBudgetInstruction::set_compute_unit_limit(2 * DEFAULT_COMPUTE_UNITS_PER_INSTRUCTION).data
+    );
+    assert_eq!(instructions[1].data, ComputeBudgetInstruction::set_compute_unit_price(5_000).data);
+    assert!(instructions[2..].iter().all(|ix| ix.program_id == program_id));
+}
+
+#[test]
+fn unpriced_transactions_only_set_a_clamped_limit() {
+    let payer = Pubkey::new_unique();
+    let instructions = TransactionBuilder::new(payer)
+        .instruction(claim(&Pubkey::new_unique(), &payer))
+        .compute_unit_limit(u32::MAX)
+        .build_instructions()
+        .unwrap();
+    assert_eq!(instructions.len(), 2);
+    assert_eq!(instructions[0].data, ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT).data);
+}
+
+#[test]
+fn fee_estimators_see_the_writable_accounts_and_can_raise_the_fee() {
+    let (program_id, payer) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let seen = RefCell::new(Vec::new());
+    let instructions = TransactionBuilder::new(payer)
+        .instruction(claim(&program_id, &payer))
+        .priority_fee(100)
+        .fee_estimator(|accounts| {
+            seen.replace(accounts.to_vec());
+            Ok(7_500)
+        })
+        .build_instructions()
+        .unwrap();
+
+    assert_eq!(instructions[1].data, ComputeBudgetInstruction::set_compute_unit_price(7_500).data);
+    let seen = seen.into_inner();
+    assert_eq!(seen[0], payer);
+    assert!(seen.contains(&find_staker_profile_address(&program_id, &payer).0));
+    // The protocol is only read by a claim
+    assert!(!seen.contains(&find_protocol_address(&program_id).0));
+}
+
+#[test]
+fn nonce_transactions_advance_the_nonce_first_and_sign_offline() {
+    let (payer, nonce_authority, nonce_account) = (Keypair::new(), Keypair::new(), Pubkey::new_unique());
+    let nonce = Hash::new_unique();
+    let builder = TransactionBuilder::new(payer.pubkey())
+        .instruction(claim(&Pubkey::new_unique(), &payer.pubkey()))
+        .durable_nonce(nonce_account, nonce_authority.pubkey());
+
+    let mut transaction = builder.build(nonce).unwrap();
+    let message = &transaction.message;
+    let advance = &message.instructions[0];
+    assert_eq!(message.account_keys[advance.program_id_index as usize], system_program::id());
+    assert_eq!(message.account_keys[advance.accounts[0] as usize], nonce_account);
+    assert_eq!(message.recent_blockhash, nonce);
+
+    transaction.sign(&[&payer, &nonce_authority], nonce);
+    assert!(transaction.verify().is_ok());
+}
+
+#[test]
+fn cold_wallets_sign_nonce_claims_alone_and_offline() {
+    let (program_id, staker, nonce_account) = (Pubkey::new_unique(), Keypair::new(), Pubkey::new_unique());
+    let knowledge_entry = Pubkey::new_unique();
+    let attributions = [AttributionClaim { attribution: Pubkey::new_unique(), epoch: None }];
+    let claim = claim_rewards(
+        &program_id,
+        &staker.pubkey(),
+        &staker.pubkey(),
+        &knowledge_entry,
+        &Pubkey::new_unique(),
+        &[],
+        &attributions,
+    );
+
+    // Built online against the nonce's stored blockhash...
+    let nonce = Hash::new_unique();
+    let mut transaction = nonce_claim_transaction(&staker.pubkey(), claim.clone(), &nonce_account, nonce, 1_000).unwrap();
+    assert_eq!(transaction.message.header.num_required_signatures, 1);
+    assert_eq!(transaction.message.account_keys[0], staker.pubkey());
+    let last = transaction.message.instructions.last().unwrap();
+    assert_eq!(last.data, claim.data);
+
+    // ...then signed by the cold wallet without a fresh blockhash
+    transaction.sign(&[&staker], transaction.message.recent_blockhash);
+    assert_eq!(transaction.message.recent_blockhash, nonce);
+    assert!(transaction.verify().is_ok());
+}
+
+#[test]
+fn only_claims_the_staker_signs_alone_go_through_the_nonce_flow() {
+    let (program_id, staker, delegate) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let (nonce_account, nonce) = (Pubkey::new_unique(), Hash::new_unique());
+    let delegated = claim_rewards(&program_id, &delegate, &staker, &Pubkey::new_unique(), &Pubkey::new_unique(), &[], &[]);
+    assert!(matches!(
+        nonce_claim_transaction(&staker, delegated, &nonce_account, nonce, 0),
+        Err(ClientError::NotAClaim)
+    ));
+    assert!(matches!(
+        nonce_claim_transaction(&staker, claim(&program_id, &staker), &nonce_account, nonce, 0),
+        Err(ClientError::NotAClaim)
+    ));
+}
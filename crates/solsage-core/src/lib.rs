@@ -0,0 +1,1051 @@
+//! Framework-independent pieces of SolSage shared by the native program and
+//! the Anchor program: PDA seeds, protocol limits, reward math, Merkle
+//! proofs, attribution receipts, bridged messages, entry cNFTs, SAGE token
+//! metadata, multisig signer addresses, the canonical content hash and the
+//! metered syscalls the rest derive addresses and hashes with. Both
+//! builds take these from here so their addresses, bounds and payouts
+//! cannot drift.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+// ============================================================================
+// SEEDS
+// ============================================================================
+
+pub mod seeds {
+    pub const PROTOCOL: &[u8] = b"protocol";
+    pub const TREASURY: &[u8] = b"treasury";
+    pub const MINT: &[u8] = b"sage_mint";
+    pub const MINT_AUTHORITY: &[u8] = b"mint_authority";
+    /// Delegate of the protocol's receipt tree, signing cNFT receipt mints
+    pub const RECEIPT_AUTHORITY: &[u8] = b"receipt_authority";
+    /// `[KNOWLEDGE, creator, entry_index (le)]`
+    pub const KNOWLEDGE: &[u8] = b"knowledge";
+    /// `[ESCROW, knowledge_entry]`
+    pub const ESCROW: &[u8] = b"escrow";
+    /// `[LISTING, knowledge_entry]`
+    pub const LISTING: &[u8] = b"listing";
+    /// `[ENTRY_VOTE, knowledge_entry, voter]`
+    pub const ENTRY_VOTE: &[u8] = b"entry_vote";
+    /// `[REPORT_QUEUE, knowledge_entry]`
+    pub const REPORT_QUEUE: &[u8] = b"report_queue";
+    /// `[BOUNTY, requester, query_hash]`
+    pub const BOUNTY: &[u8] = b"bounty";
+    /// `[BOUNTY_VAULT, bounty]`
+    pub const BOUNTY_VAULT: &[u8] = b"bounty_vault";
+    /// `[BOUNTY_ANSWER, bounty, knowledge_entry]`
+    pub const BOUNTY_ANSWER: &[u8] = b"bounty_answer";
+    /// `[ATTRIBUTION, query_hash, knowledge_entry]`
+    pub const ATTRIBUTION: &[u8] = b"attribution";
+    /// `[QUERY, query_hash]`
+    pub const QUERY: &[u8] = b"query";
+    /// `[BRIDGE_EMITTER, emitter_chain (le), emitter_address]`
+    pub const BRIDGE_EMITTER: &[u8] = b"bridge_emitter";
+    /// `[ORACLE, oracle]`
+    pub const ORACLE: &[u8] = b"oracle";
+    /// `[ORACLE_STATE, oracle]`
+    pub const ORACLE_STATE: &[u8] = b"oracle_state";
+    pub const REVIEWER_REGISTRY: &[u8] = b"reviewer_registry";
+    /// `[ACCESS_PASS, knowledge_entry, holder]`
+    pub const ACCESS_PASS: &[u8] = b"access_pass";
+    /// `[PAYER_STATS, payer]`
+    pub const PAYER_STATS: &[u8] = b"payer_stats";
+    /// `[SNAPSHOT, day]`, `day` counted in `SNAPSHOT_INTERVAL_SECS` since the
+    /// Unix epoch
+    pub const SNAPSHOT: &[u8] = b"snapshot";
+    /// `[PROFILE, staker]`
+    pub const PROFILE: &[u8] = b"profile";
+    /// `[CHALLENGE, attribution]`
+    pub const CHALLENGE: &[u8] = b"challenge";
+    /// `[CONTENT, content_hash]`
+    pub const CONTENT: &[u8] = b"content";
+    /// `[CATEGORY, name]`
+    pub const CATEGORY: &[u8] = b"category";
+    /// `[ATTRIBUTION_ROOT, oracle, epoch (le)]`
+    pub const ATTRIBUTION_ROOT: &[u8] = b"attribution_root";
+    /// `[EPOCH, index (le)]`
+    pub const EPOCH: &[u8] = b"epoch";
+    /// `[CURATOR, curator]`
+    pub const CURATOR: &[u8] = b"curator";
+    /// `[COLLECTION, owner, name]`
+    pub const COLLECTION: &[u8] = b"collection";
+    /// `[DOCUMENT, owner, content_hash]`
+    pub const DOCUMENT: &[u8] = b"document";
+    /// `[TAG, tag]`
+    pub const TAG: &[u8] = b"tag";
+    pub const REWARD_VAULT: &[u8] = b"reward_vault";
+    /// `[SUBSCRIPTION, consumer]`
+    pub const SUBSCRIPTION: &[u8] = b"subscription";
+    /// `[AGENT, agent_id]`
+    pub const AGENT: &[u8] = b"agent";
+    pub const LEADERBOARD: &[u8] = b"leaderboard";
+    /// `[PROPOSAL, index (le)]`
+    pub const PROPOSAL: &[u8] = b"proposal";
+    /// `[VOTE, proposal, voter]`
+    pub const VOTE: &[u8] = b"vote";
+    pub const GOVERNANCE_VAULT: &[u8] = b"governance_vault";
+    /// `[STAKER_INDEX, staker]`
+    pub const STAKER_INDEX: &[u8] = b"staker_index";
+}
+
+// ============================================================================
+// LIMITS
+// ============================================================================
+
+/// Layout version stamped after every account's discriminator.
+/// Fields appended to the end of a layout read as zeros from accounts
+/// allocated before them; bump it, or the account's own version below, with
+/// a migration, for any other change.
+pub const SCHEMA_VERSION: u8 = 1;
+/// Layout version of knowledge entries. Version 2 moved `category` and
+/// `is_active` ahead of the strings, to fixed offsets entries can be
+/// filtered at.
+pub const KNOWLEDGE_ENTRY_SCHEMA_VERSION: u8 = 2;
+/// Layout version of leaderboards. Version 2 keeps the entries in fixed
+/// slots behind their count, so the board is ranked in place.
+pub const LEADERBOARD_SCHEMA_VERSION: u8 = 2;
+pub const MAX_BPS: u16 = 10_000;
+pub const MAX_FEE_BPS: u16 = 1_000;
+pub const MINT_DECIMALS: u8 = 6;
+pub const MIN_REWARD_PER_ATTRIBUTION: u64 = 1_000; // 0.001 SAGE
+pub const MAX_REWARD_PER_ATTRIBUTION: u64 = 100_000_000; // 100 SAGE
+pub const DEFAULT_VESTING_PERIOD_SECS: i64 = 7 * 24 * 60 * 60;
+pub const MAX_VESTING_PERIOD_SECS: i64 = 365 * 24 * 60 * 60;
+pub const MAX_ATTRIBUTION_COOLDOWN_SECS: i64 = 24 * 60 * 60;
+/// String limits count UTF-8 bytes, which is what account space is sized in,
+/// so a title of multi-byte characters holds fewer than 100 of them
+pub const MAX_TITLE_LEN: usize = 100;
+pub const MAX_RELEVANCE_SCORE: u8 = 100;
+pub const UNSTAKE_COOLDOWN_SECS: i64 = 7 * 24 * 60 * 60;
+pub const VESTING_BUCKETS: usize = 8;
+pub const TERM_SECS: i64 = 180 * 24 * 60 * 60;
+pub const RENEWAL_FEE_LAMPORTS: u64 = 10_000_000; // 0.01 SOL
+pub const MIN_CHALLENGE_BOND: u64 = 100_000_000; // 0.1 SOL
+pub const MIN_REPORT_BOND: u64 = 50_000_000; // 0.05 SOL
+/// Independent spam reports that deactivate an entry until the authority
+/// reviews them
+pub const SPAM_REPORT_THRESHOLD: usize = 3;
+pub const MAX_BOUNTY_DURATION_SECS: i64 = 90 * 24 * 60 * 60;
+/// Category names seed the PDA, so they are capped at the 32-byte seed limit
+pub const MAX_CATEGORY_NAME_LEN: usize = 32;
+pub const DEFAULT_MULTIPLIER_BPS: u16 = 10_000;
+pub const MAX_MULTIPLIER_BPS: u16 = 50_000; // 5x
+pub const MAX_QUERY_ATTRIBUTIONS: u8 = 10;
+/// The base rate is paid in full at a relevance score of `score_divisor`, so
+/// the default pays a full score of 100 exactly once
+pub const DEFAULT_SCORE_DIVISOR: u16 = 100;
+/// Pays 10x the base rate at full score, the original formula's maximum
+pub const MIN_SCORE_DIVISOR: u16 = 10;
+pub const MAX_SCORE_DIVISOR: u16 = 10_000;
+/// Leaves one attribution root can hold; each claim is tracked by one bit
+pub const MAX_ROOT_ATTRIBUTIONS: usize = 1_024;
+pub const MIN_EPOCH_DURATION_SECS: i64 = 60 * 60;
+pub const MAX_EPOCH_DURATION_SECS: i64 = 90 * 24 * 60 * 60;
+/// Curators can take at most half of an attribution's reward
+pub const MAX_CURATOR_BPS: u16 = 5_000;
+/// Collection names seed the PDA alongside the owner, so they share the
+/// category name limit
+pub const MAX_COLLECTION_NAME_LEN: usize = 32;
+/// Co-authors sharing an entry's rewards alongside its owner
+pub const MAX_CO_AUTHORS: usize = 4;
+/// Entries a derivative entry can cite as its parents
+pub const MAX_PARENT_ENTRIES: usize = 5;
+/// Parents can take at most half of a derivative's reward
+pub const MAX_CITATION_ROYALTY_BPS: u16 = 5_000;
+/// Chunk entries one document can be split into
+pub const MAX_DOCUMENT_CHUNKS: usize = 32;
+/// Sibling hashes in a proof that a chunk is in an entry's committed chunk
+/// root, so an entry can commit to up to `1 << MAX_CHUNK_PROOF_LEN` chunks
+pub const MAX_CHUNK_PROOF_LEN: usize = 16;
+/// Bytes in an entry's embedding fingerprint: 512 locality-sensitive bits
+pub const EMBEDDING_FINGERPRINT_LEN: usize = 64;
+/// Reviewers the authority can approve to attest entries
+pub const MAX_REVIEWERS: usize = 32;
+pub const MAX_ATTESTATION_SCORE: u8 = 100;
+/// Lowest accuracy and freshness an attestation can score and still verify
+/// the entry
+pub const MIN_VERIFIED_SCORE: u8 = 70;
+/// Multiplier on a verified entry's rewards, on top of its quality votes
+pub const VERIFIED_BOOST_BPS: u16 = 12_000;
+pub const MAX_LICENSE_URI_LEN: usize = 128;
+/// Long enough for Arweave and IPFS URIs with a path
+pub const MAX_CONTENT_URI_LEN: usize = 200;
+pub const MAX_TAGS: usize = 10;
+/// Tags seed their index PDA, so they share the category name limit
+pub const MAX_TAG_LEN: usize = 32;
+/// Entries ranked on the leaderboard
+pub const LEADERBOARD_SIZE: usize = 32;
+pub const MIN_VOTING_PERIOD_SECS: i64 = 60 * 60;
+pub const MAX_VOTING_PERIOD_SECS: i64 = 30 * 24 * 60 * 60;
+/// Longest a passed proposal can be made to wait before it executes
+pub const MAX_EXECUTION_DELAY_SECS: i64 = 30 * 24 * 60 * 60;
+/// Longest notice the authority can be made to give of a parameter change
+pub const MAX_ADMIN_DELAY_SECS: i64 = 30 * 24 * 60 * 60;
+pub const MIN_SCORE_HALF_LIFE_SECS: i64 = 24 * 60 * 60;
+pub const MAX_SCORE_HALF_LIFE_SECS: i64 = 10 * 365 * 24 * 60 * 60;
+/// Shortest time vested rewards are left to their staker before anyone can
+/// expire them
+pub const MIN_CLAIM_WINDOW_SECS: i64 = 30 * 24 * 60 * 60;
+/// Share of expired rewards paid to whoever expires them
+pub const EXPIRY_BOUNTY_BPS: u16 = 100;
+/// One protocol snapshot can be taken per interval
+pub const SNAPSHOT_INTERVAL_SECS: i64 = 24 * 60 * 60;
+/// Oracle attribution budgets refill once per epoch of this length
+pub const ORACLE_BUDGET_EPOCH_SECS: i64 = 24 * 60 * 60;
+/// Most matching oracle reports a consensus attribution can wait on
+pub const MAX_CONSENSUS_ORACLES: usize = 5;
+/// Longest an attribution may take oracle scores before it is finalized
+pub const MAX_AGGREGATION_WINDOW_SECS: i64 = 7 * 24 * 60 * 60;
+/// Per-payer attribution caps reset once per day of this length
+pub const PAYER_CAP_DAY_SECS: i64 = 24 * 60 * 60;
+/// Net SAGE voted on an entry that moves its reward multiplier by one basis
+/// point
+pub const QUALITY_SCORE_PER_BPS: u64 = 100_000; // 0.1 SAGE
+/// Bounds of the reward multiplier an entry's quality votes can set
+pub const MIN_QUALITY_BPS: u16 = 5_000;
+pub const MAX_QUALITY_BPS: u16 = 20_000;
+/// Bounds of the period streamed rewards are spread over
+pub const MIN_REWARD_STREAM_SECS: i64 = 60 * 60;
+pub const MAX_REWARD_STREAM_SECS: i64 = 365 * 24 * 60 * 60;
+/// Fixed-point scale of streamed reward rates, so streams paying less than
+/// one base unit a second still accrue
+pub const REWARD_STREAM_RATE_SCALE: u128 = 1_000_000_000_000;
+
+// ============================================================================
+// REWARD MATH
+// ============================================================================
+
+/// How an attribution's relevance score turns into a reward
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RewardCurve {
+    /// `rate * score / divisor`
+    Linear,
+    /// `rate * score^2 / (divisor * 100)`: same maximum, low scores earn far
+    /// less
+    Quadratic,
+    /// Linear, divided by one more for every `DECAY_STEP` prior attributions
+    DecayByAttributionCount,
+}
+
+impl RewardCurve {
+    pub const DECAY_STEP: u64 = 10;
+
+    /// Reward for one attribution, or `None` on overflow or a zero divisor
+    pub fn reward(
+        &self,
+        rate: u64,
+        relevance_score: u8,
+        prior_attributions: u64,
+        score_divisor: u16,
+    ) -> Option<u64> {
+        let score = relevance_score as u64;
+        let divisor = score_divisor as u64;
+        match self {
+            RewardCurve::Linear => rate.checked_mul(score)?.checked_div(divisor),
+            RewardCurve::Quadratic => rate
+                .checked_mul(score)?
+                .checked_mul(score)?
+                .checked_div(divisor * MAX_RELEVANCE_SCORE as u64),
+            RewardCurve::DecayByAttributionCount => rate
+                .checked_mul(score)?
+                .checked_div(divisor)?
+                .checked_div(1 + prior_attributions / Self::DECAY_STEP),
+        }
+    }
+}
+
+/// How long a stake stays locked past the unstake cooldown, for a boost on
+/// every reward its entry earns
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LockupTier {
+    #[default]
+    None,
+    Days30,
+    Days90,
+    Days365,
+}
+
+impl LockupTier {
+    pub const LEN: usize = 1;
+
+    /// Time from staking until the stake can be withdrawn
+    pub fn duration_secs(&self) -> i64 {
+        const DAY_SECS: i64 = 24 * 60 * 60;
+        match self {
+            LockupTier::None => 0,
+            LockupTier::Days30 => 30 * DAY_SECS,
+            LockupTier::Days90 => 90 * DAY_SECS,
+            LockupTier::Days365 => 365 * DAY_SECS,
+        }
+    }
+
+    /// Multiplier on attribution rewards, in basis points
+    pub fn boost_bps(&self) -> u16 {
+        match self {
+            LockupTier::None => MAX_BPS,
+            LockupTier::Days30 => 11_000,
+            LockupTier::Days90 => 12_500,
+            LockupTier::Days365 => 15_000,
+        }
+    }
+
+    /// Scale `reward` by the tier's boost
+    pub fn apply_boost(&self, reward: u64) -> Option<u64> {
+        apply_bps(reward, self.boost_bps())
+    }
+}
+
+/// Share of an epoch's `budget` earned by `weight` out of the epoch's
+/// `total_weight`, or `None` if the epoch carries no weight
+pub fn epoch_share(budget: u64, weight: u64, total_weight: u64) -> Option<u64> {
+    let share = (budget as u128 * weight as u128).checked_div(total_weight as u128)?;
+    u64::try_from(share).ok()
+}
+
+/// `bps` basis points of `amount`, or `None` if the result overflows
+pub fn apply_bps(amount: u64, bps: u16) -> Option<u64> {
+    u64::try_from((amount as u128) * (bps as u128) / (MAX_BPS as u128)).ok()
+}
+
+/// Weight, in basis points, of an attribution to an entry `age_secs` old,
+/// halving every `half_life_secs` and never below `floor_bps`. Between
+/// whole half-lives the weight falls linearly, which keeps the math in
+/// integers and is exact at every half-life. A half-life of 0 turns decay
+/// off.
+pub fn score_decay_bps(age_secs: i64, half_life_secs: i64, floor_bps: u16) -> u16 {
+    if half_life_secs <= 0 || age_secs <= 0 {
+        return MAX_BPS;
+    }
+    let halvings = age_secs / half_life_secs;
+    // `MAX_BPS` halves to 0 within 14 half-lives
+    if halvings >= 14 {
+        return floor_bps.min(MAX_BPS);
+    }
+    let start = MAX_BPS as u64 >> halvings;
+    let elapsed = (age_secs % half_life_secs) as u64;
+    let weight = start - (start / 2) * elapsed / half_life_secs as u64;
+    (weight as u16).max(floor_bps).min(MAX_BPS)
+}
+
+/// `reward` held to an entry's price `floor`: rounded up to it when
+/// `round_up`, or `None` if it falls below it otherwise
+pub fn apply_price_floor(reward: u64, floor: u64, round_up: bool) -> Option<u64> {
+    if reward >= floor {
+        Some(reward)
+    } else if round_up {
+        Some(floor)
+    } else {
+        None
+    }
+}
+
+/// Multiplier on an entry's rewards, in basis points, for a net
+/// `quality_score` of SAGE voted up, less SAGE voted down
+pub fn quality_multiplier_bps(quality_score: i64) -> u16 {
+    let shift = quality_score / QUALITY_SCORE_PER_BPS as i64;
+    (MAX_BPS as i64 + shift).clamp(MIN_QUALITY_BPS as i64, MAX_QUALITY_BPS as i64) as u16
+}
+
+/// Per-second rate, scaled by `REWARD_STREAM_RATE_SCALE`, streaming `amount`
+/// over `duration_secs`, or `None` for a period that isn't positive
+pub fn reward_stream_rate(amount: u64, duration_secs: i64) -> Option<u128> {
+    let duration = u128::try_from(duration_secs).ok().filter(|duration| *duration > 0)?;
+    Some(amount as u128 * REWARD_STREAM_RATE_SCALE / duration)
+}
+
+/// Rewards a stream paying `rate_per_sec`, scaled by
+/// `REWARD_STREAM_RATE_SCALE`, accrues in `elapsed_secs`, or `None` if they
+/// overflow
+pub fn streamed_rewards(rate_per_sec: u128, elapsed_secs: i64) -> Option<u64> {
+    let elapsed = u128::try_from(elapsed_secs.max(0)).ok()?;
+    u64::try_from(rate_per_sec.checked_mul(elapsed)? / REWARD_STREAM_RATE_SCALE).ok()
+}
+
+/// Whether a proposal passes: at least `quorum_votes` SAGE voted, and more
+/// than `approval_threshold_bps` of it in favour
+pub fn proposal_passes(votes_for: u64, votes_against: u64, quorum_votes: u64, approval_threshold_bps: u16) -> bool {
+    let total = votes_for as u128 + votes_against as u128;
+    total > 0
+        && total >= quorum_votes as u128
+        && votes_for as u128 * MAX_BPS as u128 > total * approval_threshold_bps as u128
+}
+
+// ============================================================================
+// MERKLE PROOFS
+// ============================================================================
+
+/// Keccak Merkle trees over attribution and chunk leaves. Leaves and inner nodes are
+/// hashed under different prefixes so a node can never pass as a leaf, and
+/// sibling pairs are sorted so proofs carry no left/right flags.
+pub mod merkle {
+    use super::syscalls::keccak256;
+    use solana_program::pubkey::Pubkey;
+
+    const LEAF_PREFIX: &[u8] = &[0];
+    const NODE_PREFIX: &[u8] = &[1];
+
+    /// Leaf for the attribution claimed through slot `index` of a root
+    pub fn attribution_leaf(
+        index: u32,
+        knowledge_entry: &Pubkey,
+        query_hash: &[u8; 32],
+        relevance_score: u8,
+    ) -> [u8; 32] {
+        keccak256(&[
+            LEAF_PREFIX,
+            &index.to_le_bytes(),
+            knowledge_entry.as_ref(),
+            query_hash,
+            &[relevance_score],
+        ])
+        .to_bytes()
+    }
+
+    /// Leaf for chunk `index` of an entry's content, hashed to `chunk_hash`
+    /// with `hashing::content_hash`
+    pub fn chunk_leaf(index: u32, chunk_hash: &[u8; 32]) -> [u8; 32] {
+        keccak256(&[LEAF_PREFIX, &index.to_le_bytes(), chunk_hash]).to_bytes()
+    }
+
+    fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+        let (low, high) = if a <= b { (a, b) } else { (b, a) };
+        keccak256(&[NODE_PREFIX, low, high]).to_bytes()
+    }
+
+    /// Whether `proof` leads from `leaf` up to `root`
+    pub fn verify(root: &[u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+        proof.iter().fold(leaf, |node, sibling| hash_pair(&node, sibling)) == *root
+    }
+
+    /// Root of the tree over `leaves`. A node left without a sibling moves up
+    /// a level unchanged.
+    pub fn root(leaves: &[[u8; 32]]) -> [u8; 32] {
+        let mut level = leaves.to_vec();
+        while level.len() > 1 {
+            level = next_level(&level);
+        }
+        level.first().copied().unwrap_or_default()
+    }
+
+    /// Proof that `leaves[index]` is in `root(leaves)`
+    pub fn proof(leaves: &[[u8; 32]], mut index: usize) -> Vec<[u8; 32]> {
+        let mut proof = Vec::new();
+        let mut level = leaves.to_vec();
+        while level.len() > 1 {
+            if let Some(sibling) = level.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            level = next_level(&level);
+            index /= 2;
+        }
+        proof
+    }
+
+    fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+        level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => hash_pair(a, b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+}
+
+// ============================================================================
+// ATTRIBUTION RECEIPTS
+// ============================================================================
+
+/// Attributions an oracle signs off-chain for any relayer to submit. The
+/// relayer puts an Ed25519 program instruction checking the oracle's
+/// signature over the receipt just before the attribution, which reads it
+/// back through the instructions sysvar.
+pub mod receipts {
+    use solana_program::{ed25519_program, instruction::Instruction, pubkey::Pubkey};
+
+    pub const RECEIPT_LEN: usize = 32 + 32 + 1 + 8;
+    const PUBKEY_LEN: usize = 32;
+    const SIGNATURE_LEN: usize = 64;
+    /// One signature count, one padding byte and one offsets entry
+    const HEADER_LEN: usize = 2 + 14;
+    /// Instruction index the Ed25519 program reads as its own instruction
+    const THIS_INSTRUCTION: u16 = u16::MAX;
+
+    /// Bytes an oracle signs to attribute `query_hash` to `knowledge_entry`
+    /// at `relevance_score`; `nonce` tells apart receipts it reissues
+    pub fn attribution_receipt(
+        query_hash: &[u8; 32],
+        knowledge_entry: &Pubkey,
+        relevance_score: u8,
+        nonce: u64,
+    ) -> [u8; RECEIPT_LEN] {
+        let mut receipt = [0; RECEIPT_LEN];
+        receipt[..32].copy_from_slice(query_hash);
+        receipt[32..64].copy_from_slice(knowledge_entry.as_ref());
+        receipt[64] = relevance_score;
+        receipt[65..].copy_from_slice(&nonce.to_le_bytes());
+        receipt
+    }
+
+    /// Ed25519 program instruction checking `signature` by `signer` over
+    /// `message`, all carried in its own data
+    pub fn verify_signature(signer: &Pubkey, signature: &[u8; 64], message: &[u8]) -> Instruction {
+        let public_key_offset = HEADER_LEN;
+        let signature_offset = public_key_offset + PUBKEY_LEN;
+        let message_offset = signature_offset + SIGNATURE_LEN;
+        let offsets = [
+            signature_offset as u16,
+            THIS_INSTRUCTION,
+            public_key_offset as u16,
+            THIS_INSTRUCTION,
+            message_offset as u16,
+            message.len() as u16,
+            THIS_INSTRUCTION,
+        ];
+
+        let mut data = vec![1, 0];
+        data.extend(offsets.iter().flat_map(|offset| offset.to_le_bytes()));
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(signature);
+        data.extend_from_slice(message);
+        Instruction { program_id: ed25519_program::id(), accounts: Vec::new(), data }
+    }
+
+    /// Signer and message of an Ed25519 program instruction checking one
+    /// signature held in its own data; `None` for any other instruction,
+    /// including one reading them from elsewhere in the transaction
+    pub fn signed_message(instruction: &Instruction) -> Option<(Pubkey, &[u8])> {
+        let data = &instruction.data;
+        if instruction.program_id != ed25519_program::id() || data.first() != Some(&1) {
+            return None;
+        }
+        let offset = |index: usize| {
+            let at = 2 + index * 2;
+            data.get(at..at + 2).map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        };
+        let [signature_index, public_key_offset, public_key_index, message_offset, message_len, message_index] =
+            [offset(1)?, offset(2)?, offset(3)?, offset(4)?, offset(5)?, offset(6)?];
+        if [signature_index, public_key_index, message_index] != [THIS_INSTRUCTION; 3] {
+            return None;
+        }
+
+        let public_key_offset = public_key_offset as usize;
+        let signer = data.get(public_key_offset..public_key_offset + PUBKEY_LEN)?;
+        let message_offset = message_offset as usize;
+        let message = data.get(message_offset..message_offset + message_len as usize)?;
+        Some((Pubkey::try_from(signer).ok()?, message))
+    }
+}
+
+// ============================================================================
+// BRIDGED ATTRIBUTIONS
+// ============================================================================
+
+/// Attributions made by agents on other chains, carried in messages a
+/// Wormhole-style bridge posts once its guardians have attested them. The
+/// posted account, owned by the bridge program, holds Wormhole's
+/// `PostedVAAData`: a `vaa` prefix, the message header and the payload.
+pub mod bridge {
+    pub const ATTRIBUTION_PAYLOAD_LEN: usize = 32 + 32 + 1;
+    const MAGIC: &[u8; 3] = b"vaa";
+    const SEQUENCE_OFFSET: usize = 49;
+    const EMITTER_CHAIN_OFFSET: usize = 57;
+    const EMITTER_ADDRESS_OFFSET: usize = 59;
+    const PAYLOAD_OFFSET: usize = 91;
+
+    /// A message the bridge posted from `emitter_address` on `emitter_chain`
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct PostedMessage<'a> {
+        pub emitter_chain: u16,
+        pub emitter_address: [u8; 32],
+        /// Per-emitter message number
+        pub sequence: u64,
+        pub payload: &'a [u8],
+    }
+
+    /// Payload an emitter publishes to attribute `query_hash` to the Solana
+    /// `knowledge_entry` at `relevance_score`
+    pub fn attribution_payload(
+        query_hash: &[u8; 32],
+        knowledge_entry: &[u8; 32],
+        relevance_score: u8,
+    ) -> [u8; ATTRIBUTION_PAYLOAD_LEN] {
+        let mut payload = [0; ATTRIBUTION_PAYLOAD_LEN];
+        payload[..32].copy_from_slice(query_hash);
+        payload[32..64].copy_from_slice(knowledge_entry);
+        payload[64] = relevance_score;
+        payload
+    }
+
+    /// Account data of `message` as the bridge posts it, signature account
+    /// and timestamps zeroed
+    pub fn posted_message_data(message: &PostedMessage) -> Vec<u8> {
+        let mut data = vec![0; PAYLOAD_OFFSET];
+        data[..MAGIC.len()].copy_from_slice(MAGIC);
+        data[SEQUENCE_OFFSET..EMITTER_CHAIN_OFFSET].copy_from_slice(&message.sequence.to_le_bytes());
+        data[EMITTER_CHAIN_OFFSET..EMITTER_ADDRESS_OFFSET].copy_from_slice(&message.emitter_chain.to_le_bytes());
+        data[EMITTER_ADDRESS_OFFSET..PAYLOAD_OFFSET].copy_from_slice(&message.emitter_address);
+        data.extend((message.payload.len() as u32).to_le_bytes());
+        data.extend_from_slice(message.payload);
+        data
+    }
+
+    /// Message held in a posted message account's `data`; `None` when it
+    /// isn't one
+    pub fn posted_message(data: &[u8]) -> Option<PostedMessage<'_>> {
+        if data.get(..MAGIC.len())? != MAGIC {
+            return None;
+        }
+        let payload_len = u32::from_le_bytes(data.get(PAYLOAD_OFFSET..PAYLOAD_OFFSET + 4)?.try_into().ok()?);
+        let payload_start = PAYLOAD_OFFSET + 4;
+        Some(PostedMessage {
+            emitter_chain: u16::from_le_bytes(data[EMITTER_CHAIN_OFFSET..EMITTER_ADDRESS_OFFSET].try_into().ok()?),
+            emitter_address: data[EMITTER_ADDRESS_OFFSET..PAYLOAD_OFFSET].try_into().ok()?,
+            sequence: u64::from_le_bytes(data[SEQUENCE_OFFSET..EMITTER_CHAIN_OFFSET].try_into().ok()?),
+            payload: data.get(payload_start..payload_start.checked_add(payload_len as usize)?)?,
+        })
+    }
+}
+
+// ============================================================================
+// STAKER IDENTITY
+// ============================================================================
+
+/// Verified-identity credentials stakers present when staking, issued
+/// through the Solana Attestation Service. An attestation account, owned by
+/// the service, holds a one-byte discriminator, the nonce it was issued for
+/// (here the staker's wallet), its credential, which names the issuer, and
+/// schema, the attested data, the issuer's signer, its expiry and the token
+/// account of a tokenized attestation.
+pub mod identity {
+    use super::syscalls::sha256;
+    use solana_program::pubkey::Pubkey;
+
+    const ATTESTATION_DISCRIMINATOR: u8 = 2;
+    /// Domain of `credential_hash`, so it can't collide with other hashes
+    /// the protocol stores
+    pub const CREDENTIAL_HASH_DOMAIN: &[u8] = b"solsage-credential-v1";
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct StakerAttestation<'a> {
+        /// Wallet the attestation was issued to
+        pub nonce: Pubkey,
+        pub credential: Pubkey,
+        pub schema: Pubkey,
+        pub data: &'a [u8],
+        pub signer: Pubkey,
+        /// Unix time the attestation lapses at; 0 never lapses
+        pub expiry: i64,
+    }
+
+    impl StakerAttestation<'_> {
+        pub fn is_expired(&self, now: i64) -> bool {
+            self.expiry != 0 && self.expiry <= now
+        }
+
+        /// What an entry records of the identity, the same for every entry
+        /// staked with the same attested data under the same credential
+        pub fn credential_hash(&self) -> [u8; 32] {
+            sha256(&[CREDENTIAL_HASH_DOMAIN, self.credential.as_ref(), self.schema.as_ref(), self.data]).to_bytes()
+        }
+    }
+
+    /// Account data of `attestation` as the service stores it, with no
+    /// token account
+    pub fn attestation_data(attestation: &StakerAttestation) -> Vec<u8> {
+        let mut data = vec![ATTESTATION_DISCRIMINATOR];
+        for key in [&attestation.nonce, &attestation.credential, &attestation.schema] {
+            data.extend_from_slice(key.as_ref());
+        }
+        data.extend((attestation.data.len() as u32).to_le_bytes());
+        data.extend_from_slice(attestation.data);
+        data.extend_from_slice(attestation.signer.as_ref());
+        data.extend(attestation.expiry.to_le_bytes());
+        data.extend_from_slice(Pubkey::default().as_ref());
+        data
+    }
+
+    /// Attestation held in an attestation account's `data`; `None` when it
+    /// isn't one
+    pub fn attestation(data: &[u8]) -> Option<StakerAttestation<'_>> {
+        if *data.first()? != ATTESTATION_DISCRIMINATOR {
+            return None;
+        }
+        let key = |at: usize| data.get(at..at + 32).map(|bytes| Pubkey::try_from(bytes).expect("32 bytes"));
+        let data_len = u32::from_le_bytes(data.get(97..101)?.try_into().ok()?) as usize;
+        let signer_at = 101usize.checked_add(data_len)?;
+        Some(StakerAttestation {
+            nonce: key(1)?,
+            credential: key(33)?,
+            schema: key(65)?,
+            data: data.get(101..signer_at)?,
+            signer: key(signer_at)?,
+            expiry: i64::from_le_bytes(data.get(signer_at + 32..signer_at + 40)?.try_into().ok()?),
+        })
+    }
+}
+
+// ============================================================================
+// ENTRY RECEIPTS
+// ============================================================================
+
+/// Compressed NFTs minted through Metaplex Bubblegum as wallet-visible,
+/// tradable receipts for knowledge entries. The protocol's receipt tree
+/// makes SolSage's receipt authority PDA its tree delegate, which signs each
+/// mint.
+pub mod bubblegum {
+    use super::syscalls;
+    use solana_program::{
+        instruction::{AccountMeta, Instruction},
+        pubkey,
+        pubkey::Pubkey,
+        system_program,
+    };
+
+    pub const PROGRAM_ID: Pubkey = pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+    pub const SPL_NOOP_PROGRAM_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+    pub const SPL_ACCOUNT_COMPRESSION_PROGRAM_ID: Pubkey = pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+    pub const MAX_NAME_LEN: usize = 32;
+    pub const SYMBOL: &str = "SAGE";
+    /// Anchor discriminator of `mint_v1`
+    const MINT_V1: [u8; 8] = [145, 98, 192, 118, 184, 147, 118, 104];
+    /// A tree config's discriminator, creator, delegate and mint capacity
+    /// come before its `num_minted`
+    const NUM_MINTED_OFFSET: usize = 8 + 32 + 32 + 8;
+
+    /// Accounts of a `mint_v1`
+    pub struct MintAccounts<'a> {
+        pub tree_config: &'a Pubkey,
+        pub leaf_owner: &'a Pubkey,
+        pub merkle_tree: &'a Pubkey,
+        pub payer: &'a Pubkey,
+        pub tree_delegate: &'a Pubkey,
+    }
+
+    pub fn tree_config_address(merkle_tree: &Pubkey) -> Pubkey {
+        syscalls::find_program_address(&[merkle_tree.as_ref()], &PROGRAM_ID).0
+    }
+
+    /// Asset ID of the `nonce`-th leaf minted into `merkle_tree`
+    pub fn asset_id(merkle_tree: &Pubkey, nonce: u64) -> Pubkey {
+        syscalls::find_program_address(&[b"asset", merkle_tree.as_ref(), &nonce.to_le_bytes()], &PROGRAM_ID).0
+    }
+
+    /// Leaves minted so far into the tree of the config holding `data`,
+    /// which numbers the next one
+    pub fn num_minted(data: &[u8]) -> Option<u64> {
+        Some(u64::from_le_bytes(data.get(NUM_MINTED_OFFSET..NUM_MINTED_OFFSET + 8)?.try_into().ok()?))
+    }
+
+    /// `name` cut to the longest prefix Bubblegum takes as a name
+    pub fn receipt_name(name: &str) -> &str {
+        let mut len = name.len().min(MAX_NAME_LEN);
+        while !name.is_char_boundary(len) {
+            len -= 1;
+        }
+        &name[..len]
+    }
+
+    /// `mint_v1` of an immutable receipt named `name`, pointing at `uri`,
+    /// held and delegated by the leaf owner
+    pub fn mint_v1(accounts: &MintAccounts, name: &str, uri: &str) -> Instruction {
+        let mut data = MINT_V1.to_vec();
+        for field in [receipt_name(name), SYMBOL, uri] {
+            data.extend((field.len() as u32).to_le_bytes());
+            data.extend_from_slice(field.as_bytes());
+        }
+        // No royalties, primary sale, immutable, no edition nonce, a
+        // non-fungible token standard, no collection or uses, the original
+        // token program and no creators
+        data.extend([0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*accounts.tree_config, false),
+                AccountMeta::new_readonly(*accounts.leaf_owner, false),
+                AccountMeta::new_readonly(*accounts.leaf_owner, false),
+                AccountMeta::new(*accounts.merkle_tree, false),
+                AccountMeta::new_readonly(*accounts.payer, true),
+                AccountMeta::new_readonly(*accounts.tree_delegate, true),
+                AccountMeta::new_readonly(SPL_NOOP_PROGRAM_ID, false),
+                AccountMeta::new_readonly(SPL_ACCOUNT_COMPRESSION_PROGRAM_ID, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            data,
+        }
+    }
+}
+
+// ============================================================================
+// TOKEN METADATA
+// ============================================================================
+
+/// Metaplex Token Metadata for the SAGE mint, which wallets read its name,
+/// symbol and logo from. The mint authority PDA is the metadata's update
+/// authority, so only the protocol can change it.
+pub mod token_metadata {
+    use super::syscalls;
+    use solana_program::{
+        instruction::{AccountMeta, Instruction},
+        pubkey,
+        pubkey::Pubkey,
+        system_program,
+    };
+
+    pub const PROGRAM_ID: Pubkey = pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+    pub const MAX_NAME_LEN: usize = 32;
+    pub const MAX_SYMBOL_LEN: usize = 10;
+    pub const MAX_URI_LEN: usize = 200;
+    const CREATE_METADATA_ACCOUNT_V3: u8 = 33;
+    const UPDATE_METADATA_ACCOUNT_V2: u8 = 15;
+
+    /// What wallets display for a token
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct TokenMetadata<'a> {
+        pub name: &'a str,
+        pub symbol: &'a str,
+        pub uri: &'a str,
+    }
+
+    impl TokenMetadata<'_> {
+        /// Whether every field fits in the metadata account
+        pub fn is_valid(&self) -> bool {
+            self.name.len() <= MAX_NAME_LEN && self.symbol.len() <= MAX_SYMBOL_LEN && self.uri.len() <= MAX_URI_LEN
+        }
+
+        /// `DataV2` of a fungible token with no royalties, creators,
+        /// collection or uses
+        fn data_v2(&self) -> Vec<u8> {
+            let mut data = Vec::new();
+            for field in [self.name, self.symbol, self.uri] {
+                data.extend((field.len() as u32).to_le_bytes());
+                data.extend_from_slice(field.as_bytes());
+            }
+            data.extend([0, 0, 0, 0, 0]);
+            data
+        }
+    }
+
+    pub fn metadata_address(mint: &Pubkey) -> Pubkey {
+        syscalls::find_program_address(&[b"metadata", PROGRAM_ID.as_ref(), mint.as_ref()], &PROGRAM_ID).0
+    }
+
+    /// `CreateMetadataAccountV3` of mutable `metadata` for `mint`, updatable
+    /// by its `mint_authority`
+    pub fn create_metadata_account_v3(
+        mint: &Pubkey,
+        mint_authority: &Pubkey,
+        payer: &Pubkey,
+        metadata: &TokenMetadata,
+    ) -> Instruction {
+        let mut data = vec![CREATE_METADATA_ACCOUNT_V3];
+        data.extend(metadata.data_v2());
+        // Mutable, not a collection
+        data.extend([1, 0]);
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(metadata_address(mint), false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new_readonly(*mint_authority, true),
+                AccountMeta::new(*payer, true),
+                AccountMeta::new_readonly(*mint_authority, true),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            data,
+        }
+    }
+
+    /// `UpdateMetadataAccountV2` replacing the metadata of `mint` with
+    /// `metadata`, leaving its update authority and mutability
+    pub fn update_metadata_account_v2(mint: &Pubkey, update_authority: &Pubkey, metadata: &TokenMetadata) -> Instruction {
+        let mut data = vec![UPDATE_METADATA_ACCOUNT_V2, 1];
+        data.extend(metadata.data_v2());
+        // Same update authority, primary sale and mutability
+        data.extend([0, 0, 0]);
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(metadata_address(mint), false),
+                AccountMeta::new_readonly(*update_authority, true),
+            ],
+            data,
+        }
+    }
+}
+
+// ============================================================================
+// MULTISIG AUTHORITIES
+// ============================================================================
+
+/// Addresses multisig programs sign with. A multisig authority is one of
+/// these PDAs, signing admin instructions through CPI once its members have
+/// approved them.
+pub mod multisig {
+    use super::syscalls;
+    use solana_program::{pubkey, pubkey::Pubkey};
+
+    /// The shared SPL Governance deployment; realms running their own
+    /// instance pass its program ID instead
+    pub const SPL_GOVERNANCE_PROGRAM_ID: Pubkey = pubkey!("GovER5Lthms3bLBqWub97yVrMmEogzX7xNjdXpPPCVZw");
+    /// Squads v4
+    pub const SQUADS_PROGRAM_ID: Pubkey = pubkey!("SQDS4ep65T869zMMBKyuUq6aD6EgTu8psMjkvj52pCf");
+
+    /// Native treasury a governance signs with once a proposal passes
+    pub fn governance_treasury(program_id: &Pubkey, governance: &Pubkey) -> Pubkey {
+        syscalls::find_program_address(&[b"native-treasury", governance.as_ref()], program_id).0
+    }
+
+    /// Vault a Squads multisig signs with once a vault transaction executes
+    pub fn squads_vault(multisig: &Pubkey, vault_index: u8) -> Pubkey {
+        syscalls::find_program_address(
+            &[b"multisig", multisig.as_ref(), b"vault", &[vault_index]],
+            &SQUADS_PROGRAM_ID,
+        )
+        .0
+    }
+}
+
+// ============================================================================
+// CONTENT HASHING
+// ============================================================================
+
+/// The canonical `content_hash` of a document: SHA-256 over its UTF-8 text
+/// after `canonicalize`. Agents that save, reflow or re-export the same
+/// markdown differently still arrive at the same hash, and a document
+/// already in canonical form hashes the same as its raw bytes.
+pub mod hashing {
+    use super::syscalls::sha256;
+
+    /// Revision of the scheme below; a change to `canonicalize` bumps it
+    pub const CONTENT_HASH_VERSION: u8 = 1;
+
+    /// Whitespace collapsed and trimmed outside code blocks. Spelled out
+    /// rather than `char::is_whitespace` so implementations in other
+    /// languages can match it exactly.
+    fn is_space(c: char) -> bool {
+        matches!(c, ' ' | '\t' | '\u{0B}' | '\u{0C}' | '\u{A0}')
+    }
+
+    /// Opening or closing marker of a fenced code block
+    fn fence(line: &str) -> Option<&'static str> {
+        let line = line.trim_start_matches(is_space);
+        ["```", "~~~"].into_iter().find(|marker| line.starts_with(marker))
+    }
+
+    /// Outside fenced code blocks, each line has its runs of whitespace
+    /// collapsed to one space and is trimmed, and `*` and `+` list bullets
+    /// become `-`. Inside them only trailing whitespace goes, so indentation
+    /// survives. Line endings become `\n`, a leading byte order mark is
+    /// dropped, runs of blank lines outside code blocks collapse to one and
+    /// there are no blank lines at either end or final newline.
+    pub fn canonicalize(content: &str) -> String {
+        let content = content.strip_prefix('\u{FEFF}').unwrap_or(content).replace("\r\n", "\n").replace('\r', "\n");
+        let mut lines: Vec<String> = Vec::new();
+        let mut open_fence: Option<&str> = None;
+        for line in content.split('\n') {
+            if let Some(marker) = open_fence {
+                if fence(line) == Some(marker) {
+                    lines.push(line.trim_matches(is_space).to_string());
+                    open_fence = None;
+                } else {
+                    lines.push(line.trim_end_matches(is_space).to_string());
+                }
+                continue;
+            }
+            let words: Vec<&str> = line
+                .split(is_space)
+                .filter(|word| !word.is_empty())
+                .enumerate()
+                .map(|(index, word)| if index == 0 && (word == "*" || word == "+") { "-" } else { word })
+                .collect();
+            if words.is_empty() && lines.last().is_none_or(String::is_empty) {
+                continue;
+            }
+            let line = words.join(" ");
+            open_fence = fence(&line);
+            lines.push(line);
+        }
+        while lines.last().is_some_and(String::is_empty) {
+            lines.pop();
+        }
+        lines.join("\n")
+    }
+
+    /// The hash `StakedContent::content_hash` should carry for `content`
+    pub fn content_hash(content: &str) -> [u8; 32] {
+        sha256(&[canonicalize(content).as_bytes()]).to_bytes()
+    }
+}
+
+// ============================================================================
+// SYSCALLS
+// ============================================================================
+
+/// PDA derivation and hashing as the programs make them. On chain each is a
+/// syscall the validator meters. Off chain `solana_program` computes them in
+/// process, so each also reports what the syscall would have cost, at the
+/// validator's default compute budget, to the meter a test runtime installs
+/// with `set_meter`.
+pub mod syscalls {
+    use solana_program::{
+        hash::{self, Hash},
+        keccak,
+        pubkey::{Pubkey, PubkeyError},
+    };
+
+    /// Each address `create_program_address` derives and each bump
+    /// `find_program_address` tries
+    pub const CREATE_PROGRAM_ADDRESS_UNITS: u64 = 1_500;
+    /// Per SHA-256 or Keccak hash, and per slice hashed
+    pub const HASH_BASE_COST: u64 = 85;
+    pub const MEM_OP_BASE_COST: u64 = 10;
+
+    #[cfg(not(target_os = "solana"))]
+    static METER: std::sync::OnceLock<fn(u64)> = std::sync::OnceLock::new();
+
+    /// Report the cost of every syscall made off chain to `meter`. Only the
+    /// first meter installed takes effect.
+    #[cfg(not(target_os = "solana"))]
+    pub fn set_meter(meter: fn(u64)) {
+        let _ = METER.set(meter);
+    }
+
+    #[allow(unused_variables)]
+    fn consume(units: u64) {
+        #[cfg(not(target_os = "solana"))]
+        if let Some(meter) = METER.get() {
+            meter(units);
+        }
+    }
+
+    fn hash_cost(vals: &[&[u8]]) -> u64 {
+        vals.iter().fold(HASH_BASE_COST, |cost, val| cost + MEM_OP_BASE_COST.max(val.len() as u64 / 2))
+    }
+
+    pub fn create_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> Result<Pubkey, PubkeyError> {
+        consume(CREATE_PROGRAM_ADDRESS_UNITS);
+        Pubkey::create_program_address(seeds, program_id)
+    }
+
+    /// Charged once per bump tried, from 255 down to the one found
+    pub fn find_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> (Pubkey, u8) {
+        let (address, bump) = Pubkey::find_program_address(seeds, program_id);
+        consume(CREATE_PROGRAM_ADDRESS_UNITS * (u64::from(u8::MAX - bump) + 1));
+        (address, bump)
+    }
+
+    pub fn sha256(vals: &[&[u8]]) -> Hash {
+        consume(hash_cost(vals));
+        hash::hashv(vals)
+    }
+
+    pub fn keccak256(vals: &[&[u8]]) -> keccak::Hash {
+        consume(hash_cost(vals));
+        keccak::hashv(vals)
+    }
+}
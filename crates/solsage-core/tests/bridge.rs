@@ -0,0 +1,34 @@
+use solsage_core::bridge::{self, PostedMessage};
+
+#[test]
+fn posted_messages_carry_their_emitter_sequence_and_payload() {
+    let payload = bridge::attribution_payload(&[3; 32], &[4; 32], 80);
+    let message = PostedMessage { emitter_chain: 2, emitter_address: [5; 32], sequence: 7, payload: &payload };
+    let data = bridge::posted_message_data(&message);
+    assert_eq!(bridge::posted_message(&data), Some(message));
+
+    // Every field of the attribution is in the payload
+    assert_ne!(bridge::attribution_payload(&[3; 32], &[4; 32], 81), payload);
+    assert_ne!(bridge::attribution_payload(&[3; 32], &[6; 32], 80), payload);
+    assert_ne!(bridge::attribution_payload(&[6; 32], &[4; 32], 80), payload);
+}
+
+#[test]
+fn other_accounts_are_not_read_as_posted_messages() {
+    let payload = bridge::attribution_payload(&[3; 32], &[4; 32], 80);
+    let data = bridge::posted_message_data(&PostedMessage {
+        emitter_chain: 2,
+        emitter_address: [5; 32],
+        sequence: 7,
+        payload: &payload,
+    });
+
+    let mut unposted = data.clone();
+    unposted[..3].copy_from_slice(b"msg");
+    assert_eq!(bridge::posted_message(&unposted), None);
+
+    let mut truncated = data;
+    truncated.truncate(truncated.len() - 1);
+    assert_eq!(bridge::posted_message(&truncated), None);
+    assert_eq!(bridge::posted_message(&[]), None);
+}
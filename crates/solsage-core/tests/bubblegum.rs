@@ -0,0 +1,46 @@
+use solana_program::pubkey::Pubkey;
+use solsage_core::bubblegum::{self, MintAccounts};
+
+#[test]
+fn receipts_mint_through_the_tree_delegate_with_bubblegum_metadata() {
+    let (merkle_tree, staker, delegate) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let tree_config = bubblegum::tree_config_address(&merkle_tree);
+    let accounts = MintAccounts {
+        tree_config: &tree_config,
+        leaf_owner: &staker,
+        merkle_tree: &merkle_tree,
+        payer: &staker,
+        tree_delegate: &delegate,
+    };
+    let ix = bubblegum::mint_v1(&accounts, "Async Rust", "https://example.com/async-rust.json");
+    assert_eq!(ix.program_id, bubblegum::PROGRAM_ID);
+    assert_eq!(ix.accounts.len(), 9);
+    assert!(ix.accounts[5].is_signer && ix.accounts[5].pubkey == delegate);
+    assert_eq!([ix.accounts[1].pubkey, ix.accounts[2].pubkey], [staker; 2]);
+
+    // Name, symbol and URI lead the metadata, each prefixed with its length
+    let mut expected = vec![145, 98, 192, 118, 184, 147, 118, 104, 10, 0, 0, 0];
+    expected.extend(b"Async Rust");
+    expected.extend([4, 0, 0, 0]);
+    expected.extend(b"SAGE");
+    assert_eq!(ix.data[..expected.len()], expected);
+    assert_eq!(ix.data.len(), expected.len() + 4 + 35 + 14);
+}
+
+#[test]
+fn receipt_names_are_cut_at_a_char_boundary() {
+    assert_eq!(bubblegum::receipt_name("Pinning"), "Pinning");
+    let long = "é".repeat(20);
+    assert_eq!(bubblegum::receipt_name(&long), "é".repeat(16));
+    assert_eq!(bubblegum::receipt_name(&"a".repeat(40)).len(), bubblegum::MAX_NAME_LEN);
+}
+
+#[test]
+fn asset_ids_follow_the_number_of_leaves_minted() {
+    let merkle_tree = Pubkey::new_unique();
+    let mut tree_config = vec![0; 8 + 32 + 32 + 8];
+    assert_eq!(bubblegum::num_minted(&tree_config), None);
+    tree_config.extend(7u64.to_le_bytes());
+    assert_eq!(bubblegum::num_minted(&tree_config), Some(7));
+    assert_ne!(bubblegum::asset_id(&merkle_tree, 7), bubblegum::asset_id(&merkle_tree, 8));
+}
@@ -0,0 +1,28 @@
+use solana_program::hash::hash;
+use solsage_core::hashing::{canonicalize, content_hash};
+
+#[test]
+fn formatting_differences_hash_the_same() {
+    let saved = "# Async Rust\n\n* Use tokio\n+ Pin  futures\n\nDone.\n";
+    let reflowed = "\u{FEFF}# Async   Rust  \r\n\r\n\r\n  - Use\ttokio\r\n-  Pin futures\r\n\r\nDone.\r\n\r\n";
+    assert_eq!(canonicalize(reflowed), "# Async Rust\n\n- Use tokio\n- Pin futures\n\nDone.");
+    assert_eq!(content_hash(saved), content_hash(reflowed));
+    assert_ne!(content_hash(saved), content_hash("# Async Rust\n\n- Use tokio\n\nDone."));
+}
+
+#[test]
+fn code_blocks_keep_their_indentation_and_blank_lines() {
+    let content = "Example:\n```rust   \nfn main() {\n    let x  =  1;   \n\n\n}\n  ```  \n\n\nAfter";
+    assert_eq!(canonicalize(content), "Example:\n```rust\nfn main() {\n    let x  =  1;\n\n\n}\n```\n\nAfter");
+
+    // A tilde fence isn't closed by backticks
+    assert_eq!(canonicalize("~~~\n```\n  *  x\n~~~\n  *  x"), "~~~\n```\n  *  x\n~~~\n- x");
+}
+
+#[test]
+fn canonical_documents_hash_as_their_raw_bytes() {
+    let content = "# Title\n\n- one\n- two";
+    assert_eq!(canonicalize(content), content);
+    assert_eq!(content_hash(content), hash(content.as_bytes()).to_bytes());
+    assert_eq!(canonicalize("\n \n\t"), "");
+}
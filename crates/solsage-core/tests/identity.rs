@@ -0,0 +1,41 @@
+use solana_program::pubkey::Pubkey;
+use solsage_core::identity::{self, StakerAttestation};
+
+fn attestation(data: &[u8]) -> StakerAttestation<'_> {
+    StakerAttestation {
+        nonce: Pubkey::new_unique(),
+        credential: Pubkey::new_unique(),
+        schema: Pubkey::new_unique(),
+        data,
+        signer: Pubkey::new_unique(),
+        expiry: 1_000,
+    }
+}
+
+#[test]
+fn attestation_accounts_carry_their_subject_credential_and_expiry() {
+    let attestation = attestation(b"kyc:tier-2");
+    let data = identity::attestation_data(&attestation);
+    assert_eq!(identity::attestation(&data), Some(attestation));
+
+    let mut other = data.clone();
+    other[0] = 1;
+    assert_eq!(identity::attestation(&other), None);
+    assert_eq!(identity::attestation(&data[..data.len() - 40]), None);
+
+    assert!(!attestation.is_expired(999));
+    assert!(attestation.is_expired(1_000));
+    assert!(!StakerAttestation { expiry: 0, ..attestation }.is_expired(i64::MAX));
+}
+
+#[test]
+fn credential_hashes_follow_the_attested_identity_not_its_subject() {
+    let attestation = attestation(b"kyc:tier-2");
+    let reissued = StakerAttestation { nonce: Pubkey::new_unique(), signer: Pubkey::new_unique(), expiry: 0, ..attestation };
+    assert_eq!(reissued.credential_hash(), attestation.credential_hash());
+
+    let other_data = StakerAttestation { data: b"kyc:tier-1", ..attestation };
+    let other_credential = StakerAttestation { credential: Pubkey::new_unique(), ..attestation };
+    assert_ne!(other_data.credential_hash(), attestation.credential_hash());
+    assert_ne!(other_credential.credential_hash(), attestation.credential_hash());
+}
@@ -0,0 +1,52 @@
+use solana_program::pubkey::Pubkey;
+use solsage_core::merkle;
+
+fn leaves(count: u32) -> Vec<[u8; 32]> {
+    (0..count)
+        .map(|index| merkle::attribution_leaf(index, &Pubkey::new_unique(), &[index as u8; 32], 50))
+        .collect()
+}
+
+#[test]
+fn every_leaf_proves_against_the_root() {
+    for count in 1..=9 {
+        let leaves = leaves(count);
+        let root = merkle::root(&leaves);
+        for (index, leaf) in leaves.iter().enumerate() {
+            assert!(merkle::verify(&root, *leaf, &merkle::proof(&leaves, index)));
+        }
+    }
+}
+
+#[test]
+fn proofs_reject_other_leaves_and_roots() {
+    let leaves = leaves(5);
+    let root = merkle::root(&leaves);
+    let proof = merkle::proof(&leaves, 2);
+
+    assert!(!merkle::verify(&root, leaves[3], &proof));
+    assert!(!merkle::verify(&merkle::root(&leaves[..4]), leaves[2], &proof));
+    assert!(!merkle::verify(&root, leaves[2], &proof[1..]));
+}
+
+#[test]
+fn leaf_commits_to_every_field() {
+    let entry = Pubkey::new_unique();
+    let leaf = merkle::attribution_leaf(0, &entry, &[1; 32], 50);
+
+    assert_ne!(leaf, merkle::attribution_leaf(1, &entry, &[1; 32], 50));
+    assert_ne!(leaf, merkle::attribution_leaf(0, &Pubkey::new_unique(), &[1; 32], 50));
+    assert_ne!(leaf, merkle::attribution_leaf(0, &entry, &[2; 32], 50));
+    assert_ne!(leaf, merkle::attribution_leaf(0, &entry, &[1; 32], 51));
+}
+
+#[test]
+fn chunk_leaves_commit_to_their_position() {
+    let leaves: Vec<_> = (0..4).map(|index| merkle::chunk_leaf(index, &[index as u8; 32])).collect();
+    let root = merkle::root(&leaves);
+    let proof = merkle::proof(&leaves, 1);
+
+    assert!(merkle::verify(&root, merkle::chunk_leaf(1, &[1; 32]), &proof));
+    assert!(!merkle::verify(&root, merkle::chunk_leaf(0, &[1; 32]), &proof));
+    assert!(!merkle::verify(&root, merkle::chunk_leaf(1, &[2; 32]), &proof));
+}
@@ -0,0 +1,39 @@
+use solana_program::{ed25519_program, pubkey::Pubkey};
+use solsage_core::receipts;
+
+#[test]
+fn signature_checks_carry_their_signer_and_receipt() {
+    let (oracle, knowledge_entry) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let receipt = receipts::attribution_receipt(&[3; 32], &knowledge_entry, 80, 7);
+    let ix = receipts::verify_signature(&oracle, &[5; 64], &receipt);
+    assert_eq!(ix.program_id, ed25519_program::id());
+    assert_eq!(receipts::signed_message(&ix), Some((oracle, &receipt[..])));
+
+    // Every field of the attribution is signed for
+    assert_ne!(receipts::attribution_receipt(&[3; 32], &knowledge_entry, 81, 7), receipt);
+    assert_ne!(receipts::attribution_receipt(&[3; 32], &knowledge_entry, 80, 8), receipt);
+    assert_ne!(receipts::attribution_receipt(&[3; 32], &Pubkey::new_unique(), 80, 7), receipt);
+}
+
+#[test]
+fn signature_checks_reading_elsewhere_are_not_taken() {
+    let receipt = receipts::attribution_receipt(&[3; 32], &Pubkey::new_unique(), 80, 7);
+    let ix = receipts::verify_signature(&Pubkey::new_unique(), &[5; 64], &receipt);
+
+    let mut other_program = ix.clone();
+    other_program.program_id = Pubkey::new_unique();
+    assert_eq!(receipts::signed_message(&other_program), None);
+
+    // A message read from another instruction isn't the one this carries
+    let mut message_elsewhere = ix.clone();
+    message_elsewhere.data[14..16].copy_from_slice(&0u16.to_le_bytes());
+    assert_eq!(receipts::signed_message(&message_elsewhere), None);
+
+    let mut two_signatures = ix.clone();
+    two_signatures.data[0] = 2;
+    assert_eq!(receipts::signed_message(&two_signatures), None);
+
+    let mut truncated = ix;
+    truncated.data.truncate(truncated.data.len() - 1);
+    assert_eq!(receipts::signed_message(&truncated), None);
+}
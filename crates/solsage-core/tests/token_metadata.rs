@@ -0,0 +1,57 @@
+use solana_program::pubkey::Pubkey;
+use solsage_core::token_metadata::{self, TokenMetadata};
+
+const SAGE: TokenMetadata = TokenMetadata { name: "SolSage", symbol: "SAGE", uri: "https://example.com/sage.json" };
+
+/// Name, symbol and URI of `SAGE`, each prefixed with its length, then no
+/// royalties, creators, collection or uses
+fn sage_data_v2() -> Vec<u8> {
+    let mut data = vec![7, 0, 0, 0];
+    data.extend(b"SolSage");
+    data.extend([4, 0, 0, 0]);
+    data.extend(b"SAGE");
+    data.extend([29, 0, 0, 0]);
+    data.extend(b"https://example.com/sage.json");
+    data.extend([0, 0, 0, 0, 0]);
+    data
+}
+
+#[test]
+fn metadata_is_created_mutable_under_the_mint_authority() {
+    let (mint, mint_authority, payer) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let ix = token_metadata::create_metadata_account_v3(&mint, &mint_authority, &payer, &SAGE);
+    assert_eq!(ix.program_id, token_metadata::PROGRAM_ID);
+    assert_eq!(ix.accounts[0].pubkey, token_metadata::metadata_address(&mint));
+    assert!(ix.accounts[0].is_writable && ix.accounts[3].is_signer && ix.accounts[3].pubkey == payer);
+    // The mint authority signs both as the mint's and the update authority
+    assert_eq!([ix.accounts[2].pubkey, ix.accounts[4].pubkey], [mint_authority; 2]);
+    assert!(ix.accounts[2].is_signer && ix.accounts[4].is_signer);
+
+    let mut expected = vec![33];
+    expected.extend(sage_data_v2());
+    expected.extend([1, 0]);
+    assert_eq!(ix.data, expected);
+}
+
+#[test]
+fn updates_replace_only_the_displayed_fields() {
+    let (mint, mint_authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let ix = token_metadata::update_metadata_account_v2(&mint, &mint_authority, &SAGE);
+    assert_eq!(ix.accounts.len(), 2);
+    assert_eq!(ix.accounts[0].pubkey, token_metadata::metadata_address(&mint));
+    assert!(ix.accounts[1].is_signer && ix.accounts[1].pubkey == mint_authority);
+
+    let mut expected = vec![15, 1];
+    expected.extend(sage_data_v2());
+    expected.extend([0, 0, 0]);
+    assert_eq!(ix.data, expected);
+}
+
+#[test]
+fn metadata_past_its_limits_is_invalid() {
+    assert!(SAGE.is_valid());
+    let long = "a".repeat(token_metadata::MAX_URI_LEN + 1);
+    assert!(!TokenMetadata { name: &long[..token_metadata::MAX_NAME_LEN + 1], ..SAGE }.is_valid());
+    assert!(!TokenMetadata { symbol: &long[..token_metadata::MAX_SYMBOL_LEN + 1], ..SAGE }.is_valid());
+    assert!(!TokenMetadata { uri: &long, ..SAGE }.is_valid());
+}
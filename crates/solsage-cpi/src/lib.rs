@@ -0,0 +1,2519 @@
+//! Building blocks for composing with the SolSage native program: PDA
+//! derivation, instruction builders for every [`SolSageInstruction`] variant,
+//! and typed [`cpi`] helpers for calling the program from another on-chain
+//! program without copying its seeds or account layouts.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+
+pub use solsage::{
+    self, AccessPass, AccountType, AgentStats, AuthorityType, Attribution, AttributionOracle, AttributionRoot, Bounty, BountyAnswer, BridgeEmitter, Category, Challenge, ChunkCommitment,
+    ChunkProof, Collection, ConsensusVote, ContentRegistry, CuratorAccount, Document, EntryVote, Epoch, FeeConfig, IdentityConfig, KnowledgeEntry, Leaderboard, LeaderboardEntry, LicenseType, Listing, LockupTier, OracleState,
+    PayerStats, ProgramAccount, Proposal, ProposalAction, Protocol, QueryRecord, ReportQueue, ReviewAttestation, ReviewerRegistry, RewardCurve, RewardSource, RewardStream, RoyaltySplit, Snapshot, SolSageError, SolSageInstruction, SpamReport,
+    StakerIndex, StakerProfile, Subscription, TagIndex, Visibility, VoteRecord, KNOWLEDGE_ENTRY_SCHEMA_VERSION,
+    LEADERBOARD_SCHEMA_VERSION, bridge, bubblegum, hashing, identity, merkle, receipts, token_metadata,
+};
+
+// ============================================================================
+// PDAS
+// ============================================================================
+
+pub fn find_protocol_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Protocol::SEED], program_id)
+}
+
+pub fn find_treasury_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Protocol::TREASURY_SEED], program_id)
+}
+
+pub fn find_mint_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Protocol::MINT_SEED], program_id)
+}
+
+pub fn find_mint_authority_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Protocol::MINT_AUTHORITY_SEED], program_id)
+}
+
+/// Tree delegate of the protocol's receipt tree
+pub fn find_receipt_authority_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Protocol::RECEIPT_AUTHORITY_SEED], program_id)
+}
+
+/// Entries are seeded by their original staker, so ownership transfers keep
+/// the address
+pub fn find_knowledge_entry_address(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    entry_index: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[KnowledgeEntry::SEED, creator.as_ref(), &entry_index.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn find_escrow_address(program_id: &Pubkey, knowledge_entry: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[KnowledgeEntry::ESCROW_SEED, knowledge_entry.as_ref()], program_id)
+}
+
+pub fn find_listing_address(program_id: &Pubkey, knowledge_entry: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Listing::SEED, knowledge_entry.as_ref()], program_id)
+}
+
+pub fn find_staker_profile_address(program_id: &Pubkey, staker: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[StakerProfile::SEED, staker.as_ref()], program_id)
+}
+
+pub fn find_content_registry_address(program_id: &Pubkey, content_hash: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ContentRegistry::SEED, content_hash], program_id)
+}
+
+pub fn find_category_address(program_id: &Pubkey, name: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Category::SEED, name.as_bytes()], program_id)
+}
+
+pub fn find_query_record_address(program_id: &Pubkey, query_hash: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[QueryRecord::SEED, query_hash], program_id)
+}
+
+pub fn find_attribution_address(
+    program_id: &Pubkey,
+    query_hash: &[u8; 32],
+    knowledge_entry: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[Attribution::SEED, query_hash, knowledge_entry.as_ref()],
+        program_id,
+    )
+}
+
+pub fn find_oracle_address(program_id: &Pubkey, oracle: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AttributionOracle::SEED, oracle.as_ref()], program_id)
+}
+
+pub fn find_oracle_state_address(program_id: &Pubkey, oracle: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[OracleState::SEED, oracle.as_ref()], program_id)
+}
+
+pub fn find_payer_stats_address(program_id: &Pubkey, payer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PayerStats::SEED, payer.as_ref()], program_id)
+}
+
+/// Emitter `emitter_address` on the Wormhole chain `emitter_chain`; bridged
+/// attributions name this address as their oracle
+pub fn find_bridge_emitter_address(program_id: &Pubkey, emitter_chain: u16, emitter_address: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BridgeEmitter::SEED, &emitter_chain.to_le_bytes(), emitter_address], program_id)
+}
+
+/// Snapshot of the protocol's totals for `day`, counted in
+/// `Snapshot::INTERVAL_SECS` since the Unix epoch
+pub fn find_snapshot_address(program_id: &Pubkey, day: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Snapshot::SEED, &day.to_le_bytes()], program_id)
+}
+
+pub fn find_challenge_address(program_id: &Pubkey, attribution: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Challenge::SEED, attribution.as_ref()], program_id)
+}
+
+pub fn find_report_queue_address(program_id: &Pubkey, knowledge_entry: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ReportQueue::SEED, knowledge_entry.as_ref()], program_id)
+}
+
+pub fn find_bounty_address(program_id: &Pubkey, requester: &Pubkey, query_hash: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Bounty::SEED, requester.as_ref(), query_hash], program_id)
+}
+
+pub fn find_bounty_vault_address(program_id: &Pubkey, bounty: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Bounty::VAULT_SEED, bounty.as_ref()], program_id)
+}
+
+pub fn find_bounty_answer_address(program_id: &Pubkey, bounty: &Pubkey, knowledge_entry: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BountyAnswer::SEED, bounty.as_ref(), knowledge_entry.as_ref()], program_id)
+}
+
+pub fn find_attribution_root_address(program_id: &Pubkey, oracle: &Pubkey, epoch: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[AttributionRoot::SEED, oracle.as_ref(), &epoch.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn find_epoch_address(program_id: &Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Epoch::SEED, &index.to_le_bytes()], program_id)
+}
+
+pub fn find_curator_address(program_id: &Pubkey, curator: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CuratorAccount::SEED, curator.as_ref()], program_id)
+}
+
+pub fn find_collection_address(program_id: &Pubkey, owner: &Pubkey, name: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Collection::SEED, owner.as_ref(), name.as_bytes()], program_id)
+}
+
+pub fn find_document_address(program_id: &Pubkey, owner: &Pubkey, content_hash: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Document::SEED, owner.as_ref(), content_hash], program_id)
+}
+
+pub fn find_tag_index_address(program_id: &Pubkey, tag: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TagIndex::SEED, tag.as_bytes()], program_id)
+}
+
+pub fn find_reward_vault_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Protocol::REWARD_VAULT_SEED], program_id)
+}
+
+pub fn find_subscription_address(program_id: &Pubkey, consumer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Subscription::SEED, consumer.as_ref()], program_id)
+}
+
+pub fn find_leaderboard_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Leaderboard::SEED], program_id)
+}
+
+pub fn find_reviewer_registry_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ReviewerRegistry::SEED], program_id)
+}
+
+pub fn find_access_pass_address(program_id: &Pubkey, knowledge_entry: &Pubkey, holder: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AccessPass::SEED, knowledge_entry.as_ref(), holder.as_ref()], program_id)
+}
+
+pub fn find_agent_stats_address(program_id: &Pubkey, agent_id: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AgentStats::SEED, agent_id], program_id)
+}
+
+pub fn find_governance_vault_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Protocol::GOVERNANCE_VAULT_SEED], program_id)
+}
+
+pub fn find_proposal_address(program_id: &Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Proposal::SEED, &index.to_le_bytes()], program_id)
+}
+
+pub fn find_staker_index_address(program_id: &Pubkey, staker: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[StakerIndex::SEED, staker.as_ref()], program_id)
+}
+
+/// Entries `staker` has created, given their index's `entry_count`. Closed
+/// entries, and indices the staker skipped before the index existed, hold no
+/// account.
+pub fn staked_entry_addresses(program_id: &Pubkey, staker: &Pubkey, entry_count: u64) -> Vec<Pubkey> {
+    (0..entry_count).map(|entry_index| find_knowledge_entry_address(program_id, staker, entry_index).0).collect()
+}
+
+pub fn find_vote_record_address(program_id: &Pubkey, proposal: &Pubkey, voter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VoteRecord::SEED, proposal.as_ref(), voter.as_ref()], program_id)
+}
+
+pub fn find_entry_vote_address(program_id: &Pubkey, knowledge_entry: &Pubkey, voter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EntryVote::SEED, knowledge_entry.as_ref(), voter.as_ref()], program_id)
+}
+
+// ============================================================================
+// INSTRUCTIONS
+// ============================================================================
+
+/// Content of an entry being staked and the license it is offered under
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StakedContent {
+    pub content_hash: [u8; 32],
+    pub title: String,
+    pub license: LicenseType,
+}
+
+/// Consuming agent and model an oracle reports a `RecordAttribution` on
+/// behalf of
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AttributionMetadata {
+    pub agent_id: Option<[u8; 32]>,
+    pub model_id: Option<[u8; 32]>,
+}
+
+/// One entry scored by a `RecordAttributionBatch`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttributionTarget {
+    pub knowledge_entry: Pubkey,
+    /// Current owner of the entry, whose profile is credited
+    pub staker: Pubkey,
+    pub category: Pubkey,
+    /// Collection the entry is in, if any
+    pub collection: Option<Pubkey>,
+    /// Payer's access pass to the entry, when it is private
+    pub access_pass: Option<Pubkey>,
+    pub relevance_score: u8,
+}
+
+impl AttributionTarget {
+    /// Target for an entry outside any collection, scored without an access
+    /// pass
+    pub fn new(knowledge_entry: Pubkey, staker: Pubkey, category: Pubkey, relevance_score: u8) -> Self {
+        Self { knowledge_entry, staker, category, collection: None, access_pass: None, relevance_score }
+    }
+}
+
+/// Inclusion proof for one leaf of a committed attribution root, built with
+/// `solsage_core::merkle`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributionProof {
+    pub leaf_index: u32,
+    pub query_hash: [u8; 32],
+    pub proof: Vec<[u8; 32]>,
+}
+
+/// One attribution claimed by `ClaimRewards`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttributionClaim {
+    pub attribution: Pubkey,
+    /// Epoch the attribution was recorded in, if any
+    pub epoch: Option<u64>,
+}
+
+/// One (entry, attribution) pair claimed by `ClaimAllRewards`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RewardClaim {
+    pub knowledge_entry: Pubkey,
+    pub attribution: Pubkey,
+    /// Epoch the attribution was recorded in, if any
+    pub epoch: Option<u64>,
+}
+
+fn build(program_id: &Pubkey, instruction: SolSageInstruction, accounts: Vec<AccountMeta>) -> Instruction {
+    let data = instruction.try_to_vec().expect("instruction serialization is infallible");
+    Instruction::new_with_bytes(*program_id, &data, accounts)
+}
+
+/// Accounts shared by every authority-only protocol setting
+fn protocol_admin(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    instruction: SolSageInstruction,
+) -> Instruction {
+    build(
+        program_id,
+        instruction,
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(find_protocol_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn initialize(program_id: &Pubkey, authority: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::Initialize,
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(find_protocol_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(find_treasury_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Stake an entry with no lockup; see [`locked_up`] to lock it for boosted
+/// rewards and [`derived_from`] to cite the entries it builds on
+pub fn stake_knowledge(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    entry_index: u64,
+    content: StakedContent,
+    stake_amount: u64,
+    category: &Pubkey,
+) -> Instruction {
+    let StakedContent { content_hash, title, license } = content;
+    let knowledge_entry = find_knowledge_entry_address(program_id, staker, entry_index).0;
+    build(
+        program_id,
+        SolSageInstruction::StakeKnowledge {
+            entry_index,
+            content_hash,
+            title,
+            stake_amount,
+            license,
+            lockup: LockupTier::None,
+            mint_receipt: false,
+            derived_from: Vec::new(),
+            chunks: None,
+            identity: false,
+        },
+        vec![
+            AccountMeta::new(*staker, true),
+            AccountMeta::new(find_protocol_address(program_id).0, false),
+            AccountMeta::new(knowledge_entry, false),
+            AccountMeta::new(find_escrow_address(program_id, &knowledge_entry).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(find_staker_profile_address(program_id, staker).0, false),
+            AccountMeta::new(find_content_registry_address(program_id, &content_hash).0, false),
+            AccountMeta::new(*category, false),
+            AccountMeta::new(find_staker_index_address(program_id, staker).0, false),
+        ],
+    )
+}
+
+/// Pass the open `epoch` while the protocol has epochs enabled; a `curator`
+/// can only be paid while they are disabled
+pub fn record_attribution(
+    program_id: &Pubkey,
+    oracle: &Pubkey,
+    target: &AttributionTarget,
+    query_hash: [u8; 32],
+    epoch: Option<u64>,
+    curator: Option<Pubkey>,
+    metadata: AttributionMetadata,
+) -> Instruction {
+    let AttributionMetadata { agent_id, model_id } = metadata;
+    let mut accounts = vec![
+        AccountMeta::new(*oracle, true),
+        AccountMeta::new(find_protocol_address(program_id).0, false),
+        AccountMeta::new(target.knowledge_entry, false),
+        AccountMeta::new(
+            find_attribution_address(program_id, &query_hash, &target.knowledge_entry).0,
+            false,
+        ),
+        AccountMeta::new(find_query_record_address(program_id, &query_hash).0, false),
+        AccountMeta::new_readonly(find_oracle_address(program_id, oracle).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(find_staker_profile_address(program_id, &target.staker).0, false),
+        AccountMeta::new(find_treasury_address(program_id).0, false),
+        AccountMeta::new(target.category, false),
+        AccountMeta::new(find_leaderboard_address(program_id).0, false),
+        AccountMeta::new(find_oracle_state_address(program_id, oracle).0, false),
+        AccountMeta::new(find_payer_stats_address(program_id, oracle).0, false),
+    ];
+    accounts.extend(epoch.map(|index| AccountMeta::new(find_epoch_address(program_id, index).0, false)));
+    accounts.extend(curator.map(|curator| AccountMeta::new(find_curator_address(program_id, &curator).0, false)));
+    accounts.extend(agent_id.map(|agent_id| AccountMeta::new(find_agent_stats_address(program_id, &agent_id).0, false)));
+    accounts.extend(target.collection.map(|collection| AccountMeta::new(collection, false)));
+    accounts.extend(target.access_pass.map(|access_pass| AccountMeta::new_readonly(access_pass, false)));
+    build(
+        program_id,
+        SolSageInstruction::RecordAttribution {
+            query_hash,
+            relevance_score: target.relevance_score,
+            curator,
+            agent_id,
+            model_id,
+            chunk: None,
+        },
+        accounts,
+    )
+}
+
+/// Mint accounts shared by both claim instructions
+fn reward_mint_accounts(program_id: &Pubkey, destination: &Pubkey) -> [AccountMeta; 4] {
+    [
+        AccountMeta::new(find_mint_address(program_id).0, false),
+        AccountMeta::new(*destination, false),
+        AccountMeta::new_readonly(find_mint_authority_address(program_id).0, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ]
+}
+
+/// `claimer` is the staker or their claim delegate; rewards always go to
+/// `destination`, a SAGE token account owned by `staker`, less the shares
+/// paid to `co_author_destinations`, one SAGE token account per co-author in
+/// the entry's split order
+pub fn claim_rewards(
+    program_id: &Pubkey,
+    claimer: &Pubkey,
+    staker: &Pubkey,
+    knowledge_entry: &Pubkey,
+    destination: &Pubkey,
+    co_author_destinations: &[Pubkey],
+    attributions: &[AttributionClaim],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*claimer, true),
+        AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+        AccountMeta::new(*knowledge_entry, false),
+    ];
+    accounts.extend(reward_mint_accounts(program_id, destination));
+    accounts.push(AccountMeta::new(find_staker_profile_address(program_id, staker).0, false));
+    accounts.extend(co_author_destinations.iter().map(|destination| AccountMeta::new(*destination, false)));
+    for claim in attributions {
+        accounts.push(AccountMeta::new(claim.attribution, false));
+        accounts.extend(claim.epoch.map(|index| AccountMeta::new(find_epoch_address(program_id, index).0, false)));
+    }
+    build(program_id, SolSageInstruction::ClaimRewards, accounts)
+}
+
+pub fn initialize_mint(program_id: &Pubkey, authority: &Pubkey, max_supply: u64) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::InitializeMint { max_supply },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(find_protocol_address(program_id).0, false),
+            AccountMeta::new(find_mint_address(program_id).0, false),
+            AccountMeta::new_readonly(find_mint_authority_address(program_id).0, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Wallets show the SAGE mint as `name` and `symbol`, with the logo and
+/// description of the JSON at `uri`
+pub fn set_mint_metadata(program_id: &Pubkey, authority: &Pubkey, name: &str, symbol: &str, uri: &str) -> Instruction {
+    let mint = find_mint_address(program_id).0;
+    build(
+        program_id,
+        SolSageInstruction::SetMintMetadata { name: name.to_string(), symbol: symbol.to_string(), uri: uri.to_string() },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new_readonly(find_mint_authority_address(program_id).0, false),
+            AccountMeta::new(token_metadata::metadata_address(&mint), false),
+            AccountMeta::new_readonly(token_metadata::PROGRAM_ID, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Anyone may snapshot the protocol's totals once a day; `day` must be the
+/// current one, `Snapshot::day_of` the cluster time
+pub fn take_snapshot(program_id: &Pubkey, payer: &Pubkey, day: u64) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::TakeSnapshot,
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new(find_snapshot_address(program_id, day).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Start the unstake cooldown of `knowledge_entry`
+pub fn request_unstake(program_id: &Pubkey, staker: &Pubkey, knowledge_entry: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::RequestUnstake,
+        vec![
+            AccountMeta::new(*staker, true),
+            AccountMeta::new(*knowledge_entry, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Withdraw the stake of `knowledge_entry` once its unstake cooldown is over
+pub fn complete_unstake(program_id: &Pubkey, staker: &Pubkey, knowledge_entry: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::CompleteUnstake,
+        vec![
+            AccountMeta::new(*staker, true),
+            AccountMeta::new(*knowledge_entry, false),
+            AccountMeta::new(find_escrow_address(program_id, knowledge_entry).0, false),
+        ],
+    )
+}
+
+/// Edit an entry whose content is currently `current_content_hash`, moving
+/// its content registry along with a new `content_hash`
+pub fn update_knowledge(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    knowledge_entry: &Pubkey,
+    current_content_hash: &[u8; 32],
+    title: Option<String>,
+    content_hash: Option<[u8; 32]>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*staker, true),
+        AccountMeta::new(*knowledge_entry, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if let Some(content_hash) = content_hash.filter(|content_hash| content_hash != current_content_hash) {
+        accounts.push(AccountMeta::new(find_content_registry_address(program_id, current_content_hash).0, false));
+        accounts.push(AccountMeta::new(find_content_registry_address(program_id, &content_hash).0, false));
+    }
+    build(program_id, SolSageInstruction::UpdateKnowledge { title, content_hash }, accounts)
+}
+
+fn set_knowledge_active(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    knowledge_entry: &Pubkey,
+    instruction: SolSageInstruction,
+) -> Instruction {
+    build(
+        program_id,
+        instruction,
+        vec![
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new(*knowledge_entry, false),
+        ],
+    )
+}
+
+/// `signer` is the staker or the protocol authority
+pub fn deactivate_knowledge(program_id: &Pubkey, signer: &Pubkey, knowledge_entry: &Pubkey) -> Instruction {
+    set_knowledge_active(program_id, signer, knowledge_entry, SolSageInstruction::DeactivateKnowledge)
+}
+
+/// `signer` is the staker or the protocol authority
+pub fn reactivate_knowledge(program_id: &Pubkey, signer: &Pubkey, knowledge_entry: &Pubkey) -> Instruction {
+    set_knowledge_active(program_id, signer, knowledge_entry, SolSageInstruction::ReactivateKnowledge)
+}
+
+/// `creator` is the entry's original staker, whose index it is counted in
+pub fn close_knowledge_entry(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    creator: &Pubkey,
+    knowledge_entry: &Pubkey,
+    category: &Pubkey,
+    content_hash: &[u8; 32],
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::CloseKnowledgeEntry,
+        vec![
+            AccountMeta::new(*staker, true),
+            AccountMeta::new(find_protocol_address(program_id).0, false),
+            AccountMeta::new(*knowledge_entry, false),
+            AccountMeta::new(find_escrow_address(program_id, knowledge_entry).0, false),
+            AccountMeta::new(*category, false),
+            AccountMeta::new(find_content_registry_address(program_id, content_hash).0, false),
+            AccountMeta::new(find_staker_index_address(program_id, creator).0, false),
+        ],
+    )
+}
+
+pub fn set_reward_rate(program_id: &Pubkey, authority: &Pubkey, reward_per_attribution: u64) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::SetRewardRate { reward_per_attribution })
+}
+
+pub fn propose_authority_transfer(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    new_authority: &Pubkey,
+    authority_type: AuthorityType,
+) -> Instruction {
+    protocol_admin(
+        program_id,
+        authority,
+        SolSageInstruction::ProposeAuthorityTransfer { new_authority: *new_authority, authority_type },
+    )
+}
+
+pub fn accept_authority_transfer(program_id: &Pubkey, new_authority: &Pubkey) -> Instruction {
+    protocol_admin(program_id, new_authority, SolSageInstruction::AcceptAuthorityTransfer)
+}
+
+pub fn set_vesting_period(program_id: &Pubkey, authority: &Pubkey, vesting_period_secs: i64) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::SetVestingPeriod { vesting_period_secs })
+}
+
+pub fn get_vested_rewards(program_id: &Pubkey, knowledge_entry: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::GetVestedRewards,
+        vec![AccountMeta::new_readonly(*knowledge_entry, false)],
+    )
+}
+
+pub fn add_oracle(program_id: &Pubkey, authority: &Pubkey, oracle: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::AddOracle { oracle: *oracle },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new(find_oracle_address(program_id, oracle).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn add_bridge_emitter(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    bridge: &Pubkey,
+    emitter_chain: u16,
+    emitter_address: [u8; 32],
+) -> Instruction {
+    let bridge_emitter = find_bridge_emitter_address(program_id, emitter_chain, &emitter_address).0;
+    build(
+        program_id,
+        SolSageInstruction::AddBridgeEmitter { bridge: *bridge, emitter_chain, emitter_address },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new(bridge_emitter, false),
+            AccountMeta::new(find_oracle_address(program_id, &bridge_emitter).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn remove_oracle(program_id: &Pubkey, authority: &Pubkey, oracle: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::RemoveOracle,
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new(find_oracle_address(program_id, oracle).0, false),
+        ],
+    )
+}
+
+pub fn add_reviewer(program_id: &Pubkey, authority: &Pubkey, reviewer: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::AddReviewer { reviewer: *reviewer },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new(find_reviewer_registry_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn remove_reviewer(program_id: &Pubkey, authority: &Pubkey, reviewer: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::RemoveReviewer { reviewer: *reviewer },
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new(find_reviewer_registry_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn set_oracle_budget(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    oracle: &Pubkey,
+    max_attributions_per_epoch: u64,
+    max_rewards_per_epoch: u64,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::SetOracleBudget { oracle: *oracle, max_attributions_per_epoch, max_rewards_per_epoch },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new(find_oracle_state_address(program_id, oracle).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Pass the open `epoch` while the protocol has epochs enabled
+pub fn record_attribution_batch(
+    program_id: &Pubkey,
+    oracle: &Pubkey,
+    query_hash: [u8; 32],
+    targets: &[AttributionTarget],
+    epoch: Option<u64>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*oracle, true),
+        AccountMeta::new(find_protocol_address(program_id).0, false),
+        AccountMeta::new(find_query_record_address(program_id, &query_hash).0, false),
+        AccountMeta::new_readonly(find_oracle_address(program_id, oracle).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(find_treasury_address(program_id).0, false),
+        AccountMeta::new(find_leaderboard_address(program_id).0, false),
+        AccountMeta::new(find_oracle_state_address(program_id, oracle).0, false),
+        AccountMeta::new(find_payer_stats_address(program_id, oracle).0, false),
+    ];
+    for target in targets {
+        accounts.extend([
+            AccountMeta::new(target.knowledge_entry, false),
+            AccountMeta::new(
+                find_attribution_address(program_id, &query_hash, &target.knowledge_entry).0,
+                false,
+            ),
+            AccountMeta::new(find_staker_profile_address(program_id, &target.staker).0, false),
+            AccountMeta::new(target.category, false),
+        ]);
+    }
+    for target in targets {
+        accounts.extend(target.collection.map(|collection| AccountMeta::new(collection, false)));
+        accounts.extend(target.access_pass.map(|access_pass| AccountMeta::new_readonly(access_pass, false)));
+    }
+    accounts.extend(epoch.map(|index| AccountMeta::new(find_epoch_address(program_id, index).0, false)));
+    let scores = targets.iter().map(|target| target.relevance_score).collect();
+    build(program_id, SolSageInstruction::RecordAttributionBatch { query_hash, scores }, accounts)
+}
+
+/// `claimer` is the staker or their claim delegate; rewards always go to
+/// `destination`, a SAGE token account owned by `staker`
+pub fn claim_all_rewards(
+    program_id: &Pubkey,
+    claimer: &Pubkey,
+    staker: &Pubkey,
+    destination: &Pubkey,
+    claims: &[RewardClaim],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*claimer, true),
+        AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+    ];
+    accounts.extend(reward_mint_accounts(program_id, destination));
+    accounts.push(AccountMeta::new(find_staker_profile_address(program_id, staker).0, false));
+    for claim in claims {
+        accounts.extend([
+            AccountMeta::new(claim.knowledge_entry, false),
+            AccountMeta::new(claim.attribution, false),
+        ]);
+        accounts.extend(claim.epoch.map(|index| AccountMeta::new(find_epoch_address(program_id, index).0, false)));
+    }
+    build(program_id, SolSageInstruction::ClaimAllRewards, accounts)
+}
+
+pub fn transfer_knowledge_ownership(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    knowledge_entry: &Pubkey,
+    new_owner: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::TransferKnowledgeOwnership { new_owner: *new_owner },
+        vec![
+            AccountMeta::new(*staker, true),
+            AccountMeta::new(*knowledge_entry, false),
+            AccountMeta::new(find_staker_profile_address(program_id, new_owner).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(find_staker_profile_address(program_id, staker).0, false),
+        ],
+    )
+}
+
+pub fn list_entry_for_sale(program_id: &Pubkey, staker: &Pubkey, knowledge_entry: &Pubkey, price: u64) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::ListEntryForSale { price },
+        vec![
+            AccountMeta::new(*staker, true),
+            AccountMeta::new_readonly(*knowledge_entry, false),
+            AccountMeta::new(find_listing_address(program_id, knowledge_entry).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// `signer` is the seller, or the entry's owner once the listing is stale;
+/// the rent goes back to `seller` either way
+pub fn cancel_listing(program_id: &Pubkey, signer: &Pubkey, knowledge_entry: &Pubkey, seller: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::CancelListing,
+        vec![
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new_readonly(*knowledge_entry, false),
+            AccountMeta::new(find_listing_address(program_id, knowledge_entry).0, false),
+            AccountMeta::new(*seller, false),
+        ],
+    )
+}
+
+/// Buy `knowledge_entry` from `seller` for the listed `price`
+pub fn buy_entry(program_id: &Pubkey, buyer: &Pubkey, knowledge_entry: &Pubkey, seller: &Pubkey, price: u64) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::BuyEntry { price },
+        vec![
+            AccountMeta::new(*buyer, true),
+            AccountMeta::new(find_protocol_address(program_id).0, false),
+            AccountMeta::new(*knowledge_entry, false),
+            AccountMeta::new(find_listing_address(program_id, knowledge_entry).0, false),
+            AccountMeta::new(*seller, false),
+            AccountMeta::new(find_treasury_address(program_id).0, false),
+            AccountMeta::new(find_staker_profile_address(program_id, buyer).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(find_staker_profile_address(program_id, seller).0, false),
+        ],
+    )
+}
+
+pub fn set_reward_curve(program_id: &Pubkey, authority: &Pubkey, reward_curve: RewardCurve) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::SetRewardCurve { reward_curve })
+}
+
+pub fn challenge_attribution(
+    program_id: &Pubkey,
+    challenger: &Pubkey,
+    attribution: &Pubkey,
+    bond: u64,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::ChallengeAttribution { bond },
+        vec![
+            AccountMeta::new(*challenger, true),
+            AccountMeta::new_readonly(*attribution, false),
+            AccountMeta::new(find_challenge_address(program_id, attribution).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn resolve_challenge(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    attribution: &Pubkey,
+    knowledge_entry: &Pubkey,
+    challenger: &Pubkey,
+    entry_owner: &Pubkey,
+    uphold: bool,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::ResolveChallenge { uphold },
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new(*attribution, false),
+            AccountMeta::new(*knowledge_entry, false),
+            AccountMeta::new(find_challenge_address(program_id, attribution).0, false),
+            AccountMeta::new(*challenger, false),
+            AccountMeta::new(*entry_owner, false),
+        ],
+    )
+}
+
+pub fn report_entry(program_id: &Pubkey, reporter: &Pubkey, knowledge_entry: &Pubkey, bond: u64) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::ReportEntry { bond },
+        vec![
+            AccountMeta::new(*reporter, true),
+            AccountMeta::new(*knowledge_entry, false),
+            AccountMeta::new(find_report_queue_address(program_id, knowledge_entry).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// `reporters` are the queue's reporters in the order they reported
+pub fn resolve_reports(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    knowledge_entry: &Pubkey,
+    entry_owner: &Pubkey,
+    reporters: &[Pubkey],
+    uphold: bool,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+        AccountMeta::new(*knowledge_entry, false),
+        AccountMeta::new(find_report_queue_address(program_id, knowledge_entry).0, false),
+        AccountMeta::new(*entry_owner, false),
+    ];
+    accounts.extend(reporters.iter().map(|reporter| AccountMeta::new(*reporter, false)));
+    build(program_id, SolSageInstruction::ResolveReports { uphold }, accounts)
+}
+
+/// `requester_token_account` is the requester's SAGE account the reward is
+/// escrowed from
+pub fn create_bounty(
+    program_id: &Pubkey,
+    requester: &Pubkey,
+    requester_token_account: &Pubkey,
+    query_hash: [u8; 32],
+    reward: u64,
+    deadline: i64,
+) -> Instruction {
+    let bounty = find_bounty_address(program_id, requester, &query_hash).0;
+    build(
+        program_id,
+        SolSageInstruction::CreateBounty { query_hash, reward, deadline },
+        vec![
+            AccountMeta::new(*requester, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new(bounty, false),
+            AccountMeta::new(find_bounty_vault_address(program_id, &bounty).0, false),
+            AccountMeta::new(*requester_token_account, false),
+            AccountMeta::new_readonly(find_mint_address(program_id).0, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn submit_answer(program_id: &Pubkey, staker: &Pubkey, bounty: &Pubkey, knowledge_entry: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::SubmitAnswer,
+        vec![
+            AccountMeta::new(*staker, true),
+            AccountMeta::new(*bounty, false),
+            AccountMeta::new_readonly(*knowledge_entry, false),
+            AccountMeta::new(find_bounty_answer_address(program_id, bounty, knowledge_entry).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// `destination` is a SAGE account of the answering staker's
+pub fn accept_answer(
+    program_id: &Pubkey,
+    requester: &Pubkey,
+    bounty: &Pubkey,
+    knowledge_entry: &Pubkey,
+    destination: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::AcceptAnswer,
+        vec![
+            AccountMeta::new(*requester, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new(*bounty, false),
+            AccountMeta::new_readonly(find_bounty_answer_address(program_id, bounty, knowledge_entry).0, false),
+            AccountMeta::new(find_bounty_vault_address(program_id, bounty).0, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(find_mint_authority_address(program_id).0, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+pub fn reclaim_expired_bounty(program_id: &Pubkey, requester: &Pubkey, bounty: &Pubkey, destination: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::ReclaimExpiredBounty,
+        vec![
+            AccountMeta::new(*requester, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new(*bounty, false),
+            AccountMeta::new(find_bounty_vault_address(program_id, bounty).0, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(find_mint_authority_address(program_id).0, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+pub fn slash_knowledge(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    knowledge_entry: &Pubkey,
+    slash_bps: u16,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::SlashKnowledge { slash_bps },
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new(*knowledge_entry, false),
+            AccountMeta::new(find_escrow_address(program_id, knowledge_entry).0, false),
+            AccountMeta::new(find_treasury_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn set_protocol_fee(program_id: &Pubkey, authority: &Pubkey, fee_bps: u16) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::SetProtocolFee { fee_bps })
+}
+
+pub fn withdraw_treasury(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::WithdrawTreasury { amount },
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new(find_treasury_address(program_id).0, false),
+            AccountMeta::new(*destination, false),
+        ],
+    )
+}
+
+pub fn create_category(program_id: &Pubkey, authority: &Pubkey, name: &str) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::CreateCategory { name: name.to_string() },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new(find_category_address(program_id, name).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn set_category_multiplier(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    category: &Pubkey,
+    multiplier_bps: u16,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::SetCategoryMultiplier { multiplier_bps },
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new(*category, false),
+        ],
+    )
+}
+
+pub fn renew_knowledge(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    knowledge_entry: &Pubkey,
+    content_hash: Option<[u8; 32]>,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::RenewKnowledge { content_hash },
+        vec![
+            AccountMeta::new(*staker, true),
+            AccountMeta::new(find_protocol_address(program_id).0, false),
+            AccountMeta::new(*knowledge_entry, false),
+            AccountMeta::new(find_treasury_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn set_claim_delegate(program_id: &Pubkey, staker: &Pubkey, delegate: Option<Pubkey>) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::SetClaimDelegate { delegate },
+        vec![
+            AccountMeta::new_readonly(*staker, true),
+            AccountMeta::new(find_staker_profile_address(program_id, staker).0, false),
+        ],
+    )
+}
+
+pub fn set_min_stake(program_id: &Pubkey, authority: &Pubkey, min_stake_lamports: u64) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::SetMinStake { min_stake_lamports })
+}
+
+pub fn set_attribution_cooldown(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    attribution_cooldown_secs: i64,
+) -> Instruction {
+    protocol_admin(
+        program_id,
+        authority,
+        SolSageInstruction::SetAttributionCooldown { attribution_cooldown_secs },
+    )
+}
+
+pub fn pause(program_id: &Pubkey, authority: &Pubkey) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::Pause)
+}
+
+pub fn unpause(program_id: &Pubkey, authority: &Pubkey) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::Unpause)
+}
+
+pub fn set_score_divisor(program_id: &Pubkey, authority: &Pubkey, score_divisor: u16) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::SetScoreDivisor { score_divisor })
+}
+
+pub fn commit_attribution_root(
+    program_id: &Pubkey,
+    oracle: &Pubkey,
+    epoch: u64,
+    root: [u8; 32],
+    attribution_count: u32,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::CommitAttributionRoot { epoch, root, attribution_count },
+        vec![
+            AccountMeta::new(*oracle, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new_readonly(find_oracle_address(program_id, oracle).0, false),
+            AccountMeta::new(find_attribution_root_address(program_id, oracle, epoch).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// `claimer` is the entry owner or their claim delegate; the reward goes to
+/// `destination`, a SAGE token account owned by `target.staker`
+pub fn claim_attributed_reward(
+    program_id: &Pubkey,
+    claimer: &Pubkey,
+    attribution_root: &Pubkey,
+    target: &AttributionTarget,
+    destination: &Pubkey,
+    proof: AttributionProof,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*claimer, true),
+        AccountMeta::new(find_protocol_address(program_id).0, false),
+        AccountMeta::new(*attribution_root, false),
+        AccountMeta::new(target.knowledge_entry, false),
+    ];
+    accounts.extend(reward_mint_accounts(program_id, destination));
+    accounts.push(AccountMeta::new(find_staker_profile_address(program_id, &target.staker).0, false));
+    accounts.push(AccountMeta::new(target.category, false));
+    accounts.extend(target.collection.map(|collection| AccountMeta::new(collection, false)));
+    build(
+        program_id,
+        SolSageInstruction::ClaimAttributedReward {
+            leaf_index: proof.leaf_index,
+            query_hash: proof.query_hash,
+            relevance_score: target.relevance_score,
+            proof: proof.proof,
+        },
+        accounts,
+    )
+}
+
+pub fn set_epoch_schedule(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    epoch_duration_secs: i64,
+    epoch_reward_budget: u64,
+) -> Instruction {
+    protocol_admin(
+        program_id,
+        authority,
+        SolSageInstruction::SetEpochSchedule { epoch_duration_secs, epoch_reward_budget },
+    )
+}
+
+/// Open epoch `index`, which must be the protocol's `epoch_count`
+pub fn open_epoch(program_id: &Pubkey, payer: &Pubkey, index: u64) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::OpenEpoch,
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(find_protocol_address(program_id).0, false),
+            AccountMeta::new(find_epoch_address(program_id, index).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn finalize_epoch(program_id: &Pubkey, index: u64) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::FinalizeEpoch,
+        vec![AccountMeta::new(find_epoch_address(program_id, index).0, false)],
+    )
+}
+
+pub fn set_curator_share(program_id: &Pubkey, authority: &Pubkey, curator_bps: u16) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::SetCuratorShare { curator_bps })
+}
+
+/// Rewards go to `destination`, a SAGE token account owned by `curator`
+pub fn claim_curator_rewards(program_id: &Pubkey, curator: &Pubkey, destination: &Pubkey) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*curator, true),
+        AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+        AccountMeta::new(find_curator_address(program_id, curator).0, false),
+    ];
+    accounts.extend(reward_mint_accounts(program_id, destination));
+    build(program_id, SolSageInstruction::ClaimCuratorRewards, accounts)
+}
+
+pub fn create_collection(program_id: &Pubkey, owner: &Pubkey, name: &str) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::CreateCollection { name: name.to_string() },
+        vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(find_collection_address(program_id, owner, name).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn add_to_collection(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    collection: &Pubkey,
+    knowledge_entry: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::AddToCollection,
+        vec![
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new(*collection, false),
+            AccountMeta::new(*knowledge_entry, false),
+        ],
+    )
+}
+
+/// `signer` is the collection owner or the entry owner
+pub fn remove_from_collection(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    collection: &Pubkey,
+    knowledge_entry: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::RemoveFromCollection,
+        vec![
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new(*collection, false),
+            AccountMeta::new(*knowledge_entry, false),
+        ],
+    )
+}
+
+pub fn set_royalty_split(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    knowledge_entry: &Pubkey,
+    splits: Vec<RoyaltySplit>,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::SetRoyaltySplit { splits },
+        vec![
+            AccountMeta::new(*staker, true),
+            AccountMeta::new(*knowledge_entry, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn update_license(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    knowledge_entry: &Pubkey,
+    license: LicenseType,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::UpdateLicense { license },
+        vec![
+            AccountMeta::new(*staker, true),
+            AccountMeta::new(*knowledge_entry, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn update_content_uri(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    knowledge_entry: &Pubkey,
+    content_uri: Option<String>,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::UpdateContentUri { content_uri },
+        vec![
+            AccountMeta::new(*staker, true),
+            AccountMeta::new(*knowledge_entry, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn update_embedding_fingerprint(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    knowledge_entry: &Pubkey,
+    embedding_fingerprint: Option<[u8; KnowledgeEntry::EMBEDDING_FINGERPRINT_LEN]>,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::UpdateEmbeddingFingerprint { embedding_fingerprint },
+        vec![
+            AccountMeta::new(*staker, true),
+            AccountMeta::new(*knowledge_entry, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn set_entry_visibility(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    knowledge_entry: &Pubkey,
+    visibility: Visibility,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::SetEntryVisibility { visibility },
+        vec![
+            AccountMeta::new(*staker, true),
+            AccountMeta::new(*knowledge_entry, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn grant_access(program_id: &Pubkey, staker: &Pubkey, knowledge_entry: &Pubkey, holder: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::GrantAccess { holder: *holder },
+        vec![
+            AccountMeta::new(*staker, true),
+            AccountMeta::new_readonly(*knowledge_entry, false),
+            AccountMeta::new(find_access_pass_address(program_id, knowledge_entry, holder).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// `price` is the entry's current access price, paid to `owner`, the entry
+/// owner
+pub fn buy_access(program_id: &Pubkey, buyer: &Pubkey, knowledge_entry: &Pubkey, owner: &Pubkey, price: u64) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::BuyAccess { price },
+        vec![
+            AccountMeta::new(*buyer, true),
+            AccountMeta::new_readonly(*knowledge_entry, false),
+            AccountMeta::new(*owner, false),
+            AccountMeta::new(find_access_pass_address(program_id, knowledge_entry, buyer).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn attest_entry(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    knowledge_entry: &Pubkey,
+    accuracy: u8,
+    freshness: u8,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::AttestEntry { accuracy, freshness },
+        vec![
+            AccountMeta::new(*reviewer, true),
+            AccountMeta::new_readonly(find_reviewer_registry_address(program_id).0, false),
+            AccountMeta::new(*knowledge_entry, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Migrate the protocol account before any other
+pub fn migrate_account(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    account: &Pubkey,
+    account_type: AccountType,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::MigrateAccount { account_type },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(find_protocol_address(program_id).0, false),
+            AccountMeta::new(*account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn add_tag(program_id: &Pubkey, staker: &Pubkey, knowledge_entry: &Pubkey, tag: &str) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::AddTag { tag: tag.to_string() },
+        vec![
+            AccountMeta::new(*staker, true),
+            AccountMeta::new(*knowledge_entry, false),
+            AccountMeta::new(find_tag_index_address(program_id, tag).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn remove_tag(program_id: &Pubkey, staker: &Pubkey, knowledge_entry: &Pubkey, tag: &str) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::RemoveTag { tag: tag.to_string() },
+        vec![
+            AccountMeta::new(*staker, true),
+            AccountMeta::new(*knowledge_entry, false),
+            AccountMeta::new(find_tag_index_address(program_id, tag).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create the reward vault on the first switch to `RewardSource::Payer`
+pub fn set_reward_source(program_id: &Pubkey, authority: &Pubkey, reward_source: RewardSource) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::SetRewardSource { reward_source },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(find_protocol_address(program_id).0, false),
+            AccountMeta::new(find_reward_vault_address(program_id).0, false),
+            AccountMeta::new_readonly(find_mint_address(program_id).0, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Append the accounts a `RecordAttribution` or `RecordAttributionBatch`
+/// payer funds rewards through, when attributions fund rewards.
+/// `payer_token_account` is a SAGE token account owned by the payer.
+pub fn with_reward_funding(program_id: &Pubkey, mut instruction: Instruction, payer_token_account: &Pubkey) -> Instruction {
+    instruction.accounts.extend([
+        AccountMeta::new(*payer_token_account, false),
+        AccountMeta::new(find_reward_vault_address(program_id).0, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ]);
+    instruction
+}
+
+/// Append the subscription of a `RecordAttribution` or
+/// `RecordAttributionBatch` payer, which funds rewards in place of the
+/// accounts `with_reward_funding` appends
+pub fn with_subscription_funding(program_id: &Pubkey, mut instruction: Instruction, payer: &Pubkey) -> Instruction {
+    instruction.accounts.push(AccountMeta::new(find_subscription_address(program_id, payer).0, false));
+    instruction
+}
+
+/// `consumer_token_account` is a SAGE token account owned by the consumer
+pub fn top_up_subscription(
+    program_id: &Pubkey,
+    consumer: &Pubkey,
+    consumer_token_account: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::TopUpSubscription { amount },
+        vec![
+            AccountMeta::new(*consumer, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new(find_subscription_address(program_id, consumer).0, false),
+            AccountMeta::new(*consumer_token_account, false),
+            AccountMeta::new(find_reward_vault_address(program_id).0, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// `destination` is the SAGE token account receiving the unspent balance
+pub fn close_subscription(program_id: &Pubkey, consumer: &Pubkey, destination: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::CloseSubscription,
+        vec![
+            AccountMeta::new(*consumer, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new(find_subscription_address(program_id, consumer).0, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new(find_reward_vault_address(program_id).0, false),
+            AccountMeta::new_readonly(find_mint_authority_address(program_id).0, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+pub fn set_price_floor(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    knowledge_entry: &Pubkey,
+    min_reward_per_use: u64,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::SetPriceFloor { min_reward_per_use },
+        vec![
+            AccountMeta::new(*staker, true),
+            AccountMeta::new(*knowledge_entry, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// `knowledge_entries` are re-ranked at their current attribution counts
+pub fn rebuild_leaderboard(program_id: &Pubkey, payer: &Pubkey, knowledge_entries: &[Pubkey]) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(find_leaderboard_address(program_id).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(knowledge_entries.iter().map(|knowledge_entry| AccountMeta::new_readonly(*knowledge_entry, false)));
+    build(program_id, SolSageInstruction::RebuildLeaderboard, accounts)
+}
+
+/// Create the governance vault on the first switch to a non-zero voting
+/// period
+pub fn set_governance_config(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    quorum_votes: u64,
+    approval_threshold_bps: u16,
+    voting_period_secs: i64,
+    execution_delay_secs: i64,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::SetGovernanceConfig {
+            quorum_votes,
+            approval_threshold_bps,
+            voting_period_secs,
+            execution_delay_secs,
+        },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(find_protocol_address(program_id).0, false),
+            AccountMeta::new(find_governance_vault_address(program_id).0, false),
+            AccountMeta::new_readonly(find_mint_address(program_id).0, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// `index` is the protocol's current `proposal_count`
+pub fn create_proposal(program_id: &Pubkey, proposer: &Pubkey, index: u64, action: ProposalAction) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::CreateProposal { action },
+        vec![
+            AccountMeta::new(*proposer, true),
+            AccountMeta::new(find_protocol_address(program_id).0, false),
+            AccountMeta::new(find_proposal_address(program_id, index).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// `voter_token_account` is a SAGE token account owned by the voter
+pub fn cast_vote(
+    program_id: &Pubkey,
+    voter: &Pubkey,
+    index: u64,
+    voter_token_account: &Pubkey,
+    approve: bool,
+    weight: u64,
+) -> Instruction {
+    let proposal = find_proposal_address(program_id, index).0;
+    build(
+        program_id,
+        SolSageInstruction::CastVote { approve, weight },
+        vec![
+            AccountMeta::new(*voter, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new(proposal, false),
+            AccountMeta::new(find_vote_record_address(program_id, &proposal, voter).0, false),
+            AccountMeta::new(*voter_token_account, false),
+            AccountMeta::new(find_governance_vault_address(program_id).0, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Accounts an oracle change needs after the ones every execution takes
+fn parameter_change_accounts(program_id: &Pubkey, change: &ProposalAction) -> Vec<AccountMeta> {
+    match change {
+        ProposalAction::AddOracle { oracle } => vec![
+            AccountMeta::new(find_oracle_address(program_id, oracle).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        ProposalAction::RemoveOracle { oracle } => vec![AccountMeta::new(find_oracle_address(program_id, oracle).0, false)],
+        ProposalAction::SetRewardRate { .. }
+        | ProposalAction::SetProtocolFee { .. }
+        | ProposalAction::SetAdminDelay { .. } => Vec::new(),
+    }
+}
+
+/// `action` is the proposal's action, which decides the trailing accounts
+pub fn execute_proposal(program_id: &Pubkey, executor: &Pubkey, index: u64, action: &ProposalAction) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*executor, true),
+        AccountMeta::new(find_protocol_address(program_id).0, false),
+        AccountMeta::new(find_proposal_address(program_id, index).0, false),
+    ];
+    accounts.extend(parameter_change_accounts(program_id, action));
+    build(program_id, SolSageInstruction::ExecuteProposal, accounts)
+}
+
+/// `destination` is the SAGE token account receiving the voted tokens
+pub fn withdraw_vote(program_id: &Pubkey, voter: &Pubkey, index: u64, destination: &Pubkey) -> Instruction {
+    let proposal = find_proposal_address(program_id, index).0;
+    build(
+        program_id,
+        SolSageInstruction::WithdrawVote,
+        vec![
+            AccountMeta::new(*voter, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new_readonly(proposal, false),
+            AccountMeta::new(find_vote_record_address(program_id, &proposal, voter).0, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new(find_governance_vault_address(program_id).0, false),
+            AccountMeta::new_readonly(find_mint_authority_address(program_id).0, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+pub fn upvote_entry(
+    program_id: &Pubkey,
+    voter: &Pubkey,
+    knowledge_entry: &Pubkey,
+    voter_token_account: &Pubkey,
+    weight: u64,
+) -> Instruction {
+    entry_vote(program_id, voter, knowledge_entry, voter_token_account, SolSageInstruction::UpvoteEntry { weight })
+}
+
+pub fn downvote_entry(
+    program_id: &Pubkey,
+    voter: &Pubkey,
+    knowledge_entry: &Pubkey,
+    voter_token_account: &Pubkey,
+    weight: u64,
+) -> Instruction {
+    entry_vote(program_id, voter, knowledge_entry, voter_token_account, SolSageInstruction::DownvoteEntry { weight })
+}
+
+fn entry_vote(
+    program_id: &Pubkey,
+    voter: &Pubkey,
+    knowledge_entry: &Pubkey,
+    voter_token_account: &Pubkey,
+    ix: SolSageInstruction,
+) -> Instruction {
+    build(
+        program_id,
+        ix,
+        vec![
+            AccountMeta::new(*voter, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new(*knowledge_entry, false),
+            AccountMeta::new(find_entry_vote_address(program_id, knowledge_entry, voter).0, false),
+            AccountMeta::new(*voter_token_account, false),
+            AccountMeta::new(find_governance_vault_address(program_id).0, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn withdraw_entry_vote(program_id: &Pubkey, voter: &Pubkey, knowledge_entry: &Pubkey, destination: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::WithdrawEntryVote,
+        vec![
+            AccountMeta::new(*voter, true),
+            AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+            AccountMeta::new(*knowledge_entry, false),
+            AccountMeta::new(find_entry_vote_address(program_id, knowledge_entry, voter).0, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new(find_governance_vault_address(program_id).0, false),
+            AccountMeta::new_readonly(find_mint_authority_address(program_id).0, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+pub fn queue_parameter_change(program_id: &Pubkey, authority: &Pubkey, change: ProposalAction) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::QueueParameterChange { change })
+}
+
+pub fn cancel_parameter_change(program_id: &Pubkey, authority: &Pubkey) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::CancelParameterChange)
+}
+
+/// `change` is the protocol's queued change, which decides the trailing
+/// accounts
+pub fn execute_parameter_change(program_id: &Pubkey, executor: &Pubkey, change: &ProposalAction) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*executor, true),
+        AccountMeta::new(find_protocol_address(program_id).0, false),
+    ];
+    accounts.extend(parameter_change_accounts(program_id, change));
+    build(program_id, SolSageInstruction::ExecuteParameterChange, accounts)
+}
+
+pub fn set_score_decay(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    score_half_life_secs: i64,
+    min_score_weight_bps: u16,
+) -> Instruction {
+    protocol_admin(
+        program_id,
+        authority,
+        SolSageInstruction::SetScoreDecay { score_half_life_secs, min_score_weight_bps },
+    )
+}
+
+pub fn set_attribution_verifier(program_id: &Pubkey, authority: &Pubkey, verifier: Option<Pubkey>) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::SetAttributionVerifier { verifier })
+}
+
+/// Mint entry receipts from `merkle_tree`, which must delegate to
+/// `find_receipt_authority_address`; `None` stops minting them
+pub fn set_receipt_tree(program_id: &Pubkey, authority: &Pubkey, merkle_tree: Option<Pubkey>) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::SetReceiptTree { merkle_tree })
+}
+
+pub fn set_sale_fee(program_id: &Pubkey, authority: &Pubkey, sale_fee_bps: u16) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::SetSaleFee { sale_fee_bps })
+}
+
+/// Hold attributions until `consensus_threshold` oracles report matching
+/// scores; 0 turns consensus off
+pub fn set_consensus(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    consensus_threshold: u8,
+    consensus_tolerance: u8,
+) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::SetConsensus { consensus_threshold, consensus_tolerance })
+}
+
+pub fn set_payer_attribution_cap(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    max_payer_attributions_per_day: u64,
+) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::SetPayerAttributionCap { max_payer_attributions_per_day })
+}
+
+/// Stream new attribution rewards to their entry over
+/// `reward_stream_secs`; 0 vests them again
+pub fn set_reward_stream(program_id: &Pubkey, authority: &Pubkey, reward_stream_secs: i64) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::SetRewardStream { reward_stream_secs })
+}
+
+/// Carve the treasury's, curators' and burned shares out of every
+/// attribution reward
+pub fn set_fee_config(program_id: &Pubkey, authority: &Pubkey, fee_config: FeeConfig) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::SetFeeConfig { fee_config })
+}
+
+/// The treasury's share goes to `destination`, a SAGE token account owned by
+/// `authority`
+pub fn claim_treasury_rewards(program_id: &Pubkey, authority: &Pubkey, destination: &Pubkey) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(find_protocol_address(program_id).0, false),
+    ];
+    accounts.extend(reward_mint_accounts(program_id, destination));
+    build(program_id, SolSageInstruction::ClaimTreasuryRewards, accounts)
+}
+
+/// Burn `low_relevance_burn_bps` of the reward of attributions scored below
+/// `low_relevance_threshold` instead of paying it out
+pub fn set_low_relevance_burn(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    low_relevance_threshold: u8,
+    low_relevance_burn_bps: u16,
+) -> Instruction {
+    protocol_admin(
+        program_id,
+        authority,
+        SolSageInstruction::SetLowRelevanceBurn { low_relevance_threshold, low_relevance_burn_bps },
+    )
+}
+
+/// Anyone may burn the SAGE payers funded into the reward vault for burns
+pub fn burn_rewards(program_id: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::BurnRewards,
+        vec![
+            AccountMeta::new(find_protocol_address(program_id).0, false),
+            AccountMeta::new(find_reward_vault_address(program_id).0, false),
+            AccountMeta::new(find_mint_address(program_id).0, false),
+            AccountMeta::new_readonly(find_mint_authority_address(program_id).0, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+pub fn set_citation_royalty(program_id: &Pubkey, authority: &Pubkey, citation_royalty_bps: u16) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::SetCitationRoyalty { citation_royalty_bps })
+}
+
+/// `None` stops taking identity attestations with stakes
+pub fn set_identity_config(program_id: &Pubkey, authority: &Pubkey, identity_config: Option<IdentityConfig>) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::SetIdentityConfig { identity_config })
+}
+
+/// Anyone may pay the royalties set aside on `knowledge_entry` to `parents`,
+/// the entries it was derived from in stake order
+pub fn pay_citation_royalties(program_id: &Pubkey, knowledge_entry: &Pubkey, parents: &[Pubkey]) -> Instruction {
+    let mut accounts = vec![AccountMeta::new(*knowledge_entry, false)];
+    accounts.extend(parents.iter().map(|parent| AccountMeta::new(*parent, false)));
+    build(program_id, SolSageInstruction::PayCitationRoyalties, accounts)
+}
+
+/// `content_hash` is the whole document's, seeding its address
+pub fn stake_document(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    content_hash: [u8; 32],
+    title: &str,
+    chunk_count: u16,
+    category: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::StakeDocument { content_hash, title: title.to_string(), chunk_count },
+        vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(find_document_address(program_id, owner, &content_hash).0, false),
+            AccountMeta::new_readonly(*category, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn add_document_chunk(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    document: &Pubkey,
+    knowledge_entry: &Pubkey,
+    chunk_index: u16,
+) -> Instruction {
+    build(
+        program_id,
+        SolSageInstruction::AddDocumentChunk { chunk_index },
+        vec![
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new(*document, false),
+            AccountMeta::new(*knowledge_entry, false),
+        ],
+    )
+}
+
+/// Anyone may refresh `document` from `chunks`, the chunk entries added so
+/// far in chunk order
+pub fn refresh_document(program_id: &Pubkey, document: &Pubkey, chunks: &[Pubkey]) -> Instruction {
+    let mut accounts = vec![AccountMeta::new(*document, false)];
+    accounts.extend(chunks.iter().map(|chunk| AccountMeta::new_readonly(*chunk, false)));
+    build(program_id, SolSageInstruction::RefreshDocument, accounts)
+}
+
+/// Every instruction staking a document as `chunks`, in the order they must
+/// land: the document, then for each chunk its entry, at consecutive indexes
+/// from `first_entry_index`, and its placement at the same chunk index.
+/// `content_hash` is the whole document's; each chunk's content carries its
+/// own.
+#[allow(clippy::too_many_arguments)]
+pub fn stake_document_chunks(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    content_hash: [u8; 32],
+    title: &str,
+    category: &Pubkey,
+    first_entry_index: u64,
+    chunks: &[StakedContent],
+    stake_per_chunk: u64,
+) -> Vec<Instruction> {
+    let document = find_document_address(program_id, owner, &content_hash).0;
+    let mut instructions = vec![stake_document(program_id, owner, content_hash, title, chunks.len() as u16, category)];
+    for (chunk_index, (entry_index, content)) in (first_entry_index..).zip(chunks).enumerate() {
+        let knowledge_entry = find_knowledge_entry_address(program_id, owner, entry_index).0;
+        instructions.push(stake_knowledge(program_id, owner, entry_index, content.clone(), stake_per_chunk, category));
+        instructions.push(add_document_chunk(program_id, owner, &document, &knowledge_entry, chunk_index as u16));
+    }
+    instructions
+}
+
+pub fn set_aggregation_window(program_id: &Pubkey, authority: &Pubkey, aggregation_window_secs: i64) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::SetAggregationWindow { aggregation_window_secs })
+}
+
+/// Anyone may finalize the pending attribution of `query_hash` to `target`
+/// once its aggregation window has closed. `target`'s relevance score is
+/// ignored; the attribution finalizes at the average of the scores it took.
+/// `agent_id` is the attribution's agent, when it has one.
+pub fn finalize_attribution(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    target: &AttributionTarget,
+    query_hash: [u8; 32],
+    epoch: Option<u64>,
+    agent_id: Option<[u8; 32]>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(find_protocol_address(program_id).0, false),
+        AccountMeta::new(target.knowledge_entry, false),
+        AccountMeta::new(find_attribution_address(program_id, &query_hash, &target.knowledge_entry).0, false),
+        AccountMeta::new(find_query_record_address(program_id, &query_hash).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(find_staker_profile_address(program_id, &target.staker).0, false),
+        AccountMeta::new(find_treasury_address(program_id).0, false),
+        AccountMeta::new(target.category, false),
+        AccountMeta::new(find_leaderboard_address(program_id).0, false),
+    ];
+    accounts.extend(epoch.map(|index| AccountMeta::new(find_epoch_address(program_id, index).0, false)));
+    accounts.extend(agent_id.map(|agent_id| AccountMeta::new(find_agent_stats_address(program_id, &agent_id).0, false)));
+    accounts.extend(target.collection.map(|collection| AccountMeta::new(collection, false)));
+    accounts.extend(target.access_pass.map(|access_pass| AccountMeta::new_readonly(access_pass, false)));
+    build(program_id, SolSageInstruction::FinalizeAttribution { query_hash }, accounts)
+}
+
+pub fn set_claim_window(program_id: &Pubkey, authority: &Pubkey, claim_window_secs: i64) -> Instruction {
+    protocol_admin(program_id, authority, SolSageInstruction::SetClaimWindow { claim_window_secs })
+}
+
+/// Anyone may expire `attributions` of `knowledge_entry` once their claim
+/// window has passed; the bounty goes to `destination`, a SAGE token account
+/// owned by `caller`
+pub fn expire_rewards(
+    program_id: &Pubkey,
+    caller: &Pubkey,
+    knowledge_entry: &Pubkey,
+    destination: &Pubkey,
+    attributions: &[AttributionClaim],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*caller, true),
+        AccountMeta::new_readonly(find_protocol_address(program_id).0, false),
+        AccountMeta::new(*knowledge_entry, false),
+    ];
+    accounts.extend(reward_mint_accounts(program_id, destination));
+    for claim in attributions {
+        accounts.push(AccountMeta::new(claim.attribution, false));
+        accounts.extend(claim.epoch.map(|index| AccountMeta::new(find_epoch_address(program_id, index).0, false)));
+    }
+    build(program_id, SolSageInstruction::ExpireRewards, accounts)
+}
+
+/// Pass the instructions sysvar to an attribution, as the protocol needs
+/// once it has an attribution verifier; it follows the instruction's fixed
+/// accounts. Other instructions are returned unchanged.
+pub fn with_instructions_sysvar(mut instruction: Instruction) -> Instruction {
+    let position = match SolSageInstruction::try_from_slice(&instruction.data) {
+        Ok(SolSageInstruction::RecordAttribution { .. }) => 13,
+        Ok(SolSageInstruction::RecordBridgedAttribution { .. }) => 15,
+        Ok(SolSageInstruction::RecordAttributionBatch { .. }) => 9,
+        Ok(SolSageInstruction::CommitAttributionRoot { .. }) => 5,
+        _ => return instruction,
+    };
+    instruction
+        .accounts
+        .insert(position, AccountMeta::new_readonly(sysvar::instructions::id(), false));
+    instruction
+}
+
+/// Turn an oracle's attribution into one `relayer` submits under the
+/// oracle's signed receipt numbered `nonce`; it must directly follow the
+/// `receipts::verify_signature` instruction over that receipt. Receipts
+/// don't cover a chunk proof, so any is dropped. Other instructions are
+/// returned unchanged.
+pub fn relayed_by(mut instruction: Instruction, relayer: &Pubkey, nonce: u64) -> Instruction {
+    let Ok(SolSageInstruction::RecordAttribution { query_hash, relevance_score, curator, agent_id, model_id, .. }) =
+        SolSageInstruction::try_from_slice(&instruction.data)
+    else {
+        return instruction;
+    };
+    let signed = SolSageInstruction::RecordSignedAttribution {
+        query_hash,
+        relevance_score,
+        curator,
+        agent_id,
+        model_id,
+        nonce,
+    };
+    instruction.data = signed.try_to_vec().expect("instruction serialization is infallible");
+    instruction.accounts[0] = AccountMeta::new(*relayer, true);
+    instruction.accounts[12] = AccountMeta::new(find_payer_stats_address(&instruction.program_id, relayer).0, false);
+    instruction
+        .accounts
+        .insert(13, AccountMeta::new_readonly(sysvar::instructions::id(), false));
+    instruction
+}
+
+/// Turn an attribution built with a bridge emitter's address as its oracle
+/// into one `relayer` submits under the message the emitter's bridge posted
+/// at `posted_message`. Bridged messages don't cover a chunk proof, so any
+/// is dropped. Other instructions are returned unchanged.
+pub fn bridged_from(mut instruction: Instruction, relayer: &Pubkey, posted_message: &Pubkey) -> Instruction {
+    let Ok(SolSageInstruction::RecordAttribution { query_hash, relevance_score, curator, agent_id, model_id, .. }) =
+        SolSageInstruction::try_from_slice(&instruction.data)
+    else {
+        return instruction;
+    };
+    let bridged = SolSageInstruction::RecordBridgedAttribution { query_hash, relevance_score, curator, agent_id, model_id };
+    let bridge_emitter = instruction.accounts[0].pubkey;
+    instruction.data = bridged.try_to_vec().expect("instruction serialization is infallible");
+    instruction.accounts[0] = AccountMeta::new(*relayer, true);
+    instruction.accounts[12] = AccountMeta::new(find_payer_stats_address(&instruction.program_id, relayer).0, false);
+    instruction.accounts.splice(
+        13..13,
+        [AccountMeta::new(bridge_emitter, false), AccountMeta::new_readonly(*posted_message, false)],
+    );
+    instruction
+}
+
+/// Lock the stake of a `StakeKnowledge` for `lockup`, boosting the entry's
+/// rewards. Other instructions are returned unchanged.
+pub fn locked_up(mut instruction: Instruction, lockup: LockupTier) -> Instruction {
+    let Ok(SolSageInstruction::StakeKnowledge {
+        entry_index,
+        content_hash,
+        title,
+        stake_amount,
+        license,
+        mint_receipt,
+        derived_from,
+        chunks,
+        identity,
+        ..
+    }) = SolSageInstruction::try_from_slice(&instruction.data)
+    else {
+        return instruction;
+    };
+    let locked = SolSageInstruction::StakeKnowledge {
+        entry_index,
+        content_hash,
+        title,
+        stake_amount,
+        license,
+        lockup,
+        mint_receipt,
+        derived_from,
+        chunks,
+        identity,
+    };
+    instruction.data = locked.try_to_vec().expect("instruction serialization is infallible");
+    instruction
+}
+
+/// Mint the staker of a `StakeKnowledge` a compressed NFT receipt for the
+/// entry from `merkle_tree`, the protocol's receipt tree. Other instructions
+/// are returned unchanged.
+pub fn with_receipt(program_id: &Pubkey, mut instruction: Instruction, merkle_tree: &Pubkey) -> Instruction {
+    let Ok(SolSageInstruction::StakeKnowledge {
+        entry_index,
+        content_hash,
+        title,
+        stake_amount,
+        license,
+        lockup,
+        derived_from,
+        chunks,
+        identity,
+        ..
+    }) = SolSageInstruction::try_from_slice(&instruction.data)
+    else {
+        return instruction;
+    };
+    // The receipt accounts go ahead of any identity attestation and the
+    // parents, which come last
+    let receipt_at = instruction.accounts.len() - derived_from.len() - usize::from(identity);
+    let receipted = SolSageInstruction::StakeKnowledge {
+        entry_index,
+        content_hash,
+        title,
+        stake_amount,
+        license,
+        lockup,
+        mint_receipt: true,
+        derived_from,
+        chunks,
+        identity,
+    };
+    instruction.data = receipted.try_to_vec().expect("instruction serialization is infallible");
+    instruction.accounts.splice(receipt_at..receipt_at, [
+        AccountMeta::new(bubblegum::tree_config_address(merkle_tree), false),
+        AccountMeta::new(*merkle_tree, false),
+        AccountMeta::new_readonly(find_receipt_authority_address(program_id).0, false),
+        AccountMeta::new_readonly(bubblegum::PROGRAM_ID, false),
+        AccountMeta::new_readonly(bubblegum::SPL_NOOP_PROGRAM_ID, false),
+        AccountMeta::new_readonly(bubblegum::SPL_ACCOUNT_COMPRESSION_PROGRAM_ID, false),
+    ]);
+    instruction
+}
+
+/// Derive the entry of a `StakeKnowledge` from `parents`, which are paid a
+/// royalty on its attributions. Other instructions are returned unchanged.
+pub fn derived_from(mut instruction: Instruction, parents: &[Pubkey]) -> Instruction {
+    let Ok(SolSageInstruction::StakeKnowledge {
+        entry_index,
+        content_hash,
+        title,
+        stake_amount,
+        license,
+        lockup,
+        mint_receipt,
+        chunks,
+        identity,
+        ..
+    }) = SolSageInstruction::try_from_slice(&instruction.data)
+    else {
+        return instruction;
+    };
+    let derived = SolSageInstruction::StakeKnowledge {
+        entry_index,
+        content_hash,
+        title,
+        stake_amount,
+        license,
+        lockup,
+        mint_receipt,
+        derived_from: parents.to_vec(),
+        chunks,
+        identity,
+    };
+    instruction.data = derived.try_to_vec().expect("instruction serialization is infallible");
+    instruction
+        .accounts
+        .extend(parents.iter().map(|parent| AccountMeta::new_readonly(*parent, false)));
+    instruction
+}
+
+/// Commit the entry of a `StakeKnowledge` to `chunks`, so attributions can
+/// prove which chunk they used. Other instructions are returned unchanged.
+pub fn with_chunks(mut instruction: Instruction, chunks: ChunkCommitment) -> Instruction {
+    let Ok(SolSageInstruction::StakeKnowledge {
+        entry_index,
+        content_hash,
+        title,
+        stake_amount,
+        license,
+        lockup,
+        mint_receipt,
+        derived_from,
+        identity,
+        ..
+    }) = SolSageInstruction::try_from_slice(&instruction.data)
+    else {
+        return instruction;
+    };
+    let committed = SolSageInstruction::StakeKnowledge {
+        entry_index,
+        content_hash,
+        title,
+        stake_amount,
+        license,
+        lockup,
+        mint_receipt,
+        derived_from,
+        chunks: Some(chunks),
+        identity,
+    };
+    instruction.data = committed.try_to_vec().expect("instruction serialization is infallible");
+    instruction
+}
+
+/// Present `attestation`, the staker's identity attestation, with a
+/// `StakeKnowledge`, recording its credential hash on the entry. Other
+/// instructions are returned unchanged.
+pub fn with_identity(mut instruction: Instruction, attestation: &Pubkey) -> Instruction {
+    let Ok(SolSageInstruction::StakeKnowledge {
+        entry_index,
+        content_hash,
+        title,
+        stake_amount,
+        license,
+        lockup,
+        mint_receipt,
+        derived_from,
+        chunks,
+        ..
+    }) = SolSageInstruction::try_from_slice(&instruction.data)
+    else {
+        return instruction;
+    };
+    // The attestation goes after any receipt accounts, ahead of the parents
+    let parents_at = instruction.accounts.len() - derived_from.len();
+    let identified = SolSageInstruction::StakeKnowledge {
+        entry_index,
+        content_hash,
+        title,
+        stake_amount,
+        license,
+        lockup,
+        mint_receipt,
+        derived_from,
+        chunks,
+        identity: true,
+    };
+    instruction.data = identified.try_to_vec().expect("instruction serialization is infallible");
+    instruction.accounts.insert(parents_at, AccountMeta::new_readonly(*attestation, false));
+    instruction
+}
+
+/// Prove a `RecordAttribution` used `chunk` of its entry's committed chunks.
+/// Other instructions are returned unchanged.
+pub fn with_chunk_proof(mut instruction: Instruction, chunk: ChunkProof) -> Instruction {
+    let Ok(SolSageInstruction::RecordAttribution { query_hash, relevance_score, curator, agent_id, model_id, .. }) =
+        SolSageInstruction::try_from_slice(&instruction.data)
+    else {
+        return instruction;
+    };
+    let proven =
+        SolSageInstruction::RecordAttribution { query_hash, relevance_score, curator, agent_id, model_id, chunk: Some(chunk) };
+    instruction.data = proven.try_to_vec().expect("instruction serialization is infallible");
+    instruction
+}
+
+/// Refund the content registry a `CloseKnowledgeEntry`, or an
+/// `UpdateKnowledge` changing the content hash, closes to `payer`, who paid
+/// for it in place of the staker. Other instructions are returned unchanged.
+pub fn refunding_registry_to(mut instruction: Instruction, payer: &Pubkey) -> Instruction {
+    let closes_registry = match SolSageInstruction::try_from_slice(&instruction.data) {
+        Ok(SolSageInstruction::CloseKnowledgeEntry) => true,
+        Ok(SolSageInstruction::UpdateKnowledge { .. }) => instruction.accounts.len() > 3,
+        _ => false,
+    };
+    if closes_registry {
+        instruction.accounts.push(AccountMeta::new(*payer, false));
+    }
+    instruction
+}
+
+/// Commitment to `chunks`, the content split in order, each hashed with
+/// `hashing::content_hash`
+pub fn chunk_commitment(chunks: &[&str]) -> ChunkCommitment {
+    ChunkCommitment { root: merkle::root(&chunk_leaves(chunks)), chunk_count: chunks.len() as u32 }
+}
+
+/// Proof that `chunks[index]` is in `chunk_commitment(chunks)`
+pub fn chunk_proof(chunks: &[&str], index: usize) -> ChunkProof {
+    ChunkProof {
+        index: index as u32,
+        chunk_hash: hashing::content_hash(chunks[index]),
+        proof: merkle::proof(&chunk_leaves(chunks), index),
+    }
+}
+
+fn chunk_leaves(chunks: &[&str]) -> Vec<[u8; 32]> {
+    (0u32..).zip(chunks).map(|(index, chunk)| merkle::chunk_leaf(index, &hashing::content_hash(chunk))).collect()
+}
+
+/// Point a claim at the reward vault in place of the SAGE mint, when
+/// attributions fund rewards
+pub fn paid_from_reward_vault(program_id: &Pubkey, mut instruction: Instruction) -> Instruction {
+    let sage_mint = find_mint_address(program_id).0;
+    for account in instruction.accounts.iter_mut().filter(|account| account.pubkey == sage_mint) {
+        account.pubkey = find_reward_vault_address(program_id).0;
+    }
+    instruction
+}
+
+// ============================================================================
+// CPI
+// ============================================================================
+
+/// Invoke SolSage from another program. Instructions are built straight from
+/// the passed accounts, so no PDAs are derived on-chain; SolSage itself
+/// checks every address.
+pub mod cpi {
+    use super::*;
+    use solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, program::invoke_signed,
+    };
+
+    pub mod accounts {
+        use solana_program::account_info::AccountInfo;
+
+        /// Accounts of `StakeKnowledge`, in instruction order
+        pub struct StakeKnowledge<'a, 'info> {
+            pub solsage_program: &'a AccountInfo<'info>,
+            pub staker: &'a AccountInfo<'info>,
+            pub protocol: &'a AccountInfo<'info>,
+            pub knowledge_entry: &'a AccountInfo<'info>,
+            pub escrow: &'a AccountInfo<'info>,
+            pub system_program: &'a AccountInfo<'info>,
+            pub staker_profile: &'a AccountInfo<'info>,
+            pub content_registry: &'a AccountInfo<'info>,
+            pub category: &'a AccountInfo<'info>,
+            pub staker_index: &'a AccountInfo<'info>,
+        }
+
+        /// Accounts of `RecordAttribution`, in instruction order; `payer`
+        /// must be a registered oracle
+        pub struct RecordAttribution<'a, 'info> {
+            pub solsage_program: &'a AccountInfo<'info>,
+            pub payer: &'a AccountInfo<'info>,
+            pub protocol: &'a AccountInfo<'info>,
+            pub knowledge_entry: &'a AccountInfo<'info>,
+            pub attribution: &'a AccountInfo<'info>,
+            pub query_record: &'a AccountInfo<'info>,
+            pub attribution_oracle: &'a AccountInfo<'info>,
+            pub system_program: &'a AccountInfo<'info>,
+            pub staker_profile: &'a AccountInfo<'info>,
+            pub treasury: &'a AccountInfo<'info>,
+            pub category: &'a AccountInfo<'info>,
+            pub leaderboard: &'a AccountInfo<'info>,
+            pub oracle_state: &'a AccountInfo<'info>,
+            pub payer_stats: &'a AccountInfo<'info>,
+            /// Instructions sysvar, required while the protocol has an
+            /// attribution verifier
+            pub instructions_sysvar: Option<&'a AccountInfo<'info>>,
+            /// Open epoch, required while epochs are enabled
+            pub epoch: Option<&'a AccountInfo<'info>>,
+            /// Curator account PDA, required when a curator is passed
+            pub curator_account: Option<&'a AccountInfo<'info>>,
+            /// Agent stats PDA, required when an agent ID is passed
+            pub agent_stats: Option<&'a AccountInfo<'info>>,
+            /// Entry's collection, required when it is in one
+            pub collection: Option<&'a AccountInfo<'info>>,
+            /// Payer's SAGE token account, the reward vault PDA and the token
+            /// program, required when attributions fund rewards without a
+            /// subscription
+            pub reward_funding: Option<[&'a AccountInfo<'info>; 3]>,
+            /// Payer's subscription PDA, funding rewards in place of
+            /// `reward_funding`
+            pub subscription: Option<&'a AccountInfo<'info>>,
+        }
+    }
+
+    fn writable(account: &AccountInfo) -> AccountMeta {
+        AccountMeta::new(*account.key, account.is_signer)
+    }
+
+    fn readonly(account: &AccountInfo) -> AccountMeta {
+        AccountMeta::new_readonly(*account.key, account.is_signer)
+    }
+
+    /// Stake an entry, locked for `lockup`; pass `signer_seeds` when the
+    /// staker is a PDA of the calling program
+    pub fn stake_knowledge(
+        accounts: &accounts::StakeKnowledge,
+        entry_index: u64,
+        content: StakedContent,
+        stake_amount: u64,
+        lockup: LockupTier,
+        signer_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let StakedContent { content_hash, title, license } = content;
+        let instruction = build(
+            accounts.solsage_program.key,
+            SolSageInstruction::StakeKnowledge {
+                entry_index,
+                content_hash,
+                title,
+                stake_amount,
+                license,
+                lockup,
+                mint_receipt: false,
+                derived_from: Vec::new(),
+                chunks: None,
+                identity: false,
+            },
+            vec![
+                AccountMeta::new(*accounts.staker.key, true),
+                writable(accounts.protocol),
+                writable(accounts.knowledge_entry),
+                writable(accounts.escrow),
+                readonly(accounts.system_program),
+                writable(accounts.staker_profile),
+                writable(accounts.content_registry),
+                writable(accounts.category),
+                writable(accounts.staker_index),
+            ],
+        );
+        invoke_signed(
+            &instruction,
+            &[
+                accounts.staker.clone(),
+                accounts.protocol.clone(),
+                accounts.knowledge_entry.clone(),
+                accounts.escrow.clone(),
+                accounts.system_program.clone(),
+                accounts.staker_profile.clone(),
+                accounts.content_registry.clone(),
+                accounts.category.clone(),
+                accounts.staker_index.clone(),
+                accounts.solsage_program.clone(),
+            ],
+            signer_seeds,
+        )
+    }
+
+    /// Record an attribution; pass `signer_seeds` when the oracle is a PDA
+    /// of the calling program
+    pub fn record_attribution(
+        accounts: &accounts::RecordAttribution,
+        query_hash: [u8; 32],
+        relevance_score: u8,
+        curator: Option<Pubkey>,
+        metadata: AttributionMetadata,
+        signer_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let AttributionMetadata { agent_id, model_id } = metadata;
+        let mut metas = vec![
+            AccountMeta::new(*accounts.payer.key, true),
+            writable(accounts.protocol),
+            writable(accounts.knowledge_entry),
+            writable(accounts.attribution),
+            writable(accounts.query_record),
+            readonly(accounts.attribution_oracle),
+            readonly(accounts.system_program),
+            writable(accounts.staker_profile),
+            writable(accounts.treasury),
+            writable(accounts.category),
+            writable(accounts.leaderboard),
+            writable(accounts.oracle_state),
+            writable(accounts.payer_stats),
+        ];
+        let mut infos = vec![
+            accounts.payer.clone(),
+            accounts.protocol.clone(),
+            accounts.knowledge_entry.clone(),
+            accounts.attribution.clone(),
+            accounts.query_record.clone(),
+            accounts.attribution_oracle.clone(),
+            accounts.system_program.clone(),
+            accounts.staker_profile.clone(),
+            accounts.treasury.clone(),
+            accounts.category.clone(),
+            accounts.leaderboard.clone(),
+            accounts.oracle_state.clone(),
+            accounts.payer_stats.clone(),
+        ];
+        if let Some(instructions_sysvar) = accounts.instructions_sysvar {
+            metas.push(readonly(instructions_sysvar));
+            infos.push(instructions_sysvar.clone());
+        }
+        let optional_accounts = [accounts.epoch, accounts.curator_account, accounts.agent_stats, accounts.collection];
+        for account in optional_accounts.into_iter().flatten() {
+            metas.push(writable(account));
+            infos.push(account.clone());
+        }
+        if let Some([payer_token_account, reward_vault, token_program]) = accounts.reward_funding {
+            metas.extend([writable(payer_token_account), writable(reward_vault), readonly(token_program)]);
+            infos.extend([payer_token_account.clone(), reward_vault.clone(), token_program.clone()]);
+        } else if let Some(subscription) = accounts.subscription {
+            metas.push(writable(subscription));
+            infos.push(subscription.clone());
+        }
+        infos.push(accounts.solsage_program.clone());
+
+        let instruction = build(
+            accounts.solsage_program.key,
+            SolSageInstruction::RecordAttribution { query_hash, relevance_score, curator, agent_id, model_id, chunk: None },
+            metas,
+        );
+        invoke_signed(&instruction, &infos, signer_seeds)
+    }
+}
@@ -0,0 +1,306 @@
+use borsh::BorshDeserialize;
+use solana_program::{pubkey::Pubkey, sysvar};
+use solsage_cpi::{
+    bubblegum, claim_all_rewards, claim_rewards, derived_from, execute_proposal, find_agent_stats_address, find_collection_address,
+    find_curator_address, find_epoch_address, find_escrow_address, find_knowledge_entry_address,
+    find_leaderboard_address, find_mint_address, find_oracle_address, find_oracle_state_address,
+    find_payer_stats_address, find_proposal_address, find_reward_vault_address, find_staker_index_address,
+    find_subscription_address, paid_from_reward_vault, receipts, record_attribution, record_attribution_batch,
+    relayed_by, stake_knowledge, staked_entry_addresses, with_instructions_sysvar, with_receipt, with_reward_funding,
+    with_subscription_funding, AttributionClaim, AttributionMetadata, AttributionTarget, LicenseType, ProposalAction,
+    RewardClaim, SolSageInstruction, StakedContent,
+};
+
+fn decode(data: &[u8]) -> SolSageInstruction {
+    SolSageInstruction::try_from_slice(data).unwrap()
+}
+
+#[test]
+fn stake_knowledge_round_trips_and_derives_entry_pdas() {
+    let program_id = Pubkey::new_unique();
+    let staker = Pubkey::new_unique();
+    let category = Pubkey::new_unique();
+    let content = StakedContent {
+        content_hash: [1; 32],
+        title: "Title".to_string(),
+        license: LicenseType::CcBy,
+    };
+    let ix = stake_knowledge(&program_id, &staker, 7, content, 500, &category);
+
+    assert!(matches!(
+        decode(&ix.data),
+        SolSageInstruction::StakeKnowledge {
+            entry_index: 7,
+            stake_amount: 500,
+            ref title,
+            license: LicenseType::CcBy,
+            ..
+        } if title == "Title"
+    ));
+
+    let knowledge_entry = find_knowledge_entry_address(&program_id, &staker, 7).0;
+    assert_eq!(ix.accounts.len(), 9);
+    assert!(ix.accounts[0].is_signer && ix.accounts[0].pubkey == staker);
+    assert_eq!(ix.accounts[2].pubkey, knowledge_entry);
+    assert_eq!(ix.accounts[3].pubkey, find_escrow_address(&program_id, &knowledge_entry).0);
+    assert_eq!(ix.accounts[7].pubkey, category);
+    assert!(ix.accounts[8].is_writable && ix.accounts[8].pubkey == find_staker_index_address(&program_id, &staker).0);
+
+    // The index enumerates the entry's address back from its position
+    assert_eq!(staked_entry_addresses(&program_id, &staker, 8)[7], knowledge_entry);
+}
+
+#[test]
+fn derived_entries_pass_their_parents_after_every_other_account() {
+    let (program_id, staker, merkle_tree) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let parents = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let content = StakedContent { content_hash: [1; 32], title: "Title".to_string(), license: LicenseType::Mit };
+    let staked = stake_knowledge(&program_id, &staker, 0, content, 500, &Pubkey::new_unique());
+    let ix = with_receipt(&program_id, derived_from(staked, &parents), &merkle_tree);
+
+    assert!(matches!(
+        decode(&ix.data),
+        SolSageInstruction::StakeKnowledge { mint_receipt: true, ref derived_from, .. } if derived_from[..] == parents
+    ));
+    assert_eq!(ix.accounts.len(), 9 + 6 + 2);
+    assert_eq!(ix.accounts[13].pubkey, bubblegum::SPL_NOOP_PROGRAM_ID);
+    assert_eq!([ix.accounts[15].pubkey, ix.accounts[16].pubkey], parents);
+    assert!(!ix.accounts[15].is_writable && !ix.accounts[16].is_writable);
+}
+
+#[test]
+fn attribution_batch_appends_one_quadruple_per_target() {
+    let program_id = Pubkey::new_unique();
+    let targets: Vec<_> = [40, 90]
+        .into_iter()
+        .map(|relevance_score| {
+            AttributionTarget::new(Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), relevance_score)
+        })
+        .collect();
+    let oracle = Pubkey::new_unique();
+    let ix = record_attribution_batch(&program_id, &oracle, [2; 32], &targets, None);
+
+    assert!(matches!(
+        decode(&ix.data),
+        SolSageInstruction::RecordAttributionBatch { ref scores, .. } if scores == &[40, 90]
+    ));
+    assert_eq!(ix.accounts.len(), 9 + 4 * targets.len());
+    assert_eq!(ix.accounts[6].pubkey, find_leaderboard_address(&program_id).0);
+    assert!(ix.accounts[7].is_writable && ix.accounts[7].pubkey == find_oracle_state_address(&program_id, &oracle).0);
+    assert!(ix.accounts[8].is_writable && ix.accounts[8].pubkey == find_payer_stats_address(&program_id, &oracle).0);
+    assert_eq!(ix.accounts[9].pubkey, targets[0].knowledge_entry);
+    assert_eq!(ix.accounts[16].pubkey, targets[1].category);
+}
+
+#[test]
+fn epoch_attributions_are_followed_by_their_epoch() {
+    let program_id = Pubkey::new_unique();
+    let claims = [
+        RewardClaim { knowledge_entry: Pubkey::new_unique(), attribution: Pubkey::new_unique(), epoch: Some(3) },
+        RewardClaim { knowledge_entry: Pubkey::new_unique(), attribution: Pubkey::new_unique(), epoch: None },
+    ];
+    let staker = Pubkey::new_unique();
+    let ix = claim_all_rewards(&program_id, &staker, &staker, &Pubkey::new_unique(), &claims);
+
+    assert_eq!(ix.accounts.len(), 7 + 3 + 2);
+    assert_eq!(ix.accounts[8].pubkey, claims[0].attribution);
+    assert_eq!(ix.accounts[9].pubkey, find_epoch_address(&program_id, 3).0);
+    assert_eq!(ix.accounts[10].pubkey, claims[1].knowledge_entry);
+}
+
+#[test]
+fn curated_attribution_appends_curator_account() {
+    let program_id = Pubkey::new_unique();
+    let curator = Pubkey::new_unique();
+    let target = AttributionTarget::new(Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), 60);
+    let ix = record_attribution(
+        &program_id,
+        &Pubkey::new_unique(),
+        &target,
+        [4; 32],
+        None,
+        Some(curator),
+        AttributionMetadata::default(),
+    );
+
+    assert!(matches!(
+        decode(&ix.data),
+        SolSageInstruction::RecordAttribution { curator: Some(key), .. } if key == curator
+    ));
+    assert_eq!(ix.accounts.len(), 14);
+    assert!(ix.accounts[10].is_writable && ix.accounts[10].pubkey == find_leaderboard_address(&program_id).0);
+    assert_eq!(ix.accounts[13].pubkey, find_curator_address(&program_id, &curator).0);
+}
+
+#[test]
+fn agent_attribution_appends_agent_stats_before_the_collection() {
+    let program_id = Pubkey::new_unique();
+    let collection = Pubkey::new_unique();
+    let target = AttributionTarget {
+        collection: Some(collection),
+        ..AttributionTarget::new(Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), 60)
+    };
+    let metadata = AttributionMetadata { agent_id: Some([7; 32]), model_id: Some([8; 32]) };
+    let ix = record_attribution(&program_id, &Pubkey::new_unique(), &target, [4; 32], None, None, metadata);
+
+    assert!(matches!(
+        decode(&ix.data),
+        SolSageInstruction::RecordAttribution { agent_id: Some([7, ..]), model_id: Some([8, ..]), .. }
+    ));
+    assert_eq!(ix.accounts.len(), 15);
+    assert_eq!(ix.accounts[13].pubkey, find_agent_stats_address(&program_id, &[7; 32]).0);
+    assert_eq!(ix.accounts[14].pubkey, collection);
+}
+
+#[test]
+fn batch_collections_follow_the_quadruples_before_the_epoch() {
+    let program_id = Pubkey::new_unique();
+    let collection = find_collection_address(&program_id, &Pubkey::new_unique(), "Rust async book").0;
+    let targets: Vec<_> = [Some(collection), None]
+        .into_iter()
+        .map(|collection| AttributionTarget {
+            collection,
+            ..AttributionTarget::new(Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), 70)
+        })
+        .collect();
+    let ix = record_attribution_batch(&program_id, &Pubkey::new_unique(), [5; 32], &targets, Some(2));
+
+    assert_eq!(ix.accounts.len(), 9 + 4 * targets.len() + 1 + 1);
+    assert_eq!(ix.accounts[17].pubkey, collection);
+    assert_eq!(ix.accounts[18].pubkey, find_epoch_address(&program_id, 2).0);
+}
+
+#[test]
+fn co_author_accounts_precede_the_claimed_attributions() {
+    let program_id = Pubkey::new_unique();
+    let staker = Pubkey::new_unique();
+    let co_author_destinations = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let claims = [AttributionClaim { attribution: Pubkey::new_unique(), epoch: None }];
+    let ix = claim_rewards(
+        &program_id,
+        &staker,
+        &staker,
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &co_author_destinations,
+        &claims,
+    );
+
+    assert_eq!(ix.accounts.len(), 8 + 2 + 1);
+    assert_eq!(ix.accounts[8].pubkey, co_author_destinations[0]);
+    assert!(ix.accounts[9].is_writable && ix.accounts[9].pubkey == co_author_destinations[1]);
+    assert_eq!(ix.accounts[10].pubkey, claims[0].attribution);
+}
+
+#[test]
+fn payer_funded_rewards_flow_through_the_reward_vault() {
+    let program_id = Pubkey::new_unique();
+    let oracle = Pubkey::new_unique();
+    let payer_token_account = Pubkey::new_unique();
+    let reward_vault = find_reward_vault_address(&program_id).0;
+    let targets = [AttributionTarget::new(Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), 60)];
+
+    // Funding accounts trail everything else
+    let ix = with_reward_funding(
+        &program_id,
+        record_attribution_batch(&program_id, &oracle, [6; 32], &targets, None),
+        &payer_token_account,
+    );
+    assert_eq!(ix.accounts.len(), 9 + 4 + 3);
+    assert_eq!(ix.accounts[13].pubkey, payer_token_account);
+    assert!(ix.accounts[14].is_writable && ix.accounts[14].pubkey == reward_vault);
+
+    // Claims pay out of the vault where they would mint
+    let staker = Pubkey::new_unique();
+    let claims = [RewardClaim { knowledge_entry: Pubkey::new_unique(), attribution: Pubkey::new_unique(), epoch: None }];
+    let ix = paid_from_reward_vault(
+        &program_id,
+        claim_all_rewards(&program_id, &staker, &staker, &Pubkey::new_unique(), &claims),
+    );
+    assert_eq!(ix.accounts[2].pubkey, reward_vault);
+    assert!(ix.accounts.iter().all(|account| account.pubkey != find_mint_address(&program_id).0));
+
+    // A subscription funds rewards in place of the token accounts
+    let ix = with_subscription_funding(
+        &program_id,
+        record_attribution_batch(&program_id, &oracle, [6; 32], &targets, None),
+        &oracle,
+    );
+    assert_eq!(ix.accounts.len(), 9 + 4 + 1);
+    assert_eq!(ix.accounts[13].pubkey, find_subscription_address(&program_id, &oracle).0);
+    assert!(ix.accounts[13].is_writable);
+}
+
+#[test]
+fn oracle_proposals_append_the_oracle_registration() {
+    let program_id = Pubkey::new_unique();
+    let oracle = Pubkey::new_unique();
+
+    let ix = execute_proposal(&program_id, &Pubkey::new_unique(), 3, &ProposalAction::SetProtocolFee { fee_bps: 50 });
+    assert_eq!(ix.accounts.len(), 3);
+    assert_eq!(ix.accounts[2].pubkey, find_proposal_address(&program_id, 3).0);
+
+    let ix = execute_proposal(&program_id, &Pubkey::new_unique(), 3, &ProposalAction::AddOracle { oracle });
+    assert_eq!(ix.accounts.len(), 5);
+    assert!(ix.accounts[3].is_writable && ix.accounts[3].pubkey == find_oracle_address(&program_id, &oracle).0);
+
+    let ix = execute_proposal(&program_id, &Pubkey::new_unique(), 3, &ProposalAction::RemoveOracle { oracle });
+    assert_eq!(ix.accounts.len(), 4);
+}
+
+#[test]
+fn verified_attributions_pass_the_instructions_sysvar_after_their_fixed_accounts() {
+    let program_id = Pubkey::new_unique();
+    let (oracle, curator) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let target = AttributionTarget::new(Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), 60);
+
+    let ix = with_instructions_sysvar(record_attribution(
+        &program_id,
+        &oracle,
+        &target,
+        [4; 32],
+        None,
+        Some(curator),
+        AttributionMetadata::default(),
+    ));
+    assert_eq!(ix.accounts.len(), 15);
+    assert!(!ix.accounts[13].is_writable && ix.accounts[13].pubkey == sysvar::instructions::id());
+    assert_eq!(ix.accounts[14].pubkey, find_curator_address(&program_id, &curator).0);
+
+    let ix = with_instructions_sysvar(record_attribution_batch(&program_id, &oracle, [4; 32], &[target], None));
+    assert_eq!(ix.accounts[7].pubkey, find_oracle_state_address(&program_id, &oracle).0);
+    assert_eq!(ix.accounts[9].pubkey, sysvar::instructions::id());
+    assert_eq!(ix.accounts[10].pubkey, target.knowledge_entry);
+
+    // Instructions that aren't attributions are left as they are
+    let content = StakedContent { content_hash: [1; 32], title: "Pinning".to_string(), license: LicenseType::Mit };
+    let stake = stake_knowledge(&program_id, &target.staker, 0, content, 1, &target.category);
+    assert_eq!(with_instructions_sysvar(stake.clone()), stake);
+}
+
+#[test]
+fn relayed_attributions_are_paid_by_the_relayer_under_the_oracles_receipt() {
+    let program_id = Pubkey::new_unique();
+    let (oracle, relayer, agent_id) = (Pubkey::new_unique(), Pubkey::new_unique(), [6; 32]);
+    let target = AttributionTarget::new(Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), 60);
+    let metadata = AttributionMetadata { agent_id: Some(agent_id), model_id: None };
+
+    let ix = relayed_by(record_attribution(&program_id, &oracle, &target, [4; 32], None, None, metadata), &relayer, 9);
+    assert!(matches!(
+        decode(&ix.data),
+        SolSageInstruction::RecordSignedAttribution { relevance_score: 60, agent_id: Some(id), nonce: 9, .. } if id == agent_id
+    ));
+    assert!(ix.accounts[0].pubkey == relayer && ix.accounts[0].is_signer);
+    assert_eq!(ix.accounts[5].pubkey, find_oracle_address(&program_id, &oracle).0);
+    assert!(ix.accounts[11].is_writable && ix.accounts[11].pubkey == find_oracle_state_address(&program_id, &oracle).0);
+    // The relayer's own daily cap applies, not the oracle's
+    assert_eq!(ix.accounts[12].pubkey, find_payer_stats_address(&program_id, &relayer).0);
+    assert_eq!(ix.accounts[13].pubkey, sysvar::instructions::id());
+    assert_eq!(ix.accounts[14].pubkey, find_agent_stats_address(&program_id, &agent_id).0);
+
+    // The receipt already takes the instructions sysvar along
+    assert_eq!(with_instructions_sysvar(ix.clone()), ix);
+    let receipt = receipts::attribution_receipt(&[4; 32], &target.knowledge_entry, 60, 9);
+    let verify = receipts::verify_signature(&oracle, &[1; 64], &receipt);
+    assert_eq!(receipts::signed_message(&verify), Some((oracle, &receipt[..])));
+}
@@ -0,0 +1,681 @@
+//! Off-chain indexer for the SolSage native program.
+//!
+//! Follows the program's account updates and logs over a Solana websocket
+//! endpoint and mirrors knowledge entries, attributions and claims into
+//! SQLite, where the query functions on `Store` read them back. Entries and
+//! attribution state come from account updates; signatures, claims and
+//! closures from the `SolSageEvent`s the program logs.
+
+use std::{path::Path, str::FromStr, time::Duration};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use borsh::BorshDeserialize;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row, ToSql};
+use serde_json::{json, Value};
+use solana_program::pubkey::Pubkey;
+use solsage_client::{
+    knowledge_entries, solsage::SolSageEvent, Attribution, ClientError, KnowledgeEntry, ProgramAccount, RpcFilter,
+    SolSageClient,
+};
+use thiserror::Error;
+use tungstenite::Message;
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Error, Debug)]
+pub enum IndexerError {
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
+    #[error(transparent)]
+    WebSocket(Box<tungstenite::Error>),
+    #[error(transparent)]
+    Client(#[from] ClientError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("Malformed notification")]
+    InvalidNotification,
+}
+
+impl From<tungstenite::Error> for IndexerError {
+    fn from(e: tungstenite::Error) -> Self {
+        IndexerError::WebSocket(Box::new(e))
+    }
+}
+
+pub type IndexerResult<T> = Result<T, IndexerError>;
+
+// ============================================================================
+// STORE
+// ============================================================================
+
+/// Amounts and timestamps are stored as SQLite's signed integers; `u64`s
+/// round trip through their bit pattern
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS knowledge_entries (
+        address TEXT PRIMARY KEY,
+        staker TEXT NOT NULL,
+        category TEXT NOT NULL,
+        content_hash BLOB NOT NULL,
+        title TEXT NOT NULL,
+        is_active INTEGER NOT NULL,
+        stake_amount INTEGER NOT NULL,
+        total_attributions INTEGER NOT NULL,
+        pending_rewards INTEGER NOT NULL,
+        slot INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS knowledge_entries_by_staker ON knowledge_entries (staker);
+    CREATE INDEX IF NOT EXISTS knowledge_entries_by_category ON knowledge_entries (category);
+
+    CREATE TABLE IF NOT EXISTS attributions (
+        address TEXT PRIMARY KEY,
+        knowledge_entry TEXT NOT NULL,
+        query_hash BLOB NOT NULL,
+        relevance_score INTEGER NOT NULL,
+        reward INTEGER NOT NULL,
+        unlocks_at INTEGER NOT NULL,
+        reward_claimed INTEGER NOT NULL DEFAULT 0,
+        reversed INTEGER NOT NULL DEFAULT 0,
+        signature TEXT,
+        slot INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS attributions_by_entry ON attributions (knowledge_entry);
+    CREATE INDEX IF NOT EXISTS attributions_by_query ON attributions (query_hash);
+
+    CREATE TABLE IF NOT EXISTS claims (
+        signature TEXT NOT NULL,
+        event_index INTEGER NOT NULL,
+        staker TEXT NOT NULL,
+        amount INTEGER NOT NULL,
+        attributions INTEGER NOT NULL,
+        slot INTEGER NOT NULL,
+        PRIMARY KEY (signature, event_index)
+    );
+    CREATE INDEX IF NOT EXISTS claims_by_staker ON claims (staker);
+";
+
+/// How long a query waits on another connection's write lock
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const ENTRY_COLUMNS: &str =
+    "address, staker, category, content_hash, title, is_active, stake_amount, total_attributions, pending_rewards, slot";
+const ATTRIBUTION_COLUMNS: &str =
+    "address, knowledge_entry, query_hash, relevance_score, reward, unlocks_at, reward_claimed, reversed, signature, slot";
+const CLAIM_COLUMNS: &str = "signature, staker, amount, attributions, slot";
+
+/// A knowledge entry as last seen on chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryRow {
+    pub address: Pubkey,
+    pub staker: Pubkey,
+    pub category: Pubkey,
+    pub content_hash: [u8; 32],
+    pub title: String,
+    pub is_active: bool,
+    pub stake_amount: u64,
+    pub total_attributions: u64,
+    pub pending_rewards: u64,
+    /// Slot of the update the row reflects
+    pub slot: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributionRow {
+    pub address: Pubkey,
+    pub knowledge_entry: Pubkey,
+    pub query_hash: [u8; 32],
+    pub relevance_score: u8,
+    pub reward: u64,
+    pub unlocks_at: i64,
+    pub reward_claimed: bool,
+    pub reversed: bool,
+    /// Transaction that recorded the attribution, once its log was seen
+    pub signature: Option<String>,
+    pub slot: u64,
+}
+
+/// One `RewardsClaimed` event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimRow {
+    pub signature: String,
+    pub staker: Pubkey,
+    pub amount: u64,
+    pub attributions: u64,
+    pub slot: u64,
+}
+
+/// One page of query results: at most `limit` rows, after skipping
+/// `offset`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page {
+    pub limit: usize,
+    pub offset: usize,
+}
+
+impl Page {
+    pub const ALL: Page = Page { limit: usize::MAX, offset: 0 };
+}
+
+/// Filters for `Store::knowledge_entries`; unset fields match every entry
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EntryFilter {
+    pub staker: Option<Pubkey>,
+    pub category: Option<Pubkey>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryOrder {
+    Address,
+    /// Most attributed first, ties by address
+    MostAttributed,
+}
+
+/// Filters for `Store::attributions`; unset fields match every attribution
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AttributionFilter {
+    pub knowledge_entry: Option<Pubkey>,
+    pub query_hash: Option<[u8; 32]>,
+}
+
+/// SQLite mirror of the program's entries, attributions and claims
+pub struct Store {
+    connection: Connection,
+}
+
+impl Store {
+    pub fn open(path: impl AsRef<Path>) -> IndexerResult<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> IndexerResult<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(connection: Connection) -> IndexerResult<Self> {
+        // The indexer writes while API servers read the same file
+        connection.busy_timeout(BUSY_TIMEOUT)?;
+        connection.execute_batch("PRAGMA journal_mode = WAL;")?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self { connection })
+    }
+
+    /// The underlying connection, for queries the functions below don't
+    /// cover
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// Updates older than the stored row's slot are ignored, so replays and
+    /// out-of-order notifications can't roll an entry back
+    pub fn upsert_knowledge_entry(&self, address: &Pubkey, entry: &KnowledgeEntry, slot: u64) -> IndexerResult<()> {
+        self.connection.execute(
+            &format!(
+                "INSERT INTO knowledge_entries ({ENTRY_COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                 ON CONFLICT (address) DO UPDATE SET
+                     staker = excluded.staker, category = excluded.category,
+                     content_hash = excluded.content_hash, title = excluded.title,
+                     is_active = excluded.is_active, stake_amount = excluded.stake_amount,
+                     total_attributions = excluded.total_attributions,
+                     pending_rewards = excluded.pending_rewards, slot = excluded.slot
+                 WHERE excluded.slot >= knowledge_entries.slot"
+            ),
+            params![
+                address.to_string(),
+                entry.staker.to_string(),
+                entry.category.to_string(),
+                &entry.content_hash[..],
+                entry.title,
+                entry.is_active,
+                entry.stake_amount as i64,
+                entry.total_attributions as i64,
+                entry.pending_rewards as i64,
+                slot as i64,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Keeps the signature an `AttributionRecorded` event filled in
+    pub fn upsert_attribution(&self, address: &Pubkey, attribution: &Attribution, slot: u64) -> IndexerResult<()> {
+        self.connection.execute(
+            &format!(
+                "INSERT INTO attributions ({ATTRIBUTION_COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, NULL, ?9)
+                 ON CONFLICT (address) DO UPDATE SET
+                     relevance_score = excluded.relevance_score, reward = excluded.reward,
+                     unlocks_at = excluded.unlocks_at, reward_claimed = excluded.reward_claimed,
+                     reversed = excluded.reversed, slot = excluded.slot
+                 WHERE excluded.slot >= attributions.slot"
+            ),
+            params![
+                address.to_string(),
+                attribution.knowledge_entry.to_string(),
+                &attribution.query_hash[..],
+                attribution.relevance_score,
+                attribution.reward as i64,
+                attribution.unlocks_at,
+                attribution.reward_claimed,
+                attribution.reversed,
+                slot as i64,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Apply the events one transaction logged. Recording the same
+    /// transaction twice changes nothing.
+    pub fn record_events(&self, signature: &str, slot: u64, events: &[SolSageEvent]) -> IndexerResult<()> {
+        for (index, event) in events.iter().enumerate() {
+            match event {
+                SolSageEvent::AttributionRecorded {
+                    knowledge_entry,
+                    attribution,
+                    query_hash,
+                    relevance_score,
+                    reward,
+                    unlocks_at,
+                    ..
+                } => {
+                    self.connection.execute(
+                        &format!(
+                            "INSERT INTO attributions ({ATTRIBUTION_COLUMNS})
+                             VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0, 0, ?7, ?8)
+                             ON CONFLICT (address) DO UPDATE SET signature = excluded.signature"
+                        ),
+                        params![
+                            attribution.to_string(),
+                            knowledge_entry.to_string(),
+                            &query_hash[..],
+                            relevance_score,
+                            *reward as i64,
+                            unlocks_at,
+                            signature,
+                            slot as i64,
+                        ],
+                    )?;
+                }
+                SolSageEvent::RewardsClaimed { staker, amount, attributions } => {
+                    self.connection.execute(
+                        "INSERT OR IGNORE INTO claims (signature, event_index, staker, amount, attributions, slot)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                        params![
+                            signature,
+                            index as i64,
+                            staker.to_string(),
+                            *amount as i64,
+                            *attributions as i64,
+                            slot as i64,
+                        ],
+                    )?;
+                }
+                SolSageEvent::KnowledgeEntryClosed { knowledge_entry } => {
+                    self.connection.execute(
+                        "DELETE FROM knowledge_entries WHERE address = ?1",
+                        params![knowledge_entry.to_string()],
+                    )?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn knowledge_entry(&self, address: &Pubkey) -> IndexerResult<Option<EntryRow>> {
+        Ok(self
+            .connection
+            .query_row(
+                &format!("SELECT {ENTRY_COLUMNS} FROM knowledge_entries WHERE address = ?1"),
+                params![address.to_string()],
+                entry_row,
+            )
+            .optional()?)
+    }
+
+    /// Entries matching `filter`, one `page` of them in `order`
+    pub fn knowledge_entries(&self, filter: &EntryFilter, order: EntryOrder, page: Page) -> IndexerResult<Vec<EntryRow>> {
+        let mut conditions = Vec::new();
+        let mut values: Vec<Box<dyn ToSql>> = Vec::new();
+        if let Some(staker) = filter.staker {
+            values.push(Box::new(staker.to_string()));
+            conditions.push(format!("staker = ?{}", values.len()));
+        }
+        if let Some(category) = filter.category {
+            values.push(Box::new(category.to_string()));
+            conditions.push(format!("category = ?{}", values.len()));
+        }
+        if let Some(active) = filter.active {
+            values.push(Box::new(active));
+            conditions.push(format!("is_active = ?{}", values.len()));
+        }
+        let order = match order {
+            EntryOrder::Address => "address",
+            EntryOrder::MostAttributed => "total_attributions DESC, address",
+        };
+        self.query_page(
+            &format!("SELECT {ENTRY_COLUMNS} FROM knowledge_entries{} ORDER BY {order}", where_clause(&conditions)),
+            values,
+            page,
+            entry_row,
+        )
+    }
+
+    /// Entries currently owned by `staker`
+    pub fn knowledge_entries_by_staker(&self, staker: &Pubkey) -> IndexerResult<Vec<EntryRow>> {
+        let filter = EntryFilter { staker: Some(*staker), ..EntryFilter::default() };
+        self.knowledge_entries(&filter, EntryOrder::Address, Page::ALL)
+    }
+
+    /// Active entries filed under `category`, most attributed first
+    pub fn active_knowledge_entries_by_category(&self, category: &Pubkey) -> IndexerResult<Vec<EntryRow>> {
+        let filter = EntryFilter { category: Some(*category), active: Some(true), ..EntryFilter::default() };
+        self.knowledge_entries(&filter, EntryOrder::MostAttributed, Page::ALL)
+    }
+
+    /// The `limit` most attributed entries, inactive ones included
+    pub fn top_knowledge_entries(&self, limit: usize) -> IndexerResult<Vec<EntryRow>> {
+        self.knowledge_entries(&EntryFilter::default(), EntryOrder::MostAttributed, Page { limit, offset: 0 })
+    }
+
+    /// Attributions matching `filter`, oldest first
+    pub fn attributions(&self, filter: &AttributionFilter, page: Page) -> IndexerResult<Vec<AttributionRow>> {
+        let mut conditions = Vec::new();
+        let mut values: Vec<Box<dyn ToSql>> = Vec::new();
+        if let Some(knowledge_entry) = filter.knowledge_entry {
+            values.push(Box::new(knowledge_entry.to_string()));
+            conditions.push(format!("knowledge_entry = ?{}", values.len()));
+        }
+        if let Some(query_hash) = filter.query_hash {
+            values.push(Box::new(query_hash.to_vec()));
+            conditions.push(format!("query_hash = ?{}", values.len()));
+        }
+        self.query_page(
+            &format!("SELECT {ATTRIBUTION_COLUMNS} FROM attributions{} ORDER BY slot, address", where_clause(&conditions)),
+            values,
+            page,
+            attribution_row,
+        )
+    }
+
+    /// Every attribution recorded against `knowledge_entry`, oldest first
+    pub fn attributions_by_entry(&self, knowledge_entry: &Pubkey) -> IndexerResult<Vec<AttributionRow>> {
+        let filter = AttributionFilter { knowledge_entry: Some(*knowledge_entry), ..AttributionFilter::default() };
+        self.attributions(&filter, Page::ALL)
+    }
+
+    /// Claims `staker` made, oldest first
+    pub fn claims_by_staker(&self, staker: &Pubkey, page: Page) -> IndexerResult<Vec<ClaimRow>> {
+        self.query_page(
+            &format!("SELECT {CLAIM_COLUMNS} FROM claims WHERE staker = ?1 ORDER BY slot, signature, event_index"),
+            vec![Box::new(staker.to_string())],
+            page,
+            claim_row,
+        )
+    }
+
+    /// SAGE `staker` has claimed in total
+    pub fn total_claimed(&self, staker: &Pubkey) -> IndexerResult<u64> {
+        let total: i64 = self.connection.query_row(
+            "SELECT COALESCE(SUM(amount), 0) FROM claims WHERE staker = ?1",
+            params![staker.to_string()],
+            |row| row.get(0),
+        )?;
+        Ok(total as u64)
+    }
+
+    /// Run `sql` with `values` bound in order, then one `page` of it
+    fn query_page<T>(
+        &self,
+        sql: &str,
+        mut values: Vec<Box<dyn ToSql>>,
+        page: Page,
+        map: fn(&Row) -> rusqlite::Result<T>,
+    ) -> IndexerResult<Vec<T>> {
+        let sql = format!("{sql} LIMIT ?{} OFFSET ?{}", values.len() + 1, values.len() + 2);
+        values.push(Box::new(page.limit.min(i64::MAX as usize) as i64));
+        values.push(Box::new(page.offset as i64));
+        let mut statement = self.connection.prepare(&sql)?;
+        let rows = statement.query_map(params_from_iter(values), map)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+}
+
+fn where_clause(conditions: &[String]) -> String {
+    match conditions.is_empty() {
+        true => String::new(),
+        false => format!(" WHERE {}", conditions.join(" AND ")),
+    }
+}
+
+fn entry_row(row: &Row) -> rusqlite::Result<EntryRow> {
+    Ok(EntryRow {
+        address: pubkey_column(row, 0)?,
+        staker: pubkey_column(row, 1)?,
+        category: pubkey_column(row, 2)?,
+        content_hash: hash_column(row, 3)?,
+        title: row.get(4)?,
+        is_active: row.get(5)?,
+        stake_amount: row.get::<_, i64>(6)? as u64,
+        total_attributions: row.get::<_, i64>(7)? as u64,
+        pending_rewards: row.get::<_, i64>(8)? as u64,
+        slot: row.get::<_, i64>(9)? as u64,
+    })
+}
+
+fn attribution_row(row: &Row) -> rusqlite::Result<AttributionRow> {
+    Ok(AttributionRow {
+        address: pubkey_column(row, 0)?,
+        knowledge_entry: pubkey_column(row, 1)?,
+        query_hash: hash_column(row, 2)?,
+        relevance_score: row.get(3)?,
+        reward: row.get::<_, i64>(4)? as u64,
+        unlocks_at: row.get(5)?,
+        reward_claimed: row.get(6)?,
+        reversed: row.get(7)?,
+        signature: row.get(8)?,
+        slot: row.get::<_, i64>(9)? as u64,
+    })
+}
+
+fn claim_row(row: &Row) -> rusqlite::Result<ClaimRow> {
+    Ok(ClaimRow {
+        signature: row.get(0)?,
+        staker: pubkey_column(row, 1)?,
+        amount: row.get::<_, i64>(2)? as u64,
+        attributions: row.get::<_, i64>(3)? as u64,
+        slot: row.get::<_, i64>(4)? as u64,
+    })
+}
+
+fn pubkey_column(row: &Row, index: usize) -> rusqlite::Result<Pubkey> {
+    let text: String = row.get(index)?;
+    Pubkey::from_str(&text).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+fn hash_column(row: &Row, index: usize) -> rusqlite::Result<[u8; 32]> {
+    let blob: Vec<u8> = row.get(index)?;
+    blob.try_into().map_err(|_| rusqlite::Error::InvalidColumnType(index, "hash".to_string(), rusqlite::types::Type::Blob))
+}
+
+// ============================================================================
+// DECODING
+// ============================================================================
+
+/// Program account state the indexer mirrors
+#[derive(Debug, Clone)]
+pub enum IndexedAccount {
+    KnowledgeEntry(Box<KnowledgeEntry>),
+    Attribution(Box<Attribution>),
+}
+
+/// Decode a program account's `data`, told apart by its discriminator.
+/// Entries still awaiting `MigrateAccount` and every other account type
+/// decode to `None`.
+pub fn decode_account(data: &[u8]) -> Option<IndexedAccount> {
+    match *data.first()? {
+        KnowledgeEntry::DISCRIMINATOR => {
+            KnowledgeEntry::unpack(data).ok().map(|entry| IndexedAccount::KnowledgeEntry(Box::new(entry)))
+        }
+        Attribution::DISCRIMINATOR => Attribution::unpack(data).ok().map(|attribution| IndexedAccount::Attribution(Box::new(attribution))),
+        _ => None,
+    }
+}
+
+/// Events `program_id` logged, in order. Data other programs logged,
+/// including ones SolSage invokes, is skipped by following the invoke and
+/// return lines around it.
+pub fn parse_events<S: AsRef<str>>(program_id: &Pubkey, logs: &[S]) -> Vec<SolSageEvent> {
+    let program = program_id.to_string();
+    let mut invoked = Vec::new();
+    let mut events = Vec::new();
+    for line in logs.iter().map(AsRef::as_ref) {
+        if let Some(data) = line.strip_prefix("Program data: ") {
+            if invoked.last() != Some(&true) {
+                continue;
+            }
+            let data = data.split(' ').next().and_then(|field| BASE64.decode(field).ok());
+            events.extend(data.and_then(|data| SolSageEvent::try_from_slice(&data).ok()));
+        } else if let Some(rest) = line.strip_prefix("Program ") {
+            let mut words = rest.split(' ');
+            let (id, action) = (words.next(), words.next());
+            match action {
+                Some("invoke") => invoked.push(id == Some(program.as_str())),
+                Some("success" | "failed:") => {
+                    invoked.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    events
+}
+
+// ============================================================================
+// INDEXER
+// ============================================================================
+
+/// Keeps a `Store` in step with one deployment of the program
+pub struct Indexer {
+    program_id: Pubkey,
+    store: Store,
+}
+
+impl Indexer {
+    pub fn new(program_id: Pubkey, store: Store) -> Self {
+        Self { program_id, store }
+    }
+
+    pub fn program_id(&self) -> &Pubkey {
+        &self.program_id
+    }
+
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    /// Mirror one program account as of `slot`; accounts the indexer
+    /// doesn't track are ignored
+    pub fn apply_account(&self, address: &Pubkey, data: &[u8], slot: u64) -> IndexerResult<()> {
+        match decode_account(data) {
+            Some(IndexedAccount::KnowledgeEntry(entry)) => self.store.upsert_knowledge_entry(address, &entry, slot),
+            Some(IndexedAccount::Attribution(attribution)) => {
+                self.store.upsert_attribution(address, &attribution, slot)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Record the events a transaction logged. Failed transactions logged
+    /// events for changes that were rolled back, so they're skipped.
+    pub fn apply_logs<S: AsRef<str>>(&self, signature: &str, slot: u64, logs: &[S], failed: bool) -> IndexerResult<()> {
+        if failed {
+            return Ok(());
+        }
+        self.store.record_events(signature, slot, &parse_events(&self.program_id, logs))
+    }
+
+    /// Load every current entry and attribution over RPC, so the mirror is
+    /// complete before the subscription takes over. Returns how many
+    /// accounts were loaded.
+    pub fn backfill(&self, client: &SolSageClient) -> IndexerResult<usize> {
+        // Anything the subscription delivers after this slot supersedes the
+        // snapshot
+        let slot = client.fetch_slot()?;
+        let entries: Vec<(Pubkey, KnowledgeEntry)> = client.fetch_program_accounts(&knowledge_entries())?;
+        for (address, entry) in &entries {
+            self.store.upsert_knowledge_entry(address, entry, slot)?;
+        }
+        let attributions: Vec<(Pubkey, Attribution)> =
+            client.fetch_program_accounts(&[
+                RpcFilter::DataSize(Attribution::LEN),
+                RpcFilter::memcmp(0, &[Attribution::DISCRIMINATOR]),
+            ])?;
+        for (address, attribution) in &attributions {
+            self.store.upsert_attribution(address, attribution, slot)?;
+        }
+        Ok(entries.len() + attributions.len())
+    }
+
+    /// Subscribe to the program's accounts and logs at `ws_url` and apply
+    /// every notification until the server closes the connection
+    pub fn run(&self, ws_url: &str) -> IndexerResult<()> {
+        let (mut socket, _) = tungstenite::connect(ws_url)?;
+        let program = self.program_id.to_string();
+        let subscriptions = [
+            json!({
+                "jsonrpc": "2.0", "id": 1, "method": "programSubscribe",
+                "params": [program, { "encoding": "base64", "commitment": "confirmed" }],
+            }),
+            json!({
+                "jsonrpc": "2.0", "id": 2, "method": "logsSubscribe",
+                "params": [{ "mentions": [program] }, { "commitment": "confirmed" }],
+            }),
+        ];
+        for subscription in subscriptions {
+            socket.send(Message::Text(subscription.to_string()))?;
+        }
+
+        loop {
+            match socket.read()? {
+                Message::Text(text) => self.handle_notification(&serde_json::from_str(&text)?)?,
+                Message::Close(_) => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+
+    /// Apply one `programNotification` or `logsNotification`. Subscription
+    /// confirmations and other messages are ignored.
+    pub fn handle_notification(&self, message: &Value) -> IndexerResult<()> {
+        let result = &message["params"]["result"];
+        let slot = result["context"]["slot"].as_u64();
+        let value = &result["value"];
+        match message["method"].as_str() {
+            Some("programNotification") => {
+                let address = value["pubkey"]
+                    .as_str()
+                    .and_then(|address| Pubkey::from_str(address).ok())
+                    .ok_or(IndexerError::InvalidNotification)?;
+                let data = value["account"]["data"][0]
+                    .as_str()
+                    .and_then(|data| BASE64.decode(data).ok())
+                    .ok_or(IndexerError::InvalidNotification)?;
+                self.apply_account(&address, &data, slot.ok_or(IndexerError::InvalidNotification)?)
+            }
+            Some("logsNotification") => {
+                let signature = value["signature"].as_str().ok_or(IndexerError::InvalidNotification)?;
+                let logs = value["logs"]
+                    .as_array()
+                    .ok_or(IndexerError::InvalidNotification)?
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>();
+                let failed = !value["err"].is_null();
+                self.apply_logs(signature, slot.ok_or(IndexerError::InvalidNotification)?, &logs, failed)
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! `solsage-indexer <program-id> <rpc-url> <ws-url> <database>`
+//!
+//! Backfills the SQLite database at `<database>` over `<rpc-url>`, then
+//! follows the program over `<ws-url>`, backfilling again each time the
+//! connection drops so nothing missed while reconnecting is lost.
+
+use std::{env, process::ExitCode, str::FromStr, thread, time::Duration};
+
+use solana_program::pubkey::Pubkey;
+use solsage_client::SolSageClient;
+use solsage_indexer::{Indexer, Store};
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let [program_id, rpc_url, ws_url, database] = &args[..] else {
+        eprintln!("usage: solsage-indexer <program-id> <rpc-url> <ws-url> <database>");
+        return ExitCode::FAILURE;
+    };
+    let Ok(program_id) = Pubkey::from_str(program_id) else {
+        eprintln!("invalid program id: {program_id}");
+        return ExitCode::FAILURE;
+    };
+    let indexer = match Store::open(database) {
+        Ok(store) => Indexer::new(program_id, store),
+        Err(e) => {
+            eprintln!("failed to open {database}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let client = SolSageClient::new(rpc_url.as_str(), program_id);
+
+    loop {
+        match indexer.backfill(&client) {
+            Ok(loaded) => println!("backfilled {loaded} accounts"),
+            Err(e) => eprintln!("backfill failed: {e}"),
+        }
+        match indexer.run(ws_url) {
+            Ok(()) => eprintln!("subscription closed, reconnecting"),
+            Err(e) => eprintln!("subscription failed: {e}, reconnecting"),
+        }
+        thread::sleep(RECONNECT_DELAY);
+    }
+}
@@ -0,0 +1,226 @@
+use std::slice;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use borsh::BorshSerialize;
+use serde_json::json;
+use solana_program::pubkey::Pubkey;
+use solsage_client::{
+    solsage::{SolSageEvent, VestingBucket},
+    Attribution, KnowledgeEntry, LicenseType, LockupTier, ProgramAccount, Visibility, KNOWLEDGE_ENTRY_SCHEMA_VERSION,
+};
+use solsage_indexer::{parse_events, Indexer, Page, Store};
+
+fn entry(staker: Pubkey, category: Pubkey, total_attributions: u64) -> KnowledgeEntry {
+    KnowledgeEntry {
+        is_initialized: true,
+        schema_version: KNOWLEDGE_ENTRY_SCHEMA_VERSION,
+        staker,
+        creator: staker,
+        entry_index: 0,
+        content_hash: [7; 32],
+        category,
+        is_active: true,
+        license: LicenseType::Mit,
+        title: "Async Rust".to_string(),
+        content_uri: None,
+        collection: None,
+        royalty_splits: Vec::new(),
+        created_at: 0,
+        updated_at: 0,
+        expires_at: 0,
+        last_attribution_at: 0,
+        version: 1,
+        total_attributions,
+        pending_rewards: 0,
+        vesting_schedule: [VestingBucket::default(); KnowledgeEntry::VESTING_BUCKETS],
+        stake_amount: 1_000,
+        bump: 0,
+        escrow_bump: 0,
+        tags: Vec::new(),
+        min_reward_per_use: 0,
+        lockup_tier: LockupTier::None,
+        locked_until: 0,
+        unstake_requested_at: 0,
+        receipt_asset_id: None,
+        quality_score: 0,
+        under_review: false,
+        reward_stream: Default::default(),
+        derived_from: Vec::new(),
+        citation_royalties: 0,
+        citation_rewards: 0,
+        document: None,
+        chunk_index: 0,
+        chunks: None,
+        embedding_fingerprint: None,
+        attestation: None,
+        verified: false,
+        identity_credential: None,
+        visibility: Visibility::Public,
+    }
+}
+
+fn attribution(knowledge_entry: Pubkey, reward_claimed: bool) -> Attribution {
+    Attribution {
+        is_initialized: true,
+        schema_version: 1,
+        knowledge_entry,
+        query_hash: [9; 32],
+        relevance_score: 80,
+        reward: 500,
+        timestamp: 0,
+        unlocks_at: 100,
+        reward_claimed,
+        reversed: false,
+        epoch: None,
+        bump: 0,
+        agent_id: None,
+        model_id: None,
+        pending: false,
+        votes: Vec::new(),
+        streamed: false,
+        chunk_index: None,
+    }
+}
+
+/// Account data as the program allocates it, at the type's full size
+fn account_data(state: &impl ProgramAccount, len: usize) -> Vec<u8> {
+    let mut data = vec![0; len];
+    state.pack_into(&mut data).unwrap();
+    data
+}
+
+fn event_log(event: &SolSageEvent) -> String {
+    format!("Program data: {}", BASE64.encode(event.try_to_vec().unwrap()))
+}
+
+fn indexer() -> (Indexer, Pubkey) {
+    let program_id = Pubkey::new_unique();
+    (Indexer::new(program_id, Store::open_in_memory().unwrap()), program_id)
+}
+
+#[test]
+fn entries_follow_account_updates_but_never_roll_back() {
+    let (indexer, _) = indexer();
+    let (address, staker, category) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    indexer.apply_account(&address, &account_data(&entry(staker, category, 3), KnowledgeEntry::LEN), 10).unwrap();
+    // An older update arriving late is ignored
+    indexer.apply_account(&address, &account_data(&entry(staker, category, 1), KnowledgeEntry::LEN), 9).unwrap();
+
+    let store = indexer.store();
+    let row = store.knowledge_entry(&address).unwrap().unwrap();
+    assert_eq!((row.total_attributions, row.stake_amount, row.slot), (3, 1_000, 10));
+    assert_eq!(store.knowledge_entries_by_staker(&staker).unwrap(), slice::from_ref(&row));
+    assert_eq!(store.active_knowledge_entries_by_category(&category).unwrap(), slice::from_ref(&row));
+    assert_eq!(store.top_knowledge_entries(5).unwrap(), [row]);
+
+    // Accounts of other types, or awaiting migration, aren't mirrored
+    indexer.apply_account(&Pubkey::new_unique(), &[1; 64], 11).unwrap();
+    let mut legacy = account_data(&entry(staker, category, 0), KnowledgeEntry::LEN - 8);
+    // Written before accounts were tagged with their type
+    legacy[0] = 1;
+    indexer.apply_account(&Pubkey::new_unique(), &legacy, 11).unwrap();
+    assert_eq!(store.top_knowledge_entries(5).unwrap().len(), 1);
+}
+
+#[test]
+fn events_fill_in_signatures_claims_and_closures() {
+    let (indexer, program_id) = indexer();
+    let (knowledge_entry, address, staker) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let other_program = Pubkey::new_unique();
+    let recorded = SolSageEvent::AttributionRecorded {
+        knowledge_entry,
+        attribution: address,
+        query_hash: [9; 32],
+        relevance_score: 80,
+        reward: 500,
+        unlocks_at: 100,
+        license: LicenseType::Mit,
+    };
+    let claimed = SolSageEvent::RewardsClaimed { staker, amount: 500, attributions: 1 };
+    let logs = [
+        format!("Program {program_id} invoke [1]"),
+        event_log(&recorded),
+        format!("Program {other_program} invoke [2]"),
+        // Data another program logs mid-instruction isn't a SolSage event
+        event_log(&SolSageEvent::RewardsClaimed { staker, amount: 1, attributions: 1 }),
+        format!("Program {other_program} success"),
+        event_log(&claimed),
+        format!("Program {program_id} success"),
+    ];
+    assert_eq!(parse_events(&program_id, &logs), [recorded, claimed]);
+
+    // The signature survives the account update, and replays add nothing
+    indexer.apply_logs("sig-1", 20, &logs, false).unwrap();
+    indexer.apply_account(&address, &account_data(&attribution(knowledge_entry, true), Attribution::LEN), 21).unwrap();
+    indexer.apply_logs("sig-1", 20, &logs, false).unwrap();
+    // Failed transactions rolled their events back
+    indexer.apply_logs("sig-2", 22, &logs, true).unwrap();
+
+    let store = indexer.store();
+    let attributions = store.attributions_by_entry(&knowledge_entry).unwrap();
+    assert_eq!(attributions.len(), 1);
+    assert_eq!(attributions[0].signature.as_deref(), Some("sig-1"));
+    assert!(attributions[0].reward_claimed);
+    assert_eq!(store.claims_by_staker(&staker, Page::ALL).unwrap().len(), 1);
+    assert_eq!(store.total_claimed(&staker).unwrap(), 500);
+
+    let category = Pubkey::new_unique();
+    indexer.apply_account(&knowledge_entry, &account_data(&entry(staker, category, 1), KnowledgeEntry::LEN), 23).unwrap();
+    let closed = [
+        format!("Program {program_id} invoke [1]"),
+        event_log(&SolSageEvent::KnowledgeEntryClosed { knowledge_entry }),
+        format!("Program {program_id} success"),
+    ];
+    indexer.apply_logs("sig-3", 24, &closed, false).unwrap();
+    assert_eq!(store.knowledge_entry(&knowledge_entry).unwrap(), None);
+}
+
+#[test]
+fn websocket_notifications_are_applied() {
+    let (indexer, program_id) = indexer();
+    let (address, staker) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let data = account_data(&entry(staker, Pubkey::new_unique(), 2), KnowledgeEntry::LEN);
+    indexer
+        .handle_notification(&json!({
+            "jsonrpc": "2.0",
+            "method": "programNotification",
+            "params": {
+                "result": {
+                    "context": { "slot": 30 },
+                    "value": {
+                        "pubkey": address.to_string(),
+                        "account": { "data": [BASE64.encode(&data), "base64"], "owner": program_id.to_string() },
+                    },
+                },
+                "subscription": 1,
+            },
+        }))
+        .unwrap();
+    indexer
+        .handle_notification(&json!({
+            "jsonrpc": "2.0",
+            "method": "logsNotification",
+            "params": {
+                "result": {
+                    "context": { "slot": 31 },
+                    "value": {
+                        "signature": "sig",
+                        "err": null,
+                        "logs": [
+                            format!("Program {program_id} invoke [1]"),
+                            event_log(&SolSageEvent::RewardsClaimed { staker, amount: 42, attributions: 2 }),
+                            format!("Program {program_id} success"),
+                        ],
+                    },
+                },
+                "subscription": 2,
+            },
+        }))
+        .unwrap();
+    // Subscription confirmations carry nothing to apply
+    indexer.handle_notification(&json!({ "jsonrpc": "2.0", "result": 1, "id": 1 })).unwrap();
+
+    let store = indexer.store();
+    assert_eq!(store.knowledge_entry(&address).unwrap().unwrap().total_attributions, 2);
+    assert_eq!(store.total_claimed(&staker).unwrap(), 42);
+}
@@ -0,0 +1,205 @@
+//! Seeded sample data for local SolSage deployments.
+//!
+//! `Fixtures::generate` turns a seed into categories, staked entries and
+//! attributions; the same seed always yields the same data. The
+//! `solsage-sim` binary sends them to a local validator so frontends and
+//! agents have something realistic to read, and the tests replay them on
+//! the in-process runtime.
+
+use solana_program::{hash::hashv, instruction::Instruction, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use solsage_client::{
+    add_oracle, create_category, find_category_address, find_knowledge_entry_address, initialize, initialize_mint,
+    record_attribution, stake_knowledge, AttributionMetadata, AttributionTarget, LicenseType, StakedContent,
+};
+use solsage_core::MAX_RELEVANCE_SCORE;
+
+/// SAGE the sample mint can ever issue, in base units
+pub const MAX_SUPPLY: u64 = 1_000_000_000 * 1_000_000;
+
+/// Categories sample entries are spread across, with the topics their
+/// titles are drawn from
+const TOPICS: &[(&str, &[&str])] = &[
+    ("solana-basics", &["RPC connections", "account rent", "program derived addresses", "transaction fees"]),
+    ("defi", &["token swaps", "liquidity pools", "liquid staking", "lending health factors"]),
+    ("rust", &["async runtimes", "error handling", "borrow checker patterns", "Borsh serialization"]),
+    ("security", &["signer checks", "PDA validation", "integer overflow", "reentrancy through CPI"]),
+    ("ai-agents", &["tool calling", "retrieval pipelines", "prompt caching", "agent wallets"]),
+];
+
+const FORMATS: &[&str] = &["A guide to", "Debugging", "Best practices for", "Benchmarks of", "FAQ:"];
+
+const LICENSES: &[LicenseType] = &[LicenseType::Mit, LicenseType::CcBy, LicenseType::Proprietary];
+
+/// Lowest relevance score a sample attribution gets
+const MIN_SCORE: u8 = 40;
+
+/// How much sample data to generate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixtureConfig {
+    pub seed: u64,
+    pub stakers: usize,
+    pub entries: usize,
+    pub attributions: usize,
+}
+
+impl Default for FixtureConfig {
+    fn default() -> Self {
+        Self { seed: 0, stakers: 4, entries: 24, attributions: 120 }
+    }
+}
+
+/// One entry to stake
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StakeFixture {
+    /// Index into the stakers the fixtures are seeded with
+    pub staker: usize,
+    /// The staker's entry index; entries are numbered per staker from 0
+    pub entry_index: u64,
+    pub category: &'static str,
+    pub content: StakedContent,
+    pub stake_amount: u64,
+}
+
+/// One attribution to record against `entry`, an index into the stakes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttributionFixture {
+    pub entry: usize,
+    pub query_hash: [u8; 32],
+    pub relevance_score: u8,
+}
+
+/// Sample data for one deployment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fixtures {
+    pub config: FixtureConfig,
+    pub stakes: Vec<StakeFixture>,
+    pub attributions: Vec<AttributionFixture>,
+}
+
+impl Fixtures {
+    /// Entries go round robin over the stakers and categories. Attributions
+    /// favour the first entries of each category, so some entries end up
+    /// popular and most see little use, as on a live deployment.
+    pub fn generate(config: FixtureConfig) -> Self {
+        let mut rng = SplitMix64(config.seed);
+        let mut entry_counts = vec![0; config.stakers.max(1)];
+        let stakes = (0..config.entries)
+            .map(|index| {
+                let staker = index % entry_counts.len();
+                let (category, topics) = TOPICS[index % TOPICS.len()];
+                let topic = topics[rng.below(topics.len())];
+                let title = format!("{} {topic} #{index}", FORMATS[rng.below(FORMATS.len())]);
+                let stake = StakeFixture {
+                    staker,
+                    entry_index: entry_counts[staker],
+                    category,
+                    content: StakedContent {
+                        content_hash: hashv(&[title.as_bytes(), &config.seed.to_le_bytes()]).to_bytes(),
+                        title,
+                        license: LICENSES[rng.below(LICENSES.len())].clone(),
+                    },
+                    // 0.1 to 2 SOL
+                    stake_amount: (1 + rng.below(20)) as u64 * LAMPORTS_PER_SOL / 10,
+                };
+                entry_counts[staker] += 1;
+                stake
+            })
+            .collect();
+
+        let attributions = match config.entries {
+            0 => Vec::new(),
+            entries => (0..config.attributions)
+                .map(|index| {
+                    // The product of two uniform draws skews toward 0
+                    let entry = rng.below(entries) * rng.below(entries) / entries;
+                    AttributionFixture {
+                        entry,
+                        query_hash: hashv(&[b"query", &index.to_le_bytes(), &config.seed.to_le_bytes()]).to_bytes(),
+                        relevance_score: MIN_SCORE + rng.below((MAX_RELEVANCE_SCORE - MIN_SCORE + 1) as usize) as u8,
+                    }
+                })
+                .collect(),
+        };
+        Self { config, stakes, attributions }
+    }
+
+    /// Distinct categories the stakes use, in first-use order
+    pub fn categories(&self) -> Vec<&'static str> {
+        let mut categories = Vec::new();
+        for stake in &self.stakes {
+            if !categories.contains(&stake.category) {
+                categories.push(stake.category);
+            }
+        }
+        categories
+    }
+
+    /// The entry `stake` creates, staked by `stakers[stake.staker]`
+    pub fn entry_address(&self, program_id: &Pubkey, stakers: &[Pubkey], stake: &StakeFixture) -> Pubkey {
+        find_knowledge_entry_address(program_id, &stakers[stake.staker], stake.entry_index).0
+    }
+
+    /// Initialize the protocol and its mint, create the categories and
+    /// register `oracle`; `authority` signs each
+    pub fn setup_instructions(&self, program_id: &Pubkey, authority: &Pubkey, oracle: &Pubkey) -> Vec<Instruction> {
+        let mut instructions = vec![initialize(program_id, authority), initialize_mint(program_id, authority, MAX_SUPPLY)];
+        instructions.extend(self.categories().into_iter().map(|name| create_category(program_id, authority, name)));
+        instructions.push(add_oracle(program_id, authority, oracle));
+        instructions
+    }
+
+    /// One `StakeKnowledge` per entry, each signed by the staker its
+    /// fixture names
+    pub fn stake_instructions(&self, program_id: &Pubkey, stakers: &[Pubkey]) -> Vec<(usize, Instruction)> {
+        self.stakes
+            .iter()
+            .map(|stake| {
+                let ix = stake_knowledge(
+                    program_id,
+                    &stakers[stake.staker],
+                    stake.entry_index,
+                    stake.content.clone(),
+                    stake.stake_amount,
+                    &find_category_address(program_id, stake.category).0,
+                );
+                (stake.staker, ix)
+            })
+            .collect()
+    }
+
+    /// One `RecordAttribution` per attribution, signed by `oracle`
+    pub fn attribution_instructions(&self, program_id: &Pubkey, oracle: &Pubkey, stakers: &[Pubkey]) -> Vec<Instruction> {
+        self.attributions
+            .iter()
+            .map(|attribution| {
+                let stake = &self.stakes[attribution.entry];
+                let target = AttributionTarget::new(
+                    self.entry_address(program_id, stakers, stake),
+                    stakers[stake.staker],
+                    find_category_address(program_id, stake.category).0,
+                    attribution.relevance_score,
+                );
+                record_attribution(program_id, oracle, &target, attribution.query_hash, None, None, AttributionMetadata::default())
+            })
+            .collect()
+    }
+}
+
+/// Small, seedable and stable across platforms and releases, unlike the
+/// generators in `rand`
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..bound`; `bound` must be nonzero
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
@@ -0,0 +1,169 @@
+//! `solsage-sim [--program <solsage.so>] [--seed N] ...`
+//!
+//! Starts `solana-test-validator` with the program loaded, initializes the
+//! protocol, stakes sample entries across categories and records seeded
+//! attributions against them, then keeps the validator running so
+//! frontends and agents can read the data. With `--no-validator` the data
+//! goes to an already running validator at `--url` instead.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Child, Command, ExitCode, Stdio},
+    thread,
+    time::Duration,
+};
+
+use clap::Parser;
+use solana_program::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use solana_sdk::signature::{read_keypair_file, write_keypair_file, Keypair, Signer};
+use solsage_client::{SolSageClient, TransactionBuilder};
+use solsage_sim::{FixtureConfig, Fixtures};
+
+/// SOL airdropped to each generated wallet; stakes top out at 2 SOL
+const AIRDROP_SOL: u64 = 100;
+/// How long to wait for a fresh validator to answer
+const STARTUP_POLLS: u32 = 60;
+const STARTUP_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Attributions per transaction
+const ATTRIBUTION_CHUNK: usize = 4;
+
+#[derive(Parser, Debug)]
+#[command(name = "solsage-sim", about = "Seed a local validator with a SolSage deployment and sample data")]
+struct Args {
+    /// Program binary to load into a fresh solana-test-validator
+    #[arg(long, required_unless_present = "no_validator")]
+    program: Option<PathBuf>,
+    /// Use the validator already running at --url
+    #[arg(long, requires = "program_id")]
+    no_validator: bool,
+    /// Address of the deployed program; generated when starting a validator
+    #[arg(long)]
+    program_id: Option<Pubkey>,
+    #[arg(long, default_value = "http://127.0.0.1:8899")]
+    url: String,
+    /// Directory for the ledger and the generated keypairs
+    #[arg(long, default_value = "target/sim")]
+    out: PathBuf,
+    #[arg(long, default_value_t = FixtureConfig::default().seed)]
+    seed: u64,
+    #[arg(long, default_value_t = FixtureConfig::default().stakers)]
+    stakers: usize,
+    #[arg(long, default_value_t = FixtureConfig::default().entries)]
+    entries: usize,
+    #[arg(long, default_value_t = FixtureConfig::default().attributions)]
+    attributions: usize,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let mut validator = None;
+    let result = (|| {
+        fs::create_dir_all(&args.out)?;
+        let program_id = match args.program_id {
+            Some(program_id) => program_id,
+            None => keypair(&args, "program")?.pubkey(),
+        };
+        if let (false, Some(program)) = (args.no_validator, &args.program) {
+            validator = Some(start_validator(&args, &program_id, program)?);
+        }
+        seed(&args, program_id)
+    })();
+
+    match result {
+        Ok(()) => match validator {
+            Some(mut validator) => {
+                println!("validator running at {}; press Ctrl-C to stop it", args.url);
+                let _ = validator.wait();
+                ExitCode::SUCCESS
+            }
+            None => ExitCode::SUCCESS,
+        },
+        Err(e) => {
+            eprintln!("error: {e}");
+            if let Some(mut validator) = validator {
+                let _ = validator.kill();
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+type SimResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+/// The keypair saved as `<name>.json` under `--out`, generated on first use
+/// so reruns against the same ledger sign as the same wallets
+fn keypair(args: &Args, name: &str) -> SimResult<Keypair> {
+    let path = args.out.join(format!("{name}.json"));
+    if path.exists() {
+        return read_keypair_file(&path).map_err(|e| format!("failed to read {}: {e}", path.display()).into());
+    }
+    let keypair = Keypair::new();
+    write_keypair_file(&keypair, &path).map_err(|e| format!("failed to write {}: {e}", path.display()))?;
+    Ok(keypair)
+}
+
+fn start_validator(args: &Args, program_id: &Pubkey, program: &Path) -> SimResult<Child> {
+    let child = Command::new("solana-test-validator")
+        .arg("--reset")
+        .arg("--quiet")
+        .arg("--ledger")
+        .arg(args.out.join("ledger"))
+        .arg("--bpf-program")
+        .arg(program_id.to_string())
+        .arg(program)
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("failed to start solana-test-validator: {e}"))?;
+    println!("started solana-test-validator with {program_id}");
+    Ok(child)
+}
+
+fn seed(args: &Args, program_id: Pubkey) -> SimResult<()> {
+    let client = SolSageClient::new(args.url.as_str(), program_id);
+    wait_for_validator(&client)?;
+
+    let config =
+        FixtureConfig { seed: args.seed, stakers: args.stakers, entries: args.entries, attributions: args.attributions };
+    let fixtures = Fixtures::generate(config);
+    let authority = keypair(args, "authority")?;
+    let oracle = keypair(args, "oracle")?;
+    let stakers = (0..config.stakers.max(1))
+        .map(|index| keypair(args, &format!("staker-{index}")))
+        .collect::<SimResult<Vec<_>>>()?;
+    let staker_keys: Vec<Pubkey> = stakers.iter().map(Keypair::pubkey).collect();
+
+    for wallet in [&authority, &oracle].into_iter().chain(&stakers) {
+        client.request_airdrop(&wallet.pubkey(), AIRDROP_SOL * LAMPORTS_PER_SOL)?;
+    }
+
+    for instruction in fixtures.setup_instructions(&program_id, &authority.pubkey(), &oracle.pubkey()) {
+        client.send_transaction(&TransactionBuilder::new(authority.pubkey()).instruction(instruction), &[&authority])?;
+    }
+    println!("initialized the protocol with {} categories", fixtures.categories().len());
+
+    for (staker, instruction) in fixtures.stake_instructions(&program_id, &staker_keys) {
+        let staker = &stakers[staker];
+        client.send_transaction(&TransactionBuilder::new(staker.pubkey()).instruction(instruction), &[staker])?;
+    }
+    println!("staked {} entries from {} stakers", fixtures.stakes.len(), stakers.len());
+
+    for chunk in fixtures.attribution_instructions(&program_id, &oracle.pubkey(), &staker_keys).chunks(ATTRIBUTION_CHUNK) {
+        client.send_transaction(&TransactionBuilder::new(oracle.pubkey()).instructions(chunk.to_vec()), &[&oracle])?;
+    }
+    println!("recorded {} attributions", fixtures.attributions.len());
+
+    println!("program id: {program_id}");
+    println!("keypairs:   {}", args.out.display());
+    Ok(())
+}
+
+fn wait_for_validator(client: &SolSageClient) -> SimResult<()> {
+    for _ in 0..STARTUP_POLLS {
+        if client.fetch_slot().is_ok() {
+            return Ok(());
+        }
+        thread::sleep(STARTUP_POLL_INTERVAL);
+    }
+    Err("validator did not start".into())
+}
@@ -0,0 +1,84 @@
+use solana_program::pubkey::Pubkey;
+use solsage_client::{find_category_address, find_protocol_address, Category, KnowledgeEntry, ProgramAccount, Protocol};
+use solsage_sim::{FixtureConfig, Fixtures};
+use solsage_test_runtime::TestRuntime;
+
+const SOL: u64 = 1_000_000_000;
+
+fn state<T: ProgramAccount>(runtime: &TestRuntime, address: &Pubkey) -> T {
+    T::unpack(&runtime.account(address).expect("account exists").data).unwrap()
+}
+
+#[test]
+fn the_same_seed_generates_the_same_fixtures() {
+    let config = FixtureConfig { seed: 7, ..FixtureConfig::default() };
+    assert_eq!(Fixtures::generate(config), Fixtures::generate(config));
+    assert_ne!(Fixtures::generate(config), Fixtures::generate(FixtureConfig { seed: 8, ..config }));
+}
+
+#[test]
+fn entries_spread_across_stakers_and_categories() {
+    let fixtures = Fixtures::generate(FixtureConfig { seed: 3, stakers: 3, entries: 12, attributions: 40 });
+    assert_eq!(fixtures.stakes.len(), 12);
+    assert_eq!(fixtures.categories().len(), 5);
+
+    for staker in 0..3 {
+        let indexes: Vec<u64> =
+            fixtures.stakes.iter().filter(|stake| stake.staker == staker).map(|stake| stake.entry_index).collect();
+        assert_eq!(indexes, [0, 1, 2, 3]);
+    }
+    assert_eq!(fixtures.attributions.len(), 40);
+    for attribution in &fixtures.attributions {
+        assert!(attribution.entry < 12);
+        assert!((40..=100).contains(&attribution.relevance_score));
+    }
+}
+
+#[test]
+fn no_entries_means_no_attributions() {
+    let fixtures = Fixtures::generate(FixtureConfig { entries: 0, ..FixtureConfig::default() });
+    assert!(fixtures.stakes.is_empty());
+    assert!(fixtures.attributions.is_empty());
+}
+
+#[test]
+fn fixtures_replay_on_a_fresh_deployment() {
+    let config = FixtureConfig::default();
+    let fixtures = Fixtures::generate(config);
+    let program_id = Pubkey::new_unique();
+    let mut runtime = TestRuntime::new(program_id, solsage::process_instruction);
+    let (authority, oracle) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let stakers: Vec<Pubkey> = (0..config.stakers).map(|_| Pubkey::new_unique()).collect();
+    for account in [authority, oracle].iter().chain(&stakers) {
+        runtime.fund(*account, 100 * SOL);
+    }
+
+    for instruction in fixtures.setup_instructions(&program_id, &authority, &oracle) {
+        runtime.process(&instruction).unwrap();
+    }
+    for (_, instruction) in fixtures.stake_instructions(&program_id, &stakers) {
+        runtime.process(&instruction).unwrap();
+    }
+    for instruction in fixtures.attribution_instructions(&program_id, &oracle, &stakers) {
+        runtime.process(&instruction).unwrap();
+    }
+
+    let protocol: Protocol = state(&runtime, &find_protocol_address(&program_id).0);
+    assert_eq!(protocol.total_knowledge_entries, config.entries as u64);
+
+    let mut attributions = 0;
+    for stake in &fixtures.stakes {
+        let entry: KnowledgeEntry = state(&runtime, &fixtures.entry_address(&program_id, &stakers, stake));
+        assert_eq!(entry.category, find_category_address(&program_id, stake.category).0);
+        assert_eq!(entry.stake_amount, stake.stake_amount);
+        attributions += entry.total_attributions;
+    }
+    assert_eq!(attributions, config.attributions as u64);
+
+    let categories: u64 = fixtures
+        .categories()
+        .into_iter()
+        .map(|name| state::<Category>(&runtime, &find_category_address(&program_id, name).0).attributions)
+        .sum();
+    assert_eq!(categories, config.attributions as u64);
+}
@@ -0,0 +1,446 @@
+//! In-process Solana runtime for the SolSage lifecycle tests.
+//!
+//! Instructions run through a program's entrypoint on accounts serialized in
+//! the validator's input layout, so `realloc` behaves as it does on chain.
+//! Syscall stubs serve `Clock` and `Rent` and carry out CPIs into the system
+//! and SPL Token programs. A failed instruction leaves every account as it
+//! was, as a failed transaction would.
+//!
+//! Programs run natively rather than on the SBF VM, so the instructions they
+//! execute go unmetered. What is metered is what the validator charges for at
+//! its syscall boundary: logs, sysvars, return data and CPIs, and the PDA
+//! derivations and hashes made through `solsage_core::syscalls`, at the costs
+//! of its default compute budget. That makes [`TestRuntime::compute_units`] a
+//! floor on an instruction's cost which moves with the syscalls and CPIs it
+//! makes, not a measurement of it. What an instruction logs is kept for
+//! [`TestRuntime::logs`] rather than printed.
+//!
+//! `tests/banks.rs` runs the lifecycle and compute budget through
+//! solana-program-test's `BanksClient` as well. That is why the workspace
+//! pins Solana 1.18.26 rather than 1.18.0: solana-program-test 1.18.0 needs
+//! solana_rbpf 0.8.0, which has been yanked, where 1.18.26 takes 0.8.3.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::sync::Once;
+
+use solana_program::{
+    account_info::AccountInfo,
+    bpf_loader,
+    clock::Clock,
+    entrypoint::{self, ProcessInstruction, ProgramResult, BPF_ALIGN_OF_U128, MAX_PERMITTED_DATA_INCREASE, NON_DUP_MARKER, SUCCESS},
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_pack::Pack,
+    program_stubs::{self, SyscallStubs},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction::{SystemError, SystemInstruction},
+    system_program,
+};
+use solsage_core::syscalls;
+use spl_token::state::{Account as TokenAccount, AccountState};
+
+/// Unix timestamp the clock starts at
+pub const GENESIS_TIMESTAMP: i64 = 1_700_000_000;
+
+/// Compute units an instruction may spend without a compute budget request
+pub const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u64 = 200_000;
+
+// Costs from the validator's default compute budget
+const SYSCALL_BASE_COST: u64 = 100;
+const SYSVAR_BASE_COST: u64 = 100;
+const INVOKE_UNITS: u64 = 1_000;
+const CPI_BYTES_PER_UNIT: u64 = 250;
+/// What the system program, a builtin, charges per instruction
+const SYSTEM_PROGRAM_UNITS: u64 = 150;
+
+static INSTALL_STUBS: Once = Once::new();
+
+thread_local! {
+    /// Clock and program of the instruction running on this thread, read by
+    /// the syscall stubs
+    static CLOCK: RefCell<Clock> = RefCell::new(Clock::default());
+    static PROGRAM_ID: Cell<Pubkey> = Cell::new(Pubkey::default());
+    /// Compute units the running instruction has been charged
+    static COMPUTE_UNITS: Cell<u64> = const { Cell::new(0) };
+    /// What the running instruction has logged
+    static LOGS: RefCell<Vec<Log>> = const { RefCell::new(Vec::new()) };
+}
+
+fn consume(units: u64) {
+    COMPUTE_UNITS.with(|consumed| consumed.set(consumed.get() + units));
+}
+
+/// One line of an instruction's log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Log {
+    /// Logged with `msg!` or `sol_log`
+    Message(String),
+    /// Fields logged with `sol_log_data`, as events are
+    Data(Vec<Vec<u8>>),
+}
+
+/// State of one account between instructions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Account {
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub owner: Pubkey,
+    pub executable: bool,
+}
+
+impl Account {
+    /// Rent-exempt account holding `data`
+    pub fn new(data: Vec<u8>, owner: Pubkey) -> Self {
+        Self { lamports: Rent::default().minimum_balance(data.len()), data, owner, executable: false }
+    }
+
+    /// What the runtime passes for an address nothing has been stored at
+    fn empty() -> Self {
+        Self { lamports: 0, data: Vec::new(), owner: system_program::id(), executable: false }
+    }
+}
+
+/// Accounts and clock one program's instructions run against
+pub struct TestRuntime {
+    accounts: HashMap<Pubkey, Account>,
+    programs: HashMap<Pubkey, ProcessInstruction>,
+    clock: Clock,
+    compute_units: u64,
+    logs: Vec<Log>,
+}
+
+impl TestRuntime {
+    /// Runtime executing `processor` for instructions to `program_id`
+    pub fn new(program_id: Pubkey, processor: ProcessInstruction) -> Self {
+        INSTALL_STUBS.call_once(|| {
+            program_stubs::set_syscall_stubs(Box::new(Stubs));
+            syscalls::set_meter(consume);
+        });
+
+        let mut accounts = HashMap::new();
+        for program in [program_id, system_program::id(), spl_token::id()] {
+            accounts.insert(
+                program,
+                Account { lamports: 1, data: Vec::new(), owner: bpf_loader::id(), executable: true },
+            );
+        }
+        Self {
+            accounts,
+            programs: HashMap::from([(program_id, processor)]),
+            clock: Clock { unix_timestamp: GENESIS_TIMESTAMP, ..Clock::default() },
+            compute_units: 0,
+            logs: Vec::new(),
+        }
+    }
+
+    pub fn add_account(&mut self, address: Pubkey, account: Account) {
+        self.accounts.insert(address, account);
+    }
+
+    /// Credit `lamports` to a system account at `address`
+    pub fn fund(&mut self, address: Pubkey, lamports: u64) {
+        self.accounts.entry(address).or_insert_with(Account::empty).lamports += lamports;
+    }
+
+    /// Open an empty token account for `mint` owned by `owner`
+    pub fn add_token_account(&mut self, address: Pubkey, mint: Pubkey, owner: Pubkey) {
+        let mut data = vec![0; TokenAccount::LEN];
+        TokenAccount { mint, owner, state: AccountState::Initialized, ..TokenAccount::default() }.pack_into_slice(&mut data);
+        self.add_account(address, Account::new(data, spl_token::id()));
+    }
+
+    pub fn account(&self, address: &Pubkey) -> Option<&Account> {
+        self.accounts.get(address)
+    }
+
+    /// Every account the runtime holds, in no particular order
+    pub fn accounts(&self) -> impl Iterator<Item = (&Pubkey, &Account)> {
+        self.accounts.iter()
+    }
+
+    pub fn token_balance(&self, address: &Pubkey) -> u64 {
+        let account = self.account(address).expect("token account exists");
+        TokenAccount::unpack(&account.data).expect("account holds token account state").amount
+    }
+
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// Compute units metered for the last instruction processed, whether or
+    /// not it succeeded
+    pub fn compute_units(&self) -> u64 {
+        self.compute_units
+    }
+
+    /// What the last instruction processed logged, whether or not it
+    /// succeeded
+    pub fn logs(&self) -> &[Log] {
+        &self.logs
+    }
+
+    /// Move the clock `secs` forward, a slot at a time
+    pub fn warp_forward(&mut self, secs: i64) {
+        self.clock.unix_timestamp += secs;
+        self.clock.slot += 1;
+    }
+
+    /// Run `instruction`, keeping its changes only if it succeeds. Panics if
+    /// it breaks a rule the validator enforces outside the program: writing
+    /// to a read-only account or changing the total lamports.
+    pub fn process(&mut self, instruction: &Instruction) -> ProgramResult {
+        let processor = *self
+            .programs
+            .get(&instruction.program_id)
+            .expect("instruction for a program the runtime doesn't run");
+        let mut input = self.serialize(instruction);
+        CLOCK.with(|clock| *clock.borrow_mut() = self.clock.clone());
+        PROGRAM_ID.with(|program_id| program_id.set(instruction.program_id));
+        COMPUTE_UNITS.with(|consumed| consumed.set(0));
+        LOGS.with(|logs| logs.borrow_mut().clear());
+
+        // The account infos point into `input`, so copy their state out
+        // before it is dropped
+        let (result, updated) = unsafe {
+            let (program_id, accounts, data) = entrypoint::deserialize(input.as_mut_ptr() as *mut u8);
+            let result = processor(program_id, &accounts, data);
+            let updated: Vec<_> = accounts
+                .iter()
+                .map(|info| {
+                    let account = Account {
+                        lamports: info.lamports(),
+                        data: info.data.borrow().to_vec(),
+                        owner: *info.owner,
+                        executable: info.executable,
+                    };
+                    (*info.key, info.is_writable, account)
+                })
+                .collect();
+            (result, updated)
+        };
+        self.compute_units = COMPUTE_UNITS.with(Cell::get);
+        self.logs = LOGS.with(RefCell::take);
+        result?;
+
+        let (mut lamports_before, mut lamports_after) = (0u128, 0u128);
+        let mut written = Vec::new();
+        for (address, is_writable, account) in updated {
+            if written.contains(&address) {
+                continue;
+            }
+            written.push(address);
+
+            let before = self.accounts.get(&address).cloned().unwrap_or_else(Account::empty);
+            lamports_before += u128::from(before.lamports);
+            lamports_after += u128::from(account.lamports);
+            if !is_writable {
+                assert_eq!(before, account, "instruction modified read-only account {address}");
+            } else if account.lamports == 0 {
+                // Emptied accounts are garbage collected
+                self.accounts.remove(&address);
+            } else {
+                self.accounts.insert(address, account);
+            }
+        }
+        assert_eq!(lamports_before, lamports_after, "instruction created or destroyed lamports");
+        Ok(())
+    }
+
+    /// Lay `instruction` out as the runtime passes it to a program's
+    /// entrypoint, in an 8-byte aligned buffer
+    fn serialize(&self, instruction: &Instruction) -> Vec<u64> {
+        let metas = &instruction.accounts;
+        let mut input = Vec::new();
+        input.extend_from_slice(&(metas.len() as u64).to_le_bytes());
+        for (i, meta) in metas.iter().enumerate() {
+            if let Some(first) = metas[..i].iter().position(|other| other.pubkey == meta.pubkey) {
+                input.push(first as u8);
+                input.extend_from_slice(&[0; 7]);
+                continue;
+            }
+
+            // Repeated accounts get the privileges of every position
+            let positions = metas.iter().filter(|other| other.pubkey == meta.pubkey);
+            let is_signer = positions.clone().any(|other| other.is_signer);
+            let is_writable = positions.clone().any(|other| other.is_writable);
+            let account = self.accounts.get(&meta.pubkey).cloned().unwrap_or_else(Account::empty);
+
+            input.extend_from_slice(&[NON_DUP_MARKER, is_signer as u8, is_writable as u8, account.executable as u8]);
+            // Original data length, filled in by `deserialize`
+            input.extend_from_slice(&[0; 4]);
+            input.extend_from_slice(meta.pubkey.as_ref());
+            input.extend_from_slice(account.owner.as_ref());
+            input.extend_from_slice(&account.lamports.to_le_bytes());
+            input.extend_from_slice(&(account.data.len() as u64).to_le_bytes());
+            input.extend_from_slice(&account.data);
+            input.resize(input.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+            input.resize(input.len().next_multiple_of(BPF_ALIGN_OF_U128), 0);
+            // Rent epoch
+            input.extend_from_slice(&u64::MAX.to_le_bytes());
+        }
+        input.extend_from_slice(&(instruction.data.len() as u64).to_le_bytes());
+        input.extend_from_slice(&instruction.data);
+        input.extend_from_slice(instruction.program_id.as_ref());
+
+        input
+            .chunks(8)
+            .map(|chunk| {
+                let mut word = [0; 8];
+                word[..chunk.len()].copy_from_slice(chunk);
+                u64::from_ne_bytes(word)
+            })
+            .collect()
+    }
+}
+
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_log(&self, message: &str) {
+        consume(SYSCALL_BASE_COST.max(message.len() as u64));
+        LOGS.with(|logs| logs.borrow_mut().push(Log::Message(message.to_string())));
+    }
+
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        let bytes: usize = fields.iter().map(|field| field.len()).sum();
+        consume(SYSCALL_BASE_COST * (1 + fields.len() as u64) + bytes as u64);
+        LOGS.with(|logs| logs.borrow_mut().push(Log::Data(fields.iter().map(|field| field.to_vec()).collect())));
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        consume(SYSCALL_BASE_COST + data.len() as u64 / CPI_BYTES_PER_UNIT);
+    }
+
+    fn sol_remaining_compute_units(&self) -> u64 {
+        DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT.saturating_sub(COMPUTE_UNITS.with(Cell::get))
+    }
+
+    fn sol_log_compute_units(&self) {
+        self.sol_log(&format!("Program consumption: {} units remaining", self.sol_remaining_compute_units()));
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        consume(SYSVAR_BASE_COST + std::mem::size_of::<Clock>() as u64);
+        let clock = CLOCK.with(|clock| clock.borrow().clone());
+        unsafe { *(var_addr as *mut Clock) = clock };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        consume(SYSVAR_BASE_COST + std::mem::size_of::<Rent>() as u64);
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        consume(INVOKE_UNITS + instruction.data.len() as u64 / CPI_BYTES_PER_UNIT);
+        let caller = PROGRAM_ID.with(Cell::get);
+        let pda_signers = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &caller))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ProgramError::InvalidSeeds)?;
+
+        // The callee gets the privileges its instruction asks for, which the
+        // caller must hold or, for signatures, sign for with seeds
+        let accounts = instruction
+            .accounts
+            .iter()
+            .map(|meta| {
+                let info = account_infos
+                    .iter()
+                    .find(|info| *info.key == meta.pubkey)
+                    .ok_or(ProgramError::NotEnoughAccountKeys)?;
+                if meta.is_signer && !info.is_signer && !pda_signers.contains(&meta.pubkey) {
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+                if meta.is_writable && !info.is_writable {
+                    return Err(ProgramError::InvalidArgument);
+                }
+                let mut info = info.clone();
+                info.is_signer = meta.is_signer;
+                info.is_writable = meta.is_writable;
+                Ok(info)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        // Each account's data is copied into the callee's address space
+        for account in &accounts {
+            consume(account.data_len() as u64 / CPI_BYTES_PER_UNIT);
+        }
+
+        if instruction.program_id == system_program::id() {
+            consume(SYSTEM_PROGRAM_UNITS);
+            process_system_instruction(&accounts, &instruction.data)
+        } else if instruction.program_id == spl_token::id() {
+            spl_token::processor::Processor::process(&instruction.program_id, &accounts, &instruction.data)
+        } else {
+            Err(ProgramError::IncorrectProgramId)
+        }
+    }
+}
+
+/// The system program instructions the SolSage programs invoke
+fn process_system_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let instruction: SystemInstruction =
+        bincode::deserialize(data).map_err(|_| ProgramError::InvalidInstructionData)?;
+    let signer = |index: usize| {
+        let account = accounts.get(index).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if !account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Ok(account)
+    };
+
+    match instruction {
+        SystemInstruction::CreateAccount { lamports, space, owner } => {
+            let (from, to) = (signer(0)?, signer(1)?);
+            if to.lamports() > 0 {
+                return Err(system_error(SystemError::AccountAlreadyInUse));
+            }
+            transfer(from, to, lamports)?;
+            allocate(to, space)?;
+            to.assign(&owner);
+        }
+        SystemInstruction::Transfer { lamports } => {
+            let from = signer(0)?;
+            if !from.data_is_empty() {
+                return Err(ProgramError::InvalidArgument);
+            }
+            transfer(from, accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?, lamports)?;
+        }
+        SystemInstruction::Allocate { space } => allocate(signer(0)?, space)?,
+        SystemInstruction::Assign { owner } => signer(0)?.assign(&owner),
+        _ => return Err(ProgramError::InvalidInstructionData),
+    }
+    Ok(())
+}
+
+fn transfer(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    if *from.owner != system_program::id() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if from.lamports() < lamports {
+        return Err(system_error(SystemError::ResultWithNegativeLamports));
+    }
+    **from.try_borrow_mut_lamports()? -= lamports;
+    **to.try_borrow_mut_lamports()? += lamports;
+    Ok(())
+}
+
+fn allocate(account: &AccountInfo, space: u64) -> ProgramResult {
+    if !account.data_is_empty() || *account.owner != system_program::id() {
+        return Err(system_error(SystemError::AccountAlreadyInUse));
+    }
+    account.realloc(space as usize, true)
+}
+
+fn system_error(error: SystemError) -> ProgramError {
+    ProgramError::Custom(error as u32)
+}
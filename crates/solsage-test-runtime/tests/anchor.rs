@@ -0,0 +1,201 @@
+use anchor_lang::{
+    error::ErrorCode,
+    prelude::AccountInfo,
+    solana_program::{entrypoint::ProgramResult, instruction::Instruction, program_error::ProgramError},
+    InstructionData, ToAccountMetas,
+};
+use solana_program::{pubkey::Pubkey, system_program};
+use solsage_anchor::{accounts, instruction, ID};
+use solsage_cpi::{
+    add_oracle, claim_rewards, create_category, find_attribution_address, find_category_address,
+    find_knowledge_entry_address, find_leaderboard_address, find_mint_address, find_oracle_address,
+    find_protocol_address, initialize, initialize_mint, record_attribution, stake_knowledge, AttributionClaim,
+    AttributionMetadata, AttributionTarget, KnowledgeEntry, Leaderboard, LeaderboardEntry, LicenseType,
+    ProgramAccount, Protocol, SolSageError, StakedContent,
+};
+use solsage_test_runtime::{Account, TestRuntime};
+
+const SOL: u64 = 1_000_000_000;
+const MAX_SUPPLY: u64 = 1_000_000_000_000;
+
+/// Anchor ties the account infos to the lifetime of their slice, which the
+/// runtime's entrypoint signature doesn't carry
+fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(accounts.to_vec().into_boxed_slice());
+    solsage_anchor::entry(program_id, accounts, data)
+}
+
+/// `instruction`, built for the native entrypoint, as the Anchor build takes
+/// it
+fn anchor(instruction: Instruction) -> Instruction {
+    solsage_anchor::from_native(instruction).unwrap()
+}
+
+fn state<T: ProgramAccount>(runtime: &TestRuntime, address: &Pubkey) -> T {
+    T::unpack(&runtime.account(address).expect("account exists").data).unwrap()
+}
+
+fn content(title: &str) -> StakedContent {
+    StakedContent { content_hash: [7; 32], title: title.to_string(), license: LicenseType::Mit }
+}
+
+/// Instructions setting up a protocol with a mint, a category and an oracle,
+/// and a staker with one entry in that category
+fn setup(authority: &Pubkey, oracle: &Pubkey, staker: &Pubkey) -> Vec<Instruction> {
+    let category = find_category_address(&ID, "rust").0;
+    vec![
+        initialize(&ID, authority),
+        initialize_mint(&ID, authority, MAX_SUPPLY),
+        create_category(&ID, authority, "rust"),
+        add_oracle(&ID, authority, oracle),
+        stake_knowledge(&ID, staker, 0, content("Async Rust"), SOL, &category),
+    ]
+}
+
+fn attribute(oracle: &Pubkey, staker: &Pubkey, query_hash: [u8; 32]) -> Instruction {
+    let knowledge_entry = find_knowledge_entry_address(&ID, staker, 0).0;
+    let target = AttributionTarget::new(knowledge_entry, *staker, find_category_address(&ID, "rust").0, 80);
+    record_attribution(&ID, oracle, &target, query_hash, None, None, AttributionMetadata::default())
+}
+
+struct Deployment {
+    runtime: TestRuntime,
+    authority: Pubkey,
+    oracle: Pubkey,
+    staker: Pubkey,
+}
+
+fn runtime(processor: solana_program::entrypoint::ProcessInstruction, signers: [Pubkey; 3]) -> TestRuntime {
+    let mut runtime = TestRuntime::new(ID, processor);
+    for account in signers {
+        runtime.fund(account, 10 * SOL);
+    }
+    runtime
+}
+
+fn deploy() -> Deployment {
+    let (authority, oracle, staker) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let mut runtime = runtime(process_instruction, [authority, oracle, staker]);
+    for ix in setup(&authority, &oracle, &staker) {
+        runtime.process(&anchor(ix)).unwrap();
+    }
+    Deployment { runtime, authority, oracle, staker }
+}
+
+#[test]
+fn stakes_are_attributed_and_claimed_as_minted_rewards() {
+    let Deployment { mut runtime, authority, oracle, staker } = deploy();
+
+    let protocol: Protocol = state(&runtime, &find_protocol_address(&ID).0);
+    assert_eq!(protocol.authority, authority);
+    assert_eq!(protocol.reward_mint, find_mint_address(&ID).0);
+    assert_eq!(protocol.total_knowledge_entries, 1);
+
+    let knowledge_entry = find_knowledge_entry_address(&ID, &staker, 0).0;
+    let entry: KnowledgeEntry = state(&runtime, &knowledge_entry);
+    assert_eq!((entry.staker, entry.stake_amount), (staker, SOL));
+    assert!(entry.is_active);
+
+    let query_hash = [9; 32];
+    runtime.process(&anchor(attribute(&oracle, &staker, query_hash))).unwrap();
+    let entry: KnowledgeEntry = state(&runtime, &knowledge_entry);
+    assert_eq!(entry.total_attributions, 1);
+    assert!(entry.pending_rewards > 0);
+    let leaderboard: Leaderboard = state(&runtime, &find_leaderboard_address(&ID).0);
+    assert_eq!(leaderboard.entries(), [LeaderboardEntry { knowledge_entry, total_attributions: 1 }]);
+
+    // Rewards vest before they can be claimed
+    let destination = Pubkey::new_unique();
+    runtime.add_token_account(destination, find_mint_address(&ID).0, staker);
+    let attribution = find_attribution_address(&ID, &query_hash, &knowledge_entry).0;
+    let claims = [AttributionClaim { attribution, epoch: None }];
+    let claim = || anchor(claim_rewards(&ID, &staker, &staker, &knowledge_entry, &destination, &[], &claims));
+    assert_eq!(runtime.process(&claim()), Err(SolSageError::RewardLocked.into()));
+    runtime.warp_forward(2 * Protocol::DEFAULT_VESTING_PERIOD_SECS);
+    runtime.process(&claim()).unwrap();
+    assert_eq!(runtime.token_balance(&destination), entry.pending_rewards);
+
+    // Each attribution pays out once
+    assert_eq!(runtime.process(&claim()), Err(SolSageError::RewardAlreadyClaimed.into()));
+}
+
+#[test]
+fn accounts_off_their_pdas_are_rejected() {
+    let Deployment { mut runtime, staker, .. } = deploy();
+    let category = find_category_address(&ID, "rust").0;
+
+    // An entry must sit at the PDA of its staker and index
+    let mut ix = stake_knowledge(&ID, &staker, 1, content("Pinning"), SOL, &category);
+    ix.accounts[2].pubkey = find_knowledge_entry_address(&ID, &staker, 2).0;
+    assert_eq!(runtime.process(&anchor(ix)), Err(SolSageError::InvalidPda.into()));
+    assert!(runtime.account(&find_knowledge_entry_address(&ID, &staker, 2).0).is_none());
+}
+
+#[test]
+fn instructions_need_their_signers() {
+    let Deployment { mut runtime, authority, oracle, staker } = deploy();
+    let category = find_category_address(&ID, "rust").0;
+    let not_signer = || Err(ProgramError::from(anchor_lang::error::Error::from(ErrorCode::AccountNotSigner)));
+
+    let mut ix = stake_knowledge(&ID, &staker, 1, content("Pinning"), SOL, &category);
+    ix.accounts[0].is_signer = false;
+    assert_eq!(runtime.process(&anchor(ix)), not_signer());
+
+    // Only the authority administers the protocol
+    let unauthorized = runtime.process(&anchor(add_oracle(&ID, &staker, &Pubkey::new_unique())));
+    assert_eq!(unauthorized, Err(SolSageError::Unauthorized.into()));
+    let mut ix = add_oracle(&ID, &authority, &Pubkey::new_unique());
+    ix.accounts[0].is_signer = false;
+    assert_eq!(runtime.process(&anchor(ix)), not_signer());
+
+    // Attributions need a registered oracle's signature
+    let mut ix = attribute(&oracle, &staker, [9; 32]);
+    ix.accounts[0].is_signer = false;
+    assert_eq!(runtime.process(&anchor(ix)), not_signer());
+    let ix = attribute(&staker, &staker, [9; 32]);
+    assert_eq!(runtime.process(&anchor(ix)), Err(SolSageError::UnauthorizedOracle.into()));
+}
+
+#[test]
+fn instructions_from_anchor_clients_are_processed() {
+    let Deployment { mut runtime, authority, .. } = deploy();
+    let oracle = Pubkey::new_unique();
+    let accounts = accounts::AddOracle {
+        authority,
+        protocol: find_protocol_address(&ID).0,
+        attribution_oracle: find_oracle_address(&ID, &oracle).0,
+        system_program: system_program::id(),
+    };
+    let ix = Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: instruction::AddOracle { oracle }.data(),
+    };
+    assert_eq!(ix, anchor(add_oracle(&ID, &authority, &oracle)));
+    runtime.process(&ix).unwrap();
+}
+
+#[test]
+fn both_entrypoints_leave_the_same_accounts() {
+    let signers = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+    let [authority, oracle, staker] = signers;
+    let instructions: Vec<_> = setup(&authority, &oracle, &staker)
+        .into_iter()
+        .chain([attribute(&oracle, &staker, [9; 32])])
+        .collect();
+
+    let mut native = runtime(solsage::process_instruction, signers);
+    let mut anchored = runtime(process_instruction, signers);
+    for ix in instructions {
+        native.process(&ix).unwrap();
+        anchored.process(&anchor(ix)).unwrap();
+    }
+
+    let sorted = |runtime: &TestRuntime| {
+        let mut accounts: Vec<(Pubkey, Account)> =
+            runtime.accounts().map(|(address, account)| (*address, account.clone())).collect();
+        accounts.sort_by_key(|(address, _)| *address);
+        accounts
+    };
+    assert_eq!(sorted(&native), sorted(&anchored));
+}
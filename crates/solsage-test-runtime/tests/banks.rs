@@ -0,0 +1,179 @@
+//! The native program's lifecycle and compute budget, run through
+//! solana-program-test's `BanksClient` rather than `TestRuntime`.
+//!
+//! Under `cargo test` the program runs natively as a builtin, so the suite
+//! checks the bank accepts what the program does but reports next to no
+//! compute units. Under `cargo test-sbf` `ProgramTest` loads the
+//! `solsage.so` built into `SBF_OUT_DIR` instead, and the compute units are
+//! the VM's.
+
+use solana_program::{clock::Clock, instruction::Instruction, program_pack::Pack, pubkey::Pubkey, system_instruction};
+use solana_program_test::{processor, tokio, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use solsage_cpi::{
+    add_oracle, claim_rewards, create_category, find_attribution_address, find_category_address,
+    find_knowledge_entry_address, find_mint_address, initialize, initialize_mint, record_attribution, stake_knowledge,
+    AttributionClaim, AttributionMetadata, AttributionTarget, KnowledgeEntry, LicenseType, ProgramAccount, Protocol,
+    SolSageError, StakedContent,
+};
+use spl_token::state::Account as TokenAccount;
+
+const SOL: u64 = 1_000_000_000;
+const MAX_SUPPLY: u64 = 1_000_000_000_000;
+
+/// Compute units any one instruction may spend, as in `compute_units.rs`
+const BUDGET: u64 = 60_000;
+
+/// Bank the program is deployed to
+struct Banks {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+}
+
+impl Banks {
+    /// Bank with each of `signers` funded
+    async fn start(signers: [&Keypair; 3]) -> Self {
+        let program_id = Pubkey::new_unique();
+        let mut program_test = ProgramTest::new("solsage", program_id, processor!(solsage::process_instruction));
+        for signer in signers {
+            program_test.add_account(signer.pubkey(), Account { lamports: 10 * SOL, ..Account::default() });
+        }
+        Self { context: program_test.start_with_context().await, program_id }
+    }
+
+    /// Submit `instruction` signed by `signer`, returning the compute units
+    /// it consumed. Each transaction gets a fresh blockhash, so a retried
+    /// instruction isn't taken for the one before it.
+    async fn process(&mut self, instruction: Instruction, signer: &Keypair) -> Result<u64, TransactionError> {
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let payer = &self.context.payer;
+        let transaction =
+            Transaction::new_signed_with_payer(&[instruction], Some(&payer.pubkey()), &[payer, signer], blockhash);
+        let processed = self.context.banks_client.process_transaction_with_metadata(transaction).await.unwrap();
+        processed.result?;
+        Ok(processed.metadata.expect("processed transactions carry metadata").compute_units_consumed)
+    }
+
+    async fn state<T: ProgramAccount>(&mut self, address: &Pubkey) -> T {
+        let account = self.context.banks_client.get_account(*address).await.unwrap().expect("account exists");
+        T::unpack(&account.data).unwrap()
+    }
+
+    /// Open an empty token account for the SAGE mint owned by `owner`
+    async fn add_token_account(&mut self, owner: &Pubkey) -> Pubkey {
+        let account = Keypair::new();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let payer = &self.context.payer;
+        let instructions = [
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &account.pubkey(),
+                rent.minimum_balance(TokenAccount::LEN),
+                TokenAccount::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account3(
+                &spl_token::id(),
+                &account.pubkey(),
+                &find_mint_address(&self.program_id).0,
+                owner,
+            )
+            .unwrap(),
+        ];
+        let transaction =
+            Transaction::new_signed_with_payer(&instructions, Some(&payer.pubkey()), &[payer, &account], blockhash);
+        self.context.banks_client.process_transaction(transaction).await.unwrap();
+        account.pubkey()
+    }
+
+    async fn token_balance(&mut self, address: &Pubkey) -> u64 {
+        let account = self.context.banks_client.get_account(*address).await.unwrap().expect("token account exists");
+        TokenAccount::unpack(&account.data).unwrap().amount
+    }
+
+    async fn warp_forward(&mut self, secs: i64) {
+        let clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        self.context.set_sysvar(&Clock { unix_timestamp: clock.unix_timestamp + secs, ..clock });
+    }
+}
+
+/// What the program fails a transaction's only instruction with
+fn failed_with(error: SolSageError) -> Result<u64, TransactionError> {
+    Err(TransactionError::InstructionError(0, solana_program::instruction::InstructionError::Custom(error as u32)))
+}
+
+#[tokio::test]
+async fn stakes_are_attributed_and_claimed_as_minted_rewards() {
+    let (authority, oracle, staker) = (Keypair::new(), Keypair::new(), Keypair::new());
+    let mut banks = Banks::start([&authority, &oracle, &staker]).await;
+    let program_id = banks.program_id;
+
+    banks.process(initialize(&program_id, &authority.pubkey()), &authority).await.unwrap();
+    banks.process(initialize_mint(&program_id, &authority.pubkey(), MAX_SUPPLY), &authority).await.unwrap();
+    banks.process(create_category(&program_id, &authority.pubkey(), "rust"), &authority).await.unwrap();
+    banks.process(add_oracle(&program_id, &authority.pubkey(), &oracle.pubkey()), &authority).await.unwrap();
+
+    let category = find_category_address(&program_id, "rust").0;
+    let content = StakedContent { content_hash: [7; 32], title: "Async Rust".to_string(), license: LicenseType::Mit };
+    banks.process(stake_knowledge(&program_id, &staker.pubkey(), 0, content, SOL, &category), &staker).await.unwrap();
+    let knowledge_entry = find_knowledge_entry_address(&program_id, &staker.pubkey(), 0).0;
+
+    let target = AttributionTarget::new(knowledge_entry, staker.pubkey(), category, 80);
+    let query_hash = [9; 32];
+    let metadata = AttributionMetadata::default();
+    let attribute = record_attribution(&program_id, &oracle.pubkey(), &target, query_hash, None, None, metadata);
+    banks.process(attribute, &oracle).await.unwrap();
+    let entry: KnowledgeEntry = banks.state(&knowledge_entry).await;
+    assert_eq!(entry.total_attributions, 1);
+    assert!(entry.pending_rewards > 0);
+
+    // Rewards vest before they can be claimed
+    let destination = banks.add_token_account(&staker.pubkey()).await;
+    let attribution = find_attribution_address(&program_id, &query_hash, &knowledge_entry).0;
+    let claims = [AttributionClaim { attribution, epoch: None }];
+    let staker_key = staker.pubkey();
+    let claim = || claim_rewards(&program_id, &staker_key, &staker_key, &knowledge_entry, &destination, &[], &claims);
+    assert_eq!(banks.process(claim(), &staker).await, failed_with(SolSageError::RewardLocked));
+    banks.warp_forward(2 * Protocol::DEFAULT_VESTING_PERIOD_SECS).await;
+    banks.process(claim(), &staker).await.unwrap();
+    assert_eq!(banks.token_balance(&destination).await, entry.pending_rewards);
+
+    // Each attribution pays out once
+    assert_eq!(banks.process(claim(), &staker).await, failed_with(SolSageError::RewardAlreadyClaimed));
+}
+
+#[tokio::test]
+async fn instructions_stay_within_their_compute_budget() {
+    let (authority, oracle, staker) = (Keypair::new(), Keypair::new(), Keypair::new());
+    let mut banks = Banks::start([&authority, &oracle, &staker]).await;
+    let program_id = banks.program_id;
+    let category = find_category_address(&program_id, "rust").0;
+    let knowledge_entry = find_knowledge_entry_address(&program_id, &staker.pubkey(), 0).0;
+    let content = StakedContent { content_hash: [1; 32], title: "Async Rust".to_string(), license: LicenseType::Mit };
+    let target = AttributionTarget::new(knowledge_entry, staker.pubkey(), category, 80);
+    let metadata = AttributionMetadata::default();
+    let attribute = record_attribution(&program_id, &oracle.pubkey(), &target, [9; 32], None, None, metadata);
+
+    let instructions = [
+        ("initialize", initialize(&program_id, &authority.pubkey()), &authority),
+        ("initialize_mint", initialize_mint(&program_id, &authority.pubkey(), MAX_SUPPLY), &authority),
+        ("create_category", create_category(&program_id, &authority.pubkey(), "rust"), &authority),
+        ("add_oracle", add_oracle(&program_id, &authority.pubkey(), &oracle.pubkey()), &authority),
+        ("stake_knowledge", stake_knowledge(&program_id, &staker.pubkey(), 0, content, SOL, &category), &staker),
+        ("record_attribution", attribute, &oracle),
+    ];
+    let mut over_budget = Vec::new();
+    for (name, instruction, signer) in instructions {
+        let units = banks.process(instruction, signer).await.unwrap_or_else(|err| panic!("{name} failed: {err}"));
+        println!("{name:<40} {units:>7}");
+        if units > BUDGET {
+            over_budget.push(format!("{name} spends {units} compute units, over its budget of {BUDGET}"));
+        }
+    }
+    assert!(over_budget.is_empty(), "{}", over_budget.join("\n"));
+}
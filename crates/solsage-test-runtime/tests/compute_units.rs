@@ -0,0 +1,162 @@
+//! Compute units the native program's instructions are metered for, held to
+//! a per-instruction budget and to the baseline in `compute_units.txt`.
+//!
+//! The runtime meters syscalls and CPIs, PDA derivations and hashes among
+//! them, not the program's own code, so the budget is the share of an instruction's limit those may take before the
+//! rest is left to execution. Rerun with `UPDATE_COMPUTE_UNITS=1` to rewrite
+//! the baseline once a change in cost is intended.
+
+use std::{env, fs, path::PathBuf};
+
+use solana_program::pubkey::Pubkey;
+use solsage_cpi::{
+    add_oracle, chunk_commitment, chunk_proof, claim_rewards, close_knowledge_entry, complete_unstake, create_category,
+    find_attribution_address, find_category_address, find_knowledge_entry_address, find_mint_address, initialize,
+    initialize_mint, record_attribution, record_attribution_batch, request_unstake, stake_document_chunks,
+    stake_knowledge, take_snapshot, update_knowledge, with_chunk_proof, with_chunks, AttributionClaim,
+    AttributionMetadata, AttributionTarget, LicenseType, Protocol, Snapshot, StakedContent,
+};
+use solsage_test_runtime::{TestRuntime, DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT};
+
+const SOL: u64 = 1_000_000_000;
+const MAX_SUPPLY: u64 = 1_000_000_000_000;
+
+/// Metered compute units any one instruction may spend
+const BUDGET: u64 = 60_000;
+const _: () = assert!(BUDGET < DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT);
+
+/// Instructions run in order against one runtime, with what each was metered
+struct Bench {
+    runtime: TestRuntime,
+    measured: Vec<(&'static str, u64)>,
+}
+
+impl Bench {
+    fn run(&mut self, name: &'static str, instruction: &solana_program::instruction::Instruction) {
+        if let Err(err) = self.runtime.process(instruction) {
+            panic!("{name} failed: {err}");
+        }
+        self.measured.push((name, self.runtime.compute_units()));
+    }
+}
+
+fn content(content_hash: u8, title: &str) -> StakedContent {
+    StakedContent { content_hash: [content_hash; 32], title: title.to_string(), license: LicenseType::Mit }
+}
+
+fn baseline_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/compute_units.txt")
+}
+
+/// `name units` per line
+fn read_baseline() -> Vec<(String, u64)> {
+    let baseline = fs::read_to_string(baseline_path()).unwrap_or_default();
+    baseline
+        .lines()
+        .filter_map(|line| {
+            let (name, units) = line.split_once(' ')?;
+            Some((name.to_string(), units.parse().ok()?))
+        })
+        .collect()
+}
+
+#[test]
+fn instructions_stay_within_their_compute_budget() {
+    let program_id = Pubkey::new_unique();
+    let mut bench = Bench { runtime: TestRuntime::new(program_id, solsage::process_instruction), measured: Vec::new() };
+    let (authority, oracle, staker) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    for account in [authority, oracle, staker] {
+        bench.runtime.fund(account, 100 * SOL);
+    }
+
+    bench.run("initialize", &initialize(&program_id, &authority));
+    bench.run("initialize_mint", &initialize_mint(&program_id, &authority, MAX_SUPPLY));
+    bench.run("create_category", &create_category(&program_id, &authority, "rust"));
+    bench.run("add_oracle", &add_oracle(&program_id, &authority, &oracle));
+    let category = find_category_address(&program_id, "rust").0;
+
+    bench.run("stake_knowledge", &stake_knowledge(&program_id, &staker, 0, content(1, "Async Rust"), SOL, &category));
+    let knowledge_entry = find_knowledge_entry_address(&program_id, &staker, 0).0;
+    bench.run("update_knowledge", &update_knowledge(&program_id, &staker, &knowledge_entry, &[1; 32], None, Some([2; 32])));
+
+    let chunks = ["Futures are lazy.", "Pin keeps a future in place.", "Wakers reschedule tasks."];
+    let stake = stake_knowledge(&program_id, &staker, 1, content(3, "Async Rust, chunked"), SOL, &category);
+    bench.run("stake_knowledge_with_chunks", &with_chunks(stake, chunk_commitment(&chunks)));
+    let chunked = find_knowledge_entry_address(&program_id, &staker, 1).0;
+
+    let document = [content(4, "Async Rust book, part 1"), content(5, "Async Rust book, part 2")];
+    for instruction in stake_document_chunks(&program_id, &staker, [6; 32], "Async Rust book", &category, 2, &document, SOL) {
+        bench.run("stake_document_chunks", &instruction);
+    }
+
+    let target = |knowledge_entry| AttributionTarget::new(knowledge_entry, staker, category, 80);
+    let attribute = |knowledge_entry, query_hash| {
+        record_attribution(&program_id, &oracle, &target(knowledge_entry), query_hash, None, None, AttributionMetadata::default())
+    };
+    bench.run("record_attribution", &attribute(knowledge_entry, [9; 32]));
+    bench.run("record_attribution_with_chunk_proof", &with_chunk_proof(attribute(chunked, [9; 32]), chunk_proof(&chunks, 1)));
+    let batch = [target(knowledge_entry), target(find_knowledge_entry_address(&program_id, &staker, 2).0)];
+    bench.run("record_attribution_batch", &record_attribution_batch(&program_id, &oracle, [10; 32], &batch, None));
+
+    let cranker = Pubkey::new_unique();
+    bench.runtime.fund(cranker, SOL);
+    let today = Snapshot::day_of(bench.runtime.clock().unix_timestamp).unwrap();
+    bench.run("take_snapshot", &take_snapshot(&program_id, &cranker, today));
+
+    bench.runtime.warp_forward(2 * Protocol::DEFAULT_VESTING_PERIOD_SECS);
+    let destination = Pubkey::new_unique();
+    bench.runtime.add_token_account(destination, find_mint_address(&program_id).0, staker);
+    let claims = [[9; 32], [10; 32]].map(|query_hash| AttributionClaim {
+        attribution: find_attribution_address(&program_id, &query_hash, &knowledge_entry).0,
+        epoch: None,
+    });
+    bench.run(
+        "claim_rewards",
+        &claim_rewards(&program_id, &staker, &staker, &knowledge_entry, &destination, &[], &claims),
+    );
+
+    bench.run("request_unstake", &request_unstake(&program_id, &staker, &knowledge_entry));
+    bench.runtime.warp_forward(2 * Protocol::DEFAULT_VESTING_PERIOD_SECS);
+    bench.run("complete_unstake", &complete_unstake(&program_id, &staker, &knowledge_entry));
+    let close = close_knowledge_entry(&program_id, &staker, &staker, &knowledge_entry, &category, &[2; 32]);
+    bench.run("close_knowledge_entry", &close);
+
+    // Instructions run more than once are held to their costliest run
+    let mut measured: Vec<(&str, u64)> = Vec::new();
+    for (name, units) in bench.measured {
+        match measured.iter_mut().find(|(measured_name, _)| *measured_name == name) {
+            Some((_, measured_units)) => *measured_units = (*measured_units).max(units),
+            None => measured.push((name, units)),
+        }
+    }
+
+    let baseline = read_baseline();
+    let mut report = String::new();
+    let mut failures = Vec::new();
+    for (name, units) in &measured {
+        let before = baseline.iter().find(|(baseline_name, _)| baseline_name == name).map(|(_, units)| *units);
+        let change = match before {
+            Some(before) if *units > before => {
+                failures.push(format!("{name} regressed from {before} to {units} compute units"));
+                format!("+{}", units - before)
+            }
+            Some(before) if *units < before => format!("-{}", before - units),
+            Some(_) => String::new(),
+            None => "new".to_string(),
+        };
+        if *units > BUDGET {
+            failures.push(format!("{name} spends {units} compute units, over its budget of {BUDGET}"));
+        }
+        report.push_str(&format!("{name:<40} {units:>7} {change:>7}\n"));
+    }
+    println!("{report}");
+
+    if env::var_os("UPDATE_COMPUTE_UNITS").is_some() {
+        let baseline: String = measured.iter().map(|(name, units)| format!("{name} {units}\n")).collect();
+        fs::write(baseline_path(), baseline).expect("baseline is writable");
+        return;
+    }
+    let missing: Vec<_> = measured.iter().filter(|(name, _)| !baseline.iter().any(|(baseline_name, _)| baseline_name == name)).collect();
+    assert!(missing.is_empty(), "no baseline for {missing:?}; rerun with UPDATE_COMPUTE_UNITS=1");
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
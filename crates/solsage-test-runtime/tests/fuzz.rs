@@ -0,0 +1,267 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use proptest::{collection::vec, option, prelude::*};
+use solana_program::{
+    account_info::AccountInfo,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+use solsage_cpi::{
+    add_oracle, create_category, find_attribution_address, find_category_address, find_content_registry_address,
+    find_escrow_address, find_knowledge_entry_address, find_leaderboard_address, find_mint_address,
+    find_mint_authority_address, find_oracle_address, find_protocol_address, find_query_record_address,
+    find_staker_index_address, find_staker_profile_address, find_treasury_address, initialize, initialize_mint,
+    record_attribution, stake_knowledge, Attribution, AttributionMetadata, AttributionTarget, Category, ChunkCommitment,
+    KnowledgeEntry, Leaderboard, LicenseType, LockupTier, Protocol, ReviewAttestation, RoyaltySplit, SolSageInstruction, StakedContent, StakerProfile, Visibility,
+};
+use solsage::{ProgramAccount, RewardStream, VestingBucket};
+use solsage_test_runtime::TestRuntime;
+
+const SOL: u64 = 1_000_000_000;
+/// Variants of `SolSageInstruction`; Borsh tags an enum with its variant's
+/// index in one byte
+const INSTRUCTION_VARIANTS: u8 = 111;
+
+/// A deployed protocol with one attributed entry, and every address its
+/// instructions name
+fn deploy() -> (TestRuntime, Pubkey, Vec<Pubkey>) {
+    let program_id = Pubkey::new_unique();
+    let mut runtime = TestRuntime::new(program_id, solsage::process_instruction);
+    let (authority, oracle, staker) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    for account in [authority, oracle, staker] {
+        runtime.fund(account, 10 * SOL);
+    }
+
+    let category = find_category_address(&program_id, "rust").0;
+    let knowledge_entry = find_knowledge_entry_address(&program_id, &staker, 0).0;
+    let content = StakedContent { content_hash: [7; 32], title: "Async Rust".to_string(), license: LicenseType::Mit };
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    let setup = [
+        initialize(&program_id, &authority),
+        initialize_mint(&program_id, &authority, 1_000_000_000_000),
+        create_category(&program_id, &authority, "rust"),
+        add_oracle(&program_id, &authority, &oracle),
+        stake_knowledge(&program_id, &staker, 0, content, SOL, &category),
+        record_attribution(&program_id, &oracle, &target, [9; 32], None, None, AttributionMetadata::default()),
+    ];
+    for ix in &setup {
+        runtime.process(ix).unwrap();
+    }
+
+    let token_account = Pubkey::new_unique();
+    runtime.add_token_account(token_account, find_mint_address(&program_id).0, staker);
+    let addresses = vec![
+        authority,
+        oracle,
+        staker,
+        find_protocol_address(&program_id).0,
+        find_treasury_address(&program_id).0,
+        find_mint_address(&program_id).0,
+        find_mint_authority_address(&program_id).0,
+        category,
+        knowledge_entry,
+        find_escrow_address(&program_id, &knowledge_entry).0,
+        find_staker_profile_address(&program_id, &staker).0,
+        find_staker_index_address(&program_id, &staker).0,
+        find_content_registry_address(&program_id, &[7; 32]).0,
+        find_oracle_address(&program_id, &oracle).0,
+        find_attribution_address(&program_id, &[9; 32], &knowledge_entry).0,
+        find_query_record_address(&program_id, &[9; 32]).0,
+        find_leaderboard_address(&program_id).0,
+        token_account,
+        Pubkey::new_unique(),
+        system_program::id(),
+        spl_token::id(),
+        program_id,
+    ];
+    (runtime, program_id, addresses)
+}
+
+fn bytes(max_len: usize) -> impl Strategy<Value = Vec<u8>> {
+    vec(any::<u8>(), 0..max_len)
+}
+
+fn pubkey() -> impl Strategy<Value = Pubkey> {
+    any::<[u8; 32]>().prop_map(Pubkey::new_from_array)
+}
+
+fn license() -> impl Strategy<Value = LicenseType> {
+    prop_oneof![
+        Just(LicenseType::Mit),
+        Just(LicenseType::CcBy),
+        Just(LicenseType::Proprietary),
+        ".{0,300}".prop_map(|uri| LicenseType::Custom { uri }),
+    ]
+}
+
+fn lockup_tier() -> impl Strategy<Value = LockupTier> {
+    prop_oneof![
+        Just(LockupTier::None),
+        Just(LockupTier::Days30),
+        Just(LockupTier::Days90),
+        Just(LockupTier::Days365),
+    ]
+}
+
+/// Entries with arbitrary values in every field, strings and lists past
+/// their limits included
+fn knowledge_entry() -> impl Strategy<Value = KnowledgeEntry> {
+    let identity = (any::<bool>(), any::<u8>(), pubkey(), pubkey(), any::<u64>(), any::<[u8; 32]>(), pubkey(), any::<bool>());
+    let content = (
+        license(),
+        ".{0,300}",
+        option::of(".{0,300}"),
+        option::of(pubkey()),
+        vec((pubkey(), any::<u16>()).prop_map(|(co_author, bps)| RoyaltySplit { co_author, bps }), 0..8),
+        vec(".{0,40}", 0..8),
+    );
+    let history = (
+        any::<[i64; 4]>(),
+        any::<u32>(),
+        any::<[u64; 2]>(),
+        any::<[(i64, u64); KnowledgeEntry::VESTING_BUCKETS]>(),
+        any::<(u64, u8, u8, u64)>(),
+        (lockup_tier(), any::<[i64; 3]>(), option::of(pubkey()), any::<bool>()),
+        any::<(u128, [u64; 2], [i64; 2])>(),
+        (vec(pubkey(), 0..8), any::<[u64; 2]>(), option::of(pubkey()), any::<u16>(), option::of(any::<([u8; 32], u32)>()), option::of(vec(any::<u8>(), 64))),
+        (option::of((pubkey(), any::<(u8, u8, [u8; 32], i64)>())), any::<bool>(), option::of(any::<[u8; 32]>()), option::of(any::<u64>())),
+    );
+    (identity, content, history).prop_map(
+        |(
+            (is_initialized, schema_version, staker, creator, entry_index, content_hash, category, is_active),
+            (license, title, content_uri, collection, royalty_splits, tags),
+            (
+                [created_at, updated_at, expires_at, last_attribution_at],
+                version,
+                [total_attributions, pending_rewards],
+                vesting_schedule,
+                (stake_amount, bump, escrow_bump, min_reward_per_use),
+                (lockup_tier, [locked_until, unstake_requested_at, quality_score], receipt_asset_id, under_review),
+                (rate_per_sec, [remaining, accrued], [streamed_at, ends_at]),
+                (derived_from, [citation_royalties, citation_rewards], document, chunk_index, chunks, embedding_fingerprint),
+                (attestation, verified, identity_credential, access_price),
+            ),
+        )| KnowledgeEntry {
+            is_initialized,
+            schema_version,
+            staker,
+            creator,
+            entry_index,
+            content_hash,
+            category,
+            is_active,
+            license,
+            title,
+            content_uri,
+            collection,
+            royalty_splits,
+            created_at,
+            updated_at,
+            expires_at,
+            last_attribution_at,
+            version,
+            total_attributions,
+            pending_rewards,
+            vesting_schedule: vesting_schedule.map(|(unlocks_at, amount)| VestingBucket { unlocks_at, amount }),
+            stake_amount,
+            bump,
+            escrow_bump,
+            tags,
+            min_reward_per_use,
+            lockup_tier,
+            locked_until,
+            unstake_requested_at,
+            receipt_asset_id,
+            quality_score,
+            under_review,
+            reward_stream: RewardStream { rate_per_sec, remaining, accrued, updated_at: streamed_at, ends_at },
+            derived_from,
+            citation_royalties,
+            citation_rewards,
+            document,
+            chunk_index,
+            chunks: chunks.map(|(root, chunk_count)| ChunkCommitment { root, chunk_count }),
+            embedding_fingerprint: embedding_fingerprint.map(|bytes| bytes.try_into().unwrap()),
+            attestation: attestation.map(|(reviewer, (accuracy, freshness, content_hash, attested_at))| ReviewAttestation {
+                reviewer,
+                accuracy,
+                freshness,
+                content_hash,
+                attested_at,
+            }),
+            verified,
+            identity_credential,
+            visibility: access_price.map_or(Visibility::Public, |access_price| Visibility::Private { access_price }),
+        },
+    )
+}
+
+/// Load `data` as a program account of type `T`
+fn load<T: ProgramAccount>(program_id: &Pubkey, mut data: Vec<u8>) -> Result<T, ProgramError> {
+    let (key, mut lamports) = (Pubkey::new_unique(), SOL);
+    let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, program_id, false, 0);
+    T::load(program_id, &info)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    /// Data that isn't an instruction is turned away before any account is
+    /// read
+    #[test]
+    fn unparsable_instruction_data_is_rejected(data in bytes(256)) {
+        prop_assume!(SolSageInstruction::try_from_slice(&data).is_err());
+        prop_assert_eq!(
+            solsage::process_instruction(&Pubkey::new_unique(), &[], &data),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    /// Every instruction, with arbitrary arguments and the deployment's
+    /// accounts in any order, repeated or unsigned, either runs or fails
+    /// with a `ProgramError`; never a panic
+    #[test]
+    fn arbitrary_instructions_fail_without_panicking(
+        variant in 0..INSTRUCTION_VARIANTS,
+        args in bytes(256),
+        accounts in vec((any::<prop::sample::Index>(), any::<bool>()), 0..24),
+    ) {
+        let (mut runtime, program_id, addresses) = deploy();
+        let accounts = accounts
+            .into_iter()
+            .map(|(index, is_signer)| AccountMeta {
+                pubkey: *index.get(&addresses),
+                is_signer,
+                is_writable: true,
+            })
+            .collect();
+        let data = [&[variant][..], &args].concat();
+        let _ = runtime.process(&Instruction { program_id, accounts, data });
+    }
+
+    /// Arbitrary bytes never decode into a panic, whatever account type
+    /// they're read as
+    #[test]
+    fn account_state_loads_from_arbitrary_bytes_without_panicking(data in bytes(KnowledgeEntry::LEN + 64)) {
+        let program_id = Pubkey::new_unique();
+        let _ = load::<KnowledgeEntry>(&program_id, data.clone());
+        let _ = load::<Protocol>(&program_id, data.clone());
+        let _ = load::<Attribution>(&program_id, data.clone());
+        let _ = load::<Category>(&program_id, data.clone());
+        let _ = load::<Leaderboard>(&program_id, data.clone());
+        let _ = load::<StakerProfile>(&program_id, data);
+    }
+
+    /// Entries round trip through Borsh whatever their field values, and
+    /// can't be read from a truncated encoding
+    #[test]
+    fn knowledge_entries_round_trip_and_reject_truncation(entry in knowledge_entry(), cut in any::<prop::sample::Index>()) {
+        let encoded = entry.try_to_vec().unwrap();
+        let decoded = KnowledgeEntry::try_from_slice(&encoded).unwrap();
+        prop_assert_eq!(decoded.try_to_vec().unwrap(), encoded.clone());
+
+        let cut = cut.index(encoded.len());
+        prop_assert!(KnowledgeEntry::deserialize(&mut &encoded[..cut]).is_err());
+    }
+}
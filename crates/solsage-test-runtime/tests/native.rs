@@ -0,0 +1,2255 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    program_error::ProgramError, program_option::COption, program_pack::Pack, pubkey::Pubkey, rent::Rent,
+};
+use solsage_cpi::{
+    accept_answer, accept_authority_transfer, add_bridge_emitter, add_document_chunk, add_oracle, add_reviewer,
+    attest_entry, bridge, bridged_from, burn_rewards, buy_access, buy_entry, cancel_listing, chunk_commitment,
+    chunk_proof, claim_all_rewards, claim_rewards, claim_treasury_rewards, close_knowledge_entry, complete_unstake,
+    create_bounty, create_category, deactivate_knowledge, derived_from, downvote_entry, expire_rewards,
+    finalize_attribution, get_vested_rewards, find_access_pass_address, find_attribution_address, find_bounty_address,
+    find_bounty_vault_address, find_bridge_emitter_address, find_category_address, find_content_registry_address,
+    find_document_address, find_escrow_address, find_entry_vote_address, find_knowledge_entry_address,
+    find_leaderboard_address, find_listing_address, find_mint_address, find_mint_authority_address, find_oracle_address,
+    find_oracle_state_address, find_payer_stats_address, find_protocol_address, find_query_record_address,
+    find_report_queue_address, find_snapshot_address, find_staker_index_address, find_staker_profile_address,
+    find_treasury_address, grant_access, identity, initialize, initialize_mint, list_entry_for_sale, locked_up,
+    migrate_account, pay_citation_royalties, propose_authority_transfer, reactivate_knowledge, reclaim_expired_bounty,
+    refunding_registry_to, record_attribution, record_attribution_batch, refresh_document, remove_oracle,
+    remove_reviewer, report_entry, request_unstake, resolve_reports, set_aggregation_window, set_citation_royalty,
+    set_claim_window, set_consensus, set_entry_visibility, set_fee_config, set_governance_config, set_identity_config,
+    set_low_relevance_burn, set_mint_metadata, set_oracle_budget, set_payer_attribution_cap, set_protocol_fee,
+    set_receipt_tree, set_reward_curve, set_reward_rate, set_reward_stream, set_sale_fee, set_vesting_period,
+    slash_knowledge, solsage, stake_document, stake_document_chunks, stake_knowledge, submit_answer, take_snapshot,
+    transfer_knowledge_ownership, update_embedding_fingerprint, update_knowledge, upvote_entry, with_chunk_proof,
+    with_chunks, with_identity, with_receipt, withdraw_entry_vote, AccessPass, AccountType, Attribution,
+    AttributionClaim, AttributionMetadata, AttributionOracle, AttributionTarget, AuthorityType, Bounty, Category,
+    ChunkCommitment, ContentRegistry, Document, FeeConfig, IdentityConfig, KnowledgeEntry, Leaderboard,
+    LeaderboardEntry, LicenseType, LockupTier, OracleState, PayerStats, ProgramAccount, Protocol, QueryRecord,
+    ReportQueue, RewardClaim, RewardCurve, Snapshot, SolSageError, StakedContent, StakerIndex, StakerProfile,
+    Visibility, LEADERBOARD_SCHEMA_VERSION,
+};
+use solsage_test_runtime::{Account, Log, TestRuntime};
+use spl_token::state::Mint;
+
+const SOL: u64 = 1_000_000_000;
+const MAX_SUPPLY: u64 = 1_000_000_000_000;
+
+/// Protocol set up with a mint, a category and an oracle, and a staker with
+/// one entry in that category
+struct Deployment {
+    runtime: TestRuntime,
+    program_id: Pubkey,
+    authority: Pubkey,
+    oracle: Pubkey,
+    staker: Pubkey,
+    category: Pubkey,
+    knowledge_entry: Pubkey,
+}
+
+fn state<T: ProgramAccount>(runtime: &TestRuntime, address: &Pubkey) -> T {
+    T::unpack(&runtime.account(address).expect("account exists").data).unwrap()
+}
+
+fn content(title: &str) -> StakedContent {
+    StakedContent { content_hash: [7; 32], title: title.to_string(), license: LicenseType::Mit }
+}
+
+fn deploy() -> Deployment {
+    deploy_with_max_supply(MAX_SUPPLY)
+}
+
+/// `deploy`, with the SAGE mint capped at `max_supply`
+fn deploy_with_max_supply(max_supply: u64) -> Deployment {
+    let program_id = Pubkey::new_unique();
+    let mut runtime = TestRuntime::new(program_id, solsage::process_instruction);
+    let (authority, oracle, staker) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    for account in [authority, oracle, staker] {
+        runtime.fund(account, 10 * SOL);
+    }
+
+    runtime.process(&initialize(&program_id, &authority)).unwrap();
+    runtime.process(&initialize_mint(&program_id, &authority, max_supply)).unwrap();
+    runtime.process(&create_category(&program_id, &authority, "rust")).unwrap();
+    runtime.process(&add_oracle(&program_id, &authority, &oracle)).unwrap();
+
+    let category = find_category_address(&program_id, "rust").0;
+    runtime
+        .process(&stake_knowledge(&program_id, &staker, 0, content("Async Rust"), SOL, &category))
+        .unwrap();
+
+    Deployment {
+        runtime,
+        program_id,
+        authority,
+        oracle,
+        staker,
+        category,
+        knowledge_entry: find_knowledge_entry_address(&program_id, &staker, 0).0,
+    }
+}
+
+#[test]
+fn stakes_are_attributed_and_claimed_as_minted_rewards() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+
+    let protocol: Protocol = state(&runtime, &find_protocol_address(&program_id).0);
+    assert_eq!(protocol.authority, authority);
+    assert_eq!(protocol.reward_mint, find_mint_address(&program_id).0);
+    assert_eq!(protocol.total_knowledge_entries, 1);
+
+    let entry: KnowledgeEntry = state(&runtime, &knowledge_entry);
+    assert_eq!((entry.staker, entry.category, entry.stake_amount), (staker, category, SOL));
+    assert!(entry.is_active);
+
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    let query_hash = [9; 32];
+    runtime
+        .process(&record_attribution(&program_id, &oracle, &target, query_hash, None, None, AttributionMetadata::default()))
+        .unwrap();
+    let entry: KnowledgeEntry = state(&runtime, &knowledge_entry);
+    assert_eq!(entry.total_attributions, 1);
+    assert!(entry.pending_rewards > 0);
+    let stats: Category = state(&runtime, &category);
+    assert_eq!((stats.entries, stats.attributions, stats.rewards), (1, 1, entry.pending_rewards));
+
+    // Rewards vest before they can be claimed
+    let destination = Pubkey::new_unique();
+    runtime.add_token_account(destination, find_mint_address(&program_id).0, staker);
+    let attribution = find_attribution_address(&program_id, &query_hash, &knowledge_entry).0;
+    let claims = [AttributionClaim { attribution, epoch: None }];
+    assert_eq!(
+        runtime.process(&claim_rewards(&program_id, &staker, &staker, &knowledge_entry, &destination, &[], &claims)),
+        Err(SolSageError::RewardLocked.into())
+    );
+    runtime.warp_forward(2 * Protocol::DEFAULT_VESTING_PERIOD_SECS);
+    runtime
+        .process(&claim_rewards(&program_id, &staker, &staker, &knowledge_entry, &destination, &[], &claims))
+        .unwrap();
+    assert_eq!(runtime.token_balance(&destination), entry.pending_rewards);
+
+    // Each attribution pays out once
+    assert_eq!(
+        runtime.process(&claim_rewards(&program_id, &staker, &staker, &knowledge_entry, &destination, &[], &claims)),
+        Err(SolSageError::RewardAlreadyClaimed.into())
+    );
+}
+
+#[test]
+fn instructions_log_their_name_and_events() {
+    let Deployment { mut runtime, program_id, staker, category, .. } = deploy();
+
+    let mut pinning = content("Pinning");
+    pinning.content_hash = [8; 32];
+    runtime.process(&stake_knowledge(&program_id, &staker, 1, pinning, SOL, &category)).unwrap();
+    let staked = solsage::SolSageEvent::KnowledgeStaked {
+        knowledge_entry: find_knowledge_entry_address(&program_id, &staker, 1).0,
+        staker,
+        entry_index: 1,
+        content_hash: [8; 32],
+        category,
+        stake_amount: SOL,
+        lockup_tier: LockupTier::None,
+        receipt_asset_id: None,
+        derived_from: Vec::new(),
+    };
+    assert_eq!(
+        runtime.logs(),
+        [
+            Log::Message("Instruction: StakeKnowledge".to_string()),
+            Log::Data(vec![staked.try_to_vec().unwrap()]),
+            Log::Message(format!("Knowledge staked: Pinning ({SOL} lamports)")),
+        ]
+    );
+
+    // A failed instruction keeps what it logged before failing
+    let duplicate = stake_knowledge(&program_id, &staker, 2, content("Pinning, again"), SOL, &category);
+    assert!(runtime.process(&duplicate).is_err());
+    assert_eq!(runtime.logs().first(), Some(&Log::Message("Instruction: StakeKnowledge".to_string())));
+    assert!(!runtime.logs().iter().any(|log| matches!(log, Log::Data(_))));
+}
+
+#[test]
+fn rewards_left_unclaimed_past_the_claim_window_are_expired_by_anyone() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+    runtime.process(&set_claim_window(&program_id, &authority, Protocol::MIN_CLAIM_WINDOW_SECS)).unwrap();
+
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    let query_hash = [9; 32];
+    runtime
+        .process(&record_attribution(&program_id, &oracle, &target, query_hash, None, None, AttributionMetadata::default()))
+        .unwrap();
+    let reward = state::<KnowledgeEntry>(&runtime, &knowledge_entry).pending_rewards;
+
+    let cranker = Pubkey::new_unique();
+    let bounty_account = Pubkey::new_unique();
+    runtime.fund(cranker, SOL);
+    runtime.add_token_account(bounty_account, find_mint_address(&program_id).0, cranker);
+    let attribution = find_attribution_address(&program_id, &query_hash, &knowledge_entry).0;
+    let claims = [AttributionClaim { attribution, epoch: None }];
+    let expire = expire_rewards(&program_id, &cranker, &knowledge_entry, &bounty_account, &claims);
+
+    // Vested rewards stay the staker's for the whole window
+    let unlocks_at = state::<Attribution>(&runtime, &attribution).unlocks_at;
+    runtime.warp_forward(unlocks_at + Protocol::MIN_CLAIM_WINDOW_SECS - runtime.clock().unix_timestamp - 1);
+    assert_eq!(runtime.process(&expire), Err(SolSageError::RewardNotExpired.into()));
+
+    runtime.warp_forward(1);
+    runtime.process(&expire).unwrap();
+    assert_eq!(runtime.token_balance(&bounty_account), reward * Protocol::EXPIRY_BOUNTY_BPS as u64 / 10_000);
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &knowledge_entry).pending_rewards, 0);
+
+    // Expired rewards are gone for the staker too
+    let destination = Pubkey::new_unique();
+    runtime.add_token_account(destination, find_mint_address(&program_id).0, staker);
+    assert_eq!(
+        runtime.process(&claim_rewards(&program_id, &staker, &staker, &knowledge_entry, &destination, &[], &claims)),
+        Err(SolSageError::RewardAlreadyClaimed.into())
+    );
+}
+
+#[test]
+fn locked_stakes_earn_boosted_rewards_until_they_unlock() {
+    let Deployment { mut runtime, program_id, oracle, staker, category, knowledge_entry, .. } = deploy();
+    let stake = stake_knowledge(
+        &program_id,
+        &staker,
+        1,
+        StakedContent { content_hash: [8; 32], ..content("Pinning") },
+        SOL,
+        &category,
+    );
+    runtime.process(&locked_up(stake, LockupTier::Days90)).unwrap();
+    let locked_entry = find_knowledge_entry_address(&program_id, &staker, 1).0;
+    let entry: KnowledgeEntry = state(&runtime, &locked_entry);
+    assert_eq!(entry.lockup_tier, LockupTier::Days90);
+    assert_eq!(entry.locked_until, entry.created_at + LockupTier::Days90.duration_secs());
+
+    // The same attribution pays the locked entry 1.25x
+    let mut rewards = Vec::new();
+    for (query_hash, knowledge_entry) in [([9; 32], knowledge_entry), ([10; 32], locked_entry)] {
+        let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+        runtime
+            .process(&record_attribution(&program_id, &oracle, &target, query_hash, None, None, AttributionMetadata::default()))
+            .unwrap();
+        rewards.push(state::<KnowledgeEntry>(&runtime, &knowledge_entry).pending_rewards);
+    }
+    assert_eq!(rewards[1], rewards[0] * 5 / 4);
+
+    // The lockup holds the stake past the cooldown
+    runtime.process(&request_unstake(&program_id, &staker, &locked_entry)).unwrap();
+    runtime.warp_forward(KnowledgeEntry::UNSTAKE_COOLDOWN_SECS);
+    assert_eq!(
+        runtime.process(&complete_unstake(&program_id, &staker, &locked_entry)),
+        Err(SolSageError::StakeLocked.into())
+    );
+    runtime.warp_forward(entry.locked_until - runtime.clock().unix_timestamp);
+    let destination = Pubkey::new_unique();
+    runtime.add_token_account(destination, find_mint_address(&program_id).0, staker);
+    let attribution = find_attribution_address(&program_id, &[10; 32], &locked_entry).0;
+    let claims = [AttributionClaim { attribution, epoch: None }];
+    runtime
+        .process(&claim_rewards(&program_id, &staker, &staker, &locked_entry, &destination, &[], &claims))
+        .unwrap();
+    runtime.process(&complete_unstake(&program_id, &staker, &locked_entry)).unwrap();
+}
+
+#[test]
+fn unstakes_wait_out_the_cooldown_and_pending_rewards() {
+    let Deployment { mut runtime, program_id, oracle, staker, category, knowledge_entry, .. } = deploy();
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    runtime
+        .process(&record_attribution(&program_id, &oracle, &target, [9; 32], None, None, AttributionMetadata::default()))
+        .unwrap();
+
+    assert_eq!(
+        runtime.process(&complete_unstake(&program_id, &staker, &knowledge_entry)),
+        Err(SolSageError::UnstakeNotRequested.into())
+    );
+    runtime.process(&request_unstake(&program_id, &staker, &knowledge_entry)).unwrap();
+
+    // The entry stops taking attributions for good
+    let ix = record_attribution(&program_id, &oracle, &target, [10; 32], None, None, AttributionMetadata::default());
+    assert_eq!(runtime.process(&ix), Err(SolSageError::KnowledgeInactive.into()));
+    assert_eq!(
+        runtime.process(&reactivate_knowledge(&program_id, &staker, &knowledge_entry)),
+        Err(SolSageError::UnstakeAlreadyRequested.into())
+    );
+
+    let unlocks_at = state::<KnowledgeEntry>(&runtime, &knowledge_entry).unstake_unlocks_at().unwrap();
+    runtime.warp_forward(unlocks_at - runtime.clock().unix_timestamp - 1);
+    assert_eq!(
+        runtime.process(&complete_unstake(&program_id, &staker, &knowledge_entry)),
+        Err(SolSageError::StakeLocked.into())
+    );
+
+    // The stake is held until the entry's rewards are paid out
+    runtime.warp_forward(Protocol::DEFAULT_VESTING_PERIOD_SECS);
+    assert_eq!(
+        runtime.process(&complete_unstake(&program_id, &staker, &knowledge_entry)),
+        Err(SolSageError::PendingRewardsOutstanding.into())
+    );
+    let destination = Pubkey::new_unique();
+    runtime.add_token_account(destination, find_mint_address(&program_id).0, staker);
+    let attribution = find_attribution_address(&program_id, &[9; 32], &knowledge_entry).0;
+    let claims = [AttributionClaim { attribution, epoch: None }];
+    runtime
+        .process(&claim_rewards(&program_id, &staker, &staker, &knowledge_entry, &destination, &[], &claims))
+        .unwrap();
+
+    let lamports = runtime.account(&staker).unwrap().lamports;
+    runtime.process(&complete_unstake(&program_id, &staker, &knowledge_entry)).unwrap();
+    assert!(runtime.account(&staker).unwrap().lamports >= lamports + SOL);
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &knowledge_entry).stake_amount, 0);
+}
+
+#[test]
+fn content_stays_claimed_by_one_entry_through_updates_and_closes() {
+    let Deployment { mut runtime, program_id, staker, category, knowledge_entry, .. } = deploy();
+    let rehashed = |content_hash| StakedContent { content_hash, ..content("Async Rust") };
+    let registry = |content_hash| find_content_registry_address(&program_id, &content_hash).0;
+    runtime.process(&stake_knowledge(&program_id, &staker, 1, rehashed([8; 32]), SOL, &category)).unwrap();
+
+    // Another entry's content can't be taken over
+    assert_eq!(
+        runtime.process(&update_knowledge(&program_id, &staker, &knowledge_entry, &[7; 32], None, Some([8; 32]))),
+        Err(SolSageError::DuplicateContent.into())
+    );
+
+    // Moving to new content frees the old
+    runtime.process(&update_knowledge(&program_id, &staker, &knowledge_entry, &[7; 32], None, Some([9; 32]))).unwrap();
+    assert!(runtime.account(&registry([7; 32])).is_none());
+    assert_eq!(state::<ContentRegistry>(&runtime, &registry([9; 32])).knowledge_entry, knowledge_entry);
+    runtime.process(&stake_knowledge(&program_id, &staker, 2, rehashed([7; 32]), SOL, &category)).unwrap();
+
+    // As does closing the entry
+    runtime.process(&request_unstake(&program_id, &staker, &knowledge_entry)).unwrap();
+    let unlocks_at = state::<KnowledgeEntry>(&runtime, &knowledge_entry).unstake_unlocks_at().unwrap();
+    runtime.warp_forward(unlocks_at - runtime.clock().unix_timestamp);
+    runtime.process(&complete_unstake(&program_id, &staker, &knowledge_entry)).unwrap();
+    runtime.process(&close_knowledge_entry(&program_id, &staker, &staker, &knowledge_entry, &category, &[9; 32])).unwrap();
+    assert!(runtime.account(&registry([9; 32])).is_none());
+    runtime.process(&stake_knowledge(&program_id, &staker, 3, rehashed([9; 32]), SOL, &category)).unwrap();
+}
+
+#[test]
+fn staker_indexes_count_entries_staked_in_order_and_closed() {
+    let Deployment { mut runtime, program_id, staker, category, knowledge_entry, .. } = deploy();
+    let staker_index = find_staker_index_address(&program_id, &staker).0;
+    let content = |content_hash| StakedContent { content_hash, ..content("Async Rust") };
+
+    // Entries take the next index, so none is skipped
+    assert_eq!(
+        runtime.process(&stake_knowledge(&program_id, &staker, 2, content([8; 32]), SOL, &category)),
+        Err(SolSageError::InvalidEntryIndex.into())
+    );
+    runtime.process(&stake_knowledge(&program_id, &staker, 1, content([8; 32]), SOL, &category)).unwrap();
+    let index: StakerIndex = state(&runtime, &staker_index);
+    assert_eq!((index.entry_count, index.closed_entries), (2, 0));
+
+    runtime.process(&request_unstake(&program_id, &staker, &knowledge_entry)).unwrap();
+    let unlocks_at = state::<KnowledgeEntry>(&runtime, &knowledge_entry).unstake_unlocks_at().unwrap();
+    runtime.warp_forward(unlocks_at - runtime.clock().unix_timestamp);
+    runtime.process(&complete_unstake(&program_id, &staker, &knowledge_entry)).unwrap();
+    let stranger = Pubkey::new_unique();
+    let mut close = close_knowledge_entry(&program_id, &staker, &stranger, &knowledge_entry, &category, &[7; 32]);
+    assert_eq!(runtime.process(&close), Err(SolSageError::InvalidPda.into()));
+    close.accounts[6].pubkey = staker_index;
+    runtime.process(&close).unwrap();
+    let index: StakerIndex = state(&runtime, &staker_index);
+    assert_eq!((index.entry_count, index.closed_entries), (2, 1));
+}
+
+#[test]
+fn bridged_attributions_land_once_from_registered_emitters() {
+    let Deployment { mut runtime, program_id, authority, staker, category, knowledge_entry, .. } = deploy();
+    let (wormhole, emitter_chain, emitter_address) = (Pubkey::new_unique(), 2, [5; 32]);
+    runtime.process(&add_bridge_emitter(&program_id, &authority, &wormhole, emitter_chain, emitter_address)).unwrap();
+    let emitter = find_bridge_emitter_address(&program_id, emitter_chain, &emitter_address).0;
+
+    let relayer = Pubkey::new_unique();
+    runtime.fund(relayer, SOL);
+    let post = |runtime: &mut TestRuntime, owner: Pubkey, sequence: u64, query_hash: [u8; 32]| {
+        let payload = bridge::attribution_payload(&query_hash, &knowledge_entry.to_bytes(), 80);
+        let message = bridge::PostedMessage { emitter_chain, emitter_address, sequence, payload: &payload };
+        let address = Pubkey::new_unique();
+        runtime.add_account(address, Account::new(bridge::posted_message_data(&message), owner));
+        address
+    };
+    let attribute = |query_hash: [u8; 32], posted_message: &Pubkey| {
+        let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+        let ix = record_attribution(&program_id, &emitter, &target, query_hash, None, None, AttributionMetadata::default());
+        bridged_from(ix, &relayer, posted_message)
+    };
+
+    let message = post(&mut runtime, wormhole, 3, [9; 32]);
+    runtime.process(&attribute([9; 32], &message)).unwrap();
+    let attribution = find_attribution_address(&program_id, &[9; 32], &knowledge_entry).0;
+    assert_eq!(state::<Attribution>(&runtime, &attribution).relevance_score, 80);
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &knowledge_entry).total_attributions, 1);
+
+    // Messages only vouch for the attribution they carry, as their bridge
+    // posted them
+    assert_eq!(runtime.process(&attribute([8; 32], &message)), Err(SolSageError::InvalidBridgedMessage.into()));
+    let forged = post(&mut runtime, Pubkey::new_unique(), 4, [8; 32]);
+    assert_eq!(runtime.process(&attribute([8; 32], &forged)), Err(SolSageError::InvalidBridgedMessage.into()));
+
+    // Sequences must increase
+    let replayed = post(&mut runtime, wormhole, 3, [8; 32]);
+    assert_eq!(runtime.process(&attribute([8; 32], &replayed)), Err(SolSageError::BridgedMessageUsed.into()));
+    let next = post(&mut runtime, wormhole, 4, [8; 32]);
+    runtime.process(&attribute([8; 32], &next)).unwrap();
+
+    // Revoking the emitter's oracle registration turns it away
+    runtime.process(&remove_oracle(&program_id, &authority, &emitter)).unwrap();
+    let revoked = post(&mut runtime, wormhole, 5, [7; 32]);
+    assert_eq!(runtime.process(&attribute([7; 32], &revoked)), Err(SolSageError::UnauthorizedOracle.into()));
+}
+
+#[test]
+fn snapshots_record_each_day_s_totals_once() {
+    let Deployment { mut runtime, program_id, oracle, staker, category, knowledge_entry, .. } = deploy();
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    runtime
+        .process(&record_attribution(&program_id, &oracle, &target, [9; 32], None, None, AttributionMetadata::default()))
+        .unwrap();
+
+    let cranker = Pubkey::new_unique();
+    runtime.fund(cranker, SOL);
+    let today = Snapshot::day_of(runtime.clock().unix_timestamp).unwrap();
+    runtime.process(&take_snapshot(&program_id, &cranker, today)).unwrap();
+
+    let protocol: Protocol = state(&runtime, &find_protocol_address(&program_id).0);
+    let snapshot: Snapshot = state(&runtime, &find_snapshot_address(&program_id, today).0);
+    assert_eq!(snapshot.day, today);
+    assert_eq!(snapshot.taken_at, runtime.clock().unix_timestamp);
+    assert_eq!(
+        (snapshot.total_knowledge_entries, snapshot.total_attributions, snapshot.fees_collected),
+        (protocol.total_knowledge_entries, protocol.total_attributions, protocol.fees_collected)
+    );
+    assert_eq!((snapshot.total_knowledge_entries, snapshot.total_attributions), (1, 1));
+    assert_eq!(snapshot.total_rewards, state::<KnowledgeEntry>(&runtime, &knowledge_entry).pending_rewards);
+
+    // One snapshot a day, and only for the current one
+    assert_eq!(
+        runtime.process(&take_snapshot(&program_id, &cranker, today)),
+        Err(SolSageError::AlreadyInitialized.into())
+    );
+    assert_eq!(
+        runtime.process(&take_snapshot(&program_id, &cranker, today + 1)),
+        Err(SolSageError::InvalidPda.into())
+    );
+
+    runtime.warp_forward(Snapshot::INTERVAL_SECS);
+    runtime.process(&take_snapshot(&program_id, &cranker, today + 1)).unwrap();
+}
+
+#[test]
+fn receipts_are_only_minted_from_the_protocol_s_tree() {
+    let Deployment { mut runtime, program_id, authority, staker, category, .. } = deploy();
+    let merkle_tree = Pubkey::new_unique();
+    let stake = |content_hash| {
+        let content = StakedContent { content_hash, title: "Tokio".to_string(), license: LicenseType::Mit };
+        with_receipt(&program_id, stake_knowledge(&program_id, &staker, 1, content, SOL, &category), &merkle_tree)
+    };
+
+    // No tree is pinned until the authority sets one
+    assert_eq!(runtime.process(&stake([8; 32])), Err(SolSageError::InvalidReceiptTree.into()));
+    assert_eq!(
+        runtime.process(&set_receipt_tree(&program_id, &staker, Some(merkle_tree))),
+        Err(SolSageError::Unauthorized.into())
+    );
+    runtime.process(&set_receipt_tree(&program_id, &authority, Some(Pubkey::new_unique()))).unwrap();
+    assert_eq!(runtime.process(&stake([8; 32])), Err(SolSageError::InvalidReceiptTree.into()));
+
+    runtime.process(&set_receipt_tree(&program_id, &authority, Some(merkle_tree))).unwrap();
+    let protocol: Protocol = state(&runtime, &find_protocol_address(&program_id).0);
+    assert_eq!(protocol.receipt_tree, Some(merkle_tree));
+}
+
+#[test]
+fn only_the_authority_sets_valid_mint_metadata() {
+    let Deployment { mut runtime, program_id, authority, staker, .. } = deploy();
+    let uri = "https://example.com/sage.json";
+    assert_eq!(
+        runtime.process(&set_mint_metadata(&program_id, &staker, "SolSage", "SAGE", uri)),
+        Err(SolSageError::Unauthorized.into())
+    );
+    assert_eq!(
+        runtime.process(&set_mint_metadata(&program_id, &authority, "SolSage", "SAGE-REWARDS", uri)),
+        Err(SolSageError::MetadataTooLong.into())
+    );
+
+    let mut wrong_program = set_mint_metadata(&program_id, &authority, "SolSage", "SAGE", uri);
+    wrong_program.accounts[5].pubkey = Pubkey::new_unique();
+    assert_eq!(runtime.process(&wrong_program), Err(ProgramError::IncorrectProgramId));
+}
+
+#[test]
+fn listed_entries_are_bought_through_an_escrowed_payment() {
+    let Deployment { mut runtime, program_id, authority, staker, knowledge_entry, .. } = deploy();
+    let (buyer, listing) = (Pubkey::new_unique(), find_listing_address(&program_id, &knowledge_entry).0);
+    let (protocol_address, treasury) = (find_protocol_address(&program_id).0, find_treasury_address(&program_id).0);
+    runtime.fund(buyer, 10 * SOL);
+    runtime.process(&set_sale_fee(&program_id, &authority, 500)).unwrap();
+
+    assert_eq!(
+        runtime.process(&list_entry_for_sale(&program_id, &buyer, &knowledge_entry, 2 * SOL)),
+        Err(SolSageError::NotKnowledgeOwner.into())
+    );
+    assert_eq!(
+        runtime.process(&list_entry_for_sale(&program_id, &staker, &knowledge_entry, 0)),
+        Err(SolSageError::InvalidListingPrice.into())
+    );
+    runtime.process(&list_entry_for_sale(&program_id, &staker, &knowledge_entry, 2 * SOL)).unwrap();
+
+    // Buyers name the price they agreed to
+    assert_eq!(
+        runtime.process(&buy_entry(&program_id, &buyer, &knowledge_entry, &staker, SOL)),
+        Err(SolSageError::ListingPriceChanged.into())
+    );
+
+    let lamports = |runtime: &TestRuntime, address| runtime.account(address).unwrap().lamports;
+    let (staker_lamports, treasury_lamports) = (lamports(&runtime, &staker), lamports(&runtime, &treasury));
+    let listing_rent = lamports(&runtime, &listing);
+    runtime.process(&buy_entry(&program_id, &buyer, &knowledge_entry, &staker, 2 * SOL)).unwrap();
+
+    let fee = 2 * SOL / 20;
+    assert_eq!(lamports(&runtime, &treasury), treasury_lamports + fee);
+    assert_eq!(lamports(&runtime, &staker), staker_lamports + 2 * SOL - fee + listing_rent);
+    assert!(runtime.account(&listing).is_none());
+    let entry: KnowledgeEntry = state(&runtime, &knowledge_entry);
+    assert_eq!((entry.staker, entry.creator, entry.stake_amount), (buyer, staker, SOL));
+    assert_eq!(state::<Protocol>(&runtime, &protocol_address).fees_collected, fee);
+
+    // A listing left behind by the seller is cleared by the new owner
+    runtime.process(&list_entry_for_sale(&program_id, &buyer, &knowledge_entry, SOL)).unwrap();
+    runtime
+        .process(&transfer_knowledge_ownership(&program_id, &buyer, &knowledge_entry, &staker))
+        .unwrap();
+    assert_eq!(
+        runtime.process(&buy_entry(&program_id, &authority, &knowledge_entry, &buyer, SOL)),
+        Err(SolSageError::NotKnowledgeOwner.into())
+    );
+    runtime.process(&cancel_listing(&program_id, &staker, &knowledge_entry, &buyer)).unwrap();
+    assert!(runtime.account(&listing).is_none());
+}
+
+#[test]
+fn entries_sell_only_once_the_seller_has_claimed_their_rewards() {
+    let Deployment { mut runtime, program_id, oracle, staker, category, knowledge_entry, .. } = deploy();
+    let buyer = Pubkey::new_unique();
+    runtime.fund(buyer, 10 * SOL);
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    let attribute = |query: u8| {
+        record_attribution(&program_id, &oracle, &target, [query; 32], None, None, AttributionMetadata::default())
+    };
+    let claims = |query: u8| {
+        let attribution = find_attribution_address(&program_id, &[query; 32], &knowledge_entry).0;
+        [AttributionClaim { attribution, epoch: None }]
+    };
+    let (seller_tokens, buyer_tokens) = (Pubkey::new_unique(), Pubkey::new_unique());
+    runtime.add_token_account(seller_tokens, find_mint_address(&program_id).0, staker);
+    runtime.add_token_account(buyer_tokens, find_mint_address(&program_id).0, buyer);
+
+    runtime.process(&attribute(9)).unwrap();
+    let reward = state::<KnowledgeEntry>(&runtime, &knowledge_entry).pending_rewards;
+    runtime.process(&list_entry_for_sale(&program_id, &staker, &knowledge_entry, SOL)).unwrap();
+    assert_eq!(
+        runtime.process(&buy_entry(&program_id, &buyer, &knowledge_entry, &staker, SOL)),
+        Err(SolSageError::UnclaimedRewards.into())
+    );
+
+    runtime.warp_forward(2 * Protocol::DEFAULT_VESTING_PERIOD_SECS);
+    let claim = |claimer: &Pubkey, destination: &Pubkey, query: u8| {
+        claim_rewards(&program_id, claimer, claimer, &knowledge_entry, destination, &[], &claims(query))
+    };
+    runtime.process(&claim(&staker, &seller_tokens, 9)).unwrap();
+    runtime.process(&buy_entry(&program_id, &buyer, &knowledge_entry, &staker, SOL)).unwrap();
+    assert_eq!(runtime.token_balance(&seller_tokens), reward);
+    let profile = |owner: &Pubkey| state::<StakerProfile>(&runtime, &find_staker_profile_address(&program_id, owner).0);
+    assert_eq!((profile(&staker).total_entries, profile(&buyer).total_entries), (0, 1));
+
+    // What the entry earned before the sale never pays the buyer; what it
+    // earns after does
+    assert_eq!(runtime.process(&claim(&buyer, &buyer_tokens, 9)), Err(SolSageError::RewardAlreadyClaimed.into()));
+    let target = AttributionTarget::new(knowledge_entry, buyer, category, 80);
+    runtime
+        .process(&record_attribution(&program_id, &oracle, &target, [10; 32], None, None, AttributionMetadata::default()))
+        .unwrap();
+    runtime.warp_forward(2 * Protocol::DEFAULT_VESTING_PERIOD_SECS);
+    runtime.process(&claim(&buyer, &buyer_tokens, 10)).unwrap();
+    assert!(runtime.token_balance(&buyer_tokens) > 0);
+    assert_eq!(runtime.token_balance(&seller_tokens), reward);
+}
+
+#[test]
+fn entries_change_hands_only_once_the_staker_has_claimed_their_rewards() {
+    let Deployment { mut runtime, program_id, oracle, staker, category, knowledge_entry, .. } = deploy();
+    let new_owner = Pubkey::new_unique();
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    runtime
+        .process(&record_attribution(&program_id, &oracle, &target, [9; 32], None, None, AttributionMetadata::default()))
+        .unwrap();
+    let transfer = transfer_knowledge_ownership(&program_id, &staker, &knowledge_entry, &new_owner);
+    assert_eq!(runtime.process(&transfer), Err(SolSageError::UnclaimedRewards.into()));
+
+    let destination = Pubkey::new_unique();
+    runtime.add_token_account(destination, find_mint_address(&program_id).0, staker);
+    let attribution = find_attribution_address(&program_id, &[9; 32], &knowledge_entry).0;
+    let claims = [AttributionClaim { attribution, epoch: None }];
+    runtime.warp_forward(2 * Protocol::DEFAULT_VESTING_PERIOD_SECS);
+    runtime
+        .process(&claim_rewards(&program_id, &staker, &staker, &knowledge_entry, &destination, &[], &claims))
+        .unwrap();
+    runtime.process(&transfer).unwrap();
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &knowledge_entry).staker, new_owner);
+
+    // The entry's count moves with it
+    let entries = |runtime: &TestRuntime, owner: &Pubkey| {
+        state::<StakerProfile>(runtime, &find_staker_profile_address(&program_id, owner).0).total_entries
+    };
+    assert_eq!((entries(&runtime, &staker), entries(&runtime, &new_owner)), (0, 1));
+    runtime.fund(new_owner, SOL);
+    runtime.process(&transfer_knowledge_ownership(&program_id, &new_owner, &knowledge_entry, &new_owner)).unwrap();
+    assert_eq!(entries(&runtime, &new_owner), 1);
+}
+
+#[test]
+fn registries_of_a_handed_over_entry_refund_the_staker_who_paid_for_them() {
+    let Deployment { mut runtime, program_id, staker, category, knowledge_entry, .. } = deploy();
+    let new_owner = Pubkey::new_unique();
+    runtime.fund(new_owner, 10 * SOL);
+    let registry = |content_hash| find_content_registry_address(&program_id, &content_hash).0;
+    let registry_rent = runtime.account(&registry([7; 32])).unwrap().lamports;
+    runtime.process(&transfer_knowledge_ownership(&program_id, &staker, &knowledge_entry, &new_owner)).unwrap();
+
+    // Moving the entry to new content refunds the registry it leaves
+    let update = update_knowledge(&program_id, &new_owner, &knowledge_entry, &[7; 32], None, Some([8; 32]));
+    assert_eq!(runtime.process(&update), Err(ProgramError::NotEnoughAccountKeys));
+    let misdirected = refunding_registry_to(update.clone(), &new_owner);
+    assert_eq!(runtime.process(&misdirected), Err(SolSageError::RegistryPayerMismatch.into()));
+    let lamports = |runtime: &TestRuntime, address: &Pubkey| runtime.account(address).unwrap().lamports;
+    let staker_lamports = lamports(&runtime, &staker);
+    runtime.process(&refunding_registry_to(update, &staker)).unwrap();
+    assert_eq!(lamports(&runtime, &staker), staker_lamports + registry_rent);
+    assert_eq!(state::<ContentRegistry>(&runtime, &registry([8; 32])).staker, new_owner);
+
+    // The new registry was the new owner's, so closing refunds them
+    runtime.process(&request_unstake(&program_id, &new_owner, &knowledge_entry)).unwrap();
+    let unlocks_at = state::<KnowledgeEntry>(&runtime, &knowledge_entry).unstake_unlocks_at().unwrap();
+    runtime.warp_forward(unlocks_at - runtime.clock().unix_timestamp);
+    runtime.process(&complete_unstake(&program_id, &new_owner, &knowledge_entry)).unwrap();
+    let close = close_knowledge_entry(&program_id, &new_owner, &staker, &knowledge_entry, &category, &[8; 32]);
+    let owner_lamports = lamports(&runtime, &new_owner);
+    let reclaimed = lamports(&runtime, &knowledge_entry) + lamports(&runtime, &registry([8; 32]));
+    runtime.process(&close).unwrap();
+    assert!(runtime.account(&registry([8; 32])).is_none());
+    assert_eq!(lamports(&runtime, &new_owner), owner_lamports + reclaimed);
+    assert_eq!(lamports(&runtime, &staker), staker_lamports + registry_rent);
+}
+
+#[test]
+fn oracles_record_within_their_budget_each_epoch() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+    let oracle_state = find_oracle_state_address(&program_id, &oracle).0;
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    let attribute = |query: u8| {
+        record_attribution(&program_id, &oracle, &target, [query; 32], None, None, AttributionMetadata::default())
+    };
+
+    assert_eq!(
+        runtime.process(&set_oracle_budget(&program_id, &staker, &oracle, 2, 0)),
+        Err(SolSageError::Unauthorized.into())
+    );
+    runtime.process(&set_oracle_budget(&program_id, &authority, &oracle, 2, 0)).unwrap();
+    runtime.process(&attribute(1)).unwrap();
+    runtime.process(&attribute(2)).unwrap();
+    assert_eq!(runtime.process(&attribute(3)), Err(SolSageError::OracleBudgetExceeded.into()));
+
+    // The budget refills once the epoch is over
+    runtime.warp_forward(OracleState::BUDGET_EPOCH_SECS);
+    runtime.process(&attribute(3)).unwrap();
+    let budget: OracleState = state(&runtime, &oracle_state);
+    let attribution = find_attribution_address(&program_id, &[3; 32], &knowledge_entry).0;
+    let reward = state::<Attribution>(&runtime, &attribution).reward;
+    assert_eq!((budget.epoch_attributions, budget.epoch_rewards), (1, reward));
+
+    // Rewards are capped alongside the count
+    runtime.process(&set_oracle_budget(&program_id, &authority, &oracle, 0, 2 * reward - 1)).unwrap();
+    assert_eq!(runtime.process(&attribute(4)), Err(SolSageError::OracleBudgetExceeded.into()));
+    runtime.process(&set_oracle_budget(&program_id, &authority, &oracle, 0, 0)).unwrap();
+    runtime.process(&attribute(4)).unwrap();
+}
+
+#[test]
+fn attributions_under_consensus_finalize_once_enough_oracles_agree() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+    let second_oracle = Pubkey::new_unique();
+    runtime.fund(second_oracle, SOL);
+    runtime.process(&add_oracle(&program_id, &authority, &second_oracle)).unwrap();
+    let attribution = find_attribution_address(&program_id, &[9; 32], &knowledge_entry).0;
+    let attribute = |oracle: &Pubkey, relevance_score: u8| {
+        let target = AttributionTarget::new(knowledge_entry, staker, category, relevance_score);
+        record_attribution(&program_id, oracle, &target, [9; 32], None, None, AttributionMetadata::default())
+    };
+
+    assert_eq!(runtime.process(&set_consensus(&program_id, &staker, 2, 5)), Err(SolSageError::Unauthorized.into()));
+    assert_eq!(
+        runtime.process(&set_consensus(&program_id, &authority, 6, 5)),
+        Err(SolSageError::InvalidConsensusThreshold.into())
+    );
+    runtime.process(&set_consensus(&program_id, &authority, 2, 5)).unwrap();
+
+    // The first report leaves the attribution pending, with nothing credited
+    runtime.process(&attribute(&oracle, 80)).unwrap();
+    let pending: Attribution = state(&runtime, &attribution);
+    assert!(pending.pending);
+    assert_eq!((pending.reward, pending.votes.len()), (0, 1));
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &knowledge_entry).total_attributions, 0);
+
+    assert_eq!(runtime.process(&attribute(&oracle, 80)), Err(SolSageError::OracleAlreadyVoted.into()));
+    assert_eq!(runtime.process(&attribute(&second_oracle, 90)), Err(SolSageError::ConsensusScoreMismatch.into()));
+    let batch = record_attribution_batch(&program_id, &oracle, [10; 32], &[], None);
+    assert_eq!(runtime.process(&batch), Err(SolSageError::ConsensusRequired.into()));
+
+    // The matching report finalizes it at the reports' average score
+    runtime.process(&attribute(&second_oracle, 84)).unwrap();
+    let finalized: Attribution = state(&runtime, &attribution);
+    assert!(!finalized.pending && finalized.reward > 0);
+    assert_eq!((finalized.relevance_score, finalized.votes.len()), (82, 2));
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &knowledge_entry).total_attributions, 1);
+    assert_eq!(runtime.process(&attribute(&second_oracle, 84)), Err(SolSageError::AlreadyInitialized.into()));
+}
+
+#[test]
+fn aggregated_attributions_are_rewarded_at_their_average_score_once_finalized() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+    let (second_oracle, late_oracle) = (Pubkey::new_unique(), Pubkey::new_unique());
+    for added in [second_oracle, late_oracle] {
+        runtime.fund(added, SOL);
+        runtime.process(&add_oracle(&program_id, &authority, &added)).unwrap();
+    }
+    let attribution = find_attribution_address(&program_id, &[9; 32], &knowledge_entry).0;
+    let target = |relevance_score: u8| AttributionTarget::new(knowledge_entry, staker, category, relevance_score);
+    let attribute = |oracle: &Pubkey, relevance_score: u8| {
+        record_attribution(&program_id, oracle, &target(relevance_score), [9; 32], None, None, AttributionMetadata::default())
+    };
+    let finalize = finalize_attribution(&program_id, &staker, &target(0), [9; 32], None, None);
+
+    assert_eq!(
+        runtime.process(&set_aggregation_window(&program_id, &staker, 3_600)),
+        Err(SolSageError::Unauthorized.into())
+    );
+    assert_eq!(
+        runtime.process(&set_aggregation_window(&program_id, &authority, -1)),
+        Err(SolSageError::InvalidAggregationWindow.into())
+    );
+    runtime.process(&set_aggregation_window(&program_id, &authority, 3_600)).unwrap();
+    assert_eq!(
+        runtime.process(&set_consensus(&program_id, &authority, 2, 5)),
+        Err(SolSageError::ConsensusWithAggregation.into())
+    );
+
+    // Scores accumulate on the pending attribution, whatever their spread
+    runtime.process(&attribute(&oracle, 60)).unwrap();
+    runtime.process(&attribute(&second_oracle, 90)).unwrap();
+    let pending: Attribution = state(&runtime, &attribution);
+    assert!(pending.pending);
+    assert_eq!((pending.relevance_score, pending.votes.len(), pending.reward), (75, 2, 0));
+    assert_eq!(runtime.process(&finalize), Err(SolSageError::AggregationWindowOpen.into()));
+    let batch = record_attribution_batch(&program_id, &oracle, [10; 32], &[], None);
+    assert_eq!(runtime.process(&batch), Err(SolSageError::ConsensusRequired.into()));
+
+    runtime.warp_forward(3_600);
+    assert_eq!(runtime.process(&attribute(&late_oracle, 10)), Err(SolSageError::AggregationWindowClosed.into()));
+
+    // Anyone may finalize, and the reward follows the aggregate score
+    runtime.process(&finalize).unwrap();
+    let finalized: Attribution = state(&runtime, &attribution);
+    assert!(!finalized.pending && finalized.reward > 0);
+    assert_eq!(finalized.relevance_score, 75);
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &knowledge_entry).total_attributions, 1);
+    assert_eq!(state::<Protocol>(&runtime, &find_protocol_address(&program_id).0).total_attributions, 1);
+    assert_eq!(runtime.process(&finalize), Err(SolSageError::AlreadyInitialized.into()));
+}
+
+#[test]
+fn payers_are_capped_at_their_daily_attributions() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    let attribute = |query_hash: [u8; 32]| {
+        record_attribution(&program_id, &oracle, &target, query_hash, None, None, AttributionMetadata::default())
+    };
+
+    assert_eq!(
+        runtime.process(&set_payer_attribution_cap(&program_id, &staker, 2)),
+        Err(SolSageError::Unauthorized.into())
+    );
+    runtime.process(&set_payer_attribution_cap(&program_id, &authority, 2)).unwrap();
+
+    runtime.process(&attribute([9; 32])).unwrap();
+    runtime.process(&attribute([10; 32])).unwrap();
+    assert_eq!(runtime.process(&attribute([11; 32])), Err(SolSageError::PayerAttributionCapExceeded.into()));
+    // Batches count against the same cap
+    let batch = record_attribution_batch(&program_id, &oracle, [11; 32], &[target], None);
+    assert_eq!(runtime.process(&batch), Err(SolSageError::PayerAttributionCapExceeded.into()));
+
+    // The count starts over the next day
+    runtime.warp_forward(PayerStats::DAY_SECS);
+    runtime.process(&batch).unwrap();
+    let stats: PayerStats = state(&runtime, &find_payer_stats_address(&program_id, &oracle).0);
+    assert_eq!((stats.day_attributions, stats.total_attributions), (1, 3));
+}
+
+#[test]
+fn sage_voted_on_an_entry_scales_its_rewards_until_withdrawn() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    let attribute = |query_hash: [u8; 32]| {
+        record_attribution(&program_id, &oracle, &target, query_hash, None, None, AttributionMetadata::default())
+    };
+    let reward = |runtime: &TestRuntime, query_hash: [u8; 32]| {
+        state::<Attribution>(runtime, &find_attribution_address(&program_id, &query_hash, &knowledge_entry).0).reward
+    };
+
+    // The staker's claimed rewards are the SAGE they vote with
+    runtime.process(&attribute([9; 32])).unwrap();
+    runtime.warp_forward(2 * Protocol::DEFAULT_VESTING_PERIOD_SECS);
+    let sage = Pubkey::new_unique();
+    runtime.add_token_account(sage, find_mint_address(&program_id).0, staker);
+    let claims = [AttributionClaim { attribution: find_attribution_address(&program_id, &[9; 32], &knowledge_entry).0, epoch: None }];
+    runtime
+        .process(&claim_rewards(&program_id, &staker, &staker, &knowledge_entry, &sage, &[], &claims))
+        .unwrap();
+    let weight = runtime.token_balance(&sage);
+    let upvote = upvote_entry(&program_id, &staker, &knowledge_entry, &sage, weight);
+
+    assert_eq!(runtime.process(&upvote), Err(SolSageError::GovernanceDisabled.into()));
+    runtime
+        .process(&set_governance_config(&program_id, &authority, 1, 5_000, Protocol::MIN_VOTING_PERIOD_SECS, 0))
+        .unwrap();
+    runtime.process(&upvote).unwrap();
+    assert_eq!(runtime.token_balance(&sage), 0);
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &knowledge_entry).quality_score, weight as i64);
+    let downvote = downvote_entry(&program_id, &staker, &knowledge_entry, &sage, 1);
+    assert_eq!(runtime.process(&downvote), Err(SolSageError::AlreadyInitialized.into()));
+
+    // Later attributions earn the quality multiplier on top
+    runtime.process(&attribute([10; 32])).unwrap();
+    let multiplier_bps = solsage_core::quality_multiplier_bps(weight as i64);
+    assert!(multiplier_bps > Protocol::MAX_BPS);
+    assert_eq!(reward(&runtime, [10; 32]), solsage_core::apply_bps(reward(&runtime, [9; 32]), multiplier_bps).unwrap());
+
+    // Withdrawing returns the SAGE and takes back its weight
+    runtime.process(&withdraw_entry_vote(&program_id, &staker, &knowledge_entry, &sage)).unwrap();
+    assert_eq!(runtime.token_balance(&sage), weight);
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &knowledge_entry).quality_score, 0);
+    assert!(runtime.account(&find_entry_vote_address(&program_id, &knowledge_entry, &staker).0).is_none());
+}
+
+#[test]
+fn entries_reported_as_spam_are_held_for_review() {
+    let Deployment { mut runtime, program_id, authority, staker, knowledge_entry, .. } = deploy();
+    let reporters = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+    for reporter in reporters {
+        runtime.fund(reporter, SOL);
+    }
+    let bond = ReportQueue::MIN_BOND;
+    let report_queue = find_report_queue_address(&program_id, &knowledge_entry).0;
+
+    assert_eq!(
+        runtime.process(&report_entry(&program_id, &reporters[0], &knowledge_entry, bond - 1)),
+        Err(SolSageError::ReportBondTooLow.into())
+    );
+    runtime.process(&report_entry(&program_id, &reporters[0], &knowledge_entry, bond)).unwrap();
+    assert_eq!(
+        runtime.process(&report_entry(&program_id, &reporters[0], &knowledge_entry, bond)),
+        Err(SolSageError::AlreadyReported.into())
+    );
+    assert!(state::<KnowledgeEntry>(&runtime, &knowledge_entry).is_active);
+
+    // The last report needed takes the entry out of circulation
+    for reporter in &reporters[1..] {
+        runtime.process(&report_entry(&program_id, reporter, &knowledge_entry, bond)).unwrap();
+    }
+    let entry: KnowledgeEntry = state(&runtime, &knowledge_entry);
+    assert!(!entry.is_active && entry.under_review);
+    assert!(state::<ReportQueue>(&runtime, &report_queue).flagged_at > 0);
+    assert_eq!(
+        runtime.process(&reactivate_knowledge(&program_id, &staker, &knowledge_entry)),
+        Err(SolSageError::EntryUnderReview.into())
+    );
+    assert_eq!(
+        runtime.process(&request_unstake(&program_id, &staker, &knowledge_entry)),
+        Err(SolSageError::EntryUnderReview.into())
+    );
+
+    assert_eq!(
+        runtime.process(&resolve_reports(&program_id, &staker, &knowledge_entry, &staker, &reporters, false)),
+        Err(SolSageError::Unauthorized.into())
+    );
+    let out_of_order = [reporters[1], reporters[0], reporters[2]];
+    assert_eq!(
+        runtime.process(&resolve_reports(&program_id, &authority, &knowledge_entry, &staker, &out_of_order, false)),
+        Err(SolSageError::ReportMismatch.into())
+    );
+
+    // Rejected reports forfeit their bonds to the owner and restore the entry
+    let staker_lamports = runtime.account(&staker).unwrap().lamports;
+    runtime
+        .process(&resolve_reports(&program_id, &authority, &knowledge_entry, &staker, &reporters, false))
+        .unwrap();
+    assert_eq!(runtime.account(&staker).unwrap().lamports, staker_lamports + 3 * bond);
+    let entry: KnowledgeEntry = state(&runtime, &knowledge_entry);
+    assert!(entry.is_active && !entry.under_review);
+    assert!(runtime.account(&report_queue).is_none());
+
+    // Upheld reports refund their bonds and keep the entry inactive
+    for reporter in reporters {
+        runtime.process(&report_entry(&program_id, &reporter, &knowledge_entry, bond)).unwrap();
+    }
+    let reporter_lamports = runtime.account(&reporters[1]).unwrap().lamports;
+    runtime
+        .process(&resolve_reports(&program_id, &authority, &knowledge_entry, &staker, &reporters, true))
+        .unwrap();
+    assert_eq!(runtime.account(&reporters[1]).unwrap().lamports, reporter_lamports + bond);
+    let entry: KnowledgeEntry = state(&runtime, &knowledge_entry);
+    assert!(!entry.is_active && !entry.under_review);
+}
+
+#[test]
+fn bounties_escrow_their_reward_until_an_answer_is_accepted_or_they_expire() {
+    let Deployment { mut runtime, program_id, oracle, staker, category, knowledge_entry, .. } = deploy();
+
+    // The staker's claimed rewards fund the bounties
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    runtime
+        .process(&record_attribution(&program_id, &oracle, &target, [9; 32], None, None, AttributionMetadata::default()))
+        .unwrap();
+    runtime.warp_forward(2 * Protocol::DEFAULT_VESTING_PERIOD_SECS);
+    let (sage_mint, requester_sage) = (find_mint_address(&program_id).0, Pubkey::new_unique());
+    runtime.add_token_account(requester_sage, sage_mint, staker);
+    let claims = [AttributionClaim { attribution: find_attribution_address(&program_id, &[9; 32], &knowledge_entry).0, epoch: None }];
+    runtime
+        .process(&claim_rewards(&program_id, &staker, &staker, &knowledge_entry, &requester_sage, &[], &claims))
+        .unwrap();
+    let reward = runtime.token_balance(&requester_sage) / 2;
+
+    let answerer = Pubkey::new_unique();
+    runtime.fund(answerer, 10 * SOL);
+    let answer = StakedContent { content_hash: [8; 32], ..content("Pinning futures") };
+    runtime.process(&stake_knowledge(&program_id, &answerer, 0, answer, SOL, &category)).unwrap();
+    let answer_entry = find_knowledge_entry_address(&program_id, &answerer, 0).0;
+    let answerer_sage = Pubkey::new_unique();
+    runtime.add_token_account(answerer_sage, sage_mint, answerer);
+
+    let now = runtime.clock().unix_timestamp;
+    let deadline = now + 24 * 60 * 60;
+    assert_eq!(
+        runtime.process(&create_bounty(&program_id, &staker, &requester_sage, [1; 32], reward, now)),
+        Err(SolSageError::InvalidBountyDeadline.into())
+    );
+    let too_late = now + Bounty::MAX_DURATION_SECS + 1;
+    assert_eq!(
+        runtime.process(&create_bounty(&program_id, &staker, &requester_sage, [1; 32], reward, too_late)),
+        Err(SolSageError::InvalidBountyDeadline.into())
+    );
+    runtime
+        .process(&create_bounty(&program_id, &staker, &requester_sage, [1; 32], reward, deadline))
+        .unwrap();
+    let bounty = find_bounty_address(&program_id, &staker, &[1; 32]).0;
+    assert_eq!(runtime.token_balance(&find_bounty_vault_address(&program_id, &bounty).0), reward);
+
+    // Only an entry's owner answers with it, once
+    assert_eq!(
+        runtime.process(&submit_answer(&program_id, &staker, &bounty, &answer_entry)),
+        Err(SolSageError::Unauthorized.into())
+    );
+    runtime.process(&submit_answer(&program_id, &answerer, &bounty, &answer_entry)).unwrap();
+    assert_eq!(
+        runtime.process(&submit_answer(&program_id, &answerer, &bounty, &answer_entry)),
+        Err(SolSageError::AlreadyInitialized.into())
+    );
+
+    // Only the requester accepts, paying the answering staker
+    assert_eq!(
+        runtime.process(&accept_answer(&program_id, &answerer, &bounty, &answer_entry, &answerer_sage)),
+        Err(SolSageError::Unauthorized.into())
+    );
+    assert_eq!(
+        runtime.process(&accept_answer(&program_id, &staker, &bounty, &answer_entry, &requester_sage)),
+        Err(SolSageError::InvalidTokenAccountOwner.into())
+    );
+    runtime
+        .process(&accept_answer(&program_id, &staker, &bounty, &answer_entry, &answerer_sage))
+        .unwrap();
+    assert_eq!(runtime.token_balance(&answerer_sage), reward);
+    assert_eq!(state::<Bounty>(&runtime, &bounty).accepted_entry, Some(answer_entry));
+    assert!(runtime.account(&find_bounty_vault_address(&program_id, &bounty).0).is_none());
+    assert_eq!(
+        runtime.process(&reclaim_expired_bounty(&program_id, &staker, &bounty, &requester_sage)),
+        Err(SolSageError::BountyClosed.into())
+    );
+
+    // An unanswered bounty is refunded once it expires
+    runtime
+        .process(&create_bounty(&program_id, &staker, &requester_sage, [2; 32], reward, deadline))
+        .unwrap();
+    let expiring = find_bounty_address(&program_id, &staker, &[2; 32]).0;
+    let reclaim = reclaim_expired_bounty(&program_id, &staker, &expiring, &requester_sage);
+    assert_eq!(runtime.process(&reclaim), Err(SolSageError::BountyNotExpired.into()));
+    runtime.warp_forward(24 * 60 * 60);
+    assert_eq!(
+        runtime.process(&submit_answer(&program_id, &answerer, &expiring, &answer_entry)),
+        Err(SolSageError::BountyExpired.into())
+    );
+    let balance = runtime.token_balance(&requester_sage);
+    runtime.process(&reclaim).unwrap();
+    assert_eq!(runtime.token_balance(&requester_sage), balance + reward);
+    assert!(runtime.account(&expiring).is_none());
+}
+
+#[test]
+fn protocol_counters_follow_reward_rate_changes() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+    let (protocol_address, treasury) = (find_protocol_address(&program_id).0, find_treasury_address(&program_id).0);
+    runtime.process(&set_protocol_fee(&program_id, &authority, 500)).unwrap();
+    let treasury_lamports = runtime.account(&treasury).unwrap().lamports;
+
+    // The second attribution is credited at the new rate
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    let mut rewards = Vec::new();
+    for (query_hash, reward_per_attribution) in [([9; 32], 1_000_000), ([10; 32], 3_000_000)] {
+        runtime.process(&set_reward_rate(&program_id, &authority, reward_per_attribution)).unwrap();
+        runtime
+            .process(&record_attribution(&program_id, &oracle, &target, query_hash, None, None, AttributionMetadata::default()))
+            .unwrap();
+        let attribution: Attribution = state(&runtime, &find_attribution_address(&program_id, &query_hash, &knowledge_entry).0);
+        rewards.push(attribution.reward);
+    }
+    assert!(rewards[1] > rewards[0]);
+
+    let protocol: Protocol = state(&runtime, &protocol_address);
+    let fees: u64 = rewards.iter().map(|reward| protocol.fee_on(*reward).unwrap()).sum();
+    assert_eq!(protocol.total_attributions, 2);
+    assert_eq!(protocol.reward_per_attribution, 3_000_000);
+    assert_eq!(protocol.fees_collected, fees);
+    assert_eq!(runtime.account(&treasury).unwrap().lamports - treasury_lamports, fees);
+
+    let entry: KnowledgeEntry = state(&runtime, &knowledge_entry);
+    assert_eq!(entry.total_attributions, 2);
+    assert_eq!(entry.pending_rewards, rewards.iter().sum::<u64>());
+}
+
+#[test]
+fn accounts_off_their_pdas_are_rejected() {
+    let Deployment { mut runtime, program_id, staker, category, .. } = deploy();
+
+    // A second protocol can't be initialized anywhere but the protocol PDA
+    let mut ix = initialize(&program_id, &staker);
+    ix.accounts[1].pubkey = Pubkey::new_unique();
+    assert_eq!(runtime.process(&ix), Err(SolSageError::InvalidPda.into()));
+
+    // An entry must sit at the PDA of its staker and index
+    let mut ix = stake_knowledge(&program_id, &staker, 1, content("Pinning"), SOL, &category);
+    ix.accounts[2].pubkey = find_knowledge_entry_address(&program_id, &staker, 2).0;
+    assert_eq!(runtime.process(&ix), Err(SolSageError::InvalidPda.into()));
+    assert!(runtime.account(&find_knowledge_entry_address(&program_id, &staker, 2).0).is_none());
+}
+
+#[test]
+fn instructions_need_their_signers() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+
+    let mut ix = stake_knowledge(&program_id, &staker, 1, content("Pinning"), SOL, &category);
+    ix.accounts[0].is_signer = false;
+    assert_eq!(runtime.process(&ix), Err(ProgramError::MissingRequiredSignature));
+
+    // Only the authority administers the protocol
+    assert_eq!(
+        runtime.process(&add_oracle(&program_id, &staker, &Pubkey::new_unique())),
+        Err(SolSageError::Unauthorized.into())
+    );
+    let mut ix = add_oracle(&program_id, &authority, &Pubkey::new_unique());
+    ix.accounts[0].is_signer = false;
+    assert_eq!(runtime.process(&ix), Err(ProgramError::MissingRequiredSignature));
+
+    // Attributions need a registered oracle's signature
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    let mut ix = record_attribution(&program_id, &oracle, &target, [9; 32], None, None, AttributionMetadata::default());
+    ix.accounts[0].is_signer = false;
+    assert_eq!(runtime.process(&ix), Err(ProgramError::MissingRequiredSignature));
+    let ix = record_attribution(&program_id, &staker, &target, [9; 32], None, None, AttributionMetadata::default());
+    assert_eq!(runtime.process(&ix), Err(SolSageError::UnauthorizedOracle.into()));
+}
+
+#[test]
+fn leaderboards_are_ranked_in_place_and_migrated_from_borsh_vectors() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+    let leaderboard = find_leaderboard_address(&program_id).0;
+
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    runtime
+        .process(&record_attribution(&program_id, &oracle, &target, [9; 32], None, None, AttributionMetadata::default()))
+        .unwrap();
+    let board: Leaderboard = state(&runtime, &leaderboard);
+    assert_eq!(board.entries(), [LeaderboardEntry { knowledge_entry, total_attributions: 1 }]);
+
+    // Version 1 kept the entries in a Borsh vector, followed by the bump
+    let bump = board.bump;
+    let mut legacy = vec![true as u8, 1];
+    legacy.extend(vec![LeaderboardEntry { knowledge_entry, total_attributions: 1 }].try_to_vec().unwrap());
+    legacy.push(bump);
+    legacy.resize(Leaderboard::LEN - 1, 0);
+    runtime.add_account(leaderboard, Account::new(legacy, program_id));
+
+    let ix = record_attribution(&program_id, &oracle, &target, [10; 32], None, None, AttributionMetadata::default());
+    assert_eq!(runtime.process(&ix), Err(SolSageError::AccountNeedsMigration.into()));
+    runtime
+        .process(&migrate_account(&program_id, &authority, &leaderboard, AccountType::Leaderboard))
+        .unwrap();
+    runtime.process(&ix).unwrap();
+
+    let board: Leaderboard = state(&runtime, &leaderboard);
+    assert_eq!(runtime.account(&leaderboard).unwrap().data.len(), Leaderboard::LEN);
+    assert_eq!((board.bump, board.schema_version), (bump, LEADERBOARD_SCHEMA_VERSION));
+    assert_eq!(board.entries(), [LeaderboardEntry { knowledge_entry, total_attributions: 2 }]);
+}
+
+#[test]
+fn streamed_rewards_are_claimed_as_they_accrue() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+    let period = Protocol::MIN_REWARD_STREAM_SECS;
+    assert_eq!(
+        runtime.process(&set_reward_stream(&program_id, &staker, period)),
+        Err(SolSageError::Unauthorized.into())
+    );
+    assert_eq!(
+        runtime.process(&set_reward_stream(&program_id, &authority, period - 1)),
+        Err(SolSageError::InvalidRewardStream.into())
+    );
+    runtime.process(&set_reward_stream(&program_id, &authority, period)).unwrap();
+
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    runtime
+        .process(&record_attribution(&program_id, &oracle, &target, [9; 32], None, None, AttributionMetadata::default()))
+        .unwrap();
+    let attribution_address = find_attribution_address(&program_id, &[9; 32], &knowledge_entry).0;
+    let attribution: Attribution = state(&runtime, &attribution_address);
+    assert!(attribution.streamed);
+
+    // The stream pays the reward, not the attribution
+    let destination = Pubkey::new_unique();
+    runtime.add_token_account(destination, find_mint_address(&program_id).0, staker);
+    let claims = [AttributionClaim { attribution: attribution_address, epoch: None }];
+    assert_eq!(
+        runtime.process(&claim_rewards(&program_id, &staker, &staker, &knowledge_entry, &destination, &[], &claims)),
+        Err(SolSageError::RewardStreamed.into())
+    );
+
+    runtime.warp_forward(period / 2);
+    runtime.process(&claim_rewards(&program_id, &staker, &staker, &knowledge_entry, &destination, &[], &[])).unwrap();
+    let halfway = runtime.token_balance(&destination);
+    assert!(halfway > 0 && halfway < attribution.reward);
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &knowledge_entry).pending_rewards, attribution.reward - halfway);
+
+    runtime.warp_forward(period);
+    runtime.process(&claim_rewards(&program_id, &staker, &staker, &knowledge_entry, &destination, &[], &[])).unwrap();
+    assert_eq!(runtime.token_balance(&destination), attribution.reward);
+    assert_eq!(
+        runtime.process(&claim_rewards(&program_id, &staker, &staker, &knowledge_entry, &destination, &[], &[])),
+        Err(SolSageError::NoRewardsToClaim.into())
+    );
+}
+
+#[test]
+fn fee_config_carves_treasury_and_burned_shares_out_of_rewards() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+    let fees = FeeConfig { treasury_bps: 1_000, curator_bps: 500, burn_bps: 500 };
+    assert_eq!(runtime.process(&set_fee_config(&program_id, &staker, fees)), Err(SolSageError::Unauthorized.into()));
+    assert_eq!(
+        runtime.process(&set_fee_config(&program_id, &authority, FeeConfig { burn_bps: 9_000, ..fees })),
+        Err(SolSageError::InvalidFeeConfig.into())
+    );
+    runtime.process(&set_fee_config(&program_id, &authority, fees)).unwrap();
+    let protocol_address = find_protocol_address(&program_id).0;
+    assert_eq!(state::<Protocol>(&runtime, &protocol_address).curator_bps, 500);
+
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    runtime
+        .process(&record_attribution(&program_id, &oracle, &target, [9; 32], None, None, AttributionMetadata::default()))
+        .unwrap();
+    let attribution: Attribution = state(&runtime, &find_attribution_address(&program_id, &[9; 32], &knowledge_entry).0);
+    let protocol: Protocol = state(&runtime, &protocol_address);
+    let reward = attribution.reward + protocol.treasury_rewards + protocol.rewards_burned;
+    // Without a curator the curator share stays with the entry
+    let shares = FeeConfig { curator_bps: 0, ..fees }.split(reward).unwrap();
+    assert_eq!(
+        (shares.entry, shares.treasury, shares.burned),
+        (attribution.reward, protocol.treasury_rewards, protocol.rewards_burned)
+    );
+    assert!(shares.treasury > 0 && shares.burned > 0);
+    assert_eq!(protocol.total_rewards, attribution.reward + shares.treasury);
+
+    let destination = Pubkey::new_unique();
+    runtime.add_token_account(destination, find_mint_address(&program_id).0, authority);
+    assert_eq!(
+        runtime.process(&claim_treasury_rewards(&program_id, &staker, &destination)),
+        Err(SolSageError::Unauthorized.into())
+    );
+    runtime.process(&claim_treasury_rewards(&program_id, &authority, &destination)).unwrap();
+    assert_eq!(runtime.token_balance(&destination), shares.treasury);
+    assert_eq!(
+        runtime.process(&claim_treasury_rewards(&program_id, &authority, &destination)),
+        Err(SolSageError::NoRewardsToClaim.into())
+    );
+}
+
+#[test]
+fn derivative_entries_pay_their_parents_a_royalty_on_attributions() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+    assert_eq!(runtime.process(&set_citation_royalty(&program_id, &staker, 2_000)), Err(SolSageError::Unauthorized.into()));
+    assert_eq!(
+        runtime.process(&set_citation_royalty(&program_id, &authority, Protocol::MAX_CITATION_ROYALTY_BPS + 1)),
+        Err(SolSageError::InvalidCitationRoyalty.into())
+    );
+    runtime.process(&set_citation_royalty(&program_id, &authority, 2_000)).unwrap();
+
+    let author = Pubkey::new_unique();
+    runtime.fund(author, 10 * SOL);
+    let content = StakedContent { content_hash: [8; 32], title: "More async Rust".to_string(), license: LicenseType::Mit };
+    let stake = stake_knowledge(&program_id, &author, 0, content, SOL, &category);
+    assert_eq!(
+        runtime.process(&derived_from(stake.clone(), &[knowledge_entry, knowledge_entry])),
+        Err(SolSageError::InvalidParentEntries.into())
+    );
+    runtime.process(&derived_from(stake, &[knowledge_entry])).unwrap();
+    let derivative = find_knowledge_entry_address(&program_id, &author, 0).0;
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &derivative).derived_from, [knowledge_entry]);
+
+    let target = AttributionTarget::new(derivative, author, category, 80);
+    runtime
+        .process(&record_attribution(&program_id, &oracle, &target, [9; 32], None, None, AttributionMetadata::default()))
+        .unwrap();
+    let attribution: Attribution = state(&runtime, &find_attribution_address(&program_id, &[9; 32], &derivative).0);
+    let royalty = state::<KnowledgeEntry>(&runtime, &derivative).citation_royalties;
+    assert!(royalty > 0);
+    assert_eq!(royalty, (attribution.reward + royalty) / 5);
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &derivative).pending_rewards, attribution.reward);
+
+    // Anyone pays the parents; the parent's owner claims the royalty with
+    // its rewards
+    assert_eq!(
+        runtime.process(&pay_citation_royalties(&program_id, &derivative, &[derivative])),
+        Err(SolSageError::InvalidParentEntries.into())
+    );
+    runtime.process(&pay_citation_royalties(&program_id, &derivative, &[knowledge_entry])).unwrap();
+    let parent: KnowledgeEntry = state(&runtime, &knowledge_entry);
+    assert_eq!((parent.citation_rewards, parent.pending_rewards), (royalty, royalty));
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &derivative).citation_royalties, 0);
+    assert_eq!(
+        runtime.process(&pay_citation_royalties(&program_id, &derivative, &[knowledge_entry])),
+        Err(SolSageError::NoCitationRoyalties.into())
+    );
+
+    let destination = Pubkey::new_unique();
+    runtime.add_token_account(destination, find_mint_address(&program_id).0, staker);
+    runtime
+        .process(&claim_rewards(&program_id, &staker, &staker, &knowledge_entry, &destination, &[], &[]))
+        .unwrap();
+    assert_eq!(runtime.token_balance(&destination), royalty);
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &knowledge_entry).pending_rewards, 0);
+
+    // A derivative holding royalties stays open until they are paid, and a
+    // closed parent's share is forfeited
+    runtime.process(&request_unstake(&program_id, &staker, &knowledge_entry)).unwrap();
+    runtime
+        .process(&record_attribution(&program_id, &oracle, &target, [10; 32], None, None, AttributionMetadata::default()))
+        .unwrap();
+    runtime.warp_forward(2 * Protocol::DEFAULT_VESTING_PERIOD_SECS);
+    runtime.process(&complete_unstake(&program_id, &staker, &knowledge_entry)).unwrap();
+    runtime.process(&close_knowledge_entry(&program_id, &staker, &staker, &knowledge_entry, &category, &[7; 32])).unwrap();
+
+    runtime.process(&request_unstake(&program_id, &author, &derivative)).unwrap();
+    let author_sage = Pubkey::new_unique();
+    runtime.add_token_account(author_sage, find_mint_address(&program_id).0, author);
+    let claims = [[9; 32], [10; 32]].map(|query_hash| AttributionClaim {
+        attribution: find_attribution_address(&program_id, &query_hash, &derivative).0,
+        epoch: None,
+    });
+    runtime
+        .process(&claim_rewards(&program_id, &author, &author, &derivative, &author_sage, &[], &claims))
+        .unwrap();
+    runtime.warp_forward(2 * Protocol::DEFAULT_VESTING_PERIOD_SECS);
+    runtime.process(&complete_unstake(&program_id, &author, &derivative)).unwrap();
+    let close = close_knowledge_entry(&program_id, &author, &author, &derivative, &category, &[8; 32]);
+    assert_eq!(runtime.process(&close), Err(SolSageError::CitationRoyaltiesUnpaid.into()));
+
+    runtime.process(&pay_citation_royalties(&program_id, &derivative, &[knowledge_entry])).unwrap();
+    assert!(runtime.account(&knowledge_entry).is_none());
+    runtime.process(&close).unwrap();
+}
+
+#[test]
+fn low_relevance_attributions_burn_part_of_their_reward() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+    assert_eq!(
+        runtime.process(&set_low_relevance_burn(&program_id, &staker, 50, 5_000)),
+        Err(SolSageError::Unauthorized.into())
+    );
+    assert_eq!(
+        runtime.process(&set_low_relevance_burn(&program_id, &authority, 101, 5_000)),
+        Err(SolSageError::InvalidLowRelevanceBurn.into())
+    );
+    runtime.process(&set_low_relevance_burn(&program_id, &authority, 50, 5_000)).unwrap();
+
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    let relevant = AttributionMetadata::default();
+    runtime.process(&record_attribution(&program_id, &oracle, &target, [1; 32], None, None, relevant)).unwrap();
+    let paid: Attribution = state(&runtime, &find_attribution_address(&program_id, &[1; 32], &knowledge_entry).0);
+    assert!(paid.reward > 0);
+
+    let target = AttributionTarget { relevance_score: 49, ..target };
+    runtime
+        .process(&record_attribution(&program_id, &oracle, &target, [2; 32], None, None, AttributionMetadata::default()))
+        .unwrap();
+    let burned: Attribution = state(&runtime, &find_attribution_address(&program_id, &[2; 32], &knowledge_entry).0);
+    let protocol: Protocol = state(&runtime, &find_protocol_address(&program_id).0);
+    assert_eq!(burned.reward, 0);
+    assert!(protocol.rewards_burned > 0);
+    assert_eq!(protocol.total_rewards, paid.reward);
+    let entry: KnowledgeEntry = state(&runtime, &knowledge_entry);
+    assert_eq!(entry.pending_rewards, paid.reward);
+
+    // Minted rewards are burned by never minting them, so the vault owes no burn
+    assert_eq!(protocol.pending_burn, 0);
+    assert_eq!(runtime.process(&burn_rewards(&program_id)), Err(SolSageError::NothingToBurn.into()));
+}
+
+#[test]
+fn documents_roll_their_chunks_up_to_the_owner() {
+    let Deployment { mut runtime, program_id, oracle, staker, category, knowledge_entry, .. } = deploy();
+    assert_eq!(
+        runtime.process(&stake_document(&program_id, &staker, [5; 32], "Async Rust book", 0, &category)),
+        Err(SolSageError::InvalidChunkCount.into())
+    );
+
+    let chunks = [
+        StakedContent { content_hash: [11; 32], title: "Async Rust book, part 1".to_string(), license: LicenseType::Mit },
+        StakedContent { content_hash: [12; 32], title: "Async Rust book, part 2".to_string(), license: LicenseType::Mit },
+    ];
+    for instruction in stake_document_chunks(&program_id, &staker, [5; 32], "Async Rust book", &category, 1, &chunks, SOL) {
+        runtime.process(&instruction).unwrap();
+    }
+    let document_address = find_document_address(&program_id, &staker, &[5; 32]).0;
+    let parts = [find_knowledge_entry_address(&program_id, &staker, 1).0, find_knowledge_entry_address(&program_id, &staker, 2).0];
+    let document: Document = state(&runtime, &document_address);
+    assert_eq!((document.owner, document.category), (staker, category));
+    assert_eq!(document.added_chunks().collect::<Vec<_>>(), parts.iter().collect::<Vec<_>>());
+    let part: KnowledgeEntry = state(&runtime, &parts[1]);
+    assert_eq!((part.document, part.chunk_index), (Some(document_address), 1));
+
+    // Each slot takes one entry, and only an entry of the document's
+    assert_eq!(
+        runtime.process(&add_document_chunk(&program_id, &staker, &document_address, &knowledge_entry, 0)),
+        Err(SolSageError::InvalidChunkIndex.into())
+    );
+    assert_eq!(
+        runtime.process(&add_document_chunk(&program_id, &staker, &document_address, &parts[0], 1)),
+        Err(SolSageError::EntryInDocument.into())
+    );
+    let stranger = Pubkey::new_unique();
+    assert_eq!(
+        runtime.process(&add_document_chunk(&program_id, &stranger, &document_address, &knowledge_entry, 0)),
+        Err(SolSageError::NotDocumentOwner.into())
+    );
+
+    // Chunks stay with the owner
+    assert_eq!(
+        runtime.process(&transfer_knowledge_ownership(&program_id, &staker, &parts[0], &stranger)),
+        Err(SolSageError::EntryInDocument.into())
+    );
+    assert_eq!(
+        runtime.process(&list_entry_for_sale(&program_id, &staker, &parts[0], SOL)),
+        Err(SolSageError::EntryInDocument.into())
+    );
+    let part: KnowledgeEntry = state(&runtime, &parts[0]);
+    assert_eq!(
+        runtime.process(&update_knowledge(&program_id, &staker, &parts[0], &part.content_hash, None, Some([0xee; 32]))),
+        Err(SolSageError::EntryInDocument.into())
+    );
+
+    let target = AttributionTarget::new(parts[1], staker, category, 80);
+    runtime
+        .process(&record_attribution(&program_id, &oracle, &target, [9; 32], None, None, AttributionMetadata::default()))
+        .unwrap();
+    assert_eq!(
+        runtime.process(&refresh_document(&program_id, &document_address, &[parts[1], parts[0]])),
+        Err(SolSageError::DocumentMismatch.into())
+    );
+    runtime.process(&refresh_document(&program_id, &document_address, &parts)).unwrap();
+    let document: Document = state(&runtime, &document_address);
+    let part: KnowledgeEntry = state(&runtime, &parts[1]);
+    assert_eq!((document.total_attributions, document.pending_rewards), (1, part.pending_rewards));
+    assert!(document.pending_rewards > 0);
+}
+
+#[test]
+fn attributions_prove_the_committed_chunk_they_used() {
+    let Deployment { mut runtime, program_id, oracle, staker, category, knowledge_entry, .. } = deploy();
+    let chunks = ["Futures are lazy.", "Pin keeps a future in place.", "Wakers reschedule tasks."];
+    let content = StakedContent { content_hash: [8; 32], title: "Async Rust, chunked".to_string(), license: LicenseType::Mit };
+    let stake = stake_knowledge(&program_id, &staker, 1, content, SOL, &category);
+    let empty = ChunkCommitment { chunk_count: 0, ..chunk_commitment(&chunks) };
+    assert_eq!(runtime.process(&with_chunks(stake.clone(), empty)), Err(SolSageError::InvalidChunkCount.into()));
+    runtime.process(&with_chunks(stake, chunk_commitment(&chunks))).unwrap();
+    let chunked = find_knowledge_entry_address(&program_id, &staker, 1).0;
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &chunked).chunks, Some(chunk_commitment(&chunks)));
+
+    let attribute = |knowledge_entry, query_hash, chunk| {
+        let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+        let ix = record_attribution(&program_id, &oracle, &target, query_hash, None, None, AttributionMetadata::default());
+        with_chunk_proof(ix, chunk)
+    };
+
+    // The proof must lead to the entry's root from the chunk at its index
+    let mut wrong_index = chunk_proof(&chunks, 1);
+    wrong_index.index = 2;
+    assert_eq!(runtime.process(&attribute(chunked, [9; 32], wrong_index)), Err(SolSageError::InvalidChunkProof.into()));
+    let mut out_of_range = chunk_proof(&chunks, 2);
+    out_of_range.index = 3;
+    assert_eq!(runtime.process(&attribute(chunked, [9; 32], out_of_range)), Err(SolSageError::InvalidChunkProof.into()));
+    assert_eq!(
+        runtime.process(&attribute(knowledge_entry, [9; 32], chunk_proof(&chunks, 1))),
+        Err(SolSageError::NoChunkCommitment.into())
+    );
+
+    runtime.process(&attribute(chunked, [9; 32], chunk_proof(&chunks, 1))).unwrap();
+    let attribution: Attribution = state(&runtime, &find_attribution_address(&program_id, &[9; 32], &chunked).0);
+    assert_eq!(attribution.chunk_index, Some(1));
+    assert!(attribution.reward > 0);
+
+    // The commitment was over the content it replaced
+    let update = update_knowledge(&program_id, &staker, &chunked, &[8; 32], None, Some([0xcc; 32]));
+    runtime.process(&update).unwrap();
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &chunked).chunks, None);
+    assert_eq!(
+        runtime.process(&attribute(chunked, [10; 32], chunk_proof(&chunks, 1))),
+        Err(SolSageError::NoChunkCommitment.into())
+    );
+}
+
+#[test]
+fn owners_set_and_clear_their_entry_s_embedding_fingerprint() {
+    let Deployment { mut runtime, program_id, staker, knowledge_entry, .. } = deploy();
+    let stranger = Pubkey::new_unique();
+    runtime.fund(stranger, SOL);
+    let fingerprint = [0xa5; KnowledgeEntry::EMBEDDING_FINGERPRINT_LEN];
+
+    assert_eq!(
+        runtime.process(&update_embedding_fingerprint(&program_id, &stranger, &knowledge_entry, Some(fingerprint))),
+        Err(SolSageError::NotKnowledgeOwner.into())
+    );
+    runtime.process(&update_embedding_fingerprint(&program_id, &staker, &knowledge_entry, Some(fingerprint))).unwrap();
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &knowledge_entry).embedding_fingerprint, Some(fingerprint));
+
+    runtime.process(&update_embedding_fingerprint(&program_id, &staker, &knowledge_entry, None)).unwrap();
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &knowledge_entry).embedding_fingerprint, None);
+}
+
+#[test]
+fn entries_verified_by_approved_reviewers_earn_boosted_rewards() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+    let reviewer = Pubkey::new_unique();
+    runtime.fund(reviewer, SOL);
+    let content = StakedContent { content_hash: [8; 32], title: "Async Rust, unreviewed".to_string(), license: LicenseType::Mit };
+    runtime.process(&stake_knowledge(&program_id, &staker, 1, content, SOL, &category)).unwrap();
+    let unreviewed = find_knowledge_entry_address(&program_id, &staker, 1).0;
+
+    assert_eq!(runtime.process(&add_reviewer(&program_id, &staker, &reviewer)), Err(SolSageError::Unauthorized.into()));
+    runtime.process(&add_reviewer(&program_id, &authority, &reviewer)).unwrap();
+    runtime.process(&add_reviewer(&program_id, &authority, &staker)).unwrap();
+    assert_eq!(
+        runtime.process(&add_reviewer(&program_id, &authority, &reviewer)),
+        Err(SolSageError::ReviewerAlreadyAdded.into())
+    );
+    assert_eq!(
+        runtime.process(&attest_entry(&program_id, &oracle, &knowledge_entry, 90, 90)),
+        Err(SolSageError::NotReviewer.into())
+    );
+    assert_eq!(
+        runtime.process(&attest_entry(&program_id, &staker, &knowledge_entry, 90, 90)),
+        Err(SolSageError::SelfAttestation.into())
+    );
+    assert_eq!(
+        runtime.process(&attest_entry(&program_id, &reviewer, &knowledge_entry, 101, 90)),
+        Err(SolSageError::InvalidAttestationScore.into())
+    );
+
+    // Stale content is attested without being verified
+    runtime.process(&attest_entry(&program_id, &reviewer, &knowledge_entry, 90, 40)).unwrap();
+    assert!(!state::<KnowledgeEntry>(&runtime, &knowledge_entry).verified);
+    runtime.process(&attest_entry(&program_id, &reviewer, &knowledge_entry, 90, 80)).unwrap();
+    let entry: KnowledgeEntry = state(&runtime, &knowledge_entry);
+    assert!(entry.verified);
+    let attestation = entry.attestation.unwrap();
+    assert_eq!((attestation.reviewer, attestation.accuracy, attestation.freshness), (reviewer, 90, 80));
+    assert_eq!(attestation.content_hash, [7; 32]);
+
+    let attribute = |runtime: &mut TestRuntime, knowledge_entry, query_hash| {
+        let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+        runtime
+            .process(&record_attribution(&program_id, &oracle, &target, query_hash, None, None, AttributionMetadata::default()))
+            .unwrap();
+        state::<Attribution>(runtime, &find_attribution_address(&program_id, &query_hash, &knowledge_entry).0).reward
+    };
+    let verified_reward = attribute(&mut runtime, knowledge_entry, [1; 32]);
+    let unverified_reward = attribute(&mut runtime, unreviewed, [2; 32]);
+    assert_eq!(verified_reward, unverified_reward * solsage_core::VERIFIED_BOOST_BPS as u64 / 10_000);
+
+    // Removing the reviewer keeps its attestations, but new content needs a new one
+    runtime.process(&remove_reviewer(&program_id, &authority, &reviewer)).unwrap();
+    assert!(state::<KnowledgeEntry>(&runtime, &knowledge_entry).verified);
+    assert_eq!(
+        runtime.process(&attest_entry(&program_id, &reviewer, &unreviewed, 90, 90)),
+        Err(SolSageError::NotReviewer.into())
+    );
+    runtime.process(&update_knowledge(&program_id, &staker, &knowledge_entry, &[7; 32], None, Some([9; 32]))).unwrap();
+    assert!(!state::<KnowledgeEntry>(&runtime, &knowledge_entry).verified);
+}
+
+#[test]
+fn stakers_present_identity_attestations_recorded_on_their_entries() {
+    let Deployment { mut runtime, program_id, authority, staker, category, .. } = deploy();
+    let (attestation_program, credential) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let now = runtime.clock().unix_timestamp;
+    let attest = |runtime: &mut TestRuntime, owner: Pubkey, attestation: &identity::StakerAttestation| {
+        let address = Pubkey::new_unique();
+        runtime.add_account(address, Account::new(identity::attestation_data(attestation), owner));
+        address
+    };
+    let attestation = identity::StakerAttestation {
+        nonce: staker,
+        credential,
+        schema: Pubkey::new_unique(),
+        data: b"kyc:tier-2",
+        signer: Pubkey::new_unique(),
+        expiry: now + 3_600,
+    };
+    let valid = attest(&mut runtime, attestation_program, &attestation);
+    let stake = |entry_index: u64| {
+        let content =
+            StakedContent { content_hash: [entry_index as u8; 32], title: "Verified Rust".to_string(), license: LicenseType::Mit };
+        stake_knowledge(&program_id, &staker, entry_index, content, SOL, &category)
+    };
+
+    assert_eq!(
+        runtime.process(&with_identity(stake(1), &valid)),
+        Err(SolSageError::IdentityNotConfigured.into())
+    );
+    let config = IdentityConfig { attestation_program, credential, required: true };
+    assert_eq!(
+        runtime.process(&set_identity_config(&program_id, &staker, Some(config))),
+        Err(SolSageError::Unauthorized.into())
+    );
+    runtime.process(&set_identity_config(&program_id, &authority, Some(config))).unwrap();
+    assert_eq!(runtime.process(&stake(1)), Err(SolSageError::IdentityRequired.into()));
+
+    // Attestations must come from the attestation program, for the staker,
+    // under the credential and before they expire
+    let forged = attest(&mut runtime, Pubkey::new_unique(), &attestation);
+    let someone_else = attest(&mut runtime, attestation_program, &identity::StakerAttestation { nonce: authority, ..attestation });
+    let other_issuer =
+        attest(&mut runtime, attestation_program, &identity::StakerAttestation { credential: Pubkey::new_unique(), ..attestation });
+    for rejected in [forged, someone_else, other_issuer] {
+        assert_eq!(
+            runtime.process(&with_identity(stake(1), &rejected)),
+            Err(SolSageError::InvalidIdentityAttestation.into())
+        );
+    }
+    let expired = attest(&mut runtime, attestation_program, &identity::StakerAttestation { expiry: now, ..attestation });
+    assert_eq!(
+        runtime.process(&with_identity(stake(1), &expired)),
+        Err(SolSageError::IdentityAttestationExpired.into())
+    );
+
+    let parent = find_knowledge_entry_address(&program_id, &staker, 0).0;
+    runtime.process(&with_identity(derived_from(stake(1), &[parent]), &valid)).unwrap();
+    let knowledge_entry = find_knowledge_entry_address(&program_id, &staker, 1).0;
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &knowledge_entry).identity_credential, Some(attestation.credential_hash()));
+
+    // The identity stays with the staker that presented it
+    runtime.process(&transfer_knowledge_ownership(&program_id, &staker, &knowledge_entry, &authority)).unwrap();
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &knowledge_entry).identity_credential, None);
+
+    runtime.process(&set_identity_config(&program_id, &authority, None)).unwrap();
+    runtime.process(&stake(2)).unwrap();
+}
+
+#[test]
+fn private_entries_take_attributions_only_from_access_pass_holders() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+    let attribute = |access_pass: Option<Pubkey>, query_hash: [u8; 32]| {
+        let target = AttributionTarget { access_pass, ..AttributionTarget::new(knowledge_entry, staker, category, 80) };
+        record_attribution(&program_id, &oracle, &target, query_hash, None, None, AttributionMetadata::default())
+    };
+
+    assert_eq!(
+        runtime.process(&buy_access(&program_id, &oracle, &knowledge_entry, &staker, 0)),
+        Err(SolSageError::EntryNotPrivate.into())
+    );
+    let private = Visibility::Private { access_price: SOL / 10 };
+    assert_eq!(
+        runtime.process(&set_entry_visibility(&program_id, &oracle, &knowledge_entry, private)),
+        Err(SolSageError::NotKnowledgeOwner.into())
+    );
+    runtime.process(&set_entry_visibility(&program_id, &staker, &knowledge_entry, private)).unwrap();
+    assert_eq!(runtime.process(&attribute(None, [1; 32])), Err(SolSageError::AccessPassRequired.into()));
+
+    // Passes are the holder's own
+    runtime.process(&grant_access(&program_id, &staker, &knowledge_entry, &authority)).unwrap();
+    let granted = find_access_pass_address(&program_id, &knowledge_entry, &authority).0;
+    assert_eq!(state::<AccessPass>(&runtime, &granted).price_paid, 0);
+    assert_eq!(runtime.process(&attribute(Some(granted), [1; 32])), Err(SolSageError::AccessPassRequired.into()));
+
+    assert_eq!(
+        runtime.process(&buy_access(&program_id, &oracle, &knowledge_entry, &staker, SOL / 20)),
+        Err(SolSageError::AccessPriceChanged.into())
+    );
+    let lamports = runtime.account(&staker).unwrap().lamports;
+    runtime.process(&buy_access(&program_id, &oracle, &knowledge_entry, &staker, SOL / 10)).unwrap();
+    assert_eq!(runtime.account(&staker).unwrap().lamports, lamports + SOL / 10);
+    let bought = find_access_pass_address(&program_id, &knowledge_entry, &oracle).0;
+    let pass: AccessPass = state(&runtime, &bought);
+    assert_eq!((pass.knowledge_entry, pass.holder, pass.price_paid), (knowledge_entry, oracle, SOL / 10));
+
+    runtime.process(&attribute(Some(bought), [1; 32])).unwrap();
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &knowledge_entry).total_attributions, 1);
+}
+
+#[test]
+fn claimed_rewards_land_only_in_the_staker_s_sage_accounts() {
+    let Deployment { mut runtime, program_id, oracle, staker, category, knowledge_entry, .. } = deploy();
+    let mint = find_mint_address(&program_id).0;
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    runtime
+        .process(&record_attribution(&program_id, &oracle, &target, [9; 32], None, None, AttributionMetadata::default()))
+        .unwrap();
+    runtime.warp_forward(2 * Protocol::DEFAULT_VESTING_PERIOD_SECS);
+    let attribution = find_attribution_address(&program_id, &[9; 32], &knowledge_entry).0;
+    let claims = [AttributionClaim { attribution, epoch: None }];
+    let claim = |destination| claim_rewards(&program_id, &staker, &staker, &knowledge_entry, &destination, &[], &claims);
+
+    let (someone_else_s, other_mint_s) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let system_owned = Pubkey::new_unique();
+    runtime.add_token_account(someone_else_s, mint, Pubkey::new_unique());
+    runtime.add_token_account(other_mint_s, Pubkey::new_unique(), staker);
+    runtime.fund(system_owned, SOL);
+    assert_eq!(runtime.process(&claim(someone_else_s)), Err(SolSageError::InvalidTokenAccountOwner.into()));
+    assert_eq!(runtime.process(&claim(other_mint_s)), Err(SolSageError::InvalidRewardMint.into()));
+    assert_eq!(runtime.process(&claim(system_owned)), Err(SolSageError::InvalidTokenAccountOwner.into()));
+
+    let destination = Pubkey::new_unique();
+    runtime.add_token_account(destination, mint, staker);
+    let reward = state::<KnowledgeEntry>(&runtime, &knowledge_entry).pending_rewards;
+    runtime.process(&claim(destination)).unwrap();
+    assert_eq!(runtime.token_balance(&destination), reward);
+    assert_eq!(runtime.token_balance(&someone_else_s), 0);
+}
+
+#[test]
+fn rewards_are_minted_by_the_protocol_up_to_its_supply_cap() {
+    // Room for one attribution's reward at a score of 80, not two
+    let max_supply = 1_000_000;
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } =
+        deploy_with_max_supply(max_supply);
+    assert_eq!(
+        runtime.process(&initialize_mint(&program_id, &authority, MAX_SUPPLY)),
+        Err(SolSageError::MintAlreadyInitialized.into())
+    );
+    let mint = find_mint_address(&program_id).0;
+    let sage = |runtime: &TestRuntime| Mint::unpack(&runtime.account(&mint).unwrap().data).unwrap();
+    assert_eq!(sage(&runtime).mint_authority, COption::Some(find_mint_authority_address(&program_id).0));
+    assert_eq!(state::<Protocol>(&runtime, &find_protocol_address(&program_id).0).max_supply, max_supply);
+
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    for query_hash in [[9; 32], [10; 32]] {
+        runtime
+            .process(&record_attribution(&program_id, &oracle, &target, query_hash, None, None, AttributionMetadata::default()))
+            .unwrap();
+    }
+    runtime.warp_forward(2 * Protocol::DEFAULT_VESTING_PERIOD_SECS);
+    let destination = Pubkey::new_unique();
+    runtime.add_token_account(destination, mint, staker);
+    let claim = |query_hash| {
+        let attribution = find_attribution_address(&program_id, &query_hash, &knowledge_entry).0;
+        let claims = [AttributionClaim { attribution, epoch: None }];
+        claim_rewards(&program_id, &staker, &staker, &knowledge_entry, &destination, &[], &claims)
+    };
+    runtime.process(&claim([9; 32])).unwrap();
+    let minted = runtime.token_balance(&destination);
+    assert_eq!(sage(&runtime).supply, minted);
+
+    // A claim taking the supply past the cap is refused whole
+    assert_eq!(runtime.process(&claim([10; 32])), Err(SolSageError::SupplyCapExceeded.into()));
+    assert_eq!(sage(&runtime).supply, minted);
+}
+
+#[test]
+fn stakes_are_escrowed_per_entry_until_their_owner_unstakes() {
+    let Deployment { mut runtime, program_id, oracle, staker, category, knowledge_entry, .. } = deploy();
+    let escrow = find_escrow_address(&program_id, &knowledge_entry).0;
+    let escrow_rent = Rent::default().minimum_balance(0);
+    let lamports = |runtime: &TestRuntime, address: &Pubkey| runtime.account(address).unwrap().lamports;
+    assert_eq!(runtime.account(&escrow).unwrap().owner, program_id);
+    assert_eq!(lamports(&runtime, &escrow), escrow_rent + SOL);
+    assert_eq!(state::<KnowledgeEntry>(&runtime, &knowledge_entry).stake_amount, SOL);
+
+    // Each entry escrows its own stake, paid by its staker
+    let staker_lamports = lamports(&runtime, &staker);
+    let pinning = StakedContent { content_hash: [8; 32], ..content("Pinning") };
+    runtime.process(&stake_knowledge(&program_id, &staker, 1, pinning, 2 * SOL, &category)).unwrap();
+    let second_entry = find_knowledge_entry_address(&program_id, &staker, 1).0;
+    assert_eq!(lamports(&runtime, &find_escrow_address(&program_id, &second_entry).0), escrow_rent + 2 * SOL);
+    assert!(lamports(&runtime, &staker) <= staker_lamports - 2 * SOL - escrow_rent);
+    assert_eq!(lamports(&runtime, &escrow), escrow_rent + SOL);
+
+    // Only the owner takes the stake back
+    assert_eq!(
+        runtime.process(&request_unstake(&program_id, &oracle, &knowledge_entry)),
+        Err(SolSageError::NotKnowledgeOwner.into())
+    );
+    runtime.process(&request_unstake(&program_id, &staker, &knowledge_entry)).unwrap();
+    runtime.warp_forward(KnowledgeEntry::UNSTAKE_COOLDOWN_SECS);
+    let staker_lamports = lamports(&runtime, &staker);
+    runtime.process(&complete_unstake(&program_id, &staker, &knowledge_entry)).unwrap();
+    assert!(runtime.account(&escrow).is_none());
+    assert!(lamports(&runtime, &staker) >= staker_lamports + escrow_rent + SOL);
+    assert_eq!(
+        runtime.process(&request_unstake(&program_id, &staker, &knowledge_entry)),
+        Err(SolSageError::NothingStaked.into())
+    );
+}
+
+#[test]
+fn owners_edit_entries_in_place_bumping_their_version() {
+    let Deployment { mut runtime, program_id, oracle, staker, knowledge_entry, .. } = deploy();
+    let staked: KnowledgeEntry = state(&runtime, &knowledge_entry);
+    let retitle = |signer, title: &str| {
+        update_knowledge(&program_id, signer, &knowledge_entry, &[7; 32], Some(title.to_string()), None)
+    };
+    assert_eq!(runtime.process(&retitle(&oracle, "Async Rust, taken")), Err(SolSageError::NotKnowledgeOwner.into()));
+    let too_long = "a".repeat(KnowledgeEntry::MAX_TITLE_LEN + 1);
+    assert_eq!(runtime.process(&retitle(&staker, &too_long)), Err(SolSageError::TitleTooLong.into()));
+
+    runtime.warp_forward(60);
+    runtime.process(&retitle(&staker, "Async Rust, revised")).unwrap();
+    let edited: KnowledgeEntry = state(&runtime, &knowledge_entry);
+    assert_eq!(edited.title, "Async Rust, revised");
+    assert_eq!(edited.version, staked.version + 1);
+    assert_eq!(edited.updated_at, runtime.clock().unix_timestamp);
+
+    // A new content hash keeps the entry at the PDA of its staker and index
+    runtime.process(&update_knowledge(&program_id, &staker, &knowledge_entry, &[7; 32], None, Some([8; 32]))).unwrap();
+    let edited: KnowledgeEntry = state(&runtime, &knowledge_entry);
+    assert_eq!((edited.content_hash, edited.version), ([8; 32], staked.version + 2));
+    assert_eq!(find_knowledge_entry_address(&program_id, &staker, 0).0, knowledge_entry);
+}
+
+#[test]
+fn inactive_entries_take_no_attributions_until_reactivated() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    let attribute = |query: u8| {
+        record_attribution(&program_id, &oracle, &target, [query; 32], None, None, AttributionMetadata::default())
+    };
+    let is_active = |runtime: &TestRuntime| state::<KnowledgeEntry>(runtime, &knowledge_entry).is_active;
+
+    // Only the staker or the authority toggles an entry
+    assert_eq!(
+        runtime.process(&deactivate_knowledge(&program_id, &oracle, &knowledge_entry)),
+        Err(SolSageError::Unauthorized.into())
+    );
+    for signer in [staker, authority] {
+        runtime.process(&deactivate_knowledge(&program_id, &signer, &knowledge_entry)).unwrap();
+        assert!(!is_active(&runtime));
+        assert_eq!(runtime.process(&attribute(9)), Err(SolSageError::KnowledgeInactive.into()));
+        assert_eq!(
+            runtime.process(&reactivate_knowledge(&program_id, &oracle, &knowledge_entry)),
+            Err(SolSageError::Unauthorized.into())
+        );
+        runtime.process(&reactivate_knowledge(&program_id, &signer, &knowledge_entry)).unwrap();
+        assert!(is_active(&runtime));
+    }
+    runtime.process(&attribute(9)).unwrap();
+}
+
+#[test]
+fn settled_entries_close_returning_their_rent() {
+    let Deployment { mut runtime, program_id, oracle, staker, category, knowledge_entry, .. } = deploy();
+    let close = close_knowledge_entry(&program_id, &staker, &staker, &knowledge_entry, &category, &[7; 32]);
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    runtime
+        .process(&record_attribution(&program_id, &oracle, &target, [9; 32], None, None, AttributionMetadata::default()))
+        .unwrap();
+    assert_eq!(
+        runtime.process(&close_knowledge_entry(&program_id, &oracle, &staker, &knowledge_entry, &category, &[7; 32])),
+        Err(SolSageError::NotKnowledgeOwner.into())
+    );
+    assert_eq!(runtime.process(&close), Err(SolSageError::UnclaimedRewards.into()));
+
+    runtime.warp_forward(2 * Protocol::DEFAULT_VESTING_PERIOD_SECS);
+    let destination = Pubkey::new_unique();
+    runtime.add_token_account(destination, find_mint_address(&program_id).0, staker);
+    let attribution = find_attribution_address(&program_id, &[9; 32], &knowledge_entry).0;
+    let claims = [AttributionClaim { attribution, epoch: None }];
+    runtime
+        .process(&claim_rewards(&program_id, &staker, &staker, &knowledge_entry, &destination, &[], &claims))
+        .unwrap();
+    assert_eq!(runtime.process(&close), Err(SolSageError::StakeNotWithdrawn.into()));
+
+    runtime.process(&request_unstake(&program_id, &staker, &knowledge_entry)).unwrap();
+    runtime.warp_forward(KnowledgeEntry::UNSTAKE_COOLDOWN_SECS);
+    runtime.process(&complete_unstake(&program_id, &staker, &knowledge_entry)).unwrap();
+    let lamports = |runtime: &TestRuntime, address: &Pubkey| runtime.account(address).unwrap().lamports;
+    let reclaimed = lamports(&runtime, &knowledge_entry)
+        + lamports(&runtime, &find_content_registry_address(&program_id, &[7; 32]).0);
+    let staker_lamports = lamports(&runtime, &staker);
+    runtime.process(&close).unwrap();
+    assert!(runtime.account(&knowledge_entry).is_none());
+    assert_eq!(lamports(&runtime, &staker), staker_lamports + reclaimed);
+    assert_eq!(state::<Protocol>(&runtime, &find_protocol_address(&program_id).0).total_knowledge_entries, 0);
+}
+
+#[test]
+fn the_authority_sets_the_reward_rate_within_its_bounds() {
+    let Deployment { mut runtime, program_id, authority, staker, .. } = deploy();
+    let protocol_address = find_protocol_address(&program_id).0;
+    let old_rate = state::<Protocol>(&runtime, &protocol_address).reward_per_attribution;
+    assert_eq!(
+        runtime.process(&set_reward_rate(&program_id, &staker, 2_000_000)),
+        Err(SolSageError::Unauthorized.into())
+    );
+    for out_of_bounds in [Protocol::MIN_REWARD_PER_ATTRIBUTION - 1, Protocol::MAX_REWARD_PER_ATTRIBUTION + 1] {
+        assert_eq!(
+            runtime.process(&set_reward_rate(&program_id, &authority, out_of_bounds)),
+            Err(SolSageError::RewardRateOutOfBounds.into())
+        );
+    }
+    assert_eq!(state::<Protocol>(&runtime, &protocol_address).reward_per_attribution, old_rate);
+
+    let new_rate = Protocol::MAX_REWARD_PER_ATTRIBUTION;
+    runtime.process(&set_reward_rate(&program_id, &authority, new_rate)).unwrap();
+    assert_eq!(state::<Protocol>(&runtime, &protocol_address).reward_per_attribution, new_rate);
+    let changed = solsage::SolSageEvent::RewardRateChanged { old_rate, new_rate };
+    assert!(runtime.logs().contains(&Log::Data(vec![changed.try_to_vec().unwrap()])));
+}
+
+#[test]
+fn authority_passes_only_to_the_key_that_accepts_it() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, .. } = deploy();
+    let protocol_address = find_protocol_address(&program_id).0;
+    let successor = Pubkey::new_unique();
+    runtime.fund(successor, SOL);
+    let propose = |signer, new_authority| {
+        propose_authority_transfer(&program_id, signer, new_authority, AuthorityType::Keypair)
+    };
+    assert_eq!(runtime.process(&propose(&staker, &staker)), Err(SolSageError::Unauthorized.into()));
+    assert_eq!(
+        runtime.process(&accept_authority_transfer(&program_id, &successor)),
+        Err(SolSageError::NotPendingAuthority.into())
+    );
+
+    // Proposing hands nothing over yet, and a later proposal replaces it
+    runtime.process(&propose(&authority, &oracle)).unwrap();
+    runtime.process(&propose(&authority, &successor)).unwrap();
+    let protocol: Protocol = state(&runtime, &protocol_address);
+    assert_eq!((protocol.authority, protocol.pending_authority), (authority, Some(successor)));
+    let proposed = solsage::SolSageEvent::AuthorityTransferProposed {
+        current_authority: authority,
+        pending_authority: successor,
+        authority_type: AuthorityType::Keypair,
+    };
+    assert!(runtime.logs().contains(&Log::Data(vec![proposed.try_to_vec().unwrap()])));
+    assert_eq!(
+        runtime.process(&accept_authority_transfer(&program_id, &oracle)),
+        Err(SolSageError::NotPendingAuthority.into())
+    );
+
+    runtime.process(&accept_authority_transfer(&program_id, &successor)).unwrap();
+    let protocol: Protocol = state(&runtime, &protocol_address);
+    assert_eq!((protocol.authority, protocol.pending_authority), (successor, None));
+    let accepted = solsage::SolSageEvent::AuthorityTransferAccepted { old_authority: authority, new_authority: successor };
+    assert!(runtime.logs().contains(&Log::Data(vec![accepted.try_to_vec().unwrap()])));
+    assert_eq!(
+        runtime.process(&set_reward_rate(&program_id, &authority, 2_000_000)),
+        Err(SolSageError::Unauthorized.into())
+    );
+    runtime.process(&set_reward_rate(&program_id, &successor, 2_000_000)).unwrap();
+}
+
+#[test]
+fn rewards_vest_over_the_period_the_authority_sets() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+    let period = 24 * 60 * 60;
+    assert_eq!(
+        runtime.process(&set_vesting_period(&program_id, &staker, period)),
+        Err(SolSageError::Unauthorized.into())
+    );
+    for out_of_bounds in [-1, Protocol::MAX_VESTING_PERIOD_SECS + 1] {
+        assert_eq!(
+            runtime.process(&set_vesting_period(&program_id, &authority, out_of_bounds)),
+            Err(SolSageError::InvalidVestingPeriod.into())
+        );
+    }
+    runtime.process(&set_vesting_period(&program_id, &authority, period)).unwrap();
+
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    runtime
+        .process(&record_attribution(&program_id, &oracle, &target, [9; 32], None, None, AttributionMetadata::default()))
+        .unwrap();
+    let attribution_address = find_attribution_address(&program_id, &[9; 32], &knowledge_entry).0;
+    let attribution: Attribution = state(&runtime, &attribution_address);
+    // Unlock times round up to a seventh of the period
+    let unlocks_in = attribution.unlocks_at - runtime.clock().unix_timestamp;
+    assert!((period..period + period / 7).contains(&unlocks_in));
+
+    // The view reports what a claim would release
+    let vested = |runtime: &mut TestRuntime| {
+        runtime.process(&get_vested_rewards(&program_id, &knowledge_entry)).unwrap();
+        runtime.logs().last().cloned()
+    };
+    let report =
+        |claimable, locked| Some(Log::Message(format!("Vested rewards: {claimable} claimable, {locked} locked")));
+    assert_eq!(vested(&mut runtime), report(0, attribution.reward));
+    let destination = Pubkey::new_unique();
+    runtime.add_token_account(destination, find_mint_address(&program_id).0, staker);
+    let claims = [AttributionClaim { attribution: attribution_address, epoch: None }];
+    let claim = claim_rewards(&program_id, &staker, &staker, &knowledge_entry, &destination, &[], &claims);
+    runtime.warp_forward(unlocks_in - 1);
+    assert_eq!(runtime.process(&claim), Err(SolSageError::RewardLocked.into()));
+
+    runtime.warp_forward(1);
+    assert_eq!(vested(&mut runtime), report(attribution.reward, 0));
+    runtime.process(&claim).unwrap();
+    assert_eq!(runtime.token_balance(&destination), attribution.reward);
+}
+
+#[test]
+fn queries_are_attributed_by_one_oracle_to_a_capped_number_of_entries() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+    let query_hash = [9; 32];
+    runtime.fund(staker, 20 * SOL);
+    let mut entries = vec![knowledge_entry];
+    for index in 1..=u64::from(QueryRecord::MAX_ATTRIBUTIONS) {
+        let content = StakedContent { content_hash: [index as u8 + 10; 32], ..content("Async Rust") };
+        runtime.process(&stake_knowledge(&program_id, &staker, index, content, SOL, &category)).unwrap();
+        entries.push(find_knowledge_entry_address(&program_id, &staker, index).0);
+    }
+    let attribute = |oracle, knowledge_entry| {
+        let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+        record_attribution(&program_id, oracle, &target, query_hash, None, None, AttributionMetadata::default())
+    };
+    runtime.process(&attribute(&oracle, entries[0])).unwrap();
+    let query_record = find_query_record_address(&program_id, &query_hash).0;
+    let record: QueryRecord = state(&runtime, &query_record);
+    assert_eq!((record.oracle, record.attribution_count), (oracle, 1));
+
+    // The oracle that first attributed the query owns it
+    let second_oracle = Pubkey::new_unique();
+    runtime.fund(second_oracle, SOL);
+    runtime.process(&add_oracle(&program_id, &authority, &second_oracle)).unwrap();
+    assert_eq!(runtime.process(&attribute(&second_oracle, entries[1])), Err(SolSageError::QueryOracleMismatch.into()));
+
+    for knowledge_entry in &entries[1..entries.len() - 1] {
+        runtime.process(&attribute(&oracle, *knowledge_entry)).unwrap();
+    }
+    assert_eq!(state::<QueryRecord>(&runtime, &query_record).attribution_count, QueryRecord::MAX_ATTRIBUTIONS);
+    let over_cap = attribute(&oracle, *entries.last().unwrap());
+    assert_eq!(runtime.process(&over_cap), Err(SolSageError::QueryAttributionLimit.into()));
+}
+
+#[test]
+fn only_oracles_the_authority_registers_record_attributions() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+    let registration = find_oracle_address(&program_id, &oracle).0;
+    let registered: AttributionOracle = state(&runtime, &registration);
+    assert_eq!(registered.oracle, oracle);
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    let attribute = |query: u8| {
+        record_attribution(&program_id, &oracle, &target, [query; 32], None, None, AttributionMetadata::default())
+    };
+    runtime.process(&attribute(9)).unwrap();
+
+    assert_eq!(runtime.process(&remove_oracle(&program_id, &staker, &oracle)), Err(SolSageError::Unauthorized.into()));
+    runtime.process(&remove_oracle(&program_id, &authority, &oracle)).unwrap();
+    assert!(runtime.account(&registration).is_none());
+    assert_eq!(runtime.process(&attribute(10)), Err(SolSageError::UnauthorizedOracle.into()));
+
+    assert_eq!(runtime.process(&add_oracle(&program_id, &staker, &oracle)), Err(SolSageError::Unauthorized.into()));
+    runtime.process(&add_oracle(&program_id, &authority, &oracle)).unwrap();
+    runtime.process(&attribute(10)).unwrap();
+}
+
+#[test]
+fn batches_attribute_one_query_to_each_of_their_entries() {
+    let Deployment { mut runtime, program_id, oracle, staker, category, knowledge_entry, .. } = deploy();
+    let pinning = StakedContent { content_hash: [8; 32], ..content("Pinning") };
+    runtime.process(&stake_knowledge(&program_id, &staker, 1, pinning, SOL, &category)).unwrap();
+    let second_entry = find_knowledge_entry_address(&program_id, &staker, 1).0;
+    let targets = [
+        AttributionTarget::new(knowledge_entry, staker, category, 80),
+        AttributionTarget::new(second_entry, staker, category, 40),
+    ];
+
+    // Every target needs its accounts
+    let mut short = record_attribution_batch(&program_id, &oracle, [9; 32], &targets, None);
+    short.accounts.pop();
+    assert_eq!(runtime.process(&short), Err(SolSageError::BatchLengthMismatch.into()));
+    assert_eq!(
+        runtime.process(&record_attribution_batch(&program_id, &oracle, [9; 32], &[], None)),
+        Err(SolSageError::BatchLengthMismatch.into())
+    );
+
+    runtime.process(&record_attribution_batch(&program_id, &oracle, [9; 32], &targets, None)).unwrap();
+    for target in targets {
+        let attribution: Attribution =
+            state(&runtime, &find_attribution_address(&program_id, &[9; 32], &target.knowledge_entry).0);
+        assert_eq!(attribution.relevance_score, target.relevance_score);
+        let entry: KnowledgeEntry = state(&runtime, &target.knowledge_entry);
+        assert_eq!((entry.total_attributions, entry.pending_rewards), (1, attribution.reward));
+    }
+    let record: QueryRecord = state(&runtime, &find_query_record_address(&program_id, &[9; 32]).0);
+    assert_eq!(record.attribution_count, 2);
+    assert_eq!(state::<Protocol>(&runtime, &find_protocol_address(&program_id).0).total_attributions, 2);
+}
+
+#[test]
+fn stakers_claim_across_their_entries_in_one_mint() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+    let other_staker = Pubkey::new_unique();
+    runtime.fund(other_staker, 10 * SOL);
+    let pinning = StakedContent { content_hash: [8; 32], ..content("Pinning") };
+    runtime.process(&stake_knowledge(&program_id, &staker, 1, pinning, SOL, &category)).unwrap();
+    let lifetimes = StakedContent { content_hash: [9; 32], ..content("Lifetimes") };
+    runtime.process(&stake_knowledge(&program_id, &other_staker, 0, lifetimes, SOL, &category)).unwrap();
+    let entries = [
+        (knowledge_entry, staker),
+        (find_knowledge_entry_address(&program_id, &staker, 1).0, staker),
+        (find_knowledge_entry_address(&program_id, &other_staker, 0).0, other_staker),
+    ];
+    let claims: Vec<RewardClaim> = entries
+        .iter()
+        .map(|(knowledge_entry, owner)| {
+            let target = AttributionTarget::new(*knowledge_entry, *owner, category, 80);
+            let metadata = AttributionMetadata::default();
+            runtime.process(&record_attribution(&program_id, &oracle, &target, [9; 32], None, None, metadata)).unwrap();
+            let attribution = find_attribution_address(&program_id, &[9; 32], knowledge_entry).0;
+            RewardClaim { knowledge_entry: *knowledge_entry, attribution, epoch: None }
+        })
+        .collect();
+    runtime.warp_forward(2 * Protocol::DEFAULT_VESTING_PERIOD_SECS);
+    let destination = Pubkey::new_unique();
+    runtime.add_token_account(destination, find_mint_address(&program_id).0, staker);
+
+    // Only the staker claims, and only their own entries
+    assert_eq!(
+        runtime.process(&claim_all_rewards(&program_id, &staker, &staker, &destination, &claims)),
+        Err(SolSageError::NotKnowledgeOwner.into())
+    );
+    assert_eq!(
+        runtime.process(&claim_all_rewards(&program_id, &authority, &staker, &destination, &claims[..2])),
+        Err(SolSageError::NotKnowledgeOwner.into())
+    );
+
+    let owed: u64 = claims[..2]
+        .iter()
+        .map(|claim| state::<KnowledgeEntry>(&runtime, &claim.knowledge_entry).pending_rewards)
+        .sum();
+    runtime.process(&claim_all_rewards(&program_id, &staker, &staker, &destination, &claims[..2])).unwrap();
+    assert_eq!(runtime.token_balance(&destination), owed);
+    assert_eq!(runtime.logs().last(), Some(&Log::Message(format!("Claimed {owed} SAGE tokens from 2 attributions"))));
+    for claim in &claims[..2] {
+        assert_eq!(state::<KnowledgeEntry>(&runtime, &claim.knowledge_entry).pending_rewards, 0);
+    }
+}
+
+#[test]
+fn transferred_entries_earn_for_their_new_owner_at_the_same_address() {
+    let Deployment { mut runtime, program_id, oracle, staker, category, knowledge_entry, .. } = deploy();
+    let new_owner = Pubkey::new_unique();
+    runtime.fund(new_owner, SOL);
+    runtime.process(&transfer_knowledge_ownership(&program_id, &staker, &knowledge_entry, &new_owner)).unwrap();
+    let transferred =
+        solsage::SolSageEvent::KnowledgeOwnershipTransferred { knowledge_entry, old_owner: staker, new_owner };
+    assert!(runtime.logs().contains(&Log::Data(vec![transferred.try_to_vec().unwrap()])));
+    let entry: KnowledgeEntry = state(&runtime, &knowledge_entry);
+    assert_eq!((entry.staker, entry.creator), (new_owner, staker));
+    assert_eq!(
+        runtime.process(&transfer_knowledge_ownership(&program_id, &staker, &knowledge_entry, &staker)),
+        Err(SolSageError::NotKnowledgeOwner.into())
+    );
+
+    // Attributions from here on are the new owner's to claim
+    let target = AttributionTarget::new(knowledge_entry, new_owner, category, 80);
+    runtime
+        .process(&record_attribution(&program_id, &oracle, &target, [9; 32], None, None, AttributionMetadata::default()))
+        .unwrap();
+    runtime.warp_forward(2 * Protocol::DEFAULT_VESTING_PERIOD_SECS);
+    let attribution = find_attribution_address(&program_id, &[9; 32], &knowledge_entry).0;
+    let claims = [AttributionClaim { attribution, epoch: None }];
+    let (old_account, new_account) = (Pubkey::new_unique(), Pubkey::new_unique());
+    runtime.add_token_account(old_account, find_mint_address(&program_id).0, staker);
+    runtime.add_token_account(new_account, find_mint_address(&program_id).0, new_owner);
+    assert_eq!(
+        runtime.process(&claim_rewards(&program_id, &staker, &staker, &knowledge_entry, &old_account, &[], &claims)),
+        Err(SolSageError::NotKnowledgeOwner.into())
+    );
+    let reward = state::<KnowledgeEntry>(&runtime, &knowledge_entry).pending_rewards;
+    runtime
+        .process(&claim_rewards(&program_id, &new_owner, &new_owner, &knowledge_entry, &new_account, &[], &claims))
+        .unwrap();
+    assert_eq!((runtime.token_balance(&old_account), runtime.token_balance(&new_account)), (0, reward));
+    let profile: StakerProfile = state(&runtime, &find_staker_profile_address(&program_id, &new_owner).0);
+    assert_eq!((profile.total_attributions, profile.cumulative_rewards), (1, reward));
+}
+
+#[test]
+fn staker_profiles_sum_up_stakes_attributions_and_claims() {
+    let Deployment { mut runtime, program_id, oracle, staker, category, knowledge_entry, .. } = deploy();
+    let profile = |runtime: &TestRuntime| {
+        state::<StakerProfile>(runtime, &find_staker_profile_address(&program_id, &staker).0)
+    };
+    let pinning = StakedContent { content_hash: [8; 32], ..content("Pinning") };
+    runtime.process(&stake_knowledge(&program_id, &staker, 1, pinning, SOL, &category)).unwrap();
+    let second_entry = find_knowledge_entry_address(&program_id, &staker, 1).0;
+    assert_eq!((profile(&runtime).staker, profile(&runtime).total_entries), (staker, 2));
+
+    let mut claims = Vec::new();
+    for (knowledge_entry, relevance_score) in [(knowledge_entry, 80), (second_entry, 40)] {
+        let target = AttributionTarget::new(knowledge_entry, staker, category, relevance_score);
+        let metadata = AttributionMetadata::default();
+        runtime.process(&record_attribution(&program_id, &oracle, &target, [9; 32], None, None, metadata)).unwrap();
+        let attribution = find_attribution_address(&program_id, &[9; 32], &knowledge_entry).0;
+        claims.push(RewardClaim { knowledge_entry, attribution, epoch: None });
+    }
+    let reputation = profile(&runtime);
+    assert_eq!((reputation.total_attributions, reputation.total_relevance), (2, 120));
+    assert_eq!((reputation.average_relevance(), reputation.cumulative_rewards), (60, 0));
+
+    runtime.warp_forward(2 * Protocol::DEFAULT_VESTING_PERIOD_SECS);
+    let destination = Pubkey::new_unique();
+    runtime.add_token_account(destination, find_mint_address(&program_id).0, staker);
+    runtime.process(&claim_all_rewards(&program_id, &staker, &staker, &destination, &claims)).unwrap();
+    assert_eq!(profile(&runtime).cumulative_rewards, runtime.token_balance(&destination));
+}
+
+#[test]
+fn attributions_pay_along_the_reward_curve_the_authority_picks() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+    assert_eq!(
+        runtime.process(&set_reward_curve(&program_id, &staker, RewardCurve::Quadratic)),
+        Err(SolSageError::Unauthorized.into())
+    );
+    assert_eq!(state::<Protocol>(&runtime, &find_protocol_address(&program_id).0).reward_curve, RewardCurve::Linear);
+
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 40);
+    let mut rewards = Vec::new();
+    for (reward_curve, query_hash) in [(RewardCurve::Linear, [9; 32]), (RewardCurve::Quadratic, [10; 32])] {
+        runtime.process(&set_reward_curve(&program_id, &authority, reward_curve)).unwrap();
+        runtime
+            .process(&record_attribution(&program_id, &oracle, &target, query_hash, None, None, AttributionMetadata::default()))
+            .unwrap();
+        let attribution = find_attribution_address(&program_id, &query_hash, &knowledge_entry).0;
+        rewards.push(state::<Attribution>(&runtime, &attribution).reward);
+    }
+
+    // A score of 40 earns 40% of its linear reward on the quadratic curve
+    assert_eq!(rewards[1], rewards[0] * 40 / 100);
+}
+
+#[test]
+fn slashed_entries_send_part_of_their_stake_to_the_treasury_and_forfeit_their_rewards() {
+    let Deployment { mut runtime, program_id, authority, oracle, staker, category, knowledge_entry } = deploy();
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    runtime
+        .process(&record_attribution(&program_id, &oracle, &target, [9; 32], None, None, AttributionMetadata::default()))
+        .unwrap();
+    let forfeited = state::<KnowledgeEntry>(&runtime, &knowledge_entry).pending_rewards;
+    assert!(forfeited > 0);
+
+    assert_eq!(
+        runtime.process(&slash_knowledge(&program_id, &staker, &knowledge_entry, 5_000)),
+        Err(SolSageError::Unauthorized.into())
+    );
+    assert_eq!(
+        runtime.process(&slash_knowledge(&program_id, &authority, &knowledge_entry, Protocol::MAX_BPS + 1)),
+        Err(SolSageError::InvalidBasisPoints.into())
+    );
+
+    let escrow = find_escrow_address(&program_id, &knowledge_entry).0;
+    let treasury = find_treasury_address(&program_id).0;
+    let lamports = |runtime: &TestRuntime, address| runtime.account(address).unwrap().lamports;
+    let (escrow_lamports, treasury_lamports) = (lamports(&runtime, &escrow), lamports(&runtime, &treasury));
+    runtime.process(&slash_knowledge(&program_id, &authority, &knowledge_entry, 5_000)).unwrap();
+    assert_eq!(escrow_lamports - lamports(&runtime, &escrow), SOL / 2);
+    assert_eq!(lamports(&runtime, &treasury) - treasury_lamports, SOL / 2);
+    let slashed = solsage::SolSageEvent::KnowledgeSlashed { knowledge_entry, slashed: SOL / 2, forfeited };
+    assert!(runtime.logs().contains(&Log::Data(vec![slashed.try_to_vec().unwrap()])));
+
+    // The entry keeps the rest of its stake but neither earns nor pays out
+    let entry: KnowledgeEntry = state(&runtime, &knowledge_entry);
+    assert_eq!((entry.stake_amount, entry.pending_rewards, entry.is_active), (SOL / 2, 0, false));
+    let metadata = AttributionMetadata::default();
+    let attribute = record_attribution(&program_id, &oracle, &target, [10; 32], None, None, metadata);
+    assert_eq!(runtime.process(&attribute), Err(SolSageError::KnowledgeInactive.into()));
+}
+
+#[test]
+fn attributions_and_claims_log_their_events() {
+    let Deployment { mut runtime, program_id, oracle, staker, category, knowledge_entry, .. } = deploy();
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    runtime
+        .process(&record_attribution(&program_id, &oracle, &target, [9; 32], None, None, AttributionMetadata::default()))
+        .unwrap();
+    let address = find_attribution_address(&program_id, &[9; 32], &knowledge_entry).0;
+    let attribution: Attribution = state(&runtime, &address);
+    let recorded = solsage::SolSageEvent::AttributionRecorded {
+        knowledge_entry,
+        attribution: address,
+        query_hash: [9; 32],
+        relevance_score: 80,
+        reward: attribution.reward,
+        unlocks_at: attribution.unlocks_at,
+        license: LicenseType::Mit,
+    };
+    assert!(runtime.logs().contains(&Log::Data(vec![recorded.try_to_vec().unwrap()])));
+
+    runtime.warp_forward(2 * Protocol::DEFAULT_VESTING_PERIOD_SECS);
+    let destination = Pubkey::new_unique();
+    runtime.add_token_account(destination, find_mint_address(&program_id).0, staker);
+    let claims = [AttributionClaim { attribution: address, epoch: None }];
+    runtime
+        .process(&claim_rewards(&program_id, &staker, &staker, &knowledge_entry, &destination, &[], &claims))
+        .unwrap();
+    let claimed = solsage::SolSageEvent::RewardsClaimed { staker, amount: attribution.reward, attributions: 1 };
+    assert!(runtime.logs().contains(&Log::Data(vec![claimed.try_to_vec().unwrap()])));
+}
+
+#[test]
+fn content_is_registered_to_the_first_entry_staking_it() {
+    let Deployment { mut runtime, program_id, staker, category, knowledge_entry, .. } = deploy();
+    let registry: ContentRegistry = state(&runtime, &find_content_registry_address(&program_id, &[7; 32]).0);
+    assert_eq!((registry.content_hash, registry.knowledge_entry, registry.staker), ([7; 32], knowledge_entry, staker));
+
+    // Nobody else can stake the same content under another entry
+    let copier = Pubkey::new_unique();
+    runtime.fund(copier, 10 * SOL);
+    assert_eq!(
+        runtime.process(&stake_knowledge(&program_id, &copier, 0, content("Async Rust, copied"), SOL, &category)),
+        Err(SolSageError::DuplicateContent.into())
+    );
+    assert!(runtime.account(&find_knowledge_entry_address(&program_id, &copier, 0).0).is_none());
+    assert_eq!(state::<Protocol>(&runtime, &find_protocol_address(&program_id).0).total_knowledge_entries, 1);
+}
@@ -2,7 +2,7 @@
 
 import { useState } from "react";
 import { useWallet } from "@solana/wallet-adapter-react";
-import { stakeKnowledge } from "@/lib/solsage-program";
+import { stakeKnowledge, deriveCategoryPDA } from "@/lib/solsage-program";
 
 // Knowledge entries with short titles (under 100 chars) and concise content
 const KNOWLEDGE_ENTRIES = [
@@ -113,7 +113,7 @@ export default function BulkStakePage() {
             setProgress(Math.round((i / KNOWLEDGE_ENTRIES.length) * 100));
 
             try {
-                await stakeKnowledge(wallet, entry.content, entry.title, entry.category);
+                await stakeKnowledge(wallet, entry.content, entry.title, deriveCategoryPDA(entry.category)[0]);
                 setResults(prev => [...prev, { title: entry.title, success: true }]);
                 await new Promise(r => setTimeout(r, 1000));
             } catch (error: any) {
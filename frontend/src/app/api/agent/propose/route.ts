@@ -1,11 +1,17 @@
 import { NextRequest, NextResponse } from "next/server";
-import { Connection, PublicKey, Keypair, Transaction, TransactionInstruction, SystemProgram, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import { Connection, PublicKey, Keypair, Transaction, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import { DEFAULT_STAKE_LAMPORTS } from "@/lib/solsage-config";
+import {
+    LockupTier,
+    deriveCategoryPDA,
+    deriveKnowledgePDA,
+    deriveStakerIndexPDA,
+    nextEntryIndex,
+    stakeKnowledgeInstruction,
+} from "@/lib/solsage-instructions";
 
 // Import config
-const SOLSAGE_PROGRAM_ID = new PublicKey("7E5HrDxxHXMxz4rNHj8k6JXwSP34GC7SgssemZWVBF5R");
 const RPC_ENDPOINT = "https://api.devnet.solana.com";
-const PROTOCOL_SEED = "protocol";
-const KNOWLEDGE_SEED = "knowledge";
 
 // Scoring weights
 const WEIGHTS = {
@@ -36,23 +42,6 @@ const TREASURY_WALLET = new PublicKey("655kFGrvGTkX239NgPJNp2UjnxNXGPXMhcRDjsDGP
 // Connection
 const connection = new Connection(RPC_ENDPOINT, "confirmed");
 
-// Borsh helpers
-function serializeString(str: string): Uint8Array {
-    const encoder = new TextEncoder();
-    const encoded = encoder.encode(str);
-    const len = new Uint8Array(4);
-    new DataView(len.buffer).setUint32(0, encoded.length, true);
-    const result = new Uint8Array(4 + encoded.length);
-    result.set(len);
-    result.set(encoded, 4);
-    return result;
-}
-
-// Instruction discriminators
-const INSTRUCTION_DISCRIMINATORS = {
-    stake_knowledge: new Uint8Array([113, 12, 110, 85, 80, 21, 46, 84]),
-};
-
 // Hash content
 async function hashContent(content: string): Promise<Uint8Array> {
     const encoder = new TextEncoder();
@@ -61,21 +50,6 @@ async function hashContent(content: string): Promise<Uint8Array> {
     return new Uint8Array(hashBuffer);
 }
 
-// Derive PDAs
-function deriveProtocolPDA(): [PublicKey, number] {
-    return PublicKey.findProgramAddressSync(
-        [Buffer.from(PROTOCOL_SEED)],
-        SOLSAGE_PROGRAM_ID
-    );
-}
-
-function deriveKnowledgePDA(staker: PublicKey, contentHash: Uint8Array): [PublicKey, number] {
-    return PublicKey.findProgramAddressSync(
-        [Buffer.from(KNOWLEDGE_SEED), staker.toBuffer(), contentHash],
-        SOLSAGE_PROGRAM_ID
-    );
-}
-
 // Rate limit check
 function checkRateLimit(wallet: string): { allowed: boolean; remaining: number } {
     const now = Date.now();
@@ -210,25 +184,21 @@ async function buildStakeTransaction(
     content: string
 ): Promise<{ transaction: string; contentHash: string; knowledgePDA: string }> {
     const contentHash = await hashContent(content);
-    const [protocolPDA] = deriveProtocolPDA();
-    const [knowledgePDA] = deriveKnowledgePDA(stakerPubkey, contentHash);
-
-    const instructionData = new Uint8Array([
-        ...INSTRUCTION_DISCRIMINATORS.stake_knowledge,
-        ...contentHash,
-        ...serializeString(title),
-        ...serializeString(category),
-    ]);
-
-    const stakeInstruction = new TransactionInstruction({
-        programId: SOLSAGE_PROGRAM_ID,
-        keys: [
-            { pubkey: protocolPDA, isSigner: false, isWritable: true },
-            { pubkey: knowledgePDA, isSigner: false, isWritable: true },
-            { pubkey: stakerPubkey, isSigner: true, isWritable: true },
-            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
-        ],
-        data: Buffer.from(instructionData),
+
+    // Entries are staked at the staker's next entry index
+    const [stakerIndexPDA] = deriveStakerIndexPDA(stakerPubkey);
+    const stakerIndex = await connection.getAccountInfo(stakerIndexPDA);
+    const entryIndex = nextEntryIndex(stakerIndex?.data ?? null);
+    const [knowledgePDA] = deriveKnowledgePDA(stakerPubkey, entryIndex);
+
+    const stakeInstruction = stakeKnowledgeInstruction(stakerPubkey, deriveCategoryPDA(category)[0], {
+        entryIndex,
+        contentHash,
+        title,
+        stakeAmount: BigInt(DEFAULT_STAKE_LAMPORTS),
+        license: { kind: "proprietary" },
+        lockup: LockupTier.None,
+        chunks: null,
     });
 
     const transaction = new Transaction().add(stakeInstruction);
@@ -136,11 +136,7 @@ export default function DashboardPage() {
         setClaimResult(null);
 
         try {
-            // Derive the PDA for this entry
-            const { deriveKnowledgePDA } = await import("@/lib/solsage-program");
-            const [knowledgePDA] = deriveKnowledgePDA(publicKey!, entry.contentHash);
-
-            const signature = await claimRewards(wallet, knowledgePDA);
+            const signature = await claimRewards(wallet, entry.address);
 
             setClaimResult({
                 success: true,
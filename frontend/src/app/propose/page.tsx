@@ -3,7 +3,7 @@
 import { useState, useEffect, useCallback } from "react";
 import { useWallet, useConnection } from "@solana/wallet-adapter-react";
 import { Transaction, SystemProgram, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
-import { stakeKnowledge, getExplorerUrl } from "@/lib/solsage-program";
+import { stakeKnowledge, deriveCategoryPDA, getExplorerUrl } from "@/lib/solsage-program";
 
 interface ScoreBreakdown {
     originality: number;
@@ -148,7 +148,7 @@ export default function ProposePage() {
                 { publicKey, signTransaction } as any,
                 content,
                 title,
-                category
+                deriveCategoryPDA(category)[0]
             );
 
             setStakeResult({
@@ -1,7 +1,7 @@
 // SolSage Program Configuration
 // Deployed via Solana Playground to Devnet
 
-import { PublicKey, clusterApiUrl } from '@solana/web3.js';
+import { PublicKey, clusterApiUrl, LAMPORTS_PER_SOL } from '@solana/web3.js';
 
 // Program ID - Deployed to Solana Devnet
 export const SOLSAGE_PROGRAM_ID = new PublicKey('7E5HrDxxHXMxz4rNHj8k6JXwSP34GC7SgssemZWVBF5R');
@@ -17,6 +17,22 @@ export const RPC_ENDPOINT = clusterApiUrl('devnet');
 export const PROTOCOL_SEED = 'protocol';
 export const KNOWLEDGE_SEED = 'knowledge';
 export const ATTRIBUTION_SEED = 'attribution';
+export const ESCROW_SEED = 'escrow';
+export const PROFILE_SEED = 'profile';
+export const CONTENT_SEED = 'content';
+export const CATEGORY_SEED = 'category';
+export const STAKER_INDEX_SEED = 'staker_index';
+export const QUERY_SEED = 'query';
+export const ORACLE_SEED = 'oracle';
+export const ORACLE_STATE_SEED = 'oracle_state';
+export const PAYER_STATS_SEED = 'payer_stats';
+export const TREASURY_SEED = 'treasury';
+export const LEADERBOARD_SEED = 'leaderboard';
+export const CURATOR_SEED = 'curator';
+export const AGENT_SEED = 'agent';
+
+// Lamports staked on an entry when the caller doesn't choose an amount
+export const DEFAULT_STAKE_LAMPORTS = 0.01 * LAMPORTS_PER_SOL;
 
 // Reward Configuration
 export const REWARD_PER_ATTRIBUTION = 1_000_000; // 1 SAGE token (6 decimals)
@@ -34,12 +50,15 @@ export async function deriveProtocolPDA(): Promise<[PublicKey, number]> {
     );
 }
 
+// Entries are seeded by their original staker and the index they were staked at
 export async function deriveKnowledgePDA(
     staker: PublicKey,
-    contentHash: Uint8Array
+    entryIndex: bigint
 ): Promise<[PublicKey, number]> {
+    const index = Buffer.alloc(8);
+    index.writeBigUInt64LE(entryIndex);
     return PublicKey.findProgramAddressSync(
-        [Buffer.from(KNOWLEDGE_SEED), staker.toBuffer(), contentHash],
+        [Buffer.from(KNOWLEDGE_SEED), staker.toBuffer(), index],
         SOLSAGE_PROGRAM_ID
     );
 }
@@ -0,0 +1,381 @@
+// SolSage Instruction Encoders
+// Borsh layouts and account lists of the Anchor build's instructions, kept
+// byte for byte with fixtures/instructions.json, which the program's own
+// builders write
+
+import { PublicKey, SystemProgram, TransactionInstruction } from '@solana/web3.js';
+import {
+    SOLSAGE_PROGRAM_ID,
+    PROTOCOL_SEED,
+    KNOWLEDGE_SEED,
+    ATTRIBUTION_SEED,
+    ESCROW_SEED,
+    PROFILE_SEED,
+    CONTENT_SEED,
+    CATEGORY_SEED,
+    STAKER_INDEX_SEED,
+    QUERY_SEED,
+    ORACLE_SEED,
+    ORACLE_STATE_SEED,
+    PAYER_STATS_SEED,
+    TREASURY_SEED,
+    LEADERBOARD_SEED,
+    CURATOR_SEED,
+    AGENT_SEED,
+} from './solsage-config';
+
+// Borsh schema helpers for instruction serialization
+// On-chain string limits are in UTF-8 bytes, not characters
+export function byteLength(str: string): number {
+    return new TextEncoder().encode(str).length;
+}
+
+function serializeString(str: string): Uint8Array {
+    const encoder = new TextEncoder();
+    const encoded = encoder.encode(str);
+    const len = new Uint8Array(4);
+    new DataView(len.buffer).setUint32(0, encoded.length, true);
+    const result = new Uint8Array(4 + encoded.length);
+    result.set(len);
+    result.set(encoded, 4);
+    return result;
+}
+
+function serializeU8(value: number): Uint8Array {
+    return new Uint8Array([value]);
+}
+
+function serializeU32(value: number): Uint8Array {
+    const buffer = new ArrayBuffer(4);
+    new DataView(buffer).setUint32(0, value, true);
+    return new Uint8Array(buffer);
+}
+
+function serializeU64(value: bigint): Uint8Array {
+    const buffer = new ArrayBuffer(8);
+    new DataView(buffer).setBigUint64(0, value, true);
+    return new Uint8Array(buffer);
+}
+
+function serializeBool(value: boolean): Uint8Array {
+    return serializeU8(value ? 1 : 0);
+}
+
+// None is a 0 byte; Some is a 1 byte followed by the value
+function serializeOption<T>(value: T | null | undefined, serialize: (value: T) => Uint8Array): Uint8Array {
+    return value == null ? serializeU8(0) : new Uint8Array([1, ...serialize(value)]);
+}
+
+// Instruction discriminators (first 8 bytes of sha256("global:<instruction_name>"))
+export const INSTRUCTION_DISCRIMINATORS = {
+    initialize: new Uint8Array([175, 175, 109, 31, 13, 152, 155, 237]),
+    stake_knowledge: new Uint8Array([113, 12, 110, 85, 80, 21, 46, 84]),
+    record_attribution: new Uint8Array([226, 192, 112, 45, 156, 82, 14, 31]),
+    claim_rewards: new Uint8Array([4, 144, 132, 71, 116, 23, 151, 80]),
+};
+
+// Licenses entries are staked under, in on-chain variant order
+export type License =
+    | { kind: 'mit' }
+    | { kind: 'ccBy' }
+    | { kind: 'proprietary' }
+    | { kind: 'custom'; uri: string };
+
+function serializeLicense(license: License): Uint8Array {
+    switch (license.kind) {
+        case 'mit':
+            return serializeU8(0);
+        case 'ccBy':
+            return serializeU8(1);
+        case 'proprietary':
+            return serializeU8(2);
+        case 'custom':
+            return new Uint8Array([3, ...serializeString(license.uri)]);
+    }
+}
+
+// Lockups keep a stake locked for longer in return for boosted rewards
+export enum LockupTier {
+    None = 0,
+    Days30 = 1,
+    Days90 = 2,
+    Days365 = 3,
+}
+
+// Merkle root over an entry's chunks, letting attributions prove which chunk they used
+export interface ChunkCommitment {
+    root: Uint8Array;
+    chunkCount: number;
+}
+
+function serializeChunkCommitment(chunks: ChunkCommitment): Uint8Array {
+    return new Uint8Array([...chunks.root, ...serializeU32(chunks.chunkCount)]);
+}
+
+// Proof that chunk `index`, hashed to `chunkHash`, is in an entry's ChunkCommitment
+export interface ChunkProof {
+    index: number;
+    chunkHash: Uint8Array;
+    // Sibling hashes from the chunk's leaf up to the root
+    proof: Uint8Array[];
+}
+
+function serializeChunkProof(chunk: ChunkProof): Uint8Array {
+    return new Uint8Array([
+        ...serializeU32(chunk.index),
+        ...chunk.chunkHash,
+        ...serializeU32(chunk.proof.length),
+        ...chunk.proof.flatMap((sibling) => [...sibling]),
+    ]);
+}
+
+// Derive PDAs
+export function deriveProtocolPDA(): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from(PROTOCOL_SEED)],
+        SOLSAGE_PROGRAM_ID
+    );
+}
+
+// Entries are seeded by their original staker and the index they were staked at
+export function deriveKnowledgePDA(
+    staker: PublicKey,
+    entryIndex: bigint
+): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from(KNOWLEDGE_SEED), staker.toBuffer(), serializeU64(entryIndex)],
+        SOLSAGE_PROGRAM_ID
+    );
+}
+
+export function deriveEscrowPDA(knowledgeEntry: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from(ESCROW_SEED), knowledgeEntry.toBuffer()],
+        SOLSAGE_PROGRAM_ID
+    );
+}
+
+export function deriveStakerProfilePDA(staker: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from(PROFILE_SEED), staker.toBuffer()],
+        SOLSAGE_PROGRAM_ID
+    );
+}
+
+export function deriveContentRegistryPDA(contentHash: Uint8Array): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from(CONTENT_SEED), contentHash],
+        SOLSAGE_PROGRAM_ID
+    );
+}
+
+export function deriveCategoryPDA(name: string): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from(CATEGORY_SEED), Buffer.from(name)],
+        SOLSAGE_PROGRAM_ID
+    );
+}
+
+export function deriveStakerIndexPDA(staker: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from(STAKER_INDEX_SEED), staker.toBuffer()],
+        SOLSAGE_PROGRAM_ID
+    );
+}
+
+export function deriveAttributionPDA(
+    queryHash: Uint8Array,
+    knowledgeEntry: PublicKey
+): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from(ATTRIBUTION_SEED), queryHash, knowledgeEntry.toBuffer()],
+        SOLSAGE_PROGRAM_ID
+    );
+}
+
+export function deriveQueryRecordPDA(queryHash: Uint8Array): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from(QUERY_SEED), queryHash],
+        SOLSAGE_PROGRAM_ID
+    );
+}
+
+export function deriveOraclePDA(oracle: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from(ORACLE_SEED), oracle.toBuffer()],
+        SOLSAGE_PROGRAM_ID
+    );
+}
+
+export function deriveOracleStatePDA(oracle: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from(ORACLE_STATE_SEED), oracle.toBuffer()],
+        SOLSAGE_PROGRAM_ID
+    );
+}
+
+export function derivePayerStatsPDA(payer: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from(PAYER_STATS_SEED), payer.toBuffer()],
+        SOLSAGE_PROGRAM_ID
+    );
+}
+
+export function deriveTreasuryPDA(): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from(TREASURY_SEED)],
+        SOLSAGE_PROGRAM_ID
+    );
+}
+
+export function deriveLeaderboardPDA(): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from(LEADERBOARD_SEED)],
+        SOLSAGE_PROGRAM_ID
+    );
+}
+
+export function deriveCuratorPDA(curator: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from(CURATOR_SEED), curator.toBuffer()],
+        SOLSAGE_PROGRAM_ID
+    );
+}
+
+export function deriveAgentStatsPDA(agentId: Uint8Array): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from(AGENT_SEED), agentId],
+        SOLSAGE_PROGRAM_ID
+    );
+}
+
+// Index the staker's next entry is staked at, read from their staker index
+// account; a staker without one stakes their first entry at 0
+export function nextEntryIndex(stakerIndexData: Buffer | null): bigint {
+    if (!stakerIndexData) return BigInt(0);
+    // Discriminator, schema version and staker come before entry_count
+    return stakerIndexData.readBigUInt64LE(1 + 1 + 32);
+}
+
+export interface StakeKnowledgeArgs {
+    entryIndex: bigint;
+    contentHash: Uint8Array;
+    title: string;
+    stakeAmount: bigint;
+    license: License;
+    lockup: LockupTier;
+    chunks: ChunkCommitment | null;
+}
+
+// Stake an entry at the staker's next entry index in `category`, a category
+// account. Receipts, derived-from entries and identity attestations take
+// accounts after these and are left off.
+export function stakeKnowledgeInstruction(
+    staker: PublicKey,
+    category: PublicKey,
+    args: StakeKnowledgeArgs
+): TransactionInstruction {
+    if (byteLength(args.title) > 100) throw new Error('Title must be 100 bytes or less');
+
+    const [protocolPDA] = deriveProtocolPDA();
+    const [knowledgePDA] = deriveKnowledgePDA(staker, args.entryIndex);
+
+    // discriminator + entry_index + content_hash + title + stake_amount + license + lockup +
+    // mint_receipt + derived_from + chunks + identity
+    const instructionData = new Uint8Array([
+        ...INSTRUCTION_DISCRIMINATORS.stake_knowledge,
+        ...serializeU64(args.entryIndex),
+        ...args.contentHash,
+        ...serializeString(args.title),
+        ...serializeU64(args.stakeAmount),
+        ...serializeLicense(args.license),
+        ...serializeU8(args.lockup),
+        ...serializeBool(false),
+        ...serializeU32(0),
+        ...serializeOption(args.chunks, serializeChunkCommitment),
+        ...serializeBool(false),
+    ]);
+
+    return new TransactionInstruction({
+        programId: SOLSAGE_PROGRAM_ID,
+        keys: [
+            { pubkey: staker, isSigner: true, isWritable: true },
+            { pubkey: protocolPDA, isSigner: false, isWritable: true },
+            { pubkey: knowledgePDA, isSigner: false, isWritable: true },
+            { pubkey: deriveEscrowPDA(knowledgePDA)[0], isSigner: false, isWritable: true },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+            { pubkey: deriveStakerProfilePDA(staker)[0], isSigner: false, isWritable: true },
+            { pubkey: deriveContentRegistryPDA(args.contentHash)[0], isSigner: false, isWritable: true },
+            { pubkey: category, isSigner: false, isWritable: true },
+            { pubkey: deriveStakerIndexPDA(staker)[0], isSigner: false, isWritable: true },
+        ],
+        data: Buffer.from(instructionData),
+    });
+}
+
+// Entry an attribution scores: its address, current owner and category account
+export interface AttributionTarget {
+    knowledgeEntry: PublicKey;
+    staker: PublicKey;
+    category: PublicKey;
+}
+
+export interface RecordAttributionArgs {
+    queryHash: Uint8Array;
+    relevanceScore: number;
+    curator: PublicKey | null;
+    agentId: Uint8Array | null;
+    modelId: Uint8Array | null;
+    chunk: ChunkProof | null;
+}
+
+// Attribute `target` to the query hashed to queryHash, signed by `oracle`, a
+// registered oracle paying for the attribution. A curator's and an agent's
+// stats accounts follow the fixed accounts when they're credited; epochs,
+// collections and access passes take accounts after these and are left off.
+export function recordAttributionInstruction(
+    oracle: PublicKey,
+    target: AttributionTarget,
+    args: RecordAttributionArgs
+): TransactionInstruction {
+    if (args.relevanceScore > 100) throw new Error('Relevance score must be 0-100');
+
+    // discriminator + query_hash + relevance_score + curator + agent_id + model_id + chunk
+    const instructionData = new Uint8Array([
+        ...INSTRUCTION_DISCRIMINATORS.record_attribution,
+        ...args.queryHash,
+        ...serializeU8(args.relevanceScore),
+        ...serializeOption(args.curator, (curator) => curator.toBytes()),
+        ...serializeOption(args.agentId, (agentId) => agentId),
+        ...serializeOption(args.modelId, (modelId) => modelId),
+        ...serializeOption(args.chunk, serializeChunkProof),
+    ]);
+
+    const keys = [
+        { pubkey: oracle, isSigner: true, isWritable: true },
+        { pubkey: deriveProtocolPDA()[0], isSigner: false, isWritable: true },
+        { pubkey: target.knowledgeEntry, isSigner: false, isWritable: true },
+        { pubkey: deriveAttributionPDA(args.queryHash, target.knowledgeEntry)[0], isSigner: false, isWritable: true },
+        { pubkey: deriveQueryRecordPDA(args.queryHash)[0], isSigner: false, isWritable: true },
+        { pubkey: deriveOraclePDA(oracle)[0], isSigner: false, isWritable: false },
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: deriveStakerProfilePDA(target.staker)[0], isSigner: false, isWritable: true },
+        { pubkey: deriveTreasuryPDA()[0], isSigner: false, isWritable: true },
+        { pubkey: target.category, isSigner: false, isWritable: true },
+        { pubkey: deriveLeaderboardPDA()[0], isSigner: false, isWritable: true },
+        { pubkey: deriveOracleStatePDA(oracle)[0], isSigner: false, isWritable: true },
+        { pubkey: derivePayerStatsPDA(oracle)[0], isSigner: false, isWritable: true },
+    ];
+    if (args.curator) {
+        keys.push({ pubkey: deriveCuratorPDA(args.curator)[0], isSigner: false, isWritable: true });
+    }
+    if (args.agentId) {
+        keys.push({ pubkey: deriveAgentStatsPDA(args.agentId)[0], isSigner: false, isWritable: true });
+    }
+
+    return new TransactionInstruction({
+        programId: SOLSAGE_PROGRAM_ID,
+        keys,
+        data: Buffer.from(instructionData),
+    });
+}
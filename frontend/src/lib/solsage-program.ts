@@ -7,52 +7,43 @@ import {
     Transaction,
     TransactionInstruction,
     SystemProgram,
-    LAMPORTS_PER_SOL,
 } from '@solana/web3.js';
 import { WalletContextState } from '@solana/wallet-adapter-react';
 import {
     SOLSAGE_PROGRAM_ID,
     RPC_ENDPOINT,
-    PROTOCOL_SEED,
-    KNOWLEDGE_SEED,
-    ATTRIBUTION_SEED,
+    DEFAULT_STAKE_LAMPORTS,
 } from './solsage-config';
+import {
+    INSTRUCTION_DISCRIMINATORS,
+    License,
+    LockupTier,
+    ChunkCommitment,
+    ChunkProof,
+    AttributionTarget,
+    deriveProtocolPDA,
+    deriveKnowledgePDA,
+    deriveStakerIndexPDA,
+    nextEntryIndex,
+    stakeKnowledgeInstruction,
+    recordAttributionInstruction,
+} from './solsage-instructions';
+
+export {
+    deriveProtocolPDA,
+    deriveKnowledgePDA,
+    deriveAttributionPDA,
+    deriveCategoryPDA,
+} from './solsage-instructions';
+export type { License, ChunkCommitment, ChunkProof, AttributionTarget } from './solsage-instructions';
+export { LockupTier } from './solsage-instructions';
 
 // Connection to Solana devnet
 const connection = new Connection(RPC_ENDPOINT, 'confirmed');
 
-// Borsh schema helpers for instruction serialization
-function serializeString(str: string): Uint8Array {
-    const encoder = new TextEncoder();
-    const encoded = encoder.encode(str);
-    const len = new Uint8Array(4);
-    new DataView(len.buffer).setUint32(0, encoded.length, true);
-    const result = new Uint8Array(4 + encoded.length);
-    result.set(len);
-    result.set(encoded, 4);
-    return result;
-}
-
-function serializeU8(value: number): Uint8Array {
-    return new Uint8Array([value]);
-}
-
-function serializeU64(value: bigint): Uint8Array {
-    const buffer = new ArrayBuffer(8);
-    new DataView(buffer).setBigUint64(0, value, true);
-    return new Uint8Array(buffer);
-}
-
-// Instruction discriminators (first 8 bytes of sha256("global:<instruction_name>"))
-const INSTRUCTION_DISCRIMINATORS = {
-    initialize: new Uint8Array([175, 175, 109, 31, 13, 152, 155, 237]),
-    stake_knowledge: new Uint8Array([113, 12, 110, 85, 80, 21, 46, 84]),
-    record_attribution: new Uint8Array([226, 192, 112, 45, 156, 82, 14, 31]),
-    claim_rewards: new Uint8Array([4, 144, 132, 71, 116, 23, 151, 80]),
-};
-
 // Interface for knowledge entry data
 export interface KnowledgeEntryData {
+    address: PublicKey;
     staker: PublicKey;
     contentHash: Uint8Array;
     title: string;
@@ -73,34 +64,6 @@ export interface ProtocolData {
     bump: number;
 }
 
-// Derive PDAs
-export function deriveProtocolPDA(): [PublicKey, number] {
-    return PublicKey.findProgramAddressSync(
-        [Buffer.from(PROTOCOL_SEED)],
-        SOLSAGE_PROGRAM_ID
-    );
-}
-
-export function deriveKnowledgePDA(
-    staker: PublicKey,
-    contentHash: Uint8Array
-): [PublicKey, number] {
-    return PublicKey.findProgramAddressSync(
-        [Buffer.from(KNOWLEDGE_SEED), staker.toBuffer(), contentHash],
-        SOLSAGE_PROGRAM_ID
-    );
-}
-
-export function deriveAttributionPDA(
-    queryHash: Uint8Array,
-    knowledgeEntry: PublicKey
-): [PublicKey, number] {
-    return PublicKey.findProgramAddressSync(
-        [Buffer.from(ATTRIBUTION_SEED), queryHash, knowledgeEntry.toBuffer()],
-        SOLSAGE_PROGRAM_ID
-    );
-}
-
 // Hash content using SHA-256
 export async function hashContent(content: string): Promise<Uint8Array> {
     const encoder = new TextEncoder();
@@ -159,40 +122,35 @@ export async function stakeKnowledge(
     wallet: WalletContextState,
     content: string,
     title: string,
-    category: string
+    category: PublicKey,
+    options: {
+        stakeAmount?: bigint;
+        license?: License;
+        lockup?: LockupTier;
+        chunks?: ChunkCommitment;
+    } = {}
 ): Promise<{ signature: string; contentHash: Uint8Array; knowledgePDA: PublicKey }> {
     if (!wallet.publicKey || !wallet.signTransaction) {
         throw new Error('Wallet not connected');
     }
 
-    // Validate inputs
-    if (title.length > 100) throw new Error('Title must be 100 characters or less');
-    if (category.length > 50) throw new Error('Category must be 50 characters or less');
-
     // Compute content hash
     const contentHash = await hashContent(content);
 
-    // Derive PDAs
-    const [protocolPDA] = deriveProtocolPDA();
-    const [knowledgePDA] = deriveKnowledgePDA(wallet.publicKey, contentHash);
-
-    // Build instruction data: discriminator + content_hash + title + category
-    const instructionData = new Uint8Array([
-        ...INSTRUCTION_DISCRIMINATORS.stake_knowledge,
-        ...contentHash,
-        ...serializeString(title),
-        ...serializeString(category),
-    ]);
-
-    const instruction = new TransactionInstruction({
-        programId: SOLSAGE_PROGRAM_ID,
-        keys: [
-            { pubkey: protocolPDA, isSigner: false, isWritable: true },
-            { pubkey: knowledgePDA, isSigner: false, isWritable: true },
-            { pubkey: wallet.publicKey, isSigner: true, isWritable: true },
-            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
-        ],
-        data: Buffer.from(instructionData),
+    // Entries are staked at the staker's next entry index
+    const [stakerIndexPDA] = deriveStakerIndexPDA(wallet.publicKey);
+    const stakerIndex = await connection.getAccountInfo(stakerIndexPDA);
+    const entryIndex = nextEntryIndex(stakerIndex?.data ?? null);
+    const [knowledgePDA] = deriveKnowledgePDA(wallet.publicKey, entryIndex);
+
+    const instruction = stakeKnowledgeInstruction(wallet.publicKey, category, {
+        entryIndex,
+        contentHash,
+        title,
+        stakeAmount: options.stakeAmount ?? BigInt(DEFAULT_STAKE_LAMPORTS),
+        license: options.license ?? { kind: 'proprietary' },
+        lockup: options.lockup ?? LockupTier.None,
+        chunks: options.chunks ?? null,
     });
 
     const transaction = new Transaction().add(instruction);
@@ -206,40 +164,31 @@ export async function stakeKnowledge(
     return { signature, contentHash, knowledgePDA };
 }
 
-// Record an attribution when knowledge is used
+// Record an attribution when knowledge is used; the wallet must be a
+// registered oracle
 export async function recordAttribution(
     wallet: WalletContextState,
-    knowledgeEntryPDA: PublicKey,
+    target: AttributionTarget,
     queryHash: Uint8Array,
-    relevanceScore: number
+    relevanceScore: number,
+    options: {
+        curator?: PublicKey;
+        agentId?: Uint8Array;
+        modelId?: Uint8Array;
+        chunk?: ChunkProof;
+    } = {}
 ): Promise<string> {
     if (!wallet.publicKey || !wallet.signTransaction) {
         throw new Error('Wallet not connected');
     }
 
-    if (relevanceScore > 100) throw new Error('Relevance score must be 0-100');
-
-    // Derive PDAs
-    const [protocolPDA] = deriveProtocolPDA();
-    const [attributionPDA] = deriveAttributionPDA(queryHash, knowledgeEntryPDA);
-
-    // Build instruction data: discriminator + query_hash + relevance_score
-    const instructionData = new Uint8Array([
-        ...INSTRUCTION_DISCRIMINATORS.record_attribution,
-        ...queryHash,
-        ...serializeU8(relevanceScore),
-    ]);
-
-    const instruction = new TransactionInstruction({
-        programId: SOLSAGE_PROGRAM_ID,
-        keys: [
-            { pubkey: protocolPDA, isSigner: false, isWritable: true },
-            { pubkey: knowledgeEntryPDA, isSigner: false, isWritable: true },
-            { pubkey: attributionPDA, isSigner: false, isWritable: true },
-            { pubkey: wallet.publicKey, isSigner: true, isWritable: true },
-            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
-        ],
-        data: Buffer.from(instructionData),
+    const instruction = recordAttributionInstruction(wallet.publicKey, target, {
+        queryHash,
+        relevanceScore,
+        curator: options.curator ?? null,
+        agentId: options.agentId ?? null,
+        modelId: options.modelId ?? null,
+        chunk: options.chunk ?? null,
     });
 
     const transaction = new Transaction().add(instruction);
@@ -344,7 +293,7 @@ export async function fetchKnowledgeEntriesByStaker(
             ],
         });
 
-        return accounts.map(({ account }) => {
+        return accounts.map(({ pubkey, account }) => {
             const data = account.data;
             let offset = 8;
 
@@ -381,6 +330,7 @@ export async function fetchKnowledgeEntriesByStaker(
             const bump = data[offset];
 
             return {
+                address: pubkey,
                 staker: stakerPubkey,
                 contentHash,
                 title,
@@ -409,7 +359,7 @@ export async function fetchAllKnowledgeEntries(): Promise<KnowledgeEntryData[]>
             ],
         });
 
-        return accounts.map(({ account }) => {
+        return accounts.map(({ pubkey, account }) => {
             const data = account.data;
             let offset = 8;
 
@@ -446,6 +396,7 @@ export async function fetchAllKnowledgeEntries(): Promise<KnowledgeEntryData[]>
             const bump = data[offset];
 
             return {
+                address: pubkey,
                 staker: stakerPubkey,
                 contentHash,
                 title,
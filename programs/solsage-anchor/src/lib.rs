@@ -0,0 +1,2818 @@
+//! Anchor build of the SolSage program.
+//!
+//! Account layouts, instructions and their handlers all live in the
+//! `solsage` crate. This crate declares each instruction's arguments and
+//! leading accounts for Anchor's dispatch and IDL, then hands the decoded
+//! instruction to [`solsage::process`] with its accounts in the order the
+//! native entrypoint takes them. Accounts an instruction only takes some of
+//! the time, listed on its [`SolSageInstruction`] variant, follow as
+//! remaining accounts. The processor checks every account, so none carry
+//! Anchor constraints beyond being a signer or writable.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{Bumps, Discriminator};
+use anchor_spl::token::Token;
+use ::solsage::{
+    AccountType, AuthorityType, ChunkCommitment, ChunkProof, FeeConfig, IdentityConfig, LicenseType, LockupTier,
+    ProposalAction, RewardCurve, RewardSource, RoyaltySplit, SolSageInstruction, Visibility,
+};
+use solsage_core::EMBEDDING_FINGERPRINT_LEN;
+
+declare_id!("11111111111111111111111111111111");
+
+#[program]
+pub mod solsage {
+    use super::*;
+
+    /// Initialize the SolSage protocol
+    pub fn initialize<'info>(ctx: Context<'_, '_, '_, 'info, Initialize<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::Initialize)
+    }
+
+    /// Stake knowledge to the protocol
+    #[allow(clippy::too_many_arguments)]
+    pub fn stake_knowledge<'info>(
+        ctx: Context<'_, '_, '_, 'info, StakeKnowledge<'info>>,
+        entry_index: u64,
+        content_hash: [u8; 32],
+        title: String,
+        stake_amount: u64,
+        license: LicenseType,
+        lockup: LockupTier,
+        mint_receipt: bool,
+        derived_from: Vec<Pubkey>,
+        chunks: Option<ChunkCommitment>,
+        identity: bool,
+    ) -> Result<()> {
+        process(
+            ctx,
+            SolSageInstruction::StakeKnowledge {
+                entry_index,
+                content_hash,
+                title,
+                stake_amount,
+                license,
+                lockup,
+                mint_receipt,
+                derived_from,
+                chunks,
+                identity,
+            },
+        )
+    }
+
+    /// Record an attribution when knowledge is used
+    pub fn record_attribution<'info>(
+        ctx: Context<'_, '_, '_, 'info, RecordAttribution<'info>>,
+        query_hash: [u8; 32],
+        relevance_score: u8,
+        curator: Option<Pubkey>,
+        agent_id: Option<[u8; 32]>,
+        model_id: Option<[u8; 32]>,
+        chunk: Option<ChunkProof>,
+    ) -> Result<()> {
+        process(
+            ctx,
+            SolSageInstruction::RecordAttribution { query_hash, relevance_score, curator, agent_id, model_id, chunk },
+        )
+    }
+
+    /// Claim the vested rewards of the attributions passed in remaining
+    /// accounts, after the SAGE token account of each co-author, and
+    /// whatever the entry's reward stream has paid out so far
+    pub fn claim_rewards<'info>(ctx: Context<'_, '_, '_, 'info, ClaimRewards<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::ClaimRewards)
+    }
+
+    /// Create the protocol-owned SAGE mint
+    pub fn initialize_mint<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitializeMint<'info>>,
+        max_supply: u64,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::InitializeMint { max_supply })
+    }
+
+    /// Withdraw staked lamports once the cooldown started by
+    /// `request_unstake` and any lockup have elapsed, and the entry's pending
+    /// rewards have been claimed or forfeited
+    pub fn complete_unstake<'info>(ctx: Context<'_, '_, '_, 'info, CompleteUnstake<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::CompleteUnstake)
+    }
+
+    /// Edit an entry's metadata; `None` leaves a field unchanged. A new
+    /// content hash drops the chunk commitment over the old content; a
+    /// document's chunks keep theirs.
+    pub fn update_knowledge<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateKnowledge<'info>>,
+        title: Option<String>,
+        content_hash: Option<[u8; 32]>,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::UpdateKnowledge { title, content_hash })
+    }
+
+    /// Stop an entry from receiving attributions
+    pub fn deactivate_knowledge<'info>(ctx: Context<'_, '_, '_, 'info, DeactivateKnowledge<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::DeactivateKnowledge)
+    }
+
+    /// Allow an entry to receive attributions again
+    pub fn reactivate_knowledge<'info>(ctx: Context<'_, '_, '_, 'info, ReactivateKnowledge<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::ReactivateKnowledge)
+    }
+
+    /// Close a fully claimed, unstaked entry and reclaim its rent
+    pub fn close_knowledge_entry<'info>(ctx: Context<'_, '_, '_, 'info, CloseKnowledgeEntry<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::CloseKnowledgeEntry)
+    }
+
+    /// Change the base reward paid per attribution
+    pub fn set_reward_rate<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetRewardRate<'info>>,
+        reward_per_attribution: u64,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetRewardRate { reward_per_attribution })
+    }
+
+    /// Nominate a new protocol authority; takes effect once accepted. A
+    /// multisig is nominated by the PDA it signs with.
+    pub fn propose_authority_transfer<'info>(
+        ctx: Context<'_, '_, '_, 'info, ProposeAuthorityTransfer<'info>>,
+        new_authority: Pubkey,
+        authority_type: AuthorityType,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::ProposeAuthorityTransfer { new_authority, authority_type })
+    }
+
+    /// Accept a pending authority nomination, signed through CPI when the
+    /// nominee is a multisig
+    pub fn accept_authority_transfer<'info>(
+        ctx: Context<'_, '_, '_, 'info, AcceptAuthorityTransfer<'info>>,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::AcceptAuthorityTransfer)
+    }
+
+    /// Change how long newly accrued rewards stay locked
+    pub fn set_vesting_period<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetVestingPeriod<'info>>,
+        vesting_period_secs: i64,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetVestingPeriod { vesting_period_secs })
+    }
+
+    /// Report claimable vs. locked rewards for an entry
+    pub fn get_vested_rewards<'info>(ctx: Context<'_, '_, '_, 'info, GetVestedRewards<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::GetVestedRewards)
+    }
+
+    /// Allow `oracle` to record attributions
+    pub fn add_oracle<'info>(ctx: Context<'_, '_, '_, 'info, AddOracle<'info>>, oracle: Pubkey) -> Result<()> {
+        process(ctx, SolSageInstruction::AddOracle { oracle })
+    }
+
+    /// Revoke an oracle, returning its account rent to the authority
+    pub fn remove_oracle<'info>(ctx: Context<'_, '_, '_, 'info, RemoveOracle<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::RemoveOracle)
+    }
+
+    /// Record one query's attributions to several entries at once; remaining
+    /// accounts are (knowledge_entry, attribution, owner's staker_profile,
+    /// entry's category) quadruples matching `scores`, then the collection of
+    /// each entry in one, in entry order
+    pub fn record_attribution_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, RecordAttributionBatch<'info>>,
+        query_hash: [u8; 32],
+        scores: Vec<u8>,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::RecordAttributionBatch { query_hash, scores })
+    }
+
+    /// Claim vested attribution rewards across several entries with a single
+    /// mint; remaining accounts are (knowledge_entry, attribution) pairs, each
+    /// attribution recorded in an epoch followed by that epoch. Co-authored
+    /// entries must be claimed with `claim_rewards`.
+    pub fn claim_all_rewards<'info>(ctx: Context<'_, '_, '_, 'info, ClaimAllRewards<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::ClaimAllRewards)
+    }
+
+    /// Hand an entry and its stake to a new owner. Rewards the entry earned
+    /// are the staker's, so it changes hands only once they have all been
+    /// claimed or forfeited.
+    pub fn transfer_knowledge_ownership<'info>(
+        ctx: Context<'_, '_, '_, 'info, TransferKnowledgeOwnership<'info>>,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::TransferKnowledgeOwnership { new_owner })
+    }
+
+    /// Switch the curve used to price attributions
+    pub fn set_reward_curve<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetRewardCurve<'info>>,
+        reward_curve: RewardCurve,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetRewardCurve { reward_curve })
+    }
+
+    /// Dispute an attribution by bonding `bond` lamports
+    pub fn challenge_attribution<'info>(
+        ctx: Context<'_, '_, '_, 'info, ChallengeAttribution<'info>>,
+        bond: u64,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::ChallengeAttribution { bond })
+    }
+
+    /// Settle a challenge. Upheld: the attribution's reward is reversed and the
+    /// bond refunded. Rejected: the bond goes to the entry owner.
+    pub fn resolve_challenge<'info>(
+        ctx: Context<'_, '_, '_, 'info, ResolveChallenge<'info>>,
+        uphold: bool,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::ResolveChallenge { uphold })
+    }
+
+    /// Slash `slash_bps` of an entry's stake into the treasury, forfeit its
+    /// pending rewards and deactivate it
+    pub fn slash_knowledge<'info>(
+        ctx: Context<'_, '_, '_, 'info, SlashKnowledge<'info>>,
+        slash_bps: u16,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SlashKnowledge { slash_bps })
+    }
+
+    /// Change the fee, in basis points of each reward, that attribution
+    /// payers pay in lamports to the treasury
+    pub fn set_protocol_fee<'info>(ctx: Context<'_, '_, '_, 'info, SetProtocolFee<'info>>, fee_bps: u16) -> Result<()> {
+        process(ctx, SolSageInstruction::SetProtocolFee { fee_bps })
+    }
+
+    /// Move lamports out of the treasury, keeping it rent-exempt
+    pub fn withdraw_treasury<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawTreasury<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::WithdrawTreasury { amount })
+    }
+
+    /// Register a category that entries can be staked under
+    pub fn create_category<'info>(ctx: Context<'_, '_, '_, 'info, CreateCategory<'info>>, name: String) -> Result<()> {
+        process(ctx, SolSageInstruction::CreateCategory { name })
+    }
+
+    /// Scale rewards for a category's attributions by `multiplier_bps`
+    pub fn set_category_multiplier<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetCategoryMultiplier<'info>>,
+        multiplier_bps: u16,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetCategoryMultiplier { multiplier_bps })
+    }
+
+    /// Pay the renewal fee to push an entry's expiry out by a full term;
+    /// `content_hash`, if given, must match the stored hash
+    pub fn renew_knowledge<'info>(
+        ctx: Context<'_, '_, '_, 'info, RenewKnowledge<'info>>,
+        content_hash: Option<[u8; 32]>,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::RenewKnowledge { content_hash })
+    }
+
+    /// Let `delegate` claim rewards on the staker's behalf; rewards still go
+    /// to the staker's token account. `None` revokes the delegate.
+    pub fn set_claim_delegate<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetClaimDelegate<'info>>,
+        delegate: Option<Pubkey>,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetClaimDelegate { delegate })
+    }
+
+    /// Change the minimum stake, in lamports, that new entries must lock
+    pub fn set_min_stake<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetMinStake<'info>>,
+        min_stake_lamports: u64,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetMinStake { min_stake_lamports })
+    }
+
+    pub fn set_attribution_cooldown<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetAttributionCooldown<'info>>,
+        attribution_cooldown_secs: i64,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetAttributionCooldown { attribution_cooldown_secs })
+    }
+
+    /// Freeze staking, attribution and claiming until unpaused
+    pub fn pause<'info>(ctx: Context<'_, '_, '_, 'info, Pause<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::Pause)
+    }
+
+    /// Lift a pause set by `pause`
+    pub fn unpause<'info>(ctx: Context<'_, '_, '_, 'info, Unpause<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::Unpause)
+    }
+
+    /// Change the relevance score that earns the full reward rate
+    pub fn set_score_divisor<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetScoreDivisor<'info>>,
+        score_divisor: u16,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetScoreDivisor { score_divisor })
+    }
+
+    /// Post the Merkle root of an oracle's attributions for one epoch in
+    /// place of one attribution account each; see `solsage_core::merkle`
+    pub fn commit_attribution_root<'info>(
+        ctx: Context<'_, '_, '_, 'info, CommitAttributionRoot<'info>>,
+        epoch: u64,
+        root: [u8; 32],
+        attribution_count: u32,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::CommitAttributionRoot { epoch, root, attribution_count })
+    }
+
+    /// Prove an attribution's inclusion in a committed root and mint its
+    /// reward once the root has vested. Each leaf pays out once.
+    pub fn claim_attributed_reward<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimAttributedReward<'info>>,
+        leaf_index: u32,
+        query_hash: [u8; 32],
+        relevance_score: u8,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::ClaimAttributedReward { leaf_index, query_hash, relevance_score, proof })
+    }
+
+    /// Set the length and SAGE budget of epochs opened from now on; a zero
+    /// duration goes back to minting each attribution's own reward
+    pub fn set_epoch_schedule<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetEpochSchedule<'info>>,
+        epoch_duration_secs: i64,
+        epoch_reward_budget: u64,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetEpochSchedule { epoch_duration_secs, epoch_reward_budget })
+    }
+
+    /// Open the next epoch once the previous one has ended. Anyone may call
+    /// this; attributions are rejected until it is done.
+    pub fn open_epoch<'info>(ctx: Context<'_, '_, '_, 'info, OpenEpoch<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::OpenEpoch)
+    }
+
+    /// Close an ended epoch to further weight, fixing each attribution's
+    /// pro-rata share of its budget so they can be claimed. Anyone may call
+    /// this.
+    pub fn finalize_epoch<'info>(ctx: Context<'_, '_, '_, 'info, FinalizeEpoch<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::FinalizeEpoch)
+    }
+
+    /// Change the share of each attribution's reward paid to its curator
+    pub fn set_curator_share<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetCuratorShare<'info>>,
+        curator_bps: u16,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetCuratorShare { curator_bps })
+    }
+
+    /// Mint a curator's accrued rewards
+    pub fn claim_curator_rewards<'info>(ctx: Context<'_, '_, '_, 'info, ClaimCuratorRewards<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::ClaimCuratorRewards)
+    }
+
+    /// Create a named collection grouping some of the owner's entries
+    pub fn create_collection<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateCollection<'info>>,
+        name: String,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::CreateCollection { name })
+    }
+
+    /// Add an entry that is in no other collection; its attributions are
+    /// counted towards the collection from then on
+    pub fn add_to_collection<'info>(ctx: Context<'_, '_, '_, 'info, AddToCollection<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::AddToCollection)
+    }
+
+    /// Take an entry out of its collection; the collection keeps the
+    /// attributions already counted
+    pub fn remove_from_collection<'info>(ctx: Context<'_, '_, '_, 'info, RemoveFromCollection<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::RemoveFromCollection)
+    }
+
+    /// Replace the co-authors paid a share of the entry's claimed rewards;
+    /// the owner keeps whatever the splits leave
+    pub fn set_royalty_split<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetRoyaltySplit<'info>>,
+        splits: Vec<RoyaltySplit>,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetRoyaltySplit { splits })
+    }
+
+    /// Change the license the entry's content is offered under
+    pub fn update_license<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateLicense<'info>>,
+        license: LicenseType,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::UpdateLicense { license })
+    }
+
+    /// Point the entry at where its content can be fetched, or clear the
+    /// pointer with `None`
+    pub fn update_content_uri<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateContentUri<'info>>,
+        content_uri: Option<String>,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::UpdateContentUri { content_uri })
+    }
+
+    /// Upgrade an account written under an older schema version to the
+    /// current layout in place, growing it if the layout did. The authority
+    /// funds the extra rent; migrate the protocol itself first, passing it
+    /// as both `protocol` and `account`.
+    pub fn migrate_account<'info>(
+        ctx: Context<'_, '_, '_, 'info, MigrateAccount<'info>>,
+        account_type: AccountType,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::MigrateAccount { account_type })
+    }
+
+    /// Tag an entry, counting it in the tag's index
+    pub fn add_tag<'info>(ctx: Context<'_, '_, '_, 'info, AddTag<'info>>, tag: String) -> Result<()> {
+        process(ctx, SolSageInstruction::AddTag { tag })
+    }
+
+    /// Take a tag off an entry
+    pub fn remove_tag<'info>(ctx: Context<'_, '_, '_, 'info, RemoveTag<'info>>, tag: String) -> Result<()> {
+        process(ctx, SolSageInstruction::RemoveTag { tag })
+    }
+
+    /// Choose whether rewards are minted or funded by attribution payers.
+    /// Only allowed before the first attribution, so every reward is paid
+    /// the way it was earned.
+    pub fn set_reward_source<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetRewardSource<'info>>,
+        reward_source: RewardSource,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetRewardSource { reward_source })
+    }
+
+    /// Deposit SAGE into the consumer's subscription, which attributions
+    /// they pay for then draw their rewards from
+    pub fn top_up_subscription<'info>(
+        ctx: Context<'_, '_, '_, 'info, TopUpSubscription<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::TopUpSubscription { amount })
+    }
+
+    /// Refund a subscription's unspent balance and close it
+    pub fn close_subscription<'info>(ctx: Context<'_, '_, '_, 'info, CloseSubscription<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::CloseSubscription)
+    }
+
+    /// Set the smallest reward an attribution to the entry may pay. Rewards
+    /// below it are rounded up when a subscription funds them and rejected
+    /// otherwise; 0 sets no floor.
+    pub fn set_price_floor<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetPriceFloor<'info>>,
+        min_reward_per_use: u64,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetPriceFloor { min_reward_per_use })
+    }
+
+    /// Re-rank the passed knowledge entries (remaining accounts) on the
+    /// leaderboard at their current attribution counts, dropping the ones
+    /// closed or deactivated since. Permissionless.
+    pub fn rebuild_leaderboard<'info>(ctx: Context<'_, '_, '_, 'info, RebuildLeaderboard<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::RebuildLeaderboard)
+    }
+
+    /// Configure token-weighted governance. Proposals pass with at least
+    /// `quorum_votes` SAGE voted and more than `approval_threshold_bps` of it
+    /// in favour, take votes for `voting_period_secs` and then wait
+    /// `execution_delay_secs` before they can execute. A voting period of 0
+    /// turns governance off.
+    pub fn set_governance_config<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetGovernanceConfig<'info>>,
+        quorum_votes: u64,
+        approval_threshold_bps: u16,
+        voting_period_secs: i64,
+        execution_delay_secs: i64,
+    ) -> Result<()> {
+        process(
+            ctx,
+            SolSageInstruction::SetGovernanceConfig {
+                quorum_votes,
+                approval_threshold_bps,
+                voting_period_secs,
+                execution_delay_secs,
+            },
+        )
+    }
+
+    /// Put `action` to a vote of SAGE holders
+    pub fn create_proposal<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateProposal<'info>>,
+        action: ProposalAction,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::CreateProposal { action })
+    }
+
+    /// Vote on an open proposal with `weight` SAGE, held in the governance
+    /// vault until the vote is withdrawn after voting ends
+    pub fn cast_vote<'info>(
+        ctx: Context<'_, '_, '_, 'info, CastVote<'info>>,
+        approve: bool,
+        weight: u64,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::CastVote { approve, weight })
+    }
+
+    /// Apply a passed proposal once its execution delay has run.
+    /// Permissionless.
+    pub fn execute_proposal<'info>(ctx: Context<'_, '_, '_, 'info, ExecuteProposal<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::ExecuteProposal)
+    }
+
+    /// Return the SAGE behind a vote once voting has ended, closing the vote
+    /// record and returning its rent to the voter
+    pub fn withdraw_vote<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawVote<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::WithdrawVote)
+    }
+
+    /// Queue a change to the reward rate, protocol fee, oracle list or admin
+    /// delay, executable once `admin_delay_secs` has passed. Replaces any
+    /// change already queued. While the admin delay is non-zero the matching
+    /// setters are rejected, so every change gives integrators notice.
+    pub fn queue_parameter_change<'info>(
+        ctx: Context<'_, '_, '_, 'info, QueueParameterChange<'info>>,
+        change: ProposalAction,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::QueueParameterChange { change })
+    }
+
+    /// Drop the queued parameter change
+    pub fn cancel_parameter_change<'info>(ctx: Context<'_, '_, '_, 'info, CancelParameterChange<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::CancelParameterChange)
+    }
+
+    /// Apply the queued parameter change once its delay has passed.
+    /// Permissionless.
+    pub fn execute_parameter_change<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteParameterChange<'info>>,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::ExecuteParameterChange)
+    }
+
+    /// Make rewards decay with the age of the entry used, halving every
+    /// `score_half_life_secs` since it was staked down to a floor of
+    /// `min_score_weight_bps` of the full reward. A half-life of 0 turns
+    /// decay off.
+    pub fn set_score_decay<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetScoreDecay<'info>>,
+        score_half_life_secs: i64,
+        min_score_weight_bps: u16,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetScoreDecay { score_half_life_secs, min_score_weight_bps })
+    }
+
+    /// Only take attributions and attribution roots through CPIs made under
+    /// a top-level instruction to `verifier`, checked against the
+    /// instructions sysvar; `None` lets oracles call in directly again
+    pub fn set_attribution_verifier<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetAttributionVerifier<'info>>,
+        verifier: Option<Pubkey>,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetAttributionVerifier { verifier })
+    }
+
+    /// Record an attribution an oracle signed off-chain, submitted by any
+    /// relayer. The instruction before it must be an Ed25519 program
+    /// instruction checking the oracle's signature over
+    /// `solsage_core::receipts::attribution_receipt`, and `nonce` must be
+    /// above that of every receipt of the oracle relayed before.
+    pub fn record_signed_attribution<'info>(
+        ctx: Context<'_, '_, '_, 'info, RecordSignedAttribution<'info>>,
+        query_hash: [u8; 32],
+        relevance_score: u8,
+        curator: Option<Pubkey>,
+        agent_id: Option<[u8; 32]>,
+        model_id: Option<[u8; 32]>,
+        nonce: u64,
+    ) -> Result<()> {
+        process(
+            ctx,
+            SolSageInstruction::RecordSignedAttribution {
+                query_hash,
+                relevance_score,
+                curator,
+                agent_id,
+                model_id,
+                nonce,
+            },
+        )
+    }
+
+    /// Let anyone expire rewards left unclaimed for more than
+    /// `claim_window_secs` after they vested; 0 keeps them claimable forever
+    pub fn set_claim_window<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetClaimWindow<'info>>,
+        claim_window_secs: i64,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetClaimWindow { claim_window_secs })
+    }
+
+    /// Permissionless crank expiring rewards left unclaimed past the claim
+    /// window; remaining accounts are expired attributions of the entry, each
+    /// recorded in an epoch followed by that epoch. They are never paid out,
+    /// bar `EXPIRY_BOUNTY_BPS` of them paid to the caller.
+    pub fn expire_rewards<'info>(ctx: Context<'_, '_, '_, 'info, ExpireRewards<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::ExpireRewards)
+    }
+
+    /// Start the unstake cooldown; the entry stops taking attributions and
+    /// can't be reactivated
+    pub fn request_unstake<'info>(ctx: Context<'_, '_, '_, 'info, RequestUnstake<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::RequestUnstake)
+    }
+
+    /// Permissionless crank recording the protocol's totals in the snapshot
+    /// of `day`, which must be the current one; each day's snapshot is taken
+    /// once
+    pub fn take_snapshot<'info>(ctx: Context<'_, '_, '_, 'info, TakeSnapshot<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::TakeSnapshot)
+    }
+
+    /// Mint entry receipts from `merkle_tree`, a Bubblegum tree delegated to
+    /// the receipt authority PDA; `None` stops minting them
+    pub fn set_receipt_tree<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetReceiptTree<'info>>,
+        merkle_tree: Option<Pubkey>,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetReceiptTree { merkle_tree })
+    }
+
+    /// Take attributions from `emitter_address` on the Wormhole chain
+    /// `emitter_chain`, in messages `bridge` posts once it has verified
+    /// them. The emitter answers for its attributions as an oracle at its
+    /// bridge emitter PDA, registered alongside it; `remove_oracle` on that
+    /// address revokes it.
+    pub fn add_bridge_emitter<'info>(
+        ctx: Context<'_, '_, '_, 'info, AddBridgeEmitter<'info>>,
+        bridge: Pubkey,
+        emitter_chain: u16,
+        emitter_address: [u8; 32],
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::AddBridgeEmitter { bridge, emitter_chain, emitter_address })
+    }
+
+    /// Record an attribution an agent made on another chain, submitted by
+    /// any relayer. A bridge emitter published
+    /// `solsage_core::bridge::attribution_payload` for it in a message its
+    /// bridge posted, numbered above every message of the emitter relayed
+    /// before.
+    pub fn record_bridged_attribution<'info>(
+        ctx: Context<'_, '_, '_, 'info, RecordBridgedAttribution<'info>>,
+        query_hash: [u8; 32],
+        relevance_score: u8,
+        curator: Option<Pubkey>,
+        agent_id: Option<[u8; 32]>,
+        model_id: Option<[u8; 32]>,
+    ) -> Result<()> {
+        process(
+            ctx,
+            SolSageInstruction::RecordBridgedAttribution { query_hash, relevance_score, curator, agent_id, model_id },
+        )
+    }
+
+    /// Create or replace the Metaplex metadata wallets display for the SAGE
+    /// mint, updatable only through the mint authority PDA
+    pub fn set_mint_metadata<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetMintMetadata<'info>>,
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetMintMetadata { name, symbol, uri })
+    }
+
+    /// Offer an entry for `price` lamports. The entry stays with its owner
+    /// until bought; handing it on some other way leaves the listing stale.
+    pub fn list_entry_for_sale<'info>(
+        ctx: Context<'_, '_, '_, 'info, ListEntryForSale<'info>>,
+        price: u64,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::ListEntryForSale { price })
+    }
+
+    /// Withdraw a listing, by its seller or, once it is stale, the entry's
+    /// current owner
+    pub fn cancel_listing<'info>(ctx: Context<'_, '_, '_, 'info, CancelListing<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::CancelListing)
+    }
+
+    /// Buy a listed entry with its stake. Rewards the entry earned are the
+    /// seller's, so it sells only once they have all been claimed or
+    /// forfeited. `price` is escrowed in the listing, then paid to the seller
+    /// less the protocol's sale fee, which goes to the treasury.
+    pub fn buy_entry<'info>(ctx: Context<'_, '_, '_, 'info, BuyEntry<'info>>, price: u64) -> Result<()> {
+        process(ctx, SolSageInstruction::BuyEntry { price })
+    }
+
+    /// Change the fee, in basis points of the price, taken on entry sales
+    pub fn set_sale_fee<'info>(ctx: Context<'_, '_, '_, 'info, SetSaleFee<'info>>, sale_fee_bps: u16) -> Result<()> {
+        process(ctx, SolSageInstruction::SetSaleFee { sale_fee_bps })
+    }
+
+    /// Cap what `oracle` may record per budget epoch, in attributions and in
+    /// SAGE awarded; 0 leaves either uncapped
+    pub fn set_oracle_budget<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetOracleBudget<'info>>,
+        oracle: Pubkey,
+        max_attributions_per_epoch: u64,
+        max_rewards_per_epoch: u64,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetOracleBudget { oracle, max_attributions_per_epoch, max_rewards_per_epoch })
+    }
+
+    /// Hold each attribution pending until `consensus_threshold` registered
+    /// oracles report it with relevance scores within `consensus_tolerance`
+    /// of the first report; 0 finalizes each report on its own
+    pub fn set_consensus<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetConsensus<'info>>,
+        consensus_threshold: u8,
+        consensus_tolerance: u8,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetConsensus { consensus_threshold, consensus_tolerance })
+    }
+
+    /// Have attributions take relevance scores from any registered oracle
+    /// for `aggregation_window_secs` after the first, then finalize through
+    /// `finalize_attribution` at the average score; 0 finalizes each report
+    /// on its own
+    pub fn set_aggregation_window<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetAggregationWindow<'info>>,
+        aggregation_window_secs: i64,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetAggregationWindow { aggregation_window_secs })
+    }
+
+    /// Credit a pending attribution's reward at the average of the scores it
+    /// took, once its aggregation window has closed. Anyone may finalize;
+    /// the finalizer pays the protocol fee and, when attributions fund
+    /// rewards, the reward.
+    pub fn finalize_attribution<'info>(
+        ctx: Context<'_, '_, '_, 'info, FinalizeAttribution<'info>>,
+        query_hash: [u8; 32],
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::FinalizeAttribution { query_hash })
+    }
+
+    /// Cap the attributions any one payer records a day, so a single wallet
+    /// can't flood an entry it controls with attributions; 0 lifts the cap
+    pub fn set_payer_attribution_cap<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetPayerAttributionCap<'info>>,
+        max_payer_attributions_per_day: u64,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetPayerAttributionCap { max_payer_attributions_per_day })
+    }
+
+    /// Vouch for an entry's quality with `weight` SAGE, held in the
+    /// governance vault until the vote is withdrawn. The SAGE voted up on an
+    /// entry less the SAGE voted down scales every reward it earns.
+    pub fn upvote_entry<'info>(ctx: Context<'_, '_, '_, 'info, UpvoteEntry<'info>>, weight: u64) -> Result<()> {
+        process(ctx, SolSageInstruction::UpvoteEntry { weight })
+    }
+
+    /// Vote an entry's quality down with `weight` SAGE
+    pub fn downvote_entry<'info>(ctx: Context<'_, '_, '_, 'info, DownvoteEntry<'info>>, weight: u64) -> Result<()> {
+        process(ctx, SolSageInstruction::DownvoteEntry { weight })
+    }
+
+    /// Take back a quality vote, returning its SAGE and its weight on the
+    /// entry's score, closing the vote record
+    pub fn withdraw_entry_vote<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawEntryVote<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::WithdrawEntryVote)
+    }
+
+    /// Report an entry as spam by bonding `bond` lamports. Once
+    /// `SPAM_REPORT_THRESHOLD` reporters have, the entry is deactivated until
+    /// the authority resolves the reports.
+    pub fn report_entry<'info>(ctx: Context<'_, '_, '_, 'info, ReportEntry<'info>>, bond: u64) -> Result<()> {
+        process(ctx, SolSageInstruction::ReportEntry { bond })
+    }
+
+    /// Settle an entry's spam reports, closing its report queue; remaining
+    /// accounts are the reporters in report order. Upheld: the bonds are
+    /// refunded and the entry stays inactive. Rejected: the bonds go to the
+    /// entry owner and the entry is reactivated. The queue's rent returns to
+    /// the first reporter.
+    pub fn resolve_reports<'info>(ctx: Context<'_, '_, '_, 'info, ResolveReports<'info>>, uphold: bool) -> Result<()> {
+        process(ctx, SolSageInstruction::ResolveReports { uphold })
+    }
+
+    /// Offer `reward` SAGE for knowledge answering `query_hash`, escrowed in
+    /// the bounty's vault until an answer is accepted or `deadline` passes
+    pub fn create_bounty<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateBounty<'info>>,
+        query_hash: [u8; 32],
+        reward: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::CreateBounty { query_hash, reward, deadline })
+    }
+
+    /// Put an active entry forward as an answer to an open bounty before its
+    /// deadline
+    pub fn submit_answer<'info>(ctx: Context<'_, '_, '_, 'info, SubmitAnswer<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::SubmitAnswer)
+    }
+
+    /// Accept an answer, paying its staker the escrowed reward and closing
+    /// the bounty's vault. An answer can be accepted until the bounty is
+    /// reclaimed.
+    pub fn accept_answer<'info>(ctx: Context<'_, '_, '_, 'info, AcceptAnswer<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::AcceptAnswer)
+    }
+
+    /// Refund an unanswered bounty once its deadline has passed, closing the
+    /// bounty and its vault
+    pub fn reclaim_expired_bounty<'info>(ctx: Context<'_, '_, '_, 'info, ReclaimExpiredBounty<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::ReclaimExpiredBounty)
+    }
+
+    /// Stream attribution rewards to their entry over `reward_stream_secs`
+    /// instead of vesting each one; 0 goes back to vesting
+    pub fn set_reward_stream<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetRewardStream<'info>>,
+        reward_stream_secs: i64,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetRewardStream { reward_stream_secs })
+    }
+
+    /// Set the shares of each attribution's reward paid to the treasury and
+    /// curators and burned, the rest going to the entry
+    pub fn set_fee_config<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetFeeConfig<'info>>,
+        fee_config: FeeConfig,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetFeeConfig { fee_config })
+    }
+
+    /// Pay out the treasury's share of attribution rewards
+    pub fn claim_treasury_rewards<'info>(ctx: Context<'_, '_, '_, 'info, ClaimTreasuryRewards<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::ClaimTreasuryRewards)
+    }
+
+    /// Reward nobody for attributions scored below `low_relevance_threshold`
+    /// and burn `low_relevance_burn_bps` of their reward instead; a threshold
+    /// of 0 turns this off
+    pub fn set_low_relevance_burn<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetLowRelevanceBurn<'info>>,
+        low_relevance_threshold: u8,
+        low_relevance_burn_bps: u16,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetLowRelevanceBurn { low_relevance_threshold, low_relevance_burn_bps })
+    }
+
+    /// Burn the reward vault's SAGE owed to burns from payer-funded
+    /// attributions. Anyone may burn.
+    pub fn burn_rewards<'info>(ctx: Context<'_, '_, '_, 'info, BurnRewards<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::BurnRewards)
+    }
+
+    /// Set the share of a derivative entry's attribution rewards set aside
+    /// for the entries it is derived from
+    pub fn set_citation_royalty<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetCitationRoyalty<'info>>,
+        citation_royalty_bps: u16,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetCitationRoyalty { citation_royalty_bps })
+    }
+
+    /// Pay the royalties set aside on a derivative entry to its parents,
+    /// passed as the remaining accounts in `derived_from` order and split
+    /// evenly between them. Anyone may pay. A parent that has been closed
+    /// forfeits its share.
+    pub fn pay_citation_royalties<'info>(ctx: Context<'_, '_, '_, 'info, PayCitationRoyalties<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::PayCitationRoyalties)
+    }
+
+    /// Create a document to be staked as chunk entries, so attributions can
+    /// target single passages. Each chunk is staked as an entry and then
+    /// placed with `add_document_chunk`; its rewards go to the document
+    /// owner.
+    pub fn stake_document<'info>(
+        ctx: Context<'_, '_, '_, 'info, StakeDocument<'info>>,
+        content_hash: [u8; 32],
+        title: String,
+        chunk_count: u16,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::StakeDocument { content_hash, title, chunk_count })
+    }
+
+    /// Make an entry the document's chunk at `chunk_index`; the entry's
+    /// content hash is the chunk's. The entry must be the owner's, filed
+    /// under the document's category and a chunk of no other document, and
+    /// can't change owner from then on.
+    pub fn add_document_chunk<'info>(
+        ctx: Context<'_, '_, '_, 'info, AddDocumentChunk<'info>>,
+        chunk_index: u16,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::AddDocumentChunk { chunk_index })
+    }
+
+    /// Total the attributions and pending rewards of a document's chunks,
+    /// passed as the remaining accounts in chunk order, onto the document.
+    /// Anyone may refresh.
+    pub fn refresh_document<'info>(ctx: Context<'_, '_, '_, 'info, RefreshDocument<'info>>) -> Result<()> {
+        process(ctx, SolSageInstruction::RefreshDocument)
+    }
+
+    /// Publish a fingerprint of the entry's content embedding for retrievers
+    /// to filter entries by similarity, or clear it with `None`
+    pub fn update_embedding_fingerprint<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateEmbeddingFingerprint<'info>>,
+        embedding_fingerprint: Option<[u8; EMBEDDING_FINGERPRINT_LEN]>,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::UpdateEmbeddingFingerprint { embedding_fingerprint })
+    }
+
+    /// Approve `reviewer` to attest entries
+    pub fn add_reviewer<'info>(ctx: Context<'_, '_, '_, 'info, AddReviewer<'info>>, reviewer: Pubkey) -> Result<()> {
+        process(ctx, SolSageInstruction::AddReviewer { reviewer })
+    }
+
+    /// Withdraw `reviewer`'s approval; entries it attested stay verified
+    pub fn remove_reviewer<'info>(
+        ctx: Context<'_, '_, '_, 'info, RemoveReviewer<'info>>,
+        reviewer: Pubkey,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::RemoveReviewer { reviewer })
+    }
+
+    /// Attest an entry's `accuracy` and `freshness`, replacing any earlier
+    /// attestation. Scoring both at least `MIN_VERIFIED_SCORE` verifies the
+    /// entry, boosting its rewards until its content changes.
+    pub fn attest_entry<'info>(
+        ctx: Context<'_, '_, '_, 'info, AttestEntry<'info>>,
+        accuracy: u8,
+        freshness: u8,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::AttestEntry { accuracy, freshness })
+    }
+
+    /// Take stakers' identity attestations from `identity_config`'s
+    /// attestation program under its credential, requiring one of every
+    /// stake if it says so; `None` stops taking them
+    pub fn set_identity_config<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetIdentityConfig<'info>>,
+        identity_config: Option<IdentityConfig>,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetIdentityConfig { identity_config })
+    }
+
+    /// Make the entry public, or private to payers holding an access pass
+    pub fn set_entry_visibility<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetEntryVisibility<'info>>,
+        visibility: Visibility,
+    ) -> Result<()> {
+        process(ctx, SolSageInstruction::SetEntryVisibility { visibility })
+    }
+
+    /// Give `holder` an access pass to the entry, free of charge
+    pub fn grant_access<'info>(ctx: Context<'_, '_, '_, 'info, GrantAccess<'info>>, holder: Pubkey) -> Result<()> {
+        process(ctx, SolSageInstruction::GrantAccess { holder })
+    }
+
+    /// Buy an access pass to a private entry from its owner at its access
+    /// price, which must still be `price`
+    pub fn buy_access<'info>(ctx: Context<'_, '_, '_, 'info, BuyAccess<'info>>, price: u64) -> Result<()> {
+        process(ctx, SolSageInstruction::BuyAccess { price })
+    }
+}
+
+/// Hand `instruction` to the native processor with the context's accounts
+/// followed by its remaining accounts
+fn process<'info, T: ToAccountInfos<'info> + Bumps>(
+    ctx: Context<'_, '_, '_, 'info, T>,
+    instruction: SolSageInstruction,
+) -> Result<()> {
+    let mut accounts = ctx.accounts.to_account_infos();
+    accounts.extend_from_slice(ctx.remaining_accounts);
+    Ok(::solsage::process(ctx.program_id, &accounts, instruction)?)
+}
+
+/// Re-encode an instruction built for the native entrypoint, such as by
+/// `solsage_cpi`, for this one. Both take the same accounts and arguments, so
+/// only the discriminator changes.
+pub fn from_native(mut instruction: Instruction) -> Result<Instruction> {
+    let decoded = SolSageInstruction::try_from_slice(&instruction.data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    let mut data = discriminator(&decoded).to_vec();
+    data.extend_from_slice(&instruction.data[1..]);
+    instruction.data = data;
+    Ok(instruction)
+}
+
+fn discriminator(instruction: &SolSageInstruction) -> [u8; 8] {
+    match instruction {
+        SolSageInstruction::Initialize => instruction::Initialize::DISCRIMINATOR,
+        SolSageInstruction::StakeKnowledge { .. } => instruction::StakeKnowledge::DISCRIMINATOR,
+        SolSageInstruction::RecordAttribution { .. } => instruction::RecordAttribution::DISCRIMINATOR,
+        SolSageInstruction::ClaimRewards => instruction::ClaimRewards::DISCRIMINATOR,
+        SolSageInstruction::InitializeMint { .. } => instruction::InitializeMint::DISCRIMINATOR,
+        SolSageInstruction::CompleteUnstake => instruction::CompleteUnstake::DISCRIMINATOR,
+        SolSageInstruction::UpdateKnowledge { .. } => instruction::UpdateKnowledge::DISCRIMINATOR,
+        SolSageInstruction::DeactivateKnowledge => instruction::DeactivateKnowledge::DISCRIMINATOR,
+        SolSageInstruction::ReactivateKnowledge => instruction::ReactivateKnowledge::DISCRIMINATOR,
+        SolSageInstruction::CloseKnowledgeEntry => instruction::CloseKnowledgeEntry::DISCRIMINATOR,
+        SolSageInstruction::SetRewardRate { .. } => instruction::SetRewardRate::DISCRIMINATOR,
+        SolSageInstruction::ProposeAuthorityTransfer { .. } => instruction::ProposeAuthorityTransfer::DISCRIMINATOR,
+        SolSageInstruction::AcceptAuthorityTransfer => instruction::AcceptAuthorityTransfer::DISCRIMINATOR,
+        SolSageInstruction::SetVestingPeriod { .. } => instruction::SetVestingPeriod::DISCRIMINATOR,
+        SolSageInstruction::GetVestedRewards => instruction::GetVestedRewards::DISCRIMINATOR,
+        SolSageInstruction::AddOracle { .. } => instruction::AddOracle::DISCRIMINATOR,
+        SolSageInstruction::RemoveOracle => instruction::RemoveOracle::DISCRIMINATOR,
+        SolSageInstruction::RecordAttributionBatch { .. } => instruction::RecordAttributionBatch::DISCRIMINATOR,
+        SolSageInstruction::ClaimAllRewards => instruction::ClaimAllRewards::DISCRIMINATOR,
+        SolSageInstruction::TransferKnowledgeOwnership { .. } => instruction::TransferKnowledgeOwnership::DISCRIMINATOR,
+        SolSageInstruction::SetRewardCurve { .. } => instruction::SetRewardCurve::DISCRIMINATOR,
+        SolSageInstruction::ChallengeAttribution { .. } => instruction::ChallengeAttribution::DISCRIMINATOR,
+        SolSageInstruction::ResolveChallenge { .. } => instruction::ResolveChallenge::DISCRIMINATOR,
+        SolSageInstruction::SlashKnowledge { .. } => instruction::SlashKnowledge::DISCRIMINATOR,
+        SolSageInstruction::SetProtocolFee { .. } => instruction::SetProtocolFee::DISCRIMINATOR,
+        SolSageInstruction::WithdrawTreasury { .. } => instruction::WithdrawTreasury::DISCRIMINATOR,
+        SolSageInstruction::CreateCategory { .. } => instruction::CreateCategory::DISCRIMINATOR,
+        SolSageInstruction::SetCategoryMultiplier { .. } => instruction::SetCategoryMultiplier::DISCRIMINATOR,
+        SolSageInstruction::RenewKnowledge { .. } => instruction::RenewKnowledge::DISCRIMINATOR,
+        SolSageInstruction::SetClaimDelegate { .. } => instruction::SetClaimDelegate::DISCRIMINATOR,
+        SolSageInstruction::SetMinStake { .. } => instruction::SetMinStake::DISCRIMINATOR,
+        SolSageInstruction::SetAttributionCooldown { .. } => instruction::SetAttributionCooldown::DISCRIMINATOR,
+        SolSageInstruction::Pause => instruction::Pause::DISCRIMINATOR,
+        SolSageInstruction::Unpause => instruction::Unpause::DISCRIMINATOR,
+        SolSageInstruction::SetScoreDivisor { .. } => instruction::SetScoreDivisor::DISCRIMINATOR,
+        SolSageInstruction::CommitAttributionRoot { .. } => instruction::CommitAttributionRoot::DISCRIMINATOR,
+        SolSageInstruction::ClaimAttributedReward { .. } => instruction::ClaimAttributedReward::DISCRIMINATOR,
+        SolSageInstruction::SetEpochSchedule { .. } => instruction::SetEpochSchedule::DISCRIMINATOR,
+        SolSageInstruction::OpenEpoch => instruction::OpenEpoch::DISCRIMINATOR,
+        SolSageInstruction::FinalizeEpoch => instruction::FinalizeEpoch::DISCRIMINATOR,
+        SolSageInstruction::SetCuratorShare { .. } => instruction::SetCuratorShare::DISCRIMINATOR,
+        SolSageInstruction::ClaimCuratorRewards => instruction::ClaimCuratorRewards::DISCRIMINATOR,
+        SolSageInstruction::CreateCollection { .. } => instruction::CreateCollection::DISCRIMINATOR,
+        SolSageInstruction::AddToCollection => instruction::AddToCollection::DISCRIMINATOR,
+        SolSageInstruction::RemoveFromCollection => instruction::RemoveFromCollection::DISCRIMINATOR,
+        SolSageInstruction::SetRoyaltySplit { .. } => instruction::SetRoyaltySplit::DISCRIMINATOR,
+        SolSageInstruction::UpdateLicense { .. } => instruction::UpdateLicense::DISCRIMINATOR,
+        SolSageInstruction::UpdateContentUri { .. } => instruction::UpdateContentUri::DISCRIMINATOR,
+        SolSageInstruction::MigrateAccount { .. } => instruction::MigrateAccount::DISCRIMINATOR,
+        SolSageInstruction::AddTag { .. } => instruction::AddTag::DISCRIMINATOR,
+        SolSageInstruction::RemoveTag { .. } => instruction::RemoveTag::DISCRIMINATOR,
+        SolSageInstruction::SetRewardSource { .. } => instruction::SetRewardSource::DISCRIMINATOR,
+        SolSageInstruction::TopUpSubscription { .. } => instruction::TopUpSubscription::DISCRIMINATOR,
+        SolSageInstruction::CloseSubscription => instruction::CloseSubscription::DISCRIMINATOR,
+        SolSageInstruction::SetPriceFloor { .. } => instruction::SetPriceFloor::DISCRIMINATOR,
+        SolSageInstruction::RebuildLeaderboard => instruction::RebuildLeaderboard::DISCRIMINATOR,
+        SolSageInstruction::SetGovernanceConfig { .. } => instruction::SetGovernanceConfig::DISCRIMINATOR,
+        SolSageInstruction::CreateProposal { .. } => instruction::CreateProposal::DISCRIMINATOR,
+        SolSageInstruction::CastVote { .. } => instruction::CastVote::DISCRIMINATOR,
+        SolSageInstruction::ExecuteProposal => instruction::ExecuteProposal::DISCRIMINATOR,
+        SolSageInstruction::WithdrawVote => instruction::WithdrawVote::DISCRIMINATOR,
+        SolSageInstruction::QueueParameterChange { .. } => instruction::QueueParameterChange::DISCRIMINATOR,
+        SolSageInstruction::CancelParameterChange => instruction::CancelParameterChange::DISCRIMINATOR,
+        SolSageInstruction::ExecuteParameterChange => instruction::ExecuteParameterChange::DISCRIMINATOR,
+        SolSageInstruction::SetScoreDecay { .. } => instruction::SetScoreDecay::DISCRIMINATOR,
+        SolSageInstruction::SetAttributionVerifier { .. } => instruction::SetAttributionVerifier::DISCRIMINATOR,
+        SolSageInstruction::RecordSignedAttribution { .. } => instruction::RecordSignedAttribution::DISCRIMINATOR,
+        SolSageInstruction::SetClaimWindow { .. } => instruction::SetClaimWindow::DISCRIMINATOR,
+        SolSageInstruction::ExpireRewards => instruction::ExpireRewards::DISCRIMINATOR,
+        SolSageInstruction::RequestUnstake => instruction::RequestUnstake::DISCRIMINATOR,
+        SolSageInstruction::TakeSnapshot => instruction::TakeSnapshot::DISCRIMINATOR,
+        SolSageInstruction::SetReceiptTree { .. } => instruction::SetReceiptTree::DISCRIMINATOR,
+        SolSageInstruction::AddBridgeEmitter { .. } => instruction::AddBridgeEmitter::DISCRIMINATOR,
+        SolSageInstruction::RecordBridgedAttribution { .. } => instruction::RecordBridgedAttribution::DISCRIMINATOR,
+        SolSageInstruction::SetMintMetadata { .. } => instruction::SetMintMetadata::DISCRIMINATOR,
+        SolSageInstruction::ListEntryForSale { .. } => instruction::ListEntryForSale::DISCRIMINATOR,
+        SolSageInstruction::CancelListing => instruction::CancelListing::DISCRIMINATOR,
+        SolSageInstruction::BuyEntry { .. } => instruction::BuyEntry::DISCRIMINATOR,
+        SolSageInstruction::SetSaleFee { .. } => instruction::SetSaleFee::DISCRIMINATOR,
+        SolSageInstruction::SetOracleBudget { .. } => instruction::SetOracleBudget::DISCRIMINATOR,
+        SolSageInstruction::SetConsensus { .. } => instruction::SetConsensus::DISCRIMINATOR,
+        SolSageInstruction::SetAggregationWindow { .. } => instruction::SetAggregationWindow::DISCRIMINATOR,
+        SolSageInstruction::FinalizeAttribution { .. } => instruction::FinalizeAttribution::DISCRIMINATOR,
+        SolSageInstruction::SetPayerAttributionCap { .. } => instruction::SetPayerAttributionCap::DISCRIMINATOR,
+        SolSageInstruction::UpvoteEntry { .. } => instruction::UpvoteEntry::DISCRIMINATOR,
+        SolSageInstruction::DownvoteEntry { .. } => instruction::DownvoteEntry::DISCRIMINATOR,
+        SolSageInstruction::WithdrawEntryVote => instruction::WithdrawEntryVote::DISCRIMINATOR,
+        SolSageInstruction::ReportEntry { .. } => instruction::ReportEntry::DISCRIMINATOR,
+        SolSageInstruction::ResolveReports { .. } => instruction::ResolveReports::DISCRIMINATOR,
+        SolSageInstruction::CreateBounty { .. } => instruction::CreateBounty::DISCRIMINATOR,
+        SolSageInstruction::SubmitAnswer => instruction::SubmitAnswer::DISCRIMINATOR,
+        SolSageInstruction::AcceptAnswer => instruction::AcceptAnswer::DISCRIMINATOR,
+        SolSageInstruction::ReclaimExpiredBounty => instruction::ReclaimExpiredBounty::DISCRIMINATOR,
+        SolSageInstruction::SetRewardStream { .. } => instruction::SetRewardStream::DISCRIMINATOR,
+        SolSageInstruction::SetFeeConfig { .. } => instruction::SetFeeConfig::DISCRIMINATOR,
+        SolSageInstruction::ClaimTreasuryRewards => instruction::ClaimTreasuryRewards::DISCRIMINATOR,
+        SolSageInstruction::SetLowRelevanceBurn { .. } => instruction::SetLowRelevanceBurn::DISCRIMINATOR,
+        SolSageInstruction::BurnRewards => instruction::BurnRewards::DISCRIMINATOR,
+        SolSageInstruction::SetCitationRoyalty { .. } => instruction::SetCitationRoyalty::DISCRIMINATOR,
+        SolSageInstruction::PayCitationRoyalties => instruction::PayCitationRoyalties::DISCRIMINATOR,
+        SolSageInstruction::StakeDocument { .. } => instruction::StakeDocument::DISCRIMINATOR,
+        SolSageInstruction::AddDocumentChunk { .. } => instruction::AddDocumentChunk::DISCRIMINATOR,
+        SolSageInstruction::RefreshDocument => instruction::RefreshDocument::DISCRIMINATOR,
+        SolSageInstruction::UpdateEmbeddingFingerprint { .. } => instruction::UpdateEmbeddingFingerprint::DISCRIMINATOR,
+        SolSageInstruction::AddReviewer { .. } => instruction::AddReviewer::DISCRIMINATOR,
+        SolSageInstruction::RemoveReviewer { .. } => instruction::RemoveReviewer::DISCRIMINATOR,
+        SolSageInstruction::AttestEntry { .. } => instruction::AttestEntry::DISCRIMINATOR,
+        SolSageInstruction::SetIdentityConfig { .. } => instruction::SetIdentityConfig::DISCRIMINATOR,
+        SolSageInstruction::SetEntryVisibility { .. } => instruction::SetEntryVisibility::DISCRIMINATOR,
+        SolSageInstruction::GrantAccess { .. } => instruction::GrantAccess::DISCRIMINATOR,
+        SolSageInstruction::BuyAccess { .. } => instruction::BuyAccess::DISCRIMINATOR,
+    }
+}
+
+/// Leading accounts of [`SolSageInstruction::Initialize`]
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    /// Protocol authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Protocol account (PDA)
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    /// Treasury account (PDA)
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::StakeKnowledge`]
+#[derive(Accounts)]
+pub struct StakeKnowledge<'info> {
+    /// Staker
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+    /// Knowledge entry account (PDA)
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Stake escrow account (PDA)
+    #[account(mut)]
+    pub stake_escrow: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    /// Staker profile account (PDA)
+    #[account(mut)]
+    pub staker_profile: UncheckedAccount<'info>,
+    /// Content registry account for `content_hash` (PDA)
+    #[account(mut)]
+    pub content_registry: UncheckedAccount<'info>,
+    /// Category account (PDA)
+    #[account(mut)]
+    pub category: UncheckedAccount<'info>,
+    /// Staker index account (PDA); `entry_index` must be its `entry_count`
+    #[account(mut)]
+    pub staker_index: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::RecordAttribution`]
+#[derive(Accounts)]
+pub struct RecordAttribution<'info> {
+    /// Payer (registered oracle)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Attribution account (PDA)
+    #[account(mut)]
+    pub attribution: UncheckedAccount<'info>,
+    /// Query record account (PDA)
+    #[account(mut)]
+    pub query_record: UncheckedAccount<'info>,
+    /// Attribution oracle account (PDA)
+    pub attribution_oracle: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    /// Staker profile account of the entry owner (PDA)
+    #[account(mut)]
+    pub staker_profile: UncheckedAccount<'info>,
+    /// Treasury account (PDA)
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+    /// Category account of the entry
+    #[account(mut)]
+    pub category: UncheckedAccount<'info>,
+    /// Leaderboard account (PDA), created on first use
+    #[account(mut)]
+    pub leaderboard: UncheckedAccount<'info>,
+    /// Oracle state PDA of the payer, created on first use
+    #[account(mut)]
+    pub oracle_state: UncheckedAccount<'info>,
+    /// Payer stats PDA of the payer, created on first use
+    #[account(mut)]
+    pub payer_stats: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::ClaimRewards`]
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    /// Staker or their claim delegate
+    pub claimer: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::InitializeMint`]
+#[derive(Accounts)]
+pub struct InitializeMint<'info> {
+    /// Protocol authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+    /// SAGE mint (PDA)
+    #[account(mut)]
+    pub sage_mint: UncheckedAccount<'info>,
+    /// Mint authority (PDA)
+    pub mint_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::CompleteUnstake`]
+#[derive(Accounts)]
+pub struct CompleteUnstake<'info> {
+    /// Staker
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Stake escrow account (PDA)
+    #[account(mut)]
+    pub stake_escrow: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::UpdateKnowledge`]
+#[derive(Accounts)]
+pub struct UpdateKnowledge<'info> {
+    /// Staker, paying to grow the entry and for the new content registry if
+    /// needed
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::DeactivateKnowledge`]
+#[derive(Accounts)]
+pub struct DeactivateKnowledge<'info> {
+    /// Staker or protocol authority
+    pub signer: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::ReactivateKnowledge`]
+#[derive(Accounts)]
+pub struct ReactivateKnowledge<'info> {
+    /// Staker or protocol authority
+    pub signer: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::CloseKnowledgeEntry`]
+#[derive(Accounts)]
+pub struct CloseKnowledgeEntry<'info> {
+    /// Staker
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Stake escrow account (PDA)
+    #[account(mut)]
+    pub stake_escrow: UncheckedAccount<'info>,
+    /// Category account of the entry
+    #[account(mut)]
+    pub category: UncheckedAccount<'info>,
+    /// Content registry account for the entry's content hash (PDA), closed if
+    /// the entry holds it
+    #[account(mut)]
+    pub content_registry: UncheckedAccount<'info>,
+    /// Staker index account of the entry's creator (PDA), counting the entry
+    /// closed if the creator has one
+    #[account(mut)]
+    pub staker_index: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetRewardRate`]
+#[derive(Accounts)]
+pub struct SetRewardRate<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::ProposeAuthorityTransfer`]
+#[derive(Accounts)]
+pub struct ProposeAuthorityTransfer<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::AcceptAuthorityTransfer`]
+#[derive(Accounts)]
+pub struct AcceptAuthorityTransfer<'info> {
+    /// Pending authority
+    pub new_authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetVestingPeriod`]
+#[derive(Accounts)]
+pub struct SetVestingPeriod<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::GetVestedRewards`]
+#[derive(Accounts)]
+pub struct GetVestedRewards<'info> {
+    /// Knowledge entry account
+    pub knowledge_entry: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::AddOracle`]
+#[derive(Accounts)]
+pub struct AddOracle<'info> {
+    /// Protocol authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Attribution oracle account (PDA)
+    #[account(mut)]
+    pub attribution_oracle: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::RemoveOracle`]
+#[derive(Accounts)]
+pub struct RemoveOracle<'info> {
+    /// Protocol authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Attribution oracle account (PDA)
+    #[account(mut)]
+    pub attribution_oracle: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::RecordAttributionBatch`]
+#[derive(Accounts)]
+pub struct RecordAttributionBatch<'info> {
+    /// Payer (registered oracle)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+    /// Query record account (PDA)
+    #[account(mut)]
+    pub query_record: UncheckedAccount<'info>,
+    /// Attribution oracle account (PDA)
+    pub attribution_oracle: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    /// Treasury account (PDA)
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+    /// Leaderboard account (PDA), created on first use
+    #[account(mut)]
+    pub leaderboard: UncheckedAccount<'info>,
+    /// Oracle state PDA of the payer, created on first use
+    #[account(mut)]
+    pub oracle_state: UncheckedAccount<'info>,
+    /// Payer stats PDA of the payer, created on first use
+    #[account(mut)]
+    pub payer_stats: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::ClaimAllRewards`]
+#[derive(Accounts)]
+pub struct ClaimAllRewards<'info> {
+    /// Staker or their claim delegate
+    pub claimer: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::TransferKnowledgeOwnership`]
+#[derive(Accounts)]
+pub struct TransferKnowledgeOwnership<'info> {
+    /// Staker
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// New owner's staker profile account (PDA)
+    #[account(mut)]
+    pub staker_profile: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    /// Staker's staker profile account (PDA)
+    #[account(mut)]
+    pub previous_owner_profile: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetRewardCurve`]
+#[derive(Accounts)]
+pub struct SetRewardCurve<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::ChallengeAttribution`]
+#[derive(Accounts)]
+pub struct ChallengeAttribution<'info> {
+    /// Challenger
+    #[account(mut)]
+    pub challenger: Signer<'info>,
+    /// Attribution account
+    pub attribution: UncheckedAccount<'info>,
+    /// Challenge account (PDA)
+    #[account(mut)]
+    pub challenge: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::ResolveChallenge`]
+#[derive(Accounts)]
+pub struct ResolveChallenge<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Attribution account
+    #[account(mut)]
+    pub attribution: UncheckedAccount<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Challenge account (PDA)
+    #[account(mut)]
+    pub challenge: UncheckedAccount<'info>,
+    /// Challenger
+    #[account(mut)]
+    pub challenger: UncheckedAccount<'info>,
+    /// Knowledge entry owner
+    #[account(mut)]
+    pub staker: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SlashKnowledge`]
+#[derive(Accounts)]
+pub struct SlashKnowledge<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Stake escrow account (PDA)
+    #[account(mut)]
+    pub stake_escrow: UncheckedAccount<'info>,
+    /// Treasury account (PDA)
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetProtocolFee`]
+#[derive(Accounts)]
+pub struct SetProtocolFee<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::WithdrawTreasury`]
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Treasury account (PDA)
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+    /// Destination
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::CreateCategory`]
+#[derive(Accounts)]
+pub struct CreateCategory<'info> {
+    /// Protocol authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Category account (PDA)
+    #[account(mut)]
+    pub category: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetCategoryMultiplier`]
+#[derive(Accounts)]
+pub struct SetCategoryMultiplier<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Category account
+    #[account(mut)]
+    pub category: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::RenewKnowledge`]
+#[derive(Accounts)]
+pub struct RenewKnowledge<'info> {
+    /// Staker
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Treasury account (PDA)
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetClaimDelegate`]
+#[derive(Accounts)]
+pub struct SetClaimDelegate<'info> {
+    /// Staker
+    pub staker: Signer<'info>,
+    /// Staker profile account (PDA)
+    #[account(mut)]
+    pub staker_profile: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetMinStake`]
+#[derive(Accounts)]
+pub struct SetMinStake<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetAttributionCooldown`]
+#[derive(Accounts)]
+pub struct SetAttributionCooldown<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::Pause`]
+#[derive(Accounts)]
+pub struct Pause<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::Unpause`]
+#[derive(Accounts)]
+pub struct Unpause<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetScoreDivisor`]
+#[derive(Accounts)]
+pub struct SetScoreDivisor<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::CommitAttributionRoot`]
+#[derive(Accounts)]
+pub struct CommitAttributionRoot<'info> {
+    /// Registered oracle
+    #[account(mut)]
+    pub oracle: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Oracle registration PDA
+    pub attribution_oracle: UncheckedAccount<'info>,
+    /// Attribution root PDA
+    #[account(mut)]
+    pub attribution_root: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::ClaimAttributedReward`]
+#[derive(Accounts)]
+pub struct ClaimAttributedReward<'info> {
+    /// Entry owner or their claim delegate
+    pub claimer: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+    /// Attribution root PDA
+    #[account(mut)]
+    pub attribution_root: UncheckedAccount<'info>,
+    /// Knowledge entry
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetEpochSchedule`]
+#[derive(Accounts)]
+pub struct SetEpochSchedule<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::OpenEpoch`]
+#[derive(Accounts)]
+pub struct OpenEpoch<'info> {
+    /// Payer
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+    /// Epoch PDA for index `epoch_count`
+    #[account(mut)]
+    pub epoch: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::FinalizeEpoch`]
+#[derive(Accounts)]
+pub struct FinalizeEpoch<'info> {
+    /// Epoch PDA
+    #[account(mut)]
+    pub epoch: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetCuratorShare`]
+#[derive(Accounts)]
+pub struct SetCuratorShare<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::ClaimCuratorRewards`]
+#[derive(Accounts)]
+pub struct ClaimCuratorRewards<'info> {
+    /// Curator
+    pub curator: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Curator account PDA
+    #[account(mut)]
+    pub curator_account: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::CreateCollection`]
+#[derive(Accounts)]
+pub struct CreateCollection<'info> {
+    /// Owner
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// Collection PDA
+    #[account(mut)]
+    pub collection: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::AddToCollection`]
+#[derive(Accounts)]
+pub struct AddToCollection<'info> {
+    /// Owner of both the collection and the entry
+    pub owner: Signer<'info>,
+    /// Collection PDA
+    #[account(mut)]
+    pub collection: UncheckedAccount<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::RemoveFromCollection`]
+#[derive(Accounts)]
+pub struct RemoveFromCollection<'info> {
+    /// Collection owner or entry owner
+    pub signer: Signer<'info>,
+    /// Collection PDA
+    #[account(mut)]
+    pub collection: UncheckedAccount<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetRoyaltySplit`]
+#[derive(Accounts)]
+pub struct SetRoyaltySplit<'info> {
+    /// Staker, paying to grow the entry if needed
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::UpdateLicense`]
+#[derive(Accounts)]
+pub struct UpdateLicense<'info> {
+    /// Staker, paying to grow the entry if needed
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::UpdateContentUri`]
+#[derive(Accounts)]
+pub struct UpdateContentUri<'info> {
+    /// Staker, paying to grow the entry if needed
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::MigrateAccount`]
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// Protocol authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+    /// Account to migrate
+    #[account(mut)]
+    pub account: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::AddTag`]
+#[derive(Accounts)]
+pub struct AddTag<'info> {
+    /// Staker, paying for a new tag index and to grow the entry if needed
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Tag index account for `tag` (PDA), created on first use
+    #[account(mut)]
+    pub tag_index: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::RemoveTag`]
+#[derive(Accounts)]
+pub struct RemoveTag<'info> {
+    /// Staker, paying to grow the entry if needed
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Tag index account for `tag` (PDA)
+    #[account(mut)]
+    pub tag_index: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetRewardSource`]
+#[derive(Accounts)]
+pub struct SetRewardSource<'info> {
+    /// Protocol authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+    /// Reward vault (PDA), created on first switch to `Payer`
+    #[account(mut)]
+    pub reward_vault: UncheckedAccount<'info>,
+    /// SAGE mint
+    pub sage_mint: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::TopUpSubscription`]
+#[derive(Accounts)]
+pub struct TopUpSubscription<'info> {
+    /// Consumer
+    #[account(mut)]
+    pub consumer: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Subscription account of the consumer (PDA), created on first top-up
+    #[account(mut)]
+    pub subscription: UncheckedAccount<'info>,
+    /// Consumer's SAGE token account
+    #[account(mut)]
+    pub consumer_token_account: UncheckedAccount<'info>,
+    /// Reward vault (PDA)
+    #[account(mut)]
+    pub reward_vault: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::CloseSubscription`]
+#[derive(Accounts)]
+pub struct CloseSubscription<'info> {
+    /// Consumer
+    #[account(mut)]
+    pub consumer: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Subscription account of the consumer (PDA)
+    #[account(mut)]
+    pub subscription: UncheckedAccount<'info>,
+    /// SAGE token account receiving the refund
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+    /// Reward vault (PDA)
+    #[account(mut)]
+    pub reward_vault: UncheckedAccount<'info>,
+    /// Mint authority (PDA)
+    pub mint_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetPriceFloor`]
+#[derive(Accounts)]
+pub struct SetPriceFloor<'info> {
+    /// Staker, paying to grow the entry if needed
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::RebuildLeaderboard`]
+#[derive(Accounts)]
+pub struct RebuildLeaderboard<'info> {
+    /// Payer, funding the leaderboard if it is created
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// Leaderboard account (PDA)
+    #[account(mut)]
+    pub leaderboard: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetGovernanceConfig`]
+#[derive(Accounts)]
+pub struct SetGovernanceConfig<'info> {
+    /// Protocol authority, funding the governance vault if it is created
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+    /// Governance vault (PDA), created when governance is first turned on
+    #[account(mut)]
+    pub governance_vault: UncheckedAccount<'info>,
+    /// SAGE mint
+    pub sage_mint: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::CreateProposal`]
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    /// Proposer
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+    /// Proposal account (PDA), at the protocol's next proposal index
+    #[account(mut)]
+    pub proposal: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::CastVote`]
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    /// Voter
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Proposal account
+    #[account(mut)]
+    pub proposal: UncheckedAccount<'info>,
+    /// Vote record account (PDA)
+    #[account(mut)]
+    pub vote_record: UncheckedAccount<'info>,
+    /// Voter's SAGE token account
+    #[account(mut)]
+    pub voter_token_account: UncheckedAccount<'info>,
+    /// Governance vault (PDA)
+    #[account(mut)]
+    pub governance_vault: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::ExecuteProposal`]
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    /// Executor, funding the oracle account an `AddOracle` creates and
+    /// receiving the rent of one a `RemoveOracle` closes
+    #[account(mut)]
+    pub executor: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+    /// Proposal account
+    #[account(mut)]
+    pub proposal: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::WithdrawVote`]
+#[derive(Accounts)]
+pub struct WithdrawVote<'info> {
+    /// Voter
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Proposal account
+    pub proposal: UncheckedAccount<'info>,
+    /// Vote record account (PDA)
+    #[account(mut)]
+    pub vote_record: UncheckedAccount<'info>,
+    /// SAGE token account receiving the tokens
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+    /// Governance vault (PDA)
+    #[account(mut)]
+    pub governance_vault: UncheckedAccount<'info>,
+    /// Mint authority (PDA)
+    pub mint_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Leading accounts of [`SolSageInstruction::QueueParameterChange`]
+#[derive(Accounts)]
+pub struct QueueParameterChange<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::CancelParameterChange`]
+#[derive(Accounts)]
+pub struct CancelParameterChange<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::ExecuteParameterChange`]
+#[derive(Accounts)]
+pub struct ExecuteParameterChange<'info> {
+    /// Executor, funding the oracle account an `AddOracle` creates and
+    /// receiving the rent of one a `RemoveOracle` closes
+    #[account(mut)]
+    pub executor: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetScoreDecay`]
+#[derive(Accounts)]
+pub struct SetScoreDecay<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetAttributionVerifier`]
+#[derive(Accounts)]
+pub struct SetAttributionVerifier<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::RecordSignedAttribution`]
+#[derive(Accounts)]
+pub struct RecordSignedAttribution<'info> {
+    /// Relayer, paying for the attribution
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Attribution account (PDA)
+    #[account(mut)]
+    pub attribution: UncheckedAccount<'info>,
+    /// Query record account (PDA)
+    #[account(mut)]
+    pub query_record: UncheckedAccount<'info>,
+    /// Attribution oracle account (PDA) of the receipt's signer
+    pub attribution_oracle: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    /// Staker profile account of the entry owner (PDA)
+    #[account(mut)]
+    pub staker_profile: UncheckedAccount<'info>,
+    /// Treasury account (PDA)
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+    /// Category account of the entry
+    #[account(mut)]
+    pub category: UncheckedAccount<'info>,
+    /// Leaderboard account (PDA), created on first use
+    #[account(mut)]
+    pub leaderboard: UncheckedAccount<'info>,
+    /// Oracle state PDA of the receipt's signer
+    #[account(mut)]
+    pub oracle_state: UncheckedAccount<'info>,
+    /// Payer stats PDA of the relayer
+    #[account(mut)]
+    pub payer_stats: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetClaimWindow`]
+#[derive(Accounts)]
+pub struct SetClaimWindow<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::ExpireRewards`]
+#[derive(Accounts)]
+pub struct ExpireRewards<'info> {
+    /// Caller
+    pub caller: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::RequestUnstake`]
+#[derive(Accounts)]
+pub struct RequestUnstake<'info> {
+    /// Staker, paying to grow the entry if needed
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::TakeSnapshot`]
+#[derive(Accounts)]
+pub struct TakeSnapshot<'info> {
+    /// Payer
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Snapshot PDA of the current day
+    #[account(mut)]
+    pub snapshot: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetReceiptTree`]
+#[derive(Accounts)]
+pub struct SetReceiptTree<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::AddBridgeEmitter`]
+#[derive(Accounts)]
+pub struct AddBridgeEmitter<'info> {
+    /// Protocol authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Bridge emitter PDA
+    #[account(mut)]
+    pub bridge_emitter: UncheckedAccount<'info>,
+    /// Attribution oracle account (PDA) of the bridge emitter PDA
+    #[account(mut)]
+    pub attribution_oracle: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::RecordBridgedAttribution`]
+#[derive(Accounts)]
+pub struct RecordBridgedAttribution<'info> {
+    /// Relayer, paying for the attribution
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Attribution account (PDA)
+    #[account(mut)]
+    pub attribution: UncheckedAccount<'info>,
+    /// Query record account (PDA)
+    #[account(mut)]
+    pub query_record: UncheckedAccount<'info>,
+    /// Attribution oracle account (PDA) of the bridge emitter PDA
+    pub attribution_oracle: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    /// Staker profile account of the entry owner (PDA)
+    #[account(mut)]
+    pub staker_profile: UncheckedAccount<'info>,
+    /// Treasury account (PDA)
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+    /// Category account of the entry
+    #[account(mut)]
+    pub category: UncheckedAccount<'info>,
+    /// Leaderboard account (PDA), created on first use
+    #[account(mut)]
+    pub leaderboard: UncheckedAccount<'info>,
+    /// Oracle state PDA of the bridge emitter PDA
+    #[account(mut)]
+    pub oracle_state: UncheckedAccount<'info>,
+    /// Payer stats PDA of the relayer
+    #[account(mut)]
+    pub payer_stats: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetMintMetadata`]
+#[derive(Accounts)]
+pub struct SetMintMetadata<'info> {
+    /// Protocol authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// SAGE mint (PDA)
+    pub sage_mint: UncheckedAccount<'info>,
+    /// Mint authority (PDA)
+    pub mint_authority: UncheckedAccount<'info>,
+    /// Token Metadata account of the SAGE mint
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+    /// Token Metadata program
+    pub token_metadata_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::ListEntryForSale`]
+#[derive(Accounts)]
+pub struct ListEntryForSale<'info> {
+    /// Staker
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    /// Knowledge entry account
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Listing PDA of the entry
+    #[account(mut)]
+    pub listing: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::CancelListing`]
+#[derive(Accounts)]
+pub struct CancelListing<'info> {
+    /// Seller, or the entry's owner
+    pub signer: Signer<'info>,
+    /// Knowledge entry account
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Listing PDA of the entry
+    #[account(mut)]
+    pub listing: UncheckedAccount<'info>,
+    /// Seller, refunded the listing's rent
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::BuyEntry`]
+#[derive(Accounts)]
+pub struct BuyEntry<'info> {
+    /// Buyer
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Listing PDA of the entry
+    #[account(mut)]
+    pub listing: UncheckedAccount<'info>,
+    /// Seller
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+    /// Treasury (PDA)
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+    /// Buyer's staker profile account (PDA)
+    #[account(mut)]
+    pub staker_profile: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    /// Seller's staker profile account (PDA)
+    #[account(mut)]
+    pub seller_profile: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetSaleFee`]
+#[derive(Accounts)]
+pub struct SetSaleFee<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetOracleBudget`]
+#[derive(Accounts)]
+pub struct SetOracleBudget<'info> {
+    /// Protocol authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Oracle state PDA of `oracle`, created on first use
+    #[account(mut)]
+    pub oracle_state: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetConsensus`]
+#[derive(Accounts)]
+pub struct SetConsensus<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetAggregationWindow`]
+#[derive(Accounts)]
+pub struct SetAggregationWindow<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::FinalizeAttribution`]
+#[derive(Accounts)]
+pub struct FinalizeAttribution<'info> {
+    /// Finalizer
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Attribution account (PDA)
+    #[account(mut)]
+    pub attribution: UncheckedAccount<'info>,
+    /// Query record account (PDA)
+    #[account(mut)]
+    pub query_record: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    /// Staker profile account of the entry owner (PDA)
+    #[account(mut)]
+    pub staker_profile: UncheckedAccount<'info>,
+    /// Treasury account (PDA)
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+    /// Category account of the entry
+    #[account(mut)]
+    pub category: UncheckedAccount<'info>,
+    /// Leaderboard account (PDA), created on first use
+    #[account(mut)]
+    pub leaderboard: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetPayerAttributionCap`]
+#[derive(Accounts)]
+pub struct SetPayerAttributionCap<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::UpvoteEntry`]
+#[derive(Accounts)]
+pub struct UpvoteEntry<'info> {
+    /// Voter
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Entry vote account (PDA)
+    #[account(mut)]
+    pub entry_vote: UncheckedAccount<'info>,
+    /// Voter's SAGE token account
+    #[account(mut)]
+    pub voter_token_account: UncheckedAccount<'info>,
+    /// Governance vault (PDA)
+    #[account(mut)]
+    pub governance_vault: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::DownvoteEntry`]
+#[derive(Accounts)]
+pub struct DownvoteEntry<'info> {
+    /// Voter
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Entry vote account (PDA)
+    #[account(mut)]
+    pub entry_vote: UncheckedAccount<'info>,
+    /// Voter's SAGE token account
+    #[account(mut)]
+    pub voter_token_account: UncheckedAccount<'info>,
+    /// Governance vault (PDA)
+    #[account(mut)]
+    pub governance_vault: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::WithdrawEntryVote`]
+#[derive(Accounts)]
+pub struct WithdrawEntryVote<'info> {
+    /// Voter
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Knowledge entry account, which may have been closed
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Entry vote account (PDA)
+    #[account(mut)]
+    pub entry_vote: UncheckedAccount<'info>,
+    /// SAGE token account receiving the tokens
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+    /// Governance vault (PDA)
+    #[account(mut)]
+    pub governance_vault: UncheckedAccount<'info>,
+    /// Mint authority (PDA)
+    pub mint_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Leading accounts of [`SolSageInstruction::ReportEntry`]
+#[derive(Accounts)]
+pub struct ReportEntry<'info> {
+    /// Reporter
+    #[account(mut)]
+    pub reporter: Signer<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Report queue account (PDA), created by the first report
+    #[account(mut)]
+    pub report_queue: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::ResolveReports`]
+#[derive(Accounts)]
+pub struct ResolveReports<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Report queue account (PDA)
+    #[account(mut)]
+    pub report_queue: UncheckedAccount<'info>,
+    /// Knowledge entry owner
+    #[account(mut)]
+    pub staker: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::CreateBounty`]
+#[derive(Accounts)]
+pub struct CreateBounty<'info> {
+    /// Requester
+    #[account(mut)]
+    pub requester: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Bounty account (PDA)
+    #[account(mut)]
+    pub bounty: UncheckedAccount<'info>,
+    /// Bounty vault (PDA)
+    #[account(mut)]
+    pub bounty_vault: UncheckedAccount<'info>,
+    /// Requester's SAGE token account
+    #[account(mut)]
+    pub requester_token_account: UncheckedAccount<'info>,
+    /// SAGE mint
+    pub sage_mint: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SubmitAnswer`]
+#[derive(Accounts)]
+pub struct SubmitAnswer<'info> {
+    /// Staker owning the entry
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    /// Bounty account
+    #[account(mut)]
+    pub bounty: UncheckedAccount<'info>,
+    /// Knowledge entry account
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Bounty answer account (PDA)
+    #[account(mut)]
+    pub answer: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::AcceptAnswer`]
+#[derive(Accounts)]
+pub struct AcceptAnswer<'info> {
+    /// Requester
+    #[account(mut)]
+    pub requester: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Bounty account
+    #[account(mut)]
+    pub bounty: UncheckedAccount<'info>,
+    /// Bounty answer account
+    pub answer: UncheckedAccount<'info>,
+    /// Bounty vault (PDA)
+    #[account(mut)]
+    pub bounty_vault: UncheckedAccount<'info>,
+    /// Answering staker's SAGE token account
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+    /// Mint authority (PDA)
+    pub mint_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Leading accounts of [`SolSageInstruction::ReclaimExpiredBounty`]
+#[derive(Accounts)]
+pub struct ReclaimExpiredBounty<'info> {
+    /// Requester
+    #[account(mut)]
+    pub requester: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Bounty account
+    #[account(mut)]
+    pub bounty: UncheckedAccount<'info>,
+    /// Bounty vault (PDA)
+    #[account(mut)]
+    pub bounty_vault: UncheckedAccount<'info>,
+    /// SAGE token account receiving the refund
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+    /// Mint authority (PDA)
+    pub mint_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetRewardStream`]
+#[derive(Accounts)]
+pub struct SetRewardStream<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetFeeConfig`]
+#[derive(Accounts)]
+pub struct SetFeeConfig<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::ClaimTreasuryRewards`]
+#[derive(Accounts)]
+pub struct ClaimTreasuryRewards<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetLowRelevanceBurn`]
+#[derive(Accounts)]
+pub struct SetLowRelevanceBurn<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::BurnRewards`]
+#[derive(Accounts)]
+pub struct BurnRewards<'info> {
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+    /// Reward vault (PDA)
+    #[account(mut)]
+    pub reward_vault: UncheckedAccount<'info>,
+    /// SAGE mint
+    #[account(mut)]
+    pub sage_mint: UncheckedAccount<'info>,
+    /// Mint authority PDA
+    pub mint_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetCitationRoyalty`]
+#[derive(Accounts)]
+pub struct SetCitationRoyalty<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::PayCitationRoyalties`]
+#[derive(Accounts)]
+pub struct PayCitationRoyalties<'info> {
+    /// Derivative knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::StakeDocument`]
+#[derive(Accounts)]
+pub struct StakeDocument<'info> {
+    /// Owner
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// Document PDA
+    #[account(mut)]
+    pub document: UncheckedAccount<'info>,
+    /// Category the chunks are filed under
+    pub category: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::AddDocumentChunk`]
+#[derive(Accounts)]
+pub struct AddDocumentChunk<'info> {
+    /// Document owner
+    pub owner: Signer<'info>,
+    /// Document PDA
+    #[account(mut)]
+    pub document: UncheckedAccount<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::RefreshDocument`]
+#[derive(Accounts)]
+pub struct RefreshDocument<'info> {
+    /// Document PDA
+    #[account(mut)]
+    pub document: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::UpdateEmbeddingFingerprint`]
+#[derive(Accounts)]
+pub struct UpdateEmbeddingFingerprint<'info> {
+    /// Staker, paying to grow the entry if needed
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::AddReviewer`]
+#[derive(Accounts)]
+pub struct AddReviewer<'info> {
+    /// Protocol authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Reviewer registry account (PDA), created on first use
+    #[account(mut)]
+    pub reviewer_registry: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::RemoveReviewer`]
+#[derive(Accounts)]
+pub struct RemoveReviewer<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    pub protocol: UncheckedAccount<'info>,
+    /// Reviewer registry account (PDA)
+    #[account(mut)]
+    pub reviewer_registry: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::AttestEntry`]
+#[derive(Accounts)]
+pub struct AttestEntry<'info> {
+    /// Approved reviewer, paying to grow the entry if needed
+    #[account(mut)]
+    pub reviewer: Signer<'info>,
+    /// Reviewer registry account (PDA)
+    pub reviewer_registry: UncheckedAccount<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetIdentityConfig`]
+#[derive(Accounts)]
+pub struct SetIdentityConfig<'info> {
+    /// Protocol authority
+    pub authority: Signer<'info>,
+    /// Protocol account
+    #[account(mut)]
+    pub protocol: UncheckedAccount<'info>,
+}
+
+/// Leading accounts of [`SolSageInstruction::SetEntryVisibility`]
+#[derive(Accounts)]
+pub struct SetEntryVisibility<'info> {
+    /// Staker, paying to grow the entry if needed
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    /// Knowledge entry account
+    #[account(mut)]
+    pub knowledge_entry: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::GrantAccess`]
+#[derive(Accounts)]
+pub struct GrantAccess<'info> {
+    /// Staker
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    /// Knowledge entry account
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Access pass PDA of `holder`
+    #[account(mut)]
+    pub access_pass: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of [`SolSageInstruction::BuyAccess`]
+#[derive(Accounts)]
+pub struct BuyAccess<'info> {
+    /// Buyer
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    /// Knowledge entry account
+    pub knowledge_entry: UncheckedAccount<'info>,
+    /// Entry owner, paid the price
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+    /// Access pass PDA of the buyer
+    #[account(mut)]
+    pub access_pass: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
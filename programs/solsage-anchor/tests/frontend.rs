@@ -0,0 +1,90 @@
+//! Instructions the frontend's encoders in `solsage-instructions.ts` must
+//! reproduce byte for byte, held to `frontend/src/lib/fixtures/instructions.json`.
+//! The frontend's own test builds the same instructions from the same inputs
+//! and compares them with that file, so a layout change here fails one side
+//! or the other until both agree. Rerun with `UPDATE_FRONTEND_FIXTURES=1` to
+//! rewrite the file once a change in layout is intended.
+
+use std::{env, fs, path::PathBuf};
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use solsage_anchor::from_native;
+use solsage_cpi::{
+    find_category_address, find_knowledge_entry_address, locked_up, record_attribution, stake_knowledge,
+    with_chunk_proof, with_chunks, AttributionMetadata, AttributionTarget, ChunkCommitment, ChunkProof, LicenseType,
+    LockupTier, StakedContent,
+};
+
+/// Program the frontend is configured for, `SOLSAGE_PROGRAM_ID`
+fn program_id() -> Pubkey {
+    "7E5HrDxxHXMxz4rNHj8k6JXwSP34GC7SgssemZWVBF5R".parse().unwrap()
+}
+
+fn fixtures_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../frontend/src/lib/fixtures/instructions.json")
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// `instruction` as the frontend's test reads it: its accounts in order and
+/// its data in hex
+fn fixture(name: &str, instruction: &Instruction) -> String {
+    let keys: Vec<String> = instruction
+        .accounts
+        .iter()
+        .map(|meta| {
+            format!(
+                "      {{ \"pubkey\": \"{}\", \"isSigner\": {}, \"isWritable\": {} }}",
+                meta.pubkey, meta.is_signer, meta.is_writable
+            )
+        })
+        .collect();
+    let data = hex(&instruction.data);
+    format!("  \"{name}\": {{\n    \"keys\": [\n{}\n    ],\n    \"data\": \"{data}\"\n  }}", keys.join(",\n"))
+}
+
+/// What the frontend stakes in its test: entry 3 of staker `[1; 32]` in the
+/// `solana` category, locked up for 90 days with 4 chunks committed
+fn stake() -> Instruction {
+    let staker = Pubkey::new_from_array([1; 32]);
+    let category = find_category_address(&program_id(), "solana").0;
+    let license = LicenseType::Custom { uri: "https://example.com/terms".to_string() };
+    let content = StakedContent { content_hash: [7; 32], title: "Async Rust".to_string(), license };
+    let stake = stake_knowledge(&program_id(), &staker, 3, content, 10_000_000, &category);
+    with_chunks(locked_up(stake, LockupTier::Days90), ChunkCommitment { root: [9; 32], chunk_count: 4 })
+}
+
+/// What the frontend attributes in its test: the entry `stake` stakes,
+/// scored 80 by its own staker as oracle for a curator and an agent, proving
+/// chunk 1 was used
+fn attribute() -> Instruction {
+    let oracle = Pubkey::new_from_array([1; 32]);
+    let staker = Pubkey::new_from_array([1; 32]);
+    let knowledge_entry = find_knowledge_entry_address(&program_id(), &staker, 3).0;
+    let category = find_category_address(&program_id(), "solana").0;
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    let curator = Some(Pubkey::new_from_array([2; 32]));
+    let metadata = AttributionMetadata { agent_id: Some([3; 32]), model_id: Some([4; 32]) };
+    let attribute = record_attribution(&program_id(), &oracle, &target, [5; 32], None, curator, metadata);
+    with_chunk_proof(attribute, ChunkProof { index: 1, chunk_hash: [6; 32], proof: vec![[8; 32], [10; 32]] })
+}
+
+#[test]
+fn frontend_encoders_match_the_programs_layouts() {
+    let instructions = [("stakeKnowledge", stake()), ("recordAttribution", attribute())];
+    let fixtures: Vec<String> = instructions
+        .iter()
+        .map(|(name, instruction)| fixture(name, &from_native(instruction.clone()).unwrap()))
+        .collect();
+    let fixtures = format!("{{\n{}\n}}\n", fixtures.join(",\n"));
+
+    if env::var_os("UPDATE_FRONTEND_FIXTURES").is_some() {
+        fs::write(fixtures_path(), fixtures).expect("fixtures are writable");
+        return;
+    }
+    let stored = fs::read_to_string(fixtures_path()).unwrap_or_default();
+    assert!(stored == fixtures, "frontend fixtures are stale; rerun with UPDATE_FRONTEND_FIXTURES=1");
+}
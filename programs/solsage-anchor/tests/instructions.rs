@@ -0,0 +1,789 @@
+use std::collections::BTreeSet;
+
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::{prelude::Pubkey, InstructionData, ToAccountMetas};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solsage_anchor::{accounts, from_native, instruction, ID};
+use solsage_cpi::*;
+use solsage_core::EMBEDDING_FINGERPRINT_LEN;
+
+/// One instruction of every kind, as native clients build them
+fn native_instructions() -> Vec<Instruction> {
+    let key = Pubkey::new_unique;
+    let (authority, staker, oracle, knowledge_entry) = (key(), key(), key(), key());
+    let category = find_category_address(&ID, "rust").0;
+    let content = || StakedContent { content_hash: [7; 32], title: "Async".to_string(), license: LicenseType::Mit };
+    let target = AttributionTarget::new(knowledge_entry, staker, category, 80);
+    let metadata = AttributionMetadata { agent_id: Some([1; 32]), model_id: Some([2; 32]) };
+    let claims = [AttributionClaim { attribution: key(), epoch: Some(3) }];
+    let reward_claims = [RewardClaim { knowledge_entry, attribution: key(), epoch: None }];
+    let action = ProposalAction::SetProtocolFee { fee_bps: 250 };
+    let stake = stake_knowledge(&ID, &staker, 0, content(), 1_000_000_000, &category);
+    let documents = stake_document_chunks(&ID, &staker, [8; 32], "Book", &category, 1, &[content()], 1_000_000_000);
+    let update = update_knowledge(&ID, &staker, &knowledge_entry, &[7; 32], Some("Pinning".to_string()), Some([8; 32]));
+    let close = close_knowledge_entry(&ID, &staker, &staker, &knowledge_entry, &category, &[7; 32]);
+    let record = || record_attribution(&ID, &oracle, &target, [9; 32], Some(3), Some(key()), metadata);
+    let mut instructions = vec![
+        initialize(&ID, &authority),
+        with_identity(with_chunks(locked_up(stake.clone(), LockupTier::Days90), chunk_commitment(&["a", "b"])), &key()),
+        derived_from(with_receipt(&ID, stake, &key()), &[key(), key()]),
+        record(),
+        with_chunk_proof(record(), chunk_proof(&["a", "b"], 1)),
+        relayed_by(record(), &key(), 4),
+        bridged_from(record(), &key(), &key()),
+        paid_from_reward_vault(&ID, with_reward_funding(&ID, record(), &key())),
+        claim_rewards(&ID, &staker, &staker, &knowledge_entry, &key(), &[key()], &claims),
+        initialize_mint(&ID, &authority, 1_000_000),
+        complete_unstake(&ID, &staker, &knowledge_entry),
+        refunding_registry_to(update, &key()),
+        deactivate_knowledge(&ID, &authority, &knowledge_entry),
+        reactivate_knowledge(&ID, &authority, &knowledge_entry),
+        refunding_registry_to(close, &key()),
+        set_reward_rate(&ID, &authority, 5),
+        propose_authority_transfer(&ID, &authority, &key(), AuthorityType::Squads { multisig: key(), vault_index: 1 }),
+        accept_authority_transfer(&ID, &key()),
+        set_vesting_period(&ID, &authority, 60),
+        get_vested_rewards(&ID, &knowledge_entry),
+        add_oracle(&ID, &authority, &oracle),
+        remove_oracle(&ID, &authority, &oracle),
+        record_attribution_batch(&ID, &oracle, [9; 32], &[target, target], None),
+        claim_all_rewards(&ID, &staker, &staker, &key(), &reward_claims),
+        transfer_knowledge_ownership(&ID, &staker, &knowledge_entry, &key()),
+        set_reward_curve(&ID, &authority, RewardCurve::Quadratic),
+        challenge_attribution(&ID, &key(), &key(), 10),
+        resolve_challenge(&ID, &authority, &key(), &knowledge_entry, &key(), &staker, true),
+        slash_knowledge(&ID, &authority, &knowledge_entry, 500),
+        set_protocol_fee(&ID, &authority, 250),
+        withdraw_treasury(&ID, &authority, &key(), 10),
+        create_category(&ID, &authority, "rust"),
+        set_category_multiplier(&ID, &authority, &category, 15_000),
+        renew_knowledge(&ID, &staker, &knowledge_entry, Some([8; 32])),
+        set_claim_delegate(&ID, &staker, Some(key())),
+        set_min_stake(&ID, &authority, 10),
+        set_attribution_cooldown(&ID, &authority, 60),
+        pause(&ID, &authority),
+        unpause(&ID, &authority),
+        set_score_divisor(&ID, &authority, 100),
+        commit_attribution_root(&ID, &oracle, 3, [5; 32], 2),
+        claim_attributed_reward(
+            &ID,
+            &staker,
+            &key(),
+            &target,
+            &key(),
+            AttributionProof { leaf_index: 1, query_hash: [9; 32], proof: vec![[4; 32]] },
+        ),
+        set_epoch_schedule(&ID, &authority, 60, 1_000),
+        open_epoch(&ID, &key(), 3),
+        finalize_epoch(&ID, 3),
+        set_curator_share(&ID, &authority, 100),
+        claim_curator_rewards(&ID, &key(), &key()),
+        create_collection(&ID, &staker, "favourites"),
+        add_to_collection(&ID, &staker, &key(), &knowledge_entry),
+        remove_from_collection(&ID, &staker, &key(), &knowledge_entry),
+        set_royalty_split(&ID, &staker, &knowledge_entry, vec![RoyaltySplit { co_author: key(), bps: 1_000 }]),
+        update_license(&ID, &staker, &knowledge_entry, LicenseType::Custom { uri: "ar://license".to_string() }),
+        update_content_uri(&ID, &staker, &knowledge_entry, Some("ar://content".to_string())),
+        migrate_account(&ID, &authority, &knowledge_entry, AccountType::KnowledgeEntry),
+        add_tag(&ID, &staker, &knowledge_entry, "async"),
+        remove_tag(&ID, &staker, &knowledge_entry, "async"),
+        set_reward_source(&ID, &authority, RewardSource::Payer),
+        top_up_subscription(&ID, &key(), &key(), 10),
+        close_subscription(&ID, &key(), &key()),
+        set_price_floor(&ID, &staker, &knowledge_entry, 10),
+        rebuild_leaderboard(&ID, &key(), &[knowledge_entry, key()]),
+        set_governance_config(&ID, &authority, 10, 5_000, 60, 60),
+        create_proposal(&ID, &key(), 0, action),
+        cast_vote(&ID, &key(), 0, &key(), true, 10),
+        execute_proposal(&ID, &key(), 0, &action),
+        withdraw_vote(&ID, &key(), 0, &key()),
+        queue_parameter_change(&ID, &authority, action),
+        cancel_parameter_change(&ID, &authority),
+        execute_parameter_change(&ID, &key(), &action),
+        set_score_decay(&ID, &authority, 60, 1_000),
+        set_attribution_verifier(&ID, &authority, Some(key())),
+        set_claim_window(&ID, &authority, 60),
+        expire_rewards(&ID, &key(), &knowledge_entry, &key(), &claims),
+        request_unstake(&ID, &staker, &knowledge_entry),
+        take_snapshot(&ID, &key(), 3),
+        set_receipt_tree(&ID, &authority, Some(key())),
+        add_bridge_emitter(&ID, &authority, &key(), 2, [6; 32]),
+        set_mint_metadata(&ID, &authority, "SolSage", "SAGE", "ar://mint"),
+        list_entry_for_sale(&ID, &staker, &knowledge_entry, 10),
+        cancel_listing(&ID, &staker, &knowledge_entry, &staker),
+        buy_entry(&ID, &key(), &knowledge_entry, &staker, 10),
+        set_sale_fee(&ID, &authority, 100),
+        set_oracle_budget(&ID, &authority, &oracle, 10, 1_000),
+        set_consensus(&ID, &authority, 2, 10),
+        set_aggregation_window(&ID, &authority, 60),
+        finalize_attribution(&ID, &key(), &target, [9; 32], Some(3), Some([1; 32])),
+        set_payer_attribution_cap(&ID, &authority, 10),
+        upvote_entry(&ID, &key(), &knowledge_entry, &key(), 10),
+        downvote_entry(&ID, &key(), &knowledge_entry, &key(), 10),
+        withdraw_entry_vote(&ID, &key(), &knowledge_entry, &key()),
+        report_entry(&ID, &key(), &knowledge_entry, 10),
+        resolve_reports(&ID, &authority, &knowledge_entry, &staker, &[key(), key()], false),
+        create_bounty(&ID, &key(), &key(), [9; 32], 10, 60),
+        submit_answer(&ID, &staker, &key(), &knowledge_entry),
+        accept_answer(&ID, &key(), &key(), &knowledge_entry, &key()),
+        reclaim_expired_bounty(&ID, &key(), &key(), &key()),
+        set_reward_stream(&ID, &authority, 60),
+        set_fee_config(&ID, &authority, FeeConfig { treasury_bps: 100, curator_bps: 200, burn_bps: 300 }),
+        claim_treasury_rewards(&ID, &authority, &key()),
+        set_low_relevance_burn(&ID, &authority, 20, 5_000),
+        burn_rewards(&ID),
+        set_citation_royalty(&ID, &authority, 100),
+        set_identity_config(
+            &ID,
+            &authority,
+            Some(IdentityConfig { attestation_program: key(), credential: key(), required: true }),
+        ),
+        pay_citation_royalties(&ID, &knowledge_entry, &[key(), key()]),
+        stake_document(&ID, &staker, [8; 32], "Book", 2, &category),
+        add_document_chunk(&ID, &staker, &key(), &knowledge_entry, 1),
+        refresh_document(&ID, &key(), &[knowledge_entry, key()]),
+        update_embedding_fingerprint(&ID, &staker, &knowledge_entry, Some([3; EMBEDDING_FINGERPRINT_LEN])),
+        add_reviewer(&ID, &authority, &key()),
+        remove_reviewer(&ID, &authority, &key()),
+        attest_entry(&ID, &key(), &knowledge_entry, 80, 90),
+        set_entry_visibility(&ID, &staker, &knowledge_entry, Visibility::Private { access_price: 10 }),
+        grant_access(&ID, &staker, &knowledge_entry, &key()),
+        buy_access(&ID, &key(), &knowledge_entry, &staker, 10),
+    ];
+    instructions.extend(documents);
+    instructions
+}
+
+/// `native` as an Anchor client builds it: the instruction's arguments under
+/// its discriminator, its leading accounts from `accounts`, and any optional
+/// accounts after them
+fn anchor_client(native: &Instruction) -> Instruction {
+    fn anchor(accounts: impl ToAccountMetas, data: impl InstructionData) -> (Vec<AccountMeta>, Vec<u8>) {
+        (accounts.to_account_metas(None), data.data())
+    }
+
+    let mut keys = native.accounts.iter().map(|meta| meta.pubkey);
+    let mut next = || keys.next().expect("leading account");
+    let (mut metas, data) = match SolSageInstruction::try_from_slice(&native.data).unwrap() {
+        SolSageInstruction::Initialize => anchor(
+            accounts::Initialize { authority: next(), protocol: next(), system_program: next(), treasury: next() },
+            instruction::Initialize {},
+        ),
+        SolSageInstruction::StakeKnowledge {
+            entry_index,
+            content_hash,
+            title,
+            stake_amount,
+            license,
+            lockup,
+            mint_receipt,
+            derived_from,
+            chunks,
+            identity,
+        } => anchor(
+            accounts::StakeKnowledge {
+                staker: next(), protocol: next(), knowledge_entry: next(), stake_escrow: next(), system_program: next(),
+                staker_profile: next(), content_registry: next(), category: next(), staker_index: next(),
+            },
+            instruction::StakeKnowledge {
+                entry_index, content_hash, title, stake_amount, license, lockup, mint_receipt, derived_from, chunks,
+                identity,
+            },
+        ),
+        SolSageInstruction::RecordAttribution {
+            query_hash,
+            relevance_score,
+            curator,
+            agent_id,
+            model_id,
+            chunk,
+        } => anchor(
+            accounts::RecordAttribution {
+                payer: next(), protocol: next(), knowledge_entry: next(), attribution: next(), query_record: next(),
+                attribution_oracle: next(), system_program: next(), staker_profile: next(), treasury: next(),
+                category: next(), leaderboard: next(), oracle_state: next(), payer_stats: next(),
+            },
+            instruction::RecordAttribution { query_hash, relevance_score, curator, agent_id, model_id, chunk },
+        ),
+        SolSageInstruction::ClaimRewards => anchor(
+            accounts::ClaimRewards { claimer: next(), protocol: next(), knowledge_entry: next() },
+            instruction::ClaimRewards {},
+        ),
+        SolSageInstruction::InitializeMint { max_supply } => anchor(
+            accounts::InitializeMint {
+                authority: next(), protocol: next(), sage_mint: next(), mint_authority: next(), token_program: next(),
+                system_program: next(),
+            },
+            instruction::InitializeMint { max_supply },
+        ),
+        SolSageInstruction::CompleteUnstake => anchor(
+            accounts::CompleteUnstake { staker: next(), knowledge_entry: next(), stake_escrow: next() },
+            instruction::CompleteUnstake {},
+        ),
+        SolSageInstruction::UpdateKnowledge { title, content_hash } => anchor(
+            accounts::UpdateKnowledge { staker: next(), knowledge_entry: next(), system_program: next() },
+            instruction::UpdateKnowledge { title, content_hash },
+        ),
+        SolSageInstruction::DeactivateKnowledge => anchor(
+            accounts::DeactivateKnowledge { signer: next(), protocol: next(), knowledge_entry: next() },
+            instruction::DeactivateKnowledge {},
+        ),
+        SolSageInstruction::ReactivateKnowledge => anchor(
+            accounts::ReactivateKnowledge { signer: next(), protocol: next(), knowledge_entry: next() },
+            instruction::ReactivateKnowledge {},
+        ),
+        SolSageInstruction::CloseKnowledgeEntry => anchor(
+            accounts::CloseKnowledgeEntry {
+                staker: next(), protocol: next(), knowledge_entry: next(), stake_escrow: next(), category: next(),
+                content_registry: next(), staker_index: next(),
+            },
+            instruction::CloseKnowledgeEntry {},
+        ),
+        SolSageInstruction::SetRewardRate { reward_per_attribution } => anchor(
+            accounts::SetRewardRate { authority: next(), protocol: next() },
+            instruction::SetRewardRate { reward_per_attribution },
+        ),
+        SolSageInstruction::ProposeAuthorityTransfer { new_authority, authority_type } => anchor(
+            accounts::ProposeAuthorityTransfer { authority: next(), protocol: next() },
+            instruction::ProposeAuthorityTransfer { new_authority, authority_type },
+        ),
+        SolSageInstruction::AcceptAuthorityTransfer => anchor(
+            accounts::AcceptAuthorityTransfer { new_authority: next(), protocol: next() },
+            instruction::AcceptAuthorityTransfer {},
+        ),
+        SolSageInstruction::SetVestingPeriod { vesting_period_secs } => anchor(
+            accounts::SetVestingPeriod { authority: next(), protocol: next() },
+            instruction::SetVestingPeriod { vesting_period_secs },
+        ),
+        SolSageInstruction::GetVestedRewards => anchor(
+            accounts::GetVestedRewards { knowledge_entry: next() },
+            instruction::GetVestedRewards {},
+        ),
+        SolSageInstruction::AddOracle { oracle } => anchor(
+            accounts::AddOracle {
+                authority: next(), protocol: next(), attribution_oracle: next(), system_program: next(),
+            },
+            instruction::AddOracle { oracle },
+        ),
+        SolSageInstruction::RemoveOracle => anchor(
+            accounts::RemoveOracle { authority: next(), protocol: next(), attribution_oracle: next() },
+            instruction::RemoveOracle {},
+        ),
+        SolSageInstruction::RecordAttributionBatch { query_hash, scores } => anchor(
+            accounts::RecordAttributionBatch {
+                payer: next(), protocol: next(), query_record: next(), attribution_oracle: next(),
+                system_program: next(), treasury: next(), leaderboard: next(), oracle_state: next(),
+                payer_stats: next(),
+            },
+            instruction::RecordAttributionBatch { query_hash, scores },
+        ),
+        SolSageInstruction::ClaimAllRewards => anchor(
+            accounts::ClaimAllRewards { claimer: next(), protocol: next() },
+            instruction::ClaimAllRewards {},
+        ),
+        SolSageInstruction::TransferKnowledgeOwnership { new_owner } => anchor(
+            accounts::TransferKnowledgeOwnership {
+                staker: next(), knowledge_entry: next(), staker_profile: next(), system_program: next(),
+                previous_owner_profile: next(),
+            },
+            instruction::TransferKnowledgeOwnership { new_owner },
+        ),
+        SolSageInstruction::SetRewardCurve { reward_curve } => anchor(
+            accounts::SetRewardCurve { authority: next(), protocol: next() },
+            instruction::SetRewardCurve { reward_curve },
+        ),
+        SolSageInstruction::ChallengeAttribution { bond } => anchor(
+            accounts::ChallengeAttribution {
+                challenger: next(), attribution: next(), challenge: next(), system_program: next(),
+            },
+            instruction::ChallengeAttribution { bond },
+        ),
+        SolSageInstruction::ResolveChallenge { uphold } => anchor(
+            accounts::ResolveChallenge {
+                authority: next(), protocol: next(), attribution: next(), knowledge_entry: next(), challenge: next(),
+                challenger: next(), staker: next(),
+            },
+            instruction::ResolveChallenge { uphold },
+        ),
+        SolSageInstruction::SlashKnowledge { slash_bps } => anchor(
+            accounts::SlashKnowledge {
+                authority: next(), protocol: next(), knowledge_entry: next(), stake_escrow: next(), treasury: next(),
+            },
+            instruction::SlashKnowledge { slash_bps },
+        ),
+        SolSageInstruction::SetProtocolFee { fee_bps } => anchor(
+            accounts::SetProtocolFee { authority: next(), protocol: next() },
+            instruction::SetProtocolFee { fee_bps },
+        ),
+        SolSageInstruction::WithdrawTreasury { amount } => anchor(
+            accounts::WithdrawTreasury { authority: next(), protocol: next(), treasury: next(), destination: next() },
+            instruction::WithdrawTreasury { amount },
+        ),
+        SolSageInstruction::CreateCategory { name } => anchor(
+            accounts::CreateCategory { authority: next(), protocol: next(), category: next(), system_program: next() },
+            instruction::CreateCategory { name },
+        ),
+        SolSageInstruction::SetCategoryMultiplier { multiplier_bps } => anchor(
+            accounts::SetCategoryMultiplier { authority: next(), protocol: next(), category: next() },
+            instruction::SetCategoryMultiplier { multiplier_bps },
+        ),
+        SolSageInstruction::RenewKnowledge { content_hash } => anchor(
+            accounts::RenewKnowledge {
+                staker: next(), protocol: next(), knowledge_entry: next(), treasury: next(), system_program: next(),
+            },
+            instruction::RenewKnowledge { content_hash },
+        ),
+        SolSageInstruction::SetClaimDelegate { delegate } => anchor(
+            accounts::SetClaimDelegate { staker: next(), staker_profile: next() },
+            instruction::SetClaimDelegate { delegate },
+        ),
+        SolSageInstruction::SetMinStake { min_stake_lamports } => anchor(
+            accounts::SetMinStake { authority: next(), protocol: next() },
+            instruction::SetMinStake { min_stake_lamports },
+        ),
+        SolSageInstruction::SetAttributionCooldown { attribution_cooldown_secs } => anchor(
+            accounts::SetAttributionCooldown { authority: next(), protocol: next() },
+            instruction::SetAttributionCooldown { attribution_cooldown_secs },
+        ),
+        SolSageInstruction::Pause => anchor(
+            accounts::Pause { authority: next(), protocol: next() },
+            instruction::Pause {},
+        ),
+        SolSageInstruction::Unpause => anchor(
+            accounts::Unpause { authority: next(), protocol: next() },
+            instruction::Unpause {},
+        ),
+        SolSageInstruction::SetScoreDivisor { score_divisor } => anchor(
+            accounts::SetScoreDivisor { authority: next(), protocol: next() },
+            instruction::SetScoreDivisor { score_divisor },
+        ),
+        SolSageInstruction::CommitAttributionRoot { epoch, root, attribution_count } => anchor(
+            accounts::CommitAttributionRoot {
+                oracle: next(), protocol: next(), attribution_oracle: next(), attribution_root: next(),
+                system_program: next(),
+            },
+            instruction::CommitAttributionRoot { epoch, root, attribution_count },
+        ),
+        SolSageInstruction::ClaimAttributedReward { leaf_index, query_hash, relevance_score, proof } => anchor(
+            accounts::ClaimAttributedReward {
+                claimer: next(), protocol: next(), attribution_root: next(), knowledge_entry: next(),
+            },
+            instruction::ClaimAttributedReward { leaf_index, query_hash, relevance_score, proof },
+        ),
+        SolSageInstruction::SetEpochSchedule { epoch_duration_secs, epoch_reward_budget } => anchor(
+            accounts::SetEpochSchedule { authority: next(), protocol: next() },
+            instruction::SetEpochSchedule { epoch_duration_secs, epoch_reward_budget },
+        ),
+        SolSageInstruction::OpenEpoch => anchor(
+            accounts::OpenEpoch { payer: next(), protocol: next(), epoch: next(), system_program: next() },
+            instruction::OpenEpoch {},
+        ),
+        SolSageInstruction::FinalizeEpoch => anchor(
+            accounts::FinalizeEpoch { epoch: next() },
+            instruction::FinalizeEpoch {},
+        ),
+        SolSageInstruction::SetCuratorShare { curator_bps } => anchor(
+            accounts::SetCuratorShare { authority: next(), protocol: next() },
+            instruction::SetCuratorShare { curator_bps },
+        ),
+        SolSageInstruction::ClaimCuratorRewards => anchor(
+            accounts::ClaimCuratorRewards { curator: next(), protocol: next(), curator_account: next() },
+            instruction::ClaimCuratorRewards {},
+        ),
+        SolSageInstruction::CreateCollection { name } => anchor(
+            accounts::CreateCollection { owner: next(), collection: next(), system_program: next() },
+            instruction::CreateCollection { name },
+        ),
+        SolSageInstruction::AddToCollection => anchor(
+            accounts::AddToCollection { owner: next(), collection: next(), knowledge_entry: next() },
+            instruction::AddToCollection {},
+        ),
+        SolSageInstruction::RemoveFromCollection => anchor(
+            accounts::RemoveFromCollection { signer: next(), collection: next(), knowledge_entry: next() },
+            instruction::RemoveFromCollection {},
+        ),
+        SolSageInstruction::SetRoyaltySplit { splits } => anchor(
+            accounts::SetRoyaltySplit { staker: next(), knowledge_entry: next(), system_program: next() },
+            instruction::SetRoyaltySplit { splits },
+        ),
+        SolSageInstruction::UpdateLicense { license } => anchor(
+            accounts::UpdateLicense { staker: next(), knowledge_entry: next(), system_program: next() },
+            instruction::UpdateLicense { license },
+        ),
+        SolSageInstruction::UpdateContentUri { content_uri } => anchor(
+            accounts::UpdateContentUri { staker: next(), knowledge_entry: next(), system_program: next() },
+            instruction::UpdateContentUri { content_uri },
+        ),
+        SolSageInstruction::MigrateAccount { account_type } => anchor(
+            accounts::MigrateAccount { authority: next(), protocol: next(), account: next(), system_program: next() },
+            instruction::MigrateAccount { account_type },
+        ),
+        SolSageInstruction::AddTag { tag } => anchor(
+            accounts::AddTag { staker: next(), knowledge_entry: next(), tag_index: next(), system_program: next() },
+            instruction::AddTag { tag },
+        ),
+        SolSageInstruction::RemoveTag { tag } => anchor(
+            accounts::RemoveTag { staker: next(), knowledge_entry: next(), tag_index: next(), system_program: next() },
+            instruction::RemoveTag { tag },
+        ),
+        SolSageInstruction::SetRewardSource { reward_source } => anchor(
+            accounts::SetRewardSource {
+                authority: next(), protocol: next(), reward_vault: next(), sage_mint: next(), token_program: next(),
+                system_program: next(),
+            },
+            instruction::SetRewardSource { reward_source },
+        ),
+        SolSageInstruction::TopUpSubscription { amount } => anchor(
+            accounts::TopUpSubscription {
+                consumer: next(), protocol: next(), subscription: next(), consumer_token_account: next(),
+                reward_vault: next(), token_program: next(), system_program: next(),
+            },
+            instruction::TopUpSubscription { amount },
+        ),
+        SolSageInstruction::CloseSubscription => anchor(
+            accounts::CloseSubscription {
+                consumer: next(), protocol: next(), subscription: next(), destination: next(), reward_vault: next(),
+                mint_authority: next(), token_program: next(),
+            },
+            instruction::CloseSubscription {},
+        ),
+        SolSageInstruction::SetPriceFloor { min_reward_per_use } => anchor(
+            accounts::SetPriceFloor { staker: next(), knowledge_entry: next(), system_program: next() },
+            instruction::SetPriceFloor { min_reward_per_use },
+        ),
+        SolSageInstruction::RebuildLeaderboard => anchor(
+            accounts::RebuildLeaderboard { payer: next(), leaderboard: next(), system_program: next() },
+            instruction::RebuildLeaderboard {},
+        ),
+        SolSageInstruction::SetGovernanceConfig {
+            quorum_votes,
+            approval_threshold_bps,
+            voting_period_secs,
+            execution_delay_secs,
+        } => anchor(
+            accounts::SetGovernanceConfig {
+                authority: next(), protocol: next(), governance_vault: next(), sage_mint: next(), token_program: next(),
+                system_program: next(),
+            },
+            instruction::SetGovernanceConfig {
+                quorum_votes, approval_threshold_bps, voting_period_secs, execution_delay_secs,
+            },
+        ),
+        SolSageInstruction::CreateProposal { action } => anchor(
+            accounts::CreateProposal { proposer: next(), protocol: next(), proposal: next(), system_program: next() },
+            instruction::CreateProposal { action },
+        ),
+        SolSageInstruction::CastVote { approve, weight } => anchor(
+            accounts::CastVote {
+                voter: next(), protocol: next(), proposal: next(), vote_record: next(), voter_token_account: next(),
+                governance_vault: next(), token_program: next(), system_program: next(),
+            },
+            instruction::CastVote { approve, weight },
+        ),
+        SolSageInstruction::ExecuteProposal => anchor(
+            accounts::ExecuteProposal { executor: next(), protocol: next(), proposal: next() },
+            instruction::ExecuteProposal {},
+        ),
+        SolSageInstruction::WithdrawVote => anchor(
+            accounts::WithdrawVote {
+                voter: next(), protocol: next(), proposal: next(), vote_record: next(), destination: next(),
+                governance_vault: next(), mint_authority: next(), token_program: next(),
+            },
+            instruction::WithdrawVote {},
+        ),
+        SolSageInstruction::QueueParameterChange { change } => anchor(
+            accounts::QueueParameterChange { authority: next(), protocol: next() },
+            instruction::QueueParameterChange { change },
+        ),
+        SolSageInstruction::CancelParameterChange => anchor(
+            accounts::CancelParameterChange { authority: next(), protocol: next() },
+            instruction::CancelParameterChange {},
+        ),
+        SolSageInstruction::ExecuteParameterChange => anchor(
+            accounts::ExecuteParameterChange { executor: next(), protocol: next() },
+            instruction::ExecuteParameterChange {},
+        ),
+        SolSageInstruction::SetScoreDecay { score_half_life_secs, min_score_weight_bps } => anchor(
+            accounts::SetScoreDecay { authority: next(), protocol: next() },
+            instruction::SetScoreDecay { score_half_life_secs, min_score_weight_bps },
+        ),
+        SolSageInstruction::SetAttributionVerifier { verifier } => anchor(
+            accounts::SetAttributionVerifier { authority: next(), protocol: next() },
+            instruction::SetAttributionVerifier { verifier },
+        ),
+        SolSageInstruction::RecordSignedAttribution {
+            query_hash,
+            relevance_score,
+            curator,
+            agent_id,
+            model_id,
+            nonce,
+        } => anchor(
+            accounts::RecordSignedAttribution {
+                payer: next(), protocol: next(), knowledge_entry: next(), attribution: next(), query_record: next(),
+                attribution_oracle: next(), system_program: next(), staker_profile: next(), treasury: next(),
+                category: next(), leaderboard: next(), oracle_state: next(), payer_stats: next(),
+            },
+            instruction::RecordSignedAttribution { query_hash, relevance_score, curator, agent_id, model_id, nonce },
+        ),
+        SolSageInstruction::SetClaimWindow { claim_window_secs } => anchor(
+            accounts::SetClaimWindow { authority: next(), protocol: next() },
+            instruction::SetClaimWindow { claim_window_secs },
+        ),
+        SolSageInstruction::ExpireRewards => anchor(
+            accounts::ExpireRewards { caller: next(), protocol: next(), knowledge_entry: next() },
+            instruction::ExpireRewards {},
+        ),
+        SolSageInstruction::RequestUnstake => anchor(
+            accounts::RequestUnstake { staker: next(), knowledge_entry: next(), system_program: next() },
+            instruction::RequestUnstake {},
+        ),
+        SolSageInstruction::TakeSnapshot => anchor(
+            accounts::TakeSnapshot { payer: next(), protocol: next(), snapshot: next(), system_program: next() },
+            instruction::TakeSnapshot {},
+        ),
+        SolSageInstruction::SetReceiptTree { merkle_tree } => anchor(
+            accounts::SetReceiptTree { authority: next(), protocol: next() },
+            instruction::SetReceiptTree { merkle_tree },
+        ),
+        SolSageInstruction::AddBridgeEmitter { bridge, emitter_chain, emitter_address } => anchor(
+            accounts::AddBridgeEmitter {
+                authority: next(), protocol: next(), bridge_emitter: next(), attribution_oracle: next(),
+                system_program: next(),
+            },
+            instruction::AddBridgeEmitter { bridge, emitter_chain, emitter_address },
+        ),
+        SolSageInstruction::RecordBridgedAttribution {
+            query_hash,
+            relevance_score,
+            curator,
+            agent_id,
+            model_id,
+        } => anchor(
+            accounts::RecordBridgedAttribution {
+                payer: next(), protocol: next(), knowledge_entry: next(), attribution: next(), query_record: next(),
+                attribution_oracle: next(), system_program: next(), staker_profile: next(), treasury: next(),
+                category: next(), leaderboard: next(), oracle_state: next(), payer_stats: next(),
+            },
+            instruction::RecordBridgedAttribution { query_hash, relevance_score, curator, agent_id, model_id },
+        ),
+        SolSageInstruction::SetMintMetadata { name, symbol, uri } => anchor(
+            accounts::SetMintMetadata {
+                authority: next(), protocol: next(), sage_mint: next(), mint_authority: next(), metadata: next(),
+                token_metadata_program: next(), system_program: next(),
+            },
+            instruction::SetMintMetadata { name, symbol, uri },
+        ),
+        SolSageInstruction::ListEntryForSale { price } => anchor(
+            accounts::ListEntryForSale {
+                staker: next(), knowledge_entry: next(), listing: next(), system_program: next(),
+            },
+            instruction::ListEntryForSale { price },
+        ),
+        SolSageInstruction::CancelListing => anchor(
+            accounts::CancelListing { signer: next(), knowledge_entry: next(), listing: next(), seller: next() },
+            instruction::CancelListing {},
+        ),
+        SolSageInstruction::BuyEntry { price } => anchor(
+            accounts::BuyEntry {
+                buyer: next(), protocol: next(), knowledge_entry: next(), listing: next(), seller: next(),
+                treasury: next(), staker_profile: next(), system_program: next(), seller_profile: next(),
+            },
+            instruction::BuyEntry { price },
+        ),
+        SolSageInstruction::SetSaleFee { sale_fee_bps } => anchor(
+            accounts::SetSaleFee { authority: next(), protocol: next() },
+            instruction::SetSaleFee { sale_fee_bps },
+        ),
+        SolSageInstruction::SetOracleBudget { oracle, max_attributions_per_epoch, max_rewards_per_epoch } => anchor(
+            accounts::SetOracleBudget {
+                authority: next(), protocol: next(), oracle_state: next(), system_program: next(),
+            },
+            instruction::SetOracleBudget { oracle, max_attributions_per_epoch, max_rewards_per_epoch },
+        ),
+        SolSageInstruction::SetConsensus { consensus_threshold, consensus_tolerance } => anchor(
+            accounts::SetConsensus { authority: next(), protocol: next() },
+            instruction::SetConsensus { consensus_threshold, consensus_tolerance },
+        ),
+        SolSageInstruction::SetAggregationWindow { aggregation_window_secs } => anchor(
+            accounts::SetAggregationWindow { authority: next(), protocol: next() },
+            instruction::SetAggregationWindow { aggregation_window_secs },
+        ),
+        SolSageInstruction::FinalizeAttribution { query_hash } => anchor(
+            accounts::FinalizeAttribution {
+                payer: next(), protocol: next(), knowledge_entry: next(), attribution: next(), query_record: next(),
+                system_program: next(), staker_profile: next(), treasury: next(), category: next(), leaderboard: next(),
+            },
+            instruction::FinalizeAttribution { query_hash },
+        ),
+        SolSageInstruction::SetPayerAttributionCap { max_payer_attributions_per_day } => anchor(
+            accounts::SetPayerAttributionCap { authority: next(), protocol: next() },
+            instruction::SetPayerAttributionCap { max_payer_attributions_per_day },
+        ),
+        SolSageInstruction::UpvoteEntry { weight } => anchor(
+            accounts::UpvoteEntry {
+                voter: next(), protocol: next(), knowledge_entry: next(), entry_vote: next(),
+                voter_token_account: next(), governance_vault: next(), token_program: next(), system_program: next(),
+            },
+            instruction::UpvoteEntry { weight },
+        ),
+        SolSageInstruction::DownvoteEntry { weight } => anchor(
+            accounts::DownvoteEntry {
+                voter: next(), protocol: next(), knowledge_entry: next(), entry_vote: next(),
+                voter_token_account: next(), governance_vault: next(), token_program: next(), system_program: next(),
+            },
+            instruction::DownvoteEntry { weight },
+        ),
+        SolSageInstruction::WithdrawEntryVote => anchor(
+            accounts::WithdrawEntryVote {
+                voter: next(), protocol: next(), knowledge_entry: next(), entry_vote: next(), destination: next(),
+                governance_vault: next(), mint_authority: next(), token_program: next(),
+            },
+            instruction::WithdrawEntryVote {},
+        ),
+        SolSageInstruction::ReportEntry { bond } => anchor(
+            accounts::ReportEntry {
+                reporter: next(), knowledge_entry: next(), report_queue: next(), system_program: next(),
+            },
+            instruction::ReportEntry { bond },
+        ),
+        SolSageInstruction::ResolveReports { uphold } => anchor(
+            accounts::ResolveReports {
+                authority: next(), protocol: next(), knowledge_entry: next(), report_queue: next(), staker: next(),
+            },
+            instruction::ResolveReports { uphold },
+        ),
+        SolSageInstruction::CreateBounty { query_hash, reward, deadline } => anchor(
+            accounts::CreateBounty {
+                requester: next(), protocol: next(), bounty: next(), bounty_vault: next(),
+                requester_token_account: next(), sage_mint: next(), token_program: next(), system_program: next(),
+            },
+            instruction::CreateBounty { query_hash, reward, deadline },
+        ),
+        SolSageInstruction::SubmitAnswer => anchor(
+            accounts::SubmitAnswer {
+                staker: next(), bounty: next(), knowledge_entry: next(), answer: next(), system_program: next(),
+            },
+            instruction::SubmitAnswer {},
+        ),
+        SolSageInstruction::AcceptAnswer => anchor(
+            accounts::AcceptAnswer {
+                requester: next(), protocol: next(), bounty: next(), answer: next(), bounty_vault: next(),
+                destination: next(), mint_authority: next(), token_program: next(),
+            },
+            instruction::AcceptAnswer {},
+        ),
+        SolSageInstruction::ReclaimExpiredBounty => anchor(
+            accounts::ReclaimExpiredBounty {
+                requester: next(), protocol: next(), bounty: next(), bounty_vault: next(), destination: next(),
+                mint_authority: next(), token_program: next(),
+            },
+            instruction::ReclaimExpiredBounty {},
+        ),
+        SolSageInstruction::SetRewardStream { reward_stream_secs } => anchor(
+            accounts::SetRewardStream { authority: next(), protocol: next() },
+            instruction::SetRewardStream { reward_stream_secs },
+        ),
+        SolSageInstruction::SetFeeConfig { fee_config } => anchor(
+            accounts::SetFeeConfig { authority: next(), protocol: next() },
+            instruction::SetFeeConfig { fee_config },
+        ),
+        SolSageInstruction::ClaimTreasuryRewards => anchor(
+            accounts::ClaimTreasuryRewards { authority: next(), protocol: next() },
+            instruction::ClaimTreasuryRewards {},
+        ),
+        SolSageInstruction::SetLowRelevanceBurn { low_relevance_threshold, low_relevance_burn_bps } => anchor(
+            accounts::SetLowRelevanceBurn { authority: next(), protocol: next() },
+            instruction::SetLowRelevanceBurn { low_relevance_threshold, low_relevance_burn_bps },
+        ),
+        SolSageInstruction::BurnRewards => anchor(
+            accounts::BurnRewards {
+                protocol: next(), reward_vault: next(), sage_mint: next(), mint_authority: next(),
+                token_program: next(),
+            },
+            instruction::BurnRewards {},
+        ),
+        SolSageInstruction::SetCitationRoyalty { citation_royalty_bps } => anchor(
+            accounts::SetCitationRoyalty { authority: next(), protocol: next() },
+            instruction::SetCitationRoyalty { citation_royalty_bps },
+        ),
+        SolSageInstruction::PayCitationRoyalties => anchor(
+            accounts::PayCitationRoyalties { knowledge_entry: next() },
+            instruction::PayCitationRoyalties {},
+        ),
+        SolSageInstruction::StakeDocument { content_hash, title, chunk_count } => anchor(
+            accounts::StakeDocument { owner: next(), document: next(), category: next(), system_program: next() },
+            instruction::StakeDocument { content_hash, title, chunk_count },
+        ),
+        SolSageInstruction::AddDocumentChunk { chunk_index } => anchor(
+            accounts::AddDocumentChunk { owner: next(), document: next(), knowledge_entry: next() },
+            instruction::AddDocumentChunk { chunk_index },
+        ),
+        SolSageInstruction::RefreshDocument => anchor(
+            accounts::RefreshDocument { document: next() },
+            instruction::RefreshDocument {},
+        ),
+        SolSageInstruction::UpdateEmbeddingFingerprint { embedding_fingerprint } => anchor(
+            accounts::UpdateEmbeddingFingerprint { staker: next(), knowledge_entry: next(), system_program: next() },
+            instruction::UpdateEmbeddingFingerprint { embedding_fingerprint },
+        ),
+        SolSageInstruction::AddReviewer { reviewer } => anchor(
+            accounts::AddReviewer {
+                authority: next(), protocol: next(), reviewer_registry: next(), system_program: next(),
+            },
+            instruction::AddReviewer { reviewer },
+        ),
+        SolSageInstruction::RemoveReviewer { reviewer } => anchor(
+            accounts::RemoveReviewer { authority: next(), protocol: next(), reviewer_registry: next() },
+            instruction::RemoveReviewer { reviewer },
+        ),
+        SolSageInstruction::AttestEntry { accuracy, freshness } => anchor(
+            accounts::AttestEntry {
+                reviewer: next(), reviewer_registry: next(), knowledge_entry: next(), system_program: next(),
+            },
+            instruction::AttestEntry { accuracy, freshness },
+        ),
+        SolSageInstruction::SetIdentityConfig { identity_config } => anchor(
+            accounts::SetIdentityConfig { authority: next(), protocol: next() },
+            instruction::SetIdentityConfig { identity_config },
+        ),
+        SolSageInstruction::SetEntryVisibility { visibility } => anchor(
+            accounts::SetEntryVisibility { staker: next(), knowledge_entry: next(), system_program: next() },
+            instruction::SetEntryVisibility { visibility },
+        ),
+        SolSageInstruction::GrantAccess { holder } => anchor(
+            accounts::GrantAccess {
+                staker: next(), knowledge_entry: next(), access_pass: next(), system_program: next(),
+            },
+            instruction::GrantAccess { holder },
+        ),
+        SolSageInstruction::BuyAccess { price } => anchor(
+            accounts::BuyAccess {
+                buyer: next(), knowledge_entry: next(), owner: next(), access_pass: next(), system_program: next(),
+            },
+            instruction::BuyAccess { price },
+        ),
+    };
+    metas.extend(native.accounts[metas.len()..].iter().cloned());
+    Instruction { program_id: ID, accounts: metas, data }
+}
+
+#[test]
+fn anchor_clients_build_the_instructions_native_clients_do() {
+    let instructions = native_instructions();
+    for native in &instructions {
+        assert_eq!(from_native(native.clone()).unwrap(), anchor_client(native), "{:?}", native.data[0]);
+    }
+
+    let tags: BTreeSet<u8> = instructions.iter().map(|ix| ix.data[0]).collect();
+    let variants = SolSageInstruction::BuyAccess { price: 0 }.try_to_vec().unwrap()[0] as usize + 1;
+    assert_eq!(tags.len(), variants, "every instruction is covered");
+}
+
+#[test]
+fn anchor_discriminators_are_distinct() {
+    let discriminators: BTreeSet<[u8; 8]> =
+        native_instructions().into_iter().map(|ix| from_native(ix).unwrap().data[..8].try_into().unwrap()).collect();
+    let tags: BTreeSet<u8> = native_instructions().iter().map(|ix| ix.data[0]).collect();
+    assert_eq!(discriminators.len(), tags.len());
+}
@@ -1,8 +1,12 @@
+use std::cell::RefMut;
+use std::io::{self, Read};
+
 use borsh::{BorshDeserialize, BorshSerialize};
+use bytemuck::{Pod, Zeroable};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
-    entrypoint,
     entrypoint::ProgramResult,
+    log::sol_log_data,
     msg,
     program_error::ProgramError,
     pubkey::Pubkey,
@@ -10,10 +14,16 @@ use solana_program::{
     sysvar::Sysvar,
     rent::Rent,
     system_instruction,
-    program::invoke_signed,
+    system_program,
+    program::{invoke, invoke_signed, set_return_data},
+    program_pack::Pack,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
 };
+use solsage_core::{seeds, syscalls, token_metadata::TokenMetadata};
 use thiserror::Error;
 
+pub use solsage_core::{bridge, bubblegum, hashing, identity, merkle, receipts, token_metadata, LockupTier, RewardCurve, KNOWLEDGE_ENTRY_SCHEMA_VERSION, LEADERBOARD_SCHEMA_VERSION, SCHEMA_VERSION};
+
 // Program ID placeholder - will be replaced after deployment
 solana_program::declare_id!("11111111111111111111111111111111");
 
@@ -21,7 +31,8 @@ solana_program::declare_id!("11111111111111111111111111111111");
 // ENTRYPOINT
 // ============================================================================
 
-entrypoint!(process_instruction);
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
 
 pub fn process_instruction(
     program_id: &Pubkey,
@@ -30,24 +41,513 @@ pub fn process_instruction(
 ) -> ProgramResult {
     let instruction = SolSageInstruction::try_from_slice(instruction_data)
         .map_err(|_| ProgramError::InvalidInstructionData)?;
+    process(program_id, accounts, instruction)
+}
 
+/// Carry out a decoded instruction. The Anchor build decodes its own
+/// instruction data and hands it here too, so both builds share every
+/// account layout and handler.
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], instruction: SolSageInstruction) -> ProgramResult {
     match instruction {
         SolSageInstruction::Initialize => {
             msg!("Instruction: Initialize");
             process_initialize(program_id, accounts)
         }
-        SolSageInstruction::StakeKnowledge { content_hash, title, category } => {
+        SolSageInstruction::StakeKnowledge {
+            entry_index,
+            content_hash,
+            title,
+            stake_amount,
+            license,
+            lockup,
+            mint_receipt,
+            derived_from,
+            chunks,
+            identity,
+        } => {
             msg!("Instruction: StakeKnowledge");
-            process_stake_knowledge(program_id, accounts, content_hash, title, category)
+            let terms = StakeTerms { stake_amount, lockup, mint_receipt, derived_from, chunks, identity };
+            process_stake_knowledge(program_id, accounts, entry_index, content_hash, title, terms, license)
         }
-        SolSageInstruction::RecordAttribution { query_hash, relevance_score } => {
+        SolSageInstruction::RecordAttribution { query_hash, relevance_score, curator, agent_id, model_id, chunk } => {
             msg!("Instruction: RecordAttribution");
-            process_record_attribution(program_id, accounts, query_hash, relevance_score)
+            process_record_attribution(
+                program_id,
+                accounts,
+                query_hash,
+                relevance_score,
+                curator,
+                AttributionMetadata { agent_id, model_id, chunk },
+                None,
+            )
         }
         SolSageInstruction::ClaimRewards => {
             msg!("Instruction: ClaimRewards");
             process_claim_rewards(program_id, accounts)
         }
+        SolSageInstruction::InitializeMint { max_supply } => {
+            msg!("Instruction: InitializeMint");
+            process_initialize_mint(program_id, accounts, max_supply)
+        }
+        SolSageInstruction::CompleteUnstake => {
+            msg!("Instruction: CompleteUnstake");
+            process_complete_unstake(program_id, accounts)
+        }
+        SolSageInstruction::UpdateKnowledge { title, content_hash } => {
+            msg!("Instruction: UpdateKnowledge");
+            process_update_knowledge(program_id, accounts, title, content_hash)
+        }
+        SolSageInstruction::DeactivateKnowledge => {
+            msg!("Instruction: DeactivateKnowledge");
+            process_set_knowledge_active(program_id, accounts, false)
+        }
+        SolSageInstruction::ReactivateKnowledge => {
+            msg!("Instruction: ReactivateKnowledge");
+            process_set_knowledge_active(program_id, accounts, true)
+        }
+        SolSageInstruction::CloseKnowledgeEntry => {
+            msg!("Instruction: CloseKnowledgeEntry");
+            process_close_knowledge_entry(program_id, accounts)
+        }
+        SolSageInstruction::SetRewardRate { reward_per_attribution } => {
+            msg!("Instruction: SetRewardRate");
+            process_set_reward_rate(program_id, accounts, reward_per_attribution)
+        }
+        SolSageInstruction::ProposeAuthorityTransfer { new_authority, authority_type } => {
+            msg!("Instruction: ProposeAuthorityTransfer");
+            process_propose_authority_transfer(program_id, accounts, new_authority, authority_type)
+        }
+        SolSageInstruction::AcceptAuthorityTransfer => {
+            msg!("Instruction: AcceptAuthorityTransfer");
+            process_accept_authority_transfer(program_id, accounts)
+        }
+        SolSageInstruction::SetVestingPeriod { vesting_period_secs } => {
+            msg!("Instruction: SetVestingPeriod");
+            process_set_vesting_period(program_id, accounts, vesting_period_secs)
+        }
+        SolSageInstruction::GetVestedRewards => {
+            msg!("Instruction: GetVestedRewards");
+            process_get_vested_rewards(program_id, accounts)
+        }
+        SolSageInstruction::AddOracle { oracle } => {
+            msg!("Instruction: AddOracle");
+            process_add_oracle(program_id, accounts, oracle)
+        }
+        SolSageInstruction::RemoveOracle => {
+            msg!("Instruction: RemoveOracle");
+            process_remove_oracle(program_id, accounts)
+        }
+        SolSageInstruction::RecordAttributionBatch { query_hash, scores } => {
+            msg!("Instruction: RecordAttributionBatch");
+            process_record_attribution_batch(program_id, accounts, query_hash, scores)
+        }
+        SolSageInstruction::ClaimAllRewards => {
+            msg!("Instruction: ClaimAllRewards");
+            process_claim_all_rewards(program_id, accounts)
+        }
+        SolSageInstruction::TransferKnowledgeOwnership { new_owner } => {
+            msg!("Instruction: TransferKnowledgeOwnership");
+            process_transfer_knowledge_ownership(program_id, accounts, new_owner)
+        }
+        SolSageInstruction::SetRewardCurve { reward_curve } => {
+            msg!("Instruction: SetRewardCurve");
+            process_set_reward_curve(program_id, accounts, reward_curve)
+        }
+        SolSageInstruction::ChallengeAttribution { bond } => {
+            msg!("Instruction: ChallengeAttribution");
+            process_challenge_attribution(program_id, accounts, bond)
+        }
+        SolSageInstruction::ResolveChallenge { uphold } => {
+            msg!("Instruction: ResolveChallenge");
+            process_resolve_challenge(program_id, accounts, uphold)
+        }
+        SolSageInstruction::SlashKnowledge { slash_bps } => {
+            msg!("Instruction: SlashKnowledge");
+            process_slash_knowledge(program_id, accounts, slash_bps)
+        }
+        SolSageInstruction::SetProtocolFee { fee_bps } => {
+            msg!("Instruction: SetProtocolFee");
+            process_set_protocol_fee(program_id, accounts, fee_bps)
+        }
+        SolSageInstruction::WithdrawTreasury { amount } => {
+            msg!("Instruction: WithdrawTreasury");
+            process_withdraw_treasury(program_id, accounts, amount)
+        }
+        SolSageInstruction::CreateCategory { name } => {
+            msg!("Instruction: CreateCategory");
+            process_create_category(program_id, accounts, name)
+        }
+        SolSageInstruction::SetCategoryMultiplier { multiplier_bps } => {
+            msg!("Instruction: SetCategoryMultiplier");
+            process_set_category_multiplier(program_id, accounts, multiplier_bps)
+        }
+        SolSageInstruction::RenewKnowledge { content_hash } => {
+            msg!("Instruction: RenewKnowledge");
+            process_renew_knowledge(program_id, accounts, content_hash)
+        }
+        SolSageInstruction::SetClaimDelegate { delegate } => {
+            msg!("Instruction: SetClaimDelegate");
+            process_set_claim_delegate(program_id, accounts, delegate)
+        }
+        SolSageInstruction::SetMinStake { min_stake_lamports } => {
+            msg!("Instruction: SetMinStake");
+            process_set_min_stake(program_id, accounts, min_stake_lamports)
+        }
+        SolSageInstruction::SetAttributionCooldown { attribution_cooldown_secs } => {
+            msg!("Instruction: SetAttributionCooldown");
+            process_set_attribution_cooldown(program_id, accounts, attribution_cooldown_secs)
+        }
+        SolSageInstruction::Pause => {
+            msg!("Instruction: Pause");
+            process_set_paused(program_id, accounts, true)
+        }
+        SolSageInstruction::Unpause => {
+            msg!("Instruction: Unpause");
+            process_set_paused(program_id, accounts, false)
+        }
+        SolSageInstruction::SetScoreDivisor { score_divisor } => {
+            msg!("Instruction: SetScoreDivisor");
+            process_set_score_divisor(program_id, accounts, score_divisor)
+        }
+        SolSageInstruction::CommitAttributionRoot { epoch, root, attribution_count } => {
+            msg!("Instruction: CommitAttributionRoot");
+            process_commit_attribution_root(program_id, accounts, epoch, root, attribution_count)
+        }
+        SolSageInstruction::ClaimAttributedReward { leaf_index, query_hash, relevance_score, proof } => {
+            msg!("Instruction: ClaimAttributedReward");
+            process_claim_attributed_reward(program_id, accounts, leaf_index, query_hash, relevance_score, proof)
+        }
+        SolSageInstruction::SetEpochSchedule { epoch_duration_secs, epoch_reward_budget } => {
+            msg!("Instruction: SetEpochSchedule");
+            process_set_epoch_schedule(program_id, accounts, epoch_duration_secs, epoch_reward_budget)
+        }
+        SolSageInstruction::OpenEpoch => {
+            msg!("Instruction: OpenEpoch");
+            process_open_epoch(program_id, accounts)
+        }
+        SolSageInstruction::FinalizeEpoch => {
+            msg!("Instruction: FinalizeEpoch");
+            process_finalize_epoch(program_id, accounts)
+        }
+        SolSageInstruction::SetCuratorShare { curator_bps } => {
+            msg!("Instruction: SetCuratorShare");
+            process_set_curator_share(program_id, accounts, curator_bps)
+        }
+        SolSageInstruction::ClaimCuratorRewards => {
+            msg!("Instruction: ClaimCuratorRewards");
+            process_claim_curator_rewards(program_id, accounts)
+        }
+        SolSageInstruction::CreateCollection { name } => {
+            msg!("Instruction: CreateCollection");
+            process_create_collection(program_id, accounts, name)
+        }
+        SolSageInstruction::AddToCollection => {
+            msg!("Instruction: AddToCollection");
+            process_add_to_collection(program_id, accounts)
+        }
+        SolSageInstruction::RemoveFromCollection => {
+            msg!("Instruction: RemoveFromCollection");
+            process_remove_from_collection(program_id, accounts)
+        }
+        SolSageInstruction::SetRoyaltySplit { splits } => {
+            msg!("Instruction: SetRoyaltySplit");
+            process_set_royalty_split(program_id, accounts, splits)
+        }
+        SolSageInstruction::UpdateLicense { license } => {
+            msg!("Instruction: UpdateLicense");
+            process_update_license(program_id, accounts, license)
+        }
+        SolSageInstruction::UpdateContentUri { content_uri } => {
+            msg!("Instruction: UpdateContentUri");
+            process_update_content_uri(program_id, accounts, content_uri)
+        }
+        SolSageInstruction::MigrateAccount { account_type } => {
+            msg!("Instruction: MigrateAccount");
+            process_migrate_account(program_id, accounts, account_type)
+        }
+        SolSageInstruction::AddTag { tag } => {
+            msg!("Instruction: AddTag");
+            process_add_tag(program_id, accounts, tag)
+        }
+        SolSageInstruction::RemoveTag { tag } => {
+            msg!("Instruction: RemoveTag");
+            process_remove_tag(program_id, accounts, tag)
+        }
+        SolSageInstruction::SetRewardSource { reward_source } => {
+            msg!("Instruction: SetRewardSource");
+            process_set_reward_source(program_id, accounts, reward_source)
+        }
+        SolSageInstruction::TopUpSubscription { amount } => {
+            msg!("Instruction: TopUpSubscription");
+            process_top_up_subscription(program_id, accounts, amount)
+        }
+        SolSageInstruction::CloseSubscription => {
+            msg!("Instruction: CloseSubscription");
+            process_close_subscription(program_id, accounts)
+        }
+        SolSageInstruction::SetPriceFloor { min_reward_per_use } => {
+            msg!("Instruction: SetPriceFloor");
+            process_set_price_floor(program_id, accounts, min_reward_per_use)
+        }
+        SolSageInstruction::RebuildLeaderboard => {
+            msg!("Instruction: RebuildLeaderboard");
+            process_rebuild_leaderboard(program_id, accounts)
+        }
+        SolSageInstruction::SetGovernanceConfig {
+            quorum_votes,
+            approval_threshold_bps,
+            voting_period_secs,
+            execution_delay_secs,
+        } => {
+            msg!("Instruction: SetGovernanceConfig");
+            process_set_governance_config(
+                program_id,
+                accounts,
+                quorum_votes,
+                approval_threshold_bps,
+                voting_period_secs,
+                execution_delay_secs,
+            )
+        }
+        SolSageInstruction::CreateProposal { action } => {
+            msg!("Instruction: CreateProposal");
+            process_create_proposal(program_id, accounts, action)
+        }
+        SolSageInstruction::CastVote { approve, weight } => {
+            msg!("Instruction: CastVote");
+            process_cast_vote(program_id, accounts, approve, weight)
+        }
+        SolSageInstruction::ExecuteProposal => {
+            msg!("Instruction: ExecuteProposal");
+            process_execute_proposal(program_id, accounts)
+        }
+        SolSageInstruction::WithdrawVote => {
+            msg!("Instruction: WithdrawVote");
+            process_withdraw_vote(program_id, accounts)
+        }
+        SolSageInstruction::QueueParameterChange { change } => {
+            msg!("Instruction: QueueParameterChange");
+            process_queue_parameter_change(program_id, accounts, change)
+        }
+        SolSageInstruction::CancelParameterChange => {
+            msg!("Instruction: CancelParameterChange");
+            process_cancel_parameter_change(program_id, accounts)
+        }
+        SolSageInstruction::ExecuteParameterChange => {
+            msg!("Instruction: ExecuteParameterChange");
+            process_execute_parameter_change(program_id, accounts)
+        }
+        SolSageInstruction::SetScoreDecay { score_half_life_secs, min_score_weight_bps } => {
+            msg!("Instruction: SetScoreDecay");
+            process_set_score_decay(program_id, accounts, score_half_life_secs, min_score_weight_bps)
+        }
+        SolSageInstruction::SetAttributionVerifier { verifier } => {
+            msg!("Instruction: SetAttributionVerifier");
+            process_set_attribution_verifier(program_id, accounts, verifier)
+        }
+        SolSageInstruction::RecordSignedAttribution {
+            query_hash,
+            relevance_score,
+            curator,
+            agent_id,
+            model_id,
+            nonce,
+        } => {
+            msg!("Instruction: RecordSignedAttribution");
+            process_record_attribution(
+                program_id,
+                accounts,
+                query_hash,
+                relevance_score,
+                curator,
+                AttributionMetadata { agent_id, model_id, chunk: None },
+                Some(Attestation::Receipt { nonce }),
+            )
+        }
+        SolSageInstruction::SetClaimWindow { claim_window_secs } => {
+            msg!("Instruction: SetClaimWindow");
+            process_set_claim_window(program_id, accounts, claim_window_secs)
+        }
+        SolSageInstruction::ExpireRewards => {
+            msg!("Instruction: ExpireRewards");
+            process_expire_rewards(program_id, accounts)
+        }
+        SolSageInstruction::RequestUnstake => {
+            msg!("Instruction: RequestUnstake");
+            process_request_unstake(program_id, accounts)
+        }
+        SolSageInstruction::TakeSnapshot => {
+            msg!("Instruction: TakeSnapshot");
+            process_take_snapshot(program_id, accounts)
+        }
+        SolSageInstruction::SetReceiptTree { merkle_tree } => {
+            msg!("Instruction: SetReceiptTree");
+            process_set_receipt_tree(program_id, accounts, merkle_tree)
+        }
+        SolSageInstruction::AddBridgeEmitter { bridge, emitter_chain, emitter_address } => {
+            msg!("Instruction: AddBridgeEmitter");
+            process_add_bridge_emitter(program_id, accounts, bridge, emitter_chain, emitter_address)
+        }
+        SolSageInstruction::RecordBridgedAttribution { query_hash, relevance_score, curator, agent_id, model_id } => {
+            msg!("Instruction: RecordBridgedAttribution");
+            process_record_attribution(
+                program_id,
+                accounts,
+                query_hash,
+                relevance_score,
+                curator,
+                AttributionMetadata { agent_id, model_id, chunk: None },
+                Some(Attestation::Bridged),
+            )
+        }
+        SolSageInstruction::SetMintMetadata { name, symbol, uri } => {
+            msg!("Instruction: SetMintMetadata");
+            process_set_mint_metadata(program_id, accounts, TokenMetadata { name: &name, symbol: &symbol, uri: &uri })
+        }
+        SolSageInstruction::ListEntryForSale { price } => {
+            msg!("Instruction: ListEntryForSale");
+            process_list_entry_for_sale(program_id, accounts, price)
+        }
+        SolSageInstruction::CancelListing => {
+            msg!("Instruction: CancelListing");
+            process_cancel_listing(program_id, accounts)
+        }
+        SolSageInstruction::BuyEntry { price } => {
+            msg!("Instruction: BuyEntry");
+            process_buy_entry(program_id, accounts, price)
+        }
+        SolSageInstruction::SetSaleFee { sale_fee_bps } => {
+            msg!("Instruction: SetSaleFee");
+            process_set_sale_fee(program_id, accounts, sale_fee_bps)
+        }
+        SolSageInstruction::SetOracleBudget { oracle, max_attributions_per_epoch, max_rewards_per_epoch } => {
+            msg!("Instruction: SetOracleBudget");
+            process_set_oracle_budget(program_id, accounts, oracle, max_attributions_per_epoch, max_rewards_per_epoch)
+        }
+        SolSageInstruction::SetConsensus { consensus_threshold, consensus_tolerance } => {
+            msg!("Instruction: SetConsensus");
+            process_set_consensus(program_id, accounts, consensus_threshold, consensus_tolerance)
+        }
+        SolSageInstruction::SetAggregationWindow { aggregation_window_secs } => {
+            msg!("Instruction: SetAggregationWindow");
+            process_set_aggregation_window(program_id, accounts, aggregation_window_secs)
+        }
+        SolSageInstruction::FinalizeAttribution { query_hash } => {
+            msg!("Instruction: FinalizeAttribution");
+            process_finalize_attribution(program_id, accounts, query_hash)
+        }
+        SolSageInstruction::SetPayerAttributionCap { max_payer_attributions_per_day } => {
+            msg!("Instruction: SetPayerAttributionCap");
+            process_set_payer_attribution_cap(program_id, accounts, max_payer_attributions_per_day)
+        }
+        SolSageInstruction::UpvoteEntry { weight } => {
+            msg!("Instruction: UpvoteEntry");
+            process_vote_on_entry(program_id, accounts, true, weight)
+        }
+        SolSageInstruction::DownvoteEntry { weight } => {
+            msg!("Instruction: DownvoteEntry");
+            process_vote_on_entry(program_id, accounts, false, weight)
+        }
+        SolSageInstruction::WithdrawEntryVote => {
+            msg!("Instruction: WithdrawEntryVote");
+            process_withdraw_entry_vote(program_id, accounts)
+        }
+        SolSageInstruction::ReportEntry { bond } => {
+            msg!("Instruction: ReportEntry");
+            process_report_entry(program_id, accounts, bond)
+        }
+        SolSageInstruction::ResolveReports { uphold } => {
+            msg!("Instruction: ResolveReports");
+            process_resolve_reports(program_id, accounts, uphold)
+        }
+        SolSageInstruction::CreateBounty { query_hash, reward, deadline } => {
+            msg!("Instruction: CreateBounty");
+            process_create_bounty(program_id, accounts, query_hash, reward, deadline)
+        }
+        SolSageInstruction::SubmitAnswer => {
+            msg!("Instruction: SubmitAnswer");
+            process_submit_answer(program_id, accounts)
+        }
+        SolSageInstruction::AcceptAnswer => {
+            msg!("Instruction: AcceptAnswer");
+            process_accept_answer(program_id, accounts)
+        }
+        SolSageInstruction::ReclaimExpiredBounty => {
+            msg!("Instruction: ReclaimExpiredBounty");
+            process_reclaim_expired_bounty(program_id, accounts)
+        }
+        SolSageInstruction::SetRewardStream { reward_stream_secs } => {
+            msg!("Instruction: SetRewardStream");
+            process_set_reward_stream(program_id, accounts, reward_stream_secs)
+        }
+        SolSageInstruction::SetFeeConfig { fee_config } => {
+            msg!("Instruction: SetFeeConfig");
+            process_set_fee_config(program_id, accounts, fee_config)
+        }
+        SolSageInstruction::ClaimTreasuryRewards => {
+            msg!("Instruction: ClaimTreasuryRewards");
+            process_claim_treasury_rewards(program_id, accounts)
+        }
+        SolSageInstruction::SetLowRelevanceBurn { low_relevance_threshold, low_relevance_burn_bps } => {
+            msg!("Instruction: SetLowRelevanceBurn");
+            process_set_low_relevance_burn(program_id, accounts, low_relevance_threshold, low_relevance_burn_bps)
+        }
+        SolSageInstruction::BurnRewards => {
+            msg!("Instruction: BurnRewards");
+            process_burn_rewards(program_id, accounts)
+        }
+        SolSageInstruction::SetCitationRoyalty { citation_royalty_bps } => {
+            msg!("Instruction: SetCitationRoyalty");
+            process_set_citation_royalty(program_id, accounts, citation_royalty_bps)
+        }
+        SolSageInstruction::PayCitationRoyalties => {
+            msg!("Instruction: PayCitationRoyalties");
+            process_pay_citation_royalties(program_id, accounts)
+        }
+        SolSageInstruction::StakeDocument { content_hash, title, chunk_count } => {
+            msg!("Instruction: StakeDocument");
+            process_stake_document(program_id, accounts, content_hash, title, chunk_count)
+        }
+        SolSageInstruction::AddDocumentChunk { chunk_index } => {
+            msg!("Instruction: AddDocumentChunk");
+            process_add_document_chunk(program_id, accounts, chunk_index)
+        }
+        SolSageInstruction::RefreshDocument => {
+            msg!("Instruction: RefreshDocument");
+            process_refresh_document(program_id, accounts)
+        }
+        SolSageInstruction::UpdateEmbeddingFingerprint { embedding_fingerprint } => {
+            msg!("Instruction: UpdateEmbeddingFingerprint");
+            process_update_embedding_fingerprint(program_id, accounts, embedding_fingerprint)
+        }
+        SolSageInstruction::AddReviewer { reviewer } => {
+            msg!("Instruction: AddReviewer");
+            process_add_reviewer(program_id, accounts, reviewer)
+        }
+        SolSageInstruction::RemoveReviewer { reviewer } => {
+            msg!("Instruction: RemoveReviewer");
+            process_remove_reviewer(program_id, accounts, reviewer)
+        }
+        SolSageInstruction::AttestEntry { accuracy, freshness } => {
+            msg!("Instruction: AttestEntry");
+            process_attest_entry(program_id, accounts, accuracy, freshness)
+        }
+        SolSageInstruction::SetIdentityConfig { identity_config } => {
+            msg!("Instruction: SetIdentityConfig");
+            process_set_identity_config(program_id, accounts, identity_config)
+        }
+        SolSageInstruction::SetEntryVisibility { visibility } => {
+            msg!("Instruction: SetEntryVisibility");
+            process_set_entry_visibility(program_id, accounts, visibility)
+        }
+        SolSageInstruction::GrantAccess { holder } => {
+            msg!("Instruction: GrantAccess");
+            process_grant_access(program_id, accounts, holder)
+        }
+        SolSageInstruction::BuyAccess { price } => {
+            msg!("Instruction: BuyAccess");
+            process_buy_access(program_id, accounts, price)
+        }
     }
 }
 
@@ -62,6 +562,7 @@ pub enum SolSageInstruction {
     /// 0. [writable, signer] Protocol authority
     /// 1. [writable] Protocol account (PDA)
     /// 2. [] System program
+    /// 3. [writable] Treasury account (PDA)
     Initialize,
 
     /// Stake knowledge
@@ -69,355 +570,13272 @@ pub enum SolSageInstruction {
     /// 0. [writable, signer] Staker
     /// 1. [writable] Protocol account
     /// 2. [writable] Knowledge entry account (PDA)
-    /// 3. [] System program
+    /// 3. [writable] Stake escrow account (PDA)
+    /// 4. [] System program
+    /// 5. [writable] Staker profile account (PDA)
+    /// 6. [writable] Content registry account for `content_hash` (PDA)
+    /// 7. [writable] Category account (PDA)
+    /// 8. [writable] Staker index account (PDA); `entry_index` must be its
+    ///    `entry_count`
+    /// 9. [writable] Bubblegum tree config of the protocol's receipt tree,
+    ///    when `mint_receipt` is set; the rest follow it
+    /// 10. [writable] Protocol's receipt tree
+    /// 11. [] Receipt authority (PDA), the tree's delegate
+    /// 12. [] Bubblegum program
+    /// 13. [] SPL Noop program
+    /// 14. [] SPL Account Compression program
+    /// 15. [] Staker's identity attestation, when `identity` is set, after
+    ///     any receipt accounts
+    /// 16. [] Remaining accounts: each entry of `derived_from`, in order,
+    ///     after every other account
     StakeKnowledge {
+        entry_index: u64,
         content_hash: [u8; 32],
         title: String,
-        category: String,
+        stake_amount: u64,
+        license: LicenseType,
+        /// Keeps the stake locked for longer in return for boosted rewards
+        lockup: LockupTier,
+        /// Mints the staker a compressed NFT receipt for the entry
+        mint_receipt: bool,
+        /// Entries the content is derived from, paid a royalty on its
+        /// attributions
+        derived_from: Vec<Pubkey>,
+        /// Merkle root over the content's chunks, letting attributions prove
+        /// which chunk they used
+        chunks: Option<ChunkCommitment>,
+        /// Presents an attestation of the staker's identity under the
+        /// protocol's identity config, recorded on the entry
+        identity: bool,
     },
 
-    /// Record an attribution
+    /// Record an attribution. While the protocol requires consensus this
+    /// casts the oracle's vote on the attribution, which stays pending until
+    /// enough oracles' reports match; under score aggregation it adds the
+    /// oracle's score to the pending attribution's average instead.
     /// Accounts:
-    /// 0. [writable, signer] Payer
+    /// 0. [writable, signer] Payer (registered oracle)
     /// 1. [writable] Protocol account
     /// 2. [writable] Knowledge entry account
     /// 3. [writable] Attribution account (PDA)
-    /// 4. [] System program
+    /// 4. [writable] Query record account (PDA)
+    /// 5. [] Attribution oracle account (PDA)
+    /// 6. [] System program
+    /// 7. [writable] Staker profile account of the entry owner (PDA)
+    /// 8. [writable] Treasury account (PDA)
+    /// 9. [writable] Category account of the entry
+    /// 10. [writable] Leaderboard account (PDA), created on first use
+    /// 11. [writable] Oracle state PDA of the payer, created on first use
+    /// 12. [writable] Payer stats PDA of the payer, created on first use
+    /// 13. [] Instructions sysvar, when the protocol has an attribution
+    ///     verifier
+    /// 14. [writable] Open epoch PDA when epochs are enabled, otherwise the
+    ///     curator account PDA when `curator` is set
+    /// 15. [writable] Agent stats PDA of `agent_id`, when it is set
+    /// 16. [writable] Entry's collection PDA, when it is in one
+    /// 17. [] Payer's access pass PDA for the entry, when it is private
+    /// 18. [writable] Payer's subscription PDA, or the payer's SAGE token
+    ///     account, the reward vault PDA and the token program, when
+    ///     attributions fund rewards
     RecordAttribution {
         query_hash: [u8; 32],
         relevance_score: u8,
+        /// Agent or service that surfaced the entry, paid the protocol's
+        /// curator share of the reward
+        curator: Option<Pubkey>,
+        /// Consuming agent, counted in its agent stats
+        agent_id: Option<[u8; 32]>,
+        /// Model the consuming agent ran
+        model_id: Option<[u8; 32]>,
+        /// Chunk of the entry's committed chunks the answer used
+        chunk: Option<ChunkProof>,
     },
 
-    /// Claim rewards
+    /// Claim the vested rewards of some or all of an entry's attributions,
+    /// whatever its reward stream has paid out so far and the royalties
+    /// entries derived from it have paid
     /// Accounts:
-    /// 0. [signer] Staker
-    /// 1. [writable] Knowledge entry account
+    /// 0. [signer] Staker or their claim delegate
+    /// 1. [] Protocol account
+    /// 2. [writable] Knowledge entry account
+    /// 3. [writable] SAGE mint, or the reward vault PDA when attributions
+    ///    fund rewards
+    /// 4. [writable] Staker token account
+    /// 5. [] Mint authority (PDA)
+    /// 6. [] Token program
+    /// 7. [writable] Staker profile account (PDA)
+    /// 8. [writable] SAGE token account of each co-author, in split order
+    /// 9. [writable] Remaining accounts: unclaimed attributions of the entry,
+    ///    each recorded in an epoch followed by that epoch's PDA
     ClaimRewards,
-}
 
-// ============================================================================
-// STATE
-// ============================================================================
+    /// Create the protocol-owned SAGE mint
+    /// Accounts:
+    /// 0. [writable, signer] Protocol authority
+    /// 1. [writable] Protocol account
+    /// 2. [writable] SAGE mint (PDA)
+    /// 3. [] Mint authority (PDA)
+    /// 4. [] Token program
+    /// 5. [] System program
+    InitializeMint {
+        max_supply: u64,
+    },
 
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct Protocol {
-    pub is_initialized: bool,
-    pub authority: Pubkey,
-    pub total_knowledge_entries: u64,
-    pub total_attributions: u64,
-    pub reward_per_attribution: u64,
-    pub bump: u8,
-}
+    /// Withdraw staked lamports once the cooldown started by `RequestUnstake`
+    /// and any lockup have elapsed, and the entry's pending rewards have been
+    /// claimed or forfeited
+    /// Accounts:
+    /// 0. [writable, signer] Staker
+    /// 1. [writable] Knowledge entry account
+    /// 2. [writable] Stake escrow account (PDA)
+    CompleteUnstake,
 
-impl Protocol {
-    pub const LEN: usize = 1 + 32 + 8 + 8 + 8 + 1;
-    pub const SEED: &'static [u8] = b"protocol";
-}
+    /// Edit an entry's metadata; `None` leaves a field unchanged. A new
+    /// content hash moves the entry's claim on its content to it and drops
+    /// the chunk commitment over the old content; a document's chunks keep
+    /// theirs.
+    /// Accounts:
+    /// 0. [writable, signer] Staker, paying to grow the entry and for the new
+    ///    content registry if needed
+    /// 1. [writable] Knowledge entry account
+    /// 2. [] System program
+    /// 3. [writable] Content registry account for the current content hash
+    ///    (PDA), closed; only when changing it
+    /// 4. [writable] Content registry account for the new content hash
+    ///    (PDA), created; only when changing it
+    /// 5. [writable] Payer of the current content registry, refunded its
+    ///    rent; only when changing the content hash of a registry another
+    ///    account paid for
+    UpdateKnowledge {
+        title: Option<String>,
+        content_hash: Option<[u8; 32]>,
+    },
 
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct KnowledgeEntry {
-    pub is_initialized: bool,
-    pub staker: Pubkey,
-    pub content_hash: [u8; 32],
-    pub title: String,
-    pub category: String,
-    pub created_at: i64,
-    pub total_attributions: u64,
-    pub pending_rewards: u64,
-    pub is_active: bool,
-    pub bump: u8,
-}
+    /// Stop an entry from receiving attributions
+    /// Accounts:
+    /// 0. [signer] Staker or protocol authority
+    /// 1. [] Protocol account
+    /// 2. [writable] Knowledge entry account
+    DeactivateKnowledge,
 
-impl KnowledgeEntry {
-    pub const LEN: usize = 1 + 32 + 32 + 4 + 100 + 4 + 50 + 8 + 8 + 8 + 1 + 1;
-    pub const SEED: &'static [u8] = b"knowledge";
-}
+    /// Allow an entry to receive attributions again
+    /// Accounts:
+    /// 0. [signer] Staker or protocol authority
+    /// 1. [] Protocol account
+    /// 2. [writable] Knowledge entry account
+    ReactivateKnowledge,
 
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct Attribution {
-    pub is_initialized: bool,
-    pub knowledge_entry: Pubkey,
-    pub query_hash: [u8; 32],
-    pub relevance_score: u8,
-    pub timestamp: i64,
-    pub reward_claimed: bool,
-    pub bump: u8,
-}
+    /// Close a fully claimed, unstaked entry and reclaim its rent
+    /// Accounts:
+    /// 0. [writable, signer] Staker
+    /// 1. [writable] Protocol account
+    /// 2. [writable] Knowledge entry account
+    /// 3. [writable] Stake escrow account (PDA)
+    /// 4. [writable] Category account of the entry
+    /// 5. [writable] Content registry account for the entry's content hash
+    ///    (PDA), closed if the entry holds it
+    /// 6. [writable] Staker index account of the entry's creator (PDA),
+    ///    counting the entry closed if the creator has one
+    /// 7. [writable] Payer of the content registry, refunded its rent; only
+    ///    when the entry holds a registry another account paid for
+    CloseKnowledgeEntry,
 
-impl Attribution {
-    pub const LEN: usize = 1 + 32 + 32 + 1 + 8 + 1 + 1;
-    pub const SEED: &'static [u8] = b"attribution";
-}
+    /// Change the base reward paid per attribution
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetRewardRate {
+        reward_per_attribution: u64,
+    },
 
-// ============================================================================
-// ERRORS
-// ============================================================================
+    /// Nominate a new protocol authority; takes effect once accepted. A
+    /// multisig is nominated by the PDA it signs with.
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    ProposeAuthorityTransfer {
+        new_authority: Pubkey,
+        authority_type: AuthorityType,
+    },
 
-#[derive(Error, Debug)]
-pub enum SolSageError {
-    #[error("Account already initialized")]
-    AlreadyInitialized,
-    #[error("Title too long")]
-    TitleTooLong,
-    #[error("Category too long")]
-    CategoryTooLong,
-    #[error("Invalid relevance score")]
-    InvalidRelevanceScore,
-    #[error("No rewards to claim")]
-    NoRewardsToClaim,
-    #[error("Not knowledge owner")]
-    NotKnowledgeOwner,
-    #[error("Invalid PDA")]
-    InvalidPda,
-}
+    /// Accept a pending authority nomination, signed through CPI when the
+    /// nominee is a multisig
+    /// Accounts:
+    /// 0. [signer] Pending authority
+    /// 1. [writable] Protocol account
+    AcceptAuthorityTransfer,
 
-impl From<SolSageError> for ProgramError {
-    fn from(e: SolSageError) -> Self {
-        ProgramError::Custom(e as u32)
-    }
-}
+    /// Change how long newly accrued rewards stay locked
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetVestingPeriod {
+        vesting_period_secs: i64,
+    },
 
-// ============================================================================
-// PROCESSORS
-// ============================================================================
+    /// Report claimable vs. locked rewards via return data (`VestedRewards`)
+    /// Accounts:
+    /// 0. [] Knowledge entry account
+    GetVestedRewards,
 
-fn process_initialize(
-    program_id: &Pubkey,
-    accounts: &[AccountInfo],
-) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
-    let authority = next_account_info(account_info_iter)?;
-    let protocol_account = next_account_info(account_info_iter)?;
-    let system_program = next_account_info(account_info_iter)?;
+    /// Allow `oracle` to record attributions
+    /// Accounts:
+    /// 0. [writable, signer] Protocol authority
+    /// 1. [] Protocol account
+    /// 2. [writable] Attribution oracle account (PDA)
+    /// 3. [] System program
+    AddOracle {
+        oracle: Pubkey,
+    },
 
-    if !authority.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    /// Revoke an oracle, returning its account rent to the authority
+    /// Accounts:
+    /// 0. [writable, signer] Protocol authority
+    /// 1. [] Protocol account
+    /// 2. [writable] Attribution oracle account (PDA)
+    RemoveOracle,
 
-    // Derive PDA
-    let (protocol_pda, bump) = Pubkey::find_program_address(
-        &[Protocol::SEED],
-        program_id,
-    );
+    /// Record one query's attributions to several entries at once;
+    /// `scores[i]` applies to the i-th remaining account quadruple
+    /// Accounts:
+    /// 0. [writable, signer] Payer (registered oracle)
+    /// 1. [writable] Protocol account
+    /// 2. [writable] Query record account (PDA)
+    /// 3. [] Attribution oracle account (PDA)
+    /// 4. [] System program
+    /// 5. [writable] Treasury account (PDA)
+    /// 6. [writable] Leaderboard account (PDA), created on first use
+    /// 7. [writable] Oracle state PDA of the payer, created on first use
+    /// 8. [writable] Payer stats PDA of the payer, created on first use
+    /// 9. [] Instructions sysvar, when the protocol has an attribution
+    ///    verifier
+    /// 10. [writable] Remaining accounts: (Knowledge entry, Attribution PDA,
+    ///     owner's Staker profile PDA, entry's Category) quadruples, then the
+    ///     collection PDA of each entry in one and the payer's access pass
+    ///     PDA of each private entry, in entry order, then the open
+    ///     epoch PDA when epochs are enabled, or the payer's subscription PDA
+    ///     or SAGE token account, reward vault PDA and token program when
+    ///     attributions fund rewards
+    RecordAttributionBatch {
+        query_hash: [u8; 32],
+        scores: Vec<u8>,
+    },
 
-    if protocol_pda != *protocol_account.key {
-        return Err(SolSageError::InvalidPda.into());
-    }
+    /// Claim vested attribution rewards across several entries in one mint
+    /// Accounts:
+    /// 0. [signer] Staker or their claim delegate
+    /// 1. [] Protocol account
+    /// 2. [writable] SAGE mint, or the reward vault PDA when attributions
+    ///    fund rewards
+    /// 3. [writable] Staker token account
+    /// 4. [] Mint authority (PDA)
+    /// 5. [] Token program
+    /// 6. [writable] Staker profile account (PDA)
+    /// 7. [writable] Remaining accounts: (Knowledge entry, Attribution) pairs
+    ///    for entries owned by the staker, each attribution recorded in an
+    ///    epoch followed by that epoch's PDA. Co-authored entries must be
+    ///    claimed with `ClaimRewards`.
+    ClaimAllRewards,
 
-    // Create account
-    let rent = Rent::get()?;
-    let lamports = rent.minimum_balance(Protocol::LEN);
-    
-    invoke_signed(
-        &system_instruction::create_account(
-            authority.key,
-            protocol_account.key,
-            lamports,
+    /// Hand an entry and its stake to a new owner. Rewards the entry earned
+    /// are the staker's, so it changes hands only once they have all been
+    /// claimed or forfeited.
+    /// Accounts:
+    /// 0. [writable, signer] Staker
+    /// 1. [writable] Knowledge entry account
+    /// 2. [writable] New owner's staker profile account (PDA)
+    /// 3. [] System program
+    /// 4. [writable] Staker's staker profile account (PDA)
+    TransferKnowledgeOwnership { new_owner: Pubkey },
+
+    /// Switch the curve used to price attributions
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetRewardCurve {
+        reward_curve: RewardCurve,
+    },
+
+    /// Dispute an attribution by bonding `bond` lamports
+    /// Accounts:
+    /// 0. [writable, signer] Challenger
+    /// 1. [] Attribution account
+    /// 2. [writable] Challenge account (PDA)
+    /// 3. [] System program
+    ChallengeAttribution {
+        bond: u64,
+    },
+
+    /// Settle a challenge. Upheld: the attribution's reward is reversed and the
+    /// bond refunded. Rejected: the bond goes to the entry owner.
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [] Protocol account
+    /// 2. [writable] Attribution account
+    /// 3. [writable] Knowledge entry account
+    /// 4. [writable] Challenge account (PDA)
+    /// 5. [writable] Challenger
+    /// 6. [writable] Knowledge entry owner
+    ResolveChallenge {
+        uphold: bool,
+    },
+
+    /// Slash `slash_bps` of an entry's stake into the treasury, forfeit its
+    /// pending rewards and deactivate it
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [] Protocol account
+    /// 2. [writable] Knowledge entry account
+    /// 3. [writable] Stake escrow account (PDA)
+    /// 4. [writable] Treasury account (PDA)
+    SlashKnowledge {
+        slash_bps: u16,
+    },
+
+    /// Change the fee, in basis points of each reward, that attribution
+    /// payers pay in lamports to the treasury
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetProtocolFee {
+        fee_bps: u16,
+    },
+
+    /// Move lamports out of the treasury, keeping it rent-exempt
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [] Protocol account
+    /// 2. [writable] Treasury account (PDA)
+    /// 3. [writable] Destination
+    WithdrawTreasury {
+        amount: u64,
+    },
+
+    /// Register a category that entries can be staked under
+    /// Accounts:
+    /// 0. [writable, signer] Protocol authority
+    /// 1. [] Protocol account
+    /// 2. [writable] Category account (PDA)
+    /// 3. [] System program
+    CreateCategory {
+        name: String,
+    },
+
+    /// Scale rewards for a category's attributions by `multiplier_bps`
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [] Protocol account
+    /// 2. [writable] Category account
+    SetCategoryMultiplier {
+        multiplier_bps: u16,
+    },
+
+    /// Pay the renewal fee to push an entry's expiry out by a full term;
+    /// `content_hash`, if given, must match the stored hash
+    /// Accounts:
+    /// 0. [writable, signer] Staker
+    /// 1. [writable] Protocol account
+    /// 2. [writable] Knowledge entry account
+    /// 3. [writable] Treasury account (PDA)
+    /// 4. [] System program
+    RenewKnowledge {
+        content_hash: Option<[u8; 32]>,
+    },
+
+    /// Let `delegate` claim rewards on the staker's behalf; rewards still go
+    /// to the staker's token account. `None` revokes the delegate.
+    /// Accounts:
+    /// 0. [signer] Staker
+    /// 1. [writable] Staker profile account (PDA)
+    SetClaimDelegate {
+        delegate: Option<Pubkey>,
+    },
+
+    /// Change the minimum stake, in lamports, that new entries must lock
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetMinStake {
+        min_stake_lamports: u64,
+    },
+
+    /// Change how long an entry must wait between attributions
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetAttributionCooldown {
+        attribution_cooldown_secs: i64,
+    },
+
+    /// Freeze staking, attribution and claiming until unpaused
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    Pause,
+
+    /// Lift a pause set by `Pause`
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    Unpause,
+
+    /// Change the relevance score that earns the full reward rate
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetScoreDivisor {
+        score_divisor: u16,
+    },
+
+    /// Post the Merkle root of an oracle's attributions for one epoch in
+    /// place of one attribution account each; see `solsage_core::merkle`
+    /// Accounts:
+    /// 0. [signer, writable] Registered oracle
+    /// 1. [] Protocol account
+    /// 2. [] Oracle registration PDA
+    /// 3. [writable] Attribution root PDA
+    /// 4. [] System program
+    /// 5. [] Instructions sysvar, when the protocol has an attribution
+    ///    verifier
+    CommitAttributionRoot {
+        epoch: u64,
+        root: [u8; 32],
+        attribution_count: u32,
+    },
+
+    /// Prove an attribution's inclusion in a committed root and mint its
+    /// reward once the root has vested. Each leaf pays out once, and
+    /// co-authored entries cannot claim this way. Roots are unfunded, so only
+    /// minted rewards can be claimed this way.
+    /// Accounts:
+    /// 0. [signer] Entry owner or their claim delegate
+    /// 1. [writable] Protocol account
+    /// 2. [writable] Attribution root PDA
+    /// 3. [writable] Knowledge entry
+    /// 4. [writable] SAGE mint
+    /// 5. [writable] Staker's SAGE token account
+    /// 6. [] Mint authority PDA
+    /// 7. [] Token program
+    /// 8. [writable] Staker profile PDA
+    /// 9. [writable] Entry's category
+    /// 10. [writable] Entry's collection PDA, when it is in one
+    ClaimAttributedReward {
+        leaf_index: u32,
+        query_hash: [u8; 32],
+        relevance_score: u8,
+        proof: Vec<[u8; 32]>,
+    },
+
+    /// Set the length and SAGE budget of epochs opened from now on; a zero
+    /// duration goes back to minting each attribution's own reward
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetEpochSchedule {
+        epoch_duration_secs: i64,
+        epoch_reward_budget: u64,
+    },
+
+    /// Open the next epoch once the previous one has ended. Anyone may call
+    /// this; attributions are rejected until it is done.
+    /// Accounts:
+    /// 0. [writable, signer] Payer
+    /// 1. [writable] Protocol account
+    /// 2. [writable] Epoch PDA for index `epoch_count`
+    /// 3. [] System program
+    OpenEpoch,
+
+    /// Close an ended epoch to further weight, fixing each attribution's
+    /// pro-rata share of its budget so they can be claimed. Anyone may call
+    /// this.
+    /// Accounts:
+    /// 0. [writable] Epoch PDA
+    FinalizeEpoch,
+
+    /// Change the share of each attribution's reward paid to its curator
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetCuratorShare {
+        curator_bps: u16,
+    },
+
+    /// Mint a curator's accrued rewards
+    /// Accounts:
+    /// 0. [signer] Curator
+    /// 1. [] Protocol account
+    /// 2. [writable] Curator account PDA
+    /// 3. [writable] SAGE mint, or the reward vault PDA when attributions
+    ///    fund rewards
+    /// 4. [writable] Curator's SAGE token account
+    /// 5. [] Mint authority PDA
+    /// 6. [] Token program
+    ClaimCuratorRewards,
+
+    /// Create a named collection grouping some of the owner's entries
+    /// Accounts:
+    /// 0. [writable, signer] Owner
+    /// 1. [writable] Collection PDA
+    /// 2. [] System program
+    CreateCollection {
+        name: String,
+    },
+
+    /// Add an entry that is in no other collection. Its attributions are
+    /// counted towards the collection from then on.
+    /// Accounts:
+    /// 0. [signer] Owner of both the collection and the entry
+    /// 1. [writable] Collection PDA
+    /// 2. [writable] Knowledge entry account
+    AddToCollection,
+
+    /// Take an entry out of its collection; the collection keeps the
+    /// attributions already counted
+    /// Accounts:
+    /// 0. [signer] Collection owner or entry owner
+    /// 1. [writable] Collection PDA
+    /// 2. [writable] Knowledge entry account
+    RemoveFromCollection,
+
+    /// Replace the co-authors paid a share of the entry's claimed rewards;
+    /// the owner keeps whatever the splits leave
+    /// Accounts:
+    /// 0. [writable, signer] Staker, paying to grow the entry if needed
+    /// 1. [writable] Knowledge entry account
+    /// 2. [] System program
+    SetRoyaltySplit {
+        splits: Vec<RoyaltySplit>,
+    },
+
+    /// Change the license the entry's content is offered under
+    /// Accounts:
+    /// 0. [writable, signer] Staker, paying to grow the entry if needed
+    /// 1. [writable] Knowledge entry account
+    /// 2. [] System program
+    UpdateLicense {
+        license: LicenseType,
+    },
+
+    /// Point the entry at where its content can be fetched, or clear the
+    /// pointer with `None`
+    /// Accounts:
+    /// 0. [writable, signer] Staker, paying to grow the entry if needed
+    /// 1. [writable] Knowledge entry account
+    /// 2. [] System program
+    UpdateContentUri {
+        content_uri: Option<String>,
+    },
+
+    /// Upgrade an account written under an older schema version to the
+    /// current layout in place, growing it if the layout did and tagging it
+    /// with its type. Layouts can share a size, so the authority vouches for
+    /// the `account_type` of an untagged account and funds the extra rent. Migrate the protocol itself first, passing it as both
+    /// accounts 1 and 2.
+    /// Accounts:
+    /// 0. [writable, signer] Protocol authority
+    /// 1. [writable] Protocol account
+    /// 2. [writable] Account to migrate
+    /// 3. [] System program
+    MigrateAccount {
+        account_type: AccountType,
+    },
+
+    /// Tag an entry, counting it in the tag's index
+    /// Accounts:
+    /// 0. [writable, signer] Staker, paying for a new tag index and to grow
+    ///    the entry if needed
+    /// 1. [writable] Knowledge entry account
+    /// 2. [writable] Tag index account for `tag` (PDA), created on first use
+    /// 3. [] System program
+    AddTag {
+        tag: String,
+    },
+
+    /// Take a tag off an entry
+    /// Accounts:
+    /// 0. [writable, signer] Staker, paying to grow the entry if needed
+    /// 1. [writable] Knowledge entry account
+    /// 2. [writable] Tag index account for `tag` (PDA)
+    /// 3. [] System program
+    RemoveTag {
+        tag: String,
+    },
+
+    /// Choose whether rewards are minted or funded by attribution payers.
+    /// Only allowed before the first attribution, so every reward is paid
+    /// the way it was earned.
+    /// Accounts:
+    /// 0. [writable, signer] Protocol authority
+    /// 1. [writable] Protocol account
+    /// 2. [writable] Reward vault (PDA), created on first switch to `Payer`
+    /// 3. [] SAGE mint
+    /// 4. [] Token program
+    /// 5. [] System program
+    SetRewardSource {
+        reward_source: RewardSource,
+    },
+
+    /// Deposit SAGE into the consumer's subscription, which attributions
+    /// they pay for then draw their rewards from
+    /// Accounts:
+    /// 0. [writable, signer] Consumer
+    /// 1. [] Protocol account
+    /// 2. [writable] Subscription account of the consumer (PDA), created on
+    ///    first top-up
+    /// 3. [writable] Consumer's SAGE token account
+    /// 4. [writable] Reward vault (PDA)
+    /// 5. [] Token program
+    /// 6. [] System program
+    TopUpSubscription {
+        amount: u64,
+    },
+
+    /// Refund a subscription's unspent balance and close it, returning its
+    /// rent to the consumer
+    /// Accounts:
+    /// 0. [writable, signer] Consumer
+    /// 1. [] Protocol account
+    /// 2. [writable] Subscription account of the consumer (PDA)
+    /// 3. [writable] SAGE token account receiving the refund
+    /// 4. [writable] Reward vault (PDA)
+    /// 5. [] Mint authority (PDA)
+    /// 6. [] Token program
+    CloseSubscription,
+
+    /// Set the smallest reward an attribution to the entry may pay. Rewards
+    /// below it are rounded up when a subscription funds them and rejected
+    /// otherwise; 0 sets no floor.
+    /// Accounts:
+    /// 0. [writable, signer] Staker, paying to grow the entry if needed
+    /// 1. [writable] Knowledge entry account
+    /// 2. [] System program
+    SetPriceFloor {
+        min_reward_per_use: u64,
+    },
+
+    /// Re-rank the passed entries on the leaderboard at their current
+    /// attribution counts, dropping the ones closed or deactivated since.
+    /// Permissionless.
+    /// Accounts:
+    /// 0. [writable, signer] Payer, funding the leaderboard if it is created
+    /// 1. [writable] Leaderboard account (PDA)
+    /// 2. [] System program
+    /// 3. [] Remaining accounts: knowledge entries
+    RebuildLeaderboard,
+
+    /// Configure token-weighted governance. Proposals pass with at least
+    /// `quorum_votes` SAGE voted and more than `approval_threshold_bps` of it
+    /// in favour, take votes for `voting_period_secs` and then wait
+    /// `execution_delay_secs` before they can execute. A voting period of 0
+    /// turns governance off.
+    /// Accounts:
+    /// 0. [writable, signer] Protocol authority, funding the governance vault
+    ///    if it is created
+    /// 1. [writable] Protocol account
+    /// 2. [writable] Governance vault (PDA), created when governance is
+    ///    first turned on
+    /// 3. [] SAGE mint
+    /// 4. [] Token program
+    /// 5. [] System program
+    SetGovernanceConfig {
+        quorum_votes: u64,
+        approval_threshold_bps: u16,
+        voting_period_secs: i64,
+        execution_delay_secs: i64,
+    },
+
+    /// Put `action` to a vote of SAGE holders
+    /// Accounts:
+    /// 0. [writable, signer] Proposer
+    /// 1. [writable] Protocol account
+    /// 2. [writable] Proposal account (PDA), at the protocol's next
+    ///    proposal index
+    /// 3. [] System program
+    CreateProposal {
+        action: ProposalAction,
+    },
+
+    /// Vote on an open proposal with `weight` SAGE, held in the governance
+    /// vault until the vote is withdrawn after voting ends
+    /// Accounts:
+    /// 0. [writable, signer] Voter
+    /// 1. [] Protocol account
+    /// 2. [writable] Proposal account
+    /// 3. [writable] Vote record account (PDA)
+    /// 4. [writable] Voter's SAGE token account
+    /// 5. [writable] Governance vault (PDA)
+    /// 6. [] Token program
+    /// 7. [] System program
+    CastVote {
+        approve: bool,
+        weight: u64,
+    },
+
+    /// Apply a passed proposal once its execution delay has run.
+    /// Permissionless.
+    /// Accounts:
+    /// 0. [writable, signer] Executor, funding the oracle account an
+    ///    `AddOracle` creates and receiving the rent of one a `RemoveOracle`
+    ///    closes
+    /// 1. [writable] Protocol account
+    /// 2. [writable] Proposal account
+    /// 3. [writable] Attribution oracle account (PDA), for oracle actions
+    /// 4. [] System program, for `AddOracle`
+    ExecuteProposal,
+
+    /// Return the SAGE behind a vote once voting has ended, closing the vote
+    /// record and returning its rent to the voter
+    /// Accounts:
+    /// 0. [writable, signer] Voter
+    /// 1. [] Protocol account
+    /// 2. [] Proposal account
+    /// 3. [writable] Vote record account (PDA)
+    /// 4. [writable] SAGE token account receiving the tokens
+    /// 5. [writable] Governance vault (PDA)
+    /// 6. [] Mint authority (PDA)
+    /// 7. [] Token program
+    WithdrawVote,
+
+    /// Queue a change to the reward rate, protocol fee, oracle list or admin
+    /// delay, executable once `admin_delay_secs` has passed. Replaces any
+    /// change already queued. While the admin delay is non-zero the matching
+    /// setters are rejected, so every change gives integrators notice.
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    QueueParameterChange {
+        change: ProposalAction,
+    },
+
+    /// Drop the queued parameter change
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    CancelParameterChange,
+
+    /// Apply the queued parameter change once its delay has passed.
+    /// Permissionless.
+    /// Accounts:
+    /// 0. [writable, signer] Executor, funding the oracle account an
+    ///    `AddOracle` creates and receiving the rent of one a `RemoveOracle`
+    ///    closes
+    /// 1. [writable] Protocol account
+    /// 2. [writable] Attribution oracle account (PDA), for oracle changes
+    /// 3. [] System program, for `AddOracle`
+    ExecuteParameterChange,
+
+    /// Make rewards decay with the age of the entry used, halving every
+    /// `score_half_life_secs` since it was staked down to a floor of
+    /// `min_score_weight_bps` of the full reward. A half-life of 0 turns
+    /// decay off.
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetScoreDecay {
+        score_half_life_secs: i64,
+        min_score_weight_bps: u16,
+    },
+
+    /// Only take attributions and attribution roots through CPIs made under
+    /// a top-level instruction to `verifier`, checked against the
+    /// instructions sysvar; `None` lets oracles call in directly again
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetAttributionVerifier {
+        verifier: Option<Pubkey>,
+    },
+
+    /// Record an attribution an oracle signed off-chain, submitted by any
+    /// relayer. The instruction before it must be an Ed25519 program
+    /// instruction checking the oracle's signature over
+    /// `solsage_core::receipts::attribution_receipt`, which this reads back
+    /// through the instructions sysvar.
+    /// Accounts: as `RecordAttribution`, but for
+    /// 0. [writable, signer] Relayer, paying for the attribution
+    /// 5. [] Attribution oracle account (PDA) of the receipt's signer
+    /// 11. [writable] Oracle state PDA of the receipt's signer
+    /// 12. [writable] Payer stats PDA of the relayer
+    /// 13. [] Instructions sysvar
+    RecordSignedAttribution {
+        query_hash: [u8; 32],
+        relevance_score: u8,
+        curator: Option<Pubkey>,
+        agent_id: Option<[u8; 32]>,
+        model_id: Option<[u8; 32]>,
+        /// Receipt number, above that of every receipt of the oracle relayed
+        /// before
+        nonce: u64,
+    },
+
+    /// Let anyone expire rewards left unclaimed for more than
+    /// `claim_window_secs` after they vested; 0 keeps them claimable forever
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetClaimWindow {
+        claim_window_secs: i64,
+    },
+
+    /// Permissionless crank expiring rewards left unclaimed past the claim
+    /// window. They are never paid out, bar `EXPIRY_BOUNTY_BPS` of them paid
+    /// to the caller: minted rewards stay unminted and funded ones stay in
+    /// the reward vault.
+    /// Accounts:
+    /// 0. [signer] Caller
+    /// 1. [] Protocol account
+    /// 2. [writable] Knowledge entry account
+    /// 3. [writable] SAGE mint, or the reward vault PDA when attributions
+    ///    fund rewards
+    /// 4. [writable] Caller's SAGE token account, paid the bounty
+    /// 5. [] Mint authority (PDA)
+    /// 6. [] Token program
+    /// 7. [writable] Remaining accounts: expired attributions of the entry,
+    ///    each recorded in an epoch followed by that epoch's PDA
+    ExpireRewards,
+
+    /// Start the unstake cooldown; the entry stops taking attributions and
+    /// can't be reactivated
+    /// Accounts:
+    /// 0. [writable, signer] Staker, paying to grow the entry if needed
+    /// 1. [writable] Knowledge entry account
+    /// 2. [] System program
+    RequestUnstake,
+
+    /// Permissionless crank recording the protocol's totals in today's
+    /// snapshot; each day's snapshot is taken once
+    /// Accounts:
+    /// 0. [writable, signer] Payer
+    /// 1. [] Protocol account
+    /// 2. [writable] Snapshot PDA of the current day
+    /// 3. [] System program
+    TakeSnapshot,
+
+    /// Mint entry receipts from `merkle_tree`, a Bubblegum tree delegated to
+    /// the receipt authority PDA; `None` stops minting them
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetReceiptTree {
+        merkle_tree: Option<Pubkey>,
+    },
+
+    /// Take attributions from `emitter_address` on the Wormhole chain
+    /// `emitter_chain`, in messages `bridge` posts once it has verified
+    /// them. The emitter answers for its attributions as an oracle at its
+    /// bridge emitter PDA, registered alongside it; `RemoveOracle` on that
+    /// address revokes it.
+    /// Accounts:
+    /// 0. [writable, signer] Protocol authority
+    /// 1. [] Protocol account
+    /// 2. [writable] Bridge emitter PDA
+    /// 3. [writable] Attribution oracle account (PDA) of the bridge emitter
+    ///    PDA
+    /// 4. [] System program
+    AddBridgeEmitter {
+        bridge: Pubkey,
+        emitter_chain: u16,
+        emitter_address: [u8; 32],
+    },
+
+    /// Record an attribution an agent made on another chain, submitted by
+    /// any relayer. A bridge emitter published
+    /// `solsage_core::bridge::attribution_payload` for it in a message its
+    /// bridge posted, numbered above every message of the emitter relayed
+    /// before.
+    /// Accounts: as `RecordAttribution`, but for
+    /// 0. [writable, signer] Relayer, paying for the attribution
+    /// 5. [] Attribution oracle account (PDA) of the bridge emitter PDA
+    /// 11. [writable] Oracle state PDA of the bridge emitter PDA
+    /// 12. [writable] Payer stats PDA of the relayer
+    /// 13. [writable] Bridge emitter PDA of the message's emitter
+    /// 14. [] Posted message account, owned by the emitter's bridge
+    /// 15. [] Instructions sysvar, when the protocol has an attribution
+    ///     verifier; `RecordAttribution`'s accounts from 14 on follow
+    RecordBridgedAttribution {
+        query_hash: [u8; 32],
+        relevance_score: u8,
+        curator: Option<Pubkey>,
+        agent_id: Option<[u8; 32]>,
+        model_id: Option<[u8; 32]>,
+    },
+
+    /// Create or replace the Metaplex metadata wallets display for the SAGE
+    /// mint, updatable only through the mint authority PDA
+    /// Accounts:
+    /// 0. [writable, signer] Protocol authority
+    /// 1. [] Protocol account
+    /// 2. [] SAGE mint (PDA)
+    /// 3. [] Mint authority (PDA)
+    /// 4. [writable] Token Metadata account of the SAGE mint
+    /// 5. [] Token Metadata program
+    /// 6. [] System program
+    SetMintMetadata {
+        name: String,
+        symbol: String,
+        uri: String,
+    },
+
+    /// Offer an entry for `price` lamports. The entry stays with its owner
+    /// until bought; handing it on some other way leaves the listing stale.
+    /// Accounts:
+    /// 0. [writable, signer] Staker
+    /// 1. [] Knowledge entry account
+    /// 2. [writable] Listing PDA of the entry
+    /// 3. [] System program
+    ListEntryForSale {
+        price: u64,
+    },
+
+    /// Withdraw a listing, by its seller or, once it is stale, the entry's
+    /// current owner
+    /// Accounts:
+    /// 0. [signer] Seller, or the entry's owner
+    /// 1. [] Knowledge entry account
+    /// 2. [writable] Listing PDA of the entry
+    /// 3. [writable] Seller, refunded the listing's rent
+    CancelListing,
+
+    /// Buy a listed entry with its stake. Rewards the entry earned are the
+    /// seller's, so it sells only once they have all been claimed or
+    /// forfeited. `price` is escrowed in the listing, then paid to the seller
+    /// less the protocol's sale fee, which goes to the treasury.
+    /// Accounts:
+    /// 0. [writable, signer] Buyer
+    /// 1. [writable] Protocol account
+    /// 2. [writable] Knowledge entry account
+    /// 3. [writable] Listing PDA of the entry
+    /// 4. [writable] Seller
+    /// 5. [writable] Treasury (PDA)
+    /// 6. [writable] Buyer's staker profile account (PDA)
+    /// 7. [] System program
+    /// 8. [writable] Seller's staker profile account (PDA)
+    BuyEntry {
+        price: u64,
+    },
+
+    /// Change the fee, in basis points of the price, taken on entry sales
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetSaleFee {
+        sale_fee_bps: u16,
+    },
+
+    /// Cap what `oracle` may record per budget epoch of
+    /// `OracleState::BUDGET_EPOCH_SECS`, in attributions and in SAGE
+    /// awarded; 0 leaves either uncapped
+    /// Accounts:
+    /// 0. [writable, signer] Protocol authority
+    /// 1. [] Protocol account
+    /// 2. [writable] Oracle state PDA of `oracle`, created on first use
+    /// 3. [] System program
+    SetOracleBudget {
+        oracle: Pubkey,
+        max_attributions_per_epoch: u64,
+        max_rewards_per_epoch: u64,
+    },
+
+    /// Hold each attribution pending until `consensus_threshold` registered
+    /// oracles report it with relevance scores within `consensus_tolerance`
+    /// of the first report; it then finalizes at their average score. 0
+    /// finalizes each report on its own. Batches and attribution roots are
+    /// refused while consensus is required.
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetConsensus {
+        consensus_threshold: u8,
+        consensus_tolerance: u8,
+    },
+
+    /// Have attributions take relevance scores from any registered oracle
+    /// for `aggregation_window_secs` after the first, then finalize through
+    /// `FinalizeAttribution` at the average score. 0 finalizes each report
+    /// on its own. Can't be combined with consensus.
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetAggregationWindow {
+        aggregation_window_secs: i64,
+    },
+
+    /// Credit a pending attribution's reward at the average of the scores it
+    /// took, once its aggregation window has closed. Anyone may finalize;
+    /// the finalizer pays the protocol fee and, when attributions fund
+    /// rewards, the reward.
+    /// Accounts:
+    /// 0. [writable, signer] Finalizer
+    /// 1. [writable] Protocol account
+    /// 2. [writable] Knowledge entry account
+    /// 3. [writable] Attribution account (PDA)
+    /// 4. [writable] Query record account (PDA)
+    /// 5. [] System program
+    /// 6. [writable] Staker profile account of the entry owner (PDA)
+    /// 7. [writable] Treasury account (PDA)
+    /// 8. [writable] Category account of the entry
+    /// 9. [writable] Leaderboard account (PDA), created on first use
+    /// 10. [writable] Open epoch PDA, when epochs are enabled
+    /// 11. [writable] Agent stats PDA of the attribution's agent, when it
+    ///     has one
+    /// 12. [writable] Entry's collection PDA, when it is in one
+    /// 13. [] Finalizer's access pass PDA for the entry, when it is private
+    /// 14. [writable] Finalizer's subscription PDA, or their SAGE token
+    ///     account, the reward vault PDA and the token program, when
+    ///     attributions fund rewards
+    FinalizeAttribution {
+        query_hash: [u8; 32],
+    },
+
+    /// Cap the attributions any one payer records a day, so a single wallet
+    /// can't flood an entry it controls with attributions; 0 lifts the cap
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetPayerAttributionCap {
+        max_payer_attributions_per_day: u64,
+    },
+
+    /// Vouch for an entry's quality with `weight` SAGE, held in the
+    /// governance vault until the vote is withdrawn. The SAGE voted up on an
+    /// entry less the SAGE voted down scales every reward it earns, by a
+    /// basis point per `solsage_core::QUALITY_SCORE_PER_BPS`.
+    /// Accounts:
+    /// 0. [writable, signer] Voter
+    /// 1. [] Protocol account
+    /// 2. [writable] Knowledge entry account
+    /// 3. [writable] Entry vote account (PDA)
+    /// 4. [writable] Voter's SAGE token account
+    /// 5. [writable] Governance vault (PDA)
+    /// 6. [] Token program
+    /// 7. [] System program
+    UpvoteEntry {
+        weight: u64,
+    },
+
+    /// Vote an entry's quality down with `weight` SAGE. Accounts as for
+    /// `UpvoteEntry`.
+    DownvoteEntry {
+        weight: u64,
+    },
+
+    /// Take back a quality vote, returning its SAGE and its weight on the
+    /// entry's score, closing the vote record and returning its rent to the
+    /// voter
+    /// Accounts:
+    /// 0. [writable, signer] Voter
+    /// 1. [] Protocol account
+    /// 2. [writable] Knowledge entry account, which may have been closed
+    /// 3. [writable] Entry vote account (PDA)
+    /// 4. [writable] SAGE token account receiving the tokens
+    /// 5. [writable] Governance vault (PDA)
+    /// 6. [] Mint authority (PDA)
+    /// 7. [] Token program
+    WithdrawEntryVote,
+
+    /// Report an entry as spam by bonding `bond` lamports. Once
+    /// `solsage_core::SPAM_REPORT_THRESHOLD` reporters have, the entry is
+    /// deactivated until the authority resolves the reports.
+    /// Accounts:
+    /// 0. [writable, signer] Reporter
+    /// 1. [writable] Knowledge entry account
+    /// 2. [writable] Report queue account (PDA), created by the first report
+    /// 3. [] System program
+    ReportEntry {
+        bond: u64,
+    },
+
+    /// Settle an entry's spam reports, closing its report queue. Upheld: the
+    /// bonds are refunded and the entry stays inactive. Rejected: the bonds
+    /// go to the entry owner and the entry is reactivated. The queue's rent
+    /// returns to the first reporter.
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [] Protocol account
+    /// 2. [writable] Knowledge entry account
+    /// 3. [writable] Report queue account (PDA)
+    /// 4. [writable] Knowledge entry owner
+    /// 5. [writable] Remaining accounts: the reporters, in report order
+    ResolveReports {
+        uphold: bool,
+    },
+
+    /// Offer `reward` SAGE for knowledge answering `query_hash`, escrowed in
+    /// the bounty's vault until an answer is accepted or `deadline` passes
+    /// Accounts:
+    /// 0. [writable, signer] Requester
+    /// 1. [] Protocol account
+    /// 2. [writable] Bounty account (PDA)
+    /// 3. [writable] Bounty vault (PDA)
+    /// 4. [writable] Requester's SAGE token account
+    /// 5. [] SAGE mint
+    /// 6. [] Token program
+    /// 7. [] System program
+    CreateBounty {
+        query_hash: [u8; 32],
+        reward: u64,
+        deadline: i64,
+    },
+
+    /// Put an active entry forward as an answer to an open bounty before its
+    /// deadline
+    /// Accounts:
+    /// 0. [writable, signer] Staker owning the entry
+    /// 1. [writable] Bounty account
+    /// 2. [] Knowledge entry account
+    /// 3. [writable] Bounty answer account (PDA)
+    /// 4. [] System program
+    SubmitAnswer,
+
+    /// Accept an answer, paying its staker the escrowed reward and closing
+    /// the bounty's vault, its rent returned to the requester. An answer can
+    /// be accepted until the bounty is reclaimed.
+    /// Accounts:
+    /// 0. [writable, signer] Requester
+    /// 1. [] Protocol account
+    /// 2. [writable] Bounty account
+    /// 3. [] Bounty answer account
+    /// 4. [writable] Bounty vault (PDA)
+    /// 5. [writable] Answering staker's SAGE token account
+    /// 6. [] Mint authority (PDA)
+    /// 7. [] Token program
+    AcceptAnswer,
+
+    /// Refund an unanswered bounty once its deadline has passed, closing the
+    /// bounty and its vault and returning their rent to the requester
+    /// Accounts:
+    /// 0. [writable, signer] Requester
+    /// 1. [] Protocol account
+    /// 2. [writable] Bounty account
+    /// 3. [writable] Bounty vault (PDA)
+    /// 4. [writable] SAGE token account receiving the refund
+    /// 5. [] Mint authority (PDA)
+    /// 6. [] Token program
+    ReclaimExpiredBounty,
+
+    /// Stream attribution rewards to their entry over `reward_stream_secs`
+    /// instead of vesting each one; 0 goes back to vesting. Attributions
+    /// recorded in an epoch still vest.
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetRewardStream {
+        reward_stream_secs: i64,
+    },
+
+    /// Set the shares of each attribution's reward paid to the treasury and
+    /// curators and burned, the rest going to the entry. The curator share
+    /// is the one `SetCuratorShare` sets.
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetFeeConfig {
+        fee_config: FeeConfig,
+    },
+
+    /// Pay out the treasury's share of attribution rewards
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    /// 2. [writable] SAGE mint, or the reward vault PDA when attributions
+    ///    fund rewards
+    /// 3. [writable] Authority's SAGE token account
+    /// 4. [] Mint authority PDA
+    /// 5. [] Token program
+    ClaimTreasuryRewards,
+
+    /// Reward nobody for attributions scored below `low_relevance_threshold`
+    /// and burn `low_relevance_burn_bps` of their reward instead, which is
+    /// all the payer pays for them; a threshold of 0 turns this off
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetLowRelevanceBurn {
+        low_relevance_threshold: u8,
+        low_relevance_burn_bps: u16,
+    },
+
+    /// Burn the reward vault's SAGE owed to burns from payer-funded
+    /// attributions. Anyone may burn.
+    /// Accounts:
+    /// 0. [writable] Protocol account
+    /// 1. [writable] Reward vault (PDA)
+    /// 2. [writable] SAGE mint
+    /// 3. [] Mint authority PDA
+    /// 4. [] Token program
+    BurnRewards,
+
+    /// Set the share of a derivative entry's attribution rewards set aside
+    /// for the entries it is derived from
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetCitationRoyalty {
+        citation_royalty_bps: u16,
+    },
+
+    /// Pay the royalties set aside on a derivative entry to its parents,
+    /// split evenly between them. Anyone may pay. A parent that has been
+    /// closed forfeits its share.
+    /// Accounts:
+    /// 0. [writable] Derivative knowledge entry account
+    /// 1. [writable] Remaining accounts: each parent entry, in
+    ///    `derived_from` order
+    PayCitationRoyalties,
+
+    /// Create a document to be staked as chunk entries, so attributions can
+    /// target single passages. Each chunk is staked as an entry and then
+    /// placed with `AddDocumentChunk`; its rewards go to the document owner.
+    /// Accounts:
+    /// 0. [writable, signer] Owner
+    /// 1. [writable] Document PDA
+    /// 2. [] Category the chunks are filed under
+    /// 3. [] System program
+    StakeDocument {
+        /// Canonical hash of the whole document
+        content_hash: [u8; 32],
+        title: String,
+        chunk_count: u16,
+    },
+
+    /// Make an entry the document's chunk at `chunk_index`; the entry's
+    /// content hash is the chunk's. The entry must be the owner's, filed
+    /// under the document's category and a chunk of no other document, and
+    /// can't change owner from then on.
+    /// Accounts:
+    /// 0. [signer] Document owner
+    /// 1. [writable] Document PDA
+    /// 2. [writable] Knowledge entry account
+    AddDocumentChunk {
+        chunk_index: u16,
+    },
+
+    /// Total the attributions and pending rewards of a document's chunks
+    /// onto the document. Anyone may refresh.
+    /// Accounts:
+    /// 0. [writable] Document PDA
+    /// 1. [] Remaining accounts: each chunk entry added so far, in chunk
+    ///    order
+    RefreshDocument,
+
+    /// Publish a fingerprint of the entry's content embedding for retrievers
+    /// to filter entries by similarity, or clear it with `None`
+    /// Accounts:
+    /// 0. [writable, signer] Staker, paying to grow the entry if needed
+    /// 1. [writable] Knowledge entry account
+    /// 2. [] System program
+    UpdateEmbeddingFingerprint {
+        embedding_fingerprint: Option<[u8; KnowledgeEntry::EMBEDDING_FINGERPRINT_LEN]>,
+    },
+
+    /// Approve `reviewer` to attest entries
+    /// Accounts:
+    /// 0. [writable, signer] Protocol authority
+    /// 1. [] Protocol account
+    /// 2. [writable] Reviewer registry account (PDA), created on first use
+    /// 3. [] System program
+    AddReviewer {
+        reviewer: Pubkey,
+    },
+
+    /// Withdraw `reviewer`'s approval; entries it attested stay verified
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [] Protocol account
+    /// 2. [writable] Reviewer registry account (PDA)
+    RemoveReviewer {
+        reviewer: Pubkey,
+    },
+
+    /// Attest an entry's `accuracy` and `freshness`, out of
+    /// `solsage_core::MAX_ATTESTATION_SCORE`, replacing any earlier
+    /// attestation. Scoring both at least `solsage_core::MIN_VERIFIED_SCORE`
+    /// verifies the entry, boosting its rewards until its content changes.
+    /// Accounts:
+    /// 0. [writable, signer] Approved reviewer, paying to grow the entry if
+    ///    needed
+    /// 1. [] Reviewer registry account (PDA)
+    /// 2. [writable] Knowledge entry account
+    /// 3. [] System program
+    AttestEntry {
+        accuracy: u8,
+        freshness: u8,
+    },
+
+    /// Take stakers' identity attestations from `identity_config`'s
+    /// attestation program under its credential, requiring one of every
+    /// stake if it says so; `None` stops taking them
+    /// Accounts:
+    /// 0. [signer] Protocol authority
+    /// 1. [writable] Protocol account
+    SetIdentityConfig {
+        identity_config: Option<IdentityConfig>,
+    },
+
+    /// Make the entry public, or private to payers holding an access pass
+    /// Accounts:
+    /// 0. [writable, signer] Staker, paying to grow the entry if needed
+    /// 1. [writable] Knowledge entry account
+    /// 2. [] System program
+    SetEntryVisibility {
+        visibility: Visibility,
+    },
+
+    /// Give `holder` an access pass to the entry, free of charge
+    /// Accounts:
+    /// 0. [writable, signer] Staker
+    /// 1. [] Knowledge entry account
+    /// 2. [writable] Access pass PDA of `holder`
+    /// 3. [] System program
+    GrantAccess {
+        holder: Pubkey,
+    },
+
+    /// Buy an access pass to a private entry from its owner at its access
+    /// price, which must still be `price`
+    /// Accounts:
+    /// 0. [writable, signer] Buyer
+    /// 1. [] Knowledge entry account
+    /// 2. [writable] Entry owner, paid the price
+    /// 3. [writable] Access pass PDA of the buyer
+    /// 4. [] System program
+    BuyAccess {
+        price: u64,
+    },
+}
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+/// Leading byte of accounts initialized before they were tagged with their
+/// type, which is the `is_initialized` flag the tag stands in for
+pub const UNTAGGED_DISCRIMINATOR: u8 = 1;
+
+/// State kept in a program-owned account behind a leading discriminator and
+/// `schema_version`. Accounts are allocated for the longest strings they may
+/// hold, so the encoded state can be shorter than the account.
+///
+/// The discriminator takes the place of the `is_initialized` flag the state
+/// encodes first: zero while uninitialized and the type's tag once stored,
+/// so no account decodes as another type.
+pub trait ProgramAccount: BorshSerialize + BorshDeserialize {
+    /// Space allocated for the current layout
+    const LEN: usize;
+
+    /// Space of the fields appended to the layout since schema versions were
+    /// introduced
+    const APPENDED_LEN: usize = 0;
+
+    /// Shortest account holding the current schema version: ones allocated
+    /// before fields were appended to it, which read them as zeros
+    const MIN_LEN: usize = Self::LEN - Self::APPENDED_LEN;
+
+    /// Schema version stamped on accounts written under the current layout
+    const SCHEMA_VERSION: u8 = SCHEMA_VERSION;
+
+    /// Type of the state, which its discriminator tags
+    const ACCOUNT_TYPE: AccountType;
+
+    /// Leading byte of an initialized account holding this type
+    const DISCRIMINATOR: u8 = Self::ACCOUNT_TYPE.discriminator();
+
+    fn is_initialized(&self) -> bool;
+
+    /// Schema version of the state in `data`, or `None` if it holds none.
+    /// Accounts from before schema versions lack the version byte and the
+    /// appended fields, and read as version 0.
+    fn stored_schema_version(data: &[u8]) -> Option<u8> {
+        match data.first() {
+            None | Some(0) => None,
+            Some(&UNTAGGED_DISCRIMINATOR) if data.len() == Self::LEN - Self::APPENDED_LEN - 1 => Some(0),
+            Some(_) => data.get(1).copied(),
+        }
+    }
+
+    /// Deserialize `account`, rejecting accounts owned by another program or
+    /// that `unpack` would
+    fn load(program_id: &Pubkey, account: &AccountInfo) -> Result<Self, ProgramError> {
+        if account.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        Self::unpack(&account.data.borrow())
+    }
+
+    /// Decode account `data`, ignoring the padding after the encoded state and
+    /// rejecting the accounts `check_header` would
+    fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::check_header(data)?;
+
+        // The discriminator reads as the flag it replaces, and fields
+        // appended since the account was allocated read as zeros until it is
+        // resized
+        let padding = Self::LEN.saturating_sub(data.len()) as u64;
+        let mut reader = [true as u8].as_slice().chain(&data[1..]).chain(io::repeat(0).take(padding));
+        Ok(Self::deserialize_reader(&mut reader)?)
+    }
+
+    /// Reject account `data` never initialized, holding another type or still
+    /// awaiting `MigrateAccount`
+    fn check_header(data: &[u8]) -> ProgramResult {
+        match data.first() {
+            None | Some(0) => return Err(ProgramError::UninitializedAccount),
+            Some(&UNTAGGED_DISCRIMINATOR) => return Err(SolSageError::AccountNeedsMigration.into()),
+            Some(&discriminator) if discriminator != Self::DISCRIMINATOR => {
+                return Err(SolSageError::AccountTypeMismatch.into());
+            }
+            Some(_) => {}
+        }
+
+        if Self::stored_schema_version(data) != Some(Self::SCHEMA_VERSION) || data.len() < Self::MIN_LEN {
+            return Err(SolSageError::AccountNeedsMigration.into());
+        }
+        Ok(())
+    }
+
+    /// Serialize into `account` as `pack_into` does
+    fn store(&self, account: &AccountInfo) -> ProgramResult {
+        self.pack_into(&mut account.data.borrow_mut())
+    }
+
+    /// Serialize into account `data` behind the type's discriminator, zeroing
+    /// the padding after the encoded state so a shortened string leaves no
+    /// stale bytes behind. Accounts allocated before trailing fields were
+    /// added can store them only while they encode as the zeros `unpack`
+    /// reads them as.
+    fn pack_into(&self, data: &mut [u8]) -> ProgramResult {
+        let mut encoded = self.try_to_vec()?;
+        if self.is_initialized() {
+            encoded[0] = Self::DISCRIMINATOR;
+        }
+        let (stored, truncated) = encoded.split_at(encoded.len().min(data.len()));
+        if truncated.iter().any(|byte| *byte != 0) {
+            return Err(SolSageError::AccountNeedsMigration.into());
+        }
+        data[..stored.len()].copy_from_slice(stored);
+        data[stored.len()..].fill(0);
+        Ok(())
+    }
+
+    /// Rearrange the state in `data`, already versioned and grown to `LEN`,
+    /// from the layout of schema version `from_version` into the current one
+    fn upgrade_layout(_data: &mut [u8], _from_version: u8) -> ProgramResult {
+        Ok(())
+    }
+}
+
+macro_rules! impl_program_account {
+    ($($account:ident $(=> $appended:expr)?),* $(,)?) => {
+        $(impl ProgramAccount for $account {
+            const LEN: usize = $account::LEN;
+            $(const APPENDED_LEN: usize = $appended;)?
+            const ACCOUNT_TYPE: AccountType = AccountType::$account;
+
+            fn is_initialized(&self) -> bool {
+                self.is_initialized
+            }
+        })*
+    };
+}
+
+// Fields appended since schema versions were introduced follow `=>`
+impl_program_account!(
+    Protocol => {
+        RewardSource::LEN + 1 + AuthorityType::LEN * 2 + 8 + 2 + 8 + 8 + 8 + 1 + 8 + 1 + ProposalAction::LEN + 8 + 8 + 2
+            + 33 + 8 + 8 + 33 + 2 + 1 + 1 + 8 + 8 + 8 + FeeConfig::LEN + 8 + 8 + 1 + 2 + 8 + 2 + 1 + IdentityConfig::LEN
+    },
+    Attribution => 33 + 33 + 1 + 4 + ConsensusVote::LEN * Protocol::MAX_CONSENSUS_ORACLES + 1 + 5,
+    QueryRecord,
+    AttributionOracle,
+    OracleState => 8 + 8 + 8 + 8 + 8,
+    PayerStats,
+    Snapshot,
+    BridgeEmitter,
+    Listing,
+    StakerProfile,
+    Challenge,
+    ContentRegistry,
+    Category => 8,
+    AttributionRoot,
+    Epoch,
+    CuratorAccount,
+    Collection,
+    TagIndex,
+    Subscription,
+    AgentStats,
+    Proposal,
+    VoteRecord,
+    EntryVote,
+    ReportQueue,
+    Bounty,
+    BountyAnswer,
+    StakerIndex => 8,
+    Document,
+    ReviewerRegistry,
+    AccessPass,
+);
+
+impl ProgramAccount for KnowledgeEntry {
+    const LEN: usize = KnowledgeEntry::LEN;
+    const APPENDED_LEN: usize = KnowledgeEntry::TAGS_LEN + 8 + LockupTier::LEN + 8 + 8 + 33 + 8 + 1 + RewardStream::LEN
+        + 4 + 32 * KnowledgeEntry::MAX_PARENT_ENTRIES + 8 + 8 + 33 + 2 + 1 + ChunkCommitment::LEN
+        + 1 + KnowledgeEntry::EMBEDDING_FINGERPRINT_LEN + 1 + ReviewAttestation::LEN + 1 + 33 + Visibility::LEN;
+    // Version 2 entries were laid out with the tags and lockup start, and may
+    // lack only what was appended since
+    const MIN_LEN: usize = KnowledgeEntry::LEN - (Self::APPENDED_LEN - KnowledgeEntry::TAGS_LEN - 8);
+    const SCHEMA_VERSION: u8 = KNOWLEDGE_ENTRY_SCHEMA_VERSION;
+    const ACCOUNT_TYPE: AccountType = AccountType::KnowledgeEntry;
+
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+
+    fn upgrade_layout(data: &mut [u8], from_version: u8) -> ProgramResult {
+        if from_version < 2 {
+            KnowledgeEntry::move_filterable_fields(data)?;
+        }
+        Ok(())
+    }
+}
+
+impl ProgramAccount for Leaderboard {
+    const LEN: usize = Leaderboard::LEN;
+    const SCHEMA_VERSION: u8 = LEADERBOARD_SCHEMA_VERSION;
+    const ACCOUNT_TYPE: AccountType = AccountType::Leaderboard;
+
+    fn is_initialized(&self) -> bool {
+        self.is_initialized != 0
+    }
+
+    /// Leaderboards postdate schema versions; a version 1 board is one byte
+    /// short of the current layout, as a version 0 account would be, but
+    /// carries its version byte
+    fn stored_schema_version(data: &[u8]) -> Option<u8> {
+        match data.first() {
+            None | Some(0) => None,
+            Some(_) => data.get(1).copied(),
+        }
+    }
+
+    fn upgrade_layout(data: &mut [u8], from_version: u8) -> ProgramResult {
+        if from_version < 2 {
+            Leaderboard::fill_slots(data)?;
+        }
+        Ok(())
+    }
+}
+
+/// State held by an account passed to `MigrateAccount`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountType {
+    Protocol,
+    KnowledgeEntry,
+    Attribution,
+    QueryRecord,
+    AttributionOracle,
+    StakerProfile,
+    Challenge,
+    ContentRegistry,
+    Category,
+    AttributionRoot,
+    Epoch,
+    CuratorAccount,
+    Collection,
+    TagIndex,
+    Subscription,
+    AgentStats,
+    Leaderboard,
+    Proposal,
+    VoteRecord,
+    StakerIndex,
+    OracleState,
+    Snapshot,
+    BridgeEmitter,
+    Listing,
+    PayerStats,
+    EntryVote,
+    ReportQueue,
+    Bounty,
+    BountyAnswer,
+    Document,
+    ReviewerRegistry,
+    AccessPass,
+}
+
+impl AccountType {
+    /// Schema version an account of this type migrates to
+    pub fn schema_version(self) -> u8 {
+        match self {
+            AccountType::KnowledgeEntry => KNOWLEDGE_ENTRY_SCHEMA_VERSION,
+            AccountType::Leaderboard => LEADERBOARD_SCHEMA_VERSION,
+            _ => SCHEMA_VERSION,
+        }
+    }
+
+    /// Leading byte of an initialized account of this type, past the zero of
+    /// an uninitialized account and the flag of an untagged one
+    pub const fn discriminator(self) -> u8 {
+        self as u8 + UNTAGGED_DISCRIMINATOR + 1
+    }
+}
+
+/// Where attribution rewards come from
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RewardSource {
+    /// Minted by the protocol when claimed
+    Mint,
+    /// Transferred by the attribution payer into the reward vault as each
+    /// attribution is recorded, and paid out of it when claimed
+    Payer,
+}
+
+impl RewardSource {
+    pub const LEN: usize = 1;
+}
+
+/// Shares of each attribution's reward carved out before the rest goes to
+/// the entry
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeConfig {
+    /// Share accrued to the treasury, claimed by the authority
+    pub treasury_bps: u16,
+    /// Share paid to the curator that surfaced the attribution
+    pub curator_bps: u16,
+    /// Share never paid out, keeping it out of the SAGE supply
+    pub burn_bps: u16,
+}
+
+impl FeeConfig {
+    pub const LEN: usize = 2 + 2 + 2;
+
+    /// Whether the curator share is within its cap and the shares together
+    /// take at most the whole reward
+    pub fn is_valid(&self) -> bool {
+        let total = u32::from(self.treasury_bps) + u32::from(self.curator_bps) + u32::from(self.burn_bps);
+        self.curator_bps <= Protocol::MAX_CURATOR_BPS && total <= u32::from(Protocol::MAX_BPS)
+    }
+
+    /// Split `reward` into its shares, or `None` if they take more than all
+    /// of it
+    pub fn split(&self, reward: u64) -> Option<RewardShares> {
+        let curator = solsage_core::apply_bps(reward, self.curator_bps)?;
+        let treasury = solsage_core::apply_bps(reward, self.treasury_bps)?;
+        let burned = solsage_core::apply_bps(reward, self.burn_bps)?;
+        let entry = reward.checked_sub(curator)?.checked_sub(treasury)?.checked_sub(burned)?;
+        Some(RewardShares { entry, curator, treasury, burned })
+    }
+}
+
+/// Attestation program and credential stakers prove their identity under
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdentityConfig {
+    /// Program owning the attestation accounts, such as the Solana
+    /// Attestation Service
+    pub attestation_program: Pubkey,
+    /// Credential, naming the issuer, attestations must be under
+    pub credential: Pubkey,
+    /// Rejects stakes without an attestation
+    pub required: bool,
+}
+
+impl IdentityConfig {
+    pub const LEN: usize = 32 + 32 + 1;
+
+    /// Check `attestation_account` holds a live attestation issued to
+    /// `staker` under the credential, returning the credential hash its
+    /// entry records
+    pub fn verify(&self, staker: &Pubkey, attestation_account: &AccountInfo, now: i64) -> Result<[u8; 32], ProgramError> {
+        if *attestation_account.owner != self.attestation_program {
+            return Err(SolSageError::InvalidIdentityAttestation.into());
+        }
+        let data = attestation_account.try_borrow_data()?;
+        let attestation = identity::attestation(&data).ok_or(SolSageError::InvalidIdentityAttestation)?;
+        if attestation.credential != self.credential || attestation.nonce != *staker {
+            return Err(SolSageError::InvalidIdentityAttestation.into());
+        }
+        if attestation.is_expired(now) {
+            return Err(SolSageError::IdentityAttestationExpired.into());
+        }
+        Ok(attestation.credential_hash())
+    }
+}
+
+/// An attribution's reward split by a `FeeConfig`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RewardShares {
+    pub entry: u64,
+    pub curator: u64,
+    pub treasury: u64,
+    pub burned: u64,
+}
+
+impl RewardShares {
+    /// SAGE awarded: everything but the burned share
+    pub fn awarded(&self) -> Option<u64> {
+        self.entry.checked_add(self.curator)?.checked_add(self.treasury)
+    }
+
+    /// The whole reward the shares were split from
+    pub fn total(&self) -> Option<u64> {
+        self.awarded()?.checked_add(self.burned)
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(Self {
+            entry: self.entry.checked_add(other.entry)?,
+            curator: self.curator.checked_add(other.curator)?,
+            treasury: self.treasury.checked_add(other.treasury)?,
+            burned: self.burned.checked_add(other.burned)?,
+        })
+    }
+}
+
+/// Who holds the protocol authority. Admin instructions take the authority
+/// as their signer either way; a multisig's PDA signs them through CPI once
+/// its members approve.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthorityType {
+    Keypair,
+    /// Native treasury of `governance`, under the SPL Governance deployment
+    /// `program_id`
+    SplGovernance { program_id: Pubkey, governance: Pubkey },
+    /// Vault `vault_index` of a Squads `multisig`
+    Squads { multisig: Pubkey, vault_index: u8 },
+}
+
+impl AuthorityType {
+    pub const LEN: usize = 1 + 32 + 32;
+
+    /// Whether `authority` is the key this type signs with; any key can be
+    /// a keypair
+    pub fn signs_as(&self, authority: &Pubkey) -> bool {
+        match self {
+            AuthorityType::Keypair => true,
+            AuthorityType::SplGovernance { program_id, governance } => {
+                solsage_core::multisig::governance_treasury(program_id, governance) == *authority
+            }
+            AuthorityType::Squads { multisig, vault_index } => {
+                solsage_core::multisig::squads_vault(multisig, *vault_index) == *authority
+            }
+        }
+    }
+}
+
+/// Settings and running totals at `[Protocol::SEED]`. Its optional and
+/// enum-valued settings keep it on Borsh rather than plain data; instructions
+/// decode and store it once each, attributions and batches included.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Protocol {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub authority: Pubkey,
+    pub pending_authority: Option<Pubkey>,
+    pub reward_mint: Pubkey,
+    pub total_knowledge_entries: u64,
+    pub total_attributions: u64,
+    pub reward_per_attribution: u64,
+    pub max_supply: u64,
+    pub vesting_period_secs: i64,
+    pub reward_curve: RewardCurve,
+    pub fee_bps: u16,
+    /// Lamports paid into the treasury as attribution fees
+    pub fees_collected: u64,
+    /// Smallest stake `StakeKnowledge` accepts
+    pub min_stake_lamports: u64,
+    /// Minimum gap between two attributions to the same entry
+    pub attribution_cooldown_secs: i64,
+    /// Emergency switch rejecting stakes, attributions and claims
+    pub is_paused: bool,
+    /// Relevance score at which an attribution earns the full reward rate
+    pub score_divisor: u16,
+    /// Length of each reward epoch; 0 pays every attribution its own reward
+    pub epoch_duration_secs: i64,
+    /// SAGE shared out by each epoch opened under the current schedule
+    pub epoch_reward_budget: u64,
+    /// Epochs opened so far; the open epoch is `epoch_count - 1`
+    pub epoch_count: u64,
+    /// When the latest epoch stops taking attributions
+    pub epoch_ends_at: i64,
+    /// Share of an attribution's reward paid to the curator that surfaced it
+    pub curator_bps: u16,
+    pub bump: u8,
+    pub mint_authority_bump: u8,
+    pub treasury_bump: u8,
+    pub reward_source: RewardSource,
+    pub reward_vault_bump: u8,
+    pub authority_type: AuthorityType,
+    /// Type of `pending_authority`, applied when it accepts
+    pub pending_authority_type: AuthorityType,
+    /// SAGE that must be voted on a proposal for it to pass
+    pub quorum_votes: u64,
+    /// Share of the votes cast a proposal must beat in favour
+    pub approval_threshold_bps: u16,
+    /// How long proposals take votes; 0 turns governance off
+    pub voting_period_secs: i64,
+    /// Wait between a proposal's vote ending and its execution
+    pub execution_delay_secs: i64,
+    /// Proposals created so far; the next is at index `proposal_count`
+    pub proposal_count: u64,
+    pub governance_vault_bump: u8,
+    /// Notice the authority must give of a parameter change; 0 lets its
+    /// setters apply immediately
+    pub admin_delay_secs: i64,
+    pub queued_change: Option<ProposalAction>,
+    /// When `queued_change` can first be executed
+    pub queued_change_executable_at: i64,
+    /// Age at which an entry's rewards have halved; 0 turns decay off
+    pub score_half_life_secs: i64,
+    /// Share of the full reward the oldest entries still earn
+    pub min_score_weight_bps: u16,
+    /// Program attributions must be made under; `None` takes them from
+    /// oracles directly
+    pub attribution_verifier: Option<Pubkey>,
+    /// How long vested rewards stay claimable before anyone can expire them;
+    /// 0 keeps them claimable forever
+    pub claim_window_secs: i64,
+    /// SAGE awarded by attributions, curator shares included
+    pub total_rewards: u64,
+    /// Bubblegum tree entry receipts are minted from, if any
+    pub receipt_tree: Option<Pubkey>,
+    /// Share of each entry sale's price paid to the treasury
+    pub sale_fee_bps: u16,
+    /// Matching oracle reports an attribution waits on before it finalizes;
+    /// 0 finalizes each report on its own
+    pub consensus_threshold: u8,
+    /// Most a report's relevance score may differ from the first report's
+    /// and still match it
+    pub consensus_tolerance: u8,
+    /// Seconds a pending attribution takes further oracles' scores after
+    /// the first; 0 finalizes each report on its own
+    pub aggregation_window_secs: i64,
+    /// Attributions one payer may record a day; 0 leaves payers uncapped
+    pub max_payer_attributions_per_day: u64,
+    /// Seconds each entry's attribution rewards stream over; 0 vests them
+    /// attribution by attribution instead
+    pub reward_stream_secs: i64,
+    /// Shares of each attribution's reward paid to the treasury and curators
+    /// and burned
+    pub fee_config: FeeConfig,
+    /// Treasury share of attribution rewards not yet claimed
+    pub treasury_rewards: u64,
+    /// SAGE burned out of attribution rewards
+    pub rewards_burned: u64,
+    /// Relevance score below which attributions reward nobody; 0 rewards
+    /// every score
+    pub low_relevance_threshold: u8,
+    /// Share of a low-relevance attribution's reward burned
+    pub low_relevance_burn_bps: u16,
+    /// Burned SAGE payers funded into the reward vault, awaiting
+    /// `BurnRewards`
+    pub pending_burn: u64,
+    /// Share of a derivative entry's attribution rewards set aside for the
+    /// entries it is derived from
+    pub citation_royalty_bps: u16,
+    /// Where stakers' identity attestations come from, if the protocol
+    /// takes them
+    pub identity_config: Option<IdentityConfig>,
+}
+
+impl Protocol {
+    pub const LEN: usize =
+        1 + 1 + 32 + 33 + 32 + 8 + 8 + 8 + 8 + 8 + 1 + 2 + 8 + 8 + 8 + 1 + 2 + 8 + 8 + 8 + 8 + 2 + 1 + 1 + 1
+            + RewardSource::LEN + 1 + AuthorityType::LEN * 2 + 8 + 2 + 8 + 8 + 8 + 1 + 8 + 1 + ProposalAction::LEN + 8 + 8 + 2
+            + 33 + 8 + 8 + 33 + 2 + 1 + 1 + 8 + 8 + 8 + FeeConfig::LEN + 8 + 8 + 1 + 2 + 8 + 2 + 1 + IdentityConfig::LEN;
+    pub const SEED: &'static [u8] = seeds::PROTOCOL;
+    pub const REWARD_VAULT_SEED: &'static [u8] = seeds::REWARD_VAULT;
+    pub const GOVERNANCE_VAULT_SEED: &'static [u8] = seeds::GOVERNANCE_VAULT;
+    pub const MINT_SEED: &'static [u8] = seeds::MINT;
+    pub const MINT_AUTHORITY_SEED: &'static [u8] = seeds::MINT_AUTHORITY;
+    pub const RECEIPT_AUTHORITY_SEED: &'static [u8] = seeds::RECEIPT_AUTHORITY;
+    pub const TREASURY_SEED: &'static [u8] = seeds::TREASURY;
+    pub const MAX_BPS: u16 = solsage_core::MAX_BPS;
+    pub const MAX_FEE_BPS: u16 = solsage_core::MAX_FEE_BPS;
+
+    /// Reject the call while the protocol is paused
+    pub fn check_not_paused(&self) -> ProgramResult {
+        if self.is_paused {
+            return Err(SolSageError::ProtocolPaused.into());
+        }
+        Ok(())
+    }
+
+    /// Lamport fee owed on `reward`, or `None` on overflow
+    pub fn fee_on(&self, reward: u64) -> Option<u64> {
+        solsage_core::apply_bps(reward, self.fee_bps)
+    }
+
+    /// Reject an attribution not made under the attribution verifier, when
+    /// one is set: the transaction's current top-level instruction must be
+    /// to the verifier, so this program can only be running in a CPI it led
+    /// to
+    pub fn check_attribution_caller(&self, instructions_account: Option<&AccountInfo>) -> ProgramResult {
+        let Some(verifier) = self.attribution_verifier else {
+            return Ok(());
+        };
+        let instructions_account = instructions_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let current = load_current_index_checked(instructions_account)?;
+        if load_instruction_at_checked(current.into(), instructions_account)?.program_id != verifier {
+            return Err(SolSageError::AttributionNotVerified.into());
+        }
+        Ok(())
+    }
+    pub const MINT_DECIMALS: u8 = solsage_core::MINT_DECIMALS;
+    pub const MIN_REWARD_PER_ATTRIBUTION: u64 = solsage_core::MIN_REWARD_PER_ATTRIBUTION;
+    pub const MAX_REWARD_PER_ATTRIBUTION: u64 = solsage_core::MAX_REWARD_PER_ATTRIBUTION;
+    pub const DEFAULT_VESTING_PERIOD_SECS: i64 = solsage_core::DEFAULT_VESTING_PERIOD_SECS;
+    pub const MAX_VESTING_PERIOD_SECS: i64 = solsage_core::MAX_VESTING_PERIOD_SECS;
+    pub const MAX_ATTRIBUTION_COOLDOWN_SECS: i64 = solsage_core::MAX_ATTRIBUTION_COOLDOWN_SECS;
+    pub const DEFAULT_SCORE_DIVISOR: u16 = solsage_core::DEFAULT_SCORE_DIVISOR;
+    pub const MIN_SCORE_DIVISOR: u16 = solsage_core::MIN_SCORE_DIVISOR;
+    pub const MAX_SCORE_DIVISOR: u16 = solsage_core::MAX_SCORE_DIVISOR;
+    pub const MIN_EPOCH_DURATION_SECS: i64 = solsage_core::MIN_EPOCH_DURATION_SECS;
+    pub const MAX_EPOCH_DURATION_SECS: i64 = solsage_core::MAX_EPOCH_DURATION_SECS;
+    pub const MAX_CURATOR_BPS: u16 = solsage_core::MAX_CURATOR_BPS;
+    pub const MIN_VOTING_PERIOD_SECS: i64 = solsage_core::MIN_VOTING_PERIOD_SECS;
+    pub const MAX_VOTING_PERIOD_SECS: i64 = solsage_core::MAX_VOTING_PERIOD_SECS;
+    pub const MAX_EXECUTION_DELAY_SECS: i64 = solsage_core::MAX_EXECUTION_DELAY_SECS;
+    pub const MAX_ADMIN_DELAY_SECS: i64 = solsage_core::MAX_ADMIN_DELAY_SECS;
+    pub const MIN_SCORE_HALF_LIFE_SECS: i64 = solsage_core::MIN_SCORE_HALF_LIFE_SECS;
+    pub const MAX_SCORE_HALF_LIFE_SECS: i64 = solsage_core::MAX_SCORE_HALF_LIFE_SECS;
+    pub const MIN_CLAIM_WINDOW_SECS: i64 = solsage_core::MIN_CLAIM_WINDOW_SECS;
+    pub const EXPIRY_BOUNTY_BPS: u16 = solsage_core::EXPIRY_BOUNTY_BPS;
+    pub const MAX_CONSENSUS_ORACLES: usize = solsage_core::MAX_CONSENSUS_ORACLES;
+    pub const MAX_AGGREGATION_WINDOW_SECS: i64 = solsage_core::MAX_AGGREGATION_WINDOW_SECS;
+    pub const MIN_REWARD_STREAM_SECS: i64 = solsage_core::MIN_REWARD_STREAM_SECS;
+    pub const MAX_REWARD_STREAM_SECS: i64 = solsage_core::MAX_REWARD_STREAM_SECS;
+    pub const MAX_CITATION_ROYALTY_BPS: u16 = solsage_core::MAX_CITATION_ROYALTY_BPS;
+
+    /// Whether attributions accrue weight in epochs instead of minting their
+    /// own reward
+    pub fn epochs_enabled(&self) -> bool {
+        self.epoch_duration_secs > 0
+    }
+
+    /// Whether SAGE holders can propose and vote on parameter changes
+    pub fn governance_enabled(&self) -> bool {
+        self.voting_period_secs > 0
+    }
+
+    /// Share of the full reward earned by an attribution at `now` to an
+    /// entry staked at `created_at`
+    pub fn score_weight_bps(&self, created_at: i64, now: i64) -> u16 {
+        solsage_core::score_decay_bps(now.saturating_sub(created_at), self.score_half_life_secs, self.min_score_weight_bps)
+    }
+
+    /// Reject an immediate parameter change while the authority must queue
+    /// them
+    pub fn check_no_admin_delay(&self) -> ProgramResult {
+        if self.admin_delay_secs > 0 {
+            return Err(SolSageError::ChangeMustBeQueued.into());
+        }
+        Ok(())
+    }
+
+    /// Reject the call when rewards are funded by attribution payers; epoch
+    /// budgets and attribution roots are only ever paid by minting
+    pub fn check_minted_rewards(&self) -> ProgramResult {
+        if self.reward_source == RewardSource::Payer {
+            return Err(SolSageError::MintedRewardsOnly.into());
+        }
+        Ok(())
+    }
+
+    /// Whether attributions wait on matching reports from several oracles
+    pub fn consensus_enabled(&self) -> bool {
+        self.consensus_threshold > 0
+    }
+
+    /// Whether attributions take scores from several oracles before they
+    /// are finalized
+    pub fn aggregation_enabled(&self) -> bool {
+        self.aggregation_window_secs > 0
+    }
+
+    /// Whether reports leave attributions pending on further reports
+    pub fn reports_pending(&self) -> bool {
+        self.consensus_enabled() || self.aggregation_enabled()
+    }
+
+    /// Whether attribution rewards stream to their entry rather than vest
+    pub fn streams_rewards(&self) -> bool {
+        self.reward_stream_secs > 0
+    }
+
+    /// Shares carved out of an attribution's reward: the curator's when one
+    /// surfaced it, and the treasury's and the burn outside epochs, whose
+    /// rewards are weights rather than SAGE
+    pub fn attribution_fees(&self, curated: bool, in_epoch: bool) -> FeeConfig {
+        let fees = if in_epoch { FeeConfig::default() } else { self.fee_config };
+        FeeConfig { curator_bps: if curated { self.curator_bps } else { 0 }, ..fees }
+    }
+
+    /// Count the rewards of recorded attributions. Burned shares payers
+    /// funded wait in the reward vault to be burned; minted rewards are
+    /// burned by never minting them.
+    pub fn record_reward_shares(&mut self, shares: &RewardShares) -> ProgramResult {
+        let awarded = shares.awarded().ok_or(SolSageError::ArithmeticOverflow)?;
+        self.total_rewards = self.total_rewards.checked_add(awarded).ok_or(SolSageError::ArithmeticOverflow)?;
+        self.treasury_rewards =
+            self.treasury_rewards.checked_add(shares.treasury).ok_or(SolSageError::ArithmeticOverflow)?;
+        self.rewards_burned = self.rewards_burned.checked_add(shares.burned).ok_or(SolSageError::ArithmeticOverflow)?;
+        if self.reward_source == RewardSource::Payer {
+            self.pending_burn = self.pending_burn.checked_add(shares.burned).ok_or(SolSageError::ArithmeticOverflow)?;
+        }
+        Ok(())
+    }
+
+    /// Replace the shares of an attribution scored below the low-relevance
+    /// threshold with a burn of `low_relevance_burn_bps` of its reward.
+    /// Epoch rewards are weights, so a low-relevance one just adds none.
+    pub fn apply_low_relevance(&self, shares: RewardShares, relevance_score: u8, in_epoch: bool) -> Option<RewardShares> {
+        if relevance_score >= self.low_relevance_threshold {
+            return Some(shares);
+        }
+        let burned = if in_epoch { 0 } else { solsage_core::apply_bps(shares.total()?, self.low_relevance_burn_bps)? };
+        Some(RewardShares { burned, ..RewardShares::default() })
+    }
+
+    /// Reject attributions recorded in bulk by one oracle while attributions
+    /// wait on several oracles' reports; each must be reported on its own
+    pub fn check_single_reports(&self) -> ProgramResult {
+        if self.reports_pending() {
+            return Err(SolSageError::ConsensusRequired.into());
+        }
+        Ok(())
+    }
+}
+
+/// A staked entry at `[KnowledgeEntry::SEED, creator, entry_index]`. Its
+/// title, URI and tags are variable-length, so unlike the leaderboard it stays
+/// on Borsh; an attribution decodes and stores it once.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct KnowledgeEntry {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    /// Current owner; receives rewards and the stake
+    pub staker: Pubkey,
+    /// Original staker, kept because it seeds the entry PDA
+    pub creator: Pubkey,
+    pub entry_index: u64,
+    pub content_hash: [u8; 32],
+    /// Category account, fixed at stake time
+    pub category: Pubkey,
+    pub is_active: bool,
+    /// Terms the content may be used under. It and the fields before it sit
+    /// at fixed offsets so entries can be filtered by them.
+    pub license: LicenseType,
+    pub title: String,
+    /// Off-chain location of the content, e.g. an Arweave or IPFS URI
+    pub content_uri: Option<String>,
+    /// Collection the entry is grouped under, if any
+    pub collection: Option<Pubkey>,
+    /// Co-authors paid a share of each claim
+    pub royalty_splits: Vec<RoyaltySplit>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// Attributions are rejected from this time until the entry is renewed
+    pub expires_at: i64,
+    pub last_attribution_at: i64,
+    pub version: u32,
+    pub total_attributions: u64,
+    pub pending_rewards: u64,
+    pub vesting_schedule: [VestingBucket; KnowledgeEntry::VESTING_BUCKETS],
+    pub stake_amount: u64,
+    pub bump: u8,
+    pub escrow_bump: u8,
+    /// Free-form labels for finer-grained discovery than the category
+    pub tags: Vec<String>,
+    /// Smallest reward an attribution may pay; 0 sets no floor
+    pub min_reward_per_use: u64,
+    /// Lockup chosen at stake time, boosting every reward the entry earns
+    pub lockup_tier: LockupTier,
+    /// The stake can't be withdrawn before this time
+    pub locked_until: i64,
+    /// When `RequestUnstake` started the cooldown; 0 if it hasn't
+    pub unstake_requested_at: i64,
+    /// Compressed NFT receipt minted for the entry at stake time, if any
+    pub receipt_asset_id: Option<Pubkey>,
+    /// SAGE voted up on the entry less SAGE voted down, scaling every reward
+    /// it earns
+    pub quality_score: i64,
+    /// Deactivated by spam reports; stays inactive and staked until the
+    /// authority resolves them
+    pub under_review: bool,
+    /// Rewards streaming to the entry while the protocol streams rewards;
+    /// they count towards `pending_rewards` until claimed
+    pub reward_stream: RewardStream,
+    /// Entries the content is derived from, fixed at stake time
+    pub derived_from: Vec<Pubkey>,
+    /// Royalties set aside from the entry's attributions for its parents,
+    /// waiting for `PayCitationRoyalties`
+    pub citation_royalties: u64,
+    /// Royalties entries derived from this one have paid it; they count
+    /// towards `pending_rewards` until claimed
+    pub citation_rewards: u64,
+    /// Document the entry is a chunk of, if any
+    pub document: Option<Pubkey>,
+    /// Position of the entry's passage in `document`
+    pub chunk_index: u16,
+    /// Chunks of the content committed to at stake time, if any
+    pub chunks: Option<ChunkCommitment>,
+    /// Locality-sensitive hash of the content's embedding, so retrievers
+    /// can filter entries by similarity from on-chain data
+    pub embedding_fingerprint: Option<[u8; KnowledgeEntry::EMBEDDING_FINGERPRINT_LEN]>,
+    /// Latest reviewer attestation of the content, if any
+    pub attestation: Option<ReviewAttestation>,
+    /// Whether an approved reviewer verified the current content
+    pub verified: bool,
+    /// `identity::StakerAttestation::credential_hash` of the identity the
+    /// staker presented, for retrievers to weight entries by trust
+    pub identity_credential: Option<[u8; 32]>,
+    pub visibility: Visibility,
+}
+
+impl KnowledgeEntry {
+    pub const LEN: usize = 1 + 1 + 32 + 32 + 8 + 32 + 32 + 1 + LicenseType::LEN + 4 + Self::MAX_TITLE_LEN
+        + 1 + 4 + Self::MAX_CONTENT_URI_LEN + 33
+        + 4 + RoyaltySplit::LEN * Self::MAX_CO_AUTHORS + 8 + 8 + 8 + 8 + 4 + 8 + 8
+        + VestingBucket::LEN * Self::VESTING_BUCKETS + 8 + 1 + 1 + Self::TAGS_LEN + 8 + LockupTier::LEN + 8 + 8 + 33 + 8 + 1
+        + RewardStream::LEN + 4 + 32 * Self::MAX_PARENT_ENTRIES + 8 + 8 + 33 + 2 + 1 + ChunkCommitment::LEN
+        + 1 + Self::EMBEDDING_FINGERPRINT_LEN + 1 + ReviewAttestation::LEN + 1 + 33 + Visibility::LEN;
+    pub const TAGS_LEN: usize = 4 + (4 + Self::MAX_TAG_LEN) * Self::MAX_TAGS;
+    pub const MAX_TAGS: usize = solsage_core::MAX_TAGS;
+    pub const MAX_TAG_LEN: usize = solsage_core::MAX_TAG_LEN;
+    /// Byte offsets of the fields entries can be filtered by
+    pub const STAKER_OFFSET: usize = 1 + 1;
+    pub const CATEGORY_OFFSET: usize = Self::STAKER_OFFSET + 32 + 32 + 8 + 32;
+    pub const IS_ACTIVE_OFFSET: usize = Self::CATEGORY_OFFSET + 32;
+    pub const LICENSE_OFFSET: usize = Self::IS_ACTIVE_OFFSET + 1;
+    pub const SEED: &'static [u8] = seeds::KNOWLEDGE;
+    pub const ESCROW_SEED: &'static [u8] = seeds::ESCROW;
+    pub const MAX_TITLE_LEN: usize = solsage_core::MAX_TITLE_LEN;
+    pub const MAX_CONTENT_URI_LEN: usize = solsage_core::MAX_CONTENT_URI_LEN;
+    pub const UNSTAKE_COOLDOWN_SECS: i64 = solsage_core::UNSTAKE_COOLDOWN_SECS;
+    pub const VESTING_BUCKETS: usize = solsage_core::VESTING_BUCKETS;
+    pub const TERM_SECS: i64 = solsage_core::TERM_SECS;
+    pub const RENEWAL_FEE_LAMPORTS: u64 = solsage_core::RENEWAL_FEE_LAMPORTS;
+    pub const MAX_CO_AUTHORS: usize = solsage_core::MAX_CO_AUTHORS;
+    pub const MAX_PARENT_ENTRIES: usize = solsage_core::MAX_PARENT_ENTRIES;
+    pub const EMBEDDING_FINGERPRINT_LEN: usize = solsage_core::EMBEDDING_FINGERPRINT_LEN;
+    pub const VERIFIED_BOOST_BPS: u16 = solsage_core::VERIFIED_BOOST_BPS;
+
+    /// Check `chunk` against the entry's committed chunks, returning the
+    /// index of the chunk it proves, if the attribution names one
+    pub fn verify_chunk(&self, chunk: Option<&ChunkProof>) -> Result<Option<u32>, ProgramError> {
+        match (chunk, &self.chunks) {
+            (None, _) => Ok(None),
+            (Some(_), None) => Err(SolSageError::NoChunkCommitment.into()),
+            (Some(chunk), Some(chunks)) if chunks.verify(chunk) => Ok(Some(chunk.index)),
+            (Some(_), Some(_)) => Err(SolSageError::InvalidChunkProof.into()),
+        }
+    }
+
+    /// Scale `reward` by the entry's quality votes, boosted once reviewers
+    /// have verified it
+    pub fn apply_quality(&self, reward: u64) -> Option<u64> {
+        let reward = solsage_core::apply_bps(reward, solsage_core::quality_multiplier_bps(self.quality_score))?;
+        match self.verified {
+            true => solsage_core::apply_bps(reward, Self::VERIFIED_BOOST_BPS),
+            false => Some(reward),
+        }
+    }
+
+    /// Count `weight` SAGE voted up or down on the entry
+    pub fn record_quality_vote(&mut self, upvote: bool, weight: u64) -> ProgramResult {
+        let weight = i64::try_from(weight).map_err(|_| SolSageError::ArithmeticOverflow)?;
+        let weight = if upvote { weight } else { -weight };
+        self.quality_score = self.quality_score.checked_add(weight).ok_or(SolSageError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// When a requested unstake can complete: after the cooldown from the
+    /// request, and no earlier than the lockup ends
+    pub fn unstake_unlocks_at(&self) -> Result<i64, ProgramError> {
+        let unlocks_at = self
+            .unstake_requested_at
+            .checked_add(Self::UNSTAKE_COOLDOWN_SECS)
+            .ok_or(SolSageError::ArithmeticOverflow)?;
+        Ok(unlocks_at.max(self.locked_until))
+    }
+
+    /// Grow or shrink `account` to the current layout, so entries allocated
+    /// before a field was added can hold it. Growing tops up the rent from
+    /// `payer`; shrinking refunds the rent no longer needed to it.
+    pub fn resize<'info>(
+        account: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        rent: &Rent,
+    ) -> ProgramResult {
+        let old_len = account.data_len();
+        if old_len == Self::LEN {
+            return Ok(());
+        }
+
+        // Entries hold nothing but their rent; the stake is in the escrow
+        let required = rent.minimum_balance(Self::LEN);
+        let lamports = account.lamports();
+        if lamports < required {
+            invoke(
+                &system_instruction::transfer(payer.key, account.key, required - lamports),
+                &[payer.clone(), account.clone(), system_program.clone()],
+            )?;
+        } else if old_len > Self::LEN {
+            let refund = lamports - required;
+            **account.try_borrow_mut_lamports()? -= refund;
+            **payer.try_borrow_mut_lamports()? += refund;
+        }
+
+        account.realloc(Self::LEN, true)
+    }
+
+    /// Move `category` and `is_active` of a version 1 entry in `data` from
+    /// behind the variable-length fields ahead of `license`, rejecting data
+    /// too short to hold them
+    fn move_filterable_fields(data: &mut [u8]) -> ProgramResult {
+        let len = data.len();
+        let mut rest = data.get(Self::CATEGORY_OFFSET..).ok_or(ProgramError::InvalidAccountData)?;
+        LicenseType::deserialize(&mut rest)?;
+        String::deserialize(&mut rest)?;
+        Option::<String>::deserialize(&mut rest)?;
+        let category_at = len - rest.len();
+        Pubkey::deserialize(&mut rest)?;
+        Option::<Pubkey>::deserialize(&mut rest)?;
+        Vec::<RoyaltySplit>::deserialize(&mut rest)?;
+        // Timestamps, version, counters, vesting schedule and stake amount
+        let is_active_at = len - rest.len() + 8 + 8 + 8 + 8 + 4 + 8 + 8
+            + VestingBucket::LEN * Self::VESTING_BUCKETS + 8;
+
+        let category: [u8; 32] = data
+            .get(category_at..category_at + 32)
+            .and_then(|category| category.try_into().ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        let is_active = *data.get(is_active_at).ok_or(ProgramError::InvalidAccountData)?;
+        data.copy_within(category_at + 32..is_active_at, category_at + 33);
+        data.copy_within(Self::CATEGORY_OFFSET..category_at, Self::LICENSE_OFFSET);
+        data[Self::CATEGORY_OFFSET..Self::IS_ACTIVE_OFFSET].copy_from_slice(&category);
+        data[Self::IS_ACTIVE_OFFSET] = is_active;
+        Ok(())
+    }
+
+    /// Add `tag`, rejecting an empty or oversized tag, a repeat, or one past
+    /// `MAX_TAGS`
+    pub fn add_tag(&mut self, tag: String) -> ProgramResult {
+        if tag.is_empty() || tag.len() > Self::MAX_TAG_LEN {
+            return Err(SolSageError::InvalidTag.into());
+        }
+        if self.tags.contains(&tag) {
+            return Err(SolSageError::DuplicateTag.into());
+        }
+        if self.tags.len() >= Self::MAX_TAGS {
+            return Err(SolSageError::TooManyTags.into());
+        }
+        self.tags.push(tag);
+        Ok(())
+    }
+
+    /// Remove `tag`, rejecting one the entry doesn't carry
+    pub fn remove_tag(&mut self, tag: &str) -> ProgramResult {
+        let position = self.tags.iter().position(|t| t == tag).ok_or(SolSageError::TagNotFound)?;
+        self.tags.remove(position);
+        Ok(())
+    }
+
+    /// Replace the content URI, rejecting an empty one or one that doesn't fit
+    pub fn set_content_uri(&mut self, content_uri: Option<String>) -> ProgramResult {
+        if content_uri
+            .as_ref()
+            .is_some_and(|uri| uri.is_empty() || uri.len() > Self::MAX_CONTENT_URI_LEN)
+        {
+            return Err(SolSageError::InvalidContentUri.into());
+        }
+        self.content_uri = content_uri;
+        Ok(())
+    }
+
+    /// Replace the co-author splits, rejecting too many co-authors, zero or
+    /// repeated shares, the owner as a co-author and shares above 100%
+    pub fn set_royalty_splits(&mut self, splits: Vec<RoyaltySplit>) -> ProgramResult {
+        if splits.len() > Self::MAX_CO_AUTHORS {
+            return Err(SolSageError::InvalidRoyaltySplit.into());
+        }
+
+        let mut total_bps: u16 = 0;
+        for (i, split) in splits.iter().enumerate() {
+            if split.bps == 0
+                || split.co_author == self.staker
+                || splits[..i].iter().any(|other| other.co_author == split.co_author)
+            {
+                return Err(SolSageError::InvalidRoyaltySplit.into());
+            }
+            total_bps = total_bps
+                .checked_add(split.bps)
+                .filter(|bps| *bps <= Protocol::MAX_BPS)
+                .ok_or(SolSageError::InvalidRoyaltySplit)?;
+        }
+
+        self.royalty_splits = splits;
+        Ok(())
+    }
+
+    /// Split a claim of `amount` into the owner's part and each co-author's
+    /// share, in split order. Shares round down, so the owner keeps the dust.
+    pub fn split_royalties(&self, amount: u64) -> Option<(u64, Vec<u64>)> {
+        let shares = self
+            .royalty_splits
+            .iter()
+            .map(|split| solsage_core::apply_bps(amount, split.bps))
+            .collect::<Option<Vec<_>>>()?;
+        let staker_amount = shares.iter().try_fold(amount, |rest, share| rest.checked_sub(*share))?;
+        Some((staker_amount, shares))
+    }
+
+    /// Record an attribution at `now`, rejecting it if the previous one was
+    /// less than `cooldown_secs` ago
+    pub fn mark_attributed(&mut self, now: i64, cooldown_secs: i64) -> ProgramResult {
+        let ready_at = self
+            .last_attribution_at
+            .checked_add(cooldown_secs)
+            .ok_or(SolSageError::ArithmeticOverflow)?;
+        if self.total_attributions > 0 && now < ready_at {
+            return Err(SolSageError::AttributionCooldown.into());
+        }
+        self.last_attribution_at = now;
+        Ok(())
+    }
+
+    /// Add `amount` to pending rewards, locked until `now + vesting_period_secs`.
+    /// Unlock times are rounded up to 1/7th of the period so nearby accruals
+    /// share a bucket, and under a steady period no more unlock times are
+    /// ever locked than there are buckets. Should the period have changed and
+    /// every bucket still be locked, the amount joins the nearest bucket
+    /// unlocking later; with none, the accrual is rejected. No bucket's
+    /// unlock time ever moves. Returns the unlock time.
+    pub fn accrue_rewards(
+        &mut self,
+        amount: u64,
+        now: i64,
+        vesting_period_secs: i64,
+    ) -> Result<i64, ProgramError> {
+        let pending_rewards = self
+            .pending_rewards
+            .checked_add(amount)
+            .ok_or(SolSageError::ArithmeticOverflow)?;
+        if vesting_period_secs <= 0 || amount == 0 {
+            self.pending_rewards = pending_rewards;
+            return Ok(now);
+        }
+
+        let steps = Self::VESTING_BUCKETS as i64 - 1;
+        let granularity = (vesting_period_secs.saturating_add(steps - 1) / steps).max(1);
+        let unlocks_at = now
+            .checked_add(vesting_period_secs)
+            .and_then(|t| t.checked_add(granularity - 1))
+            .ok_or(SolSageError::ArithmeticOverflow)?
+            / granularity
+            * granularity;
+
+        let unlocks_at = if let Some(bucket) = self.vesting_schedule.iter_mut().find(|b| b.unlocks_at == unlocks_at) {
+            bucket.amount = bucket.amount.checked_add(amount).ok_or(SolSageError::ArithmeticOverflow)?;
+            unlocks_at
+        } else if let Some(bucket) = self.vesting_schedule.iter_mut().find(|b| b.unlocks_at <= now) {
+            *bucket = VestingBucket { unlocks_at, amount };
+            unlocks_at
+        } else {
+            let bucket = self
+                .vesting_schedule
+                .iter_mut()
+                .filter(|b| b.unlocks_at > unlocks_at)
+                .min_by_key(|b| b.unlocks_at)
+                .ok_or(SolSageError::VestingScheduleFull)?;
+            bucket.amount = bucket.amount.checked_add(amount).ok_or(SolSageError::ArithmeticOverflow)?;
+            bucket.unlocks_at
+        };
+        self.pending_rewards = pending_rewards;
+        Ok(unlocks_at)
+    }
+
+    /// Add `amount` to pending rewards and to the entry's reward stream,
+    /// which pays everything still streaming out over the next
+    /// `reward_stream_secs`
+    pub fn stream_rewards(&mut self, amount: u64, now: i64, reward_stream_secs: i64) -> ProgramResult {
+        self.pending_rewards = self
+            .pending_rewards
+            .checked_add(amount)
+            .ok_or(SolSageError::ArithmeticOverflow)?;
+        self.reward_stream.extend(amount, now, reward_stream_secs)
+    }
+
+    /// Deduct and return the rewards streamed to the entry by `now`
+    pub fn claim_streamed_rewards(&mut self, now: i64) -> Result<u64, ProgramError> {
+        let streamed = self.reward_stream.take_accrued(now)?;
+        self.pending_rewards = self
+            .pending_rewards
+            .checked_sub(streamed)
+            .ok_or(SolSageError::NoRewardsToClaim)?;
+        Ok(streamed)
+    }
+
+    /// Set aside `citation_royalty_bps` of `reward` for the entry's parents,
+    /// returning what the entry keeps. An entry derived from nothing keeps
+    /// it all.
+    pub fn set_aside_citation_royalty(&mut self, reward: u64, citation_royalty_bps: u16) -> Result<u64, ProgramError> {
+        if self.derived_from.is_empty() {
+            return Ok(reward);
+        }
+        let royalty = solsage_core::apply_bps(reward, citation_royalty_bps).ok_or(SolSageError::ArithmeticOverflow)?;
+        self.citation_royalties = self
+            .citation_royalties
+            .checked_add(royalty)
+            .ok_or(SolSageError::ArithmeticOverflow)?;
+        Ok(reward - royalty)
+    }
+
+    /// Take the royalties set aside for the entry's parents, split evenly
+    /// between them, and return each parent's share in `derived_from` order.
+    /// The dust goes a unit each to the first parents, so nothing is left
+    /// set aside.
+    pub fn take_citation_royalties(&mut self) -> Vec<u64> {
+        let parents = self.derived_from.len() as u64;
+        if parents == 0 {
+            return Vec::new();
+        }
+        let royalties = std::mem::take(&mut self.citation_royalties);
+        let (share, dust) = (royalties / parents, royalties % parents);
+        (0..parents).map(|parent| share + u64::from(parent < dust)).collect()
+    }
+
+    /// Add a royalty paid by an entry derived from this one to its pending
+    /// rewards
+    pub fn receive_citation_royalty(&mut self, amount: u64) -> ProgramResult {
+        self.pending_rewards = self
+            .pending_rewards
+            .checked_add(amount)
+            .ok_or(SolSageError::ArithmeticOverflow)?;
+        self.citation_rewards = self
+            .citation_rewards
+            .checked_add(amount)
+            .ok_or(SolSageError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Deduct and return the royalties entries derived from this one have
+    /// paid it
+    pub fn claim_citation_rewards(&mut self) -> Result<u64, ProgramError> {
+        let cited = std::mem::take(&mut self.citation_rewards);
+        self.pending_rewards = self
+            .pending_rewards
+            .checked_sub(cited)
+            .ok_or(SolSageError::NoRewardsToClaim)?;
+        Ok(cited)
+    }
+
+    /// Take back up to `amount` of unclaimed streamed rewards, what has yet
+    /// to stream first; returns how much was actually reversed
+    pub fn reverse_streamed_rewards(&mut self, amount: u64, now: i64) -> Result<u64, ProgramError> {
+        let reversed = self.reward_stream.reverse(amount.min(self.pending_rewards), now)?;
+        self.pending_rewards -= reversed;
+        Ok(reversed)
+    }
+
+    /// Take back up to `amount` of pending rewards, draining the latest locked
+    /// buckets first; returns how much was actually reversed
+    pub fn reverse_rewards(&mut self, amount: u64, now: i64) -> u64 {
+        let reversed = amount.min(self.pending_rewards);
+        self.pending_rewards -= reversed;
+
+        let mut locked: Vec<&mut VestingBucket> = self
+            .vesting_schedule
+            .iter_mut()
+            .filter(|b| b.unlocks_at > now)
+            .collect();
+        locked.sort_by_key(|b| std::cmp::Reverse(b.unlocks_at));
+
+        let mut remaining = reversed;
+        for bucket in locked {
+            let taken = remaining.min(bucket.amount);
+            bucket.amount -= taken;
+            remaining -= taken;
+        }
+
+        reversed
+    }
+
+    /// Split pending rewards into what is claimable at `now` and what is still
+    /// locked, rewards yet to stream included
+    pub fn vested_rewards(&self, now: i64) -> VestedRewards {
+        let streaming = self.reward_stream.remaining - self.reward_stream.streamed_by(now);
+        let locked: u64 = self
+            .vesting_schedule
+            .iter()
+            .filter(|b| b.unlocks_at > now)
+            .fold(streaming, |sum, b| sum.saturating_add(b.amount))
+            .min(self.pending_rewards);
+
+        VestedRewards {
+            claimable: self.pending_rewards - locked,
+            locked,
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct VestingBucket {
+    pub unlocks_at: i64,
+    pub amount: u64,
+}
+
+impl VestingBucket {
+    pub const LEN: usize = 8 + 8;
+}
+
+/// Rewards paid out to an entry second by second rather than as each
+/// attribution vests. Every reward added spreads everything still to stream
+/// over a fresh period.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct RewardStream {
+    /// Rewards streamed a second, scaled by `RewardStream::RATE_SCALE`
+    pub rate_per_sec: u128,
+    /// Rewards left to stream
+    pub remaining: u64,
+    /// Rewards streamed and not yet claimed
+    pub accrued: u64,
+    pub updated_at: i64,
+    /// When the rest of `remaining` has streamed
+    pub ends_at: i64,
+}
+
+impl RewardStream {
+    pub const LEN: usize = 16 + 8 + 8 + 8 + 8;
+    pub const RATE_SCALE: u128 = solsage_core::REWARD_STREAM_RATE_SCALE;
+
+    /// Rewards streamed since the last update, by `now`
+    pub fn streamed_by(&self, now: i64) -> u64 {
+        if now >= self.ends_at {
+            return self.remaining;
+        }
+        solsage_core::streamed_rewards(self.rate_per_sec, now.saturating_sub(self.updated_at))
+            .map_or(self.remaining, |streamed| streamed.min(self.remaining))
+    }
+
+    /// Move what has streamed by `now` into `accrued`
+    pub fn accrue(&mut self, now: i64) -> ProgramResult {
+        // Nothing to stream leaves the stream untouched, so entries never
+        // streamed to can be written back at their old size
+        if self.remaining == 0 {
+            return Ok(());
+        }
+        let streamed = self.streamed_by(now);
+        self.remaining -= streamed;
+        self.accrued = self.accrued.checked_add(streamed).ok_or(SolSageError::ArithmeticOverflow)?;
+        self.updated_at = now;
+        if self.remaining == 0 {
+            self.rate_per_sec = 0;
+        }
+        Ok(())
+    }
+
+    /// Add `amount` to the stream at `now`, spreading everything left to
+    /// stream over the next `duration_secs`
+    pub fn extend(&mut self, amount: u64, now: i64, duration_secs: i64) -> ProgramResult {
+        self.accrue(now)?;
+        self.remaining = self.remaining.checked_add(amount).ok_or(SolSageError::ArithmeticOverflow)?;
+        self.updated_at = now;
+        self.ends_at = now.checked_add(duration_secs).ok_or(SolSageError::ArithmeticOverflow)?;
+        self.rate_per_sec =
+            solsage_core::reward_stream_rate(self.remaining, duration_secs).ok_or(SolSageError::InvalidRewardStream)?;
+        Ok(())
+    }
+
+    /// Take back up to `amount` of the unclaimed rewards, what has yet to
+    /// stream first, leaving the rest to stream until the same end; returns
+    /// how much was taken
+    pub fn reverse(&mut self, amount: u64, now: i64) -> Result<u64, ProgramError> {
+        self.accrue(now)?;
+        let unstreamed = amount.min(self.remaining);
+        self.remaining -= unstreamed;
+        let streamed = (amount - unstreamed).min(self.accrued);
+        self.accrued -= streamed;
+        self.rate_per_sec = if self.remaining == 0 {
+            0
+        } else {
+            solsage_core::reward_stream_rate(self.remaining, self.ends_at.saturating_sub(now)).unwrap_or(0)
+        };
+        Ok(unstreamed + streamed)
+    }
+
+    /// Take every reward streamed by `now`
+    pub fn take_accrued(&mut self, now: i64) -> Result<u64, ProgramError> {
+        self.accrue(now)?;
+        Ok(std::mem::take(&mut self.accrued))
+    }
+}
+
+/// A co-author's share of an entry's claimed rewards
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct RoyaltySplit {
+    pub co_author: Pubkey,
+    pub bps: u16,
+}
+
+impl RoyaltySplit {
+    pub const LEN: usize = 32 + 2;
+}
+
+/// Merkle root an entry commits to over its content's chunks, so
+/// attributions can name the chunk they used without an account per chunk
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkCommitment {
+    /// `merkle::root` over the `merkle::chunk_leaf` of each chunk, in order
+    pub root: [u8; 32],
+    pub chunk_count: u32,
+}
+
+impl ChunkCommitment {
+    pub const LEN: usize = 32 + 4;
+    pub const MAX_PROOF_LEN: usize = solsage_core::MAX_CHUNK_PROOF_LEN;
+    pub const MAX_CHUNKS: u32 = 1 << Self::MAX_PROOF_LEN;
+
+    pub fn validate(&self) -> ProgramResult {
+        if self.chunk_count == 0 || self.chunk_count > Self::MAX_CHUNKS {
+            return Err(SolSageError::InvalidChunkCount.into());
+        }
+        Ok(())
+    }
+
+    /// Whether `chunk` is one of the committed chunks
+    pub fn verify(&self, chunk: &ChunkProof) -> bool {
+        chunk.index < self.chunk_count
+            && chunk.proof.len() <= Self::MAX_PROOF_LEN
+            && solsage_core::merkle::verify(
+                &self.root,
+                solsage_core::merkle::chunk_leaf(chunk.index, &chunk.chunk_hash),
+                &chunk.proof,
+            )
+    }
+}
+
+/// Proof that chunk `index`, hashed to `chunk_hash`, is in an entry's
+/// `ChunkCommitment`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChunkProof {
+    pub index: u32,
+    pub chunk_hash: [u8; 32],
+    /// Sibling hashes from the chunk's leaf up to the root
+    pub proof: Vec<[u8; 32]>,
+}
+
+/// A reviewer's scores for an entry's content
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReviewAttestation {
+    pub reviewer: Pubkey,
+    pub accuracy: u8,
+    pub freshness: u8,
+    /// Content the scores are for
+    pub content_hash: [u8; 32],
+    pub attested_at: i64,
+}
+
+impl ReviewAttestation {
+    pub const LEN: usize = 32 + 1 + 1 + 32 + 8;
+    pub const MAX_SCORE: u8 = solsage_core::MAX_ATTESTATION_SCORE;
+    pub const MIN_VERIFIED_SCORE: u8 = solsage_core::MIN_VERIFIED_SCORE;
+
+    /// Whether the scores are high enough to verify the entry
+    pub fn verifies(&self) -> bool {
+        self.accuracy >= Self::MIN_VERIFIED_SCORE && self.freshness >= Self::MIN_VERIFIED_SCORE
+    }
+}
+
+/// License a knowledge entry's content is offered under
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum LicenseType {
+    Mit,
+    CcBy,
+    Proprietary,
+    /// Terms published at `uri`
+    Custom { uri: String },
+}
+
+impl LicenseType {
+    pub const LEN: usize = 1 + 4 + Self::MAX_URI_LEN;
+    pub const MAX_URI_LEN: usize = solsage_core::MAX_LICENSE_URI_LEN;
+
+    /// Reject a custom license without a URI or with one that doesn't fit
+    pub fn validate(&self) -> ProgramResult {
+        match self {
+            LicenseType::Custom { uri } if uri.is_empty() || uri.len() > Self::MAX_URI_LEN => {
+                Err(SolSageError::InvalidLicense.into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Who may record attributions to an entry
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    Public,
+    /// Only payers holding an access pass, sold for `access_price` lamports
+    Private { access_price: u64 },
+}
+
+impl Visibility {
+    pub const LEN: usize = 1 + 8;
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct VestedRewards {
+    pub claimable: u64,
+    pub locked: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Attribution {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub knowledge_entry: Pubkey,
+    pub query_hash: [u8; 32],
+    pub relevance_score: u8,
+    /// Reward credited to the entry, kept so a challenge can reverse it.
+    /// Within an epoch this is the attribution's weight, not SAGE.
+    pub reward: u64,
+    pub timestamp: i64,
+    /// When `reward` vests and becomes claimable
+    pub unlocks_at: i64,
+    pub reward_claimed: bool,
+    /// Set once an upheld challenge has reversed the reward
+    pub reversed: bool,
+    /// Epoch the attribution was recorded in, if epochs were enabled
+    pub epoch: Option<u64>,
+    pub bump: u8,
+    /// Consuming agent and model, when the oracle reported them
+    pub agent_id: Option<[u8; 32]>,
+    pub model_id: Option<[u8; 32]>,
+    /// Set while the attribution waits on more matching oracle reports; it
+    /// credits no reward until then
+    pub pending: bool,
+    /// Reports of the oracles that agreed on the attribution, under
+    /// consensus
+    pub votes: Vec<ConsensusVote>,
+    /// Set when the reward was added to the entry's reward stream; it is
+    /// claimed from the stream, not the attribution
+    pub streamed: bool,
+    /// Chunk of the entry the attribution proved it used, if any
+    pub chunk_index: Option<u32>,
+}
+
+impl Attribution {
+    pub const LEN: usize =
+        1 + 1 + 32 + 32 + 1 + 8 + 8 + 8 + 1 + 1 + 9 + 1 + 33 + 33 + 1 + 4 + ConsensusVote::LEN * Protocol::MAX_CONSENSUS_ORACLES + 1 + 5;
+    pub const SEED: &'static [u8] = seeds::ATTRIBUTION;
+
+    /// Count `oracle`'s report of the pending attribution toward consensus.
+    /// Reports match when their scores are within the protocol's tolerance of
+    /// the first; once its threshold of them is met this returns the average
+    /// score the attribution finalizes at. Under score aggregation every
+    /// score counts, the relevance score tracks their running average and
+    /// the attribution waits on `FinalizeAttribution` instead.
+    pub fn cast_vote(&mut self, oracle: Pubkey, relevance_score: u8, protocol: &Protocol) -> Result<Option<u8>, ProgramError> {
+        if self.votes.iter().any(|vote| vote.oracle == oracle) {
+            return Err(SolSageError::OracleAlreadyVoted.into());
+        }
+
+        if self.votes.len() >= Protocol::MAX_CONSENSUS_ORACLES {
+            return Err(SolSageError::AttributionReportsFull.into());
+        }
+
+        if !protocol.aggregation_enabled() && self.relevance_score.abs_diff(relevance_score) > protocol.consensus_tolerance {
+            return Err(SolSageError::ConsensusScoreMismatch.into());
+        }
+
+        self.votes.push(ConsensusVote { oracle, relevance_score });
+        if protocol.aggregation_enabled() {
+            self.relevance_score = self.average_score();
+            return Ok(None);
+        }
+
+        if self.votes.len() < usize::from(protocol.consensus_threshold) {
+            return Ok(None);
+        }
+        Ok(Some(self.average_score()))
+    }
+
+    /// Average relevance score of the reports counted so far
+    pub fn average_score(&self) -> u8 {
+        let total: u32 = self.votes.iter().map(|vote| u32::from(vote.relevance_score)).sum();
+        total.checked_div(self.votes.len() as u32).map_or(self.relevance_score, |average| average as u8)
+    }
+
+    /// Whether scores arrive too late to count: under aggregation, once its
+    /// window since the first has closed
+    pub fn aggregation_closed(&self, protocol: &Protocol, now: i64) -> bool {
+        now >= self.timestamp.saturating_add(protocol.aggregation_window_secs)
+    }
+}
+
+/// An oracle's report of an attribution under consensus
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ConsensusVote {
+    pub oracle: Pubkey,
+    pub relevance_score: u8,
+}
+
+impl ConsensusVote {
+    pub const LEN: usize = 32 + 1;
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct QueryRecord {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub query_hash: [u8; 32],
+    pub oracle: Pubkey,
+    pub attribution_count: u8,
+    pub first_attributed_at: i64,
+    pub bump: u8,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct AttributionOracle {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub oracle: Pubkey,
+    pub added_at: i64,
+    pub bump: u8,
+}
+
+impl AttributionOracle {
+    pub const LEN: usize = 1 + 1 + 32 + 8 + 1;
+    pub const SEED: &'static [u8] = seeds::ORACLE;
+}
+
+/// Receipts relayed for an oracle and its attribution budget, at
+/// `[OracleState::SEED, oracle]`. Receipt nonces must increase, so each
+/// signed receipt lands once whoever relays it; the budget caps what a
+/// compromised oracle can award before it is removed.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct OracleState {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub oracle: Pubkey,
+    /// Lowest nonce a receipt may still carry
+    pub next_nonce: u64,
+    pub bump: u8,
+    /// Attributions the oracle may record per budget epoch; 0 leaves them
+    /// uncapped
+    pub max_attributions_per_epoch: u64,
+    /// SAGE the oracle's attributions may award per budget epoch; 0 leaves
+    /// it uncapped
+    pub max_rewards_per_epoch: u64,
+    /// Budget epoch the spent counts below belong to
+    pub budget_epoch: u64,
+    pub epoch_attributions: u64,
+    pub epoch_rewards: u64,
+}
+
+impl OracleState {
+    pub const LEN: usize = 1 + 1 + 32 + 8 + 1 + 8 + 8 + 8 + 8 + 8;
+    pub const SEED: &'static [u8] = seeds::ORACLE_STATE;
+    pub const BUDGET_EPOCH_SECS: i64 = solsage_core::ORACLE_BUDGET_EPOCH_SECS;
+
+    /// Charge `attributions` awarding `rewards` at `now` to the budget,
+    /// refilling it first when a new budget epoch has begun
+    pub fn spend_budget(&mut self, now: i64, attributions: u64, rewards: u64) -> ProgramResult {
+        let epoch =
+            u64::try_from(now.div_euclid(Self::BUDGET_EPOCH_SECS)).map_err(|_| SolSageError::ArithmeticOverflow)?;
+        if epoch != self.budget_epoch {
+            self.budget_epoch = epoch;
+            self.epoch_attributions = 0;
+            self.epoch_rewards = 0;
+        }
+
+        let epoch_attributions = self
+            .epoch_attributions
+            .checked_add(attributions)
+            .ok_or(SolSageError::ArithmeticOverflow)?;
+        let epoch_rewards = self.epoch_rewards.checked_add(rewards).ok_or(SolSageError::ArithmeticOverflow)?;
+        if (self.max_attributions_per_epoch > 0 && epoch_attributions > self.max_attributions_per_epoch)
+            || (self.max_rewards_per_epoch > 0 && epoch_rewards > self.max_rewards_per_epoch)
+        {
+            return Err(SolSageError::OracleBudgetExceeded.into());
+        }
+        self.epoch_attributions = epoch_attributions;
+        self.epoch_rewards = epoch_rewards;
+        Ok(())
+    }
+
+    /// Spend `nonce`, and every nonce below it
+    pub fn use_nonce(&mut self, nonce: u64) -> ProgramResult {
+        if nonce < self.next_nonce {
+            return Err(SolSageError::ReceiptNonceUsed.into());
+        }
+        self.next_nonce = nonce.checked_add(1).ok_or(SolSageError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+/// Attributions a wallet has paid for, at `[PayerStats::SEED, payer]`, so the
+/// protocol can cap how many one payer records a day
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct PayerStats {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub payer: Pubkey,
+    pub bump: u8,
+    /// Day, counted in `PayerStats::DAY_SECS` since the Unix epoch, that
+    /// `day_attributions` belongs to
+    pub day: u64,
+    pub day_attributions: u64,
+    pub total_attributions: u64,
+}
+
+impl PayerStats {
+    pub const LEN: usize = 1 + 1 + 32 + 1 + 8 + 8 + 8;
+    pub const SEED: &'static [u8] = seeds::PAYER_STATS;
+    pub const DAY_SECS: i64 = solsage_core::PAYER_CAP_DAY_SECS;
+
+    /// Count `attributions` paid for at `now` against `cap` a day, starting
+    /// the count over on a new day; a cap of 0 leaves the payer uncapped
+    pub fn record_attributions(&mut self, now: i64, attributions: u64, cap: u64) -> ProgramResult {
+        let day = u64::try_from(now.div_euclid(Self::DAY_SECS)).map_err(|_| SolSageError::ArithmeticOverflow)?;
+        if day != self.day {
+            self.day = day;
+            self.day_attributions = 0;
+        }
+
+        let day_attributions = self
+            .day_attributions
+            .checked_add(attributions)
+            .ok_or(SolSageError::ArithmeticOverflow)?;
+        if cap > 0 && day_attributions > cap {
+            return Err(SolSageError::PayerAttributionCapExceeded.into());
+        }
+        self.day_attributions = day_attributions;
+        self.total_attributions = self
+            .total_attributions
+            .checked_add(attributions)
+            .ok_or(SolSageError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+/// A registered emitter on another chain, at
+/// `[BridgeEmitter::SEED, emitter_chain, emitter_address]`. Message
+/// sequences must increase, so each bridged attribution lands once whoever
+/// relays it.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct BridgeEmitter {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    /// Program owning the messages it posts for the emitter
+    pub bridge: Pubkey,
+    /// Wormhole chain ID
+    pub emitter_chain: u16,
+    pub emitter_address: [u8; 32],
+    /// Lowest sequence a message may still carry
+    pub next_sequence: u64,
+    pub added_at: i64,
+    pub bump: u8,
+}
+
+impl BridgeEmitter {
+    pub const LEN: usize = 1 + 1 + 32 + 2 + 32 + 8 + 8 + 1;
+    pub const SEED: &'static [u8] = seeds::BRIDGE_EMITTER;
+
+    /// Spend `sequence`, and every sequence below it
+    pub fn use_sequence(&mut self, sequence: u64) -> ProgramResult {
+        if sequence < self.next_sequence {
+            return Err(SolSageError::BridgedMessageUsed.into());
+        }
+        self.next_sequence = sequence.checked_add(1).ok_or(SolSageError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+/// Protocol totals as of one day, at `[Snapshot::SEED, day]`, so growth can
+/// be charted without replaying transactions
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Snapshot {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    /// Days since the Unix epoch
+    pub day: u64,
+    pub taken_at: i64,
+    pub total_knowledge_entries: u64,
+    pub total_attributions: u64,
+    pub total_rewards: u64,
+    pub fees_collected: u64,
+    pub bump: u8,
+}
+
+impl Snapshot {
+    pub const LEN: usize = 1 + 1 + 8 + 8 + 8 + 8 + 8 + 8 + 1;
+    pub const SEED: &'static [u8] = seeds::SNAPSHOT;
+    pub const INTERVAL_SECS: i64 = solsage_core::SNAPSHOT_INTERVAL_SECS;
+
+    /// Day a snapshot taken at `now` is filed under
+    pub fn day_of(now: i64) -> Result<u64, ProgramError> {
+        u64::try_from(now.div_euclid(Self::INTERVAL_SECS)).map_err(|_| SolSageError::ArithmeticOverflow.into())
+    }
+}
+
+/// An entry offered for sale, at `[Listing::SEED, knowledge_entry]`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Listing {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub knowledge_entry: Pubkey,
+    /// Owner of the entry when it was listed
+    pub seller: Pubkey,
+    /// Lamports the seller asks for the entry
+    pub price: u64,
+    pub listed_at: i64,
+    pub bump: u8,
+}
+
+impl Listing {
+    pub const LEN: usize = 1 + 1 + 32 + 32 + 8 + 8 + 1;
+    pub const SEED: &'static [u8] = seeds::LISTING;
+}
+
+/// Per-staker reputation, readable by other programs at
+/// `[StakerProfile::SEED, staker]`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct StakerProfile {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub staker: Pubkey,
+    pub total_entries: u64,
+    pub total_attributions: u64,
+    /// Sum of relevance scores across all attributions
+    pub total_relevance: u64,
+    /// SAGE claimed to date
+    pub cumulative_rewards: u64,
+    /// Key allowed to claim rewards on the staker's behalf
+    pub claim_delegate: Option<Pubkey>,
+    pub bump: u8,
+}
+
+impl StakerProfile {
+    pub const LEN: usize = 1 + 1 + 32 + 8 + 8 + 8 + 8 + 33 + 1;
+    pub const SEED: &'static [u8] = seeds::PROFILE;
+
+    pub fn record_attribution(&mut self, relevance_score: u8) -> ProgramResult {
+        self.total_attributions = self
+            .total_attributions
+            .checked_add(1)
+            .ok_or(SolSageError::ArithmeticOverflow)?;
+        self.total_relevance = self
+            .total_relevance
+            .checked_add(relevance_score as u64)
+            .ok_or(SolSageError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    pub fn average_relevance(&self) -> u64 {
+        if self.total_attributions == 0 {
+            return 0;
+        }
+        self.total_relevance / self.total_attributions
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Challenge {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub attribution: Pubkey,
+    pub challenger: Pubkey,
+    pub bond: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl Challenge {
+    pub const LEN: usize = 1 + 1 + 32 + 32 + 8 + 8 + 1;
+    pub const SEED: &'static [u8] = seeds::CHALLENGE;
+    pub const MIN_BOND: u64 = solsage_core::MIN_CHALLENGE_BOND;
+}
+
+/// A reporter's bond behind a spam report
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct SpamReport {
+    pub reporter: Pubkey,
+    pub bond: u64,
+}
+
+impl SpamReport {
+    pub const LEN: usize = 32 + 8;
+}
+
+/// Spam reports against one entry, at `[ReportQueue::SEED, knowledge_entry]`.
+/// The account holds the reporters' bonds on top of its rent until the
+/// reports are resolved.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ReportQueue {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub knowledge_entry: Pubkey,
+    pub reports: Vec<SpamReport>,
+    /// When the reports reached the threshold and deactivated the entry; 0
+    /// until they do
+    pub flagged_at: i64,
+    pub bump: u8,
+}
+
+impl ReportQueue {
+    pub const LEN: usize = 1 + 1 + 32 + 4 + SpamReport::LEN * Self::THRESHOLD + 8 + 1;
+    pub const SEED: &'static [u8] = seeds::REPORT_QUEUE;
+    pub const MIN_BOND: u64 = solsage_core::MIN_REPORT_BOND;
+    pub const THRESHOLD: usize = solsage_core::SPAM_REPORT_THRESHOLD;
+
+    /// Add `reporter`'s report, once per reporter, returning whether it
+    /// brings the reports to the threshold
+    pub fn record_report(&mut self, reporter: Pubkey, bond: u64) -> Result<bool, ProgramError> {
+        if self.reports.iter().any(|report| report.reporter == reporter) {
+            return Err(SolSageError::AlreadyReported.into());
+        }
+        if self.reports.len() >= Self::THRESHOLD {
+            return Err(SolSageError::ReportQueueFull.into());
+        }
+        self.reports.push(SpamReport { reporter, bond });
+        Ok(self.reports.len() == Self::THRESHOLD)
+    }
+
+    /// Lamports bonded behind every report
+    pub fn total_bonds(&self) -> Result<u64, ProgramError> {
+        self.reports
+            .iter()
+            .try_fold(0u64, |total, report| total.checked_add(report.bond))
+            .ok_or_else(|| SolSageError::ArithmeticOverflow.into())
+    }
+}
+
+/// SAGE a requester offers for knowledge answering a query, at
+/// `[Bounty::SEED, requester, query_hash]`. The reward is held in the
+/// bounty's vault, at `[Bounty::VAULT_SEED, bounty]`, until it is paid out or
+/// reclaimed.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Bounty {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub requester: Pubkey,
+    pub query_hash: [u8; 32],
+    pub reward: u64,
+    /// When answers stop being taken and the reward can be reclaimed
+    pub deadline: i64,
+    pub created_at: i64,
+    pub answers: u32,
+    /// Entry whose answer was accepted; the bounty is open until one is
+    pub accepted_entry: Option<Pubkey>,
+    pub bump: u8,
+    pub vault_bump: u8,
+}
+
+impl Bounty {
+    pub const LEN: usize = 1 + 1 + 32 + 32 + 8 + 8 + 8 + 4 + 33 + 1 + 1;
+    pub const SEED: &'static [u8] = seeds::BOUNTY;
+    pub const VAULT_SEED: &'static [u8] = seeds::BOUNTY_VAULT;
+    pub const MAX_DURATION_SECS: i64 = solsage_core::MAX_BOUNTY_DURATION_SECS;
+
+    pub fn is_open(&self) -> bool {
+        self.accepted_entry.is_none()
+    }
+}
+
+/// An entry put forward for a bounty, at
+/// `[BountyAnswer::SEED, bounty, knowledge_entry]`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct BountyAnswer {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub bounty: Pubkey,
+    pub knowledge_entry: Pubkey,
+    /// Entry owner when the answer was submitted, paid if it is accepted
+    pub staker: Pubkey,
+    pub submitted_at: i64,
+    pub bump: u8,
+}
+
+impl BountyAnswer {
+    pub const LEN: usize = 1 + 1 + 32 + 32 + 32 + 8 + 1;
+    pub const SEED: &'static [u8] = seeds::BOUNTY_ANSWER;
+}
+
+/// A document staked as chunk entries, at
+/// `[Document::SEED, owner, content_hash]`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Document {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub owner: Pubkey,
+    /// Canonical hash of the whole document
+    pub content_hash: [u8; 32],
+    pub title: String,
+    /// Category every chunk is filed under
+    pub category: Pubkey,
+    /// Chunk entries by chunk index; the default address until added
+    pub chunks: Vec<Pubkey>,
+    /// Attributions to the chunks as of `refreshed_at`
+    pub total_attributions: u64,
+    /// Rewards the chunks had waiting to be claimed as of `refreshed_at`
+    pub pending_rewards: u64,
+    pub refreshed_at: i64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl Document {
+    pub const LEN: usize = 1 + 1 + 32 + 32 + 4 + Self::MAX_TITLE_LEN + 32 + 4 + 32 * Self::MAX_CHUNKS + 8 + 8 + 8 + 8 + 1;
+    pub const SEED: &'static [u8] = seeds::DOCUMENT;
+    pub const MAX_TITLE_LEN: usize = solsage_core::MAX_TITLE_LEN;
+    pub const MAX_CHUNKS: usize = solsage_core::MAX_DOCUMENT_CHUNKS;
+
+    /// The chunk entries added so far, in chunk order
+    pub fn added_chunks(&self) -> impl Iterator<Item = &Pubkey> {
+        self.chunks.iter().filter(|chunk| **chunk != Pubkey::default())
+    }
+}
+
+/// Reviewers the authority has approved to attest entries, at
+/// `[ReviewerRegistry::SEED]`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ReviewerRegistry {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub reviewers: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl ReviewerRegistry {
+    pub const LEN: usize = 1 + 1 + 4 + 32 * Self::MAX_REVIEWERS + 1;
+    pub const SEED: &'static [u8] = seeds::REVIEWER_REGISTRY;
+    pub const MAX_REVIEWERS: usize = solsage_core::MAX_REVIEWERS;
+
+    pub fn is_reviewer(&self, reviewer: &Pubkey) -> bool {
+        self.reviewers.contains(reviewer)
+    }
+}
+
+/// A payer's pass to record attributions to a private entry, at
+/// `[AccessPass::SEED, knowledge_entry, holder]`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct AccessPass {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub knowledge_entry: Pubkey,
+    pub holder: Pubkey,
+    /// Lamports paid to the entry owner; 0 when granted
+    pub price_paid: u64,
+    pub granted_at: i64,
+    pub bump: u8,
+}
+
+impl AccessPass {
+    pub const LEN: usize = 1 + 1 + 32 + 32 + 8 + 8 + 1;
+    pub const SEED: &'static [u8] = seeds::ACCESS_PASS;
+}
+
+/// First entry staked with a given content hash, at
+/// `[ContentRegistry::SEED, content_hash]`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ContentRegistry {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub content_hash: [u8; 32],
+    pub knowledge_entry: Pubkey,
+    pub staker: Pubkey,
+    pub registered_at: i64,
+    pub bump: u8,
+}
+
+impl ContentRegistry {
+    pub const LEN: usize = 1 + 1 + 32 + 32 + 32 + 8 + 1;
+    pub const SEED: &'static [u8] = seeds::CONTENT;
+}
+
+/// Knowledge category registered by the authority, at
+/// `[Category::SEED, name]`. It doubles as the category's statistics: every
+/// instruction that moves its entries, attributions or rewards already
+/// writes it for its multiplier and counts, so a separate stats account
+/// would cost those instructions another writable account and hold nothing a
+/// reader doesn't get from this one.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Category {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub name: String,
+    /// Open entries staked under this category
+    pub entries: u64,
+    pub attributions: u64,
+    /// Reward scaling for this category's attributions; 10000 is 1x
+    pub multiplier_bps: u16,
+    pub created_at: i64,
+    pub bump: u8,
+    /// Rewards credited to its entries for their attributions
+    pub rewards: u64,
+}
+
+impl Category {
+    pub const LEN: usize = 1 + 1 + 4 + Self::MAX_NAME_LEN + 8 + 8 + 2 + 8 + 1 + 8;
+    pub const SEED: &'static [u8] = seeds::CATEGORY;
+    pub const MAX_NAME_LEN: usize = solsage_core::MAX_CATEGORY_NAME_LEN;
+    pub const DEFAULT_MULTIPLIER_BPS: u16 = solsage_core::DEFAULT_MULTIPLIER_BPS;
+    pub const MAX_MULTIPLIER_BPS: u16 = solsage_core::MAX_MULTIPLIER_BPS;
+
+    /// Scale `reward` by the category multiplier
+    pub fn apply_multiplier(&self, reward: u64) -> Option<u64> {
+        solsage_core::apply_bps(reward, self.multiplier_bps)
+    }
+
+    /// Count an attribution to one of its entries that credited it `reward`
+    pub fn record_attribution(&mut self, reward: u64) -> ProgramResult {
+        self.attributions = self.attributions.checked_add(1).ok_or(SolSageError::ArithmeticOverflow)?;
+        self.rewards = self.rewards.checked_add(reward).ok_or(SolSageError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+impl QueryRecord {
+    pub const LEN: usize = 1 + 1 + 32 + 32 + 1 + 8 + 1;
+    pub const SEED: &'static [u8] = seeds::QUERY;
+    pub const MAX_ATTRIBUTIONS: u8 = solsage_core::MAX_QUERY_ATTRIBUTIONS;
+}
+
+/// Merkle root of one oracle's attributions for an epoch, at
+/// `[AttributionRoot::SEED, oracle, epoch]`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct AttributionRoot {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub oracle: Pubkey,
+    pub epoch: u64,
+    pub root: [u8; 32],
+    /// Leaves in the tree; leaf indices run below this
+    pub attribution_count: u32,
+    pub claimed_count: u32,
+    pub committed_at: i64,
+    /// Claims open once the protocol vesting period has passed
+    pub unlocks_at: i64,
+    /// One bit per leaf, set once its reward is claimed
+    pub claimed: [u8; AttributionRoot::MAX_ATTRIBUTIONS / 8],
+    pub bump: u8,
+}
+
+impl AttributionRoot {
+    pub const LEN: usize = 1 + 1 + 32 + 8 + 32 + 4 + 4 + 8 + 8 + Self::MAX_ATTRIBUTIONS / 8 + 1;
+    pub const SEED: &'static [u8] = seeds::ATTRIBUTION_ROOT;
+    pub const MAX_ATTRIBUTIONS: usize = solsage_core::MAX_ROOT_ATTRIBUTIONS;
+
+    pub fn is_claimed(&self, leaf_index: u32) -> bool {
+        self.claimed[leaf_index as usize / 8] & (1 << (leaf_index % 8)) != 0
+    }
+
+    /// Mark `leaf_index` claimed, failing if it already was
+    pub fn set_claimed(&mut self, leaf_index: u32) -> ProgramResult {
+        if self.is_claimed(leaf_index) {
+            return Err(SolSageError::RewardAlreadyClaimed.into());
+        }
+        self.claimed[leaf_index as usize / 8] |= 1 << (leaf_index % 8);
+        self.claimed_count = self.claimed_count.checked_add(1).ok_or(SolSageError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+/// Fixed SAGE budget shared by the attributions recorded while it was open,
+/// at `[Epoch::SEED, index]`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Epoch {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub index: u64,
+    pub started_at: i64,
+    pub ends_at: i64,
+    pub reward_budget: u64,
+    /// Sum of the weights recorded in the epoch. Reversed attributions keep
+    /// their weight, so their share is never minted.
+    pub total_weight: u64,
+    /// Set once the epoch has ended and its shares are fixed
+    pub is_finalized: bool,
+    pub bump: u8,
+}
+
+impl Epoch {
+    pub const LEN: usize = 1 + 1 + 8 + 8 + 8 + 8 + 8 + 1 + 1;
+    pub const SEED: &'static [u8] = seeds::EPOCH;
+
+    pub fn add_weight(&mut self, weight: u64) -> ProgramResult {
+        self.total_weight = self.total_weight.checked_add(weight).ok_or(SolSageError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// SAGE owed to an attribution of `weight` in this finalized epoch
+    pub fn share_of(&self, weight: u64) -> Result<u64, ProgramError> {
+        if !self.is_finalized {
+            return Err(SolSageError::EpochNotFinalized.into());
+        }
+        solsage_core::epoch_share(self.reward_budget, weight, self.total_weight)
+            .ok_or_else(|| SolSageError::ArithmeticOverflow.into())
+    }
+}
+
+/// Rewards earned by an agent or service for surfacing attributed entries,
+/// at `[CuratorAccount::SEED, curator]`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct CuratorAccount {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub curator: Pubkey,
+    pub attributions: u64,
+    /// SAGE accrued and not yet claimed. Curator shares skip vesting and are
+    /// not reversed when a challenge against the attribution is upheld.
+    pub pending_rewards: u64,
+    pub total_claimed: u64,
+    pub bump: u8,
+}
+
+impl CuratorAccount {
+    pub const LEN: usize = 1 + 1 + 32 + 8 + 8 + 8 + 1;
+    pub const SEED: &'static [u8] = seeds::CURATOR;
+}
+
+/// Named group of related entries, at `[Collection::SEED, owner, name]`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Collection {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub owner: Pubkey,
+    pub name: String,
+    /// Entries currently in the collection
+    pub entries: u32,
+    /// Attributions to entries while they were in the collection
+    pub attributions: u64,
+    /// Rewards credited to those entries for them
+    pub rewards: u64,
+    pub last_attribution_at: i64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl Collection {
+    pub const LEN: usize = 1 + 1 + 32 + 4 + Self::MAX_NAME_LEN + 4 + 8 + 8 + 8 + 8 + 1;
+    pub const SEED: &'static [u8] = seeds::COLLECTION;
+    pub const MAX_NAME_LEN: usize = solsage_core::MAX_COLLECTION_NAME_LEN;
+
+    /// Count an attribution to a member entry that credited it `reward`
+    pub fn record_attribution(&mut self, reward: u64, now: i64) -> ProgramResult {
+        self.attributions = self.attributions.checked_add(1).ok_or(SolSageError::ArithmeticOverflow)?;
+        self.rewards = self.rewards.checked_add(reward).ok_or(SolSageError::ArithmeticOverflow)?;
+        self.last_attribution_at = now;
+        Ok(())
+    }
+}
+
+/// Count of entries carrying one tag, at `[TagIndex::SEED, tag]`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct TagIndex {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub tag: String,
+    pub entries: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl TagIndex {
+    pub const LEN: usize = 1 + 1 + 4 + KnowledgeEntry::MAX_TAG_LEN + 8 + 8 + 1;
+    pub const SEED: &'static [u8] = seeds::TAG;
+}
+
+/// Prepaid SAGE an attribution payer's rewards are drawn from, at
+/// `[Subscription::SEED, consumer]`. Deposits are held in the reward vault.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Subscription {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub consumer: Pubkey,
+    /// Deposited SAGE not yet spent on attributions
+    pub balance: u64,
+    pub total_deposited: u64,
+    pub total_spent: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl Subscription {
+    pub const LEN: usize = 1 + 1 + 32 + 8 + 8 + 8 + 8 + 1;
+    pub const SEED: &'static [u8] = seeds::SUBSCRIPTION;
+}
+
+/// Attributions paid for by one consuming agent, at
+/// `[AgentStats::SEED, agent_id]`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct AgentStats {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub agent_id: [u8; 32],
+    pub attributions: u64,
+    /// Rewards its attributions paid, curator shares included
+    pub rewards: u64,
+    pub last_attribution_at: i64,
+    pub bump: u8,
+}
+
+impl AgentStats {
+    pub const LEN: usize = 1 + 1 + 32 + 8 + 8 + 8 + 1;
+    pub const SEED: &'static [u8] = seeds::AGENT;
+
+    /// Count an attribution by the agent that paid `reward`
+    pub fn record_attribution(&mut self, reward: u64, now: i64) -> ProgramResult {
+        self.attributions = self.attributions.checked_add(1).ok_or(SolSageError::ArithmeticOverflow)?;
+        self.rewards = self.rewards.checked_add(reward).ok_or(SolSageError::ArithmeticOverflow)?;
+        self.last_attribution_at = now;
+        Ok(())
+    }
+}
+
+/// One ranked entry on the leaderboard
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq, Pod, Zeroable)]
+pub struct LeaderboardEntry {
+    pub knowledge_entry: Pubkey,
+    pub total_attributions: u64,
+}
+
+impl LeaderboardEntry {
+    pub const LEN: usize = 32 + 8;
+}
+
+/// The most attributed entries, most first, at `[Leaderboard::SEED]`. Every
+/// attribution reranks it, so it's laid out as plain data and ranked in place
+/// in the account; its Borsh encoding is the same bytes.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Pod, Zeroable)]
+pub struct Leaderboard {
+    pub is_initialized: u8,
+    pub schema_version: u8,
+    pub bump: u8,
+    /// Aligns `slots` for in-place access
+    pub padding: [u8; 1],
+    /// Slots holding ranked entries, from the first
+    pub len: u32,
+    pub slots: [LeaderboardEntry; Leaderboard::MAX_ENTRIES],
+}
+
+impl Leaderboard {
+    pub const LEN: usize = std::mem::size_of::<Self>();
+    pub const SEED: &'static [u8] = seeds::LEADERBOARD;
+    pub const MAX_ENTRIES: usize = solsage_core::LEADERBOARD_SIZE;
+
+    pub fn new(bump: u8) -> Self {
+        Self {
+            is_initialized: <Self as ProgramAccount>::DISCRIMINATOR,
+            schema_version: LEADERBOARD_SCHEMA_VERSION,
+            bump,
+            ..Self::zeroed()
+        }
+    }
+
+    /// Ranked entries, most attributed first
+    pub fn entries(&self) -> &[LeaderboardEntry] {
+        &self.slots[..self.len as usize]
+    }
+
+    /// Borrow the leaderboard in `account` in place, rejecting the accounts
+    /// `load` would
+    pub fn load_mut<'a>(program_id: &Pubkey, account: &'a AccountInfo) -> Result<RefMut<'a, Self>, ProgramError> {
+        if account.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        let data = account.try_borrow_mut_data()?;
+        <Self as ProgramAccount>::check_header(&data)?;
+        RefMut::filter_map(data, |data| {
+            data.get_mut(..Self::LEN).and_then(|data| bytemuck::try_from_bytes_mut(data).ok())
+        })
+        .map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// Rank `knowledge_entry` at `total_attributions`, moving it into place
+    /// with one insertion sort pass. An entry that doesn't beat the last on
+    /// a full board is left off; ties keep the entry ranked first.
+    pub fn record(&mut self, knowledge_entry: Pubkey, total_attributions: u64) {
+        let ranked = LeaderboardEntry { knowledge_entry, total_attributions };
+        let len = self.entries().len();
+        let mut index = match self.entries().iter().position(|entry| entry.knowledge_entry == knowledge_entry) {
+            Some(index) => index,
+            None if len < Self::MAX_ENTRIES => {
+                self.len += 1;
+                len
+            }
+            None => match self.entries().last() {
+                Some(last) if last.total_attributions < total_attributions => len - 1,
+                _ => return,
+            },
+        };
+        let entries = &mut self.slots[..self.len as usize];
+        entries[index] = ranked;
+
+        while index > 0 && entries[index - 1].total_attributions < total_attributions {
+            entries.swap(index - 1, index);
+            index -= 1;
+        }
+        while index + 1 < entries.len() && entries[index + 1].total_attributions > total_attributions {
+            entries.swap(index, index + 1);
+            index += 1;
+        }
+    }
+
+    pub fn remove(&mut self, knowledge_entry: &Pubkey) {
+        let Some(index) = self.entries().iter().position(|entry| entry.knowledge_entry == *knowledge_entry) else {
+            return;
+        };
+        let len = self.len as usize;
+        self.slots.copy_within(index + 1..len, index);
+        self.slots[len - 1] = LeaderboardEntry::default();
+        self.len -= 1;
+    }
+
+    /// Lay a version 1 leaderboard in `data`, whose entries were a Borsh
+    /// vector followed by the bump, out in slots
+    fn fill_slots(data: &mut [u8]) -> ProgramResult {
+        let mut rest = &data[2..];
+        let entries = Vec::<LeaderboardEntry>::deserialize(&mut rest)?;
+        let bump = u8::deserialize(&mut rest)?;
+        if entries.len() > Self::MAX_ENTRIES {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let mut board = Self::new(bump);
+        board.slots[..entries.len()].copy_from_slice(&entries);
+        board.len = entries.len() as u32;
+        data[..Self::LEN].copy_from_slice(bytemuck::bytes_of(&board));
+        Ok(())
+    }
+}
+
+/// Parameter change a passed governance proposal or the authority's
+/// timelock applies
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalAction {
+    SetRewardRate { reward_per_attribution: u64 },
+    SetProtocolFee { fee_bps: u16 },
+    AddOracle { oracle: Pubkey },
+    RemoveOracle { oracle: Pubkey },
+    SetAdminDelay { admin_delay_secs: i64 },
+}
+
+impl ProposalAction {
+    pub const LEN: usize = 1 + 32;
+
+    /// Reject parameters the matching admin instruction would reject, so
+    /// changes that can never execute aren't put to a vote or queued
+    pub fn validate(&self) -> ProgramResult {
+        match self {
+            ProposalAction::SetRewardRate { reward_per_attribution }
+                if !(Protocol::MIN_REWARD_PER_ATTRIBUTION..=Protocol::MAX_REWARD_PER_ATTRIBUTION)
+                    .contains(reward_per_attribution) =>
+            {
+                Err(SolSageError::RewardRateOutOfBounds.into())
+            }
+            ProposalAction::SetProtocolFee { fee_bps } if *fee_bps > Protocol::MAX_FEE_BPS => {
+                Err(SolSageError::FeeTooHigh.into())
+            }
+            ProposalAction::SetAdminDelay { admin_delay_secs }
+                if !(0..=Protocol::MAX_ADMIN_DELAY_SECS).contains(admin_delay_secs) =>
+            {
+                Err(SolSageError::InvalidAdminDelay.into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Vote on one parameter change, at `[Proposal::SEED, index (le)]`. Quorum
+/// and threshold are fixed from the protocol when it is created.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Proposal {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub action: ProposalAction,
+    pub votes_for: u64,
+    pub votes_against: u64,
+    pub quorum_votes: u64,
+    pub approval_threshold_bps: u16,
+    pub created_at: i64,
+    pub voting_ends_at: i64,
+    /// When a passed proposal can first be executed
+    pub executable_at: i64,
+    pub executed: bool,
+    pub bump: u8,
+}
+
+impl Proposal {
+    pub const LEN: usize = 1 + 1 + 8 + 32 + ProposalAction::LEN + 8 + 8 + 8 + 2 + 8 + 8 + 8 + 1 + 1;
+    pub const SEED: &'static [u8] = seeds::PROPOSAL;
+
+    pub fn passed(&self) -> bool {
+        solsage_core::proposal_passes(self.votes_for, self.votes_against, self.quorum_votes, self.approval_threshold_bps)
+    }
+
+    /// Count `weight` SAGE for or against
+    pub fn record_vote(&mut self, approve: bool, weight: u64) -> ProgramResult {
+        let votes = if approve { &mut self.votes_for } else { &mut self.votes_against };
+        *votes = votes.checked_add(weight).ok_or(SolSageError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+/// SAGE one voter has locked behind a proposal, at
+/// `[VoteRecord::SEED, proposal, voter]`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct VoteRecord {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub approve: bool,
+    pub weight: u64,
+    pub bump: u8,
+}
+
+impl VoteRecord {
+    pub const LEN: usize = 1 + 1 + 32 + 32 + 1 + 8 + 1;
+    pub const SEED: &'static [u8] = seeds::VOTE;
+}
+
+/// SAGE one voter has locked behind their vote on an entry's quality, at
+/// `[EntryVote::SEED, knowledge_entry, voter]`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct EntryVote {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub knowledge_entry: Pubkey,
+    pub voter: Pubkey,
+    pub upvote: bool,
+    pub weight: u64,
+    pub bump: u8,
+}
+
+impl EntryVote {
+    pub const LEN: usize = 1 + 1 + 32 + 32 + 1 + 8 + 1;
+    pub const SEED: &'static [u8] = seeds::ENTRY_VOTE;
+}
+
+/// Entries one staker has staked, at `[StakerIndex::SEED, staker]`. Every
+/// entry the staker created is at an index below `entry_count`, so they can
+/// be derived without scanning program accounts. Entries are staked at the
+/// next index, so only closed entries and those staked before the index was
+/// opened leave indices without an account.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct StakerIndex {
+    pub is_initialized: bool,
+    pub schema_version: u8,
+    pub staker: Pubkey,
+    /// Index the staker's next entry is staked at
+    pub entry_count: u64,
+    pub bump: u8,
+    /// Entries below `entry_count` closed since they were staked
+    pub closed_entries: u64,
+}
+
+impl StakerIndex {
+    pub const LEN: usize = 1 + 1 + 32 + 8 + 1 + 8;
+    pub const SEED: &'static [u8] = seeds::STAKER_INDEX;
+
+    /// Count an entry staked at `entry_index`, which must be the next index.
+    /// An index without entries starts at the first one staked with it, past
+    /// any the staker had before.
+    pub fn record_entry(&mut self, entry_index: u64) -> ProgramResult {
+        if self.entry_count > 0 && entry_index != self.entry_count {
+            return Err(SolSageError::InvalidEntryIndex.into());
+        }
+        self.entry_count = entry_index.checked_add(1).ok_or(SolSageError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Count the closing of one of the staker's entries
+    pub fn record_closed_entry(&mut self) -> ProgramResult {
+        self.closed_entries = self.closed_entries.checked_add(1).ok_or(SolSageError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+/// Structured events for indexers; each is logged with `sol_log_data` as its
+/// Borsh encoding, led by the variant index
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum SolSageEvent {
+    ProtocolInitialized {
+        authority: Pubkey,
+    },
+    KnowledgeStaked {
+        knowledge_entry: Pubkey,
+        staker: Pubkey,
+        entry_index: u64,
+        content_hash: [u8; 32],
+        category: Pubkey,
+        stake_amount: u64,
+        lockup_tier: LockupTier,
+        receipt_asset_id: Option<Pubkey>,
+        derived_from: Vec<Pubkey>,
+    },
+    AttributionRecorded {
+        knowledge_entry: Pubkey,
+        attribution: Pubkey,
+        query_hash: [u8; 32],
+        relevance_score: u8,
+        reward: u64,
+        unlocks_at: i64,
+        license: LicenseType,
+    },
+    AttributionFeeCharged {
+        payer: Pubkey,
+        fee: u64,
+    },
+    RewardsClaimed {
+        staker: Pubkey,
+        amount: u64,
+        attributions: u64,
+    },
+    MintInitialized {
+        mint: Pubkey,
+        max_supply: u64,
+    },
+    KnowledgeUnstaked {
+        knowledge_entry: Pubkey,
+        staker: Pubkey,
+        amount: u64,
+    },
+    KnowledgeUpdated {
+        knowledge_entry: Pubkey,
+        content_hash: [u8; 32],
+        version: u32,
+    },
+    KnowledgeOwnershipTransferred {
+        knowledge_entry: Pubkey,
+        old_owner: Pubkey,
+        new_owner: Pubkey,
+    },
+    KnowledgeActiveChanged {
+        knowledge_entry: Pubkey,
+        is_active: bool,
+    },
+    KnowledgeEntryClosed {
+        knowledge_entry: Pubkey,
+    },
+    VestingPeriodChanged {
+        old_period_secs: i64,
+        new_period_secs: i64,
+    },
+    OracleAdded {
+        oracle: Pubkey,
+    },
+    OracleRemoved {
+        oracle: Pubkey,
+    },
+    KnowledgeSlashed {
+        knowledge_entry: Pubkey,
+        slashed: u64,
+        forfeited: u64,
+    },
+    ProtocolFeeChanged {
+        old_fee_bps: u16,
+        new_fee_bps: u16,
+    },
+    TreasuryWithdrawn {
+        destination: Pubkey,
+        amount: u64,
+    },
+    AttributionChallenged {
+        attribution: Pubkey,
+        challenger: Pubkey,
+        bond: u64,
+    },
+    ChallengeResolved {
+        attribution: Pubkey,
+        challenger: Pubkey,
+        upheld: bool,
+        reversed: u64,
+    },
+    RewardRateChanged {
+        old_rate: u64,
+        new_rate: u64,
+    },
+    RewardCurveChanged {
+        old_curve: RewardCurve,
+        new_curve: RewardCurve,
+    },
+    AuthorityTransferProposed {
+        current_authority: Pubkey,
+        pending_authority: Pubkey,
+        authority_type: AuthorityType,
+    },
+    AuthorityTransferAccepted {
+        old_authority: Pubkey,
+        new_authority: Pubkey,
+    },
+    CategoryCreated {
+        category: Pubkey,
+        name: String,
+    },
+    CategoryMultiplierChanged {
+        category: Pubkey,
+        old_multiplier_bps: u16,
+        new_multiplier_bps: u16,
+    },
+    KnowledgeRenewed {
+        knowledge_entry: Pubkey,
+        expires_at: i64,
+        fee: u64,
+    },
+    ClaimDelegateChanged {
+        staker: Pubkey,
+        delegate: Option<Pubkey>,
+    },
+    MinStakeChanged {
+        old_min_stake_lamports: u64,
+        new_min_stake_lamports: u64,
+    },
+    AttributionCooldownChanged {
+        old_cooldown_secs: i64,
+        new_cooldown_secs: i64,
+    },
+    ProtocolPauseChanged {
+        authority: Pubkey,
+        is_paused: bool,
+    },
+    ScoreDivisorChanged {
+        old_score_divisor: u16,
+        new_score_divisor: u16,
+    },
+    AttributionRootCommitted {
+        attribution_root: Pubkey,
+        oracle: Pubkey,
+        epoch: u64,
+        root: [u8; 32],
+        attribution_count: u32,
+        unlocks_at: i64,
+    },
+    AttributedRewardClaimed {
+        attribution_root: Pubkey,
+        knowledge_entry: Pubkey,
+        leaf_index: u32,
+        query_hash: [u8; 32],
+        relevance_score: u8,
+        reward: u64,
+    },
+    EpochScheduleChanged {
+        old_epoch_duration_secs: i64,
+        new_epoch_duration_secs: i64,
+        old_epoch_reward_budget: u64,
+        new_epoch_reward_budget: u64,
+    },
+    EpochOpened {
+        epoch: Pubkey,
+        index: u64,
+        reward_budget: u64,
+        ends_at: i64,
+    },
+    EpochFinalized {
+        epoch: Pubkey,
+        index: u64,
+        reward_budget: u64,
+        total_weight: u64,
+    },
+    CuratorShareChanged {
+        old_curator_bps: u16,
+        new_curator_bps: u16,
+    },
+    CuratorRewarded {
+        curator: Pubkey,
+        attribution: Pubkey,
+        reward: u64,
+    },
+    CuratorRewardsClaimed {
+        curator: Pubkey,
+        amount: u64,
+    },
+    CollectionCreated {
+        collection: Pubkey,
+        owner: Pubkey,
+        name: String,
+    },
+    CollectionEntryAdded {
+        collection: Pubkey,
+        knowledge_entry: Pubkey,
+    },
+    CollectionEntryRemoved {
+        collection: Pubkey,
+        knowledge_entry: Pubkey,
+    },
+    RoyaltySplitChanged {
+        knowledge_entry: Pubkey,
+        splits: Vec<RoyaltySplit>,
+    },
+    RoyaltyPaid {
+        knowledge_entry: Pubkey,
+        co_author: Pubkey,
+        amount: u64,
+    },
+    LicenseUpdated {
+        knowledge_entry: Pubkey,
+        license: LicenseType,
+    },
+    ContentUriUpdated {
+        knowledge_entry: Pubkey,
+        content_uri: Option<String>,
+    },
+    AccountMigrated {
+        account: Pubkey,
+        account_type: AccountType,
+        from_version: u8,
+        to_version: u8,
+    },
+    TagAdded {
+        knowledge_entry: Pubkey,
+        tag: String,
+    },
+    TagRemoved {
+        knowledge_entry: Pubkey,
+        tag: String,
+    },
+    RewardSourceChanged {
+        old_reward_source: RewardSource,
+        new_reward_source: RewardSource,
+    },
+    AttributionRewardFunded {
+        payer: Pubkey,
+        amount: u64,
+    },
+    SubscriptionToppedUp {
+        subscription: Pubkey,
+        consumer: Pubkey,
+        amount: u64,
+        balance: u64,
+    },
+    SubscriptionClosed {
+        subscription: Pubkey,
+        consumer: Pubkey,
+        refunded: u64,
+    },
+    PriceFloorSet {
+        knowledge_entry: Pubkey,
+        min_reward_per_use: u64,
+    },
+    LeaderboardRebuilt {
+        ranked: u32,
+    },
+    GovernanceConfigChanged {
+        quorum_votes: u64,
+        approval_threshold_bps: u16,
+        voting_period_secs: i64,
+        execution_delay_secs: i64,
+    },
+    ProposalCreated {
+        proposal: Pubkey,
+        index: u64,
+        proposer: Pubkey,
+        action: ProposalAction,
+        voting_ends_at: i64,
+    },
+    VoteCast {
+        proposal: Pubkey,
+        voter: Pubkey,
+        approve: bool,
+        weight: u64,
+    },
+    ProposalExecuted {
+        proposal: Pubkey,
+        action: ProposalAction,
+    },
+    VoteWithdrawn {
+        proposal: Pubkey,
+        voter: Pubkey,
+        weight: u64,
+    },
+    ParameterChangeQueued {
+        change: ProposalAction,
+        executable_at: i64,
+    },
+    ParameterChangeCancelled {
+        change: ProposalAction,
+    },
+    ParameterChangeExecuted {
+        change: ProposalAction,
+    },
+    AdminDelayChanged {
+        old_delay_secs: i64,
+        new_delay_secs: i64,
+    },
+    ScoreDecayChanged {
+        score_half_life_secs: i64,
+        min_score_weight_bps: u16,
+    },
+    AttributionVerifierChanged {
+        verifier: Option<Pubkey>,
+    },
+    ClaimWindowChanged {
+        claim_window_secs: i64,
+    },
+    ReceiptTreeChanged {
+        merkle_tree: Option<Pubkey>,
+    },
+    MintMetadataSet {
+        mint: Pubkey,
+        name: String,
+        symbol: String,
+        uri: String,
+    },
+    EntryListed {
+        knowledge_entry: Pubkey,
+        seller: Pubkey,
+        price: u64,
+    },
+    ListingCancelled {
+        knowledge_entry: Pubkey,
+        seller: Pubkey,
+    },
+    EntryBought {
+        knowledge_entry: Pubkey,
+        seller: Pubkey,
+        buyer: Pubkey,
+        price: u64,
+        fee: u64,
+    },
+    SaleFeeChanged {
+        sale_fee_bps: u16,
+    },
+    OracleBudgetChanged {
+        oracle: Pubkey,
+        max_attributions_per_epoch: u64,
+        max_rewards_per_epoch: u64,
+    },
+    AttributionVoteCast {
+        attribution: Pubkey,
+        oracle: Pubkey,
+        relevance_score: u8,
+        votes: u8,
+    },
+    ConsensusChanged {
+        consensus_threshold: u8,
+        consensus_tolerance: u8,
+    },
+    AggregationWindowChanged {
+        aggregation_window_secs: i64,
+    },
+    PayerAttributionCapChanged {
+        max_payer_attributions_per_day: u64,
+    },
+    RewardStreamChanged {
+        reward_stream_secs: i64,
+    },
+    FeeConfigChanged {
+        treasury_bps: u16,
+        curator_bps: u16,
+        burn_bps: u16,
+    },
+    TreasuryRewardsClaimed {
+        destination: Pubkey,
+        amount: u64,
+    },
+    LowRelevanceBurnChanged {
+        low_relevance_threshold: u8,
+        low_relevance_burn_bps: u16,
+    },
+    RewardsBurned {
+        amount: u64,
+    },
+    CitationRoyaltyChanged {
+        citation_royalty_bps: u16,
+    },
+    CitationRoyaltyPaid {
+        knowledge_entry: Pubkey,
+        parent: Pubkey,
+        amount: u64,
+    },
+    EntryVoted {
+        knowledge_entry: Pubkey,
+        voter: Pubkey,
+        upvote: bool,
+        weight: u64,
+        quality_score: i64,
+    },
+    EntryVoteWithdrawn {
+        knowledge_entry: Pubkey,
+        voter: Pubkey,
+        weight: u64,
+    },
+    EntryReported {
+        knowledge_entry: Pubkey,
+        reporter: Pubkey,
+        bond: u64,
+        reports: u8,
+    },
+    EntryFlagged {
+        knowledge_entry: Pubkey,
+        reports: u8,
+    },
+    ReportsResolved {
+        knowledge_entry: Pubkey,
+        upheld: bool,
+        bonds: u64,
+    },
+    BountyCreated {
+        bounty: Pubkey,
+        requester: Pubkey,
+        query_hash: [u8; 32],
+        reward: u64,
+        deadline: i64,
+    },
+    AnswerSubmitted {
+        bounty: Pubkey,
+        knowledge_entry: Pubkey,
+        staker: Pubkey,
+    },
+    AnswerAccepted {
+        bounty: Pubkey,
+        knowledge_entry: Pubkey,
+        staker: Pubkey,
+        reward: u64,
+    },
+    BountyReclaimed {
+        bounty: Pubkey,
+        requester: Pubkey,
+        reward: u64,
+    },
+    UnstakeRequested {
+        knowledge_entry: Pubkey,
+        staker: Pubkey,
+        unlocks_at: i64,
+    },
+    BridgeEmitterAdded {
+        bridge_emitter: Pubkey,
+        bridge: Pubkey,
+        emitter_chain: u16,
+        emitter_address: [u8; 32],
+    },
+    SnapshotTaken {
+        day: u64,
+        total_knowledge_entries: u64,
+        total_attributions: u64,
+        total_rewards: u64,
+    },
+    RewardsExpired {
+        knowledge_entry: Pubkey,
+        amount: u64,
+        attributions: u64,
+        bounty: u64,
+    },
+    DocumentStaked {
+        document: Pubkey,
+        owner: Pubkey,
+        content_hash: [u8; 32],
+        chunk_count: u16,
+    },
+    DocumentChunkAdded {
+        document: Pubkey,
+        knowledge_entry: Pubkey,
+        chunk_index: u16,
+    },
+    DocumentRefreshed {
+        document: Pubkey,
+        total_attributions: u64,
+        pending_rewards: u64,
+    },
+    EmbeddingFingerprintUpdated {
+        knowledge_entry: Pubkey,
+        embedding_fingerprint: Option<[u8; KnowledgeEntry::EMBEDDING_FINGERPRINT_LEN]>,
+    },
+    ReviewerAdded {
+        reviewer: Pubkey,
+    },
+    ReviewerRemoved {
+        reviewer: Pubkey,
+    },
+    EntryAttested {
+        knowledge_entry: Pubkey,
+        reviewer: Pubkey,
+        accuracy: u8,
+        freshness: u8,
+        verified: bool,
+    },
+    IdentityConfigChanged {
+        identity_config: Option<IdentityConfig>,
+    },
+    EntryVisibilityChanged {
+        knowledge_entry: Pubkey,
+        visibility: Visibility,
+    },
+    AccessGranted {
+        knowledge_entry: Pubkey,
+        holder: Pubkey,
+        price_paid: u64,
+    },
+}
+
+impl SolSageEvent {
+    pub fn emit(&self) -> ProgramResult {
+        sol_log_data(&[&self.try_to_vec()?]);
+        Ok(())
+    }
+}
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Error, Debug)]
+pub enum SolSageError {
+    #[error("Account already initialized")]
+    AlreadyInitialized,
+    #[error("Title too long")]
+    TitleTooLong,
+    #[error("Category too long")]
+    CategoryTooLong,
+    #[error("Invalid relevance score")]
+    InvalidRelevanceScore,
+    #[error("No rewards to claim")]
+    NoRewardsToClaim,
+    #[error("Not knowledge owner")]
+    NotKnowledgeOwner,
+    #[error("Invalid PDA")]
+    InvalidPda,
+    #[error("Invalid reward mint")]
+    InvalidRewardMint,
+    #[error("Token account not owned by staker")]
+    InvalidTokenAccountOwner,
+    #[error("Not protocol authority")]
+    Unauthorized,
+    #[error("Mint already initialized")]
+    MintAlreadyInitialized,
+    #[error("SAGE supply cap exceeded")]
+    SupplyCapExceeded,
+    #[error("Nothing staked")]
+    NothingStaked,
+    #[error("Stake still in cooldown or lockup")]
+    StakeLocked,
+    #[error("Knowledge entry inactive")]
+    KnowledgeInactive,
+    #[error("Unclaimed rewards outstanding")]
+    UnclaimedRewards,
+    #[error("Stake not withdrawn")]
+    StakeNotWithdrawn,
+    #[error("Reward rate out of bounds")]
+    RewardRateOutOfBounds,
+    #[error("Not pending authority")]
+    NotPendingAuthority,
+    #[error("Invalid vesting period")]
+    InvalidVestingPeriod,
+    #[error("Query attribution limit reached")]
+    QueryAttributionLimit,
+    #[error("Query attributed by another oracle")]
+    QueryOracleMismatch,
+    #[error("Payer is not a registered oracle")]
+    UnauthorizedOracle,
+    #[error("Batch scores and accounts mismatch")]
+    BatchLengthMismatch,
+    #[error("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[error("Challenge bond too low")]
+    ChallengeBondTooLow,
+    #[error("Attribution already reversed")]
+    AttributionReversed,
+    #[error("Accounts do not match challenge")]
+    ChallengeMismatch,
+    #[error("Basis points above 10000")]
+    InvalidBasisPoints,
+    #[error("Protocol fee too high")]
+    FeeTooHigh,
+    #[error("Insufficient treasury balance")]
+    InsufficientTreasuryBalance,
+    #[error("Attribution belongs to another entry")]
+    AttributionMismatch,
+    #[error("Reward already claimed")]
+    RewardAlreadyClaimed,
+    #[error("Reward still vesting")]
+    RewardLocked,
+    #[error("Content already staked")]
+    DuplicateContent,
+    #[error("Category does not match entry")]
+    CategoryMismatch,
+    #[error("Category multiplier out of bounds")]
+    MultiplierOutOfBounds,
+    #[error("Knowledge entry expired")]
+    KnowledgeExpired,
+    #[error("Content hash mismatch")]
+    ContentHashMismatch,
+    #[error("Stake below protocol minimum")]
+    StakeTooSmall,
+    #[error("Entry attributed too recently")]
+    AttributionCooldown,
+    #[error("Invalid attribution cooldown")]
+    InvalidAttributionCooldown,
+    #[error("Protocol is paused")]
+    ProtocolPaused,
+    #[error("Score divisor out of bounds")]
+    InvalidScoreDivisor,
+    #[error("Root attribution count out of bounds")]
+    InvalidRootAttributionCount,
+    #[error("Invalid Merkle proof")]
+    InvalidMerkleProof,
+    #[error("Invalid epoch schedule")]
+    InvalidEpochSchedule,
+    #[error("Epochs are disabled")]
+    EpochsDisabled,
+    #[error("Epoch is not open")]
+    EpochNotOpen,
+    #[error("Epoch has not ended")]
+    EpochNotEnded,
+    #[error("Epoch already finalized")]
+    EpochAlreadyFinalized,
+    #[error("Epoch not finalized")]
+    EpochNotFinalized,
+    #[error("Curator share too high")]
+    InvalidCuratorShare,
+    #[error("Curators are not paid while epochs are enabled")]
+    CuratorWithEpochs,
+    #[error("Collection name too long")]
+    CollectionNameTooLong,
+    #[error("Not the collection owner")]
+    NotCollectionOwner,
+    #[error("Entry already in a collection")]
+    EntryInCollection,
+    #[error("Entry is not in this collection")]
+    CollectionMismatch,
+    #[error("Invalid royalty split")]
+    InvalidRoyaltySplit,
+    #[error("Co-authored entries must be claimed with ClaimRewards")]
+    CoAuthoredEntry,
+    #[error("Custom license URI is empty or too long")]
+    InvalidLicense,
+    #[error("Content URI is empty or too long")]
+    InvalidContentUri,
+    #[error("Account layout is outdated; run MigrateAccount")]
+    AccountNeedsMigration,
+    #[error("Account is already on the current schema version")]
+    AccountUpToDate,
+    #[error("Tag is empty or too long")]
+    InvalidTag,
+    #[error("Entry already has this tag")]
+    DuplicateTag,
+    #[error("Entry has too many tags")]
+    TooManyTags,
+    #[error("Entry does not have this tag")]
+    TagNotFound,
+    #[error("Entry still has tags")]
+    EntryTagged,
+    #[error("Reward source can only change before the first attribution")]
+    RewardSourceLocked,
+    #[error("Epochs and attribution roots only pay minted rewards")]
+    MintedRewardsOnly,
+    #[error("Rewards are minted, not funded by attribution payers")]
+    RewardsNotPayerFunded,
+    #[error("Subscription balance too low")]
+    InsufficientSubscriptionBalance,
+    #[error("Deposit must be greater than zero")]
+    ZeroDeposit,
+    #[error("Reward is below the entry's price floor")]
+    RewardBelowPriceFloor,
+    #[error("Authority is not the address its multisig signs with")]
+    AuthorityTypeMismatch,
+    #[error("Invalid governance configuration")]
+    InvalidGovernanceConfig,
+    #[error("Governance is disabled")]
+    GovernanceDisabled,
+    #[error("Proposal voting has ended")]
+    VotingClosed,
+    #[error("Proposal voting is still open")]
+    VotingOpen,
+    #[error("Proposal did not pass")]
+    ProposalRejected,
+    #[error("Proposal execution delay has not elapsed")]
+    ProposalTimelocked,
+    #[error("Proposal already executed")]
+    ProposalAlreadyExecuted,
+    #[error("Admin delay is set; queue the change instead")]
+    ChangeMustBeQueued,
+    #[error("No parameter change is queued")]
+    NoQueuedChange,
+    #[error("Queued parameter change is still timelocked")]
+    ParameterChangeTimelocked,
+    #[error("Admin delay out of bounds")]
+    InvalidAdminDelay,
+    #[error("Invalid score decay")]
+    InvalidScoreDecay,
+    #[error("Attribution not made through the attribution verifier")]
+    AttributionNotVerified,
+    #[error("The program can't verify its own attributions")]
+    InvalidAttributionVerifier,
+    #[error("Attribution receipt not signed in the preceding Ed25519 instruction")]
+    InvalidAttributionReceipt,
+    #[error("Attribution receipt nonce already used")]
+    ReceiptNonceUsed,
+    #[error("Claim window out of bounds")]
+    InvalidClaimWindow,
+    #[error("Rewards are still within their claim window")]
+    RewardNotExpired,
+    #[error("Unstake has not been requested")]
+    UnstakeNotRequested,
+    #[error("Unstake already requested")]
+    UnstakeAlreadyRequested,
+    #[error("Pending rewards must be claimed or forfeited before unstaking")]
+    PendingRewardsOutstanding,
+    #[error("Message not posted by the emitter's bridge for this attribution")]
+    InvalidBridgedMessage,
+    #[error("Bridged message already relayed")]
+    BridgedMessageUsed,
+    #[error("Not the protocol's receipt tree")]
+    InvalidReceiptTree,
+    #[error("Token metadata name, symbol or URI too long")]
+    MetadataTooLong,
+    #[error("Listing price must be above zero")]
+    InvalidListingPrice,
+    #[error("Listing price differs from the price offered")]
+    ListingPriceChanged,
+    #[error("Oracle has spent its attribution budget for this epoch")]
+    OracleBudgetExceeded,
+    #[error("Oracle already reported this attribution")]
+    OracleAlreadyVoted,
+    #[error("Relevance score out of tolerance of the attribution's first report")]
+    ConsensusScoreMismatch,
+    #[error("Attribution still awaits consensus")]
+    AttributionPending,
+    #[error("Attributions must be reported one at a time while consensus is required")]
+    ConsensusRequired,
+    #[error("Consensus threshold above the reports an attribution can wait on")]
+    InvalidConsensusThreshold,
+    #[error("Attribution already holds as many reports as it can")]
+    AttributionReportsFull,
+    #[error("Attribution no longer takes scores")]
+    AggregationWindowClosed,
+    #[error("Attribution still takes scores")]
+    AggregationWindowOpen,
+    #[error("Attributions can't wait on both consensus and score aggregation")]
+    ConsensusWithAggregation,
+    #[error("Aggregation window out of bounds")]
+    InvalidAggregationWindow,
+    #[error("Payer has recorded as many attributions as it may today")]
+    PayerAttributionCapExceeded,
+    #[error("Report bond below minimum")]
+    ReportBondTooLow,
+    #[error("Reporter has already reported this entry")]
+    AlreadyReported,
+    #[error("Entry already holds as many reports as it can")]
+    ReportQueueFull,
+    #[error("Reporter accounts do not match the report queue")]
+    ReportMismatch,
+    #[error("Entry is under review for spam reports")]
+    EntryUnderReview,
+    #[error("Bounty deadline out of bounds")]
+    InvalidBountyDeadline,
+    #[error("Bounty deadline has passed")]
+    BountyExpired,
+    #[error("Bounty deadline has not passed yet")]
+    BountyNotExpired,
+    #[error("Bounty has already been awarded")]
+    BountyClosed,
+    #[error("Answer was submitted for another bounty")]
+    BountyAnswerMismatch,
+    #[error("Reward stream period out of bounds")]
+    InvalidRewardStream,
+    #[error("Attribution reward is claimed from the entry's reward stream")]
+    RewardStreamed,
+    #[error("Fee shares exceed the reward or the curator cap")]
+    InvalidFeeConfig,
+    #[error("Low-relevance threshold or burn share out of bounds")]
+    InvalidLowRelevanceBurn,
+    #[error("No rewards are waiting to be burned")]
+    NothingToBurn,
+    #[error("Parent entries are too many, repeated or don't match")]
+    InvalidParentEntries,
+    #[error("Citation royalty above the maximum")]
+    InvalidCitationRoyalty,
+    #[error("No citation royalties to pay")]
+    NoCitationRoyalties,
+    #[error("Chunk count is zero or above the maximum")]
+    InvalidChunkCount,
+    #[error("Not the document owner")]
+    NotDocumentOwner,
+    #[error("Chunk index out of range or already added")]
+    InvalidChunkIndex,
+    #[error("Entry is a document chunk")]
+    EntryInDocument,
+    #[error("Chunk entries don't match the document")]
+    DocumentMismatch,
+    #[error("Entry has no committed chunks")]
+    NoChunkCommitment,
+    #[error("Chunk proof doesn't match the entry's chunk root")]
+    InvalidChunkProof,
+    #[error("Not an approved reviewer")]
+    NotReviewer,
+    #[error("Reviewer already approved")]
+    ReviewerAlreadyAdded,
+    #[error("Reviewer registry is full")]
+    ReviewerRegistryFull,
+    #[error("Attestation score above the maximum")]
+    InvalidAttestationScore,
+    #[error("Reviewers can't attest their own entries")]
+    SelfAttestation,
+    #[error("Stakes must present an identity attestation")]
+    IdentityRequired,
+    #[error("Protocol takes no identity attestations")]
+    IdentityNotConfigured,
+    #[error("Not an attestation of the staker under the protocol's credential")]
+    InvalidIdentityAttestation,
+    #[error("Identity attestation has expired")]
+    IdentityAttestationExpired,
+    #[error("Private entries need the payer's access pass")]
+    AccessPassRequired,
+    #[error("Entry is public")]
+    EntryNotPrivate,
+    #[error("Access price no longer matches")]
+    AccessPriceChanged,
+    #[error("Account holds state of another type")]
+    AccountTypeMismatch,
+    #[error("Citation royalties are still owed to the entry's parents")]
+    CitationRoyaltiesUnpaid,
+    #[error("Entry index is not the staker's next")]
+    InvalidEntryIndex,
+    #[error("Every vesting bucket is locked until before the reward would unlock")]
+    VestingScheduleFull,
+    #[error("Account did not pay for the content registry")]
+    RegistryPayerMismatch,
+}
+
+impl From<SolSageError> for ProgramError {
+    fn from(e: SolSageError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
+// ============================================================================
+// PROCESSORS
+// ============================================================================
+
+fn process_initialize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let treasury_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    // Derive PDA
+    let (protocol_pda, bump) = syscalls::find_program_address(
+        &[Protocol::SEED],
+        program_id,
+    );
+
+    if protocol_pda != *protocol_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(protocol_account)?;
+
+    // Create account
+    let rent = Rent::get()?;
+    let lamports = rent.minimum_balance(Protocol::LEN);
+    
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            protocol_account.key,
+            lamports,
             Protocol::LEN as u64,
             program_id,
         ),
-        &[authority.clone(), protocol_account.clone(), system_program.clone()],
-        &[&[Protocol::SEED, &[bump]]],
+        &[authority.clone(), protocol_account.clone(), system_program.clone()],
+        &[&[Protocol::SEED, &[bump]]],
+    )?;
+
+    // The treasury is a data-less, program-owned lamport account
+    let (treasury_pda, treasury_bump) =
+        syscalls::find_program_address(&[Protocol::TREASURY_SEED], program_id);
+
+    if treasury_pda != *treasury_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(treasury_account)?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            treasury_account.key,
+            rent.minimum_balance(0),
+            0,
+            program_id,
+        ),
+        &[authority.clone(), treasury_account.clone(), system_program.clone()],
+        &[&[Protocol::TREASURY_SEED, &[treasury_bump]]],
+    )?;
+
+    // Initialize data
+    let protocol = Protocol {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        authority: *authority.key,
+        pending_authority: None,
+        reward_mint: Pubkey::default(),
+        total_knowledge_entries: 0,
+        total_attributions: 0,
+        reward_per_attribution: 1_000_000, // 1 SAGE (6 decimals)
+        max_supply: 0,
+        vesting_period_secs: Protocol::DEFAULT_VESTING_PERIOD_SECS,
+        reward_curve: RewardCurve::Linear,
+        fee_bps: 0,
+        fees_collected: 0,
+        min_stake_lamports: 0,
+        attribution_cooldown_secs: 0,
+        is_paused: false,
+        score_divisor: Protocol::DEFAULT_SCORE_DIVISOR,
+        epoch_duration_secs: 0,
+        epoch_reward_budget: 0,
+        epoch_count: 0,
+        epoch_ends_at: 0,
+        curator_bps: 0,
+        bump,
+        mint_authority_bump: 0,
+        treasury_bump,
+        reward_source: RewardSource::Mint,
+        reward_vault_bump: 0,
+        authority_type: AuthorityType::Keypair,
+        pending_authority_type: AuthorityType::Keypair,
+        quorum_votes: 0,
+        approval_threshold_bps: 0,
+        voting_period_secs: 0,
+        execution_delay_secs: 0,
+        proposal_count: 0,
+        governance_vault_bump: 0,
+        admin_delay_secs: 0,
+        queued_change: None,
+        queued_change_executable_at: 0,
+        score_half_life_secs: 0,
+        min_score_weight_bps: 0,
+        attribution_verifier: None,
+        claim_window_secs: 0,
+        total_rewards: 0,
+        receipt_tree: None,
+        sale_fee_bps: 0,
+        consensus_threshold: 0,
+        consensus_tolerance: 0,
+        aggregation_window_secs: 0,
+        max_payer_attributions_per_day: 0,
+        reward_stream_secs: 0,
+        fee_config: FeeConfig::default(),
+        treasury_rewards: 0,
+        rewards_burned: 0,
+        low_relevance_threshold: 0,
+        low_relevance_burn_bps: 0,
+        pending_burn: 0,
+        citation_royalty_bps: 0,
+        identity_config: None,
+    };
+
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::ProtocolInitialized { authority: *authority.key }.emit()?;
+
+    msg!("SolSage Protocol initialized!");
+    Ok(())
+}
+
+fn process_stake_knowledge(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    entry_index: u64,
+    content_hash: [u8; 32],
+    title: String,
+    terms: StakeTerms,
+    license: LicenseType,
+) -> ProgramResult {
+    let StakeTerms { stake_amount, lockup, mint_receipt, derived_from, chunks, identity } = terms;
+    // Parents follow every other account, however many optional ones come
+    // first
+    let parents_at = accounts
+        .len()
+        .checked_sub(derived_from.len())
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let (accounts, parent_accounts) = accounts.split_at(parents_at);
+    let account_info_iter = &mut accounts.iter();
+    let staker = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let profile_account = next_account_info(account_info_iter)?;
+    let registry_account = next_account_info(account_info_iter)?;
+    let category_account = next_account_info(account_info_iter)?;
+    let staker_index_account = next_account_info(account_info_iter)?;
+
+    if !staker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    if title.len() > KnowledgeEntry::MAX_TITLE_LEN {
+        return Err(SolSageError::TitleTooLong.into());
+    }
+    license.validate()?;
+    if let Some(chunks) = &chunks {
+        chunks.validate()?;
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+    protocol.check_not_paused()?;
+
+    if stake_amount < protocol.min_stake_lamports {
+        return Err(SolSageError::StakeTooSmall.into());
+    }
+
+    let mut category = load_category(program_id, category_account)?;
+    check_parent_entries(program_id, &derived_from, parent_accounts)?;
+
+    // Derive knowledge PDA from the entry index so edits keep the address
+    let index_seed = entry_index.to_le_bytes();
+    let (knowledge_pda, bump) = syscalls::find_program_address(
+        &[KnowledgeEntry::SEED, staker.key.as_ref(), &index_seed],
+        program_id,
+    );
+
+    if knowledge_pda != *knowledge_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(knowledge_account)?;
+
+    // Create knowledge account
+    let rent = Rent::get()?;
+    let lamports = rent.minimum_balance(KnowledgeEntry::LEN);
+    
+    invoke_signed(
+        &system_instruction::create_account(
+            staker.key,
+            knowledge_account.key,
+            lamports,
+            KnowledgeEntry::LEN as u64,
+            program_id,
+        ),
+        &[staker.clone(), knowledge_account.clone(), system_program.clone()],
+        &[&[KnowledgeEntry::SEED, staker.key.as_ref(), &index_seed, &[bump]]],
+    )?;
+
+    // Derive escrow PDA
+    let (escrow_pda, escrow_bump) = syscalls::find_program_address(
+        &[KnowledgeEntry::ESCROW_SEED, knowledge_account.key.as_ref()],
+        program_id,
+    );
+
+    if escrow_pda != *escrow_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(escrow_account)?;
+
+    // Create escrow holding the stake on top of its own rent
+    let escrow_lamports = rent
+        .minimum_balance(0)
+        .checked_add(stake_amount)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            staker.key,
+            escrow_account.key,
+            escrow_lamports,
+            0,
+            program_id,
+        ),
+        &[staker.clone(), escrow_account.clone(), system_program.clone()],
+        &[&[KnowledgeEntry::ESCROW_SEED, knowledge_account.key.as_ref(), &[escrow_bump]]],
+    )?;
+
+    let receipt_asset_id = if mint_receipt {
+        Some(mint_entry_receipt(program_id, &protocol, staker, system_program, account_info_iter, &title)?)
+    } else {
+        None
+    };
+
+    let clock = Clock::get()?;
+    let identity_credential = match (identity, protocol.identity_config) {
+        (true, Some(config)) => Some(config.verify(staker.key, next_account_info(account_info_iter)?, clock.unix_timestamp)?),
+        (true, None) => return Err(SolSageError::IdentityNotConfigured.into()),
+        (false, Some(config)) if config.required => return Err(SolSageError::IdentityRequired.into()),
+        (false, _) => None,
+    };
+
+    // Initialize knowledge entry
+    let knowledge = KnowledgeEntry {
+        is_initialized: true,
+        schema_version: KNOWLEDGE_ENTRY_SCHEMA_VERSION,
+        staker: *staker.key,
+        creator: *staker.key,
+        entry_index,
+        content_hash,
+        category: *category_account.key,
+        is_active: true,
+        license,
+        title: title.clone(),
+        content_uri: None,
+        collection: None,
+        royalty_splits: Vec::new(),
+        created_at: clock.unix_timestamp,
+        updated_at: clock.unix_timestamp,
+        expires_at: clock
+            .unix_timestamp
+            .checked_add(KnowledgeEntry::TERM_SECS)
+            .ok_or(SolSageError::ArithmeticOverflow)?,
+        last_attribution_at: 0,
+        version: 1,
+        total_attributions: 0,
+        pending_rewards: 0,
+        vesting_schedule: [VestingBucket::default(); KnowledgeEntry::VESTING_BUCKETS],
+        stake_amount,
+        bump,
+        escrow_bump,
+        tags: Vec::new(),
+        min_reward_per_use: 0,
+        lockup_tier: lockup,
+        locked_until: clock
+            .unix_timestamp
+            .checked_add(lockup.duration_secs())
+            .ok_or(SolSageError::ArithmeticOverflow)?,
+        unstake_requested_at: 0,
+        receipt_asset_id,
+        quality_score: 0,
+        under_review: false,
+        reward_stream: RewardStream::default(),
+        derived_from: derived_from.clone(),
+        citation_royalties: 0,
+        citation_rewards: 0,
+        document: None,
+        chunk_index: 0,
+        chunks,
+        embedding_fingerprint: None,
+        attestation: None,
+        verified: false,
+        identity_credential,
+        visibility: Visibility::Public,
+    };
+
+    knowledge.store(knowledge_account)?;
+
+    // Only the first stake of a content hash may earn from it
+    register_content(
+        program_id,
+        staker,
+        knowledge_account,
+        registry_account,
+        system_program,
+        content_hash,
+        clock.unix_timestamp,
+    )?;
+
+    category.entries = category.entries.checked_add(1).ok_or(SolSageError::ArithmeticOverflow)?;
+    category.store(category_account)?;
+
+    // Update protocol
+    protocol.total_knowledge_entries = protocol
+        .total_knowledge_entries
+        .checked_add(1)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    protocol.store(protocol_account)?;
+
+    let mut profile = load_or_create_staker_profile(
+        program_id,
+        staker,
+        staker.key,
+        profile_account,
+        system_program,
+        &rent,
+    )?;
+    profile.total_entries = profile.total_entries.checked_add(1).ok_or(SolSageError::ArithmeticOverflow)?;
+    profile.store(profile_account)?;
+
+    let mut staker_index = load_or_create_staker_index(program_id, staker, staker_index_account, system_program)?;
+    staker_index.record_entry(entry_index)?;
+    staker_index.store(staker_index_account)?;
+
+    SolSageEvent::KnowledgeStaked {
+        knowledge_entry: *knowledge_account.key,
+        staker: *staker.key,
+        entry_index,
+        content_hash,
+        category: *category_account.key,
+        stake_amount,
+        lockup_tier: lockup,
+        receipt_asset_id,
+        derived_from,
+    }
+    .emit()?;
+
+    msg!("Knowledge staked: {} ({} lamports)", title, stake_amount);
+    Ok(())
+}
+
+fn process_record_attribution(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    query_hash: [u8; 32],
+    relevance_score: u8,
+    curator: Option<Pubkey>,
+    metadata: AttributionMetadata,
+    attestation: Option<Attestation>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let attribution_account = next_account_info(account_info_iter)?;
+    let query_account = next_account_info(account_info_iter)?;
+    let oracle_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let profile_account = next_account_info(account_info_iter)?;
+    let treasury_account = next_account_info(account_info_iter)?;
+    let category_account = next_account_info(account_info_iter)?;
+    let leaderboard_account = next_account_info(account_info_iter)?;
+    let oracle_state_account = next_account_info(account_info_iter)?;
+    let payer_stats_account = next_account_info(account_info_iter)?;
+
+    if !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+    protocol.check_not_paused()?;
+
+    let bridged_accounts = match attestation {
+        Some(Attestation::Bridged) => Some((next_account_info(account_info_iter)?, next_account_info(account_info_iter)?)),
+        _ => None,
+    };
+    let instructions_account = match (protocol.attribution_verifier, attestation) {
+        (None, None | Some(Attestation::Bridged)) => None,
+        _ => Some(next_account_info(account_info_iter)?),
+    };
+    protocol.check_attribution_caller(instructions_account)?;
+
+    // A signed receipt or a bridged message stands in for the oracle's
+    // signature on the transaction
+    let receipt = match (attestation, instructions_account) {
+        (Some(Attestation::Receipt { nonce }), Some(instructions_account)) => {
+            let receipt = receipts::attribution_receipt(&query_hash, knowledge_account.key, relevance_score, nonce);
+            let signer = receipt_signer(instructions_account, &receipt)?;
+            Some((signer, nonce))
+        }
+        _ => None,
+    };
+    let oracle = match (receipt, bridged_accounts) {
+        (Some((signer, _)), _) => signer,
+        (None, Some((emitter_account, message_account))) => {
+            let payload = bridge::attribution_payload(&query_hash, &knowledge_account.key.to_bytes(), relevance_score);
+            let (mut emitter, sequence) = bridged_message(program_id, emitter_account, message_account, &payload)?;
+            emitter.use_sequence(sequence)?;
+            emitter.store(emitter_account)?;
+            *emitter_account.key
+        }
+        (None, None) => *payer.key,
+    };
+    check_registered_oracle(program_id, &oracle, oracle_account)?;
+
+    let epoch_account = if protocol.epochs_enabled() {
+        if curator.is_some() {
+            return Err(SolSageError::CuratorWithEpochs.into());
+        }
+        Some(next_account_info(account_info_iter)?)
+    } else {
+        None
+    };
+    let curator_account = match curator {
+        Some(_) => Some(next_account_info(account_info_iter)?),
+        None => None,
+    };
+    let agent_id = metadata.agent_id;
+    let agent_stats_account = match agent_id {
+        Some(_) => Some(next_account_info(account_info_iter)?),
+        None => None,
+    };
+
+    // The accounts funding the rewards, when attributions fund them, follow
+    // the entry's collection
+    let (collection_accounts, funding) = match protocol.reward_source {
+        RewardSource::Payer => {
+            let (collection_accounts, funding) = RewardFunding::split_last(program_id, account_info_iter.as_slice())?;
+            (collection_accounts, Some(funding))
+        }
+        RewardSource::Mint => (account_info_iter.as_slice(), None),
+    };
+
+    let now = Clock::get()?.unix_timestamp;
+    let mut epoch = load_open_epoch(program_id, &protocol, epoch_account, now)?;
+    let ctx = AttributionContext {
+        program_id,
+        payer,
+        oracle,
+        system_program,
+        protocol: &protocol,
+        query_hash,
+        epoch: epoch.as_ref().map(|epoch| epoch.index),
+        fees: protocol.attribution_fees(curator.is_some(), epoch.is_some()),
+        subscription_funded: matches!(funding, Some(RewardFunding::Subscription(_))),
+        metadata,
+        rent: Rent::get()?,
+        now,
+    };
+
+    let mut oracle_state =
+        load_or_create_oracle_state(program_id, payer, &oracle, oracle_state_account, system_program, &ctx.rent)?;
+    if let Some((_, nonce)) = receipt {
+        oracle_state.use_nonce(nonce)?;
+    }
+    let mut payer_stats = load_or_create_payer_stats(program_id, payer, payer_stats_account, system_program, &ctx.rent)?;
+    payer_stats.record_attributions(now, 1, protocol.max_payer_attributions_per_day)?;
+    payer_stats.store(payer_stats_account)?;
+    let relevance_score = if protocol.reports_pending() {
+        match vote_on_attribution(&ctx, knowledge_account, attribution_account, relevance_score)? {
+            Some(relevance_score) => relevance_score,
+            None => {
+                oracle_state.spend_budget(now, 1, 0)?;
+                oracle_state.store(oracle_state_account)?;
+                msg!("Attribution vote cast");
+                return Ok(());
+            }
+        }
+    } else {
+        relevance_score
+    };
+    reserve_query_attributions(&ctx, query_account, 1)?;
+    let (shares, entry_attributions) = record_attribution_to(
+        &ctx,
+        knowledge_account,
+        attribution_account,
+        profile_account,
+        category_account,
+        relevance_score,
+        &mut collection_accounts.iter(),
+    )?;
+    load_or_create_leaderboard(program_id, payer, leaderboard_account, system_program)?
+        .record(*knowledge_account.key, entry_attributions);
+
+    let (reward, curator_reward) = (shares.entry, shares.curator);
+    let total_reward = shares.total().ok_or(SolSageError::ArithmeticOverflow)?;
+    oracle_state.spend_budget(now, 1, total_reward)?;
+    oracle_state.store(oracle_state_account)?;
+    let fee = charge_attribution_fee(&ctx, treasury_account, total_reward)?;
+    if let Some(funding) = &funding {
+        fund_attribution_rewards(&ctx, funding, total_reward)?;
+    }
+
+    if let (Some(epoch), Some(epoch_account)) = (epoch.as_mut(), epoch_account) {
+        epoch.add_weight(reward)?;
+        epoch.store(epoch_account)?;
+    }
+
+    if let (Some(curator), Some(curator_account)) = (curator, curator_account) {
+        let mut curator_state = load_or_create_curator_account(&ctx, &curator, curator_account)?;
+        curator_state.attributions = curator_state
+            .attributions
+            .checked_add(1)
+            .ok_or(SolSageError::ArithmeticOverflow)?;
+        curator_state.pending_rewards = curator_state
+            .pending_rewards
+            .checked_add(curator_reward)
+            .ok_or(SolSageError::ArithmeticOverflow)?;
+        curator_state.store(curator_account)?;
+
+        SolSageEvent::CuratorRewarded {
+            curator,
+            attribution: *attribution_account.key,
+            reward: curator_reward,
+        }
+        .emit()?;
+    }
+
+    if let (Some(agent_id), Some(agent_stats_account)) = (agent_id, agent_stats_account) {
+        let mut agent_stats = load_or_create_agent_stats(&ctx, &agent_id, agent_stats_account)?;
+        agent_stats.record_attribution(total_reward, now)?;
+        agent_stats.store(agent_stats_account)?;
+    }
+
+    // Update protocol
+    protocol.total_attributions = protocol
+        .total_attributions
+        .checked_add(1)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    protocol.record_reward_shares(&shares)?;
+    protocol.fees_collected = protocol
+        .fees_collected
+        .checked_add(fee)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    protocol.store(protocol_account)?;
+
+    msg!("Attribution recorded, reward: {}", reward);
+    Ok(())
+}
+
+fn process_record_attribution_batch(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    query_hash: [u8; 32],
+    scores: Vec<u8>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let query_account = next_account_info(account_info_iter)?;
+    let oracle_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let treasury_account = next_account_info(account_info_iter)?;
+    let leaderboard_account = next_account_info(account_info_iter)?;
+    let oracle_state_account = next_account_info(account_info_iter)?;
+    let payer_stats_account = next_account_info(account_info_iter)?;
+
+    if !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    check_registered_oracle(program_id, payer.key, oracle_account)?;
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+    protocol.check_not_paused()?;
+    protocol.check_single_reports()?;
+
+    let instructions_account = match protocol.attribution_verifier {
+        Some(_) => Some(next_account_info(account_info_iter)?),
+        None => None,
+    };
+    protocol.check_attribution_caller(instructions_account)?;
+    let remaining_accounts = account_info_iter.as_slice();
+
+    // The accounts funding the rewards, when attributions fund them, trail
+    // the quadruples
+    let (remaining_accounts, funding) = match protocol.reward_source {
+        RewardSource::Payer => {
+            let (remaining_accounts, funding) = RewardFunding::split_last(program_id, remaining_accounts)?;
+            (remaining_accounts, Some(funding))
+        }
+        RewardSource::Mint => (remaining_accounts, None),
+    };
+
+    // The open epoch, when there is one, trails the quadruples
+    let (entry_accounts, epoch_account) = match remaining_accounts.split_last() {
+        Some((epoch_account, entry_accounts)) if protocol.epochs_enabled() => (entry_accounts, Some(epoch_account)),
+        _ => (remaining_accounts, None),
+    };
+
+    // Collection PDAs follow the quadruples
+    let quad_accounts_len = scores.len().checked_mul(4).ok_or(SolSageError::BatchLengthMismatch)?;
+    if scores.is_empty() || entry_accounts.len() < quad_accounts_len {
+        return Err(SolSageError::BatchLengthMismatch.into());
+    }
+    let (entry_accounts, collection_accounts) = entry_accounts.split_at(quad_accounts_len);
+    let collection_accounts = &mut collection_accounts.iter();
+
+    let now = Clock::get()?.unix_timestamp;
+    let mut epoch = load_open_epoch(program_id, &protocol, epoch_account, now)?;
+    let ctx = AttributionContext {
+        program_id,
+        payer,
+        oracle: *payer.key,
+        system_program,
+        protocol: &protocol,
+        query_hash,
+        epoch: epoch.as_ref().map(|epoch| epoch.index),
+        fees: protocol.attribution_fees(false, epoch.is_some()),
+        subscription_funded: matches!(funding, Some(RewardFunding::Subscription(_))),
+        metadata: AttributionMetadata::default(),
+        rent: Rent::get()?,
+        now,
+    };
+
+    let count = u8::try_from(scores.len()).map_err(|_| SolSageError::QueryAttributionLimit)?;
+    reserve_query_attributions(&ctx, query_account, count)?;
+
+    let mut ranked = Vec::with_capacity(scores.len());
+    let mut total_shares = RewardShares::default();
+    for (quad, relevance_score) in entry_accounts.chunks_exact(4).zip(scores) {
+        let (shares, entry_attributions) = record_attribution_to(
+            &ctx,
+            &quad[0],
+            &quad[1],
+            &quad[2],
+            &quad[3],
+            relevance_score,
+            collection_accounts,
+        )?;
+        ranked.push((*quad[0].key, entry_attributions));
+        total_shares = total_shares.checked_add(&shares).ok_or(SolSageError::ArithmeticOverflow)?;
+    }
+    let total_reward = total_shares.total().ok_or(SolSageError::ArithmeticOverflow)?;
+    let mut leaderboard = load_or_create_leaderboard(program_id, payer, leaderboard_account, system_program)?;
+    for (knowledge_entry, total_attributions) in ranked {
+        leaderboard.record(knowledge_entry, total_attributions);
+    }
+    drop(leaderboard);
+
+    if collection_accounts.next().is_some() {
+        return Err(SolSageError::BatchLengthMismatch.into());
+    }
+    let mut oracle_state =
+        load_or_create_oracle_state(program_id, payer, payer.key, oracle_state_account, system_program, &ctx.rent)?;
+    oracle_state.spend_budget(now, count.into(), total_reward)?;
+    oracle_state.store(oracle_state_account)?;
+    let mut payer_stats = load_or_create_payer_stats(program_id, payer, payer_stats_account, system_program, &ctx.rent)?;
+    payer_stats.record_attributions(now, count.into(), protocol.max_payer_attributions_per_day)?;
+    payer_stats.store(payer_stats_account)?;
+    let fee = charge_attribution_fee(&ctx, treasury_account, total_reward)?;
+    if let Some(funding) = &funding {
+        fund_attribution_rewards(&ctx, funding, total_reward)?;
+    }
+
+    if let (Some(epoch), Some(epoch_account)) = (epoch.as_mut(), epoch_account) {
+        epoch.add_weight(total_shares.entry)?;
+        epoch.store(epoch_account)?;
+    }
+
+    // Update protocol
+    protocol.total_attributions = protocol
+        .total_attributions
+        .checked_add(count as u64)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    protocol.record_reward_shares(&total_shares)?;
+    protocol.fees_collected = protocol
+        .fees_collected
+        .checked_add(fee)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    protocol.store(protocol_account)?;
+
+    msg!("Batch of {} attributions recorded, total reward: {}", count, total_reward);
+    Ok(())
+}
+
+/// Amount staked on an entry, how long it is locked and whether it comes
+/// with a receipt
+struct StakeTerms {
+    stake_amount: u64,
+    lockup: LockupTier,
+    mint_receipt: bool,
+    derived_from: Vec<Pubkey>,
+    chunks: Option<ChunkCommitment>,
+    identity: bool,
+}
+
+/// Check that `parent_accounts` are the entries of `derived_from`, no more
+/// than `MAX_PARENT_ENTRIES` of them and none repeated
+fn check_parent_entries(program_id: &Pubkey, derived_from: &[Pubkey], parent_accounts: &[AccountInfo]) -> ProgramResult {
+    if derived_from.len() > KnowledgeEntry::MAX_PARENT_ENTRIES {
+        return Err(SolSageError::InvalidParentEntries.into());
+    }
+
+    for (i, (parent, parent_account)) in derived_from.iter().zip(parent_accounts).enumerate() {
+        if parent_account.key != parent || derived_from[..i].contains(parent) {
+            return Err(SolSageError::InvalidParentEntries.into());
+        }
+        KnowledgeEntry::load(program_id, parent_account)?;
+    }
+    Ok(())
+}
+
+/// Mint `staker` a compressed NFT receipt named after `title` from the
+/// protocol's receipt tree, returning its asset ID
+fn mint_entry_receipt<'info>(
+    program_id: &Pubkey,
+    protocol: &Protocol,
+    staker: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    receipt_accounts: &mut std::slice::Iter<AccountInfo<'info>>,
+    title: &str,
+) -> Result<Pubkey, ProgramError> {
+    let tree_config = next_account_info(receipt_accounts)?;
+    let merkle_tree = next_account_info(receipt_accounts)?;
+    let receipt_authority = next_account_info(receipt_accounts)?;
+    let bubblegum_program = next_account_info(receipt_accounts)?;
+    let log_wrapper = next_account_info(receipt_accounts)?;
+    let compression_program = next_account_info(receipt_accounts)?;
+
+    if protocol.receipt_tree != Some(*merkle_tree.key) {
+        return Err(SolSageError::InvalidReceiptTree.into());
+    }
+
+    if *bubblegum_program.key != bubblegum::PROGRAM_ID
+        || *log_wrapper.key != bubblegum::SPL_NOOP_PROGRAM_ID
+        || *compression_program.key != bubblegum::SPL_ACCOUNT_COMPRESSION_PROGRAM_ID
+    {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let (authority_pda, bump) = syscalls::find_program_address(&[Protocol::RECEIPT_AUTHORITY_SEED], program_id);
+
+    if authority_pda != *receipt_authority.key || bubblegum::tree_config_address(merkle_tree.key) != *tree_config.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    // Leaves are numbered in mint order, so the receipt takes the count
+    // minted before it
+    let nonce = bubblegum::num_minted(&tree_config.data.borrow()).ok_or(ProgramError::InvalidAccountData)?;
+    let accounts = bubblegum::MintAccounts {
+        tree_config: tree_config.key,
+        leaf_owner: staker.key,
+        merkle_tree: merkle_tree.key,
+        payer: staker.key,
+        tree_delegate: receipt_authority.key,
+    };
+    invoke_signed(
+        &bubblegum::mint_v1(&accounts, title, ""),
+        &[
+            tree_config.clone(),
+            staker.clone(),
+            merkle_tree.clone(),
+            receipt_authority.clone(),
+            log_wrapper.clone(),
+            compression_program.clone(),
+            system_program.clone(),
+            bubblegum_program.clone(),
+        ],
+        &[&[Protocol::RECEIPT_AUTHORITY_SEED, &[bump]]],
+    )?;
+
+    Ok(bubblegum::asset_id(merkle_tree.key, nonce))
+}
+
+/// What vouches for an attribution a relayer submits, in place of the
+/// oracle's signature on the transaction
+#[derive(Clone, Copy)]
+enum Attestation {
+    /// Receipt the oracle signed, checked by the preceding Ed25519
+    /// instruction
+    Receipt { nonce: u64 },
+    /// Message a bridge posted for a registered emitter
+    Bridged,
+}
+
+/// Consumer an oracle reports an attribution on behalf of, and the chunk of
+/// the entry it used
+#[derive(Default, Clone)]
+struct AttributionMetadata {
+    agent_id: Option<[u8; 32]>,
+    model_id: Option<[u8; 32]>,
+    chunk: Option<ChunkProof>,
+}
+
+/// Accounts and values shared by every attribution in one instruction
+struct AttributionContext<'a, 'info> {
+    program_id: &'a Pubkey,
+    payer: &'a AccountInfo<'info>,
+    /// Oracle answering for the attributions: the payer, or the signer of
+    /// the receipt it relays
+    oracle: Pubkey,
+    system_program: &'a AccountInfo<'info>,
+    protocol: &'a Protocol,
+    query_hash: [u8; 32],
+    /// Open epoch the attributions accrue weight in
+    epoch: Option<u64>,
+    /// Shares carved out of each reward before the rest goes to the entry
+    fees: FeeConfig,
+    /// Whether a subscription funds the rewards, which then round up to an
+    /// entry's price floor instead of being rejected below it
+    subscription_funded: bool,
+    metadata: AttributionMetadata,
+    rent: Rent,
+    now: i64,
+}
+
+/// Transfer the protocol fee on `reward` from the payer to the treasury
+fn charge_attribution_fee<'info>(
+    ctx: &AttributionContext<'_, 'info>,
+    treasury_account: &AccountInfo<'info>,
+    reward: u64,
+) -> Result<u64, ProgramError> {
+    let treasury_pda = syscalls::create_program_address(
+        &[Protocol::TREASURY_SEED, &[ctx.protocol.treasury_bump]],
+        ctx.program_id,
+    )?;
+
+    if treasury_pda != *treasury_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    let fee = ctx.protocol.fee_on(reward).ok_or(SolSageError::ArithmeticOverflow)?;
+    if fee > 0 {
+        invoke(
+            &system_instruction::transfer(ctx.payer.key, treasury_account.key, fee),
+            &[ctx.payer.clone(), treasury_account.clone(), ctx.system_program.clone()],
+        )?;
+
+        SolSageEvent::AttributionFeeCharged { payer: *ctx.payer.key, fee }.emit()?;
+    }
+
+    Ok(fee)
+}
+
+/// Where an attribution payer funds rewards from
+enum RewardFunding<'a, 'info> {
+    /// The payer's SAGE token account, paying into the reward vault
+    Transfer {
+        payer_token_account: &'a AccountInfo<'info>,
+        reward_vault: &'a AccountInfo<'info>,
+        token_program: &'a AccountInfo<'info>,
+    },
+    /// The payer's subscription, whose deposits are already in the vault
+    Subscription(&'a AccountInfo<'info>),
+}
+
+impl<'a, 'info> RewardFunding<'a, 'info> {
+    /// Split the funding accounts off the end of `accounts`; of them, only a
+    /// subscription is owned by the program
+    fn split_last(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<(&'a [AccountInfo<'info>], Self), ProgramError> {
+        if let Some((subscription, rest)) = accounts.split_last().filter(|(last, _)| last.owner == program_id) {
+            return Ok((rest, Self::Subscription(subscription)));
+        }
+        let split = accounts.len().checked_sub(3).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let (rest, funding) = accounts.split_at(split);
+        Ok((
+            rest,
+            Self::Transfer {
+                payer_token_account: &funding[0],
+                reward_vault: &funding[1],
+                token_program: &funding[2],
+            },
+        ))
+    }
+}
+
+/// Fund `amount` SAGE of attribution rewards, moving it from the payer's
+/// token account into the reward vault or spending it from their
+/// subscription
+fn fund_attribution_rewards<'info>(
+    ctx: &AttributionContext<'_, 'info>,
+    funding: &RewardFunding<'_, 'info>,
+    amount: u64,
+) -> ProgramResult {
+    match funding {
+        RewardFunding::Transfer { payer_token_account, reward_vault, token_program } => {
+            if *token_program.key != spl_token::id() {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+
+            check_reward_vault(ctx.program_id, ctx.protocol, reward_vault)?;
+
+            if amount > 0 {
+                // The token program rejects a payer account of another mint
+                invoke(
+                    &spl_token::instruction::transfer(
+                        token_program.key,
+                        payer_token_account.key,
+                        reward_vault.key,
+                        ctx.payer.key,
+                        &[],
+                        amount,
+                    )?,
+                    &[
+                        (*payer_token_account).clone(),
+                        (*reward_vault).clone(),
+                        ctx.payer.clone(),
+                        (*token_program).clone(),
+                    ],
+                )?;
+            }
+        }
+        RewardFunding::Subscription(subscription_account) => {
+            let mut subscription = load_subscription(ctx.program_id, ctx.payer.key, subscription_account)?;
+            subscription.balance = subscription
+                .balance
+                .checked_sub(amount)
+                .ok_or(SolSageError::InsufficientSubscriptionBalance)?;
+            subscription.total_spent = subscription
+                .total_spent
+                .checked_add(amount)
+                .ok_or(SolSageError::ArithmeticOverflow)?;
+            subscription.store(subscription_account)?;
+        }
+    }
+
+    if amount > 0 {
+        SolSageEvent::AttributionRewardFunded { payer: *ctx.payer.key, amount }.emit()?;
+    }
+
+    Ok(())
+}
+
+/// Check `reward_vault` is the protocol's reward vault PDA
+fn check_reward_vault(program_id: &Pubkey, protocol: &Protocol, reward_vault: &AccountInfo) -> ProgramResult {
+    let reward_vault_pda = syscalls::create_program_address(
+        &[Protocol::REWARD_VAULT_SEED, &[protocol.reward_vault_bump]],
+        program_id,
+    )?;
+
+    if reward_vault_pda != *reward_vault.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    Ok(())
+}
+
+fn check_registered_oracle(
+    program_id: &Pubkey,
+    oracle: &Pubkey,
+    oracle_account: &AccountInfo,
+) -> ProgramResult {
+    let (oracle_pda, _) = syscalls::find_program_address(
+        &[AttributionOracle::SEED, oracle.as_ref()],
+        program_id,
+    );
+
+    if oracle_pda != *oracle_account.key || AttributionOracle::load(program_id, oracle_account).is_err() {
+        return Err(SolSageError::UnauthorizedOracle.into());
+    }
+
+    Ok(())
+}
+
+/// Signer of `receipt`, checked by the Ed25519 program instruction just
+/// before the current one
+fn receipt_signer(instructions_account: &AccountInfo, receipt: &[u8]) -> Result<Pubkey, ProgramError> {
+    let current = load_current_index_checked(instructions_account)?;
+    let previous = current.checked_sub(1).ok_or(SolSageError::InvalidAttributionReceipt)?;
+    let instruction = load_instruction_at_checked(previous.into(), instructions_account)?;
+    match receipts::signed_message(&instruction) {
+        Some((signer, message)) if message == receipt => Ok(signer),
+        _ => Err(SolSageError::InvalidAttributionReceipt.into()),
+    }
+}
+
+/// Registered emitter of the message in `message_account` and the message's
+/// sequence, once the emitter's bridge is found to have posted it with
+/// `payload`
+fn bridged_message(
+    program_id: &Pubkey,
+    emitter_account: &AccountInfo,
+    message_account: &AccountInfo,
+    payload: &[u8],
+) -> Result<(BridgeEmitter, u64), ProgramError> {
+    let emitter = BridgeEmitter::load(program_id, emitter_account)?;
+    let emitter_pda = syscalls::create_program_address(
+        &[BridgeEmitter::SEED, &emitter.emitter_chain.to_le_bytes(), &emitter.emitter_address, &[emitter.bump]],
+        program_id,
+    )?;
+
+    if emitter_pda != *emitter_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    if *message_account.owner != emitter.bridge {
+        return Err(SolSageError::InvalidBridgedMessage.into());
+    }
+
+    let data = message_account.data.borrow();
+    match bridge::posted_message(&data) {
+        Some(message)
+            if message.emitter_chain == emitter.emitter_chain
+                && message.emitter_address == emitter.emitter_address
+                && message.payload == payload =>
+        {
+            let sequence = message.sequence;
+            Ok((emitter, sequence))
+        }
+        _ => Err(SolSageError::InvalidBridgedMessage.into()),
+    }
+}
+
+/// Load the state of `oracle`, creating it at `payer`'s expense the first
+/// time it is needed
+fn load_or_create_oracle_state<'info>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'info>,
+    oracle: &Pubkey,
+    oracle_state_account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    rent: &Rent,
+) -> Result<OracleState, ProgramError> {
+    if !oracle_state_account.data_is_empty() {
+        let oracle_state = OracleState::load(program_id, oracle_state_account)?;
+        let oracle_state_pda = syscalls::create_program_address(
+            &[OracleState::SEED, oracle.as_ref(), &[oracle_state.bump]],
+            program_id,
+        )?;
+        if oracle_state_pda != *oracle_state_account.key || oracle_state.oracle != *oracle {
+            return Err(SolSageError::InvalidPda.into());
+        }
+        return Ok(oracle_state);
+    }
+
+    let (oracle_state_pda, bump) = syscalls::find_program_address(&[OracleState::SEED, oracle.as_ref()], program_id);
+
+    if oracle_state_pda != *oracle_state_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(oracle_state_account)?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            oracle_state_account.key,
+            rent.minimum_balance(OracleState::LEN),
+            OracleState::LEN as u64,
+            program_id,
+        ),
+        &[payer.clone(), oracle_state_account.clone(), system_program.clone()],
+        &[&[OracleState::SEED, oracle.as_ref(), &[bump]]],
+    )?;
+
+    Ok(OracleState {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        oracle: *oracle,
+        next_nonce: 0,
+        bump,
+        max_attributions_per_epoch: 0,
+        max_rewards_per_epoch: 0,
+        budget_epoch: 0,
+        epoch_attributions: 0,
+        epoch_rewards: 0,
+    })
+}
+
+/// Load the payer's stats PDA, creating it at their expense the first time
+/// they pay for an attribution
+fn load_or_create_payer_stats<'info>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'info>,
+    payer_stats_account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    rent: &Rent,
+) -> Result<PayerStats, ProgramError> {
+    if !payer_stats_account.data_is_empty() {
+        let payer_stats = PayerStats::load(program_id, payer_stats_account)?;
+        let payer_stats_pda =
+            syscalls::create_program_address(&[PayerStats::SEED, payer.key.as_ref(), &[payer_stats.bump]], program_id)?;
+        if payer_stats_pda != *payer_stats_account.key || payer_stats.payer != *payer.key {
+            return Err(SolSageError::InvalidPda.into());
+        }
+        return Ok(payer_stats);
+    }
+
+    let (payer_stats_pda, bump) = syscalls::find_program_address(&[PayerStats::SEED, payer.key.as_ref()], program_id);
+
+    if payer_stats_pda != *payer_stats_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(payer_stats_account)?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            payer_stats_account.key,
+            rent.minimum_balance(PayerStats::LEN),
+            PayerStats::LEN as u64,
+            program_id,
+        ),
+        &[payer.clone(), payer_stats_account.clone(), system_program.clone()],
+        &[&[PayerStats::SEED, payer.key.as_ref(), &[bump]]],
+    )?;
+
+    Ok(PayerStats {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        payer: *payer.key,
+        bump,
+        day: 0,
+        day_attributions: 0,
+        total_attributions: 0,
+    })
+}
+
+/// Count `count` more attributions against the query record, creating it on
+/// first use and enforcing the per-query cap and oracle
+fn reserve_query_attributions<'info>(
+    ctx: &AttributionContext<'_, 'info>,
+    query_account: &AccountInfo<'info>,
+    count: u8,
+) -> ProgramResult {
+    let (query_pda, query_bump) = syscalls::find_program_address(
+        &[QueryRecord::SEED, &ctx.query_hash],
+        ctx.program_id,
+    );
+
+    if query_pda != *query_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    let mut query_record = if query_account.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                ctx.payer.key,
+                query_account.key,
+                ctx.rent.minimum_balance(QueryRecord::LEN),
+                QueryRecord::LEN as u64,
+                ctx.program_id,
+            ),
+            &[ctx.payer.clone(), query_account.clone(), ctx.system_program.clone()],
+            &[&[QueryRecord::SEED, &ctx.query_hash, &[query_bump]]],
+        )?;
+
+        QueryRecord {
+            is_initialized: true,
+            schema_version: SCHEMA_VERSION,
+            query_hash: ctx.query_hash,
+            oracle: ctx.oracle,
+            attribution_count: 0,
+            first_attributed_at: ctx.now,
+            bump: query_bump,
+        }
+    } else {
+        QueryRecord::load(ctx.program_id, query_account)?
+    };
+
+    if query_record.oracle != ctx.oracle {
+        return Err(SolSageError::QueryOracleMismatch.into());
+    }
+
+    let new_count = query_record
+        .attribution_count
+        .checked_add(count)
+        .filter(|c| *c <= QueryRecord::MAX_ATTRIBUTIONS)
+        .ok_or(SolSageError::QueryAttributionLimit)?;
+
+    query_record.attribution_count = new_count;
+    query_record.store(query_account)?;
+    Ok(())
+}
+
+/// Allocate the attribution PDA of `knowledge_account` for the context's
+/// query, at the payer's expense. `bump` is the PDA's bump.
+fn create_attribution_account<'info>(
+    ctx: &AttributionContext<'_, 'info>,
+    knowledge_account: &AccountInfo<'info>,
+    attribution_account: &AccountInfo<'info>,
+    bump: u8,
+) -> ProgramResult {
+    invoke_signed(
+        &system_instruction::create_account(
+            ctx.payer.key,
+            attribution_account.key,
+            ctx.rent.minimum_balance(Attribution::LEN),
+            Attribution::LEN as u64,
+            ctx.program_id,
+        ),
+        &[ctx.payer.clone(), attribution_account.clone(), ctx.system_program.clone()],
+        &[&[Attribution::SEED, &ctx.query_hash, knowledge_account.key.as_ref(), &[bump]]],
+    )
+}
+
+/// Add the oracle's report to the consensus or score aggregation on an
+/// attribution, creating it pending on the first report. Returns the
+/// relevance score the attribution finalizes at once the protocol's
+/// threshold of matching reports is met, or `None` while it waits on more.
+fn vote_on_attribution<'info>(
+    ctx: &AttributionContext<'_, 'info>,
+    knowledge_account: &AccountInfo<'info>,
+    attribution_account: &AccountInfo<'info>,
+    relevance_score: u8,
+) -> Result<Option<u8>, ProgramError> {
+    if relevance_score > solsage_core::MAX_RELEVANCE_SCORE {
+        return Err(SolSageError::InvalidRelevanceScore.into());
+    }
+
+    if knowledge_account.owner != ctx.program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let (attribution_pda, bump) = syscalls::find_program_address(
+        &[Attribution::SEED, &ctx.query_hash, knowledge_account.key.as_ref()],
+        ctx.program_id,
+    );
+
+    if attribution_pda != *attribution_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    let mut attribution = if attribution_account.data_is_empty() {
+        check_uninitialized(attribution_account)?;
+        create_attribution_account(ctx, knowledge_account, attribution_account, bump)?;
+        Attribution {
+            is_initialized: true,
+            schema_version: SCHEMA_VERSION,
+            knowledge_entry: *knowledge_account.key,
+            query_hash: ctx.query_hash,
+            relevance_score,
+            reward: 0,
+            timestamp: ctx.now,
+            unlocks_at: 0,
+            reward_claimed: false,
+            reversed: false,
+            epoch: None,
+            bump,
+            agent_id: ctx.metadata.agent_id,
+            model_id: ctx.metadata.model_id,
+            pending: true,
+            votes: Vec::new(),
+            streamed: false,
+            chunk_index: None,
+        }
+    } else {
+        let attribution = Attribution::load(ctx.program_id, attribution_account)?;
+        if !attribution.pending {
+            return Err(SolSageError::AlreadyInitialized.into());
+        }
+
+        if ctx.protocol.aggregation_enabled() && attribution.aggregation_closed(ctx.protocol, ctx.now) {
+            return Err(SolSageError::AggregationWindowClosed.into());
+        }
+        attribution
+    };
+
+    let finalized_score = attribution.cast_vote(ctx.oracle, relevance_score, ctx.protocol)?;
+    attribution.store(attribution_account)?;
+
+    SolSageEvent::AttributionVoteCast {
+        attribution: *attribution_account.key,
+        oracle: ctx.oracle,
+        relevance_score,
+        votes: attribution.votes.len() as u8,
+    }
+    .emit()?;
+
+    Ok(finalized_score)
+}
+
+fn record_attribution_to<'info>(
+    ctx: &AttributionContext<'_, 'info>,
+    knowledge_account: &AccountInfo<'info>,
+    attribution_account: &AccountInfo<'info>,
+    profile_account: &AccountInfo<'info>,
+    category_account: &AccountInfo<'info>,
+    relevance_score: u8,
+    collection_accounts: &mut std::slice::Iter<AccountInfo<'info>>,
+) -> Result<(RewardShares, u64), ProgramError> {
+    if relevance_score > solsage_core::MAX_RELEVANCE_SCORE {
+        return Err(SolSageError::InvalidRelevanceScore.into());
+    }
+
+    if knowledge_account.owner != ctx.program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    // Derive attribution PDA
+    let (attribution_pda, bump) = syscalls::find_program_address(
+        &[Attribution::SEED, &ctx.query_hash, knowledge_account.key.as_ref()],
+        ctx.program_id,
+    );
+
+    if attribution_pda != *attribution_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    // An attribution pending consensus is finalized in place
+    let votes = if attribution_account.data_is_empty() {
+        check_uninitialized(attribution_account)?;
+        create_attribution_account(ctx, knowledge_account, attribution_account, bump)?;
+        Vec::new()
+    } else {
+        let attribution = Attribution::load(ctx.program_id, attribution_account)?;
+        if !attribution.pending {
+            return Err(SolSageError::AlreadyInitialized.into());
+        }
+        attribution.votes
+    };
+
+    // Update knowledge entry
+    let mut knowledge = KnowledgeEntry::load(ctx.program_id, knowledge_account)?;
+    if !knowledge.is_active {
+        return Err(SolSageError::KnowledgeInactive.into());
+    }
+
+    if ctx.now >= knowledge.expires_at {
+        return Err(SolSageError::KnowledgeExpired.into());
+    }
+
+    knowledge.mark_attributed(ctx.now, ctx.protocol.attribution_cooldown_secs)?;
+    let chunk_index = knowledge.verify_chunk(ctx.metadata.chunk.as_ref())?;
+
+    if knowledge.category != *category_account.key {
+        return Err(SolSageError::CategoryMismatch.into());
+    }
+    let mut category = load_category(ctx.program_id, category_account)?;
+
+    // Calculate reward from the attributions seen before this one, scaled
+    // by the category multiplier and lockup boost and decayed by the entry's
+    // age
+    let reward = ctx
+        .protocol
+        .reward_curve
+        .reward(
+            ctx.protocol.reward_per_attribution,
+            relevance_score,
+            knowledge.total_attributions,
+            ctx.protocol.score_divisor,
+        )
+        .and_then(|reward| category.apply_multiplier(reward))
+        .and_then(|reward| knowledge.lockup_tier.apply_boost(reward))
+        .and_then(|reward| knowledge.apply_quality(reward))
+        .and_then(|reward| solsage_core::apply_bps(reward, ctx.protocol.score_weight_bps(knowledge.created_at, ctx.now)))
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    let reward = solsage_core::apply_price_floor(reward, knowledge.min_reward_per_use, ctx.subscription_funded)
+        .ok_or(SolSageError::RewardBelowPriceFloor)?;
+    let shares = ctx
+        .fees
+        .split(reward)
+        .and_then(|shares| ctx.protocol.apply_low_relevance(shares, relevance_score, ctx.epoch.is_some()))
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    // Epoch rewards are weights, so parents are paid a royalty on none
+    let reward = if ctx.epoch.is_some() {
+        shares.entry
+    } else {
+        knowledge.set_aside_citation_royalty(shares.entry, ctx.protocol.citation_royalty_bps)?
+    };
+    knowledge.total_attributions = knowledge
+        .total_attributions
+        .checked_add(1)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    // Epoch attributions carry a weight, not SAGE, so they always vest
+    let streamed = ctx.protocol.streams_rewards() && ctx.epoch.is_none();
+    let unlocks_at = if streamed {
+        knowledge.stream_rewards(reward, ctx.now, ctx.protocol.reward_stream_secs)?;
+        ctx.now
+    } else {
+        knowledge.accrue_rewards(reward, ctx.now, ctx.protocol.vesting_period_secs)?
+    };
+    knowledge.store(knowledge_account)?;
+
+    let mut profile = load_staker_profile(ctx.program_id, &knowledge.staker, profile_account)?;
+    profile.record_attribution(relevance_score)?;
+    profile.store(profile_account)?;
+
+    category.record_attribution(reward)?;
+    category.store(category_account)?;
+
+    record_collection_attribution(ctx.program_id, &knowledge, collection_accounts, reward, ctx.now)?;
+    check_access_pass(ctx.program_id, &knowledge, knowledge_account.key, ctx.payer.key, collection_accounts)?;
+
+    // Create attribution
+    let attribution = Attribution {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        knowledge_entry: *knowledge_account.key,
+        query_hash: ctx.query_hash,
+        relevance_score,
+        reward,
+        timestamp: ctx.now,
+        unlocks_at,
+        reward_claimed: false,
+        reversed: false,
+        epoch: ctx.epoch,
+        bump,
+        agent_id: ctx.metadata.agent_id,
+        model_id: ctx.metadata.model_id,
+        pending: false,
+        votes,
+        streamed,
+        chunk_index,
+    };
+    attribution.store(attribution_account)?;
+
+    SolSageEvent::AttributionRecorded {
+        knowledge_entry: *knowledge_account.key,
+        attribution: *attribution_account.key,
+        query_hash: ctx.query_hash,
+        relevance_score,
+        reward,
+        unlocks_at,
+        license: knowledge.license,
+    }
+    .emit()?;
+
+    Ok((shares, knowledge.total_attributions))
+}
+
+/// Check `payer` holds an access pass to a private entry, taking it from
+/// `entry_accounts` after the entry's collection
+fn check_access_pass(
+    program_id: &Pubkey,
+    knowledge: &KnowledgeEntry,
+    knowledge_entry: &Pubkey,
+    payer: &Pubkey,
+    entry_accounts: &mut std::slice::Iter<AccountInfo>,
+) -> ProgramResult {
+    if knowledge.visibility == Visibility::Public {
+        return Ok(());
+    }
+    let pass_account = next_account_info(entry_accounts).map_err(|_| SolSageError::AccessPassRequired)?;
+    if pass_account.data_is_empty() {
+        return Err(SolSageError::AccessPassRequired.into());
+    }
+
+    let pass = AccessPass::load(program_id, pass_account)?;
+    let pass_pda = syscalls::create_program_address(
+        &[AccessPass::SEED, knowledge_entry.as_ref(), payer.as_ref(), &[pass.bump]],
+        program_id,
+    )
+    .map_err(|_| SolSageError::AccessPassRequired)?;
+
+    if pass_pda != *pass_account.key {
+        return Err(SolSageError::AccessPassRequired.into());
+    }
+
+    Ok(())
+}
+
+/// Count an attribution towards the entry's collection, taking its PDA from
+/// `collection_accounts` when the entry is in one
+fn record_collection_attribution(
+    program_id: &Pubkey,
+    knowledge: &KnowledgeEntry,
+    collection_accounts: &mut std::slice::Iter<AccountInfo>,
+    reward: u64,
+    now: i64,
+) -> ProgramResult {
+    let Some(collection_key) = knowledge.collection else {
+        return Ok(());
+    };
+    let collection_account = next_account_info(collection_accounts)?;
+
+    if *collection_account.key != collection_key {
+        return Err(SolSageError::CollectionMismatch.into());
+    }
+
+    let mut collection = load_collection(program_id, collection_account)?;
+    collection.record_attribution(reward, now)?;
+    collection.store(collection_account)
+}
+
+/// Load the curator account PDA for `curator`, creating it at the payer's
+/// expense on first use
+fn load_or_create_curator_account<'info>(
+    ctx: &AttributionContext<'_, 'info>,
+    curator: &Pubkey,
+    curator_account: &AccountInfo<'info>,
+) -> Result<CuratorAccount, ProgramError> {
+    if !curator_account.data_is_empty() {
+        return load_curator_account(ctx.program_id, curator, curator_account);
+    }
+
+    let (curator_pda, bump) = syscalls::find_program_address(
+        &[CuratorAccount::SEED, curator.as_ref()],
+        ctx.program_id,
+    );
+
+    if curator_pda != *curator_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(curator_account)?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            ctx.payer.key,
+            curator_account.key,
+            ctx.rent.minimum_balance(CuratorAccount::LEN),
+            CuratorAccount::LEN as u64,
+            ctx.program_id,
+        ),
+        &[ctx.payer.clone(), curator_account.clone(), ctx.system_program.clone()],
+        &[&[CuratorAccount::SEED, curator.as_ref(), &[bump]]],
+    )?;
+
+    Ok(CuratorAccount {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        curator: *curator,
+        attributions: 0,
+        pending_rewards: 0,
+        total_claimed: 0,
+        bump,
+    })
+}
+
+/// Load the stats PDA of `agent_id`, creating it at the payer's expense on
+/// the agent's first attribution
+fn load_or_create_agent_stats<'info>(
+    ctx: &AttributionContext<'_, 'info>,
+    agent_id: &[u8; 32],
+    agent_stats_account: &AccountInfo<'info>,
+) -> Result<AgentStats, ProgramError> {
+    if !agent_stats_account.data_is_empty() {
+        let agent_stats = AgentStats::load(ctx.program_id, agent_stats_account)?;
+        let agent_stats_pda = syscalls::create_program_address(
+            &[AgentStats::SEED, agent_id, &[agent_stats.bump]],
+            ctx.program_id,
+        )?;
+
+        if agent_stats_pda != *agent_stats_account.key {
+            return Err(SolSageError::InvalidPda.into());
+        }
+
+        return Ok(agent_stats);
+    }
+
+    let (agent_stats_pda, bump) = syscalls::find_program_address(&[AgentStats::SEED, agent_id], ctx.program_id);
+
+    if agent_stats_pda != *agent_stats_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(agent_stats_account)?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            ctx.payer.key,
+            agent_stats_account.key,
+            ctx.rent.minimum_balance(AgentStats::LEN),
+            AgentStats::LEN as u64,
+            ctx.program_id,
+        ),
+        &[ctx.payer.clone(), agent_stats_account.clone(), ctx.system_program.clone()],
+        &[&[AgentStats::SEED, agent_id, &[bump]]],
+    )?;
+
+    Ok(AgentStats {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        agent_id: *agent_id,
+        attributions: 0,
+        rewards: 0,
+        last_attribution_at: 0,
+        bump,
+    })
+}
+
+fn load_curator_account(
+    program_id: &Pubkey,
+    curator: &Pubkey,
+    curator_account: &AccountInfo,
+) -> Result<CuratorAccount, ProgramError> {
+    let curator_state = CuratorAccount::load(program_id, curator_account)?;
+    let curator_pda = syscalls::create_program_address(
+        &[CuratorAccount::SEED, curator.as_ref(), &[curator_state.bump]],
+        program_id,
+    )?;
+
+    if curator_pda != *curator_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    Ok(curator_state)
+}
+
+fn process_claim_rewards(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let claimer = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let mint_accounts = RewardMintAccounts {
+        sage_mint: next_account_info(account_info_iter)?,
+        destination: next_account_info(account_info_iter)?,
+        mint_authority: next_account_info(account_info_iter)?,
+        token_program: next_account_info(account_info_iter)?,
+    };
+    let profile_account = next_account_info(account_info_iter)?;
+
+    if !claimer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Minting authority is at stake, so the protocol must belong to us
+    let protocol = load_protocol(program_id, protocol_account)?;
+    protocol.check_not_paused()?;
+    let clock = Clock::get()?;
+
+    let mut profile = load_claim_profile(program_id, claimer, profile_account)?;
+    let staker = profile.staker;
+
+    let knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+    let co_author_accounts = knowledge
+        .royalty_splits
+        .iter()
+        .map(|_| next_account_info(account_info_iter))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut reward_amount: u64 = 0;
+    let mut attributions: u64 = 0;
+    while let Some(attribution_account) = account_info_iter.next() {
+        let reward = take_attribution_reward(
+            program_id,
+            &staker,
+            knowledge_account,
+            attribution_account,
+            account_info_iter,
+            clock.unix_timestamp,
+        )?;
+        reward_amount = reward_amount.checked_add(reward).ok_or(SolSageError::ArithmeticOverflow)?;
+        attributions += 1;
+    }
+
+    // Streamed rewards and royalties accrue on the entry rather than its
+    // attributions
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+    if knowledge.staker != staker {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+    let streamed = knowledge.claim_streamed_rewards(clock.unix_timestamp)?;
+    let cited = knowledge.claim_citation_rewards()?;
+    knowledge.store(knowledge_account)?;
+    reward_amount = reward_amount
+        .checked_add(streamed)
+        .and_then(|amount| amount.checked_add(cited))
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+
+    if reward_amount == 0 {
+        return Err(SolSageError::NoRewardsToClaim.into());
+    }
+
+    mint_split_rewards(
+        program_id,
+        &protocol,
+        knowledge_account.key,
+        &knowledge,
+        &mint_accounts,
+        &co_author_accounts,
+        reward_amount,
+    )?;
+
+    profile.cumulative_rewards = profile
+        .cumulative_rewards
+        .checked_add(reward_amount)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    profile.store(profile_account)?;
+
+    SolSageEvent::RewardsClaimed {
+        staker,
+        amount: reward_amount,
+        attributions,
+    }
+    .emit()?;
+
+    msg!("Claimed {} SAGE tokens from {} attributions", reward_amount, attributions);
+    Ok(())
+}
+
+fn process_claim_all_rewards(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let claimer = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let mint_accounts = RewardMintAccounts {
+        sage_mint: next_account_info(account_info_iter)?,
+        destination: next_account_info(account_info_iter)?,
+        mint_authority: next_account_info(account_info_iter)?,
+        token_program: next_account_info(account_info_iter)?,
+    };
+    let profile_account = next_account_info(account_info_iter)?;
+
+    if !claimer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+    protocol.check_not_paused()?;
+    let clock = Clock::get()?;
+
+    let mut profile = load_claim_profile(program_id, claimer, profile_account)?;
+    let staker = profile.staker;
+
+    let mut reward_amount: u64 = 0;
+    let mut attributions: u64 = 0;
+    while let Some(knowledge_account) = account_info_iter.next() {
+        // A single mint to the staker cannot pay co-authors their shares
+        if !KnowledgeEntry::load(program_id, knowledge_account)?.royalty_splits.is_empty() {
+            return Err(SolSageError::CoAuthoredEntry.into());
+        }
+
+        let attribution_account = next_account_info(account_info_iter)?;
+        let reward = take_attribution_reward(
+            program_id,
+            &staker,
+            knowledge_account,
+            attribution_account,
+            account_info_iter,
+            clock.unix_timestamp,
+        )?;
+        reward_amount = reward_amount.checked_add(reward).ok_or(SolSageError::ArithmeticOverflow)?;
+        attributions += 1;
+    }
+
+    if reward_amount == 0 {
+        return Err(SolSageError::NoRewardsToClaim.into());
+    }
+
+    mint_rewards(program_id, &protocol, &staker, &mint_accounts, reward_amount)?;
+
+    profile.cumulative_rewards = profile
+        .cumulative_rewards
+        .checked_add(reward_amount)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    profile.store(profile_account)?;
+
+    SolSageEvent::RewardsClaimed {
+        staker,
+        amount: reward_amount,
+        attributions,
+    }
+    .emit()?;
+
+    msg!("Claimed {} SAGE tokens from {} attributions", reward_amount, attributions);
+    Ok(())
+}
+
+/// Load the index PDA of `staker`, creating it at their expense on their
+/// first stake
+fn load_or_create_staker_index<'info>(
+    program_id: &Pubkey,
+    staker: &AccountInfo<'info>,
+    staker_index_account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<StakerIndex, ProgramError> {
+    if !staker_index_account.data_is_empty() {
+        let staker_index = StakerIndex::load(program_id, staker_index_account)?;
+        let staker_index_pda = syscalls::create_program_address(
+            &[StakerIndex::SEED, staker.key.as_ref(), &[staker_index.bump]],
+            program_id,
+        )?;
+
+        if staker_index_pda != *staker_index_account.key {
+            return Err(SolSageError::InvalidPda.into());
+        }
+
+        return Ok(staker_index);
+    }
+
+    let (staker_index_pda, bump) =
+        syscalls::find_program_address(&[StakerIndex::SEED, staker.key.as_ref()], program_id);
+
+    if staker_index_pda != *staker_index_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            staker.key,
+            staker_index_account.key,
+            Rent::get()?.minimum_balance(StakerIndex::LEN),
+            StakerIndex::LEN as u64,
+            program_id,
+        ),
+        &[staker.clone(), staker_index_account.clone(), system_program.clone()],
+        &[&[StakerIndex::SEED, staker.key.as_ref(), &[bump]]],
+    )?;
+
+    Ok(StakerIndex {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        staker: *staker.key,
+        entry_count: 0,
+        bump,
+        closed_entries: 0,
+    })
+}
+
+/// Load the profile PDA for `staker`, creating it at `payer`'s expense on
+/// first use
+fn load_or_create_staker_profile<'info>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'info>,
+    staker: &Pubkey,
+    profile_account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    rent: &Rent,
+) -> Result<StakerProfile, ProgramError> {
+    if !profile_account.data_is_empty() {
+        return load_staker_profile(program_id, staker, profile_account);
+    }
+
+    let (profile_pda, bump) = syscalls::find_program_address(
+        &[StakerProfile::SEED, staker.as_ref()],
+        program_id,
+    );
+
+    if profile_pda != *profile_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            profile_account.key,
+            rent.minimum_balance(StakerProfile::LEN),
+            StakerProfile::LEN as u64,
+            program_id,
+        ),
+        &[payer.clone(), profile_account.clone(), system_program.clone()],
+        &[&[StakerProfile::SEED, staker.as_ref(), &[bump]]],
+    )?;
+
+    Ok(StakerProfile {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        staker: *staker,
+        total_entries: 0,
+        total_attributions: 0,
+        total_relevance: 0,
+        cumulative_rewards: 0,
+        claim_delegate: None,
+        bump,
+    })
+}
+
+/// Count an entry handed from `old_owner` to `new_owner` on their profiles,
+/// creating the new owner's paid by `payer`. Profiles created for new
+/// owners before handovers were counted may have no entry to give up.
+fn move_profile_entry<'info>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'info>,
+    old_owner: &Pubkey,
+    old_profile_account: &AccountInfo<'info>,
+    new_owner: &Pubkey,
+    new_profile_account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> ProgramResult {
+    let mut new_profile = load_or_create_staker_profile(
+        program_id,
+        payer,
+        new_owner,
+        new_profile_account,
+        system_program,
+        &Rent::get()?,
+    )?;
+    if old_owner == new_owner {
+        return new_profile.store(new_profile_account);
+    }
+
+    let mut old_profile = load_staker_profile(program_id, old_owner, old_profile_account)?;
+    old_profile.total_entries = old_profile.total_entries.saturating_sub(1);
+    new_profile.total_entries = new_profile.total_entries.checked_add(1).ok_or(SolSageError::ArithmeticOverflow)?;
+    old_profile.store(old_profile_account)?;
+    new_profile.store(new_profile_account)
+}
+
+fn load_staker_profile(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    profile_account: &AccountInfo,
+) -> Result<StakerProfile, ProgramError> {
+    let profile = StakerProfile::load(program_id, profile_account)?;
+    let profile_pda = syscalls::create_program_address(
+        &[StakerProfile::SEED, staker.as_ref(), &[profile.bump]],
+        program_id,
+    )?;
+
+    if profile_pda != *profile_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    Ok(profile)
+}
+
+/// Load the profile a claim is made against, checking the signer is its
+/// staker or their claim delegate
+fn load_claim_profile(
+    program_id: &Pubkey,
+    claimer: &AccountInfo,
+    profile_account: &AccountInfo,
+) -> Result<StakerProfile, ProgramError> {
+    // The profile names its own staker; the PDA check proves it
+    let profile = StakerProfile::load(program_id, profile_account)?;
+    let profile_pda = syscalls::create_program_address(
+        &[StakerProfile::SEED, profile.staker.as_ref(), &[profile.bump]],
+        program_id,
+    )?;
+
+    if profile_pda != *profile_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    if profile.staker != *claimer.key && profile.claim_delegate != Some(*claimer.key) {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    Ok(profile)
+}
+
+/// Token accounts every claim path mints through
+struct RewardMintAccounts<'a, 'info> {
+    sage_mint: &'a AccountInfo<'info>,
+    destination: &'a AccountInfo<'info>,
+    mint_authority: &'a AccountInfo<'info>,
+    token_program: &'a AccountInfo<'info>,
+}
+
+/// Mark one vested attribution of the staker's entry claimed, deduct its
+/// reward from the entry and return the SAGE it pays
+fn take_attribution_reward(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    knowledge_account: &AccountInfo,
+    attribution_account: &AccountInfo,
+    epoch_accounts: &mut std::slice::Iter<AccountInfo>,
+    now: i64,
+) -> Result<u64, ProgramError> {
+    let knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.staker != *staker {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    let unlocked = AttributionRelease { by: now, otherwise: SolSageError::RewardLocked };
+    release_attribution_reward(program_id, knowledge, knowledge_account, attribution_account, epoch_accounts, unlocked)
+}
+
+/// Latest time an attribution's reward must have unlocked by to be released,
+/// and the error rejecting it otherwise
+struct AttributionRelease {
+    by: i64,
+    otherwise: SolSageError,
+}
+
+/// Mark one attribution of the entry paid out once its reward unlocked as
+/// `release` requires, deduct the reward from the entry and return the SAGE
+/// it pays. An attribution recorded in an epoch takes that epoch's PDA from
+/// `epoch_accounts` and pays its share of the epoch budget.
+fn release_attribution_reward(
+    program_id: &Pubkey,
+    mut knowledge: KnowledgeEntry,
+    knowledge_account: &AccountInfo,
+    attribution_account: &AccountInfo,
+    epoch_accounts: &mut std::slice::Iter<AccountInfo>,
+    release: AttributionRelease,
+) -> Result<u64, ProgramError> {
+    let mut attribution = Attribution::load(program_id, attribution_account)?;
+
+    if attribution.knowledge_entry != *knowledge_account.key {
+        return Err(SolSageError::AttributionMismatch.into());
+    }
+
+    if attribution.pending {
+        return Err(SolSageError::AttributionPending.into());
+    }
+
+    if attribution.reward_claimed {
+        return Err(SolSageError::RewardAlreadyClaimed.into());
+    }
+
+    if attribution.reversed {
+        return Err(SolSageError::AttributionReversed.into());
+    }
+
+    if attribution.streamed {
+        return Err(SolSageError::RewardStreamed.into());
+    }
+
+    // Only vested rewards are released
+    if attribution.unlocks_at > release.by {
+        return Err(release.otherwise.into());
+    }
+
+    let payout = match attribution.epoch {
+        Some(index) => {
+            let epoch = load_epoch(program_id, next_account_info(epoch_accounts)?)?;
+            if epoch.index != index {
+                return Err(SolSageError::InvalidPda.into());
+            }
+            epoch.share_of(attribution.reward)?
+        }
+        None => attribution.reward,
+    };
+
+    // Slashing zeroes the entry's ledger, forfeiting unclaimed attributions
+    knowledge.pending_rewards = knowledge
+        .pending_rewards
+        .checked_sub(attribution.reward)
+        .ok_or(SolSageError::NoRewardsToClaim)?;
+    knowledge.store(knowledge_account)?;
+
+    attribution.reward_claimed = true;
+    attribution.store(attribution_account)?;
+
+    Ok(payout)
+}
+
+/// Mint `amount` SAGE to the staker's token account, enforcing the supply
+/// cap, or pay it out of the reward vault when attributions fund rewards
+fn mint_rewards(
+    program_id: &Pubkey,
+    protocol: &Protocol,
+    staker: &Pubkey,
+    accounts: &RewardMintAccounts,
+    amount: u64,
+) -> ProgramResult {
+    if *accounts.token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mint_authority_pda = syscalls::create_program_address(
+        &[Protocol::MINT_AUTHORITY_SEED, &[protocol.mint_authority_bump]],
+        program_id,
+    )?;
+
+    if mint_authority_pda != *accounts.mint_authority.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    // Rewards must land in a SAGE account the staker controls
+    if *accounts.destination.owner != spl_token::id() {
+        return Err(SolSageError::InvalidTokenAccountOwner.into());
+    }
+    let destination = spl_token::state::Account::unpack(&accounts.destination.data.borrow())?;
+    if destination.mint != protocol.reward_mint {
+        return Err(SolSageError::InvalidRewardMint.into());
+    }
+    if destination.owner != *staker {
+        return Err(SolSageError::InvalidTokenAccountOwner.into());
+    }
+
+    // The reward vault stands in for the mint
+    if protocol.reward_source == RewardSource::Payer {
+        check_reward_vault(program_id, protocol, accounts.sage_mint)?;
+
+        return invoke_signed(
+            &spl_token::instruction::transfer(
+                accounts.token_program.key,
+                accounts.sage_mint.key,
+                accounts.destination.key,
+                accounts.mint_authority.key,
+                &[],
+                amount,
+            )?,
+            &[
+                accounts.sage_mint.clone(),
+                accounts.destination.clone(),
+                accounts.mint_authority.clone(),
+                accounts.token_program.clone(),
+            ],
+            &[&[Protocol::MINT_AUTHORITY_SEED, &[protocol.mint_authority_bump]]],
+        );
+    }
+
+    if protocol.reward_mint != *accounts.sage_mint.key {
+        return Err(SolSageError::InvalidRewardMint.into());
+    }
+
+    // Enforce the supply cap before minting
+    let mint = spl_token::state::Mint::unpack(&accounts.sage_mint.data.borrow())?;
+    let new_supply = mint
+        .supply
+        .checked_add(amount)
+        .ok_or(SolSageError::SupplyCapExceeded)?;
+    if new_supply > protocol.max_supply {
+        return Err(SolSageError::SupplyCapExceeded.into());
+    }
+
+    invoke_signed(
+        &spl_token::instruction::mint_to(
+            accounts.token_program.key,
+            accounts.sage_mint.key,
+            accounts.destination.key,
+            accounts.mint_authority.key,
+            &[],
+            amount,
+        )?,
+        &[
+            accounts.sage_mint.clone(),
+            accounts.destination.clone(),
+            accounts.mint_authority.clone(),
+            accounts.token_program.clone(),
+        ],
+        &[&[Protocol::MINT_AUTHORITY_SEED, &[protocol.mint_authority_bump]]],
+    )
+}
+
+/// Mint an entry's claimed `amount`, paying each co-author their share into
+/// the matching token account of `co_author_accounts` and the rest to the
+/// entry owner
+fn mint_split_rewards<'a, 'info>(
+    program_id: &Pubkey,
+    protocol: &Protocol,
+    knowledge_key: &Pubkey,
+    knowledge: &KnowledgeEntry,
+    accounts: &RewardMintAccounts<'a, 'info>,
+    co_author_accounts: &[&'a AccountInfo<'info>],
+    amount: u64,
+) -> ProgramResult {
+    let (staker_amount, shares) = knowledge.split_royalties(amount).ok_or(SolSageError::ArithmeticOverflow)?;
+
+    for ((split, share), destination) in knowledge.royalty_splits.iter().zip(shares).zip(co_author_accounts) {
+        if share == 0 {
+            continue;
+        }
+        let co_author_accounts = RewardMintAccounts {
+            sage_mint: accounts.sage_mint,
+            destination,
+            mint_authority: accounts.mint_authority,
+            token_program: accounts.token_program,
+        };
+        mint_rewards(program_id, protocol, &split.co_author, &co_author_accounts, share)?;
+
+        SolSageEvent::RoyaltyPaid {
+            knowledge_entry: *knowledge_key,
+            co_author: split.co_author,
+            amount: share,
+        }
+        .emit()?;
+    }
+
+    if staker_amount > 0 {
+        mint_rewards(program_id, protocol, &knowledge.staker, accounts, staker_amount)?;
+    }
+    Ok(())
+}
+
+fn process_initialize_mint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_supply: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let sage_mint = next_account_info(account_info_iter)?;
+    let mint_authority = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    if protocol.reward_mint != Pubkey::default() {
+        return Err(SolSageError::MintAlreadyInitialized.into());
+    }
+
+    // Derive mint PDAs
+    let (mint_pda, mint_bump) = syscalls::find_program_address(
+        &[Protocol::MINT_SEED],
+        program_id,
+    );
+    let (mint_authority_pda, mint_authority_bump) = syscalls::find_program_address(
+        &[Protocol::MINT_AUTHORITY_SEED],
+        program_id,
+    );
+
+    if mint_pda != *sage_mint.key || mint_authority_pda != *mint_authority.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(sage_mint)?;
+
+    // Create the mint account, owned by the token program
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            sage_mint.key,
+            rent.minimum_balance(spl_token::state::Mint::LEN),
+            spl_token::state::Mint::LEN as u64,
+            token_program.key,
+        ),
+        &[authority.clone(), sage_mint.clone(), system_program.clone()],
+        &[&[Protocol::MINT_SEED, &[mint_bump]]],
+    )?;
+
+    invoke_signed(
+        &spl_token::instruction::initialize_mint2(
+            token_program.key,
+            sage_mint.key,
+            mint_authority.key,
+            None,
+            Protocol::MINT_DECIMALS,
+        )?,
+        &[sage_mint.clone(), token_program.clone()],
+        &[],
+    )?;
+
+    protocol.reward_mint = *sage_mint.key;
+    protocol.max_supply = max_supply;
+    protocol.mint_authority_bump = mint_authority_bump;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::MintInitialized { mint: *sage_mint.key, max_supply }.emit()?;
+
+    msg!("SAGE mint created, supply cap: {}", max_supply);
+    Ok(())
+}
+
+fn process_take_snapshot(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let snapshot_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let day = Snapshot::day_of(now)?;
+    let (snapshot_pda, bump) = syscalls::find_program_address(&[Snapshot::SEED, &day.to_le_bytes()], program_id);
+
+    if snapshot_pda != *snapshot_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(snapshot_account)?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            snapshot_account.key,
+            Rent::get()?.minimum_balance(Snapshot::LEN),
+            Snapshot::LEN as u64,
+            program_id,
+        ),
+        &[payer.clone(), snapshot_account.clone(), system_program.clone()],
+        &[&[Snapshot::SEED, &day.to_le_bytes(), &[bump]]],
+    )?;
+
+    let snapshot = Snapshot {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        day,
+        taken_at: now,
+        total_knowledge_entries: protocol.total_knowledge_entries,
+        total_attributions: protocol.total_attributions,
+        total_rewards: protocol.total_rewards,
+        fees_collected: protocol.fees_collected,
+        bump,
+    };
+    snapshot.store(snapshot_account)?;
+
+    SolSageEvent::SnapshotTaken {
+        day,
+        total_knowledge_entries: snapshot.total_knowledge_entries,
+        total_attributions: snapshot.total_attributions,
+        total_rewards: snapshot.total_rewards,
+    }
+    .emit()?;
+
+    msg!("Snapshot taken for day {}", day);
+    Ok(())
+}
+
+fn process_request_unstake(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let staker = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !staker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.staker != *staker.key {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    if knowledge.stake_amount == 0 {
+        return Err(SolSageError::NothingStaked.into());
+    }
+
+    if knowledge.unstake_requested_at != 0 {
+        return Err(SolSageError::UnstakeAlreadyRequested.into());
+    }
+
+    // The stake stays slashable until the entry's reports are resolved
+    if knowledge.under_review {
+        return Err(SolSageError::EntryUnderReview.into());
+    }
+
+    // Leaving knowledge no longer earns attributions
+    let now = Clock::get()?.unix_timestamp;
+    knowledge.unstake_requested_at = now;
+    knowledge.is_active = false;
+    let unlocks_at = knowledge.unstake_unlocks_at()?;
+    KnowledgeEntry::resize(knowledge_account, staker, system_program, &Rent::get()?)?;
+    knowledge.store(knowledge_account)?;
+
+    SolSageEvent::UnstakeRequested {
+        knowledge_entry: *knowledge_account.key,
+        staker: *staker.key,
+        unlocks_at,
+    }
+    .emit()?;
+
+    msg!("Unstake requested, unlocks at {}", unlocks_at);
+    Ok(())
+}
+
+fn process_complete_unstake(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let staker = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+
+    if !staker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if escrow_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.staker != *staker.key {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    let escrow_pda = syscalls::create_program_address(
+        &[KnowledgeEntry::ESCROW_SEED, knowledge_account.key.as_ref(), &[knowledge.escrow_bump]],
+        program_id,
+    )?;
+
+    if escrow_pda != *escrow_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    if knowledge.stake_amount == 0 {
+        return Err(SolSageError::NothingStaked.into());
+    }
+
+    if knowledge.unstake_requested_at == 0 {
+        return Err(SolSageError::UnstakeNotRequested.into());
+    }
+
+    if Clock::get()?.unix_timestamp < knowledge.unstake_unlocks_at()? {
+        return Err(SolSageError::StakeLocked.into());
+    }
+
+    // The stake backs rewards until they are paid out or forfeited
+    if knowledge.pending_rewards > 0 {
+        return Err(SolSageError::PendingRewardsOutstanding.into());
+    }
+
+    let stake_amount = knowledge.stake_amount;
+    knowledge.stake_amount = 0;
+    knowledge.is_active = false;
+    knowledge.store(knowledge_account)?;
+
+    // Close the escrow, returning stake and rent to the staker
+    let escrow_lamports = escrow_account.lamports();
+    let staker_lamports = staker
+        .lamports()
+        .checked_add(escrow_lamports)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    **escrow_account.lamports.borrow_mut() = 0;
+    **staker.lamports.borrow_mut() = staker_lamports;
+
+    SolSageEvent::KnowledgeUnstaked {
+        knowledge_entry: *knowledge_account.key,
+        staker: *staker.key,
+        amount: stake_amount,
+    }
+    .emit()?;
+
+    msg!("Unstaked {} lamports", stake_amount);
+    Ok(())
+}
+
+fn process_update_knowledge(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    title: Option<String>,
+    content_hash: Option<[u8; 32]>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let staker = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !staker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.staker != *staker.key {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    if let Some(title) = title {
+        if title.len() > KnowledgeEntry::MAX_TITLE_LEN {
+            return Err(SolSageError::TitleTooLong.into());
+        }
+        knowledge.title = title;
+    }
+    let clock = Clock::get()?;
+    if let Some(content_hash) = content_hash.filter(|content_hash| *content_hash != knowledge.content_hash) {
+        if knowledge.document.is_some() {
+            return Err(SolSageError::EntryInDocument.into());
+        }
+
+        let current_registry = next_account_info(account_info_iter)?;
+        let new_registry = next_account_info(account_info_iter)?;
+        release_content(
+            program_id,
+            knowledge_account,
+            current_registry,
+            staker,
+            account_info_iter,
+            &knowledge.content_hash,
+        )?;
+        register_content(
+            program_id,
+            staker,
+            knowledge_account,
+            new_registry,
+            system_program,
+            content_hash,
+            clock.unix_timestamp,
+        )?;
+
+        // Attestations and chunk proofs are of the content they were made for
+        knowledge.verified = false;
+        knowledge.chunks = None;
+        knowledge.content_hash = content_hash;
+    }
+
+    knowledge.version = knowledge.version.checked_add(1).ok_or(SolSageError::ArithmeticOverflow)?;
+    knowledge.updated_at = clock.unix_timestamp;
+    KnowledgeEntry::resize(knowledge_account, staker, system_program, &Rent::get()?)?;
+    knowledge.store(knowledge_account)?;
+
+    SolSageEvent::KnowledgeUpdated {
+        knowledge_entry: *knowledge_account.key,
+        content_hash: knowledge.content_hash,
+        version: knowledge.version,
+    }
+    .emit()?;
+
+    msg!("Knowledge updated: {} (v{})", knowledge.title, knowledge.version);
+    Ok(())
+}
+
+fn process_transfer_knowledge_ownership(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_owner: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let staker = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let profile_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let staker_profile_account = next_account_info(account_info_iter)?;
+
+    if !staker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.staker != *staker.key {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    // A document's chunks all pay its owner
+    if knowledge.document.is_some() {
+        return Err(SolSageError::EntryInDocument.into());
+    }
+
+    // Claimable, locked and streaming rewards all count as pending
+    if knowledge.pending_rewards > 0 {
+        return Err(SolSageError::UnclaimedRewards.into());
+    }
+
+    // The PDA stays seeded by `creator`; only the owner changes
+    knowledge.staker = new_owner;
+    // The identity presented was the previous owner's
+    knowledge.identity_credential = None;
+    knowledge.updated_at = Clock::get()?.unix_timestamp;
+    knowledge.store(knowledge_account)?;
+
+    // The new owner needs a profile for future attributions and claims
+    move_profile_entry(
+        program_id,
+        staker,
+        staker.key,
+        staker_profile_account,
+        &new_owner,
+        profile_account,
+        system_program,
+    )?;
+
+    SolSageEvent::KnowledgeOwnershipTransferred {
+        knowledge_entry: *knowledge_account.key,
+        old_owner: *staker.key,
+        new_owner,
+    }
+    .emit()?;
+
+    msg!("Knowledge ownership transferred to {}", new_owner);
+    Ok(())
+}
+
+fn process_list_entry_for_sale(program_id: &Pubkey, accounts: &[AccountInfo], price: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let staker = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let listing_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !staker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.staker != *staker.key {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    // An entry on its way out can't be sold
+    if knowledge.unstake_requested_at != 0 {
+        return Err(SolSageError::UnstakeAlreadyRequested.into());
+    }
+
+    if knowledge.document.is_some() {
+        return Err(SolSageError::EntryInDocument.into());
+    }
+
+    if price == 0 {
+        return Err(SolSageError::InvalidListingPrice.into());
+    }
+
+    let (listing_pda, bump) =
+        syscalls::find_program_address(&[Listing::SEED, knowledge_account.key.as_ref()], program_id);
+
+    if listing_pda != *listing_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(listing_account)?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            staker.key,
+            listing_account.key,
+            Rent::get()?.minimum_balance(Listing::LEN),
+            Listing::LEN as u64,
+            program_id,
+        ),
+        &[staker.clone(), listing_account.clone(), system_program.clone()],
+        &[&[Listing::SEED, knowledge_account.key.as_ref(), &[bump]]],
+    )?;
+
+    let listing = Listing {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        knowledge_entry: *knowledge_account.key,
+        seller: *staker.key,
+        price,
+        listed_at: Clock::get()?.unix_timestamp,
+        bump,
+    };
+    listing.store(listing_account)?;
+
+    SolSageEvent::EntryListed { knowledge_entry: *knowledge_account.key, seller: *staker.key, price }.emit()?;
+
+    msg!("Knowledge entry listed for {} lamports", price);
+    Ok(())
+}
+
+/// Load the listing of `knowledge_account`
+fn load_listing(
+    program_id: &Pubkey,
+    knowledge_account: &AccountInfo,
+    listing_account: &AccountInfo,
+) -> Result<Listing, ProgramError> {
+    let listing = Listing::load(program_id, listing_account)?;
+    let listing_pda = syscalls::create_program_address(
+        &[Listing::SEED, knowledge_account.key.as_ref(), &[listing.bump]],
+        program_id,
+    )?;
+
+    if listing_pda != *listing_account.key || listing.knowledge_entry != *knowledge_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+    Ok(listing)
+}
+
+/// Close `listing_account`, paying its lamports to `seller`
+fn close_listing(listing_account: &AccountInfo, seller: &AccountInfo) -> ProgramResult {
+    listing_account.data.borrow_mut().fill(0);
+    let seller_lamports = seller
+        .lamports()
+        .checked_add(listing_account.lamports())
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    **listing_account.lamports.borrow_mut() = 0;
+    **seller.lamports.borrow_mut() = seller_lamports;
+    Ok(())
+}
+
+fn process_cancel_listing(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let signer = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let listing_account = next_account_info(account_info_iter)?;
+    let seller = next_account_info(account_info_iter)?;
+
+    if !signer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+    let listing = load_listing(program_id, knowledge_account, listing_account)?;
+
+    if listing.seller != *seller.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    // A new owner clears the listing the entry came with
+    if listing.seller != *signer.key && knowledge.staker != *signer.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    close_listing(listing_account, seller)?;
+
+    SolSageEvent::ListingCancelled { knowledge_entry: *knowledge_account.key, seller: listing.seller }.emit()?;
+
+    msg!("Listing cancelled");
+    Ok(())
+}
+
+fn process_buy_entry(program_id: &Pubkey, accounts: &[AccountInfo], price: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let buyer = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let listing_account = next_account_info(account_info_iter)?;
+    let seller = next_account_info(account_info_iter)?;
+    let treasury_account = next_account_info(account_info_iter)?;
+    let profile_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let seller_profile_account = next_account_info(account_info_iter)?;
+
+    if !buyer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    protocol.check_not_paused()?;
+
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+    let listing = load_listing(program_id, knowledge_account, listing_account)?;
+
+    if listing.seller != *seller.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    // The listing lapses once the seller no longer owns the entry
+    if knowledge.staker != listing.seller {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    if knowledge.unstake_requested_at != 0 {
+        return Err(SolSageError::UnstakeAlreadyRequested.into());
+    }
+
+    // Listed before it became a chunk; the listing lapses
+    if knowledge.document.is_some() {
+        return Err(SolSageError::EntryInDocument.into());
+    }
+
+    if listing.price != price {
+        return Err(SolSageError::ListingPriceChanged.into());
+    }
+
+    // Claimable, locked and streaming rewards all count as pending
+    if knowledge.pending_rewards > 0 {
+        return Err(SolSageError::UnclaimedRewards.into());
+    }
+
+    let treasury_pda = syscalls::create_program_address(
+        &[Protocol::TREASURY_SEED, &[protocol.treasury_bump]],
+        program_id,
+    )?;
+
+    if treasury_pda != *treasury_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    // Escrow the price in the listing, then pay it out as the listing closes
+    invoke(
+        &system_instruction::transfer(buyer.key, listing_account.key, price),
+        &[buyer.clone(), listing_account.clone(), system_program.clone()],
+    )?;
+    let fee = solsage_core::apply_bps(price, protocol.sale_fee_bps).ok_or(SolSageError::ArithmeticOverflow)?;
+    let treasury_lamports = treasury_account
+        .lamports()
+        .checked_add(fee)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    **listing_account.lamports.borrow_mut() -= fee;
+    **treasury_account.lamports.borrow_mut() = treasury_lamports;
+    close_listing(listing_account, seller)?;
+
+    // The PDA stays seeded by `creator`; only the owner changes
+    knowledge.staker = *buyer.key;
+    knowledge.identity_credential = None;
+    knowledge.updated_at = Clock::get()?.unix_timestamp;
+    knowledge.store(knowledge_account)?;
+
+    // The buyer needs a profile for future attributions and claims
+    move_profile_entry(
+        program_id,
+        buyer,
+        &listing.seller,
+        seller_profile_account,
+        buyer.key,
+        profile_account,
+        system_program,
+    )?;
+
+    protocol.fees_collected = protocol
+        .fees_collected
+        .checked_add(fee)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::EntryBought {
+        knowledge_entry: *knowledge_account.key,
+        seller: listing.seller,
+        buyer: *buyer.key,
+        price,
+        fee,
+    }
+    .emit()?;
+
+    msg!("Knowledge entry bought for {} lamports, fee: {}", price, fee);
+    Ok(())
+}
+
+fn process_set_knowledge_active(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    is_active: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let signer = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+
+    if !signer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.staker != *signer.key && protocol.authority != *signer.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    // An entry on its way out stays inactive
+    if is_active && knowledge.unstake_requested_at != 0 {
+        return Err(SolSageError::UnstakeAlreadyRequested.into());
+    }
+
+    // A reported entry comes back only once its reports are rejected
+    if is_active && knowledge.under_review {
+        return Err(SolSageError::EntryUnderReview.into());
+    }
+
+    knowledge.is_active = is_active;
+    knowledge.store(knowledge_account)?;
+
+    SolSageEvent::KnowledgeActiveChanged {
+        knowledge_entry: *knowledge_account.key,
+        is_active,
+    }
+    .emit()?;
+
+    msg!("Knowledge {}: {}", if is_active { "reactivated" } else { "deactivated" }, knowledge.title);
+    Ok(())
+}
+
+fn process_close_knowledge_entry(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let staker = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let category_account = next_account_info(account_info_iter)?;
+    let registry_account = next_account_info(account_info_iter)?;
+    let staker_index_account = next_account_info(account_info_iter)?;
+
+    if !staker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.staker != *staker.key {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    let escrow_pda = syscalls::create_program_address(
+        &[KnowledgeEntry::ESCROW_SEED, knowledge_account.key.as_ref(), &[knowledge.escrow_bump]],
+        program_id,
+    )?;
+
+    if escrow_pda != *escrow_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    if knowledge.pending_rewards > 0 {
+        return Err(SolSageError::UnclaimedRewards.into());
+    }
+
+    if knowledge.citation_royalties > 0 {
+        return Err(SolSageError::CitationRoyaltiesUnpaid.into());
+    }
+
+    if knowledge.stake_amount > 0 {
+        return Err(SolSageError::StakeNotWithdrawn.into());
+    }
+
+    if knowledge.collection.is_some() {
+        return Err(SolSageError::EntryInCollection.into());
+    }
+
+    if knowledge.document.is_some() {
+        return Err(SolSageError::EntryInDocument.into());
+    }
+
+    if !knowledge.tags.is_empty() {
+        return Err(SolSageError::EntryTagged.into());
+    }
+
+    // Update protocol
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+    protocol.total_knowledge_entries = protocol
+        .total_knowledge_entries
+        .checked_sub(1)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    protocol.store(protocol_account)?;
+
+    if knowledge.category != *category_account.key {
+        return Err(SolSageError::CategoryMismatch.into());
+    }
+    let mut category = load_category(program_id, category_account)?;
+    category.entries = category.entries.checked_sub(1).ok_or(SolSageError::ArithmeticOverflow)?;
+    category.store(category_account)?;
+
+    release_content(
+        program_id,
+        knowledge_account,
+        registry_account,
+        staker,
+        account_info_iter,
+        &knowledge.content_hash,
+    )?;
+
+    // Creators who staked only before indices existed have none
+    let staker_index_pda = syscalls::find_program_address(&[StakerIndex::SEED, knowledge.creator.as_ref()], program_id).0;
+    if staker_index_pda != *staker_index_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+    if !staker_index_account.data_is_empty() {
+        let mut staker_index = StakerIndex::load(program_id, staker_index_account)?;
+        staker_index.record_closed_entry()?;
+        staker_index.store(staker_index_account)?;
+    }
+
+    // Zero the data so the entry reads as uninitialized, then drain the
+    // entry and any leftover escrow rent back to the staker
+    knowledge_account.data.borrow_mut().fill(0);
+
+    let reclaimed = knowledge_account
+        .lamports()
+        .checked_add(escrow_account.lamports())
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    let staker_lamports = staker
+        .lamports()
+        .checked_add(reclaimed)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    **knowledge_account.lamports.borrow_mut() = 0;
+    **escrow_account.lamports.borrow_mut() = 0;
+    **staker.lamports.borrow_mut() = staker_lamports;
+
+    SolSageEvent::KnowledgeEntryClosed { knowledge_entry: *knowledge_account.key }.emit()?;
+
+    msg!("Knowledge closed: {}, reclaimed {} lamports", knowledge.title, reclaimed);
+    Ok(())
+}
+
+/// Claim `content_hash` for `knowledge_account` in a new content registry
+/// paid for by `payer`, rejecting content another entry already claims
+fn register_content<'info>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'info>,
+    knowledge_account: &AccountInfo<'info>,
+    registry_account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    content_hash: [u8; 32],
+    registered_at: i64,
+) -> ProgramResult {
+    let (registry_pda, registry_bump) =
+        syscalls::find_program_address(&[ContentRegistry::SEED, &content_hash], program_id);
+
+    if registry_pda != *registry_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    if !registry_account.data_is_empty() {
+        return Err(SolSageError::DuplicateContent.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            registry_account.key,
+            Rent::get()?.minimum_balance(ContentRegistry::LEN),
+            ContentRegistry::LEN as u64,
+            program_id,
+        ),
+        &[payer.clone(), registry_account.clone(), system_program.clone()],
+        &[&[ContentRegistry::SEED, &content_hash, &[registry_bump]]],
+    )?;
+
+    ContentRegistry {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        content_hash,
+        knowledge_entry: *knowledge_account.key,
+        staker: *payer.key,
+        registered_at,
+        bump: registry_bump,
+    }
+    .store(registry_account)
+}
+
+/// Close the content registry of `content_hash` if `knowledge_account`
+/// holds it, so the content can be staked again. Its rent goes back to
+/// whoever paid for it: `staker`, or else the next of `remaining_accounts`,
+/// as an entry handed over keeps the registry its earlier owner paid for.
+/// Entries staked before content was registered, or after another entry
+/// registered the same content, hold none.
+fn release_content<'a, 'info: 'a>(
+    program_id: &Pubkey,
+    knowledge_account: &AccountInfo<'info>,
+    registry_account: &AccountInfo<'info>,
+    staker: &'a AccountInfo<'info>,
+    remaining_accounts: &mut impl Iterator<Item = &'a AccountInfo<'info>>,
+    content_hash: &[u8; 32],
+) -> ProgramResult {
+    let registry_pda = syscalls::find_program_address(&[ContentRegistry::SEED, content_hash], program_id).0;
+
+    if registry_pda != *registry_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    if registry_account.data_is_empty() {
+        return Ok(());
+    }
+    let registry = ContentRegistry::load(program_id, registry_account)?;
+    if registry.knowledge_entry != *knowledge_account.key {
+        return Ok(());
+    }
+
+    let recipient = if registry.staker == *staker.key { staker } else { next_account_info(remaining_accounts)? };
+    if registry.staker != *recipient.key {
+        return Err(SolSageError::RegistryPayerMismatch.into());
+    }
+
+    registry_account.data.borrow_mut().fill(0);
+    let recipient_lamports = recipient
+        .lamports()
+        .checked_add(registry_account.lamports())
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    **registry_account.lamports.borrow_mut() = 0;
+    **recipient.lamports.borrow_mut() = recipient_lamports;
+    Ok(())
+}
+
+fn process_set_reward_rate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    reward_per_attribution: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    protocol.check_no_admin_delay()?;
+
+    apply_reward_rate(&mut protocol, reward_per_attribution)?;
+    protocol.store(protocol_account)
+}
+
+/// Change the base reward rate, by the authority or a passed proposal
+fn apply_reward_rate(protocol: &mut Protocol, reward_per_attribution: u64) -> ProgramResult {
+    if !(Protocol::MIN_REWARD_PER_ATTRIBUTION..=Protocol::MAX_REWARD_PER_ATTRIBUTION)
+        .contains(&reward_per_attribution)
+    {
+        return Err(SolSageError::RewardRateOutOfBounds.into());
+    }
+
+    let old_rate = protocol.reward_per_attribution;
+    protocol.reward_per_attribution = reward_per_attribution;
+
+    SolSageEvent::RewardRateChanged {
+        old_rate,
+        new_rate: reward_per_attribution,
+    }
+    .emit()?;
+
+    msg!("Reward rate changed: {} -> {}", old_rate, reward_per_attribution);
+    Ok(())
+}
+
+fn process_set_reward_curve(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    reward_curve: RewardCurve,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    let old_curve = protocol.reward_curve;
+    protocol.reward_curve = reward_curve;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::RewardCurveChanged {
+        old_curve,
+        new_curve: reward_curve,
+    }
+    .emit()?;
+
+    msg!("Reward curve changed: {:?} -> {:?}", old_curve, reward_curve);
+    Ok(())
+}
+
+fn process_propose_authority_transfer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_authority: Pubkey,
+    authority_type: AuthorityType,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+    // A mistyped multisig would hand the protocol to a PDA no one can sign for
+    if !authority_type.signs_as(&new_authority) {
+        return Err(SolSageError::AuthorityTypeMismatch.into());
+    }
+
+    protocol.pending_authority = Some(new_authority);
+    protocol.pending_authority_type = authority_type;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::AuthorityTransferProposed {
+        current_authority: protocol.authority,
+        pending_authority: new_authority,
+        authority_type,
+    }
+    .emit()?;
+
+    msg!("Authority transfer proposed: {} -> {}", protocol.authority, new_authority);
+    Ok(())
+}
+
+fn process_accept_authority_transfer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let new_authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !new_authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.pending_authority != Some(*new_authority.key) {
+        return Err(SolSageError::NotPendingAuthority.into());
+    }
+
+    let old_authority = protocol.authority;
+    protocol.authority = *new_authority.key;
+    protocol.authority_type = protocol.pending_authority_type;
+    protocol.pending_authority = None;
+    protocol.pending_authority_type = AuthorityType::Keypair;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::AuthorityTransferAccepted {
+        old_authority,
+        new_authority: protocol.authority,
+    }
+    .emit()?;
+
+    msg!("Authority transfer accepted: {} -> {}", old_authority, new_authority.key);
+    Ok(())
+}
+
+fn process_set_vesting_period(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    vesting_period_secs: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    if !(0..=Protocol::MAX_VESTING_PERIOD_SECS).contains(&vesting_period_secs) {
+        return Err(SolSageError::InvalidVestingPeriod.into());
+    }
+
+    let old_period_secs = protocol.vesting_period_secs;
+    protocol.vesting_period_secs = vesting_period_secs;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::VestingPeriodChanged {
+        old_period_secs,
+        new_period_secs: vesting_period_secs,
+    }
+    .emit()?;
+
+    msg!("Vesting period set to {}s", vesting_period_secs);
+    Ok(())
+}
+
+fn process_get_vested_rewards(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let knowledge_account = next_account_info(account_info_iter)?;
+
+    let knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+    let clock = Clock::get()?;
+    let vested = knowledge.vested_rewards(clock.unix_timestamp);
+
+    set_return_data(&vested.try_to_vec()?);
+
+    msg!("Vested rewards: {} claimable, {} locked", vested.claimable, vested.locked);
+    Ok(())
+}
+
+fn process_add_oracle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    oracle: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let oracle_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    protocol.check_no_admin_delay()?;
+
+    create_oracle_registration(program_id, authority, oracle_account, system_program, oracle)
+}
+
+/// Register `oracle`, by the authority or a passed proposal, with `payer`
+/// funding the registration
+fn create_oracle_registration<'info>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'info>,
+    oracle_account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    oracle: Pubkey,
+) -> ProgramResult {
+    let (oracle_pda, bump) = syscalls::find_program_address(
+        &[AttributionOracle::SEED, oracle.as_ref()],
+        program_id,
+    );
+
+    if oracle_pda != *oracle_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(oracle_account)?;
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            oracle_account.key,
+            rent.minimum_balance(AttributionOracle::LEN),
+            AttributionOracle::LEN as u64,
+            program_id,
+        ),
+        &[payer.clone(), oracle_account.clone(), system_program.clone()],
+        &[&[AttributionOracle::SEED, oracle.as_ref(), &[bump]]],
+    )?;
+
+    let clock = Clock::get()?;
+    let registration = AttributionOracle {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        oracle,
+        added_at: clock.unix_timestamp,
+        bump,
+    };
+    registration.store(oracle_account)?;
+
+    SolSageEvent::OracleAdded { oracle }.emit()?;
+
+    msg!("Oracle added: {}", oracle);
+    Ok(())
+}
+
+fn process_add_bridge_emitter(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    bridge: Pubkey,
+    emitter_chain: u16,
+    emitter_address: [u8; 32],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let emitter_account = next_account_info(account_info_iter)?;
+    let oracle_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    protocol.check_no_admin_delay()?;
+
+    let (emitter_pda, bump) = syscalls::find_program_address(
+        &[BridgeEmitter::SEED, &emitter_chain.to_le_bytes(), &emitter_address],
+        program_id,
+    );
+
+    if emitter_pda != *emitter_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(emitter_account)?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            emitter_account.key,
+            Rent::get()?.minimum_balance(BridgeEmitter::LEN),
+            BridgeEmitter::LEN as u64,
+            program_id,
+        ),
+        &[authority.clone(), emitter_account.clone(), system_program.clone()],
+        &[&[BridgeEmitter::SEED, &emitter_chain.to_le_bytes(), &emitter_address, &[bump]]],
+    )?;
+
+    let emitter = BridgeEmitter {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        bridge,
+        emitter_chain,
+        emitter_address,
+        next_sequence: 0,
+        added_at: Clock::get()?.unix_timestamp,
+        bump,
+    };
+    emitter.store(emitter_account)?;
+
+    SolSageEvent::BridgeEmitterAdded { bridge_emitter: emitter_pda, bridge, emitter_chain, emitter_address }.emit()?;
+
+    create_oracle_registration(program_id, authority, oracle_account, system_program, emitter_pda)
+}
+
+fn process_remove_oracle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let oracle_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    protocol.check_no_admin_delay()?;
+
+    close_oracle_registration(program_id, oracle_account, authority)
+}
+
+/// Revoke an oracle, by the authority or a passed proposal, returning its
+/// registration's rent to `destination`
+fn close_oracle_registration(
+    program_id: &Pubkey,
+    oracle_account: &AccountInfo,
+    destination: &AccountInfo,
+) -> ProgramResult {
+    let registration = AttributionOracle::load(program_id, oracle_account)?;
+    let oracle_pda = syscalls::create_program_address(
+        &[AttributionOracle::SEED, registration.oracle.as_ref(), &[registration.bump]],
+        program_id,
+    )?;
+
+    if oracle_pda != *oracle_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    oracle_account.data.borrow_mut().fill(0);
+    let reclaimed = oracle_account.lamports();
+    let destination_lamports = destination
+        .lamports()
+        .checked_add(reclaimed)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    **oracle_account.lamports.borrow_mut() = 0;
+    **destination.lamports.borrow_mut() = destination_lamports;
+
+    SolSageEvent::OracleRemoved { oracle: registration.oracle }.emit()?;
+
+    msg!("Oracle removed: {}", registration.oracle);
+    Ok(())
+}
+
+fn process_challenge_attribution(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    bond: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let challenger = next_account_info(account_info_iter)?;
+    let attribution_account = next_account_info(account_info_iter)?;
+    let challenge_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !challenger.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let attribution = Attribution::load(program_id, attribution_account)?;
+
+    if attribution.pending {
+        return Err(SolSageError::AttributionPending.into());
+    }
+
+    if attribution.reversed {
+        return Err(SolSageError::AttributionReversed.into());
+    }
+
+    if bond < Challenge::MIN_BOND {
+        return Err(SolSageError::ChallengeBondTooLow.into());
+    }
+
+    // One open challenge per attribution
+    let (challenge_pda, bump) = syscalls::find_program_address(
+        &[Challenge::SEED, attribution_account.key.as_ref()],
+        program_id,
+    );
+
+    if challenge_pda != *challenge_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(challenge_account)?;
+
+    // The challenge account holds the bond on top of its own rent
+    let lamports = Rent::get()?
+        .minimum_balance(Challenge::LEN)
+        .checked_add(bond)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            challenger.key,
+            challenge_account.key,
+            lamports,
+            Challenge::LEN as u64,
+            program_id,
+        ),
+        &[challenger.clone(), challenge_account.clone(), system_program.clone()],
+        &[&[Challenge::SEED, attribution_account.key.as_ref(), &[bump]]],
+    )?;
+
+    let challenge = Challenge {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        attribution: *attribution_account.key,
+        challenger: *challenger.key,
+        bond,
+        created_at: Clock::get()?.unix_timestamp,
+        bump,
+    };
+    challenge.store(challenge_account)?;
+
+    SolSageEvent::AttributionChallenged {
+        attribution: *attribution_account.key,
+        challenger: *challenger.key,
+        bond,
+    }
+    .emit()?;
+
+    msg!("Attribution challenged with {} lamport bond", bond);
+    Ok(())
+}
+
+fn process_resolve_challenge(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    uphold: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let attribution_account = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let challenge_account = next_account_info(account_info_iter)?;
+    let challenger = next_account_info(account_info_iter)?;
+    let staker = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    let challenge = Challenge::load(program_id, challenge_account)?;
+    let mut attribution = Attribution::load(program_id, attribution_account)?;
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if challenge.attribution != *attribution_account.key
+        || challenge.challenger != *challenger.key
+        || attribution.knowledge_entry != *knowledge_account.key
+        || knowledge.staker != *staker.key
+    {
+        return Err(SolSageError::ChallengeMismatch.into());
+    }
+
+    let challenge_lamports = challenge_account.lamports();
+    let (challenger_share, staker_share, reversed) = if uphold {
+        if attribution.reversed {
+            return Err(SolSageError::AttributionReversed.into());
+        }
+
+        // A claimed reward has already been minted and cannot be taken back
+        let now = Clock::get()?.unix_timestamp;
+        let reversed = if attribution.streamed {
+            knowledge.reverse_streamed_rewards(attribution.reward, now)?
+        } else {
+            let outstanding = if attribution.reward_claimed { 0 } else { attribution.reward };
+            knowledge.reverse_rewards(outstanding, now)
+        };
+        knowledge.total_attributions = knowledge.total_attributions.saturating_sub(1);
+        knowledge.store(knowledge_account)?;
+
+        attribution.reversed = true;
+        attribution.store(attribution_account)?;
+
+        msg!("Challenge upheld, reversed {} of {} reward", reversed, attribution.reward);
+        (challenge_lamports, 0, reversed)
+    } else {
+        // The challenger forfeits the bond to the entry owner and keeps the rent
+        msg!("Challenge rejected, {} lamport bond forfeited", challenge.bond);
+        let rent_refund = challenge_lamports
+            .checked_sub(challenge.bond)
+            .ok_or(SolSageError::ArithmeticOverflow)?;
+        (rent_refund, challenge.bond, 0)
+    };
+
+    challenge_account.data.borrow_mut().fill(0);
+    **challenge_account.lamports.borrow_mut() = 0;
+
+    // Credit one at a time in case the challenger is also the entry owner
+    let challenger_lamports = challenger
+        .lamports()
+        .checked_add(challenger_share)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    **challenger.lamports.borrow_mut() = challenger_lamports;
+
+    let staker_lamports = staker
+        .lamports()
+        .checked_add(staker_share)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    **staker.lamports.borrow_mut() = staker_lamports;
+
+    SolSageEvent::ChallengeResolved {
+        attribution: *attribution_account.key,
+        challenger: *challenger.key,
+        upheld: uphold,
+        reversed,
+    }
+    .emit()?;
+
+    Ok(())
+}
+
+fn process_slash_knowledge(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    slash_bps: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let treasury_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if escrow_account.owner != program_id || treasury_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    if slash_bps > Protocol::MAX_BPS {
+        return Err(SolSageError::InvalidBasisPoints.into());
+    }
+
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    let escrow_pda = syscalls::create_program_address(
+        &[KnowledgeEntry::ESCROW_SEED, knowledge_account.key.as_ref(), &[knowledge.escrow_bump]],
+        program_id,
+    )?;
+
+    if escrow_pda != *escrow_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    let treasury_pda = syscalls::create_program_address(
+        &[Protocol::TREASURY_SEED, &[protocol.treasury_bump]],
+        program_id,
+    )?;
+
+    if treasury_pda != *treasury_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    let slashed = u64::try_from(
+        (knowledge.stake_amount as u128) * (slash_bps as u128) / (Protocol::MAX_BPS as u128),
+    )
+    .map_err(|_| SolSageError::ArithmeticOverflow)?;
+
+    // Malicious content forfeits everything it has earned and stops earning
+    knowledge.stake_amount = knowledge
+        .stake_amount
+        .checked_sub(slashed)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    let forfeited = knowledge.pending_rewards;
+    knowledge.pending_rewards = 0;
+    knowledge.vesting_schedule = [VestingBucket::default(); KnowledgeEntry::VESTING_BUCKETS];
+    knowledge.reward_stream = RewardStream::default();
+    knowledge.citation_rewards = 0;
+    knowledge.is_active = false;
+    knowledge.store(knowledge_account)?;
+
+    let escrow_lamports = escrow_account
+        .lamports()
+        .checked_sub(slashed)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    let treasury_lamports = treasury_account
+        .lamports()
+        .checked_add(slashed)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    **escrow_account.lamports.borrow_mut() = escrow_lamports;
+    **treasury_account.lamports.borrow_mut() = treasury_lamports;
+
+    SolSageEvent::KnowledgeSlashed {
+        knowledge_entry: *knowledge_account.key,
+        slashed,
+        forfeited,
+    }
+    .emit()?;
+
+    msg!("Slashed {} lamports, forfeited {} rewards", slashed, forfeited);
+    Ok(())
+}
+
+fn process_set_protocol_fee(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_bps: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    protocol.check_no_admin_delay()?;
+
+    apply_protocol_fee(&mut protocol, fee_bps)?;
+    protocol.store(protocol_account)
+}
+
+/// Change the attribution fee, by the authority or a passed proposal
+fn apply_protocol_fee(protocol: &mut Protocol, fee_bps: u16) -> ProgramResult {
+    if fee_bps > Protocol::MAX_FEE_BPS {
+        return Err(SolSageError::FeeTooHigh.into());
+    }
+
+    let old_fee = protocol.fee_bps;
+    protocol.fee_bps = fee_bps;
+
+    SolSageEvent::ProtocolFeeChanged {
+        old_fee_bps: old_fee,
+        new_fee_bps: fee_bps,
+    }
+    .emit()?;
+
+    msg!("Protocol fee changed: {} -> {} bps", old_fee, fee_bps);
+    Ok(())
+}
+
+fn process_withdraw_treasury(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let treasury_account = next_account_info(account_info_iter)?;
+    let destination = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if treasury_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    let treasury_pda = syscalls::create_program_address(
+        &[Protocol::TREASURY_SEED, &[protocol.treasury_bump]],
+        program_id,
+    )?;
+
+    if treasury_pda != *treasury_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    // Never dip into the treasury's own rent reserve
+    let available = treasury_account
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(0));
+    if amount > available {
+        return Err(SolSageError::InsufficientTreasuryBalance.into());
+    }
+
+    let treasury_lamports = treasury_account
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    **treasury_account.lamports.borrow_mut() = treasury_lamports;
+
+    let destination_lamports = destination
+        .lamports()
+        .checked_add(amount)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    **destination.lamports.borrow_mut() = destination_lamports;
+
+    SolSageEvent::TreasuryWithdrawn {
+        destination: *destination.key,
+        amount,
+    }
+    .emit()?;
+
+    msg!("Withdrew {} lamports from treasury", amount);
+    Ok(())
+}
+
+fn process_create_category(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    name: String,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let category_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    if name.len() > Category::MAX_NAME_LEN {
+        return Err(SolSageError::CategoryTooLong.into());
+    }
+
+    // Derive category PDA
+    let (category_pda, bump) = syscalls::find_program_address(
+        &[Category::SEED, name.as_bytes()],
+        program_id,
+    );
+
+    if category_pda != *category_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(category_account)?;
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            category_account.key,
+            rent.minimum_balance(Category::LEN),
+            Category::LEN as u64,
+            program_id,
+        ),
+        &[authority.clone(), category_account.clone(), system_program.clone()],
+        &[&[Category::SEED, name.as_bytes(), &[bump]]],
+    )?;
+
+    let category = Category {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        name: name.clone(),
+        entries: 0,
+        attributions: 0,
+        multiplier_bps: Category::DEFAULT_MULTIPLIER_BPS,
+        created_at: Clock::get()?.unix_timestamp,
+        bump,
+        rewards: 0,
+    };
+    category.store(category_account)?;
+
+    SolSageEvent::CategoryCreated {
+        category: *category_account.key,
+        name: name.clone(),
+    }
+    .emit()?;
+
+    msg!("Category created: {}", name);
+    Ok(())
+}
+
+fn process_set_category_multiplier(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    multiplier_bps: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let category_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    if multiplier_bps > Category::MAX_MULTIPLIER_BPS {
+        return Err(SolSageError::MultiplierOutOfBounds.into());
+    }
+
+    let mut category = load_category(program_id, category_account)?;
+    let old_multiplier_bps = category.multiplier_bps;
+    category.multiplier_bps = multiplier_bps;
+    category.store(category_account)?;
+
+    SolSageEvent::CategoryMultiplierChanged {
+        category: *category_account.key,
+        old_multiplier_bps,
+        new_multiplier_bps: multiplier_bps,
+    }
+    .emit()?;
+
+    msg!("Category multiplier changed: {} -> {} bps", old_multiplier_bps, multiplier_bps);
+    Ok(())
+}
+
+fn process_renew_knowledge(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    content_hash: Option<[u8; 32]>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let staker = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let treasury_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !staker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.staker != *staker.key {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    if content_hash.is_some_and(|hash| hash != knowledge.content_hash) {
+        return Err(SolSageError::ContentHashMismatch.into());
+    }
+
+    let treasury_pda = syscalls::create_program_address(
+        &[Protocol::TREASURY_SEED, &[protocol.treasury_bump]],
+        program_id,
+    )?;
+
+    if treasury_pda != *treasury_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    let fee = KnowledgeEntry::RENEWAL_FEE_LAMPORTS;
+    invoke(
+        &system_instruction::transfer(staker.key, treasury_account.key, fee),
+        &[staker.clone(), treasury_account.clone(), system_program.clone()],
+    )?;
+
+    // A renewal buys a full term from now; terms do not stack
+    let clock = Clock::get()?;
+    knowledge.expires_at = clock
+        .unix_timestamp
+        .checked_add(KnowledgeEntry::TERM_SECS)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    knowledge.updated_at = clock.unix_timestamp;
+    knowledge.store(knowledge_account)?;
+
+    protocol.fees_collected = protocol
+        .fees_collected
+        .checked_add(fee)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::KnowledgeRenewed {
+        knowledge_entry: *knowledge_account.key,
+        expires_at: knowledge.expires_at,
+        fee,
+    }
+    .emit()?;
+
+    msg!("Knowledge renewed until {}", knowledge.expires_at);
+    Ok(())
+}
+
+fn process_set_claim_delegate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    delegate: Option<Pubkey>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let staker = next_account_info(account_info_iter)?;
+    let profile_account = next_account_info(account_info_iter)?;
+
+    if !staker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut profile = load_staker_profile(program_id, staker.key, profile_account)?;
+    profile.claim_delegate = delegate;
+    profile.store(profile_account)?;
+
+    SolSageEvent::ClaimDelegateChanged {
+        staker: *staker.key,
+        delegate,
+    }
+    .emit()?;
+
+    msg!("Claim delegate set to {:?}", delegate);
+    Ok(())
+}
+
+fn process_set_min_stake(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    min_stake_lamports: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    let old_min_stake_lamports = protocol.min_stake_lamports;
+    protocol.min_stake_lamports = min_stake_lamports;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::MinStakeChanged {
+        old_min_stake_lamports,
+        new_min_stake_lamports: min_stake_lamports,
+    }
+    .emit()?;
+
+    msg!("Minimum stake changed: {} -> {} lamports", old_min_stake_lamports, min_stake_lamports);
+    Ok(())
+}
+
+fn process_set_attribution_cooldown(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    attribution_cooldown_secs: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    if !(0..=Protocol::MAX_ATTRIBUTION_COOLDOWN_SECS).contains(&attribution_cooldown_secs) {
+        return Err(SolSageError::InvalidAttributionCooldown.into());
+    }
+
+    let old_cooldown_secs = protocol.attribution_cooldown_secs;
+    protocol.attribution_cooldown_secs = attribution_cooldown_secs;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::AttributionCooldownChanged {
+        old_cooldown_secs,
+        new_cooldown_secs: attribution_cooldown_secs,
+    }
+    .emit()?;
+
+    msg!("Attribution cooldown changed: {}s -> {}s", old_cooldown_secs, attribution_cooldown_secs);
+    Ok(())
+}
+
+fn process_set_paused(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    is_paused: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    protocol.is_paused = is_paused;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::ProtocolPauseChanged { authority: *authority.key, is_paused }.emit()?;
+
+    msg!("Protocol {}", if is_paused { "paused" } else { "unpaused" });
+    Ok(())
+}
+
+fn process_set_score_divisor(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    score_divisor: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    if !(Protocol::MIN_SCORE_DIVISOR..=Protocol::MAX_SCORE_DIVISOR).contains(&score_divisor) {
+        return Err(SolSageError::InvalidScoreDivisor.into());
+    }
+
+    let old_score_divisor = protocol.score_divisor;
+    protocol.score_divisor = score_divisor;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::ScoreDivisorChanged {
+        old_score_divisor,
+        new_score_divisor: score_divisor,
+    }
+    .emit()?;
+
+    msg!("Score divisor changed: {} -> {}", old_score_divisor, score_divisor);
+    Ok(())
+}
+
+fn process_commit_attribution_root(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    epoch: u64,
+    root: [u8; 32],
+    attribution_count: u32,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let oracle = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let oracle_account = next_account_info(account_info_iter)?;
+    let root_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !oracle.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    check_registered_oracle(program_id, oracle.key, oracle_account)?;
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+    protocol.check_not_paused()?;
+    protocol.check_minted_rewards()?;
+    protocol.check_single_reports()?;
+    protocol.check_attribution_caller(account_info_iter.next())?;
+
+    if attribution_count == 0 || attribution_count as usize > AttributionRoot::MAX_ATTRIBUTIONS {
+        return Err(SolSageError::InvalidRootAttributionCount.into());
+    }
+
+    let epoch_seed = epoch.to_le_bytes();
+    let (root_pda, bump) = syscalls::find_program_address(
+        &[AttributionRoot::SEED, oracle.key.as_ref(), &epoch_seed],
+        program_id,
+    );
+
+    if root_pda != *root_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(root_account)?;
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            oracle.key,
+            root_account.key,
+            rent.minimum_balance(AttributionRoot::LEN),
+            AttributionRoot::LEN as u64,
+            program_id,
+        ),
+        &[oracle.clone(), root_account.clone(), system_program.clone()],
+        &[&[AttributionRoot::SEED, oracle.key.as_ref(), &epoch_seed, &[bump]]],
+    )?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let unlocks_at = now
+        .checked_add(protocol.vesting_period_secs)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    let attribution_root = AttributionRoot {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        oracle: *oracle.key,
+        epoch,
+        root,
+        attribution_count,
+        claimed_count: 0,
+        committed_at: now,
+        unlocks_at,
+        claimed: [0; AttributionRoot::MAX_ATTRIBUTIONS / 8],
+        bump,
+    };
+    attribution_root.store(root_account)?;
+
+    SolSageEvent::AttributionRootCommitted {
+        attribution_root: *root_account.key,
+        oracle: *oracle.key,
+        epoch,
+        root,
+        attribution_count,
+        unlocks_at,
+    }
+    .emit()?;
+
+    msg!("Attribution root committed for epoch {}: {} attributions", epoch, attribution_count);
+    Ok(())
+}
+
+fn process_claim_attributed_reward(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    leaf_index: u32,
+    query_hash: [u8; 32],
+    relevance_score: u8,
+    proof: Vec<[u8; 32]>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let claimer = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let root_account = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let mint_accounts = RewardMintAccounts {
+        sage_mint: next_account_info(account_info_iter)?,
+        destination: next_account_info(account_info_iter)?,
+        mint_authority: next_account_info(account_info_iter)?,
+        token_program: next_account_info(account_info_iter)?,
+    };
+    let profile_account = next_account_info(account_info_iter)?;
+    let category_account = next_account_info(account_info_iter)?;
+
+    if !claimer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if relevance_score > solsage_core::MAX_RELEVANCE_SCORE {
+        return Err(SolSageError::InvalidRelevanceScore.into());
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+    protocol.check_not_paused()?;
+    protocol.check_minted_rewards()?;
+
+    let mut attribution_root = AttributionRoot::load(program_id, root_account)?;
+    let root_pda = syscalls::create_program_address(
+        &[
+            AttributionRoot::SEED,
+            attribution_root.oracle.as_ref(),
+            &attribution_root.epoch.to_le_bytes(),
+            &[attribution_root.bump],
+        ],
+        program_id,
+    )?;
+
+    if root_pda != *root_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    if now < attribution_root.unlocks_at {
+        return Err(SolSageError::RewardLocked.into());
+    }
+
+    if leaf_index >= attribution_root.attribution_count {
+        return Err(SolSageError::InvalidMerkleProof.into());
+    }
+
+    let leaf = solsage_core::merkle::attribution_leaf(
+        leaf_index,
+        knowledge_account.key,
+        &query_hash,
+        relevance_score,
+    );
+    if !solsage_core::merkle::verify(&attribution_root.root, leaf, &proof) {
+        return Err(SolSageError::InvalidMerkleProof.into());
+    }
+    attribution_root.set_claimed(leaf_index)?;
+
+    let mut profile = load_claim_profile(program_id, claimer, profile_account)?;
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.staker != profile.staker {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    if !knowledge.royalty_splits.is_empty() {
+        return Err(SolSageError::CoAuthoredEntry.into());
+    }
+
+    if !knowledge.is_active {
+        return Err(SolSageError::KnowledgeInactive.into());
+    }
+
+    // The attributions were made before the root was committed
+    if attribution_root.committed_at >= knowledge.expires_at {
+        return Err(SolSageError::KnowledgeExpired.into());
+    }
+
+    if knowledge.category != *category_account.key {
+        return Err(SolSageError::CategoryMismatch.into());
+    }
+    let mut category = load_category(program_id, category_account)?;
+
+    let reward = protocol
+        .reward_curve
+        .reward(
+            protocol.reward_per_attribution,
+            relevance_score,
+            knowledge.total_attributions,
+            protocol.score_divisor,
+        )
+        .and_then(|reward| category.apply_multiplier(reward))
+        .and_then(|reward| knowledge.lockup_tier.apply_boost(reward))
+        .and_then(|reward| knowledge.apply_quality(reward))
+        .and_then(|reward| {
+            solsage_core::apply_bps(reward, protocol.score_weight_bps(knowledge.created_at, attribution_root.committed_at))
+        })
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+
+    if reward == 0 {
+        return Err(SolSageError::NoRewardsToClaim.into());
+    }
+    let awarded = reward;
+    let reward = knowledge.set_aside_citation_royalty(reward, protocol.citation_royalty_bps)?;
+
+    // The root has already vested, so the reward is minted straight away
+    mint_rewards(program_id, &protocol, &profile.staker, &mint_accounts, reward)?;
+
+    knowledge.total_attributions = knowledge
+        .total_attributions
+        .checked_add(1)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    knowledge.store(knowledge_account)?;
+
+    profile.record_attribution(relevance_score)?;
+    profile.cumulative_rewards = profile
+        .cumulative_rewards
+        .checked_add(reward)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    profile.store(profile_account)?;
+
+    category.record_attribution(reward)?;
+    category.store(category_account)?;
+
+    record_collection_attribution(program_id, &knowledge, account_info_iter, reward, now)?;
+
+    protocol.total_attributions = protocol
+        .total_attributions
+        .checked_add(1)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    protocol.total_rewards = protocol
+        .total_rewards
+        .checked_add(awarded)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    protocol.store(protocol_account)?;
+
+    attribution_root.store(root_account)?;
+
+    SolSageEvent::AttributedRewardClaimed {
+        attribution_root: *root_account.key,
+        knowledge_entry: *knowledge_account.key,
+        leaf_index,
+        query_hash,
+        relevance_score,
+        reward,
+    }
+    .emit()?;
+
+    msg!("Claimed {} SAGE tokens from attribution root leaf {}", reward, leaf_index);
+    Ok(())
+}
+
+fn process_set_epoch_schedule(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    epoch_duration_secs: i64,
+    epoch_reward_budget: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    let enabled = (Protocol::MIN_EPOCH_DURATION_SECS..=Protocol::MAX_EPOCH_DURATION_SECS)
+        .contains(&epoch_duration_secs)
+        && epoch_reward_budget > 0;
+    if epoch_duration_secs != 0 && !enabled {
+        return Err(SolSageError::InvalidEpochSchedule.into());
+    }
+
+    if enabled {
+        protocol.check_minted_rewards()?;
+    }
+
+    let old_epoch_duration_secs = protocol.epoch_duration_secs;
+    let old_epoch_reward_budget = protocol.epoch_reward_budget;
+    protocol.epoch_duration_secs = epoch_duration_secs;
+    protocol.epoch_reward_budget = epoch_reward_budget;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::EpochScheduleChanged {
+        old_epoch_duration_secs,
+        new_epoch_duration_secs: epoch_duration_secs,
+        old_epoch_reward_budget,
+        new_epoch_reward_budget: epoch_reward_budget,
+    }
+    .emit()?;
+
+    msg!("Epoch schedule changed: {}s, {} SAGE per epoch", epoch_duration_secs, epoch_reward_budget);
+    Ok(())
+}
+
+fn process_open_epoch(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let epoch_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+    protocol.check_not_paused()?;
+
+    if !protocol.epochs_enabled() {
+        return Err(SolSageError::EpochsDisabled.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    if now < protocol.epoch_ends_at {
+        return Err(SolSageError::EpochNotEnded.into());
+    }
+
+    let index = protocol.epoch_count;
+    let index_seed = index.to_le_bytes();
+    let (epoch_pda, bump) = syscalls::find_program_address(&[Epoch::SEED, &index_seed], program_id);
+
+    if epoch_pda != *epoch_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(epoch_account)?;
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            epoch_account.key,
+            rent.minimum_balance(Epoch::LEN),
+            Epoch::LEN as u64,
+            program_id,
+        ),
+        &[payer.clone(), epoch_account.clone(), system_program.clone()],
+        &[&[Epoch::SEED, &index_seed, &[bump]]],
+    )?;
+
+    let ends_at = now
+        .checked_add(protocol.epoch_duration_secs)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    let epoch = Epoch {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        index,
+        started_at: now,
+        ends_at,
+        reward_budget: protocol.epoch_reward_budget,
+        total_weight: 0,
+        is_finalized: false,
+        bump,
+    };
+    epoch.store(epoch_account)?;
+
+    protocol.epoch_count = index.checked_add(1).ok_or(SolSageError::ArithmeticOverflow)?;
+    protocol.epoch_ends_at = ends_at;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::EpochOpened {
+        epoch: *epoch_account.key,
+        index,
+        reward_budget: epoch.reward_budget,
+        ends_at,
+    }
+    .emit()?;
+
+    msg!("Epoch {} opened with {} SAGE until {}", index, epoch.reward_budget, ends_at);
+    Ok(())
+}
+
+fn process_finalize_epoch(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let epoch_account = next_account_info(account_info_iter)?;
+
+    let mut epoch = load_epoch(program_id, epoch_account)?;
+
+    if epoch.is_finalized {
+        return Err(SolSageError::EpochAlreadyFinalized.into());
+    }
+
+    if Clock::get()?.unix_timestamp < epoch.ends_at {
+        return Err(SolSageError::EpochNotEnded.into());
+    }
+
+    epoch.is_finalized = true;
+    epoch.store(epoch_account)?;
+
+    SolSageEvent::EpochFinalized {
+        epoch: *epoch_account.key,
+        index: epoch.index,
+        reward_budget: epoch.reward_budget,
+        total_weight: epoch.total_weight,
+    }
+    .emit()?;
+
+    msg!("Epoch {} finalized: {} SAGE over {} weight", epoch.index, epoch.reward_budget, epoch.total_weight);
+    Ok(())
+}
+
+fn process_set_curator_share(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    curator_bps: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    if curator_bps > Protocol::MAX_CURATOR_BPS {
+        return Err(SolSageError::InvalidCuratorShare.into());
+    }
+
+    // The curator share is one of the fee config's shares
+    let fee_config = FeeConfig { curator_bps, ..protocol.fee_config };
+    if !fee_config.is_valid() {
+        return Err(SolSageError::InvalidFeeConfig.into());
+    }
+
+    let old_curator_bps = protocol.curator_bps;
+    protocol.curator_bps = curator_bps;
+    protocol.fee_config = fee_config;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::CuratorShareChanged {
+        old_curator_bps,
+        new_curator_bps: curator_bps,
+    }
+    .emit()?;
+
+    msg!("Curator share changed: {} -> {} bps", old_curator_bps, curator_bps);
+    Ok(())
+}
+
+fn process_claim_curator_rewards(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let curator = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let curator_account = next_account_info(account_info_iter)?;
+    let mint_accounts = RewardMintAccounts {
+        sage_mint: next_account_info(account_info_iter)?,
+        destination: next_account_info(account_info_iter)?,
+        mint_authority: next_account_info(account_info_iter)?,
+        token_program: next_account_info(account_info_iter)?,
+    };
+
+    if !curator.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+    protocol.check_not_paused()?;
+
+    let mut curator_state = load_curator_account(program_id, curator.key, curator_account)?;
+    let amount = curator_state.pending_rewards;
+    if amount == 0 {
+        return Err(SolSageError::NoRewardsToClaim.into());
+    }
+
+    mint_rewards(program_id, &protocol, curator.key, &mint_accounts, amount)?;
+
+    curator_state.pending_rewards = 0;
+    curator_state.total_claimed = curator_state
+        .total_claimed
+        .checked_add(amount)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    curator_state.store(curator_account)?;
+
+    SolSageEvent::CuratorRewardsClaimed {
+        curator: *curator.key,
+        amount,
+    }
+    .emit()?;
+
+    msg!("Curator claimed {} SAGE tokens", amount);
+    Ok(())
+}
+
+fn process_create_collection(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    name: String,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let owner = next_account_info(account_info_iter)?;
+    let collection_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !owner.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    if name.len() > Collection::MAX_NAME_LEN {
+        return Err(SolSageError::CollectionNameTooLong.into());
+    }
+
+    // Derive collection PDA
+    let (collection_pda, bump) = syscalls::find_program_address(
+        &[Collection::SEED, owner.key.as_ref(), name.as_bytes()],
+        program_id,
+    );
+
+    if collection_pda != *collection_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(collection_account)?;
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            owner.key,
+            collection_account.key,
+            rent.minimum_balance(Collection::LEN),
+            Collection::LEN as u64,
+            program_id,
+        ),
+        &[owner.clone(), collection_account.clone(), system_program.clone()],
+        &[&[Collection::SEED, owner.key.as_ref(), name.as_bytes(), &[bump]]],
+    )?;
+
+    let collection = Collection {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        owner: *owner.key,
+        name: name.clone(),
+        entries: 0,
+        attributions: 0,
+        rewards: 0,
+        last_attribution_at: 0,
+        created_at: Clock::get()?.unix_timestamp,
+        bump,
+    };
+    collection.store(collection_account)?;
+
+    SolSageEvent::CollectionCreated {
+        collection: *collection_account.key,
+        owner: *owner.key,
+        name: name.clone(),
+    }
+    .emit()?;
+
+    msg!("Collection created: {}", name);
+    Ok(())
+}
+
+fn process_add_to_collection(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let owner = next_account_info(account_info_iter)?;
+    let collection_account = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+
+    if !owner.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut collection = load_collection(program_id, collection_account)?;
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if collection.owner != *owner.key {
+        return Err(SolSageError::NotCollectionOwner.into());
+    }
+
+    if knowledge.staker != *owner.key {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    if knowledge.collection.is_some() {
+        return Err(SolSageError::EntryInCollection.into());
+    }
+
+    collection.entries = collection.entries.checked_add(1).ok_or(SolSageError::ArithmeticOverflow)?;
+    collection.store(collection_account)?;
+
+    knowledge.collection = Some(*collection_account.key);
+    knowledge.store(knowledge_account)?;
+
+    SolSageEvent::CollectionEntryAdded {
+        collection: *collection_account.key,
+        knowledge_entry: *knowledge_account.key,
+    }
+    .emit()?;
+
+    msg!("Entry added to collection {}", collection.name);
+    Ok(())
+}
+
+fn process_remove_from_collection(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let signer = next_account_info(account_info_iter)?;
+    let collection_account = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+
+    if !signer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut collection = load_collection(program_id, collection_account)?;
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.collection != Some(*collection_account.key) {
+        return Err(SolSageError::CollectionMismatch.into());
+    }
+
+    // An entry transferred away can still be taken out by its new owner
+    if collection.owner != *signer.key && knowledge.staker != *signer.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    collection.entries = collection.entries.checked_sub(1).ok_or(SolSageError::ArithmeticOverflow)?;
+    collection.store(collection_account)?;
+
+    knowledge.collection = None;
+    knowledge.store(knowledge_account)?;
+
+    SolSageEvent::CollectionEntryRemoved {
+        collection: *collection_account.key,
+        knowledge_entry: *knowledge_account.key,
+    }
+    .emit()?;
+
+    msg!("Entry removed from collection {}", collection.name);
+    Ok(())
+}
+
+fn process_set_royalty_split(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    splits: Vec<RoyaltySplit>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let staker = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !staker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.staker != *staker.key {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    knowledge.set_royalty_splits(splits)?;
+    KnowledgeEntry::resize(knowledge_account, staker, system_program, &Rent::get()?)?;
+    knowledge.store(knowledge_account)?;
+
+    SolSageEvent::RoyaltySplitChanged {
+        knowledge_entry: *knowledge_account.key,
+        splits: knowledge.royalty_splits.clone(),
+    }
+    .emit()?;
+
+    msg!("Royalty split set across {} co-authors", knowledge.royalty_splits.len());
+    Ok(())
+}
+
+fn process_update_license(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    license: LicenseType,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let staker = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !staker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.staker != *staker.key {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    license.validate()?;
+    knowledge.license = license;
+    knowledge.updated_at = Clock::get()?.unix_timestamp;
+    KnowledgeEntry::resize(knowledge_account, staker, system_program, &Rent::get()?)?;
+    knowledge.store(knowledge_account)?;
+
+    SolSageEvent::LicenseUpdated {
+        knowledge_entry: *knowledge_account.key,
+        license: knowledge.license.clone(),
+    }
+    .emit()?;
+
+    msg!("License updated: {:?}", knowledge.license);
+    Ok(())
+}
+
+fn process_update_content_uri(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    content_uri: Option<String>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let staker = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !staker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.staker != *staker.key {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    knowledge.set_content_uri(content_uri)?;
+    knowledge.updated_at = Clock::get()?.unix_timestamp;
+    KnowledgeEntry::resize(knowledge_account, staker, system_program, &Rent::get()?)?;
+    knowledge.store(knowledge_account)?;
+
+    SolSageEvent::ContentUriUpdated {
+        knowledge_entry: *knowledge_account.key,
+        content_uri: knowledge.content_uri.clone(),
+    }
+    .emit()?;
+
+    msg!("Content URI updated: {}", knowledge.content_uri.as_deref().unwrap_or("none"));
+    Ok(())
+}
+
+fn process_update_embedding_fingerprint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    embedding_fingerprint: Option<[u8; KnowledgeEntry::EMBEDDING_FINGERPRINT_LEN]>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let staker = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !staker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.staker != *staker.key {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    knowledge.embedding_fingerprint = embedding_fingerprint;
+    knowledge.updated_at = Clock::get()?.unix_timestamp;
+    KnowledgeEntry::resize(knowledge_account, staker, system_program, &Rent::get()?)?;
+    knowledge.store(knowledge_account)?;
+
+    SolSageEvent::EmbeddingFingerprintUpdated { knowledge_entry: *knowledge_account.key, embedding_fingerprint }.emit()?;
+
+    msg!("Embedding fingerprint {}", if embedding_fingerprint.is_some() { "updated" } else { "cleared" });
+    Ok(())
+}
+
+fn process_add_reviewer(program_id: &Pubkey, accounts: &[AccountInfo], reviewer: Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let registry_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    protocol.check_no_admin_delay()?;
+
+    let mut registry = load_or_create_reviewer_registry(program_id, authority, registry_account, system_program)?;
+
+    if registry.is_reviewer(&reviewer) {
+        return Err(SolSageError::ReviewerAlreadyAdded.into());
+    }
+    if registry.reviewers.len() >= ReviewerRegistry::MAX_REVIEWERS {
+        return Err(SolSageError::ReviewerRegistryFull.into());
+    }
+
+    registry.reviewers.push(reviewer);
+    registry.store(registry_account)?;
+
+    SolSageEvent::ReviewerAdded { reviewer }.emit()?;
+
+    msg!("Reviewer added: {}", reviewer);
+    Ok(())
+}
+
+/// Load the reviewer registry, creating it at `payer`'s expense on first use
+fn load_or_create_reviewer_registry<'info>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'info>,
+    registry_account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<ReviewerRegistry, ProgramError> {
+    if !registry_account.data_is_empty() {
+        return load_reviewer_registry(program_id, registry_account);
+    }
+
+    let (registry_pda, bump) = syscalls::find_program_address(&[ReviewerRegistry::SEED], program_id);
+
+    if registry_pda != *registry_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            registry_account.key,
+            Rent::get()?.minimum_balance(ReviewerRegistry::LEN),
+            ReviewerRegistry::LEN as u64,
+            program_id,
+        ),
+        &[payer.clone(), registry_account.clone(), system_program.clone()],
+        &[&[ReviewerRegistry::SEED, &[bump]]],
+    )?;
+
+    Ok(ReviewerRegistry { is_initialized: true, schema_version: SCHEMA_VERSION, reviewers: Vec::new(), bump })
+}
+
+/// Load the reviewer registry, checking it is the registry's PDA
+fn load_reviewer_registry(program_id: &Pubkey, registry_account: &AccountInfo) -> Result<ReviewerRegistry, ProgramError> {
+    let registry = ReviewerRegistry::load(program_id, registry_account)?;
+    let registry_pda = syscalls::create_program_address(&[ReviewerRegistry::SEED, &[registry.bump]], program_id)?;
+
+    if registry_pda != *registry_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    Ok(registry)
+}
+
+fn process_remove_reviewer(program_id: &Pubkey, accounts: &[AccountInfo], reviewer: Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let registry_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    protocol.check_no_admin_delay()?;
+
+    let mut registry = load_reviewer_registry(program_id, registry_account)?;
+
+    if !registry.is_reviewer(&reviewer) {
+        return Err(SolSageError::NotReviewer.into());
+    }
+
+    registry.reviewers.retain(|approved| *approved != reviewer);
+    registry.store(registry_account)?;
+
+    SolSageEvent::ReviewerRemoved { reviewer }.emit()?;
+
+    msg!("Reviewer removed: {}", reviewer);
+    Ok(())
+}
+
+fn process_attest_entry(program_id: &Pubkey, accounts: &[AccountInfo], accuracy: u8, freshness: u8) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let reviewer = next_account_info(account_info_iter)?;
+    let registry_account = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !reviewer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    if accuracy > ReviewAttestation::MAX_SCORE || freshness > ReviewAttestation::MAX_SCORE {
+        return Err(SolSageError::InvalidAttestationScore.into());
+    }
+
+    let registry = load_reviewer_registry(program_id, registry_account)?;
+
+    if !registry.is_reviewer(reviewer.key) {
+        return Err(SolSageError::NotReviewer.into());
+    }
+
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.staker == *reviewer.key {
+        return Err(SolSageError::SelfAttestation.into());
+    }
+
+    let attestation = ReviewAttestation {
+        reviewer: *reviewer.key,
+        accuracy,
+        freshness,
+        content_hash: knowledge.content_hash,
+        attested_at: Clock::get()?.unix_timestamp,
+    };
+    knowledge.verified = attestation.verifies();
+    knowledge.attestation = Some(attestation);
+    KnowledgeEntry::resize(knowledge_account, reviewer, system_program, &Rent::get()?)?;
+    knowledge.store(knowledge_account)?;
+
+    SolSageEvent::EntryAttested {
+        knowledge_entry: *knowledge_account.key,
+        reviewer: *reviewer.key,
+        accuracy,
+        freshness,
+        verified: knowledge.verified,
+    }
+    .emit()?;
+
+    msg!("Entry attested: {} ({})", knowledge.title, if knowledge.verified { "verified" } else { "unverified" });
+    Ok(())
+}
+
+fn process_set_identity_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    identity_config: Option<IdentityConfig>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    protocol.check_no_admin_delay()?;
+
+    protocol.identity_config = identity_config;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::IdentityConfigChanged { identity_config }.emit()?;
+
+    msg!("Identity config changed: {:?}", identity_config);
+    Ok(())
+}
+
+fn process_set_entry_visibility(program_id: &Pubkey, accounts: &[AccountInfo], visibility: Visibility) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let staker = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !staker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.staker != *staker.key {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    knowledge.visibility = visibility;
+    knowledge.updated_at = Clock::get()?.unix_timestamp;
+    KnowledgeEntry::resize(knowledge_account, staker, system_program, &Rent::get()?)?;
+    knowledge.store(knowledge_account)?;
+
+    SolSageEvent::EntryVisibilityChanged { knowledge_entry: *knowledge_account.key, visibility }.emit()?;
+
+    msg!("Entry visibility changed: {:?}", visibility);
+    Ok(())
+}
+
+fn process_grant_access(program_id: &Pubkey, accounts: &[AccountInfo], holder: Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let staker = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let pass_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !staker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.staker != *staker.key {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    create_access_pass(program_id, staker, knowledge_account, &holder, pass_account, system_program, 0)?;
+
+    msg!("Access granted to {}", holder);
+    Ok(())
+}
+
+fn process_buy_access(program_id: &Pubkey, accounts: &[AccountInfo], price: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let buyer = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let owner = next_account_info(account_info_iter)?;
+    let pass_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !buyer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.staker != *owner.key {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    let Visibility::Private { access_price } = knowledge.visibility else {
+        return Err(SolSageError::EntryNotPrivate.into());
+    };
+
+    if access_price != price {
+        return Err(SolSageError::AccessPriceChanged.into());
+    }
+
+    if price > 0 {
+        invoke(
+            &system_instruction::transfer(buyer.key, owner.key, price),
+            &[buyer.clone(), owner.clone(), system_program.clone()],
+        )?;
+    }
+    create_access_pass(program_id, buyer, knowledge_account, buyer.key, pass_account, system_program, price)?;
+
+    msg!("Access bought for {} lamports", price);
+    Ok(())
+}
+
+/// Create `holder`'s access pass to the entry at `knowledge_account`, paid
+/// for by `payer`
+fn create_access_pass<'info>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'info>,
+    knowledge_account: &AccountInfo<'info>,
+    holder: &Pubkey,
+    pass_account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    price_paid: u64,
+) -> ProgramResult {
+    let (pass_pda, bump) = syscalls::find_program_address(
+        &[AccessPass::SEED, knowledge_account.key.as_ref(), holder.as_ref()],
+        program_id,
+    );
+
+    if pass_pda != *pass_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(pass_account)?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            pass_account.key,
+            Rent::get()?.minimum_balance(AccessPass::LEN),
+            AccessPass::LEN as u64,
+            program_id,
+        ),
+        &[payer.clone(), pass_account.clone(), system_program.clone()],
+        &[&[AccessPass::SEED, knowledge_account.key.as_ref(), holder.as_ref(), &[bump]]],
+    )?;
+
+    let pass = AccessPass {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        knowledge_entry: *knowledge_account.key,
+        holder: *holder,
+        price_paid,
+        granted_at: Clock::get()?.unix_timestamp,
+        bump,
+    };
+    pass.store(pass_account)?;
+
+    SolSageEvent::AccessGranted { knowledge_entry: *knowledge_account.key, holder: *holder, price_paid }.emit()
+}
+
+fn process_migrate_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_type: AccountType,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    if account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    if account_type == AccountType::Protocol && account.key != protocol_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    let from_version = match account_type {
+        AccountType::Protocol => migrate_account::<Protocol>(program_id, authority, account, system_program),
+        AccountType::KnowledgeEntry => migrate_account::<KnowledgeEntry>(program_id, authority, account, system_program),
+        AccountType::Attribution => migrate_account::<Attribution>(program_id, authority, account, system_program),
+        AccountType::QueryRecord => migrate_account::<QueryRecord>(program_id, authority, account, system_program),
+        AccountType::AttributionOracle => {
+            migrate_account::<AttributionOracle>(program_id, authority, account, system_program)
+        }
+        AccountType::StakerProfile => migrate_account::<StakerProfile>(program_id, authority, account, system_program),
+        AccountType::Challenge => migrate_account::<Challenge>(program_id, authority, account, system_program),
+        AccountType::ContentRegistry => {
+            migrate_account::<ContentRegistry>(program_id, authority, account, system_program)
+        }
+        AccountType::Category => migrate_account::<Category>(program_id, authority, account, system_program),
+        AccountType::AttributionRoot => {
+            migrate_account::<AttributionRoot>(program_id, authority, account, system_program)
+        }
+        AccountType::Epoch => migrate_account::<Epoch>(program_id, authority, account, system_program),
+        AccountType::CuratorAccount => migrate_account::<CuratorAccount>(program_id, authority, account, system_program),
+        AccountType::Collection => migrate_account::<Collection>(program_id, authority, account, system_program),
+        AccountType::TagIndex => migrate_account::<TagIndex>(program_id, authority, account, system_program),
+        AccountType::Subscription => migrate_account::<Subscription>(program_id, authority, account, system_program),
+        AccountType::AgentStats => migrate_account::<AgentStats>(program_id, authority, account, system_program),
+        AccountType::Leaderboard => migrate_account::<Leaderboard>(program_id, authority, account, system_program),
+        AccountType::Proposal => migrate_account::<Proposal>(program_id, authority, account, system_program),
+        AccountType::VoteRecord => migrate_account::<VoteRecord>(program_id, authority, account, system_program),
+        AccountType::StakerIndex => migrate_account::<StakerIndex>(program_id, authority, account, system_program),
+        AccountType::OracleState => migrate_account::<OracleState>(program_id, authority, account, system_program),
+        AccountType::Snapshot => migrate_account::<Snapshot>(program_id, authority, account, system_program),
+        AccountType::BridgeEmitter => migrate_account::<BridgeEmitter>(program_id, authority, account, system_program),
+        AccountType::Listing => migrate_account::<Listing>(program_id, authority, account, system_program),
+        AccountType::PayerStats => migrate_account::<PayerStats>(program_id, authority, account, system_program),
+        AccountType::EntryVote => migrate_account::<EntryVote>(program_id, authority, account, system_program),
+        AccountType::ReportQueue => migrate_account::<ReportQueue>(program_id, authority, account, system_program),
+        AccountType::Bounty => migrate_account::<Bounty>(program_id, authority, account, system_program),
+        AccountType::BountyAnswer => migrate_account::<BountyAnswer>(program_id, authority, account, system_program),
+        AccountType::Document => migrate_account::<Document>(program_id, authority, account, system_program),
+        AccountType::ReviewerRegistry => {
+            migrate_account::<ReviewerRegistry>(program_id, authority, account, system_program)
+        }
+        AccountType::AccessPass => migrate_account::<AccessPass>(program_id, authority, account, system_program),
+    }?;
+
+    // Checked after migrating so the protocol can migrate itself; a failed
+    // check rolls the migration back
+    let protocol = load_protocol(program_id, protocol_account)?;
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    SolSageEvent::AccountMigrated {
+        account: *account.key,
+        account_type,
+        from_version,
+        to_version: account_type.schema_version(),
+    }
+    .emit()?;
+
+    msg!("Account migrated from schema v{} to v{}", from_version, account_type.schema_version());
+    Ok(())
+}
+
+/// Rewrite `account` from its stored schema version into the current layout
+/// of `T`, topping up its rent from `payer` and growing it first. Accounts
+/// already on the current version are only grown to fit appended fields and
+/// tagged with their type; ones tagged with another type are rejected.
+/// Returns the version it was migrated from.
+fn migrate_account<'info, T: ProgramAccount>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<u8, ProgramError> {
+    let (discriminator, from_version) = {
+        let data = account.data.borrow();
+        (data.first().copied(), T::stored_schema_version(&data).ok_or(ProgramError::UninitializedAccount)?)
+    };
+    let untagged = discriminator == Some(UNTAGGED_DISCRIMINATOR);
+    if !untagged && discriminator != Some(T::DISCRIMINATOR) {
+        return Err(SolSageError::AccountTypeMismatch.into());
+    }
+    if !untagged && from_version >= T::SCHEMA_VERSION && account.data_len() >= T::LEN {
+        return Err(SolSageError::AccountUpToDate.into());
+    }
+
+    let rent_due = Rent::get()?.minimum_balance(T::LEN).saturating_sub(account.lamports());
+    if rent_due > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, rent_due),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+
+    // Version 0 lacks the version byte: everything after the flag moves up
+    // one byte to make room for it. Appended fields read as zeros, and any
+    // layout changed since is then rearranged by its type.
+    let old_len = account.data_len();
+    account.realloc(T::LEN, true)?;
+    if from_version < T::SCHEMA_VERSION {
+        let mut data = account.data.borrow_mut();
+        if from_version == 0 {
+            data.copy_within(1..old_len, 2);
+        }
+        data[1] = T::SCHEMA_VERSION;
+        T::upgrade_layout(&mut data, from_version)?;
+    }
+    account.data.borrow_mut()[0] = T::DISCRIMINATOR;
+
+    // However `account_type` was vouched for, the result must decode
+    T::load(program_id, account)?;
+    Ok(from_version)
+}
+
+fn process_add_tag(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    tag: String,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let staker = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let tag_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !staker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.staker != *staker.key {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    knowledge.add_tag(tag.clone())?;
+
+    let rent = Rent::get()?;
+    let now = Clock::get()?.unix_timestamp;
+    let mut tag_index = load_or_create_tag_index(program_id, staker, &tag, tag_account, system_program, &rent)?;
+    tag_index.entries = tag_index.entries.checked_add(1).ok_or(SolSageError::ArithmeticOverflow)?;
+    tag_index.store(tag_account)?;
+
+    knowledge.updated_at = now;
+    KnowledgeEntry::resize(knowledge_account, staker, system_program, &rent)?;
+    knowledge.store(knowledge_account)?;
+
+    SolSageEvent::TagAdded {
+        knowledge_entry: *knowledge_account.key,
+        tag: tag.clone(),
+    }
+    .emit()?;
+
+    msg!("Tag added: {} ({} entries)", tag, tag_index.entries);
+    Ok(())
+}
+
+fn process_remove_tag(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    tag: String,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let staker = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let tag_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !staker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.staker != *staker.key {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    knowledge.remove_tag(&tag)?;
+
+    let mut tag_index = load_tag_index(program_id, &tag, tag_account)?;
+    tag_index.entries = tag_index.entries.checked_sub(1).ok_or(SolSageError::ArithmeticOverflow)?;
+    tag_index.store(tag_account)?;
+
+    knowledge.updated_at = Clock::get()?.unix_timestamp;
+    KnowledgeEntry::resize(knowledge_account, staker, system_program, &Rent::get()?)?;
+    knowledge.store(knowledge_account)?;
+
+    SolSageEvent::TagRemoved {
+        knowledge_entry: *knowledge_account.key,
+        tag: tag.clone(),
+    }
+    .emit()?;
+
+    msg!("Tag removed: {} ({} entries)", tag, tag_index.entries);
+    Ok(())
+}
+
+/// Load the protocol, checking it is the protocol PDA
+fn load_protocol(program_id: &Pubkey, protocol_account: &AccountInfo) -> Result<Protocol, ProgramError> {
+    let protocol = Protocol::load(program_id, protocol_account)?;
+    let protocol_pda = syscalls::create_program_address(&[Protocol::SEED, &[protocol.bump]], program_id)?;
+
+    if protocol_pda != *protocol_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    Ok(protocol)
+}
+
+/// Load a registered category, checking it is the PDA for its own name
+fn load_category(program_id: &Pubkey, category_account: &AccountInfo) -> Result<Category, ProgramError> {
+    let category = Category::load(program_id, category_account)?;
+    let category_pda = syscalls::create_program_address(
+        &[Category::SEED, category.name.as_bytes(), &[category.bump]],
+        program_id,
+    )?;
+
+    if category_pda != *category_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    Ok(category)
+}
+
+/// Load a document, checking it is the PDA for its own owner and content
+/// hash
+fn load_document(program_id: &Pubkey, document_account: &AccountInfo) -> Result<Document, ProgramError> {
+    let document = Document::load(program_id, document_account)?;
+    let document_pda = syscalls::create_program_address(
+        &[Document::SEED, document.owner.as_ref(), &document.content_hash, &[document.bump]],
+        program_id,
+    )?;
+
+    if document_pda != *document_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    Ok(document)
+}
+
+/// Load a collection, checking it is the PDA for its own owner and name
+fn load_collection(program_id: &Pubkey, collection_account: &AccountInfo) -> Result<Collection, ProgramError> {
+    let collection = Collection::load(program_id, collection_account)?;
+    let collection_pda = syscalls::create_program_address(
+        &[Collection::SEED, collection.owner.as_ref(), collection.name.as_bytes(), &[collection.bump]],
+        program_id,
+    )?;
+
+    if collection_pda != *collection_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    Ok(collection)
+}
+
+fn process_set_reward_source(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    reward_source: RewardSource,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let reward_vault = next_account_info(account_info_iter)?;
+    let sage_mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    // Rewards already earned must be paid the way they were earned
+    if protocol.total_attributions > 0 {
+        return Err(SolSageError::RewardSourceLocked.into());
+    }
+
+    if reward_source == RewardSource::Payer {
+        if protocol.epochs_enabled() {
+            return Err(SolSageError::MintedRewardsOnly.into());
+        }
+
+        if protocol.reward_mint == Pubkey::default() || protocol.reward_mint != *sage_mint.key {
+            return Err(SolSageError::InvalidRewardMint.into());
+        }
+
+        if reward_vault.data_is_empty() {
+            let (reward_vault_pda, reward_vault_bump) =
+                syscalls::find_program_address(&[Protocol::REWARD_VAULT_SEED], program_id);
+
+            if reward_vault_pda != *reward_vault.key {
+                return Err(SolSageError::InvalidPda.into());
+            }
+
+            // Held by the mint authority, which signs every reward payout
+            let mint_authority = syscalls::create_program_address(
+                &[Protocol::MINT_AUTHORITY_SEED, &[protocol.mint_authority_bump]],
+                program_id,
+            )?;
+
+            invoke_signed(
+                &system_instruction::create_account(
+                    authority.key,
+                    reward_vault.key,
+                    Rent::get()?.minimum_balance(spl_token::state::Account::LEN),
+                    spl_token::state::Account::LEN as u64,
+                    token_program.key,
+                ),
+                &[authority.clone(), reward_vault.clone(), system_program.clone()],
+                &[&[Protocol::REWARD_VAULT_SEED, &[reward_vault_bump]]],
+            )?;
+
+            invoke(
+                &spl_token::instruction::initialize_account3(
+                    token_program.key,
+                    reward_vault.key,
+                    sage_mint.key,
+                    &mint_authority,
+                )?,
+                &[reward_vault.clone(), sage_mint.clone(), token_program.clone()],
+            )?;
+
+            protocol.reward_vault_bump = reward_vault_bump;
+        } else {
+            check_reward_vault(program_id, &protocol, reward_vault)?;
+        }
+    }
+
+    let old_reward_source = protocol.reward_source;
+    protocol.reward_source = reward_source;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::RewardSourceChanged {
+        old_reward_source,
+        new_reward_source: reward_source,
+    }
+    .emit()?;
+
+    msg!("Reward source changed: {:?} -> {:?}", old_reward_source, reward_source);
+    Ok(())
+}
+
+fn process_top_up_subscription(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let consumer = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let subscription_account = next_account_info(account_info_iter)?;
+    let consumer_token_account = next_account_info(account_info_iter)?;
+    let reward_vault = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !consumer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+    protocol.check_not_paused()?;
+
+    if protocol.reward_source != RewardSource::Payer {
+        return Err(SolSageError::RewardsNotPayerFunded.into());
+    }
+
+    if amount == 0 {
+        return Err(SolSageError::ZeroDeposit.into());
+    }
+
+    check_reward_vault(program_id, &protocol, reward_vault)?;
+
+    let mut subscription = load_or_create_subscription(
+        program_id,
+        consumer,
+        subscription_account,
+        system_program,
+    )?;
+
+    invoke(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            consumer_token_account.key,
+            reward_vault.key,
+            consumer.key,
+            &[],
+            amount,
+        )?,
+        &[
+            consumer_token_account.clone(),
+            reward_vault.clone(),
+            consumer.clone(),
+            token_program.clone(),
+        ],
+    )?;
+
+    subscription.balance = subscription.balance.checked_add(amount).ok_or(SolSageError::ArithmeticOverflow)?;
+    subscription.total_deposited = subscription
+        .total_deposited
+        .checked_add(amount)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    subscription.store(subscription_account)?;
+
+    SolSageEvent::SubscriptionToppedUp {
+        subscription: *subscription_account.key,
+        consumer: *consumer.key,
+        amount,
+        balance: subscription.balance,
+    }
+    .emit()?;
+
+    msg!("Subscription topped up: {} SAGE, balance {}", amount, subscription.balance);
+    Ok(())
+}
+
+fn process_close_subscription(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let consumer = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let subscription_account = next_account_info(account_info_iter)?;
+    let destination = next_account_info(account_info_iter)?;
+    let reward_vault = next_account_info(account_info_iter)?;
+    let mint_authority = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    if !consumer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+    let subscription = load_subscription(program_id, consumer.key, subscription_account)?;
+
+    if subscription.balance > 0 {
+        check_reward_vault(program_id, &protocol, reward_vault)?;
+
+        let mint_authority_pda = syscalls::create_program_address(
+            &[Protocol::MINT_AUTHORITY_SEED, &[protocol.mint_authority_bump]],
+            program_id,
+        )?;
+
+        if mint_authority_pda != *mint_authority.key {
+            return Err(SolSageError::InvalidPda.into());
+        }
+
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                reward_vault.key,
+                destination.key,
+                mint_authority.key,
+                &[],
+                subscription.balance,
+            )?,
+            &[
+                reward_vault.clone(),
+                destination.clone(),
+                mint_authority.clone(),
+                token_program.clone(),
+            ],
+            &[&[Protocol::MINT_AUTHORITY_SEED, &[protocol.mint_authority_bump]]],
+        )?;
+    }
+
+    // Zero the data so the subscription reads as uninitialized, then drain
+    // its rent back to the consumer
+    subscription_account.data.borrow_mut().fill(0);
+    let consumer_lamports = consumer
+        .lamports()
+        .checked_add(subscription_account.lamports())
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    **subscription_account.lamports.borrow_mut() = 0;
+    **consumer.lamports.borrow_mut() = consumer_lamports;
+
+    SolSageEvent::SubscriptionClosed {
+        subscription: *subscription_account.key,
+        consumer: *consumer.key,
+        refunded: subscription.balance,
+    }
+    .emit()?;
+
+    msg!("Subscription closed, refunded {} SAGE", subscription.balance);
+    Ok(())
+}
+
+/// Load the consumer's subscription, creating it on their first top-up
+fn load_or_create_subscription<'info>(
+    program_id: &Pubkey,
+    consumer: &AccountInfo<'info>,
+    subscription_account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<Subscription, ProgramError> {
+    if !subscription_account.data_is_empty() {
+        return load_subscription(program_id, consumer.key, subscription_account);
+    }
+
+    let (subscription_pda, bump) =
+        syscalls::find_program_address(&[Subscription::SEED, consumer.key.as_ref()], program_id);
+
+    if subscription_pda != *subscription_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            consumer.key,
+            subscription_account.key,
+            Rent::get()?.minimum_balance(Subscription::LEN),
+            Subscription::LEN as u64,
+            program_id,
+        ),
+        &[consumer.clone(), subscription_account.clone(), system_program.clone()],
+        &[&[Subscription::SEED, consumer.key.as_ref(), &[bump]]],
+    )?;
+
+    Ok(Subscription {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        consumer: *consumer.key,
+        balance: 0,
+        total_deposited: 0,
+        total_spent: 0,
+        created_at: Clock::get()?.unix_timestamp,
+        bump,
+    })
+}
+
+/// Load the subscription of `consumer`, checking it is their PDA
+fn load_subscription(
+    program_id: &Pubkey,
+    consumer: &Pubkey,
+    subscription_account: &AccountInfo,
+) -> Result<Subscription, ProgramError> {
+    let subscription = Subscription::load(program_id, subscription_account)?;
+    let subscription_pda = syscalls::create_program_address(
+        &[Subscription::SEED, consumer.as_ref(), &[subscription.bump]],
+        program_id,
+    )?;
+
+    if subscription_pda != *subscription_account.key || subscription.consumer != *consumer {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    Ok(subscription)
+}
+
+fn process_set_price_floor(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    min_reward_per_use: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let staker = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !staker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    if min_reward_per_use > Protocol::MAX_REWARD_PER_ATTRIBUTION {
+        return Err(SolSageError::RewardRateOutOfBounds.into());
+    }
+
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if knowledge.staker != *staker.key {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    knowledge.min_reward_per_use = min_reward_per_use;
+    knowledge.updated_at = Clock::get()?.unix_timestamp;
+    KnowledgeEntry::resize(knowledge_account, staker, system_program, &Rent::get()?)?;
+    knowledge.store(knowledge_account)?;
+
+    SolSageEvent::PriceFloorSet {
+        knowledge_entry: *knowledge_account.key,
+        min_reward_per_use,
+    }
+    .emit()?;
+
+    msg!("Price floor set: {}", min_reward_per_use);
+    Ok(())
+}
+
+fn process_rebuild_leaderboard(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer = next_account_info(account_info_iter)?;
+    let leaderboard_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    // Read every entry before the leaderboard is borrowed, in case one of
+    // them is the leaderboard itself
+    let mut totals = Vec::new();
+    for knowledge_account in account_info_iter {
+        // Closed entries are left owned by the system program
+        match KnowledgeEntry::load(program_id, knowledge_account) {
+            Ok(knowledge) if knowledge.is_active => {
+                totals.push((*knowledge_account.key, Some(knowledge.total_attributions)));
+            }
+            Ok(_) | Err(ProgramError::IllegalOwner) | Err(ProgramError::UninitializedAccount) => {
+                totals.push((*knowledge_account.key, None));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    let mut leaderboard = load_or_create_leaderboard(program_id, payer, leaderboard_account, system_program)?;
+    for (knowledge_entry, total_attributions) in totals {
+        match total_attributions {
+            Some(total_attributions) => leaderboard.record(knowledge_entry, total_attributions),
+            None => leaderboard.remove(&knowledge_entry),
+        }
+    }
+    let ranked = leaderboard.entries().len() as u32;
+    drop(leaderboard);
+
+    SolSageEvent::LeaderboardRebuilt { ranked }.emit()?;
+
+    msg!("Leaderboard rebuilt: {} entries ranked", ranked);
+    Ok(())
+}
+
+/// Borrow the leaderboard in place, creating it at `payer`'s expense on first
+/// use
+fn load_or_create_leaderboard<'a, 'info>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'info>,
+    leaderboard_account: &'a AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<RefMut<'a, Leaderboard>, ProgramError> {
+    if !leaderboard_account.data_is_empty() {
+        let leaderboard = Leaderboard::load_mut(program_id, leaderboard_account)?;
+        let leaderboard_pda = syscalls::create_program_address(&[Leaderboard::SEED, &[leaderboard.bump]], program_id)?;
+
+        if leaderboard_pda != *leaderboard_account.key {
+            return Err(SolSageError::InvalidPda.into());
+        }
+
+        return Ok(leaderboard);
+    }
+
+    let (leaderboard_pda, bump) = syscalls::find_program_address(&[Leaderboard::SEED], program_id);
+
+    if leaderboard_pda != *leaderboard_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            leaderboard_account.key,
+            Rent::get()?.minimum_balance(Leaderboard::LEN),
+            Leaderboard::LEN as u64,
+            program_id,
+        ),
+        &[payer.clone(), leaderboard_account.clone(), system_program.clone()],
+        &[&[Leaderboard::SEED, &[bump]]],
+    )?;
+
+    leaderboard_account.try_borrow_mut_data()?[..Leaderboard::LEN]
+        .copy_from_slice(bytemuck::bytes_of(&Leaderboard::new(bump)));
+    Leaderboard::load_mut(program_id, leaderboard_account)
+}
+
+fn process_set_governance_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    quorum_votes: u64,
+    approval_threshold_bps: u16,
+    voting_period_secs: i64,
+    execution_delay_secs: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let governance_vault = next_account_info(account_info_iter)?;
+    let sage_mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    // A threshold of 100% could never be beaten
+    let voting_period_valid = voting_period_secs == 0
+        || (Protocol::MIN_VOTING_PERIOD_SECS..=Protocol::MAX_VOTING_PERIOD_SECS).contains(&voting_period_secs);
+    if !voting_period_valid
+        || !(0..=Protocol::MAX_EXECUTION_DELAY_SECS).contains(&execution_delay_secs)
+        || approval_threshold_bps >= Protocol::MAX_BPS
+    {
+        return Err(SolSageError::InvalidGovernanceConfig.into());
+    }
+
+    if voting_period_secs > 0 {
+        if governance_vault.data_is_empty() {
+            if protocol.reward_mint == Pubkey::default() || protocol.reward_mint != *sage_mint.key {
+                return Err(SolSageError::InvalidRewardMint.into());
+            }
+
+            let (governance_vault_pda, governance_vault_bump) =
+                syscalls::find_program_address(&[Protocol::GOVERNANCE_VAULT_SEED], program_id);
+
+            if governance_vault_pda != *governance_vault.key {
+                return Err(SolSageError::InvalidPda.into());
+            }
+
+            // Held by the mint authority, which signs every vote withdrawal
+            let mint_authority = syscalls::create_program_address(
+                &[Protocol::MINT_AUTHORITY_SEED, &[protocol.mint_authority_bump]],
+                program_id,
+            )?;
+
+            invoke_signed(
+                &system_instruction::create_account(
+                    authority.key,
+                    governance_vault.key,
+                    Rent::get()?.minimum_balance(spl_token::state::Account::LEN),
+                    spl_token::state::Account::LEN as u64,
+                    token_program.key,
+                ),
+                &[authority.clone(), governance_vault.clone(), system_program.clone()],
+                &[&[Protocol::GOVERNANCE_VAULT_SEED, &[governance_vault_bump]]],
+            )?;
+
+            invoke(
+                &spl_token::instruction::initialize_account3(
+                    token_program.key,
+                    governance_vault.key,
+                    sage_mint.key,
+                    &mint_authority,
+                )?,
+                &[governance_vault.clone(), sage_mint.clone(), token_program.clone()],
+            )?;
+
+            protocol.governance_vault_bump = governance_vault_bump;
+        } else {
+            check_governance_vault(program_id, &protocol, governance_vault)?;
+        }
+    }
+
+    protocol.quorum_votes = quorum_votes;
+    protocol.approval_threshold_bps = approval_threshold_bps;
+    protocol.voting_period_secs = voting_period_secs;
+    protocol.execution_delay_secs = execution_delay_secs;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::GovernanceConfigChanged {
+        quorum_votes,
+        approval_threshold_bps,
+        voting_period_secs,
+        execution_delay_secs,
+    }
+    .emit()?;
+
+    msg!(
+        "Governance config changed: quorum {}, threshold {} bps, voting {}s, delay {}s",
+        quorum_votes,
+        approval_threshold_bps,
+        voting_period_secs,
+        execution_delay_secs
+    );
+    Ok(())
+}
+
+fn process_create_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    action: ProposalAction,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let proposer = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !proposer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if !protocol.governance_enabled() {
+        return Err(SolSageError::GovernanceDisabled.into());
+    }
+
+    action.validate()?;
+
+    let index = protocol.proposal_count;
+    let (proposal_pda, bump) =
+        syscalls::find_program_address(&[Proposal::SEED, &index.to_le_bytes()], program_id);
+
+    if proposal_pda != *proposal_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(proposal_account)?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            proposer.key,
+            proposal_account.key,
+            Rent::get()?.minimum_balance(Proposal::LEN),
+            Proposal::LEN as u64,
+            program_id,
+        ),
+        &[proposer.clone(), proposal_account.clone(), system_program.clone()],
+        &[&[Proposal::SEED, &index.to_le_bytes(), &[bump]]],
+    )?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let voting_ends_at = now
+        .checked_add(protocol.voting_period_secs)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    let proposal = Proposal {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        index,
+        proposer: *proposer.key,
+        action,
+        votes_for: 0,
+        votes_against: 0,
+        quorum_votes: protocol.quorum_votes,
+        approval_threshold_bps: protocol.approval_threshold_bps,
+        created_at: now,
+        voting_ends_at,
+        executable_at: voting_ends_at
+            .checked_add(protocol.execution_delay_secs)
+            .ok_or(SolSageError::ArithmeticOverflow)?,
+        executed: false,
+        bump,
+    };
+    proposal.store(proposal_account)?;
+
+    protocol.proposal_count = index.checked_add(1).ok_or(SolSageError::ArithmeticOverflow)?;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::ProposalCreated {
+        proposal: *proposal_account.key,
+        index,
+        proposer: *proposer.key,
+        action,
+        voting_ends_at,
+    }
+    .emit()?;
+
+    msg!("Proposal {} created: {:?}", index, action);
+    Ok(())
+}
+
+fn process_cast_vote(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    approve: bool,
+    weight: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let voter = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter)?;
+    let vote_record_account = next_account_info(account_info_iter)?;
+    let voter_token_account = next_account_info(account_info_iter)?;
+    let governance_vault = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !voter.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+    let mut proposal = load_proposal(program_id, proposal_account)?;
+
+    if Clock::get()?.unix_timestamp >= proposal.voting_ends_at {
+        return Err(SolSageError::VotingClosed.into());
+    }
+
+    if weight == 0 {
+        return Err(SolSageError::ZeroDeposit.into());
+    }
+
+    check_governance_vault(program_id, &protocol, governance_vault)?;
+
+    // One vote per voter; a second would find the record already created
+    let (vote_record_pda, bump) = syscalls::find_program_address(
+        &[VoteRecord::SEED, proposal_account.key.as_ref(), voter.key.as_ref()],
+        program_id,
+    );
+
+    if vote_record_pda != *vote_record_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(vote_record_account)?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            voter.key,
+            vote_record_account.key,
+            Rent::get()?.minimum_balance(VoteRecord::LEN),
+            VoteRecord::LEN as u64,
+            program_id,
+        ),
+        &[voter.clone(), vote_record_account.clone(), system_program.clone()],
+        &[&[VoteRecord::SEED, proposal_account.key.as_ref(), voter.key.as_ref(), &[bump]]],
+    )?;
+
+    // Locking the SAGE keeps it from voting again from another wallet
+    invoke(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            voter_token_account.key,
+            governance_vault.key,
+            voter.key,
+            &[],
+            weight,
+        )?,
+        &[
+            voter_token_account.clone(),
+            governance_vault.clone(),
+            voter.clone(),
+            token_program.clone(),
+        ],
+    )?;
+
+    proposal.record_vote(approve, weight)?;
+    proposal.store(proposal_account)?;
+
+    let vote_record = VoteRecord {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        proposal: *proposal_account.key,
+        voter: *voter.key,
+        approve,
+        weight,
+        bump,
+    };
+    vote_record.store(vote_record_account)?;
+
+    SolSageEvent::VoteCast {
+        proposal: *proposal_account.key,
+        voter: *voter.key,
+        approve,
+        weight,
+    }
+    .emit()?;
+
+    msg!("Vote cast on proposal {}: {} SAGE {}", proposal.index, weight, if approve { "for" } else { "against" });
+    Ok(())
+}
+
+fn process_execute_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let executor = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter)?;
+
+    if !executor.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+    let mut proposal = load_proposal(program_id, proposal_account)?;
+
+    if proposal.executed {
+        return Err(SolSageError::ProposalAlreadyExecuted.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    if now < proposal.voting_ends_at {
+        return Err(SolSageError::VotingOpen.into());
+    }
+    if !proposal.passed() {
+        return Err(SolSageError::ProposalRejected.into());
+    }
+    if now < proposal.executable_at {
+        return Err(SolSageError::ProposalTimelocked.into());
+    }
+
+    apply_parameter_change(program_id, &mut protocol, proposal.action, executor, account_info_iter)?;
+
+    proposal.executed = true;
+    proposal.store(proposal_account)?;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::ProposalExecuted {
+        proposal: *proposal_account.key,
+        action: proposal.action,
+    }
+    .emit()?;
+
+    msg!("Proposal {} executed", proposal.index);
+    Ok(())
+}
+
+fn process_withdraw_vote(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let voter = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter)?;
+    let vote_record_account = next_account_info(account_info_iter)?;
+    let destination = next_account_info(account_info_iter)?;
+    let governance_vault = next_account_info(account_info_iter)?;
+    let mint_authority = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    if !voter.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+    let proposal = load_proposal(program_id, proposal_account)?;
+
+    // Votes stay locked until they can no longer change the outcome
+    if Clock::get()?.unix_timestamp < proposal.voting_ends_at {
+        return Err(SolSageError::VotingOpen.into());
+    }
+
+    let vote_record = VoteRecord::load(program_id, vote_record_account)?;
+    let vote_record_pda = syscalls::create_program_address(
+        &[VoteRecord::SEED, proposal_account.key.as_ref(), voter.key.as_ref(), &[vote_record.bump]],
+        program_id,
+    )?;
+
+    if vote_record_pda != *vote_record_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_governance_vault(program_id, &protocol, governance_vault)?;
+
+    let mint_authority_pda = syscalls::create_program_address(
+        &[Protocol::MINT_AUTHORITY_SEED, &[protocol.mint_authority_bump]],
+        program_id,
+    )?;
+
+    if mint_authority_pda != *mint_authority.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            governance_vault.key,
+            destination.key,
+            mint_authority.key,
+            &[],
+            vote_record.weight,
+        )?,
+        &[
+            governance_vault.clone(),
+            destination.clone(),
+            mint_authority.clone(),
+            token_program.clone(),
+        ],
+        &[&[Protocol::MINT_AUTHORITY_SEED, &[protocol.mint_authority_bump]]],
+    )?;
+
+    vote_record_account.data.borrow_mut().fill(0);
+    let voter_lamports = voter
+        .lamports()
+        .checked_add(vote_record_account.lamports())
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    **vote_record_account.lamports.borrow_mut() = 0;
+    **voter.lamports.borrow_mut() = voter_lamports;
+
+    SolSageEvent::VoteWithdrawn {
+        proposal: *proposal_account.key,
+        voter: *voter.key,
+        weight: vote_record.weight,
+    }
+    .emit()?;
+
+    msg!("Vote withdrawn from proposal {}: {} SAGE", proposal.index, vote_record.weight);
+    Ok(())
+}
+
+/// Load a proposal, checking it is the PDA of its index
+fn load_proposal(program_id: &Pubkey, proposal_account: &AccountInfo) -> Result<Proposal, ProgramError> {
+    let proposal = Proposal::load(program_id, proposal_account)?;
+    let proposal_pda = syscalls::create_program_address(
+        &[Proposal::SEED, &proposal.index.to_le_bytes(), &[proposal.bump]],
+        program_id,
+    )?;
+
+    if proposal_pda != *proposal_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    Ok(proposal)
+}
+
+/// Check `governance_vault` is the protocol's governance vault PDA
+fn check_governance_vault(program_id: &Pubkey, protocol: &Protocol, governance_vault: &AccountInfo) -> ProgramResult {
+    let governance_vault_pda = syscalls::create_program_address(
+        &[Protocol::GOVERNANCE_VAULT_SEED, &[protocol.governance_vault_bump]],
+        program_id,
+    )?;
+
+    if governance_vault_pda != *governance_vault.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    Ok(())
+}
+
+fn process_queue_parameter_change(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    change: ProposalAction,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    change.validate()?;
+
+    let executable_at = Clock::get()?
+        .unix_timestamp
+        .checked_add(protocol.admin_delay_secs)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    protocol.queued_change = Some(change);
+    protocol.queued_change_executable_at = executable_at;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::ParameterChangeQueued { change, executable_at }.emit()?;
+
+    msg!("Parameter change queued until {}: {:?}", executable_at, change);
+    Ok(())
+}
+
+fn process_cancel_parameter_change(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    let change = protocol.queued_change.take().ok_or(SolSageError::NoQueuedChange)?;
+    protocol.queued_change_executable_at = 0;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::ParameterChangeCancelled { change }.emit()?;
+
+    msg!("Parameter change cancelled: {:?}", change);
+    Ok(())
+}
+
+fn process_execute_parameter_change(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let executor = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !executor.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    let change = protocol.queued_change.ok_or(SolSageError::NoQueuedChange)?;
+    if Clock::get()?.unix_timestamp < protocol.queued_change_executable_at {
+        return Err(SolSageError::ParameterChangeTimelocked.into());
+    }
+
+    protocol.queued_change = None;
+    protocol.queued_change_executable_at = 0;
+    apply_parameter_change(program_id, &mut protocol, change, executor, account_info_iter)?;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::ParameterChangeExecuted { change }.emit()?;
+
+    msg!("Parameter change executed: {:?}", change);
+    Ok(())
+}
+
+/// Apply a passed proposal's or a queued parameter change, taking the oracle
+/// accounts an oracle change needs from `account_info_iter`
+fn apply_parameter_change<'a, 'info>(
+    program_id: &Pubkey,
+    protocol: &mut Protocol,
+    change: ProposalAction,
+    executor: &'a AccountInfo<'info>,
+    account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'info>>,
+) -> ProgramResult {
+    match change {
+        ProposalAction::SetRewardRate { reward_per_attribution } => apply_reward_rate(protocol, reward_per_attribution),
+        ProposalAction::SetProtocolFee { fee_bps } => apply_protocol_fee(protocol, fee_bps),
+        ProposalAction::AddOracle { oracle } => {
+            let oracle_account = next_account_info(account_info_iter)?;
+            let system_program = next_account_info(account_info_iter)?;
+            check_system_program(system_program)?;
+            create_oracle_registration(program_id, executor, oracle_account, system_program, oracle)
+        }
+        ProposalAction::RemoveOracle { oracle } => {
+            let oracle_account = next_account_info(account_info_iter)?;
+            let (oracle_pda, _) =
+                syscalls::find_program_address(&[AttributionOracle::SEED, oracle.as_ref()], program_id);
+            if oracle_pda != *oracle_account.key {
+                return Err(SolSageError::InvalidPda.into());
+            }
+            close_oracle_registration(program_id, oracle_account, executor)
+        }
+        ProposalAction::SetAdminDelay { admin_delay_secs } => {
+            let old_delay_secs = protocol.admin_delay_secs;
+            protocol.admin_delay_secs = admin_delay_secs;
+
+            SolSageEvent::AdminDelayChanged { old_delay_secs, new_delay_secs: admin_delay_secs }.emit()?;
+
+            msg!("Admin delay changed: {}s -> {}s", old_delay_secs, admin_delay_secs);
+            Ok(())
+        }
+    }
+}
+
+fn process_set_score_decay(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    score_half_life_secs: i64,
+    min_score_weight_bps: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    let half_life_valid = score_half_life_secs == 0
+        || (Protocol::MIN_SCORE_HALF_LIFE_SECS..=Protocol::MAX_SCORE_HALF_LIFE_SECS).contains(&score_half_life_secs);
+    if !half_life_valid || min_score_weight_bps > Protocol::MAX_BPS {
+        return Err(SolSageError::InvalidScoreDecay.into());
+    }
+
+    protocol.score_half_life_secs = score_half_life_secs;
+    protocol.min_score_weight_bps = min_score_weight_bps;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::ScoreDecayChanged {
+        score_half_life_secs,
+        min_score_weight_bps,
+    }
+    .emit()?;
+
+    msg!("Score decay changed: half-life {}s, floor {} bps", score_half_life_secs, min_score_weight_bps);
+    Ok(())
+}
+
+fn process_set_receipt_tree(program_id: &Pubkey, accounts: &[AccountInfo], merkle_tree: Option<Pubkey>) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    protocol.receipt_tree = merkle_tree;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::ReceiptTreeChanged { merkle_tree }.emit()?;
+
+    msg!("Receipt tree changed: {:?}", merkle_tree);
+    Ok(())
+}
+
+fn process_set_mint_metadata(program_id: &Pubkey, accounts: &[AccountInfo], metadata: TokenMetadata) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let sage_mint = next_account_info(account_info_iter)?;
+    let mint_authority = next_account_info(account_info_iter)?;
+    let metadata_account = next_account_info(account_info_iter)?;
+    let token_metadata_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    if *token_metadata_program.key != token_metadata::PROGRAM_ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    if protocol.reward_mint == Pubkey::default() || protocol.reward_mint != *sage_mint.key {
+        return Err(SolSageError::InvalidRewardMint.into());
+    }
+
+    if !metadata.is_valid() {
+        return Err(SolSageError::MetadataTooLong.into());
+    }
+
+    let mint_authority_seeds: &[&[u8]] = &[Protocol::MINT_AUTHORITY_SEED, &[protocol.mint_authority_bump]];
+    let mint_authority_pda = syscalls::create_program_address(mint_authority_seeds, program_id)?;
+
+    if mint_authority_pda != *mint_authority.key || token_metadata::metadata_address(sage_mint.key) != *metadata_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    // The first call creates the metadata; later ones replace it
+    if metadata_account.data_is_empty() {
+        invoke_signed(
+            &token_metadata::create_metadata_account_v3(sage_mint.key, mint_authority.key, authority.key, &metadata),
+            &[
+                metadata_account.clone(),
+                sage_mint.clone(),
+                mint_authority.clone(),
+                authority.clone(),
+                system_program.clone(),
+                token_metadata_program.clone(),
+            ],
+            &[mint_authority_seeds],
+        )?;
+    } else {
+        invoke_signed(
+            &token_metadata::update_metadata_account_v2(sage_mint.key, mint_authority.key, &metadata),
+            &[metadata_account.clone(), mint_authority.clone(), token_metadata_program.clone()],
+            &[mint_authority_seeds],
+        )?;
+    }
+
+    SolSageEvent::MintMetadataSet {
+        mint: *sage_mint.key,
+        name: metadata.name.to_string(),
+        symbol: metadata.symbol.to_string(),
+        uri: metadata.uri.to_string(),
+    }
+    .emit()?;
+
+    msg!("SAGE metadata set: {} ({})", metadata.name, metadata.symbol);
+    Ok(())
+}
+
+fn process_set_sale_fee(program_id: &Pubkey, accounts: &[AccountInfo], sale_fee_bps: u16) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    protocol.check_no_admin_delay()?;
+
+    if sale_fee_bps > Protocol::MAX_FEE_BPS {
+        return Err(SolSageError::FeeTooHigh.into());
+    }
+
+    protocol.sale_fee_bps = sale_fee_bps;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::SaleFeeChanged { sale_fee_bps }.emit()?;
+
+    msg!("Sale fee changed: {} bps", sale_fee_bps);
+    Ok(())
+}
+
+fn process_set_oracle_budget(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    oracle: Pubkey,
+    max_attributions_per_epoch: u64,
+    max_rewards_per_epoch: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let oracle_state_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    protocol.check_no_admin_delay()?;
+
+    let rent = Rent::get()?;
+    let mut oracle_state =
+        load_or_create_oracle_state(program_id, authority, &oracle, oracle_state_account, system_program, &rent)?;
+    oracle_state.max_attributions_per_epoch = max_attributions_per_epoch;
+    oracle_state.max_rewards_per_epoch = max_rewards_per_epoch;
+    oracle_state.store(oracle_state_account)?;
+
+    SolSageEvent::OracleBudgetChanged { oracle, max_attributions_per_epoch, max_rewards_per_epoch }.emit()?;
+
+    msg!(
+        "Oracle budget changed: {} attributions, {} SAGE per epoch",
+        max_attributions_per_epoch,
+        max_rewards_per_epoch
+    );
+    Ok(())
+}
+
+fn process_set_consensus(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    consensus_threshold: u8,
+    consensus_tolerance: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    protocol.check_no_admin_delay()?;
+
+    if usize::from(consensus_threshold) > Protocol::MAX_CONSENSUS_ORACLES {
+        return Err(SolSageError::InvalidConsensusThreshold.into());
+    }
+
+    if consensus_threshold > 0 && protocol.aggregation_enabled() {
+        return Err(SolSageError::ConsensusWithAggregation.into());
+    }
+
+    protocol.consensus_threshold = consensus_threshold;
+    protocol.consensus_tolerance = consensus_tolerance;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::ConsensusChanged { consensus_threshold, consensus_tolerance }.emit()?;
+
+    msg!("Consensus changed: {} oracles, tolerance {}", consensus_threshold, consensus_tolerance);
+    Ok(())
+}
+
+fn process_set_aggregation_window(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    aggregation_window_secs: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    protocol.check_no_admin_delay()?;
+
+    if !(0..=Protocol::MAX_AGGREGATION_WINDOW_SECS).contains(&aggregation_window_secs) {
+        return Err(SolSageError::InvalidAggregationWindow.into());
+    }
+
+    if aggregation_window_secs > 0 && protocol.consensus_enabled() {
+        return Err(SolSageError::ConsensusWithAggregation.into());
+    }
+
+    protocol.aggregation_window_secs = aggregation_window_secs;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::AggregationWindowChanged { aggregation_window_secs }.emit()?;
+
+    msg!("Aggregation window changed: {}s", aggregation_window_secs);
+    Ok(())
+}
+
+fn process_set_payer_attribution_cap(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_payer_attributions_per_day: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    protocol.check_no_admin_delay()?;
+
+    protocol.max_payer_attributions_per_day = max_payer_attributions_per_day;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::PayerAttributionCapChanged { max_payer_attributions_per_day }.emit()?;
+
+    msg!("Payer attribution cap changed: {} a day", max_payer_attributions_per_day);
+    Ok(())
+}
+
+fn process_set_reward_stream(program_id: &Pubkey, accounts: &[AccountInfo], reward_stream_secs: i64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    protocol.check_no_admin_delay()?;
+
+    if reward_stream_secs != 0
+        && !(Protocol::MIN_REWARD_STREAM_SECS..=Protocol::MAX_REWARD_STREAM_SECS).contains(&reward_stream_secs)
+    {
+        return Err(SolSageError::InvalidRewardStream.into());
+    }
+
+    protocol.reward_stream_secs = reward_stream_secs;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::RewardStreamChanged { reward_stream_secs }.emit()?;
+
+    msg!("Reward stream changed: {}s", reward_stream_secs);
+    Ok(())
+}
+
+fn process_set_fee_config(program_id: &Pubkey, accounts: &[AccountInfo], fee_config: FeeConfig) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    protocol.check_no_admin_delay()?;
+
+    if !fee_config.is_valid() {
+        return Err(SolSageError::InvalidFeeConfig.into());
+    }
+
+    protocol.fee_config = fee_config;
+    protocol.curator_bps = fee_config.curator_bps;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::FeeConfigChanged {
+        treasury_bps: fee_config.treasury_bps,
+        curator_bps: fee_config.curator_bps,
+        burn_bps: fee_config.burn_bps,
+    }
+    .emit()?;
+
+    msg!(
+        "Fee config changed: treasury {} bps, curator {} bps, burn {} bps",
+        fee_config.treasury_bps,
+        fee_config.curator_bps,
+        fee_config.burn_bps
+    );
+    Ok(())
+}
+
+fn process_claim_treasury_rewards(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let mint_accounts = RewardMintAccounts {
+        sage_mint: next_account_info(account_info_iter)?,
+        destination: next_account_info(account_info_iter)?,
+        mint_authority: next_account_info(account_info_iter)?,
+        token_program: next_account_info(account_info_iter)?,
+    };
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+    protocol.check_not_paused()?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    let amount = protocol.treasury_rewards;
+    if amount == 0 {
+        return Err(SolSageError::NoRewardsToClaim.into());
+    }
+
+    mint_rewards(program_id, &protocol, authority.key, &mint_accounts, amount)?;
+
+    protocol.treasury_rewards = 0;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::TreasuryRewardsClaimed {
+        destination: *mint_accounts.destination.key,
+        amount,
+    }
+    .emit()?;
+
+    msg!("Treasury claimed {} SAGE tokens", amount);
+    Ok(())
+}
+
+fn process_set_low_relevance_burn(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    low_relevance_threshold: u8,
+    low_relevance_burn_bps: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    protocol.check_no_admin_delay()?;
+
+    if low_relevance_threshold > solsage_core::MAX_RELEVANCE_SCORE || low_relevance_burn_bps > Protocol::MAX_BPS {
+        return Err(SolSageError::InvalidLowRelevanceBurn.into());
+    }
+
+    protocol.low_relevance_threshold = low_relevance_threshold;
+    protocol.low_relevance_burn_bps = low_relevance_burn_bps;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::LowRelevanceBurnChanged { low_relevance_threshold, low_relevance_burn_bps }.emit()?;
+
+    msg!("Low-relevance burn changed: below {} burns {} bps", low_relevance_threshold, low_relevance_burn_bps);
+    Ok(())
+}
+
+fn process_set_citation_royalty(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    citation_royalty_bps: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    protocol.check_no_admin_delay()?;
+
+    if citation_royalty_bps > Protocol::MAX_CITATION_ROYALTY_BPS {
+        return Err(SolSageError::InvalidCitationRoyalty.into());
+    }
+
+    protocol.citation_royalty_bps = citation_royalty_bps;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::CitationRoyaltyChanged { citation_royalty_bps }.emit()?;
+
+    msg!("Citation royalty changed to {} bps", citation_royalty_bps);
+    Ok(())
+}
+
+fn process_pay_citation_royalties(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let knowledge_account = next_account_info(account_info_iter)?;
+
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+    if knowledge.citation_royalties == 0 {
+        return Err(SolSageError::NoCitationRoyalties.into());
+    }
+    let shares = knowledge.take_citation_royalties();
+    knowledge.store(knowledge_account)?;
+
+    for (parent, share) in knowledge.derived_from.iter().zip(shares) {
+        let parent_account = next_account_info(account_info_iter)?;
+        if parent_account.key != parent {
+            return Err(SolSageError::InvalidParentEntries.into());
+        }
+
+        // A closed parent's share is forfeited
+        if parent_account.data_is_empty() || parent_account.lamports() == 0 {
+            msg!("Parent {} is closed, forfeiting its {} SAGE", parent, share);
+            continue;
+        }
+
+        let mut parent_entry = KnowledgeEntry::load(program_id, parent_account)?;
+        parent_entry.receive_citation_royalty(share)?;
+        parent_entry.store(parent_account)?;
+
+        SolSageEvent::CitationRoyaltyPaid {
+            knowledge_entry: *knowledge_account.key,
+            parent: *parent,
+            amount: share,
+        }
+        .emit()?;
+    }
+
+    msg!("Paid SAGE royalties to {} parents", knowledge.derived_from.len());
+    Ok(())
+}
+
+fn process_stake_document(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    content_hash: [u8; 32],
+    title: String,
+    chunk_count: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let owner = next_account_info(account_info_iter)?;
+    let document_account = next_account_info(account_info_iter)?;
+    let category_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !owner.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    if title.len() > Document::MAX_TITLE_LEN {
+        return Err(SolSageError::TitleTooLong.into());
+    }
+
+    if chunk_count == 0 || chunk_count as usize > Document::MAX_CHUNKS {
+        return Err(SolSageError::InvalidChunkCount.into());
+    }
+
+    load_category(program_id, category_account)?;
+
+    // Derive document PDA
+    let (document_pda, bump) =
+        syscalls::find_program_address(&[Document::SEED, owner.key.as_ref(), &content_hash], program_id);
+
+    if document_pda != *document_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(document_account)?;
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            owner.key,
+            document_account.key,
+            rent.minimum_balance(Document::LEN),
+            Document::LEN as u64,
+            program_id,
+        ),
+        &[owner.clone(), document_account.clone(), system_program.clone()],
+        &[&[Document::SEED, owner.key.as_ref(), &content_hash, &[bump]]],
+    )?;
+
+    let document = Document {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        owner: *owner.key,
+        content_hash,
+        title,
+        category: *category_account.key,
+        chunks: vec![Pubkey::default(); chunk_count as usize],
+        total_attributions: 0,
+        pending_rewards: 0,
+        refreshed_at: 0,
+        created_at: Clock::get()?.unix_timestamp,
+        bump,
+    };
+    document.store(document_account)?;
+
+    SolSageEvent::DocumentStaked {
+        document: *document_account.key,
+        owner: *owner.key,
+        content_hash,
+        chunk_count,
+    }
+    .emit()?;
+
+    msg!("Document staked: {} ({} chunks)", document.title, chunk_count);
+    Ok(())
+}
+
+fn process_add_document_chunk(program_id: &Pubkey, accounts: &[AccountInfo], chunk_index: u16) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let owner = next_account_info(account_info_iter)?;
+    let document_account = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+
+    if !owner.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut document = load_document(program_id, document_account)?;
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if document.owner != *owner.key {
+        return Err(SolSageError::NotDocumentOwner.into());
+    }
+
+    if knowledge.staker != *owner.key {
+        return Err(SolSageError::NotKnowledgeOwner.into());
+    }
+
+    if knowledge.document.is_some() {
+        return Err(SolSageError::EntryInDocument.into());
+    }
+
+    if knowledge.category != document.category {
+        return Err(SolSageError::CategoryMismatch.into());
+    }
+
+    match document.chunks.get_mut(chunk_index as usize) {
+        Some(chunk) if *chunk == Pubkey::default() => *chunk = *knowledge_account.key,
+        _ => return Err(SolSageError::InvalidChunkIndex.into()),
+    }
+    document.store(document_account)?;
+
+    knowledge.document = Some(*document_account.key);
+    knowledge.chunk_index = chunk_index;
+    knowledge.store(knowledge_account)?;
+
+    SolSageEvent::DocumentChunkAdded {
+        document: *document_account.key,
+        knowledge_entry: *knowledge_account.key,
+        chunk_index,
+    }
+    .emit()?;
+
+    msg!("Chunk {} added to document {}", chunk_index, document.title);
+    Ok(())
+}
+
+fn process_refresh_document(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let document_account = next_account_info(account_info_iter)?;
+
+    let mut document = load_document(program_id, document_account)?;
+    let (mut total_attributions, mut pending_rewards) = (0u64, 0u64);
+    for chunk in document.added_chunks() {
+        let knowledge_account = next_account_info(account_info_iter)?;
+        if knowledge_account.key != chunk {
+            return Err(SolSageError::DocumentMismatch.into());
+        }
+
+        let knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+        total_attributions = total_attributions
+            .checked_add(knowledge.total_attributions)
+            .ok_or(SolSageError::ArithmeticOverflow)?;
+        pending_rewards = pending_rewards
+            .checked_add(knowledge.pending_rewards)
+            .ok_or(SolSageError::ArithmeticOverflow)?;
+    }
+
+    document.total_attributions = total_attributions;
+    document.pending_rewards = pending_rewards;
+    document.refreshed_at = Clock::get()?.unix_timestamp;
+    document.store(document_account)?;
+
+    SolSageEvent::DocumentRefreshed { document: *document_account.key, total_attributions, pending_rewards }.emit()?;
+
+    msg!("Document refreshed: {} attributions", total_attributions);
+    Ok(())
+}
+
+fn process_burn_rewards(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let protocol_account = next_account_info(account_info_iter)?;
+    let reward_vault = next_account_info(account_info_iter)?;
+    let sage_mint = next_account_info(account_info_iter)?;
+    let mint_authority = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Until payers fund rewards there's no vault to burn from
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+    let amount = protocol.pending_burn;
+    if amount == 0 {
+        return Err(SolSageError::NothingToBurn.into());
+    }
+
+    check_reward_vault(program_id, &protocol, reward_vault)?;
+
+    if protocol.reward_mint != *sage_mint.key {
+        return Err(SolSageError::InvalidRewardMint.into());
+    }
+
+    let mint_authority_pda = syscalls::create_program_address(
+        &[Protocol::MINT_AUTHORITY_SEED, &[protocol.mint_authority_bump]],
+        program_id,
+    )?;
+    if mint_authority_pda != *mint_authority.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    // The vault is held by the mint authority, which signs the burn
+    invoke_signed(
+        &spl_token::instruction::burn(
+            token_program.key,
+            reward_vault.key,
+            sage_mint.key,
+            mint_authority.key,
+            &[],
+            amount,
+        )?,
+        &[reward_vault.clone(), sage_mint.clone(), mint_authority.clone(), token_program.clone()],
+        &[&[Protocol::MINT_AUTHORITY_SEED, &[protocol.mint_authority_bump]]],
+    )?;
+
+    protocol.pending_burn = 0;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::RewardsBurned { amount }.emit()?;
+
+    msg!("Burned {} SAGE tokens", amount);
+    Ok(())
+}
+
+fn process_finalize_attribution(program_id: &Pubkey, accounts: &[AccountInfo], query_hash: [u8; 32]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let attribution_account = next_account_info(account_info_iter)?;
+    let query_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let profile_account = next_account_info(account_info_iter)?;
+    let treasury_account = next_account_info(account_info_iter)?;
+    let category_account = next_account_info(account_info_iter)?;
+    let leaderboard_account = next_account_info(account_info_iter)?;
+
+    if !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+    protocol.check_not_paused()?;
+
+    // Attributions awaiting consensus finalize on their last matching report
+    if protocol.consensus_enabled() {
+        return Err(SolSageError::AttributionPending.into());
+    }
+
+    let attribution = Attribution::load(program_id, attribution_account)?;
+    if attribution.query_hash != query_hash || attribution.knowledge_entry != *knowledge_account.key {
+        return Err(SolSageError::AttributionMismatch.into());
+    }
+
+    if !attribution.pending {
+        return Err(SolSageError::AlreadyInitialized.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    if !attribution.aggregation_closed(&protocol, now) {
+        return Err(SolSageError::AggregationWindowOpen.into());
+    }
+
+    let epoch_account = if protocol.epochs_enabled() {
+        Some(next_account_info(account_info_iter)?)
+    } else {
+        None
+    };
+    let agent_stats_account = match attribution.agent_id {
+        Some(_) => Some(next_account_info(account_info_iter)?),
+        None => None,
+    };
+    let (collection_accounts, funding) = match protocol.reward_source {
+        RewardSource::Payer => {
+            let (collection_accounts, funding) = RewardFunding::split_last(program_id, account_info_iter.as_slice())?;
+            (collection_accounts, Some(funding))
+        }
+        RewardSource::Mint => (account_info_iter.as_slice(), None),
+    };
+
+    let mut epoch = load_open_epoch(program_id, &protocol, epoch_account, now)?;
+    // The query record answers to the first oracle to report
+    let oracle = attribution.votes.first().map_or(*payer.key, |vote| vote.oracle);
+    let ctx = AttributionContext {
+        program_id,
+        payer,
+        oracle,
+        system_program,
+        protocol: &protocol,
+        query_hash,
+        epoch: epoch.as_ref().map(|epoch| epoch.index),
+        fees: protocol.attribution_fees(false, epoch.is_some()),
+        subscription_funded: matches!(funding, Some(RewardFunding::Subscription(_))),
+        metadata: AttributionMetadata { agent_id: attribution.agent_id, model_id: attribution.model_id, chunk: None },
+        rent: Rent::get()?,
+        now,
+    };
+
+    reserve_query_attributions(&ctx, query_account, 1)?;
+    let (shares, entry_attributions) = record_attribution_to(
+        &ctx,
+        knowledge_account,
+        attribution_account,
+        profile_account,
+        category_account,
+        attribution.average_score(),
+        &mut collection_accounts.iter(),
+    )?;
+    load_or_create_leaderboard(program_id, payer, leaderboard_account, system_program)?
+        .record(*knowledge_account.key, entry_attributions);
+
+    let reward = shares.entry;
+    let total_reward = shares.total().ok_or(SolSageError::ArithmeticOverflow)?;
+    let fee = charge_attribution_fee(&ctx, treasury_account, total_reward)?;
+    if let Some(funding) = &funding {
+        fund_attribution_rewards(&ctx, funding, total_reward)?;
+    }
+
+    if let (Some(epoch), Some(epoch_account)) = (epoch.as_mut(), epoch_account) {
+        epoch.add_weight(reward)?;
+        epoch.store(epoch_account)?;
+    }
+
+    if let (Some(agent_id), Some(agent_stats_account)) = (attribution.agent_id, agent_stats_account) {
+        let mut agent_stats = load_or_create_agent_stats(&ctx, &agent_id, agent_stats_account)?;
+        agent_stats.record_attribution(total_reward, now)?;
+        agent_stats.store(agent_stats_account)?;
+    }
+
+    protocol.total_attributions = protocol
+        .total_attributions
+        .checked_add(1)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    protocol.record_reward_shares(&shares)?;
+    protocol.fees_collected = protocol.fees_collected.checked_add(fee).ok_or(SolSageError::ArithmeticOverflow)?;
+    protocol.store(protocol_account)?;
+
+    msg!("Attribution finalized, reward: {}", reward);
+    Ok(())
+}
+
+fn process_vote_on_entry(program_id: &Pubkey, accounts: &[AccountInfo], upvote: bool, weight: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let voter = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let entry_vote_account = next_account_info(account_info_iter)?;
+    let voter_token_account = next_account_info(account_info_iter)?;
+    let governance_vault = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !voter.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+
+    // The SAGE is held in the governance vault, which exists once
+    // governance is enabled
+    if !protocol.governance_enabled() {
+        return Err(SolSageError::GovernanceDisabled.into());
+    }
+
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+    if !knowledge.is_active {
+        return Err(SolSageError::KnowledgeInactive.into());
+    }
+
+    if weight == 0 {
+        return Err(SolSageError::ZeroDeposit.into());
+    }
+
+    check_governance_vault(program_id, &protocol, governance_vault)?;
+
+    // One vote per voter and entry; a second would find the record already
+    // created
+    let (entry_vote_pda, bump) = syscalls::find_program_address(
+        &[EntryVote::SEED, knowledge_account.key.as_ref(), voter.key.as_ref()],
+        program_id,
+    );
+
+    if entry_vote_pda != *entry_vote_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(entry_vote_account)?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            voter.key,
+            entry_vote_account.key,
+            Rent::get()?.minimum_balance(EntryVote::LEN),
+            EntryVote::LEN as u64,
+            program_id,
+        ),
+        &[voter.clone(), entry_vote_account.clone(), system_program.clone()],
+        &[&[EntryVote::SEED, knowledge_account.key.as_ref(), voter.key.as_ref(), &[bump]]],
+    )?;
+
+    // Locking the SAGE keeps it from voting again from another wallet
+    invoke(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            voter_token_account.key,
+            governance_vault.key,
+            voter.key,
+            &[],
+            weight,
+        )?,
+        &[
+            voter_token_account.clone(),
+            governance_vault.clone(),
+            voter.clone(),
+            token_program.clone(),
+        ],
+    )?;
+
+    knowledge.record_quality_vote(upvote, weight)?;
+    knowledge.store(knowledge_account)?;
+
+    let entry_vote = EntryVote {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        knowledge_entry: *knowledge_account.key,
+        voter: *voter.key,
+        upvote,
+        weight,
+        bump,
+    };
+    entry_vote.store(entry_vote_account)?;
+
+    SolSageEvent::EntryVoted {
+        knowledge_entry: *knowledge_account.key,
+        voter: *voter.key,
+        upvote,
+        weight,
+        quality_score: knowledge.quality_score,
+    }
+    .emit()?;
+
+    msg!("Entry {} voted {}: {} SAGE", knowledge_account.key, if upvote { "up" } else { "down" }, weight);
+    Ok(())
+}
+
+fn process_withdraw_entry_vote(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let voter = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let entry_vote_account = next_account_info(account_info_iter)?;
+    let destination = next_account_info(account_info_iter)?;
+    let governance_vault = next_account_info(account_info_iter)?;
+    let mint_authority = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    if !voter.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+
+    let entry_vote = EntryVote::load(program_id, entry_vote_account)?;
+    let entry_vote_pda = syscalls::create_program_address(
+        &[EntryVote::SEED, knowledge_account.key.as_ref(), voter.key.as_ref(), &[entry_vote.bump]],
+        program_id,
+    )?;
+
+    if entry_vote_pda != *entry_vote_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_governance_vault(program_id, &protocol, governance_vault)?;
+
+    let mint_authority_pda = syscalls::create_program_address(
+        &[Protocol::MINT_AUTHORITY_SEED, &[protocol.mint_authority_bump]],
+        program_id,
+    )?;
+
+    if mint_authority_pda != *mint_authority.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    // An entry closed since the vote has no score left to take it from
+    if !knowledge_account.data_is_empty() {
+        let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+        knowledge.record_quality_vote(!entry_vote.upvote, entry_vote.weight)?;
+        knowledge.store(knowledge_account)?;
+    }
+
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            governance_vault.key,
+            destination.key,
+            mint_authority.key,
+            &[],
+            entry_vote.weight,
+        )?,
+        &[
+            governance_vault.clone(),
+            destination.clone(),
+            mint_authority.clone(),
+            token_program.clone(),
+        ],
+        &[&[Protocol::MINT_AUTHORITY_SEED, &[protocol.mint_authority_bump]]],
+    )?;
+
+    entry_vote_account.data.borrow_mut().fill(0);
+    let voter_lamports = voter
+        .lamports()
+        .checked_add(entry_vote_account.lamports())
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    **entry_vote_account.lamports.borrow_mut() = 0;
+    **voter.lamports.borrow_mut() = voter_lamports;
+
+    SolSageEvent::EntryVoteWithdrawn {
+        knowledge_entry: *knowledge_account.key,
+        voter: *voter.key,
+        weight: entry_vote.weight,
+    }
+    .emit()?;
+
+    msg!("Entry vote withdrawn: {} SAGE", entry_vote.weight);
+    Ok(())
+}
+
+fn process_report_entry(program_id: &Pubkey, accounts: &[AccountInfo], bond: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let reporter = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let queue_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !reporter.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+    if !knowledge.is_active {
+        return Err(SolSageError::KnowledgeInactive.into());
+    }
+
+    if bond < ReportQueue::MIN_BOND {
+        return Err(SolSageError::ReportBondTooLow.into());
+    }
+
+    let (queue_pda, bump) = syscalls::find_program_address(
+        &[ReportQueue::SEED, knowledge_account.key.as_ref()],
+        program_id,
+    );
+
+    if queue_pda != *queue_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    let mut queue = if queue_account.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                reporter.key,
+                queue_account.key,
+                Rent::get()?.minimum_balance(ReportQueue::LEN),
+                ReportQueue::LEN as u64,
+                program_id,
+            ),
+            &[reporter.clone(), queue_account.clone(), system_program.clone()],
+            &[&[ReportQueue::SEED, knowledge_account.key.as_ref(), &[bump]]],
+        )?;
+
+        ReportQueue {
+            is_initialized: true,
+            schema_version: SCHEMA_VERSION,
+            knowledge_entry: *knowledge_account.key,
+            reports: Vec::new(),
+            flagged_at: 0,
+            bump,
+        }
+    } else {
+        ReportQueue::load(program_id, queue_account)?
+    };
+
+    // The queue holds the bond on top of its own rent
+    invoke(
+        &system_instruction::transfer(reporter.key, queue_account.key, bond),
+        &[reporter.clone(), queue_account.clone(), system_program.clone()],
+    )?;
+
+    let flagged = queue.record_report(*reporter.key, bond)?;
+    let reports = queue.reports.len() as u8;
+
+    if flagged {
+        queue.flagged_at = Clock::get()?.unix_timestamp;
+        knowledge.is_active = false;
+        knowledge.under_review = true;
+        knowledge.store(knowledge_account)?;
+
+        SolSageEvent::EntryFlagged {
+            knowledge_entry: *knowledge_account.key,
+            reports,
+        }
+        .emit()?;
+    }
+    queue.store(queue_account)?;
+
+    SolSageEvent::EntryReported {
+        knowledge_entry: *knowledge_account.key,
+        reporter: *reporter.key,
+        bond,
+        reports,
+    }
+    .emit()?;
+
+    msg!("Entry reported as spam with {} lamport bond ({} reports)", bond, reports);
+    Ok(())
+}
+
+fn process_resolve_reports(program_id: &Pubkey, accounts: &[AccountInfo], uphold: bool) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let knowledge_account = next_account_info(account_info_iter)?;
+    let queue_account = next_account_info(account_info_iter)?;
+    let staker = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    let queue = ReportQueue::load(program_id, queue_account)?;
+    let queue_pda = syscalls::create_program_address(
+        &[ReportQueue::SEED, knowledge_account.key.as_ref(), &[queue.bump]],
+        program_id,
+    )?;
+
+    if queue_pda != *queue_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    let mut knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+
+    if queue.knowledge_entry != *knowledge_account.key || knowledge.staker != *staker.key {
+        return Err(SolSageError::ReportMismatch.into());
+    }
+
+    let reporters = queue
+        .reports
+        .iter()
+        .map(|report| {
+            let reporter = next_account_info(account_info_iter)?;
+            if *reporter.key != report.reporter {
+                return Err(SolSageError::ReportMismatch.into());
+            }
+            Ok(reporter)
+        })
+        .collect::<Result<Vec<_>, ProgramError>>()?;
+
+    let bonds = queue.total_bonds()?;
+    let rent = queue_account
+        .lamports()
+        .checked_sub(bonds)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+
+    if uphold {
+        knowledge.is_active = false;
+        msg!("Spam reports upheld, {} lamports in bonds refunded", bonds);
+    } else {
+        // Only an entry the reports deactivated comes back
+        if knowledge.under_review {
+            knowledge.is_active = true;
+        }
+        msg!("Spam reports rejected, {} lamports in bonds forfeited", bonds);
+    }
+    knowledge.under_review = false;
+    knowledge.store(knowledge_account)?;
+
+    queue_account.data.borrow_mut().fill(0);
+    **queue_account.lamports.borrow_mut() = 0;
+
+    // Credit one at a time in case a reporter is also the entry owner
+    let credit = |account: &AccountInfo, lamports: u64| -> ProgramResult {
+        let balance = account.lamports().checked_add(lamports).ok_or(SolSageError::ArithmeticOverflow)?;
+        **account.lamports.borrow_mut() = balance;
+        Ok(())
+    };
+    if uphold {
+        for (reporter, report) in reporters.iter().zip(&queue.reports) {
+            credit(reporter, report.bond)?;
+        }
+    } else {
+        credit(staker, bonds)?;
+    }
+    // The first reporter paid the queue's rent
+    let first_reporter = reporters.first().ok_or(SolSageError::ReportMismatch)?;
+    credit(first_reporter, rent)?;
+
+    SolSageEvent::ReportsResolved {
+        knowledge_entry: *knowledge_account.key,
+        upheld: uphold,
+        bonds,
+    }
+    .emit()?;
+
+    Ok(())
+}
+
+fn process_create_bounty(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    query_hash: [u8; 32],
+    reward: u64,
+    deadline: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let requester = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let bounty_account = next_account_info(account_info_iter)?;
+    let bounty_vault = next_account_info(account_info_iter)?;
+    let requester_token_account = next_account_info(account_info_iter)?;
+    let sage_mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !requester.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_system_program(system_program)?;
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.reward_mint == Pubkey::default() || protocol.reward_mint != *sage_mint.key {
+        return Err(SolSageError::InvalidRewardMint.into());
+    }
+
+    if reward == 0 {
+        return Err(SolSageError::ZeroDeposit.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    if deadline <= now || deadline > now.saturating_add(Bounty::MAX_DURATION_SECS) {
+        return Err(SolSageError::InvalidBountyDeadline.into());
+    }
+
+    let (bounty_pda, bump) = syscalls::find_program_address(
+        &[Bounty::SEED, requester.key.as_ref(), &query_hash],
+        program_id,
+    );
+
+    if bounty_pda != *bounty_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    let (bounty_vault_pda, vault_bump) =
+        syscalls::find_program_address(&[Bounty::VAULT_SEED, bounty_account.key.as_ref()], program_id);
+
+    if bounty_vault_pda != *bounty_vault.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    check_uninitialized(bounty_account)?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            requester.key,
+            bounty_account.key,
+            Rent::get()?.minimum_balance(Bounty::LEN),
+            Bounty::LEN as u64,
+            program_id,
+        ),
+        &[requester.clone(), bounty_account.clone(), system_program.clone()],
+        &[&[Bounty::SEED, requester.key.as_ref(), &query_hash, &[bump]]],
     )?;
 
-    // Initialize data
-    let protocol = Protocol {
+    // Held by the mint authority, which signs the payout or refund
+    let mint_authority = syscalls::create_program_address(
+        &[Protocol::MINT_AUTHORITY_SEED, &[protocol.mint_authority_bump]],
+        program_id,
+    )?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            requester.key,
+            bounty_vault.key,
+            Rent::get()?.minimum_balance(spl_token::state::Account::LEN),
+            spl_token::state::Account::LEN as u64,
+            token_program.key,
+        ),
+        &[requester.clone(), bounty_vault.clone(), system_program.clone()],
+        &[&[Bounty::VAULT_SEED, bounty_account.key.as_ref(), &[vault_bump]]],
+    )?;
+
+    invoke(
+        &spl_token::instruction::initialize_account3(token_program.key, bounty_vault.key, sage_mint.key, &mint_authority)?,
+        &[bounty_vault.clone(), sage_mint.clone(), token_program.clone()],
+    )?;
+
+    invoke(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            requester_token_account.key,
+            bounty_vault.key,
+            requester.key,
+            &[],
+            reward,
+        )?,
+        &[
+            requester_token_account.clone(),
+            bounty_vault.clone(),
+            requester.clone(),
+            token_program.clone(),
+        ],
+    )?;
+
+    let bounty = Bounty {
         is_initialized: true,
-        authority: *authority.key,
-        total_knowledge_entries: 0,
-        total_attributions: 0,
-        reward_per_attribution: 1_000_000, // 1 SAGE (6 decimals)
+        schema_version: SCHEMA_VERSION,
+        requester: *requester.key,
+        query_hash,
+        reward,
+        deadline,
+        created_at: now,
+        answers: 0,
+        accepted_entry: None,
         bump,
+        vault_bump,
     };
+    bounty.store(bounty_account)?;
 
-    protocol.serialize(&mut &mut protocol_account.data.borrow_mut()[..])?;
-    
-    msg!("SolSage Protocol initialized!");
+    SolSageEvent::BountyCreated {
+        bounty: *bounty_account.key,
+        requester: *requester.key,
+        query_hash,
+        reward,
+        deadline,
+    }
+    .emit()?;
+
+    msg!("Bounty created: {} SAGE until {}", reward, deadline);
     Ok(())
 }
 
-fn process_stake_knowledge(
-    program_id: &Pubkey,
-    accounts: &[AccountInfo],
-    content_hash: [u8; 32],
-    title: String,
-    category: String,
-) -> ProgramResult {
+fn process_submit_answer(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let staker = next_account_info(account_info_iter)?;
-    let protocol_account = next_account_info(account_info_iter)?;
+    let bounty_account = next_account_info(account_info_iter)?;
     let knowledge_account = next_account_info(account_info_iter)?;
+    let answer_account = next_account_info(account_info_iter)?;
     let system_program = next_account_info(account_info_iter)?;
 
     if !staker.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    if title.len() > 100 {
-        return Err(SolSageError::TitleTooLong.into());
+    check_system_program(system_program)?;
+
+    let mut bounty = Bounty::load(program_id, bounty_account)?;
+    if !bounty.is_open() {
+        return Err(SolSageError::BountyClosed.into());
     }
-    if category.len() > 50 {
-        return Err(SolSageError::CategoryTooLong.into());
+
+    let now = Clock::get()?.unix_timestamp;
+    if now >= bounty.deadline {
+        return Err(SolSageError::BountyExpired.into());
+    }
+
+    let knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+    if knowledge.staker != *staker.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+    if !knowledge.is_active {
+        return Err(SolSageError::KnowledgeInactive.into());
     }
 
-    // Derive knowledge PDA
-    let (knowledge_pda, bump) = Pubkey::find_program_address(
-        &[KnowledgeEntry::SEED, staker.key.as_ref(), &content_hash],
+    // One answer per entry and bounty; a second would find the record
+    // already created
+    let (answer_pda, bump) = syscalls::find_program_address(
+        &[BountyAnswer::SEED, bounty_account.key.as_ref(), knowledge_account.key.as_ref()],
         program_id,
     );
 
-    if knowledge_pda != *knowledge_account.key {
+    if answer_pda != *answer_account.key {
         return Err(SolSageError::InvalidPda.into());
     }
 
-    // Create knowledge account
-    let rent = Rent::get()?;
-    let lamports = rent.minimum_balance(KnowledgeEntry::LEN);
-    
+    check_uninitialized(answer_account)?;
+
     invoke_signed(
         &system_instruction::create_account(
             staker.key,
-            knowledge_account.key,
-            lamports,
-            KnowledgeEntry::LEN as u64,
+            answer_account.key,
+            Rent::get()?.minimum_balance(BountyAnswer::LEN),
+            BountyAnswer::LEN as u64,
             program_id,
         ),
-        &[staker.clone(), knowledge_account.clone(), system_program.clone()],
-        &[&[KnowledgeEntry::SEED, staker.key.as_ref(), &content_hash, &[bump]]],
+        &[staker.clone(), answer_account.clone(), system_program.clone()],
+        &[&[BountyAnswer::SEED, bounty_account.key.as_ref(), knowledge_account.key.as_ref(), &[bump]]],
     )?;
 
-    // Initialize knowledge entry
-    let clock = Clock::get()?;
-    let knowledge = KnowledgeEntry {
+    let answer = BountyAnswer {
         is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        bounty: *bounty_account.key,
+        knowledge_entry: *knowledge_account.key,
         staker: *staker.key,
-        content_hash,
-        title: title.clone(),
-        category,
-        created_at: clock.unix_timestamp,
-        total_attributions: 0,
-        pending_rewards: 0,
-        is_active: true,
+        submitted_at: now,
         bump,
     };
+    answer.store(answer_account)?;
 
-    knowledge.serialize(&mut &mut knowledge_account.data.borrow_mut()[..])?;
+    bounty.answers = bounty.answers.checked_add(1).ok_or(SolSageError::ArithmeticOverflow)?;
+    bounty.store(bounty_account)?;
 
-    // Update protocol
-    let mut protocol = Protocol::try_from_slice(&protocol_account.data.borrow())?;
-    protocol.total_knowledge_entries += 1;
-    protocol.serialize(&mut &mut protocol_account.data.borrow_mut()[..])?;
+    SolSageEvent::AnswerSubmitted {
+        bounty: *bounty_account.key,
+        knowledge_entry: *knowledge_account.key,
+        staker: *staker.key,
+    }
+    .emit()?;
 
-    msg!("Knowledge staked: {}", title);
+    msg!("Answer submitted to bounty {}", bounty_account.key);
     Ok(())
 }
 
-fn process_record_attribution(
+/// Load an open bounty of `requester`'s and check `bounty_vault` is its vault
+fn load_open_bounty(
     program_id: &Pubkey,
-    accounts: &[AccountInfo],
-    query_hash: [u8; 32],
-    relevance_score: u8,
+    requester: &AccountInfo,
+    bounty_account: &AccountInfo,
+    bounty_vault: &AccountInfo,
+) -> Result<Bounty, ProgramError> {
+    if !requester.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let bounty = Bounty::load(program_id, bounty_account)?;
+    let bounty_pda = syscalls::create_program_address(
+        &[Bounty::SEED, bounty.requester.as_ref(), &bounty.query_hash, &[bounty.bump]],
+        program_id,
+    )?;
+
+    if bounty_pda != *bounty_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    if bounty.requester != *requester.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    if !bounty.is_open() {
+        return Err(SolSageError::BountyClosed.into());
+    }
+
+    let bounty_vault_pda = syscalls::create_program_address(
+        &[Bounty::VAULT_SEED, bounty_account.key.as_ref(), &[bounty.vault_bump]],
+        program_id,
+    )?;
+
+    if bounty_vault_pda != *bounty_vault.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    Ok(bounty)
+}
+
+/// Accounts a bounty's escrowed reward is released through
+struct BountyReleaseAccounts<'a, 'info> {
+    requester: &'a AccountInfo<'info>,
+    bounty_vault: &'a AccountInfo<'info>,
+    destination: &'a AccountInfo<'info>,
+    mint_authority: &'a AccountInfo<'info>,
+    token_program: &'a AccountInfo<'info>,
+}
+
+/// Pay a bounty's escrowed reward into `destination` and close its vault,
+/// returning the vault's rent to `requester`
+fn release_bounty(
+    program_id: &Pubkey,
+    protocol: &Protocol,
+    bounty: &Bounty,
+    accounts: BountyReleaseAccounts,
 ) -> ProgramResult {
+    let BountyReleaseAccounts { requester, bounty_vault, destination, mint_authority, token_program } = accounts;
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mint_authority_pda = syscalls::create_program_address(
+        &[Protocol::MINT_AUTHORITY_SEED, &[protocol.mint_authority_bump]],
+        program_id,
+    )?;
+
+    if mint_authority_pda != *mint_authority.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    let signer_seeds: &[&[u8]] = &[Protocol::MINT_AUTHORITY_SEED, &[protocol.mint_authority_bump]];
+
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            bounty_vault.key,
+            destination.key,
+            mint_authority.key,
+            &[],
+            bounty.reward,
+        )?,
+        &[
+            bounty_vault.clone(),
+            destination.clone(),
+            mint_authority.clone(),
+            token_program.clone(),
+        ],
+        &[signer_seeds],
+    )?;
+
+    invoke_signed(
+        &spl_token::instruction::close_account(
+            token_program.key,
+            bounty_vault.key,
+            requester.key,
+            mint_authority.key,
+            &[],
+        )?,
+        &[
+            bounty_vault.clone(),
+            requester.clone(),
+            mint_authority.clone(),
+            token_program.clone(),
+        ],
+        &[signer_seeds],
+    )
+}
+
+fn process_accept_answer(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
-    let payer = next_account_info(account_info_iter)?;
+    let requester = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let bounty_account = next_account_info(account_info_iter)?;
+    let answer_account = next_account_info(account_info_iter)?;
+    let bounty_vault = next_account_info(account_info_iter)?;
+    let destination = next_account_info(account_info_iter)?;
+    let mint_authority = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+    let mut bounty = load_open_bounty(program_id, requester, bounty_account, bounty_vault)?;
+
+    let answer = BountyAnswer::load(program_id, answer_account)?;
+    let answer_pda = syscalls::create_program_address(
+        &[BountyAnswer::SEED, answer.bounty.as_ref(), answer.knowledge_entry.as_ref(), &[answer.bump]],
+        program_id,
+    )?;
+
+    if answer_pda != *answer_account.key {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    if answer.bounty != *bounty_account.key {
+        return Err(SolSageError::BountyAnswerMismatch.into());
+    }
+
+    // The reward must land in a SAGE account the answering staker controls
+    if *destination.owner != spl_token::id() {
+        return Err(SolSageError::InvalidTokenAccountOwner.into());
+    }
+    let destination_account = spl_token::state::Account::unpack(&destination.data.borrow())?;
+    if destination_account.mint != protocol.reward_mint {
+        return Err(SolSageError::InvalidRewardMint.into());
+    }
+    if destination_account.owner != answer.staker {
+        return Err(SolSageError::InvalidTokenAccountOwner.into());
+    }
+
+    let release_accounts = BountyReleaseAccounts { requester, bounty_vault, destination, mint_authority, token_program };
+    release_bounty(program_id, &protocol, &bounty, release_accounts)?;
+
+    bounty.accepted_entry = Some(answer.knowledge_entry);
+    bounty.store(bounty_account)?;
+
+    SolSageEvent::AnswerAccepted {
+        bounty: *bounty_account.key,
+        knowledge_entry: answer.knowledge_entry,
+        staker: answer.staker,
+        reward: bounty.reward,
+    }
+    .emit()?;
+
+    msg!("Answer accepted: {} SAGE paid to {}", bounty.reward, answer.staker);
+    Ok(())
+}
+
+fn process_reclaim_expired_bounty(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let requester = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+    let bounty_account = next_account_info(account_info_iter)?;
+    let bounty_vault = next_account_info(account_info_iter)?;
+    let destination = next_account_info(account_info_iter)?;
+    let mint_authority = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    let protocol = load_protocol(program_id, protocol_account)?;
+    let bounty = load_open_bounty(program_id, requester, bounty_account, bounty_vault)?;
+
+    if Clock::get()?.unix_timestamp < bounty.deadline {
+        return Err(SolSageError::BountyNotExpired.into());
+    }
+
+    let release_accounts = BountyReleaseAccounts { requester, bounty_vault, destination, mint_authority, token_program };
+    release_bounty(program_id, &protocol, &bounty, release_accounts)?;
+
+    bounty_account.data.borrow_mut().fill(0);
+    let requester_lamports = requester
+        .lamports()
+        .checked_add(bounty_account.lamports())
+        .ok_or(SolSageError::ArithmeticOverflow)?;
+    **bounty_account.lamports.borrow_mut() = 0;
+    **requester.lamports.borrow_mut() = requester_lamports;
+
+    SolSageEvent::BountyReclaimed {
+        bounty: *bounty_account.key,
+        requester: *requester.key,
+        reward: bounty.reward,
+    }
+    .emit()?;
+
+    msg!("Bounty reclaimed: {} SAGE refunded", bounty.reward);
+    Ok(())
+}
+
+fn process_set_claim_window(program_id: &Pubkey, accounts: &[AccountInfo], claim_window_secs: i64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    if claim_window_secs != 0 && claim_window_secs < Protocol::MIN_CLAIM_WINDOW_SECS {
+        return Err(SolSageError::InvalidClaimWindow.into());
+    }
+
+    protocol.claim_window_secs = claim_window_secs;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::ClaimWindowChanged { claim_window_secs }.emit()?;
+
+    msg!("Claim window changed: {}s", claim_window_secs);
+    Ok(())
+}
+
+fn process_expire_rewards(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let caller = next_account_info(account_info_iter)?;
     let protocol_account = next_account_info(account_info_iter)?;
     let knowledge_account = next_account_info(account_info_iter)?;
-    let attribution_account = next_account_info(account_info_iter)?;
-    let system_program = next_account_info(account_info_iter)?;
+    let mint_accounts = RewardMintAccounts {
+        sage_mint: next_account_info(account_info_iter)?,
+        destination: next_account_info(account_info_iter)?,
+        mint_authority: next_account_info(account_info_iter)?,
+        token_program: next_account_info(account_info_iter)?,
+    };
 
-    if !payer.is_signer {
+    if !caller.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    if relevance_score > 100 {
-        return Err(SolSageError::InvalidRelevanceScore.into());
+    let protocol = load_protocol(program_id, protocol_account)?;
+    protocol.check_not_paused()?;
+    if protocol.claim_window_secs == 0 {
+        return Err(SolSageError::RewardNotExpired.into());
     }
+    let expired_by = Clock::get()?
+        .unix_timestamp
+        .checked_sub(protocol.claim_window_secs)
+        .ok_or(SolSageError::ArithmeticOverflow)?;
 
-    // Derive attribution PDA
-    let (attribution_pda, bump) = Pubkey::find_program_address(
-        &[Attribution::SEED, &query_hash, knowledge_account.key.as_ref()],
-        program_id,
-    );
+    let mut amount: u64 = 0;
+    let mut attributions: u64 = 0;
+    while let Some(attribution_account) = account_info_iter.next() {
+        let knowledge = KnowledgeEntry::load(program_id, knowledge_account)?;
+        let expired = AttributionRelease { by: expired_by, otherwise: SolSageError::RewardNotExpired };
+        let reward = release_attribution_reward(
+            program_id,
+            knowledge,
+            knowledge_account,
+            attribution_account,
+            account_info_iter,
+            expired,
+        )?;
+        amount = amount.checked_add(reward).ok_or(SolSageError::ArithmeticOverflow)?;
+        attributions += 1;
+    }
 
-    if attribution_pda != *attribution_account.key {
+    if amount == 0 {
+        return Err(SolSageError::NoRewardsToClaim.into());
+    }
+
+    let bounty = solsage_core::apply_bps(amount, Protocol::EXPIRY_BOUNTY_BPS).ok_or(SolSageError::ArithmeticOverflow)?;
+    if bounty > 0 {
+        mint_rewards(program_id, &protocol, caller.key, &mint_accounts, bounty)?;
+    }
+
+    SolSageEvent::RewardsExpired {
+        knowledge_entry: *knowledge_account.key,
+        amount,
+        attributions,
+        bounty,
+    }
+    .emit()?;
+
+    msg!("Expired {} SAGE from {} attributions, bounty: {}", amount, attributions, bounty);
+    Ok(())
+}
+
+fn process_set_attribution_verifier(program_id: &Pubkey, accounts: &[AccountInfo], verifier: Option<Pubkey>) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let protocol_account = next_account_info(account_info_iter)?;
+
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut protocol = load_protocol(program_id, protocol_account)?;
+
+    if protocol.authority != *authority.key {
+        return Err(SolSageError::Unauthorized.into());
+    }
+
+    // A top-level instruction to the program itself is a direct call
+    if verifier == Some(*program_id) {
+        return Err(SolSageError::InvalidAttributionVerifier.into());
+    }
+
+    protocol.attribution_verifier = verifier;
+    protocol.store(protocol_account)?;
+
+    SolSageEvent::AttributionVerifierChanged { verifier }.emit()?;
+
+    msg!("Attribution verifier changed: {:?}", verifier);
+    Ok(())
+}
+
+/// Load the index of `tag`, creating it on the tag's first use
+fn load_or_create_tag_index<'info>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'info>,
+    tag: &str,
+    tag_account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    rent: &Rent,
+) -> Result<TagIndex, ProgramError> {
+    if !tag_account.data_is_empty() {
+        return load_tag_index(program_id, tag, tag_account);
+    }
+
+    let (tag_pda, bump) = syscalls::find_program_address(&[TagIndex::SEED, tag.as_bytes()], program_id);
+
+    if tag_pda != *tag_account.key {
         return Err(SolSageError::InvalidPda.into());
     }
 
-    // Create attribution account
-    let rent = Rent::get()?;
-    let lamports = rent.minimum_balance(Attribution::LEN);
-    
     invoke_signed(
         &system_instruction::create_account(
             payer.key,
-            attribution_account.key,
-            lamports,
-            Attribution::LEN as u64,
+            tag_account.key,
+            rent.minimum_balance(TagIndex::LEN),
+            TagIndex::LEN as u64,
             program_id,
         ),
-        &[payer.clone(), attribution_account.clone(), system_program.clone()],
-        &[&[Attribution::SEED, &query_hash, knowledge_account.key.as_ref(), &[bump]]],
+        &[payer.clone(), tag_account.clone(), system_program.clone()],
+        &[&[TagIndex::SEED, tag.as_bytes(), &[bump]]],
     )?;
 
-    // Update knowledge entry
-    let mut knowledge = KnowledgeEntry::try_from_slice(&knowledge_account.data.borrow())?;
-    knowledge.total_attributions += 1;
-    
-    // Calculate reward
-    let protocol = Protocol::try_from_slice(&protocol_account.data.borrow())?;
-    let reward = (protocol.reward_per_attribution * relevance_score as u64) / 10;
-    knowledge.pending_rewards += reward;
-    knowledge.serialize(&mut &mut knowledge_account.data.borrow_mut()[..])?;
-
-    // Create attribution
-    let clock = Clock::get()?;
-    let attribution = Attribution {
+    Ok(TagIndex {
         is_initialized: true,
-        knowledge_entry: *knowledge_account.key,
-        query_hash,
-        relevance_score,
-        timestamp: clock.unix_timestamp,
-        reward_claimed: false,
+        schema_version: SCHEMA_VERSION,
+        tag: tag.to_string(),
+        entries: 0,
+        created_at: Clock::get()?.unix_timestamp,
         bump,
-    };
-    attribution.serialize(&mut &mut attribution_account.data.borrow_mut()[..])?;
+    })
+}
 
-    // Update protocol
-    let mut protocol = Protocol::try_from_slice(&protocol_account.data.borrow())?;
-    protocol.total_attributions += 1;
-    protocol.serialize(&mut &mut protocol_account.data.borrow_mut()[..])?;
+/// Load the index of `tag`, checking it is that tag's PDA
+fn load_tag_index(program_id: &Pubkey, tag: &str, tag_account: &AccountInfo) -> Result<TagIndex, ProgramError> {
+    let tag_index = TagIndex::load(program_id, tag_account)?;
+    let tag_pda = syscalls::create_program_address(&[TagIndex::SEED, tag.as_bytes(), &[tag_index.bump]], program_id)?;
 
-    msg!("Attribution recorded, reward: {}", reward);
-    Ok(())
+    if tag_pda != *tag_account.key || tag_index.tag != tag {
+        return Err(SolSageError::InvalidPda.into());
+    }
+
+    Ok(tag_index)
 }
 
-fn process_claim_rewards(
-    _program_id: &Pubkey,
-    accounts: &[AccountInfo],
-) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
-    let staker = next_account_info(account_info_iter)?;
-    let knowledge_account = next_account_info(account_info_iter)?;
+/// Load an epoch, checking it is the PDA for its own index
+fn load_epoch(program_id: &Pubkey, epoch_account: &AccountInfo) -> Result<Epoch, ProgramError> {
+    let epoch = Epoch::load(program_id, epoch_account)?;
+    let epoch_pda = syscalls::create_program_address(
+        &[Epoch::SEED, &epoch.index.to_le_bytes(), &[epoch.bump]],
+        program_id,
+    )?;
 
-    if !staker.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
+    if epoch_pda != *epoch_account.key {
+        return Err(SolSageError::InvalidPda.into());
     }
 
-    let mut knowledge = KnowledgeEntry::try_from_slice(&knowledge_account.data.borrow())?;
-    
-    if knowledge.staker != *staker.key {
-        return Err(SolSageError::NotKnowledgeOwner.into());
+    Ok(epoch)
+}
+
+/// Load the epoch attributions made at `now` accrue weight in, or `None`
+/// while epochs are disabled
+fn load_open_epoch(
+    program_id: &Pubkey,
+    protocol: &Protocol,
+    epoch_account: Option<&AccountInfo>,
+    now: i64,
+) -> Result<Option<Epoch>, ProgramError> {
+    if !protocol.epochs_enabled() {
+        return Ok(None);
     }
 
-    if knowledge.pending_rewards == 0 {
-        return Err(SolSageError::NoRewardsToClaim.into());
+    let epoch_account = epoch_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let epoch = load_epoch(program_id, epoch_account)?;
+    if epoch.index.checked_add(1) != Some(protocol.epoch_count) || now >= epoch.ends_at {
+        return Err(SolSageError::EpochNotOpen.into());
     }
 
-    let reward_amount = knowledge.pending_rewards;
-    knowledge.pending_rewards = 0;
-    knowledge.serialize(&mut &mut knowledge_account.data.borrow_mut()[..])?;
+    Ok(Some(epoch))
+}
+
+/// Reject a `system_program` account that is not the System Program
+fn check_system_program(system_program: &AccountInfo) -> ProgramResult {
+    if !system_program::check_id(system_program.key) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
 
-    // In MVP, we just log - actual token transfer would happen here
-    msg!("Claimed {} SAGE tokens", reward_amount);
+/// Reject creating `account` over existing state. Accounts not yet created
+/// are empty and still owned by the System Program.
+fn check_uninitialized(account: &AccountInfo) -> ProgramResult {
+    if !account.data_is_empty() || !system_program::check_id(account.owner) {
+        return Err(SolSageError::AlreadyInitialized.into());
+    }
     Ok(())
 }
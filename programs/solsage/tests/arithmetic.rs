@@ -0,0 +1,726 @@
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solsage::{
+    AuthorityType, AttributionRoot, Category, Epoch, FeeConfig, KnowledgeEntry, Leaderboard, LicenseType, LockupTier, ProposalAction, Protocol, RewardCurve,
+    RewardShares, RewardSource, RoyaltySplit, SolSageError, StakerIndex, StakerProfile, VestingBucket, Visibility,
+    KNOWLEDGE_ENTRY_SCHEMA_VERSION, SCHEMA_VERSION,
+};
+
+const DAY: i64 = 24 * 60 * 60;
+const WEEK: i64 = 7 * DAY;
+const DIVISOR: u16 = Protocol::DEFAULT_SCORE_DIVISOR;
+
+fn overflow() -> ProgramError {
+    SolSageError::ArithmeticOverflow.into()
+}
+
+fn entry() -> KnowledgeEntry {
+    KnowledgeEntry {
+        is_initialized: true,
+        schema_version: KNOWLEDGE_ENTRY_SCHEMA_VERSION,
+        staker: Pubkey::new_unique(),
+        creator: Pubkey::new_unique(),
+        entry_index: 0,
+        content_hash: [0; 32],
+        category: Pubkey::default(),
+        is_active: true,
+        license: LicenseType::Mit,
+        title: String::new(),
+        content_uri: None,
+        collection: None,
+        royalty_splits: Vec::new(),
+        created_at: 0,
+        updated_at: 0,
+        expires_at: 0,
+        last_attribution_at: 0,
+        version: 1,
+        total_attributions: 0,
+        pending_rewards: 0,
+        vesting_schedule: [VestingBucket::default(); KnowledgeEntry::VESTING_BUCKETS],
+        stake_amount: 0,
+        bump: 0,
+        escrow_bump: 0,
+        tags: Vec::new(),
+        min_reward_per_use: 0,
+        lockup_tier: LockupTier::None,
+        locked_until: 0,
+        unstake_requested_at: 0,
+        receipt_asset_id: None,
+        quality_score: 0,
+        under_review: false,
+        reward_stream: Default::default(),
+        derived_from: Vec::new(),
+        citation_royalties: 0,
+        citation_rewards: 0,
+        document: None,
+        chunk_index: 0,
+        chunks: None,
+        embedding_fingerprint: None,
+        attestation: None,
+        verified: false,
+        identity_credential: None,
+        visibility: Visibility::Public,
+    }
+}
+
+fn protocol(fee_bps: u16) -> Protocol {
+    Protocol {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        authority: Pubkey::new_unique(),
+        pending_authority: None,
+        reward_mint: Pubkey::default(),
+        total_knowledge_entries: 0,
+        total_attributions: 0,
+        reward_per_attribution: 1_000_000,
+        max_supply: 0,
+        vesting_period_secs: WEEK,
+        reward_curve: RewardCurve::Linear,
+        fee_bps,
+        fees_collected: 0,
+        min_stake_lamports: 0,
+        attribution_cooldown_secs: 0,
+        is_paused: false,
+        score_divisor: Protocol::DEFAULT_SCORE_DIVISOR,
+        epoch_duration_secs: 0,
+        epoch_reward_budget: 0,
+        epoch_count: 0,
+        epoch_ends_at: 0,
+        curator_bps: 0,
+        bump: 0,
+        mint_authority_bump: 0,
+        treasury_bump: 0,
+        reward_source: RewardSource::Mint,
+        reward_vault_bump: 0,
+        authority_type: AuthorityType::Keypair,
+        pending_authority_type: AuthorityType::Keypair,
+        quorum_votes: 0,
+        approval_threshold_bps: 0,
+        voting_period_secs: 0,
+        execution_delay_secs: 0,
+        proposal_count: 0,
+        governance_vault_bump: 0,
+        admin_delay_secs: 0,
+        queued_change: None,
+        queued_change_executable_at: 0,
+        score_half_life_secs: 0,
+        min_score_weight_bps: 0,
+        attribution_verifier: None,
+        claim_window_secs: 0,
+        total_rewards: 0,
+        receipt_tree: None,
+        sale_fee_bps: 0,
+        consensus_threshold: 0,
+        consensus_tolerance: 0,
+        aggregation_window_secs: 0,
+        max_payer_attributions_per_day: 0,
+        reward_stream_secs: 0,
+        fee_config: FeeConfig::default(),
+        treasury_rewards: 0,
+        rewards_burned: 0,
+        low_relevance_threshold: 0,
+        low_relevance_burn_bps: 0,
+        pending_burn: 0,
+        citation_royalty_bps: 0,
+        identity_config: None,
+    }
+}
+
+fn profile() -> StakerProfile {
+    StakerProfile {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        staker: Pubkey::new_unique(),
+        total_entries: 0,
+        total_attributions: 0,
+        total_relevance: 0,
+        cumulative_rewards: 0,
+        claim_delegate: None,
+        bump: 0,
+    }
+}
+
+fn category(multiplier_bps: u16) -> Category {
+    Category {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        name: String::new(),
+        entries: 0,
+        attributions: 0,
+        multiplier_bps,
+        created_at: 0,
+        bump: 0,
+        rewards: 0,
+    }
+}
+
+#[test]
+fn linear_reward_pays_base_rate_at_full_score() {
+    let rate = Protocol::MAX_REWARD_PER_ATTRIBUTION;
+    assert_eq!(RewardCurve::Linear.reward(rate, 100, 0, DIVISOR), Some(rate));
+    assert_eq!(RewardCurve::Linear.reward(1_000_000, 73, 0, DIVISOR), Some(730_000));
+    assert_eq!(RewardCurve::Linear.reward(rate, 0, 0, DIVISOR), Some(0));
+}
+
+#[test]
+fn score_divisor_scales_full_score_payout() {
+    let rate = 1_000_000;
+    assert_eq!(RewardCurve::Linear.reward(rate, 100, 0, Protocol::MIN_SCORE_DIVISOR), Some(10 * rate));
+    assert_eq!(RewardCurve::Linear.reward(rate, 100, 0, Protocol::MAX_SCORE_DIVISOR), Some(rate / 100));
+    assert_eq!(RewardCurve::Linear.reward(rate, 100, 0, 0), None);
+}
+
+#[test]
+fn linear_reward_overflows_on_huge_rate() {
+    assert_eq!(RewardCurve::Linear.reward(u64::MAX, 2, 0, DIVISOR), None);
+    assert_eq!(RewardCurve::Linear.reward(u64::MAX, 1, 0, DIVISOR), Some(u64::MAX / 100));
+}
+
+#[test]
+fn quadratic_reward_matches_linear_at_full_score() {
+    let rate = Protocol::MAX_REWARD_PER_ATTRIBUTION;
+    assert_eq!(
+        RewardCurve::Quadratic.reward(rate, 100, 0, DIVISOR),
+        RewardCurve::Linear.reward(rate, 100, 0, DIVISOR)
+    );
+    assert_eq!(RewardCurve::Quadratic.reward(1_000_000, 50, 0, DIVISOR), Some(250_000));
+    assert_eq!(RewardCurve::Quadratic.reward(1_000, 10, 0, DIVISOR), Some(10));
+}
+
+#[test]
+fn quadratic_reward_overflows_on_squared_score() {
+    let rate = u64::MAX / 100 + 1;
+    assert_eq!(RewardCurve::Quadratic.reward(rate, 100, 0, DIVISOR), None);
+}
+
+#[test]
+fn decaying_reward_steps_down_per_attribution_count() {
+    let curve = RewardCurve::DecayByAttributionCount;
+    let step = RewardCurve::DECAY_STEP;
+    assert_eq!(curve.reward(1_000, 100, 0, DIVISOR), Some(1_000));
+    assert_eq!(curve.reward(1_000, 100, step - 1, DIVISOR), Some(1_000));
+    assert_eq!(curve.reward(1_000, 100, step, DIVISOR), Some(500));
+    assert_eq!(curve.reward(1_000, 100, u64::MAX, DIVISOR), Some(0));
+}
+
+#[test]
+fn accrue_rewards_rejects_pending_overflow() {
+    let mut knowledge = entry();
+    knowledge.pending_rewards = u64::MAX;
+
+    assert_eq!(knowledge.accrue_rewards(1, 0, WEEK), Err(overflow()));
+    assert_eq!(knowledge.pending_rewards, u64::MAX);
+    assert_eq!(knowledge.accrue_rewards(0, 0, WEEK), Ok(0));
+}
+
+#[test]
+fn accrue_rewards_rejects_unlock_time_overflow() {
+    let mut knowledge = entry();
+    assert_eq!(knowledge.accrue_rewards(1, i64::MAX - WEEK + 1, WEEK), Err(overflow()));
+}
+
+#[test]
+fn accrue_rewards_rejects_bucket_overflow() {
+    let mut knowledge = entry();
+    knowledge.accrue_rewards(u64::MAX - 1, 0, WEEK).unwrap();
+    knowledge.pending_rewards = 0;
+
+    assert_eq!(knowledge.accrue_rewards(2, 0, WEEK), Err(overflow()));
+}
+
+#[test]
+fn accruals_at_a_steady_vesting_period_always_find_a_bucket() {
+    let mut knowledge = entry();
+    let mut unlocks = Vec::new();
+    for now in (0..4 * WEEK).step_by(3_600) {
+        let schedule = knowledge.vesting_schedule;
+        let unlocks_at = knowledge.accrue_rewards(1, now, WEEK).unwrap();
+        assert!(unlocks_at >= now + WEEK);
+        for (before, after) in schedule.iter().zip(&knowledge.vesting_schedule) {
+            assert!(before.unlocks_at <= now || before.unlocks_at == after.unlocks_at);
+        }
+        unlocks.push(unlocks_at);
+    }
+    assert!(unlocks.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert_eq!(knowledge.pending_rewards, unlocks.len() as u64);
+}
+
+#[test]
+fn accruals_past_every_locked_bucket_never_move_an_unlock_time() {
+    // A vesting period since shortened left every bucket locked, one of them
+    // past the reward's own unlock
+    let mut knowledge = entry();
+    for (index, bucket) in knowledge.vesting_schedule.iter_mut().enumerate() {
+        *bucket = VestingBucket { unlocks_at: 2 * WEEK + index as i64 * DAY + 3_600, amount: 10 };
+    }
+    knowledge.pending_rewards = 80;
+    let schedule = knowledge.vesting_schedule;
+
+    // The reward joins the nearest bucket unlocking later
+    let unlocks_at = knowledge.accrue_rewards(5, WEEK + 1, WEEK).unwrap();
+    assert_eq!(unlocks_at, 2 * WEEK + DAY + 3_600);
+    assert_eq!(knowledge.vesting_schedule[1].amount, 15);
+    let unlock_times = |knowledge: &KnowledgeEntry| knowledge.vesting_schedule.map(|bucket| bucket.unlocks_at);
+    assert_eq!(unlock_times(&knowledge), schedule.map(|bucket| bucket.unlocks_at));
+    assert_eq!(knowledge.pending_rewards, 85);
+
+    // A lengthened period that would unlock after every bucket is rejected
+    let schedule = knowledge.vesting_schedule;
+    assert_eq!(knowledge.accrue_rewards(5, WEEK, 2 * WEEK), Err(SolSageError::VestingScheduleFull.into()));
+    assert_eq!(knowledge.vesting_schedule, schedule);
+    assert_eq!(knowledge.pending_rewards, 85);
+}
+
+#[test]
+fn vested_rewards_caps_locked_at_pending() {
+    let mut knowledge = entry();
+    knowledge.pending_rewards = 10;
+    knowledge.vesting_schedule[0] = VestingBucket { unlocks_at: WEEK, amount: u64::MAX };
+    knowledge.vesting_schedule[1] = VestingBucket { unlocks_at: WEEK, amount: u64::MAX };
+
+    let vested = knowledge.vested_rewards(0);
+    assert_eq!(vested.locked, 10);
+    assert_eq!(vested.claimable, 0);
+}
+
+#[test]
+fn profile_rejects_attribution_overflow() {
+    let mut staker = profile();
+    staker.total_attributions = u64::MAX;
+    assert_eq!(staker.record_attribution(50), Err(overflow()));
+
+    let mut staker = profile();
+    staker.total_relevance = u64::MAX - 99;
+    assert_eq!(staker.record_attribution(100), Err(overflow()));
+    assert_eq!(staker.total_relevance, u64::MAX - 99);
+}
+
+#[test]
+fn profile_average_relevance_handles_zero_attributions() {
+    let mut staker = profile();
+    assert_eq!(staker.average_relevance(), 0);
+
+    staker.record_attribution(100).unwrap();
+    staker.record_attribution(51).unwrap();
+    assert_eq!(staker.average_relevance(), 75);
+}
+
+#[test]
+fn reverse_rewards_is_capped_at_pending() {
+    let mut knowledge = entry();
+    knowledge.accrue_rewards(100, 0, WEEK).unwrap();
+    knowledge.pending_rewards = 40;
+
+    assert_eq!(knowledge.reverse_rewards(100, 0), 40);
+    assert_eq!(knowledge.pending_rewards, 0);
+    assert_eq!(knowledge.vested_rewards(0).locked, 0);
+    assert_eq!(knowledge.vesting_schedule.iter().map(|b| b.amount).sum::<u64>(), 60);
+}
+
+#[test]
+fn streamed_rewards_pay_out_by_the_second_and_reverse_what_is_left_first() {
+    let mut knowledge = entry();
+    knowledge.stream_rewards(1_000, 0, 100).unwrap();
+    assert_eq!(knowledge.vested_rewards(25).claimable, 250);
+    assert_eq!(knowledge.claim_streamed_rewards(25).unwrap(), 250);
+
+    // A new reward spreads everything left to stream over a fresh period
+    knowledge.stream_rewards(250, 25, 100).unwrap();
+    assert_eq!(knowledge.reward_stream.ends_at, 125);
+    assert_eq!(knowledge.vested_rewards(75).claimable, 500);
+
+    assert_eq!(knowledge.reverse_streamed_rewards(300, 75).unwrap(), 300);
+    assert_eq!(knowledge.reward_stream.remaining, 200);
+    assert_eq!(knowledge.claim_streamed_rewards(i64::MAX).unwrap(), 700);
+    assert_eq!(knowledge.pending_rewards, 0);
+    assert_eq!(knowledge.claim_streamed_rewards(i64::MAX), Ok(0));
+}
+
+#[test]
+fn reward_stream_rejects_overflow() {
+    let mut knowledge = entry();
+    assert_eq!(knowledge.stream_rewards(1, i64::MAX, 1), Err(overflow()));
+
+    let mut knowledge = entry();
+    knowledge.stream_rewards(u64::MAX, 0, 1).unwrap();
+    knowledge.pending_rewards = 0;
+    assert_eq!(knowledge.stream_rewards(1, 0, 1), Err(overflow()));
+}
+
+#[test]
+fn citation_royalties_split_evenly_with_the_dust_to_the_first_parents() {
+    let mut knowledge = entry();
+    assert_eq!(knowledge.set_aside_citation_royalty(1_000, 2_000), Ok(1_000));
+
+    knowledge.derived_from = vec![Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+    assert_eq!(knowledge.set_aside_citation_royalty(1_000, 2_000), Ok(800));
+    assert_eq!(knowledge.take_citation_royalties(), vec![67, 67, 66]);
+    assert_eq!(knowledge.citation_royalties, 0);
+    assert_eq!(knowledge.set_aside_citation_royalty(u64::MAX, 0), Ok(u64::MAX));
+
+    knowledge.citation_royalties = u64::MAX;
+    assert_eq!(knowledge.set_aside_citation_royalty(10, 5_000), Err(overflow()));
+
+    let mut parent = entry();
+    parent.receive_citation_royalty(66).unwrap();
+    assert_eq!(parent.claim_citation_rewards(), Ok(66));
+    assert_eq!((parent.pending_rewards, parent.citation_rewards), (0, 0));
+}
+
+#[test]
+fn fee_on_max_reward_does_not_overflow() {
+    assert_eq!(protocol(Protocol::MAX_BPS).fee_on(u64::MAX), Some(u64::MAX));
+    assert_eq!(protocol(Protocol::MAX_FEE_BPS).fee_on(u64::MAX), Some(u64::MAX / 10));
+    assert_eq!(protocol(0).fee_on(u64::MAX), Some(0));
+    assert_eq!(protocol(100).fee_on(99), Some(0));
+}
+
+#[test]
+fn fee_config_splits_rewards_and_leaves_the_rest_to_the_entry() {
+    let fees = FeeConfig { treasury_bps: 1_000, curator_bps: 2_000, burn_bps: 500 };
+    assert!(fees.is_valid());
+    let shares = fees.split(1_000).unwrap();
+    assert_eq!((shares.entry, shares.curator, shares.treasury, shares.burned), (650, 200, 100, 50));
+    assert_eq!((shares.awarded(), shares.total()), (Some(950), Some(1_000)));
+    assert_eq!(fees.split(u64::MAX).and_then(|shares| shares.total()), Some(u64::MAX));
+
+    assert!(!FeeConfig { curator_bps: Protocol::MAX_CURATOR_BPS + 1, ..FeeConfig::default() }.is_valid());
+    let over = FeeConfig { treasury_bps: 6_000, curator_bps: 4_000, burn_bps: 1 };
+    assert!(!over.is_valid());
+    assert_eq!(over.split(10_000), None);
+
+    // Epoch rewards are weights, so only curators take a share of them
+    let mut protocol = protocol(0);
+    protocol.curator_bps = 2_000;
+    protocol.fee_config = fees;
+    assert_eq!(protocol.attribution_fees(true, false), fees);
+    assert_eq!(protocol.attribution_fees(false, false), FeeConfig { curator_bps: 0, ..fees });
+    assert_eq!(protocol.attribution_fees(true, true), FeeConfig { curator_bps: 2_000, ..FeeConfig::default() });
+}
+
+#[test]
+fn low_relevance_attributions_burn_their_reward_instead_of_paying_it() {
+    let shares = FeeConfig { treasury_bps: 1_000, curator_bps: 2_000, burn_bps: 500 }.split(1_000).unwrap();
+    let mut protocol = protocol(0);
+    assert_eq!(protocol.apply_low_relevance(shares, 0, false), Some(shares));
+
+    protocol.low_relevance_threshold = 30;
+    protocol.low_relevance_burn_bps = 5_000;
+    assert_eq!(protocol.apply_low_relevance(shares, 30, false), Some(shares));
+    let burned = protocol.apply_low_relevance(shares, 29, false).unwrap();
+    assert_eq!((burned.awarded(), burned.burned), (Some(0), 500));
+    assert_eq!(protocol.apply_low_relevance(shares, 29, true), Some(RewardShares::default()));
+}
+
+#[test]
+fn category_attributions_count_the_rewards_credited_to_its_entries() {
+    let mut rust = category(Category::DEFAULT_MULTIPLIER_BPS);
+    rust.record_attribution(300).unwrap();
+    rust.record_attribution(200).unwrap();
+    assert_eq!((rust.attributions, rust.rewards), (2, 500));
+    assert_eq!(rust.record_attribution(u64::MAX), Err(overflow()));
+}
+
+#[test]
+fn category_multiplier_scales_and_rejects_overflow() {
+    let max = category(Category::MAX_MULTIPLIER_BPS);
+    assert_eq!(max.apply_multiplier(1_000), Some(5_000));
+    assert_eq!(max.apply_multiplier(u64::MAX), None);
+    assert_eq!(category(Category::DEFAULT_MULTIPLIER_BPS).apply_multiplier(u64::MAX), Some(u64::MAX));
+    assert_eq!(category(0).apply_multiplier(u64::MAX), Some(0));
+}
+
+#[test]
+fn longer_lockups_boost_rewards_more() {
+    let boosted: Vec<_> = [LockupTier::None, LockupTier::Days30, LockupTier::Days90, LockupTier::Days365]
+        .iter()
+        .map(|tier| tier.apply_boost(1_000).unwrap())
+        .collect();
+    assert_eq!(boosted, [1_000, 1_100, 1_250, 1_500]);
+    assert_eq!(LockupTier::None.apply_boost(u64::MAX), Some(u64::MAX));
+    assert_eq!(LockupTier::Days365.apply_boost(u64::MAX), None);
+}
+
+#[test]
+fn quality_votes_scale_rewards_within_bounds() {
+    let mut knowledge = entry();
+    assert_eq!(knowledge.apply_quality(1_000), Some(1_000));
+
+    // 1,000 SAGE net up doubles the reward, and no more SAGE goes further
+    knowledge.record_quality_vote(true, 1_000_000_000).unwrap();
+    assert_eq!(knowledge.apply_quality(1_000), Some(2_000));
+    knowledge.record_quality_vote(true, 1).unwrap();
+    assert_eq!(knowledge.apply_quality(1_000), Some(2_000));
+
+    // Downvotes count against upvotes, and can halve the reward at most
+    knowledge.record_quality_vote(false, 1_250_000_001).unwrap();
+    assert_eq!(knowledge.quality_score, -250_000_000);
+    assert_eq!(knowledge.apply_quality(1_000), Some(750));
+    assert_eq!(solsage_core::quality_multiplier_bps(i64::MIN), solsage_core::MIN_QUALITY_BPS);
+
+    assert_eq!(knowledge.record_quality_vote(true, u64::MAX), Err(overflow()));
+}
+
+#[test]
+fn attribution_cooldown_applies_after_first_attribution() {
+    let mut knowledge = entry();
+    let cooldown: Result<(), ProgramError> = Err(SolSageError::AttributionCooldown.into());
+
+    assert_eq!(knowledge.mark_attributed(5, 60), Ok(()));
+    knowledge.total_attributions = 1;
+    assert_eq!(knowledge.mark_attributed(64, 60), cooldown);
+    assert_eq!(knowledge.mark_attributed(65, 60), Ok(()));
+    assert_eq!(knowledge.last_attribution_at, 65);
+
+    knowledge.last_attribution_at = i64::MAX;
+    assert_eq!(knowledge.mark_attributed(0, 1), Err(overflow()));
+}
+
+#[test]
+fn attribution_root_leaves_claim_once() {
+    let mut root = AttributionRoot {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        oracle: Pubkey::new_unique(),
+        epoch: 0,
+        root: [0; 32],
+        attribution_count: AttributionRoot::MAX_ATTRIBUTIONS as u32,
+        claimed_count: 0,
+        committed_at: 0,
+        unlocks_at: 0,
+        claimed: [0; AttributionRoot::MAX_ATTRIBUTIONS / 8],
+        bump: 0,
+    };
+    let last = AttributionRoot::MAX_ATTRIBUTIONS as u32 - 1;
+
+    assert_eq!(root.set_claimed(9), Ok(()));
+    assert_eq!(root.set_claimed(last), Ok(()));
+    assert_eq!(root.set_claimed(9), Err(SolSageError::RewardAlreadyClaimed.into()));
+    assert!(!root.is_claimed(8) && !root.is_claimed(10));
+    assert_eq!(root.claimed_count, 2);
+}
+
+#[test]
+fn epoch_budget_is_shared_pro_rata_once_finalized() {
+    let mut epoch = Epoch {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        index: 0,
+        started_at: 0,
+        ends_at: WEEK,
+        reward_budget: 1_000,
+        total_weight: 0,
+        is_finalized: false,
+        bump: 0,
+    };
+    for weight in [1, 1, 1] {
+        epoch.add_weight(weight).unwrap();
+    }
+    assert_eq!(epoch.share_of(1), Err(SolSageError::EpochNotFinalized.into()));
+
+    // Shares round down, so the epoch never pays out more than its budget
+    epoch.is_finalized = true;
+    assert_eq!(epoch.share_of(1), Ok(333));
+
+    epoch.reward_budget = u64::MAX;
+    epoch.total_weight = u64::MAX;
+    assert_eq!(epoch.share_of(u64::MAX), Ok(u64::MAX));
+    assert_eq!(epoch.add_weight(1), Err(overflow()));
+}
+
+#[test]
+fn royalty_splits_round_down_in_the_owners_favour() {
+    let mut knowledge = entry();
+    let co_authors = [Pubkey::new_unique(), Pubkey::new_unique()];
+    knowledge
+        .set_royalty_splits(vec![
+            RoyaltySplit { co_author: co_authors[0], bps: 3_333 },
+            RoyaltySplit { co_author: co_authors[1], bps: 3_333 },
+        ])
+        .unwrap();
+
+    assert_eq!(knowledge.split_royalties(100), Some((34, vec![33, 33])));
+    assert_eq!(knowledge.split_royalties(u64::MAX).map(|(owner, _)| owner > 0), Some(true));
+
+    knowledge.royalty_splits.clear();
+    assert_eq!(knowledge.split_royalties(100), Some((100, vec![])));
+}
+
+#[test]
+fn royalty_splits_reject_invalid_shares() {
+    let mut knowledge = entry();
+    let co_author = Pubkey::new_unique();
+    let invalid: Result<(), ProgramError> = Err(SolSageError::InvalidRoyaltySplit.into());
+    let split = |co_author, bps| RoyaltySplit { co_author, bps };
+
+    assert_eq!(knowledge.set_royalty_splits(vec![split(co_author, 0)]), invalid);
+    assert_eq!(knowledge.set_royalty_splits(vec![split(knowledge.staker, 100)]), invalid);
+    assert_eq!(knowledge.set_royalty_splits(vec![split(co_author, 100), split(co_author, 100)]), invalid);
+    assert_eq!(
+        knowledge.set_royalty_splits(vec![split(co_author, 6_000), split(Pubkey::new_unique(), 4_001)]),
+        invalid
+    );
+    let too_many = (0..=KnowledgeEntry::MAX_CO_AUTHORS).map(|_| split(Pubkey::new_unique(), 1)).collect();
+    assert_eq!(knowledge.set_royalty_splits(too_many), invalid);
+    assert!(knowledge.royalty_splits.is_empty());
+
+    assert_eq!(knowledge.set_royalty_splits(vec![split(co_author, Protocol::MAX_BPS)]), Ok(()));
+    assert_eq!(knowledge.split_royalties(7), Some((0, vec![7])));
+}
+
+#[test]
+fn custom_licenses_need_a_uri_that_fits() {
+    let invalid: Result<(), ProgramError> = Err(SolSageError::InvalidLicense.into());
+    let custom = |len| LicenseType::Custom { uri: "u".repeat(len) };
+
+    assert_eq!(LicenseType::CcBy.validate(), Ok(()));
+    assert_eq!(custom(0).validate(), invalid);
+    assert_eq!(custom(LicenseType::MAX_URI_LEN).validate(), Ok(()));
+    assert_eq!(custom(LicenseType::MAX_URI_LEN + 1).validate(), invalid);
+}
+
+#[test]
+fn content_uris_must_be_non_empty_and_fit() {
+    let mut knowledge = entry();
+    let invalid: Result<(), ProgramError> = Err(SolSageError::InvalidContentUri.into());
+
+    assert_eq!(knowledge.set_content_uri(Some(String::new())), invalid);
+    assert_eq!(knowledge.set_content_uri(Some("a".repeat(KnowledgeEntry::MAX_CONTENT_URI_LEN + 1))), invalid);
+    assert_eq!(knowledge.content_uri, None);
+
+    let uri = format!("ar://{}", "a".repeat(KnowledgeEntry::MAX_CONTENT_URI_LEN - 5));
+    assert_eq!(knowledge.set_content_uri(Some(uri.clone())), Ok(()));
+    assert_eq!(knowledge.content_uri, Some(uri));
+    assert_eq!(knowledge.set_content_uri(None), Ok(()));
+    assert_eq!(knowledge.content_uri, None);
+}
+
+#[test]
+fn tags_are_bounded_and_distinct() {
+    let mut knowledge = entry();
+    let err = |e: SolSageError| -> Result<(), ProgramError> { Err(e.into()) };
+
+    assert_eq!(knowledge.add_tag(String::new()), err(SolSageError::InvalidTag));
+    assert_eq!(knowledge.add_tag("a".repeat(KnowledgeEntry::MAX_TAG_LEN + 1)), err(SolSageError::InvalidTag));
+
+    for i in 0..KnowledgeEntry::MAX_TAGS {
+        assert_eq!(knowledge.add_tag(format!("tag-{i}")), Ok(()));
+    }
+    assert_eq!(knowledge.add_tag("tag-0".to_string()), err(SolSageError::DuplicateTag));
+    assert_eq!(knowledge.add_tag("one-more".to_string()), err(SolSageError::TooManyTags));
+
+    assert_eq!(knowledge.remove_tag("tag-3"), Ok(()));
+    assert_eq!(knowledge.remove_tag("tag-3"), err(SolSageError::TagNotFound));
+    assert_eq!(knowledge.tags.len(), KnowledgeEntry::MAX_TAGS - 1);
+    assert_eq!(knowledge.add_tag("one-more".to_string()), Ok(()));
+}
+
+#[test]
+fn price_floor_rounds_up_only_for_subscriptions() {
+    assert_eq!(solsage_core::apply_price_floor(500, 0, false), Some(500));
+    assert_eq!(solsage_core::apply_price_floor(500, 500, false), Some(500));
+    assert_eq!(solsage_core::apply_price_floor(499, 500, false), None);
+    assert_eq!(solsage_core::apply_price_floor(499, 500, true), Some(500));
+    assert_eq!(solsage_core::apply_price_floor(900, 500, true), Some(900));
+}
+
+#[test]
+fn leaderboard_stays_sorted_and_evicts_the_lowest() {
+    let mut board = Leaderboard::new(0);
+    let keys: Vec<Pubkey> = (0..=Leaderboard::MAX_ENTRIES).map(|_| Pubkey::new_unique()).collect();
+    for (count, key) in keys.iter().take(Leaderboard::MAX_ENTRIES).enumerate() {
+        board.record(*key, count as u64 + 1);
+    }
+    assert_eq!(board.entries().len(), Leaderboard::MAX_ENTRIES);
+    assert_eq!(board.entries()[0].knowledge_entry, keys[Leaderboard::MAX_ENTRIES - 1]);
+    assert!(board.entries().windows(2).all(|pair| pair[0].total_attributions >= pair[1].total_attributions));
+
+    // A newcomer tying the last entry stays off the full board
+    let newcomer = keys[Leaderboard::MAX_ENTRIES];
+    board.record(newcomer, 1);
+    assert!(board.entries().iter().all(|entry| entry.knowledge_entry != newcomer));
+
+    // Beating it evicts the lowest, and ties keep the entry ranked first
+    board.record(newcomer, 2);
+    assert!(board.entries().iter().all(|entry| entry.knowledge_entry != keys[0]));
+    assert_eq!(board.entries()[Leaderboard::MAX_ENTRIES - 2].knowledge_entry, keys[1]);
+    assert_eq!(board.entries()[Leaderboard::MAX_ENTRIES - 1].knowledge_entry, newcomer);
+
+    // Recording an entry again moves it rather than duplicating it
+    board.record(keys[1], 100);
+    assert_eq!(board.entries()[0].knowledge_entry, keys[1]);
+    assert_eq!(board.entries().len(), Leaderboard::MAX_ENTRIES);
+
+    board.remove(&keys[1]);
+    assert_eq!(board.entries().len(), Leaderboard::MAX_ENTRIES - 1);
+    assert_eq!(board.entries()[0].knowledge_entry, keys[Leaderboard::MAX_ENTRIES - 1]);
+}
+
+#[test]
+fn proposals_pass_on_quorum_and_a_strict_majority_over_the_threshold() {
+    // No votes never pass, even without a quorum
+    assert!(!solsage_core::proposal_passes(0, 0, 0, 0));
+    assert!(!solsage_core::proposal_passes(599, 0, 600, 5_000));
+    assert!(solsage_core::proposal_passes(600, 0, 600, 5_000));
+    // Votes against count towards the quorum
+    assert!(solsage_core::proposal_passes(301, 299, 600, 5_000));
+    // A tie at the threshold fails
+    assert!(!solsage_core::proposal_passes(300, 300, 600, 5_000));
+    assert!(solsage_core::proposal_passes(u64::MAX, u64::MAX / 2, 0, 6_000));
+
+    assert_eq!(
+        ProposalAction::SetProtocolFee { fee_bps: Protocol::MAX_FEE_BPS + 1 }.validate(),
+        Err(SolSageError::FeeTooHigh.into())
+    );
+    assert_eq!(
+        ProposalAction::SetRewardRate { reward_per_attribution: 0 }.validate(),
+        Err(SolSageError::RewardRateOutOfBounds.into())
+    );
+    assert_eq!(ProposalAction::AddOracle { oracle: Pubkey::new_unique() }.validate(), Ok(()));
+}
+
+#[test]
+fn score_decay_halves_every_half_life_down_to_its_floor() {
+    use solsage_core::score_decay_bps;
+
+    assert_eq!(score_decay_bps(0, 100, 0), 10_000);
+    assert_eq!(score_decay_bps(50, 100, 0), 7_500);
+    assert_eq!(score_decay_bps(100, 100, 0), 5_000);
+    assert_eq!(score_decay_bps(150, 100, 0), 3_750);
+    assert_eq!(score_decay_bps(100 * 20, 100, 0), 0);
+    assert_eq!(score_decay_bps(300, 100, 1_000), 1_250);
+    assert_eq!(score_decay_bps(400, 100, 1_000), 1_000);
+    assert_eq!(score_decay_bps(i64::MAX, 100, 1_000), 1_000);
+    // Decay off, and entries dated after the attribution, earn in full
+    assert_eq!(score_decay_bps(WEEK, 0, 0), 10_000);
+    assert_eq!(score_decay_bps(-WEEK, 100, 0), 10_000);
+
+    let mut decaying = protocol(0);
+    decaying.score_half_life_secs = WEEK;
+    assert_eq!(decaying.score_weight_bps(1_000, 1_000 + 2 * WEEK), 2_500);
+    assert_eq!(decaying.score_weight_bps(i64::MIN, i64::MAX), 0);
+}
+
+#[test]
+fn staker_index_covers_every_index_staked() {
+    let staker = Pubkey::new_unique();
+    let mut index =
+        StakerIndex { is_initialized: true, schema_version: SCHEMA_VERSION, staker, entry_count: 0, bump: 0, closed_entries: 0 };
+    index.record_entry(0).unwrap();
+    index.record_entry(1).unwrap();
+    assert_eq!(index.entry_count, 2);
+
+    // Entries take the next index, without skipping or reusing one
+    assert_eq!(index.record_entry(5), Err(SolSageError::InvalidEntryIndex.into()));
+    assert_eq!(index.record_entry(1), Err(SolSageError::InvalidEntryIndex.into()));
+    index.record_closed_entry().unwrap();
+    assert_eq!((index.entry_count, index.closed_entries), (2, 1));
+
+    // A new index starts past the entries staked before it
+    let mut index =
+        StakerIndex { is_initialized: true, schema_version: SCHEMA_VERSION, staker, entry_count: 0, bump: 0, closed_entries: 0 };
+    index.record_entry(3).unwrap();
+    assert_eq!(index.entry_count, 4);
+
+    index.entry_count = u64::MAX;
+    assert_eq!(index.record_entry(u64::MAX), Err(overflow()));
+}
@@ -0,0 +1,1300 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::MAX_PERMITTED_DATA_INCREASE,
+    program_error::ProgramError, pubkey::Pubkey, rent::Rent, system_program,
+    sysvar::{self, instructions::{construct_instructions_data, store_current_index, BorrowedInstruction}},
+};
+use solsage::{
+    AuthorityType, process_instruction, receipts, Attribution, AttributionOracle, Collection, FeeConfig, KnowledgeEntry, LicenseType, LockupTier, OracleState, PayerStats, ProgramAccount,
+    ProposalAction, Protocol, ReportQueue, RewardCurve, RewardSource, SolSageError, SolSageInstruction, StakerProfile, VestingBucket,
+    Visibility, KNOWLEDGE_ENTRY_SCHEMA_VERSION, SCHEMA_VERSION,
+};
+
+struct TestAccount {
+    key: Pubkey,
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+    is_signer: bool,
+}
+
+impl TestAccount {
+    fn new(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> Self {
+        Self {
+            key,
+            owner,
+            lamports: 1_000_000_000,
+            data,
+            is_signer: false,
+        }
+    }
+
+    fn signer() -> Self {
+        Self {
+            is_signer: true,
+            ..Self::new(Pubkey::new_unique(), system_program::id(), Vec::new())
+        }
+    }
+
+    fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            self.is_signer,
+            true,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            false,
+            0,
+        )
+    }
+}
+
+/// Knowledge entry account laid out as the runtime serializes it, with the
+/// original length before the key and the length and spare room around the
+/// data, so `realloc` can run on it
+#[repr(C)]
+struct ReallocAccount {
+    original_data_len: u32,
+    key: Pubkey,
+    data_len: u64,
+    data: [u8; KnowledgeEntry::LEN + MAX_PERMITTED_DATA_INCREASE],
+    owner: Pubkey,
+    lamports: u64,
+}
+
+impl ReallocAccount {
+    fn new(owner: Pubkey, data: &[u8], lamports: u64) -> Box<Self> {
+        let mut account = Box::new(Self {
+            original_data_len: data.len() as u32,
+            key: Pubkey::new_unique(),
+            data_len: data.len() as u64,
+            data: [0; KnowledgeEntry::LEN + MAX_PERMITTED_DATA_INCREASE],
+            owner,
+            lamports,
+        });
+        account.data[..data.len()].copy_from_slice(data);
+        account
+    }
+
+    fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            false,
+            true,
+            &mut self.lamports,
+            &mut self.data[..self.data_len as usize],
+            &self.owner,
+            false,
+            0,
+        )
+    }
+}
+
+fn protocol(authority: Pubkey) -> Protocol {
+    Protocol {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        authority,
+        pending_authority: None,
+        reward_mint: Pubkey::default(),
+        total_knowledge_entries: 0,
+        total_attributions: 0,
+        reward_per_attribution: 1_000_000,
+        max_supply: 0,
+        vesting_period_secs: Protocol::DEFAULT_VESTING_PERIOD_SECS,
+        reward_curve: RewardCurve::Linear,
+        fee_bps: 0,
+        fees_collected: 0,
+        min_stake_lamports: 0,
+        attribution_cooldown_secs: 0,
+        is_paused: false,
+        score_divisor: Protocol::DEFAULT_SCORE_DIVISOR,
+        epoch_duration_secs: 0,
+        epoch_reward_budget: 0,
+        epoch_count: 0,
+        epoch_ends_at: 0,
+        curator_bps: 0,
+        bump: 0,
+        mint_authority_bump: 0,
+        treasury_bump: 0,
+        reward_source: RewardSource::Mint,
+        reward_vault_bump: 0,
+        authority_type: AuthorityType::Keypair,
+        pending_authority_type: AuthorityType::Keypair,
+        quorum_votes: 0,
+        approval_threshold_bps: 0,
+        voting_period_secs: 0,
+        execution_delay_secs: 0,
+        proposal_count: 0,
+        governance_vault_bump: 0,
+        admin_delay_secs: 0,
+        queued_change: None,
+        queued_change_executable_at: 0,
+        score_half_life_secs: 0,
+        min_score_weight_bps: 0,
+        attribution_verifier: None,
+        claim_window_secs: 0,
+        total_rewards: 0,
+        receipt_tree: None,
+        sale_fee_bps: 0,
+        consensus_threshold: 0,
+        consensus_tolerance: 0,
+        aggregation_window_secs: 0,
+        max_payer_attributions_per_day: 0,
+        reward_stream_secs: 0,
+        fee_config: FeeConfig::default(),
+        treasury_rewards: 0,
+        rewards_burned: 0,
+        low_relevance_threshold: 0,
+        low_relevance_burn_bps: 0,
+        pending_burn: 0,
+        citation_royalty_bps: 0,
+        identity_config: None,
+    }
+}
+
+fn entry(title: &str) -> KnowledgeEntry {
+    KnowledgeEntry {
+        is_initialized: true,
+        schema_version: KNOWLEDGE_ENTRY_SCHEMA_VERSION,
+        staker: Pubkey::new_unique(),
+        creator: Pubkey::new_unique(),
+        entry_index: 0,
+        content_hash: [0; 32],
+        category: Pubkey::default(),
+        is_active: true,
+        license: LicenseType::Mit,
+        title: title.to_string(),
+        content_uri: None,
+        collection: None,
+        royalty_splits: Vec::new(),
+        created_at: 0,
+        updated_at: 0,
+        expires_at: 0,
+        last_attribution_at: 0,
+        version: 1,
+        total_attributions: 0,
+        pending_rewards: 0,
+        vesting_schedule: [VestingBucket::default(); KnowledgeEntry::VESTING_BUCKETS],
+        stake_amount: 0,
+        bump: 0,
+        escrow_bump: 0,
+        tags: Vec::new(),
+        min_reward_per_use: 0,
+        lockup_tier: LockupTier::None,
+        locked_until: 0,
+        unstake_requested_at: 0,
+        receipt_asset_id: None,
+        quality_score: 0,
+        under_review: false,
+        reward_stream: Default::default(),
+        derived_from: Vec::new(),
+        citation_royalties: 0,
+        citation_rewards: 0,
+        document: None,
+        chunk_index: 0,
+        chunks: None,
+        embedding_fingerprint: None,
+        attestation: None,
+        verified: false,
+        identity_credential: None,
+        visibility: Visibility::Public,
+    }
+}
+
+fn account_data<T: ProgramAccount>(state: &T) -> Vec<u8> {
+    let mut data = vec![0; T::LEN];
+    state.pack_into(&mut data).unwrap();
+    data
+}
+
+/// Protocol account at its PDA, holding `protocol` under the bump it's found
+/// with
+fn protocol_account(program_id: &Pubkey, protocol: Protocol) -> TestAccount {
+    let (key, bump) = Pubkey::find_program_address(&[Protocol::SEED], program_id);
+    TestAccount::new(key, *program_id, account_data(&Protocol { bump, ..protocol }))
+}
+
+fn initialize(
+    program_id: &Pubkey,
+    protocol_account: &mut TestAccount,
+    system_program: &mut TestAccount,
+) -> Result<(), ProgramError> {
+    let mut authority = TestAccount::signer();
+    let treasury_key = Pubkey::find_program_address(&[Protocol::TREASURY_SEED], program_id).0;
+    let mut treasury = TestAccount::new(treasury_key, system_program::id(), Vec::new());
+
+    let accounts = [
+        authority.info(),
+        protocol_account.info(),
+        system_program.info(),
+        treasury.info(),
+    ];
+    process_instruction(
+        program_id,
+        &accounts,
+        &SolSageInstruction::Initialize.try_to_vec().unwrap(),
+    )
+}
+
+fn set_reward_rate(
+    program_id: &Pubkey,
+    authority: &mut TestAccount,
+    protocol_account: &mut TestAccount,
+) -> Result<(), ProgramError> {
+    let data = SolSageInstruction::SetRewardRate {
+        reward_per_attribution: 2_000_000,
+    }
+    .try_to_vec()
+    .unwrap();
+    process_instruction(
+        program_id,
+        &[authority.info(), protocol_account.info()],
+        &data,
+    )
+}
+
+#[test]
+fn initialize_rejects_fake_system_program() {
+    let program_id = Pubkey::new_unique();
+    let protocol_key = Pubkey::find_program_address(&[Protocol::SEED], &program_id).0;
+    let mut protocol_account = TestAccount::new(protocol_key, system_program::id(), Vec::new());
+    let mut fake_system_program =
+        TestAccount::new(Pubkey::new_unique(), Pubkey::default(), Vec::new());
+
+    assert_eq!(
+        initialize(&program_id, &mut protocol_account, &mut fake_system_program),
+        Err(ProgramError::IncorrectProgramId)
+    );
+}
+
+#[test]
+fn initialize_rejects_existing_protocol() {
+    let program_id = Pubkey::new_unique();
+    let protocol_key = Pubkey::find_program_address(&[Protocol::SEED], &program_id).0;
+    let existing = account_data(&protocol(Pubkey::new_unique()));
+    let mut protocol_account = TestAccount::new(protocol_key, program_id, existing);
+    let mut system_program = TestAccount::new(system_program::id(), Pubkey::default(), Vec::new());
+
+    assert_eq!(
+        initialize(&program_id, &mut protocol_account, &mut system_program),
+        Err(SolSageError::AlreadyInitialized.into())
+    );
+}
+
+#[test]
+fn admin_instructions_reject_protocol_owned_by_another_program() {
+    let program_id = Pubkey::new_unique();
+    let mut authority = TestAccount::signer();
+    let forged = account_data(&protocol(authority.key));
+    let mut protocol_account = TestAccount::new(Pubkey::new_unique(), Pubkey::new_unique(), forged);
+
+    assert_eq!(
+        set_reward_rate(&program_id, &mut authority, &mut protocol_account),
+        Err(ProgramError::IllegalOwner)
+    );
+}
+
+#[test]
+fn admin_instructions_reject_other_account_types_as_the_protocol() {
+    let program_id = Pubkey::new_unique();
+    let mut authority = TestAccount::signer();
+    // A staker's own profile keeps the staker where the protocol keeps its
+    // authority
+    let profile = StakerProfile {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        staker: authority.key,
+        total_entries: 0,
+        total_attributions: 0,
+        total_relevance: 0,
+        cumulative_rewards: 0,
+        claim_delegate: None,
+        bump: 0,
+    };
+    let (protocol_key, _) = Pubkey::find_program_address(&[Protocol::SEED], &program_id);
+    let mut protocol_account = TestAccount::new(protocol_key, program_id, account_data(&profile));
+    assert_eq!(
+        set_reward_rate(&program_id, &mut authority, &mut protocol_account),
+        Err(SolSageError::AccountTypeMismatch.into())
+    );
+
+    // Nor is one written before accounts were tagged read as a protocol
+    let mut protocol_account = TestAccount::new(protocol_key, program_id, borsh::to_vec(&profile).unwrap());
+    assert_eq!(
+        set_reward_rate(&program_id, &mut authority, &mut protocol_account),
+        Err(SolSageError::AccountNeedsMigration.into())
+    );
+
+    // And a protocol is only the one at the protocol PDA
+    let mut protocol_account =
+        TestAccount::new(Pubkey::new_unique(), program_id, account_data(&protocol(authority.key)));
+    assert_eq!(
+        set_reward_rate(&program_id, &mut authority, &mut protocol_account),
+        Err(SolSageError::InvalidPda.into())
+    );
+}
+
+#[test]
+fn admin_instructions_reject_uninitialized_protocol() {
+    let program_id = Pubkey::new_unique();
+    let mut authority = TestAccount::signer();
+    let mut protocol_account =
+        TestAccount::new(Pubkey::new_unique(), program_id, vec![0; Protocol::LEN]);
+
+    assert_eq!(
+        set_reward_rate(&program_id, &mut authority, &mut protocol_account),
+        Err(ProgramError::UninitializedAccount)
+    );
+}
+
+#[test]
+fn shortened_title_leaves_no_stale_bytes() {
+    let program_id = Pubkey::new_unique();
+    let mut knowledge_account = TestAccount::new(
+        Pubkey::new_unique(),
+        program_id,
+        vec![0xff; KnowledgeEntry::LEN],
+    );
+
+    // 25 four-byte characters fill the title's whole byte budget
+    let longest = "\u{1f980}".repeat(KnowledgeEntry::MAX_TITLE_LEN / 4);
+    assert_eq!(longest.len(), KnowledgeEntry::MAX_TITLE_LEN);
+    entry(&longest).store(&knowledge_account.info()).unwrap();
+    assert_eq!(
+        KnowledgeEntry::load(&program_id, &knowledge_account.info())
+            .unwrap()
+            .title,
+        longest
+    );
+
+    entry("short").store(&knowledge_account.info()).unwrap();
+    let stored = KnowledgeEntry::load(&program_id, &knowledge_account.info()).unwrap();
+    assert_eq!(stored.title, "short");
+
+    let encoded_len = borsh::to_vec(&stored).unwrap().len();
+    assert!(knowledge_account.data[encoded_len..].iter().all(|b| *b == 0));
+}
+
+#[test]
+fn record_attribution_rejects_curator_while_epochs_enabled() {
+    let program_id = Pubkey::new_unique();
+    let mut payer = TestAccount::signer();
+
+    let mut epochs = protocol(Pubkey::new_unique());
+    epochs.epoch_duration_secs = Protocol::MIN_EPOCH_DURATION_SECS;
+    epochs.epoch_reward_budget = 1_000_000;
+    let mut protocol_account = protocol_account(&program_id, epochs);
+
+    let oracle_key =
+        Pubkey::find_program_address(&[AttributionOracle::SEED, payer.key.as_ref()], &program_id).0;
+    let registration = AttributionOracle {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        oracle: payer.key,
+        added_at: 0,
+        bump: 0,
+    };
+    let mut oracle_account =
+        TestAccount::new(oracle_key, program_id, account_data(&registration));
+    let mut system_program = TestAccount::new(system_program::id(), Pubkey::default(), Vec::new());
+    let mut unused: Vec<TestAccount> = (0..9)
+        .map(|_| TestAccount::new(Pubkey::new_unique(), program_id, Vec::new()))
+        .collect();
+    let [knowledge, attribution, query, profile, treasury, category, leaderboard, oracle_state, payer_stats] =
+        &mut unused[..]
+    else {
+        unreachable!()
+    };
+
+    let accounts = [
+        payer.info(),
+        protocol_account.info(),
+        knowledge.info(),
+        attribution.info(),
+        query.info(),
+        oracle_account.info(),
+        system_program.info(),
+        profile.info(),
+        treasury.info(),
+        category.info(),
+        leaderboard.info(),
+        oracle_state.info(),
+        payer_stats.info(),
+    ];
+    let data = SolSageInstruction::RecordAttribution {
+        query_hash: [1; 32],
+        relevance_score: 80,
+        curator: Some(Pubkey::new_unique()),
+        agent_id: None,
+        model_id: None,
+        chunk: None,
+    }
+    .try_to_vec()
+    .unwrap();
+
+    assert_eq!(
+        process_instruction(&program_id, &accounts, &data),
+        Err(SolSageError::CuratorWithEpochs.into())
+    );
+}
+
+#[test]
+fn add_to_collection_rejects_entry_in_another_collection() {
+    let program_id = Pubkey::new_unique();
+    let mut owner = TestAccount::signer();
+
+    let name = "Rust async book";
+    let (collection_key, bump) = Pubkey::find_program_address(
+        &[Collection::SEED, owner.key.as_ref(), name.as_bytes()],
+        &program_id,
+    );
+    let collection = Collection {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        owner: owner.key,
+        name: name.to_string(),
+        entries: 0,
+        attributions: 0,
+        rewards: 0,
+        last_attribution_at: 0,
+        created_at: 0,
+        bump,
+    };
+    let mut collection_account =
+        TestAccount::new(collection_key, program_id, vec![0; Collection::LEN]);
+    collection.store(&collection_account.info()).unwrap();
+
+    let mut collected = entry("Chapter 1");
+    collected.staker = owner.key;
+    collected.collection = Some(Pubkey::new_unique());
+    let mut knowledge_account =
+        TestAccount::new(Pubkey::new_unique(), program_id, vec![0; KnowledgeEntry::LEN]);
+    collected.store(&knowledge_account.info()).unwrap();
+
+    let accounts = [owner.info(), collection_account.info(), knowledge_account.info()];
+    let data = SolSageInstruction::AddToCollection.try_to_vec().unwrap();
+
+    assert_eq!(
+        process_instruction(&program_id, &accounts, &data),
+        Err(SolSageError::EntryInCollection.into())
+    );
+}
+
+#[test]
+fn license_is_stored_at_its_filter_offset() {
+    let program_id = Pubkey::new_unique();
+    let mut knowledge_account =
+        TestAccount::new(Pubkey::new_unique(), program_id, vec![0; KnowledgeEntry::LEN]);
+
+    let mut licensed = entry("Licensed");
+    licensed.license = LicenseType::Custom { uri: "https://example.com/license".to_string() };
+    licensed.store(&knowledge_account.info()).unwrap();
+
+    let license = borsh::to_vec(&licensed.license).unwrap();
+    let offset = KnowledgeEntry::LICENSE_OFFSET;
+    assert_eq!(knowledge_account.data[offset..offset + license.len()], license[..]);
+}
+
+#[test]
+fn update_license_rejects_other_stakers() {
+    let program_id = Pubkey::new_unique();
+    let mut intruder = TestAccount::signer();
+    let mut knowledge_account =
+        TestAccount::new(Pubkey::new_unique(), program_id, vec![0; KnowledgeEntry::LEN]);
+    entry("Licensed").store(&knowledge_account.info()).unwrap();
+    let mut system_program = TestAccount::new(system_program::id(), Pubkey::default(), Vec::new());
+
+    let accounts = [intruder.info(), knowledge_account.info(), system_program.info()];
+    let data = SolSageInstruction::UpdateLicense { license: LicenseType::Proprietary }
+        .try_to_vec()
+        .unwrap();
+
+    assert_eq!(
+        process_instruction(&program_id, &accounts, &data),
+        Err(SolSageError::NotKnowledgeOwner.into())
+    );
+}
+
+#[test]
+fn accounts_from_before_schema_versions_must_be_migrated() {
+    let program_id = Pubkey::new_unique();
+    // The old layout is the current one without the version byte or the
+    // fields appended since
+    let mut legacy = borsh::to_vec(&protocol(Pubkey::new_unique())).unwrap();
+    legacy.remove(1);
+    legacy.truncate(Protocol::LEN - <Protocol as ProgramAccount>::APPENDED_LEN - 1);
+    let mut protocol_account = TestAccount::new(Pubkey::new_unique(), program_id, legacy);
+
+    assert_eq!(Protocol::stored_schema_version(&protocol_account.data), Some(0));
+    assert_eq!(
+        Protocol::load(&program_id, &protocol_account.info()).map(|_| ()),
+        Err(SolSageError::AccountNeedsMigration.into())
+    );
+}
+
+#[test]
+fn resize_grows_undersized_entries_to_the_current_layout() {
+    let program_id = Pubkey::new_unique();
+    let mut staker = TestAccount::signer();
+    let mut system_program = TestAccount::new(system_program::id(), Pubkey::default(), Vec::new());
+
+    // Allocated for exactly its encoded state, with no room to grow
+    let mut encoded = borsh::to_vec(&entry("Packed")).unwrap();
+    encoded[0] = KnowledgeEntry::DISCRIMINATOR;
+    let rent = Rent::default();
+    let mut knowledge_account =
+        ReallocAccount::new(program_id, &encoded, rent.minimum_balance(encoded.len()));
+
+    let knowledge_info = knowledge_account.info();
+    KnowledgeEntry::resize(&knowledge_info, &staker.info(), &system_program.info(), &rent).unwrap();
+    assert_eq!(knowledge_info.data_len(), KnowledgeEntry::LEN);
+    assert!(knowledge_info.data.borrow()[encoded.len()..].iter().all(|b| *b == 0));
+
+    let mut grown = KnowledgeEntry::load(&program_id, &knowledge_info).unwrap();
+    grown.title = "\u{1f980}".repeat(KnowledgeEntry::MAX_TITLE_LEN / 4);
+    grown.store(&knowledge_info).unwrap();
+}
+
+#[test]
+fn resize_shrinks_oversized_entries_and_refunds_the_rent() {
+    let program_id = Pubkey::new_unique();
+    let mut staker = TestAccount::signer();
+    let mut system_program = TestAccount::new(system_program::id(), Pubkey::default(), Vec::new());
+
+    let rent = Rent::default();
+    let oversized = KnowledgeEntry::LEN + 64;
+    let mut knowledge_account =
+        ReallocAccount::new(program_id, &vec![0; oversized], rent.minimum_balance(oversized));
+    entry("Padded").store(&knowledge_account.info()).unwrap();
+
+    let staker_lamports = staker.lamports;
+    let knowledge_info = knowledge_account.info();
+    KnowledgeEntry::resize(&knowledge_info, &staker.info(), &system_program.info(), &rent).unwrap();
+    assert_eq!(knowledge_info.data_len(), KnowledgeEntry::LEN);
+    assert_eq!(knowledge_info.lamports(), rent.minimum_balance(KnowledgeEntry::LEN));
+    drop(knowledge_info);
+    assert_eq!(
+        staker.lamports - staker_lamports,
+        rent.minimum_balance(oversized) - rent.minimum_balance(KnowledgeEntry::LEN)
+    );
+    assert_eq!(KnowledgeEntry::load(&program_id, &knowledge_account.info()).unwrap().title, "Padded");
+}
+
+#[test]
+fn protocols_from_before_reward_sources_mint_rewards() {
+    let program_id = Pubkey::new_unique();
+    let mut data = account_data(&protocol(Pubkey::new_unique()));
+    data.truncate(Protocol::LEN - <Protocol as ProgramAccount>::APPENDED_LEN);
+    let mut protocol_account = TestAccount::new(Pubkey::new_unique(), program_id, data);
+
+    assert_eq!(Protocol::stored_schema_version(&protocol_account.data), Some(SCHEMA_VERSION));
+    let loaded = Protocol::load(&program_id, &protocol_account.info()).unwrap();
+    assert_eq!(loaded.reward_source, RewardSource::Mint);
+}
+
+#[test]
+fn accounts_shorter_than_any_layout_of_their_version_are_not_padded() {
+    let program_id = Pubkey::new_unique();
+    let mut data = account_data(&protocol(Pubkey::new_unique()));
+    data.truncate(<Protocol as ProgramAccount>::MIN_LEN - 1);
+    let mut protocol_account = TestAccount::new(Pubkey::new_unique(), program_id, data);
+    assert_eq!(
+        Protocol::load(&program_id, &protocol_account.info()).map(|_| ()),
+        Err(SolSageError::AccountNeedsMigration.into())
+    );
+
+    // Version 2 entries were laid out with their tags
+    let mut data = account_data(&entry("Short"));
+    data.truncate(<KnowledgeEntry as ProgramAccount>::MIN_LEN);
+    let mut knowledge_account = TestAccount::new(Pubkey::new_unique(), program_id, data.clone());
+    assert_eq!(KnowledgeEntry::load(&program_id, &knowledge_account.info()).unwrap().title, "Short");
+    data.truncate(KnowledgeEntry::LEN - <KnowledgeEntry as ProgramAccount>::APPENDED_LEN);
+    let mut knowledge_account = TestAccount::new(Pubkey::new_unique(), program_id, data);
+    assert_eq!(
+        KnowledgeEntry::load(&program_id, &knowledge_account.info()).map(|_| ()),
+        Err(SolSageError::AccountNeedsMigration.into())
+    );
+}
+
+#[test]
+fn reward_source_is_locked_after_the_first_attribution() {
+    let program_id = Pubkey::new_unique();
+    let mut authority = TestAccount::signer();
+    let mut attributed = protocol(authority.key);
+    attributed.total_attributions = 1;
+    let mut protocol_account = protocol_account(&program_id, attributed);
+    let mut reward_vault = TestAccount::new(Pubkey::new_unique(), system_program::id(), Vec::new());
+    let mut sage_mint = TestAccount::new(Pubkey::new_unique(), spl_token::id(), Vec::new());
+    let mut token_program = TestAccount::new(spl_token::id(), Pubkey::default(), Vec::new());
+    let mut system_program = TestAccount::new(system_program::id(), Pubkey::default(), Vec::new());
+
+    let accounts = [
+        authority.info(),
+        protocol_account.info(),
+        reward_vault.info(),
+        sage_mint.info(),
+        token_program.info(),
+        system_program.info(),
+    ];
+    let data = SolSageInstruction::SetRewardSource { reward_source: RewardSource::Payer }
+        .try_to_vec()
+        .unwrap();
+
+    assert_eq!(
+        process_instruction(&program_id, &accounts, &data),
+        Err(SolSageError::RewardSourceLocked.into())
+    );
+}
+
+#[test]
+fn subscriptions_require_payer_funded_rewards() {
+    let program_id = Pubkey::new_unique();
+    let mut consumer = TestAccount::signer();
+    let mut protocol_account = protocol_account(&program_id, protocol(Pubkey::new_unique()));
+    let mut subscription = TestAccount::new(Pubkey::new_unique(), system_program::id(), Vec::new());
+    let mut consumer_token_account = TestAccount::new(Pubkey::new_unique(), spl_token::id(), Vec::new());
+    let mut reward_vault = TestAccount::new(Pubkey::new_unique(), spl_token::id(), Vec::new());
+    let mut token_program = TestAccount::new(spl_token::id(), Pubkey::default(), Vec::new());
+    let mut system_program = TestAccount::new(system_program::id(), Pubkey::default(), Vec::new());
+
+    let accounts = [
+        consumer.info(),
+        protocol_account.info(),
+        subscription.info(),
+        consumer_token_account.info(),
+        reward_vault.info(),
+        token_program.info(),
+        system_program.info(),
+    ];
+    let data = SolSageInstruction::TopUpSubscription { amount: 1_000_000 }.try_to_vec().unwrap();
+
+    assert_eq!(
+        process_instruction(&program_id, &accounts, &data),
+        Err(SolSageError::RewardsNotPayerFunded.into())
+    );
+}
+
+#[test]
+fn attributions_from_before_agent_ids_store_only_without_them() {
+    let program_id = Pubkey::new_unique();
+    let mut attribution = Attribution {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        knowledge_entry: Pubkey::new_unique(),
+        query_hash: [3; 32],
+        relevance_score: 90,
+        reward: 900_000,
+        timestamp: 1_700_000_000,
+        unlocks_at: 1_700_600_000,
+        reward_claimed: false,
+        reversed: false,
+        epoch: Some(4),
+        bump: 255,
+        agent_id: None,
+        model_id: None,
+        pending: false,
+        votes: Vec::new(),
+        streamed: false,
+        chunk_index: None,
+    };
+    let data = vec![0; Attribution::LEN - <Attribution as ProgramAccount>::APPENDED_LEN];
+    let mut attribution_account = TestAccount::new(Pubkey::new_unique(), program_id, data);
+    attribution.store(&attribution_account.info()).unwrap();
+
+    attribution.reward_claimed = true;
+    attribution.store(&attribution_account.info()).unwrap();
+    assert!(Attribution::load(&program_id, &attribution_account.info()).unwrap().reward_claimed);
+
+    attribution.agent_id = Some([7; 32]);
+    assert_eq!(
+        attribution.store(&attribution_account.info()),
+        Err(SolSageError::AccountNeedsMigration.into())
+    );
+}
+
+#[test]
+fn multisig_authorities_must_be_the_pda_their_multisig_signs_with() {
+    let program_id = Pubkey::new_unique();
+    let mut authority = TestAccount::signer();
+    let mut protocol_account = protocol_account(&program_id, protocol(authority.key));
+    let authority_type = AuthorityType::Squads { multisig: Pubkey::new_unique(), vault_index: 0 };
+    let propose = |new_authority| {
+        SolSageInstruction::ProposeAuthorityTransfer { new_authority, authority_type }.try_to_vec().unwrap()
+    };
+
+    assert_eq!(
+        process_instruction(
+            &program_id,
+            &[authority.info(), protocol_account.info()],
+            &propose(Pubkey::new_unique())
+        ),
+        Err(SolSageError::AuthorityTypeMismatch.into())
+    );
+
+    // The vault accepts through CPI, which reaches the program as its signature
+    let AuthorityType::Squads { multisig, vault_index } = authority_type else { unreachable!() };
+    let mut vault = TestAccount {
+        is_signer: true,
+        ..TestAccount::new(solsage_core::multisig::squads_vault(&multisig, vault_index), system_program::id(), Vec::new())
+    };
+    assert_eq!(
+        process_instruction(&program_id, &[authority.info(), protocol_account.info()], &propose(vault.key)),
+        Ok(())
+    );
+    let accept = SolSageInstruction::AcceptAuthorityTransfer.try_to_vec().unwrap();
+    assert_eq!(process_instruction(&program_id, &[vault.info(), protocol_account.info()], &accept), Ok(()));
+
+    let transferred = Protocol::load(&program_id, &protocol_account.info()).unwrap();
+    assert_eq!(transferred.authority, vault.key);
+    assert_eq!(transferred.authority_type, authority_type);
+    assert_eq!(transferred.pending_authority_type, AuthorityType::Keypair);
+}
+
+#[test]
+fn proposals_need_governance_turned_on_with_a_beatable_threshold() {
+    let program_id = Pubkey::new_unique();
+    let mut authority = TestAccount::signer();
+    let mut protocol_account = protocol_account(&program_id, protocol(authority.key));
+    let mut governance_vault = TestAccount::new(Pubkey::new_unique(), system_program::id(), Vec::new());
+    let mut sage_mint = TestAccount::new(Pubkey::new_unique(), spl_token::id(), Vec::new());
+    let mut token_program = TestAccount::new(spl_token::id(), Pubkey::default(), Vec::new());
+    let mut system_program = TestAccount::new(system_program::id(), Pubkey::default(), Vec::new());
+
+    let data = SolSageInstruction::SetGovernanceConfig {
+        quorum_votes: 1_000,
+        approval_threshold_bps: Protocol::MAX_BPS,
+        voting_period_secs: Protocol::MIN_VOTING_PERIOD_SECS,
+        execution_delay_secs: 0,
+    }
+    .try_to_vec()
+    .unwrap();
+    let accounts = [
+        authority.info(),
+        protocol_account.info(),
+        governance_vault.info(),
+        sage_mint.info(),
+        token_program.info(),
+        system_program.info(),
+    ];
+    assert_eq!(
+        process_instruction(&program_id, &accounts, &data),
+        Err(SolSageError::InvalidGovernanceConfig.into())
+    );
+
+    let mut proposal = TestAccount::new(Pubkey::new_unique(), system_program::id(), Vec::new());
+    let data = SolSageInstruction::CreateProposal { action: ProposalAction::SetProtocolFee { fee_bps: 100 } }
+        .try_to_vec()
+        .unwrap();
+    let accounts = [authority.info(), protocol_account.info(), proposal.info(), system_program.info()];
+    assert_eq!(
+        process_instruction(&program_id, &accounts, &data),
+        Err(SolSageError::GovernanceDisabled.into())
+    );
+}
+
+#[test]
+fn admin_delay_forces_parameter_changes_through_the_queue() {
+    let program_id = Pubkey::new_unique();
+    let mut authority = TestAccount::signer();
+    let mut timelocked = protocol(authority.key);
+    timelocked.admin_delay_secs = 24 * 60 * 60;
+    let mut protocol_account = protocol_account(&program_id, timelocked);
+
+    assert_eq!(
+        set_reward_rate(&program_id, &mut authority, &mut protocol_account),
+        Err(SolSageError::ChangeMustBeQueued.into())
+    );
+
+    let accounts = [authority.info(), protocol_account.info()];
+    for instruction in [SolSageInstruction::CancelParameterChange, SolSageInstruction::ExecuteParameterChange] {
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &instruction.try_to_vec().unwrap()),
+            Err(SolSageError::NoQueuedChange.into())
+        );
+    }
+
+    let data = SolSageInstruction::QueueParameterChange {
+        change: ProposalAction::SetAdminDelay { admin_delay_secs: Protocol::MAX_ADMIN_DELAY_SECS + 1 },
+    }
+    .try_to_vec()
+    .unwrap();
+    assert_eq!(
+        process_instruction(&program_id, &accounts, &data),
+        Err(SolSageError::InvalidAdminDelay.into())
+    );
+}
+
+#[test]
+fn score_decay_rejects_half_lives_out_of_bounds_and_floors_over_full_weight() {
+    let program_id = Pubkey::new_unique();
+    let mut authority = TestAccount::signer();
+    let mut protocol_account = protocol_account(&program_id, protocol(authority.key));
+    let set_score_decay = |score_half_life_secs, min_score_weight_bps| {
+        SolSageInstruction::SetScoreDecay { score_half_life_secs, min_score_weight_bps }.try_to_vec().unwrap()
+    };
+    let accounts = [authority.info(), protocol_account.info()];
+
+    for data in [
+        set_score_decay(Protocol::MIN_SCORE_HALF_LIFE_SECS - 1, 0),
+        set_score_decay(Protocol::MAX_SCORE_HALF_LIFE_SECS + 1, 0),
+        set_score_decay(0, Protocol::MAX_BPS + 1),
+    ] {
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &data),
+            Err(SolSageError::InvalidScoreDecay.into())
+        );
+    }
+
+    assert_eq!(
+        process_instruction(&program_id, &accounts, &set_score_decay(Protocol::MIN_SCORE_HALF_LIFE_SECS, 2_000)),
+        Ok(())
+    );
+    let decaying = Protocol::load(&program_id, &protocol_account.info()).unwrap();
+    assert_eq!(decaying.score_half_life_secs, Protocol::MIN_SCORE_HALF_LIFE_SECS);
+    assert_eq!(decaying.min_score_weight_bps, 2_000);
+}
+
+#[test]
+fn claim_windows_are_off_or_at_least_the_minimum() {
+    let program_id = Pubkey::new_unique();
+    let mut authority = TestAccount::signer();
+    let mut protocol_account = protocol_account(&program_id, protocol(authority.key));
+    let set_claim_window = |claim_window_secs| SolSageInstruction::SetClaimWindow { claim_window_secs }.try_to_vec().unwrap();
+    let accounts = [authority.info(), protocol_account.info()];
+
+    for claim_window_secs in [-1, 1, Protocol::MIN_CLAIM_WINDOW_SECS - 1] {
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &set_claim_window(claim_window_secs)),
+            Err(SolSageError::InvalidClaimWindow.into())
+        );
+    }
+
+    assert_eq!(process_instruction(&program_id, &accounts, &set_claim_window(0)), Ok(()));
+    assert_eq!(process_instruction(&program_id, &accounts, &set_claim_window(Protocol::MIN_CLAIM_WINDOW_SECS)), Ok(()));
+    let windowed = Protocol::load(&program_id, &protocol_account.info()).unwrap();
+    assert_eq!(windowed.claim_window_secs, Protocol::MIN_CLAIM_WINDOW_SECS);
+}
+
+/// Instructions sysvar of a transaction running its instruction at
+/// `current`
+fn instructions_sysvar(program_ids: &[Pubkey], current: u16) -> TestAccount {
+    let instructions: Vec<_> = program_ids
+        .iter()
+        .map(|program_id| BorrowedInstruction { program_id, accounts: Vec::new(), data: &[] })
+        .collect();
+    let mut data = construct_instructions_data(&instructions);
+    store_current_index(&mut data, current);
+    TestAccount::new(sysvar::instructions::id(), sysvar::id(), data)
+}
+
+#[test]
+fn attribution_verifier_is_set_by_the_authority_to_another_program() {
+    let program_id = Pubkey::new_unique();
+    let mut authority = TestAccount::signer();
+    let mut intruder = TestAccount::signer();
+    let mut protocol_account = protocol_account(&program_id, protocol(authority.key));
+    let set_attribution_verifier =
+        |verifier| SolSageInstruction::SetAttributionVerifier { verifier }.try_to_vec().unwrap();
+    let verifier = Pubkey::new_unique();
+
+    assert_eq!(
+        process_instruction(&program_id, &[intruder.info(), protocol_account.info()], &set_attribution_verifier(Some(verifier))),
+        Err(SolSageError::Unauthorized.into())
+    );
+    let accounts = [authority.info(), protocol_account.info()];
+    assert_eq!(
+        process_instruction(&program_id, &accounts, &set_attribution_verifier(Some(program_id))),
+        Err(SolSageError::InvalidAttributionVerifier.into())
+    );
+    assert_eq!(process_instruction(&program_id, &accounts, &set_attribution_verifier(Some(verifier))), Ok(()));
+    let verified = Protocol::load(&program_id, &protocol_account.info()).unwrap();
+    assert_eq!(verified.attribution_verifier, Some(verifier));
+}
+
+#[test]
+fn verified_protocols_only_take_attributions_made_under_the_verifier() {
+    let program_id = Pubkey::new_unique();
+    let verifier = Pubkey::new_unique();
+    let mut verified = protocol(Pubkey::new_unique());
+    verified.attribution_verifier = Some(verifier);
+
+    // The verifier's instruction may follow others in the transaction
+    let mut through_verifier = instructions_sysvar(&[Pubkey::new_unique(), verifier], 1);
+    assert_eq!(verified.check_attribution_caller(Some(&through_verifier.info())), Ok(()));
+    assert_eq!(verified.check_attribution_caller(None), Err(ProgramError::NotEnoughAccountKeys));
+    let mut forged = instructions_sysvar(&[verifier], 0);
+    forged.key = Pubkey::new_unique();
+    assert_eq!(verified.check_attribution_caller(Some(&forged.info())), Err(ProgramError::UnsupportedSysvar));
+
+    // An oracle calling the program directly is turned away
+    let mut payer = TestAccount::signer();
+    let mut protocol_account = protocol_account(&program_id, verified);
+    let oracle_key =
+        Pubkey::find_program_address(&[AttributionOracle::SEED, payer.key.as_ref()], &program_id).0;
+    let registration = AttributionOracle {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        oracle: payer.key,
+        added_at: 0,
+        bump: 0,
+    };
+    let mut oracle_account =
+        TestAccount::new(oracle_key, program_id, account_data(&registration));
+    let mut system_program = TestAccount::new(system_program::id(), Pubkey::default(), Vec::new());
+    let mut direct = instructions_sysvar(&[program_id], 0);
+    let mut unused: Vec<TestAccount> = (0..9)
+        .map(|_| TestAccount::new(Pubkey::new_unique(), program_id, Vec::new()))
+        .collect();
+    let [knowledge, attribution, query, profile, treasury, category, leaderboard, oracle_state, payer_stats] =
+        &mut unused[..]
+    else {
+        unreachable!()
+    };
+
+    let accounts = [
+        payer.info(),
+        protocol_account.info(),
+        knowledge.info(),
+        attribution.info(),
+        query.info(),
+        oracle_account.info(),
+        system_program.info(),
+        profile.info(),
+        treasury.info(),
+        category.info(),
+        leaderboard.info(),
+        oracle_state.info(),
+        payer_stats.info(),
+        direct.info(),
+    ];
+    let data = SolSageInstruction::RecordAttribution {
+        query_hash: [1; 32],
+        relevance_score: 80,
+        curator: None,
+        agent_id: None,
+        model_id: None,
+        chunk: None,
+    }
+    .try_to_vec()
+    .unwrap();
+
+    assert_eq!(
+        process_instruction(&program_id, &accounts, &data),
+        Err(SolSageError::AttributionNotVerified.into())
+    );
+}
+
+#[test]
+fn relayed_attributions_answer_to_the_oracle_that_signed_their_receipt() {
+    let program_id = Pubkey::new_unique();
+    let mut relayer = TestAccount::signer();
+    let oracle = Pubkey::new_unique();
+    let mut protocol_account = protocol_account(&program_id, protocol(Pubkey::new_unique()));
+    let oracle_key = Pubkey::find_program_address(&[AttributionOracle::SEED, oracle.as_ref()], &program_id).0;
+    let registration = AttributionOracle { is_initialized: true, schema_version: SCHEMA_VERSION, oracle, added_at: 0, bump: 0 };
+    let mut oracle_account = TestAccount::new(oracle_key, program_id, account_data(&registration));
+    let mut system_program = TestAccount::new(system_program::id(), Pubkey::default(), Vec::new());
+    let mut unused: Vec<TestAccount> = (0..9)
+        .map(|_| TestAccount::new(Pubkey::new_unique(), program_id, Vec::new()))
+        .collect();
+    let [knowledge, attribution, query, profile, treasury, category, leaderboard, oracle_state, payer_stats] =
+        &mut unused[..]
+    else {
+        unreachable!()
+    };
+    let data = SolSageInstruction::RecordSignedAttribution {
+        query_hash: [1; 32],
+        relevance_score: 80,
+        curator: None,
+        agent_id: None,
+        model_id: None,
+        nonce: 3,
+    }
+    .try_to_vec()
+    .unwrap();
+
+    // The Ed25519 program instruction checking the signature precedes the
+    // attribution
+    let knowledge_key = knowledge.key;
+    let mut relay = |signer: &Pubkey, receipt: &[u8]| {
+        let verify = receipts::verify_signature(signer, &[9; 64], receipt);
+        let instructions = [
+            BorrowedInstruction { program_id: &verify.program_id, accounts: Vec::new(), data: &verify.data },
+            BorrowedInstruction { program_id: &program_id, accounts: Vec::new(), data: &data },
+        ];
+        let mut sysvar_data = construct_instructions_data(&instructions);
+        store_current_index(&mut sysvar_data, 1);
+        let mut instructions_account = TestAccount::new(sysvar::instructions::id(), sysvar::id(), sysvar_data);
+        let accounts = [
+            relayer.info(),
+            protocol_account.info(),
+            knowledge.info(),
+            attribution.info(),
+            query.info(),
+            oracle_account.info(),
+            system_program.info(),
+            profile.info(),
+            treasury.info(),
+            category.info(),
+            leaderboard.info(),
+            oracle_state.info(),
+            payer_stats.info(),
+            instructions_account.info(),
+        ];
+        process_instruction(&program_id, &accounts, &data)
+    };
+
+    let receipt = receipts::attribution_receipt(&[1; 32], &knowledge_key, 80, 3);
+    let other_score = receipts::attribution_receipt(&[1; 32], &knowledge_key, 90, 3);
+    assert_eq!(relay(&oracle, &other_score), Err(SolSageError::InvalidAttributionReceipt.into()));
+    assert_eq!(relay(&Pubkey::new_unique(), &receipt), Err(SolSageError::UnauthorizedOracle.into()));
+    // Past its receipt the attribution runs on to the clock, which there's no
+    // runtime to serve here
+    assert_eq!(relay(&oracle, &receipt), Err(ProgramError::UnsupportedSysvar));
+}
+
+fn oracle_state() -> OracleState {
+    OracleState {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        oracle: Pubkey::new_unique(),
+        next_nonce: 0,
+        bump: 0,
+        max_attributions_per_epoch: 0,
+        max_rewards_per_epoch: 0,
+        budget_epoch: 0,
+        epoch_attributions: 0,
+        epoch_rewards: 0,
+    }
+}
+
+#[test]
+fn receipt_nonces_land_once_and_in_order() {
+    let mut oracle_state = oracle_state();
+    assert_eq!(oracle_state.use_nonce(0), Ok(()));
+    assert_eq!(oracle_state.use_nonce(0), Err(SolSageError::ReceiptNonceUsed.into()));
+
+    // Skipped nonces are spent along with the one used
+    assert_eq!(oracle_state.use_nonce(5), Ok(()));
+    assert_eq!(oracle_state.use_nonce(3), Err(SolSageError::ReceiptNonceUsed.into()));
+    assert_eq!(oracle_state.next_nonce, 6);
+    assert_eq!(oracle_state.use_nonce(u64::MAX), Err(SolSageError::ArithmeticOverflow.into()));
+}
+
+#[test]
+fn oracle_budgets_refill_each_epoch() {
+    let mut budget = OracleState { max_attributions_per_epoch: 3, max_rewards_per_epoch: 100, ..oracle_state() };
+    let next_epoch = OracleState::BUDGET_EPOCH_SECS;
+    assert_eq!(budget.spend_budget(0, 2, 60), Ok(()));
+    assert_eq!(budget.spend_budget(1, 2, 10), Err(SolSageError::OracleBudgetExceeded.into()));
+    assert_eq!(budget.spend_budget(1, 1, 41), Err(SolSageError::OracleBudgetExceeded.into()));
+    // A rejected spend leaves the budget as it was
+    assert_eq!((budget.epoch_attributions, budget.epoch_rewards), (2, 60));
+    assert_eq!(budget.spend_budget(next_epoch - 1, 1, 40), Ok(()));
+
+    assert_eq!(budget.spend_budget(next_epoch, 3, 100), Ok(()));
+    assert_eq!(budget.budget_epoch, 1);
+
+    // Zero caps leave the oracle unlimited
+    let mut uncapped = oracle_state();
+    assert_eq!(uncapped.spend_budget(0, u64::MAX, u64::MAX), Ok(()));
+}
+
+#[test]
+fn payer_caps_count_attributions_per_day() {
+    let mut stats = PayerStats {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        payer: Pubkey::new_unique(),
+        bump: 255,
+        day: 0,
+        day_attributions: 0,
+        total_attributions: 0,
+    };
+    let next_day = PayerStats::DAY_SECS;
+    assert_eq!(stats.record_attributions(0, 2, 3), Ok(()));
+    assert_eq!(stats.record_attributions(1, 2, 3), Err(SolSageError::PayerAttributionCapExceeded.into()));
+    // A rejected batch isn't counted
+    assert_eq!((stats.day_attributions, stats.total_attributions), (2, 2));
+    assert_eq!(stats.record_attributions(next_day - 1, 1, 3), Ok(()));
+
+    assert_eq!(stats.record_attributions(next_day, 3, 3), Ok(()));
+    assert_eq!((stats.day, stats.day_attributions, stats.total_attributions), (1, 3, 6));
+
+    // A zero cap leaves the payer unlimited
+    assert_eq!(stats.record_attributions(next_day, u64::MAX - 6, 0), Ok(()));
+}
+
+#[test]
+fn report_queues_flag_at_the_threshold_once_per_reporter() {
+    let mut queue = ReportQueue {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        knowledge_entry: Pubkey::new_unique(),
+        reports: Vec::new(),
+        flagged_at: 0,
+        bump: 255,
+    };
+    let reporters: Vec<Pubkey> = (0..=ReportQueue::THRESHOLD).map(|_| Pubkey::new_unique()).collect();
+    for (i, reporter) in reporters[..ReportQueue::THRESHOLD - 1].iter().enumerate() {
+        assert_eq!(queue.record_report(*reporter, ReportQueue::MIN_BOND + i as u64), Ok(false));
+    }
+    assert_eq!(
+        queue.record_report(reporters[0], ReportQueue::MIN_BOND),
+        Err(SolSageError::AlreadyReported.into())
+    );
+    assert_eq!(queue.record_report(reporters[ReportQueue::THRESHOLD - 1], ReportQueue::MIN_BOND), Ok(true));
+    assert_eq!(
+        queue.record_report(reporters[ReportQueue::THRESHOLD], ReportQueue::MIN_BOND),
+        Err(SolSageError::ReportQueueFull.into())
+    );
+
+    let extra: u64 = (0..ReportQueue::THRESHOLD as u64 - 1).sum();
+    assert_eq!(queue.total_bonds(), Ok(ReportQueue::MIN_BOND * ReportQueue::THRESHOLD as u64 + extra));
+    assert!(queue.try_to_vec().unwrap().len() <= ReportQueue::LEN);
+}
+
+#[test]
+fn consensus_finalizes_at_the_average_of_matching_reports() {
+    let protocol = Protocol { consensus_threshold: 3, consensus_tolerance: 5, ..protocol(Pubkey::new_unique()) };
+    let mut attribution = Attribution {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        knowledge_entry: Pubkey::new_unique(),
+        query_hash: [3; 32],
+        relevance_score: 80,
+        reward: 0,
+        timestamp: 1_700_000_000,
+        unlocks_at: 0,
+        reward_claimed: false,
+        reversed: false,
+        epoch: None,
+        bump: 255,
+        agent_id: None,
+        model_id: None,
+        pending: true,
+        votes: Vec::new(),
+        streamed: false,
+        chunk_index: None,
+    };
+    let (first, second, third) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+    assert_eq!(attribution.cast_vote(first, 80, &protocol), Ok(None));
+    assert_eq!(attribution.cast_vote(first, 80, &protocol), Err(SolSageError::OracleAlreadyVoted.into()));
+    // Tolerance is measured from the first report
+    assert_eq!(attribution.cast_vote(second, 86, &protocol), Err(SolSageError::ConsensusScoreMismatch.into()));
+    assert_eq!(attribution.cast_vote(second, 85, &protocol), Ok(None));
+    assert_eq!(attribution.cast_vote(third, 76, &protocol), Ok(Some(80)));
+    assert_eq!(attribution.votes.len(), 3);
+}
+
+#[test]
+fn aggregated_scores_keep_a_running_average_until_finalized() {
+    let protocol = Protocol { aggregation_window_secs: 3_600, ..protocol(Pubkey::new_unique()) };
+    let mut attribution = Attribution {
+        is_initialized: true,
+        schema_version: SCHEMA_VERSION,
+        knowledge_entry: Pubkey::new_unique(),
+        query_hash: [3; 32],
+        relevance_score: 40,
+        reward: 0,
+        timestamp: 1_700_000_000,
+        unlocks_at: 0,
+        reward_claimed: false,
+        reversed: false,
+        epoch: None,
+        bump: 255,
+        agent_id: None,
+        model_id: None,
+        pending: true,
+        votes: Vec::new(),
+        streamed: false,
+        chunk_index: None,
+    };
+
+    // Scores count however far apart they are, and never finalize on their
+    // own
+    for (count, (score, average)) in [(40, 40), (100, 70), (70, 70), (10, 55), (90, 62)].into_iter().enumerate() {
+        assert_eq!(attribution.cast_vote(Pubkey::new_unique(), score, &protocol), Ok(None));
+        assert_eq!((attribution.relevance_score, attribution.votes.len()), (average, count + 1));
+    }
+    assert_eq!(
+        attribution.cast_vote(Pubkey::new_unique(), 50, &protocol),
+        Err(SolSageError::AttributionReportsFull.into())
+    );
+
+    assert!(!attribution.aggregation_closed(&protocol, 1_700_003_599));
+    assert!(attribution.aggregation_closed(&protocol, 1_700_003_600));
+}
+
+#[test]
+fn version_1_entries_move_category_and_status_ahead_of_their_strings() {
+    let mut knowledge = entry("Borsh layouts");
+    knowledge.category = Pubkey::new_unique();
+    knowledge.is_active = false;
+    knowledge.content_uri = Some("ar://layouts".to_string());
+    knowledge.tags = vec!["rust".to_string()];
+
+    // Version 1 kept `category` after the content URI and `is_active` after
+    // the stake amount
+    let mut data = Vec::new();
+    (true, 1u8, knowledge.staker, knowledge.creator, knowledge.entry_index, knowledge.content_hash)
+        .serialize(&mut data)
+        .unwrap();
+    (&knowledge.license, &knowledge.title, &knowledge.content_uri, knowledge.category, knowledge.collection)
+        .serialize(&mut data)
+        .unwrap();
+    (&knowledge.royalty_splits, knowledge.created_at, knowledge.updated_at, knowledge.expires_at)
+        .serialize(&mut data)
+        .unwrap();
+    (knowledge.last_attribution_at, knowledge.version, knowledge.total_attributions, knowledge.pending_rewards)
+        .serialize(&mut data)
+        .unwrap();
+    (knowledge.vesting_schedule, knowledge.stake_amount, knowledge.is_active, knowledge.bump, knowledge.escrow_bump)
+        .serialize(&mut data)
+        .unwrap();
+    (&knowledge.tags, knowledge.min_reward_per_use).serialize(&mut data).unwrap();
+    data.resize(KnowledgeEntry::LEN, 0);
+
+    let program_id = Pubkey::new_unique();
+    let mut account = TestAccount::new(Pubkey::new_unique(), program_id, data.clone());
+    assert_eq!(
+        KnowledgeEntry::load(&program_id, &account.info()).unwrap_err(),
+        SolSageError::AccountNeedsMigration.into()
+    );
+
+    data[0] = KnowledgeEntry::DISCRIMINATOR;
+    data[1] = KNOWLEDGE_ENTRY_SCHEMA_VERSION;
+    KnowledgeEntry::upgrade_layout(&mut data, 1).unwrap();
+    assert_eq!(&data[KnowledgeEntry::CATEGORY_OFFSET..KnowledgeEntry::IS_ACTIVE_OFFSET], knowledge.category.as_ref());
+    assert_eq!(data[KnowledgeEntry::IS_ACTIVE_OFFSET], 0);
+
+    let mut account = TestAccount::new(Pubkey::new_unique(), program_id, data);
+    let upgraded = KnowledgeEntry::load(&program_id, &account.info()).unwrap();
+    assert_eq!(upgraded.try_to_vec().unwrap(), knowledge.try_to_vec().unwrap());
+}
+
+#[test]
+fn version_1_entries_too_short_for_their_fields_are_rejected() {
+    let knowledge = entry("Borsh layouts");
+
+    // Version 1 layout up to the royalty splits, with the stake amount and
+    // status cut off
+    let mut data = Vec::new();
+    (true, KNOWLEDGE_ENTRY_SCHEMA_VERSION, knowledge.staker, knowledge.creator, knowledge.entry_index)
+        .serialize(&mut data)
+        .unwrap();
+    (knowledge.content_hash, &knowledge.license, &knowledge.title, &knowledge.content_uri, knowledge.category)
+        .serialize(&mut data)
+        .unwrap();
+    (knowledge.collection, &knowledge.royalty_splits, knowledge.created_at).serialize(&mut data).unwrap();
+    let original = data.clone();
+    assert_eq!(KnowledgeEntry::upgrade_layout(&mut data, 1), Err(ProgramError::InvalidAccountData));
+    assert_eq!(data, original);
+
+    // Or cut off before the category itself
+    let mut data = original[..KnowledgeEntry::CATEGORY_OFFSET - 1].to_vec();
+    assert_eq!(KnowledgeEntry::upgrade_layout(&mut data, 1), Err(ProgramError::InvalidAccountData));
+}
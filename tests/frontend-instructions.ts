@@ -0,0 +1,75 @@
+import { expect } from 'chai';
+import * as fs from 'fs';
+import * as path from 'path';
+import { PublicKey, TransactionInstruction } from '@solana/web3.js';
+import {
+    LockupTier,
+    deriveCategoryPDA,
+    deriveKnowledgePDA,
+    nextEntryIndex,
+    recordAttributionInstruction,
+    stakeKnowledgeInstruction,
+} from '../frontend/src/lib/solsage-instructions';
+
+// Instructions the frontend encodes, built from the inputs
+// programs/solsage-anchor/tests/frontend.rs builds them from with the
+// program's own builders, and compared with the fixtures that test writes
+const fixtures = JSON.parse(
+    fs.readFileSync(path.join(__dirname, '../frontend/src/lib/fixtures/instructions.json'), 'utf8')
+);
+
+function encoded(instruction: TransactionInstruction) {
+    return {
+        keys: instruction.keys.map(({ pubkey, isSigner, isWritable }) => ({
+            pubkey: pubkey.toBase58(),
+            isSigner,
+            isWritable,
+        })),
+        data: instruction.data.toString('hex'),
+    };
+}
+
+describe('frontend instruction encoders', () => {
+    const staker = new PublicKey(new Uint8Array(32).fill(1));
+
+    it('stakes knowledge in the layout the program decodes', () => {
+        const instruction = stakeKnowledgeInstruction(staker, deriveCategoryPDA('solana')[0], {
+            entryIndex: BigInt(3),
+            contentHash: new Uint8Array(32).fill(7),
+            title: 'Async Rust',
+            stakeAmount: BigInt(10_000_000),
+            license: { kind: 'custom', uri: 'https://example.com/terms' },
+            lockup: LockupTier.Days90,
+            chunks: { root: new Uint8Array(32).fill(9), chunkCount: 4 },
+        });
+        expect(encoded(instruction)).to.deep.equal(fixtures.stakeKnowledge);
+    });
+
+    it('records attributions in the layout the program decodes', () => {
+        const target = {
+            knowledgeEntry: deriveKnowledgePDA(staker, BigInt(3))[0],
+            staker,
+            category: deriveCategoryPDA('solana')[0],
+        };
+        const instruction = recordAttributionInstruction(staker, target, {
+            queryHash: new Uint8Array(32).fill(5),
+            relevanceScore: 80,
+            curator: new PublicKey(new Uint8Array(32).fill(2)),
+            agentId: new Uint8Array(32).fill(3),
+            modelId: new Uint8Array(32).fill(4),
+            chunk: {
+                index: 1,
+                chunkHash: new Uint8Array(32).fill(6),
+                proof: [new Uint8Array(32).fill(8), new Uint8Array(32).fill(10)],
+            },
+        });
+        expect(encoded(instruction)).to.deep.equal(fixtures.recordAttribution);
+    });
+
+    it('stakes at the entry index the staker index counts up to', () => {
+        expect(nextEntryIndex(null)).to.equal(BigInt(0));
+        const stakerIndex = Buffer.alloc(1 + 1 + 32 + 8 + 1 + 8);
+        stakerIndex.writeBigUInt64LE(BigInt(5), 1 + 1 + 32);
+        expect(nextEntryIndex(stakerIndex)).to.equal(BigInt(5));
+    });
+});